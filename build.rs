@@ -0,0 +1,38 @@
+fn main() {
+    #[cfg(feature = "grpc")]
+    grpc::generate();
+}
+
+/// Generates the tonic server and client of the gRPC service from the
+/// method list below, so no `protoc` is needed. The messages are written
+/// by hand in `src/grpc/proto.rs`; `proto/spectra.proto` describes the
+/// same service for clients in other languages.
+#[cfg(feature = "grpc")]
+mod grpc {
+    use tonic_build::manual::{Builder, Method, Service};
+
+    const CODEC: &str = "tonic::codec::ProstCodec";
+
+    pub fn generate() {
+        let method = |name: &str, route: &str, input: &str, output: &str| {
+            Method::builder()
+                .name(name)
+                .route_name(route)
+                .input_type(format!("crate::grpc::proto::{}", input))
+                .output_type(format!("crate::grpc::proto::{}", output))
+                .codec_path(CODEC)
+        };
+        let service = Service::builder()
+            .name("SpectraCache")
+            .package("spectra")
+            .method(method("get", "Get", "GetRequest", "GetResponse").build())
+            .method(method("set", "Set", "SetRequest", "SetResponse").build())
+            .method(method("set_with_ttl", "SetWithTtl", "SetWithTtlRequest", "SetResponse").build())
+            .method(method("delete", "Delete", "DeleteRequest", "DeleteResponse").build())
+            .method(method("scan", "Scan", "ScanRequest", "ScanResponse").build())
+            .method(method("watch", "Watch", "WatchRequest", "WatchEvent").server_streaming().build())
+            .build();
+        Builder::new().compile(&[service]);
+        println!("cargo:rerun-if-changed=build.rs");
+    }
+}
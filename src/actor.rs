@@ -0,0 +1,160 @@
+use std::panic::{self, AssertUnwindSafe};
+use std::thread;
+use std::time::Duration;
+
+use tokio::sync::{mpsc, oneshot};
+
+use crate::{CacheError, CacheStats, DistributedHashTable, GetOptions};
+
+/// How many commands may wait for the actor by default before callers
+/// have to wait for room.
+const DEFAULT_QUEUE_CAPACITY: usize = 1024;
+
+type Job = Box<dyn FnOnce(&mut DistributedHashTable) + Send>;
+
+/// An actor-style front end to a `DistributedHashTable`.
+///
+/// The table is owned by a dedicated thread that applies commands sent
+/// over a bounded channel, so there is no lock at all: every handle just
+/// sends a command and awaits the reply. While the owner is busy, commands
+/// pile up in the channel and are applied back to back once it gets to
+/// them, so bursts are batched without callers doing anything. A full
+/// channel makes callers wait, which keeps a slow owner from being buried.
+///
+/// Handles are cheap to clone and can be used from any number of tasks and
+/// runtimes. The owner thread needs no async runtime and stops once the
+/// last handle is dropped. A panic in a [`call`](Self::call) closure
+/// reaches the task that sent it; the actor keeps serving the others.
+///
+/// # Examples
+///
+/// ```
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// use spectra_cache::{CacheActor, DistributedHashTable};
+///
+/// let cache = CacheActor::spawn(DistributedHashTable::new());
+/// let tasks: Vec<_> = (0..8)
+///     .map(|i| {
+///         let cache = cache.clone();
+///         tokio::spawn(async move { cache.insert(&format!("task:{}", i), "done").await })
+///     })
+///     .collect();
+/// for task in tasks {
+///     task.await.unwrap();
+/// }
+/// assert_eq!(cache.size().await, 8);
+/// # });
+/// ```
+#[derive(Debug, Clone)]
+pub struct CacheActor {
+    jobs: mpsc::Sender<Job>,
+}
+
+impl CacheActor {
+    /// Moves `table` onto a new owner thread and returns a handle to it.
+    pub fn spawn(table: DistributedHashTable) -> Self {
+        Self::with_capacity(table, DEFAULT_QUEUE_CAPACITY)
+    }
+
+    /// Like [`spawn`](Self::spawn), with room for `capacity` queued commands.
+    ///
+    /// Panics if `capacity` is zero.
+    pub fn with_capacity(mut table: DistributedHashTable, capacity: usize) -> Self {
+        assert!(capacity > 0, "the actor queue capacity must be non-zero");
+        let (jobs, mut queue) = mpsc::channel::<Job>(capacity);
+        thread::Builder::new()
+            .name("spectra-cache-actor".to_string())
+            .spawn(move || {
+                while let Some(job) = queue.blocking_recv() {
+                    job(&mut table);
+                    // Aplica o que chegou enquanto isso sem voltar a dormir
+                    while let Ok(job) = queue.try_recv() {
+                        job(&mut table);
+                    }
+                }
+            })
+            .expect("failed to spawn the cache actor thread");
+        Self { jobs }
+    }
+
+    /// Runs `f` on the actor's thread with exclusive access to the table
+    /// and returns its result.
+    ///
+    /// Useful for operations the handle doesn't wrap directly, or to apply
+    /// several operations atomically.
+    pub async fn call<R, F>(&self, f: F) -> R
+    where
+        R: Send + 'static,
+        F: FnOnce(&mut DistributedHashTable) -> R + Send + 'static,
+    {
+        let (reply, response) = oneshot::channel();
+        let job: Job = Box::new(move |table| {
+            // Quem pediu pode ter desistido; a resposta então é descartada
+            let _ = reply.send(panic::catch_unwind(AssertUnwindSafe(|| f(table))));
+        });
+        if self.jobs.send(job).await.is_err() {
+            unreachable!("the actor thread lives as long as its handles");
+        }
+        match response.await {
+            Ok(Ok(result)) => result,
+            Ok(Err(payload)) => panic::resume_unwind(payload),
+            Err(_) => unreachable!("the actor always replies"),
+        }
+    }
+
+    /// Returns the number of entries in the table.
+    pub async fn size(&self) -> usize {
+        self.call(|table| table.size()).await
+    }
+
+    /// Inserts a key-value pair into the table.
+    pub async fn insert(&self, key: &str, value: &str) {
+        let (key, value) = (key.to_string(), value.to_string());
+        self.call(move |table| table.insert(&key, &value)).await
+    }
+
+    /// Inserts a key-value pair that expires after `ttl`.
+    pub async fn insert_with_ttl(&self, key: &str, value: &str, ttl: Duration) {
+        let (key, value) = (key.to_string(), value.to_string());
+        self.call(move |table| table.insert_with_ttl(&key, &value, ttl)).await
+    }
+
+    /// Retrieves a copy of the value stored under `key`.
+    ///
+    /// The owner has the table mutably, so the read is applied right away
+    /// as by [`DistributedHashTable::get_with`].
+    pub async fn get(&self, key: &str) -> Option<String> {
+        let key = key.to_string();
+        self.call(move |table| table.get_with(&key, &GetOptions::default()).map(str::to_string)).await
+    }
+
+    /// Removes a key, returning its value if it existed.
+    pub async fn remove(&self, key: &str) -> Option<String> {
+        let key = key.to_string();
+        self.call(move |table| table.remove(&key)).await
+    }
+
+    /// Checks if a live key exists in the table.
+    pub async fn contains_key(&self, key: &str) -> bool {
+        let key = key.to_string();
+        self.call(move |table| table.contains_key(&key)).await
+    }
+
+    /// Adds `delta` to the integer stored under `key`.
+    ///
+    /// See [`DistributedHashTable::incr`] for details.
+    pub async fn incr(&self, key: &str, delta: i64) -> Result<i64, CacheError> {
+        let key = key.to_string();
+        self.call(move |table| table.incr(&key, delta)).await
+    }
+
+    /// Removes all entries from the table.
+    pub async fn clear(&self) {
+        self.call(|table| table.clear()).await
+    }
+
+    /// Returns a snapshot of the table's activity counters.
+    pub async fn stats(&self) -> CacheStats {
+        self.call(|table| table.stats()).await
+    }
+}
@@ -0,0 +1,672 @@
+//! Append-only log of the writes made to a cache, appended to by a cache
+//! after `enable_aof()` and applied back by `replay()`.
+//!
+//! The file starts with a header:
+//!
+//! | Bytes | Meaning |
+//! |-------|---------|
+//! | 8 | Magic, `SPECAOF\0` |
+//! | 2 | Format version, big-endian |
+//!
+//! followed by one record per operation:
+//!
+//! | Bytes | Meaning |
+//! |-------|---------|
+//! | 1 | Operation: `1` set, `2` delete, `3` TTL change, `4` clear, `5` batch |
+//! | 4 | Key length, big-endian, `0` for a clear or a batch |
+//! | n | Key bytes (UTF-8) |
+//! | 8 | Set and TTL change only: expiration in milliseconds since the Unix epoch, `0` if none |
+//! | 8 | Set and TTL change only: idle timeout in milliseconds, `0` if none |
+//! | 4 | Set only: value length; batch only: length of the wrapped records; big-endian |
+//! | n | Set only: value bytes; batch only: the wrapped records, each with its own checksum |
+//! | 8 | CRC-64 (Jones) of the record so far, big-endian |
+//!
+//! Every record is written with a single `write` call, so a crash can only
+//! leave the last record torn; `replay()` drops it. A transaction is
+//! written as one batch record, so it is replayed whole or not at all.
+//! Replication sends the same records to replicas, one per frame.
+
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufReader, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, PoisonError};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use crate::clock::{Instant, SystemTime, UNIX_EPOCH};
+use crate::dump;
+use crate::entry::CacheEntry;
+use crate::error::CacheError;
+use crate::value::CacheValue;
+
+/// Version written into every log. Logs from newer versions are rejected.
+/// Version 2 added batch records.
+pub(crate) const AOF_VERSION: u16 = 2;
+
+const MAGIC: &[u8; 8] = b"SPECAOF\0";
+const HEADER_LEN: u64 = 8 + 2;
+
+const OP_SET: u8 = 1;
+const OP_DELETE: u8 = 2;
+const OP_RETIME: u8 = 3;
+const OP_CLEAR: u8 = 4;
+const OP_BATCH: u8 = 5;
+
+/// How often `FsyncPolicy::EverySecond` syncs the log.
+const FSYNC_INTERVAL: Duration = Duration::from_secs(1);
+
+/// When the append-only log is flushed to disk with `fsync`, trading write
+/// latency for how much a power loss or OS crash can take with it.
+///
+/// Records reach the operating system as soon as they are written in every
+/// mode, so a crash of the process alone loses nothing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FsyncPolicy {
+    /// Sync after every write. Nothing acknowledged is lost, at the cost of
+    /// a disk round trip per write.
+    Always,
+    /// Sync from a background thread once a second, losing at most the
+    /// last second of writes.
+    EverySecond,
+    /// Leave syncing to the operating system.
+    Never,
+}
+
+/// What a cache does once its persistence keeps failing, as when the disk
+/// holding the append-only log fills up or snapshots can't be written.
+///
+/// Either way the failures are counted in `CacheStats::aof_errors`, and
+/// the cache reports itself degraded through `is_degraded()` and
+/// `CacheStats::persistence_degraded`. See `is_degraded()` for how it
+/// recovers.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PersistenceFailure {
+    /// Keep applying writes in memory only. Whatever is written while
+    /// degraded is lost on a restart.
+    #[default]
+    ServeFromMemory,
+    /// Refuse writes, so memory never gets ahead of the disk. Methods that
+    /// return a `Result` fail with `CacheError::PersistenceFailed`, and the
+    /// others leave the cache untouched; every refused write is counted in
+    /// `CacheStats::rejected_writes`. Since nothing reaches the log any
+    /// more, a cache degraded by its log only recovers through
+    /// `resume_persistence()`.
+    RejectWrites,
+}
+
+/// A change to a cache as read back from the log.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Operation<V> {
+    Set {
+        key: String,
+        value: V,
+        expires_at: Option<SystemTime>,
+        idle_timeout: Option<Duration>,
+    },
+    Delete {
+        key: String,
+    },
+    /// The entry's TTL and idle timeout were replaced; both `None` for `persist()`.
+    Retime {
+        key: String,
+        expires_at: Option<SystemTime>,
+        idle_timeout: Option<Duration>,
+    },
+    Clear,
+    /// The operations of a transaction, applied together.
+    Batch(Vec<Operation<V>>),
+}
+
+/// An open log that operations are appended to.
+pub(crate) struct AppendLog {
+    shared: Arc<Shared>,
+    fsync: FsyncPolicy,
+    syncer: Option<JoinHandle<()>>,
+}
+
+struct Shared {
+    /// The file and its length up to the last complete record
+    file: Mutex<(File, u64)>,
+    /// Whether records were written since the last sync
+    dirty: AtomicBool,
+    errors: AtomicU64,
+    /// Failures since the last record that was written
+    failing: AtomicU64,
+    stopped: Mutex<bool>,
+    wake: Condvar,
+}
+
+impl AppendLog {
+    /// Opens the log at `path` for appending, creating it if needed.
+    ///
+    /// Returns the log and whether it was empty, so the caller can write
+    /// the cache's current content first.
+    pub(crate) fn open(path: &Path, fsync: FsyncPolicy) -> io::Result<(Self, bool)> {
+        let mut file = OpenOptions::new().read(true).append(true).create(true).open(path)?;
+        let mut len = file.metadata()?.len();
+        let fresh = len == 0;
+        if fresh {
+            let mut header = MAGIC.to_vec();
+            header.extend_from_slice(&AOF_VERSION.to_be_bytes());
+            file.write_all(&header)?;
+            file.sync_all()?;
+            len = HEADER_LEN;
+        } else {
+            file.seek(SeekFrom::Start(0))?;
+            read_header(&mut file)?;
+        }
+
+        let shared = Arc::new(Shared {
+            file: Mutex::new((file, len)),
+            dirty: AtomicBool::new(false),
+            errors: AtomicU64::new(0),
+            failing: AtomicU64::new(0),
+            stopped: Mutex::new(false),
+            wake: Condvar::new(),
+        });
+        let syncer = match fsync {
+            FsyncPolicy::EverySecond => {
+                let shared = Arc::clone(&shared);
+                let syncer = thread::Builder::new()
+                    .name("spectra-cache-aof-fsync".to_string())
+                    .spawn(move || {
+                        while !shared.sleep(FSYNC_INTERVAL) {
+                            shared.sync_if_dirty();
+                        }
+                    })
+                    .expect("failed to spawn the append-only log fsync thread");
+                Some(syncer)
+            }
+            FsyncPolicy::Always | FsyncPolicy::Never => None,
+        };
+        Ok((Self { shared, fsync, syncer }, fresh))
+    }
+
+    /// Syncs every record written so far to disk, whatever the policy.
+    pub(crate) fn sync(&self) -> io::Result<()> {
+        self.shared.dirty.store(false, Ordering::Relaxed);
+        let result = lock(&self.shared.file).0.sync_data();
+        if result.is_err() {
+            self.shared.dirty.store(true, Ordering::Relaxed);
+            self.shared.failed();
+        }
+        result
+    }
+
+    /// Returns how many writes and syncs failed.
+    pub(crate) fn errors(&self) -> u64 {
+        self.shared.errors.load(Ordering::Relaxed)
+    }
+
+    /// Returns how many writes and syncs failed since a record was last
+    /// written.
+    pub(crate) fn consecutive_failures(&self) -> u64 {
+        self.shared.failing.load(Ordering::Relaxed)
+    }
+
+    /// Forgets the failures counted by `consecutive_failures`.
+    pub(crate) fn reset_failures(&self) {
+        self.shared.failing.store(0, Ordering::Relaxed);
+    }
+
+    /// Appends a record made by one of the `*_record` functions.
+    pub(crate) fn append(&self, record: &[u8]) {
+        let mut file = lock(&self.shared.file);
+        let (file, len) = &mut *file;
+        let result = file.write_all(record).and_then(|()| match self.fsync {
+            FsyncPolicy::Always => file.sync_data(),
+            FsyncPolicy::EverySecond | FsyncPolicy::Never => {
+                self.shared.dirty.store(true, Ordering::Relaxed);
+                Ok(())
+            }
+        });
+        match result {
+            Ok(()) => {
+                *len += record.len() as u64;
+                self.shared.failing.store(0, Ordering::Relaxed);
+            }
+            Err(_) => {
+                self.shared.failed();
+                // Descarta o que foi escrito pela metade para não corromper os próximos registros
+                let _ = file.set_len(*len);
+            }
+        }
+    }
+}
+
+impl Shared {
+    /// Sleeps for `interval` unless the log is closed first. Returns true
+    /// once the syncer should stop.
+    fn sleep(&self, interval: Duration) -> bool {
+        let stopped = lock(&self.stopped);
+        let (stopped, _) = self
+            .wake
+            .wait_timeout_while(stopped, interval, |stopped| !*stopped)
+            .unwrap_or_else(PoisonError::into_inner);
+        *stopped
+    }
+
+    fn sync_if_dirty(&self) {
+        if self.dirty.swap(false, Ordering::Relaxed) && lock(&self.file).0.sync_data().is_err() {
+            self.dirty.store(true, Ordering::Relaxed);
+            self.failed();
+        }
+    }
+
+    fn failed(&self) {
+        self.errors.fetch_add(1, Ordering::Relaxed);
+        self.failing.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+impl Drop for AppendLog {
+    fn drop(&mut self) {
+        // Para o syncer e grava o que ainda não foi sincronizado
+        *lock(&self.shared.stopped) = true;
+        self.shared.wake.notify_all();
+        if let Some(syncer) = self.syncer.take() {
+            let _ = syncer.join();
+        }
+        if self.fsync != FsyncPolicy::Never {
+            self.shared.sync_if_dirty();
+        }
+    }
+}
+
+/// Encodes the write of `entry` under `key` as a record.
+pub(crate) fn set_record<V: CacheValue>(key: &str, entry: &CacheEntry<V>, now: Instant) -> Vec<u8> {
+    let mut record = record(OP_SET, key);
+    push_expiry(&mut record, entry, now);
+    let value = entry.value().as_ref();
+    record.extend_from_slice(&(value.len() as u32).to_be_bytes());
+    record.extend_from_slice(value);
+    seal(record)
+}
+
+pub(crate) fn delete_record(key: &str) -> Vec<u8> {
+    seal(record(OP_DELETE, key))
+}
+
+pub(crate) fn retime_record<V: CacheValue>(key: &str, entry: &CacheEntry<V>, now: Instant) -> Vec<u8> {
+    let mut record = record(OP_RETIME, key);
+    push_expiry(&mut record, entry, now);
+    seal(record)
+}
+
+pub(crate) fn clear_record() -> Vec<u8> {
+    seal(record(OP_CLEAR, ""))
+}
+
+/// Wraps `records`, made by the other `*_record` functions and laid end to
+/// end, in a single batch record.
+pub(crate) fn batch_record(records: &[u8]) -> Vec<u8> {
+    let mut record = record(OP_BATCH, "");
+    record.extend_from_slice(&(records.len() as u32).to_be_bytes());
+    record.extend_from_slice(records);
+    seal(record)
+}
+
+/// Decodes a single record made by one of the `*_record` functions.
+#[cfg(feature = "cluster")]
+pub(crate) fn decode<V: CacheValue>(mut bytes: &[u8]) -> io::Result<Operation<V>> {
+    match read_record(&mut bytes)? {
+        Record::Operation(operation, _) if bytes.is_empty() => Ok(operation),
+        _ => Err(invalid("malformed record")),
+    }
+}
+
+/// Decodes a run of records made by the `*_record` functions, laid end
+/// to end.
+#[cfg(feature = "cluster")]
+pub(crate) fn decode_all<V: CacheValue>(mut bytes: &[u8]) -> Result<Vec<Operation<V>>, CacheError> {
+    let mut operations = Vec::new();
+    while !bytes.is_empty() {
+        match read_record(&mut bytes) {
+            Ok(Record::Operation(operation, _)) => operations.push(operation),
+            Ok(_) => return Err(CacheError::InvalidDump { reason: "malformed record" }),
+            // Ler de um slice só falha com os erros de `invalid`
+            Err(error) => {
+                let error = error.into_inner().and_then(|inner| inner.downcast::<CacheError>().ok());
+                return Err(error.map_or(CacheError::InvalidDump { reason: "malformed record" }, |error| *error));
+            }
+        }
+    }
+    Ok(operations)
+}
+
+/// Reads every operation in the log at `path`, in order.
+///
+/// A torn record at the end, left by a crash mid-write, is cut off the file
+/// so appending can resume after the last complete one. Anything else that
+/// is malformed fails with `io::ErrorKind::InvalidData` wrapping a
+/// [`CacheError::InvalidDump`], and the file is left untouched.
+pub(crate) fn read<V: CacheValue>(path: &Path) -> io::Result<Vec<Operation<V>>> {
+    let mut input = BufReader::new(File::open(path)?);
+    read_header(&mut input)?;
+
+    let mut operations = Vec::new();
+    let mut complete = HEADER_LEN;
+    loop {
+        match read_record(&mut input)? {
+            Record::Operation(operation, len) => {
+                operations.push(operation);
+                complete += len;
+            }
+            Record::End => break,
+            Record::Torn => {
+                OpenOptions::new().write(true).open(path)?.set_len(complete)?;
+                break;
+            }
+        }
+    }
+    Ok(operations)
+}
+
+enum Record<V> {
+    Operation(Operation<V>, u64),
+    End,
+    Torn,
+}
+
+fn read_header(input: &mut impl Read) -> io::Result<()> {
+    let mut header = [0; HEADER_LEN as usize];
+    input.read_exact(&mut header).map_err(|error| match error.kind() {
+        io::ErrorKind::UnexpectedEof => invalid("not an append-only log"),
+        _ => error,
+    })?;
+    if &header[..8] != MAGIC {
+        return Err(invalid("not an append-only log"));
+    }
+    if u16::from_be_bytes([header[8], header[9]]) > AOF_VERSION {
+        return Err(invalid("unsupported format version"));
+    }
+    Ok(())
+}
+
+fn read_record<V: CacheValue>(input: &mut impl Read) -> io::Result<Record<V>> {
+    let mut op = [0];
+    if input.read(&mut op)? == 0 {
+        return Ok(Record::End);
+    }
+    let mut record = op.to_vec();
+    let Some(key) = read_field(input, &mut record)? else {
+        return Ok(Record::Torn);
+    };
+    let expiry = match op[0] {
+        OP_SET | OP_RETIME => match read_bytes(input, &mut record, 16)? {
+            Some(expiry) => Some(expiry),
+            None => return Ok(Record::Torn),
+        },
+        OP_DELETE | OP_CLEAR | OP_BATCH => None,
+        _ => return Err(invalid("unknown operation")),
+    };
+    let value = match op[0] {
+        OP_SET | OP_BATCH => match read_field(input, &mut record)? {
+            Some(value) => Some(value),
+            None => return Ok(Record::Torn),
+        },
+        _ => None,
+    };
+    let body_len = record.len();
+    let Some(checksum) = read_bytes(input, &mut record, 8)? else {
+        return Ok(Record::Torn);
+    };
+    if dump::crc64(&record[..body_len]) != u64::from_be_bytes(checksum.try_into().unwrap()) {
+        return Err(invalid("checksum mismatch"));
+    }
+
+    let key = String::from_utf8(key).map_err(|_| invalid("key is not valid UTF-8"))?;
+    let (expires_at, idle_timeout) = match expiry {
+        Some(expiry) => {
+            let millis = |bytes: &[u8]| u64::from_be_bytes(bytes.try_into().unwrap());
+            let expires_at = Some(millis(&expiry[..8])).filter(|&at| at != 0).map(|at| UNIX_EPOCH + Duration::from_millis(at));
+            let idle_timeout = Some(millis(&expiry[8..])).filter(|&idle| idle != 0).map(Duration::from_millis);
+            (expires_at, idle_timeout)
+        }
+        None => (None, None),
+    };
+    let operation = match op[0] {
+        OP_SET => {
+            let value = V::from_bytes(value.unwrap_or_default()).ok_or_else(|| invalid("value is not valid UTF-8"))?;
+            Operation::Set { key, value, expires_at, idle_timeout }
+        }
+        OP_DELETE => Operation::Delete { key },
+        OP_RETIME => Operation::Retime { key, expires_at, idle_timeout },
+        OP_BATCH => {
+            let records = value.unwrap_or_default();
+            let mut records = records.as_slice();
+            let mut operations = Vec::new();
+            while !records.is_empty() {
+                match read_record(&mut records)? {
+                    Record::Operation(operation, _) => operations.push(operation),
+                    // O checksum do lote já passou, então um registro interno incompleto é corrupção
+                    Record::End | Record::Torn => return Err(invalid("malformed batch")),
+                }
+            }
+            Operation::Batch(operations)
+        }
+        _ => Operation::Clear,
+    };
+    Ok(Record::Operation(operation, record.len() as u64))
+}
+
+/// Reads a length-prefixed field, appending its raw bytes to `record`.
+/// Returns `None` if the input ends first.
+fn read_field(input: &mut impl Read, record: &mut Vec<u8>) -> io::Result<Option<Vec<u8>>> {
+    let Some(len) = read_bytes(input, record, 4)? else {
+        return Ok(None);
+    };
+    let len = u32::from_be_bytes(len.try_into().unwrap()) as usize;
+    read_bytes(input, record, len)
+}
+
+/// Reads exactly `len` bytes, also appending them to `record`. Returns
+/// `None` if the input ends first.
+fn read_bytes(input: &mut impl Read, record: &mut Vec<u8>, len: usize) -> io::Result<Option<Vec<u8>>> {
+    let mut bytes = Vec::new();
+    // Não confia no tamanho para alocar: um registro cortado pode ter lixo no lugar
+    if input.take(len as u64).read_to_end(&mut bytes)? != len {
+        return Ok(None);
+    }
+    record.extend_from_slice(&bytes);
+    Ok(Some(bytes))
+}
+
+fn record(op: u8, key: &str) -> Vec<u8> {
+    let mut record = Vec::with_capacity(1 + 4 + key.len() + 16 + 8);
+    record.push(op);
+    record.extend_from_slice(&(key.len() as u32).to_be_bytes());
+    record.extend_from_slice(key.as_bytes());
+    record
+}
+
+/// Appends the checksum that ends every record.
+fn seal(mut record: Vec<u8>) -> Vec<u8> {
+    let checksum = dump::crc64(&record);
+    record.extend_from_slice(&checksum.to_be_bytes());
+    record
+}
+
+fn push_expiry<V: CacheValue>(record: &mut Vec<u8>, entry: &CacheEntry<V>, now: Instant) {
+    let expires_at = entry.ttl_deadline().map_or(0, |deadline| {
+        let remaining = deadline.saturating_duration_since(now);
+        // Zero significa "sem expiração", então um prazo real nunca vale zero
+        unix_millis(SystemTime::now() + remaining).max(1)
+    });
+    let idle_timeout = entry.idle_timeout().map_or(0, |idle| (idle.as_millis() as u64).max(1));
+    record.extend_from_slice(&expires_at.to_be_bytes());
+    record.extend_from_slice(&idle_timeout.to_be_bytes());
+}
+
+fn unix_millis(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH).map_or(0, |elapsed| elapsed.as_millis() as u64)
+}
+
+fn invalid(reason: &'static str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, CacheError::InvalidDump { reason })
+}
+
+fn lock<T: ?Sized>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::mem;
+    use std::path::PathBuf;
+
+    fn temp_log(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("spectra-cache-aof-{}-{}.aof", name, std::process::id()));
+        let _ = fs::remove_file(&path);
+        path
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let now = Instant::now();
+        let path = temp_log("round-trip");
+        {
+            let (log, fresh) = AppendLog::open(&path, FsyncPolicy::Always).unwrap();
+            assert!(fresh);
+            log.append(&set_record("user:1", &CacheEntry::<String>::new("user:1", "ação", now), now));
+            log.append(&set_record("session:1", &CacheEntry::<String>::with_ttl("session:1", "active", Some(Duration::from_secs(60)), now), now));
+            log.append(&delete_record("user:1"));
+            log.append(&retime_record("session:1", &CacheEntry::<String>::new("session:1", "active", now), now));
+            log.append(&clear_record());
+            assert_eq!(log.errors(), 0);
+        }
+
+        let operations = read::<String>(&path).unwrap();
+        assert_eq!(operations.len(), 5);
+        assert_eq!(
+            operations[0],
+            Operation::Set { key: "user:1".to_string(), value: "ação".to_string(), expires_at: None, idle_timeout: None }
+        );
+        let Operation::Set { expires_at: Some(expires_at), .. } = operations[1] else {
+            panic!("expected a set with a deadline, got {:?}", operations[1]);
+        };
+        let remaining = expires_at.duration_since(SystemTime::now()).unwrap();
+        assert!(remaining <= Duration::from_secs(60) && remaining > Duration::from_secs(50));
+        assert_eq!(operations[2], Operation::Delete { key: "user:1".to_string() });
+        assert_eq!(operations[3], Operation::Retime { key: "session:1".to_string(), expires_at: None, idle_timeout: None });
+        assert_eq!(operations[4], Operation::Clear);
+
+        // Reabrir um log existente continua do fim
+        let (log, fresh) = AppendLog::open(&path, FsyncPolicy::Never).unwrap();
+        assert!(!fresh);
+        log.append(&delete_record("session:1"));
+        drop(log);
+        assert_eq!(read::<String>(&path).unwrap().len(), 6);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_torn_tail_is_cut_off() {
+        let now = Instant::now();
+        let path = temp_log("torn");
+        {
+            let (log, _) = AppendLog::open(&path, FsyncPolicy::Always).unwrap();
+            log.append(&set_record("a", &CacheEntry::<String>::new("a", "1", now), now));
+            log.append(&set_record("b", &CacheEntry::<String>::new("b", "2", now), now));
+        }
+        let complete = fs::metadata(&path).unwrap().len();
+        let file = OpenOptions::new().write(true).open(&path).unwrap();
+        file.set_len(complete - 3).unwrap();
+
+        assert_eq!(read::<String>(&path).unwrap().len(), 1);
+        let cut = fs::metadata(&path).unwrap().len();
+        assert!(cut < complete - 3);
+        assert_eq!(read::<String>(&path).unwrap().len(), 1);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_rejects_malformed_input() {
+        let now = Instant::now();
+        let reason = |path: &Path| {
+            let error = read::<String>(path).unwrap_err();
+            assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+            error.into_inner().unwrap().to_string()
+        };
+        let path = temp_log("malformed");
+        fs::write(&path, b"not a log").unwrap();
+        assert_eq!(reason(&path), "invalid dump payload: not an append-only log");
+        assert!(AppendLog::open(&path, FsyncPolicy::Never).is_err());
+        fs::remove_file(&path).unwrap();
+
+        {
+            let (log, _) = AppendLog::open(&path, FsyncPolicy::Always).unwrap();
+            log.append(&set_record("key", &CacheEntry::<String>::new("key", "value", now), now));
+            log.append(&delete_record("key"));
+        }
+        let mut bytes = fs::read(&path).unwrap();
+        bytes[HEADER_LEN as usize + 6] ^= 0xff;
+        fs::write(&path, &bytes).unwrap();
+        assert_eq!(reason(&path), "invalid dump payload: checksum mismatch");
+        // Um registro corrompido no meio não é cortado
+        assert_eq!(fs::read(&path).unwrap(), bytes);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    #[cfg(feature = "cluster")]
+    fn test_decode_single_record() {
+        let now = Instant::now();
+        let record = set_record("k", &CacheEntry::<String>::new("k", "v", now), now);
+        let set = Operation::Set { key: "k".to_string(), value: "v".to_string(), expires_at: None, idle_timeout: None };
+        assert_eq!(decode::<String>(&record).unwrap(), set);
+        assert_eq!(decode::<String>(&clear_record()).unwrap(), Operation::Clear);
+        assert!(decode::<String>(&record[..record.len() - 1]).is_err());
+        let mut trailing = delete_record("k");
+        trailing.push(0);
+        assert!(decode::<String>(&trailing).is_err());
+    }
+
+    #[test]
+    fn test_batch_round_trip() {
+        let now = Instant::now();
+        let path = temp_log("batch");
+        let mut records = set_record("a", &CacheEntry::<String>::new("a", "1", now), now);
+        records.extend(delete_record("b"));
+        let batch = batch_record(&records);
+        {
+            let (log, _) = AppendLog::open(&path, FsyncPolicy::Never).unwrap();
+            log.append(&batch);
+            // Um lote cortado no meio é descartado inteiro
+            log.append(&batch[..batch.len() - 3]);
+        }
+
+        let operations = read::<String>(&path).unwrap();
+        let set = Operation::Set { key: "a".to_string(), value: "1".to_string(), expires_at: None, idle_timeout: None };
+        assert_eq!(operations, [Operation::Batch(vec![set, Operation::Delete { key: "b".to_string() }])]);
+
+        let mut corrupted = batch_record(&records[..records.len() - 1]);
+        corrupted.splice(0..0, delete_record("c"));
+        fs::write(&path, [&b"SPECAOF\0\0\x02"[..], &corrupted].concat()).unwrap();
+        assert!(read::<String>(&path).is_err());
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_counts_consecutive_failures() {
+        let path = temp_log("failures");
+        let (log, _) = AppendLog::open(&path, FsyncPolicy::Never).unwrap();
+        log.append(&delete_record("a"));
+
+        // Um arquivo aberto só para leitura faz as escritas falharem como um disco cheio
+        let writable = mem::replace(&mut *lock(&log.shared.file), (File::open(&path).unwrap(), 0));
+        log.append(&delete_record("b"));
+        log.append(&delete_record("c"));
+        assert_eq!((log.errors(), log.consecutive_failures()), (2, 2));
+
+        *lock(&log.shared.file) = writable;
+        log.append(&delete_record("d"));
+        assert_eq!((log.errors(), log.consecutive_failures()), (2, 0));
+        drop(log);
+        assert_eq!(read::<String>(&path).unwrap().len(), 2);
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,147 @@
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::sync::Mutex;
+
+use crate::{CacheError, CacheStats, DistributedHashTable};
+
+/// An async-friendly handle to a shared `DistributedHashTable`.
+///
+/// The table is kept behind a `tokio::sync::Mutex`, so the handle can be
+/// cloned freely and used from many tasks without blocking the runtime.
+/// Values are returned as owned `String`s because a borrow cannot outlive
+/// the lock guard.
+///
+/// # Examples
+///
+/// ```
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// use spectra_cache::AsyncCache;
+///
+/// let cache = AsyncCache::new();
+/// cache.insert("user:123", "John Doe").await;
+/// assert_eq!(cache.get("user:123").await, Some("John Doe".to_string()));
+/// # });
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct AsyncCache {
+    inner: Arc<Mutex<DistributedHashTable>>,
+}
+
+impl AsyncCache {
+    /// Creates a new handle around an empty table.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the number of entries in the cache.
+    pub async fn size(&self) -> usize {
+        self.inner.lock().await.size()
+    }
+
+    /// Returns true if the cache is empty.
+    pub async fn is_empty(&self) -> bool {
+        self.inner.lock().await.is_empty()
+    }
+
+    /// Inserts a key-value pair into the cache.
+    pub async fn insert(&self, key: &str, value: &str) {
+        self.inner.lock().await.insert(key, value);
+    }
+
+    /// Inserts a key-value pair with TTL into the cache.
+    pub async fn insert_with_ttl(&self, key: &str, value: &str, ttl: Duration) {
+        self.inner.lock().await.insert_with_ttl(key, value, ttl);
+    }
+
+    /// Inserts a key-value pair that expires after `idle` without reads.
+    pub async fn insert_with_tti(&self, key: &str, value: &str, idle: Duration) {
+        self.inner.lock().await.insert_with_tti(key, value, idle);
+    }
+
+    /// Retrieves a copy of the value stored under `key`.
+    ///
+    /// Returns None if the key doesn't exist or if the entry has expired.
+    pub async fn get(&self, key: &str) -> Option<String> {
+        self.inner.lock().await.get(key).map(str::to_string)
+    }
+
+    /// Removes a key-value pair from the cache.
+    ///
+    /// Returns the removed value if the key existed.
+    pub async fn remove(&self, key: &str) -> Option<String> {
+        self.inner.lock().await.remove(key)
+    }
+
+    /// Checks if a key exists in the cache.
+    pub async fn contains_key(&self, key: &str) -> bool {
+        self.inner.lock().await.contains_key(key)
+    }
+
+    /// Atomically adds `delta` to the integer stored under `key`.
+    ///
+    /// See [`DistributedHashTable::incr`] for details.
+    pub async fn incr(&self, key: &str, delta: i64) -> Result<i64, CacheError> {
+        self.inner.lock().await.incr(key, delta)
+    }
+
+    /// Atomically subtracts `delta` from the integer stored under `key`.
+    pub async fn decr(&self, key: &str, delta: i64) -> Result<i64, CacheError> {
+        self.inner.lock().await.decr(key, delta)
+    }
+
+    /// Removes all entries from the cache.
+    pub async fn clear(&self) {
+        self.inner.lock().await.clear();
+    }
+
+    /// Returns a snapshot of the cache's activity counters.
+    pub async fn stats(&self) -> CacheStats {
+        self.inner.lock().await.stats()
+    }
+
+    /// Returns the cached value for `key`, awaiting `loader` on a miss.
+    ///
+    /// The lock is released while the loader runs, so other tasks are never
+    /// blocked behind a slow load. Concurrent misses for the same key each
+    /// run their own loader and the last one to finish wins.
+    /// If the loader panics, the panic reaches the caller and nothing is
+    /// cached; since the lock was not held, other tasks are unaffected.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to look up
+    /// * `loader` - Produces the value when the key is missing or expired
+    pub async fn get_or_load<F, Fut>(&self, key: &str, loader: F) -> String
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = String>,
+    {
+        if let Some(value) = self.get(key).await {
+            return value;
+        }
+
+        let value = loader().await;
+        self.insert(key, &value).await;
+        value
+    }
+
+    /// Runs `f` with exclusive access to the underlying table.
+    ///
+    /// Useful for operations the async handle doesn't wrap directly.
+    /// A panic in `f` propagates to the caller; `tokio::sync::Mutex` does not
+    /// poison, so the table remains usable by other handles.
+    pub async fn with_table<R>(&self, f: impl FnOnce(&mut DistributedHashTable) -> R) -> R {
+        let mut table = self.inner.lock().await;
+        f(&mut table)
+    }
+}
+
+impl From<DistributedHashTable> for AsyncCache {
+    fn from(table: DistributedHashTable) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(table)),
+        }
+    }
+}
@@ -0,0 +1,266 @@
+#[cfg(feature = "std")]
+use alloc::boxed::Box;
+use alloc::string::String;
+#[cfg(feature = "std")]
+use alloc::string::ToString;
+use core::fmt;
+use core::net::SocketAddr;
+#[cfg(feature = "std")]
+use std::io::Write;
+#[cfg(feature = "std")]
+use std::panic::{self, AssertUnwindSafe};
+#[cfg(feature = "std")]
+use std::sync::{Mutex, PoisonError};
+
+#[cfg(feature = "std")]
+use crate::clock::{SystemTime, UNIX_EPOCH};
+#[cfg(feature = "std")]
+use crate::json;
+
+/// A destructive or configuration-changing operation worth auditing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AuditAction {
+    /// Every entry in the cache was dropped.
+    Flush { entries: usize },
+    /// Several entries were removed by a single call.
+    MassDelete { operation: String, entries: usize },
+    /// Every entry in a namespace was dropped.
+    NamespaceDrop { namespace: String, entries: usize },
+    /// A runtime setting changed.
+    ConfigChange { setting: String, value: String },
+}
+
+/// Who performed an audited operation.
+///
+/// Embedded users typically set an `actor` naming the component holding the
+/// cache; in server mode the connection's peer address is filled in as well.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AuditContext {
+    pub actor: Option<String>,
+    pub client_addr: Option<SocketAddr>,
+}
+
+/// A single record in the audit log: who did what, and when.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuditEvent {
+    pub timestamp: SystemTime,
+    pub actor: Option<String>,
+    pub client_addr: Option<SocketAddr>,
+    pub action: AuditAction,
+}
+
+#[cfg(feature = "std")]
+impl AuditEvent {
+    /// Serializes the event as a single-line JSON object.
+    ///
+    /// The object always carries `timestamp_ms` (milliseconds since the Unix
+    /// epoch), `actor`, `client_addr` and `action`; the remaining fields
+    /// depend on the action.
+    pub fn to_json(&self) -> String {
+        let timestamp_ms = self
+            .timestamp
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_millis());
+        let client_addr = self.client_addr.map(|addr| addr.to_string());
+        let details = match &self.action {
+            AuditAction::Flush { entries } => format!("\"action\":\"flush\",\"entries\":{}", entries),
+            AuditAction::MassDelete { operation, entries } => format!(
+                "\"action\":\"mass_delete\",\"operation\":{},\"entries\":{}",
+                json::quote(operation),
+                entries
+            ),
+            AuditAction::NamespaceDrop { namespace, entries } => format!(
+                "\"action\":\"namespace_drop\",\"namespace\":{},\"entries\":{}",
+                json::quote(namespace),
+                entries
+            ),
+            AuditAction::ConfigChange { setting, value } => format!(
+                "\"action\":\"config_change\",\"setting\":{},\"value\":{}",
+                json::quote(setting),
+                json::quote(value)
+            ),
+        };
+
+        format!(
+            "{{\"timestamp_ms\":{},\"actor\":{},\"client_addr\":{},{}}}",
+            timestamp_ms,
+            json::quote_opt(self.actor.as_deref()),
+            json::quote_opt(client_addr.as_deref()),
+            details
+        )
+    }
+}
+
+/// Destination for audit events.
+///
+/// Implemented for any `FnMut(&AuditEvent) + Send` closure, so forwarding
+/// events to an existing logging pipeline is a one-liner.
+#[cfg(feature = "std")]
+pub trait AuditSink: Send {
+    /// Persists or forwards a single event.
+    fn record(&mut self, event: &AuditEvent);
+}
+
+#[cfg(feature = "std")]
+impl<F: FnMut(&AuditEvent) + Send> AuditSink for F {
+    fn record(&mut self, event: &AuditEvent) {
+        self(event)
+    }
+}
+
+/// An audit sink writing one JSON object per line to any `io::Write`.
+///
+/// # Examples
+///
+/// ```
+/// use spectra_cache::{DistributedHashTable, WriterAuditSink};
+///
+/// let mut cache = DistributedHashTable::new();
+/// cache.set_audit_sink(WriterAuditSink::new(std::io::stderr()));
+/// cache.clear();
+/// ```
+#[cfg(feature = "std")]
+pub struct WriterAuditSink<W> {
+    writer: W,
+}
+
+#[cfg(feature = "std")]
+impl<W: Write + Send> WriterAuditSink<W> {
+    /// Creates a sink appending to `writer`.
+    pub fn new(writer: W) -> Self {
+        Self { writer }
+    }
+
+    /// Consumes the sink and returns the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+#[cfg(feature = "std")]
+impl<W: Write + Send> AuditSink for WriterAuditSink<W> {
+    fn record(&mut self, event: &AuditEvent) {
+        // Falhas de escrita não podem interromper a operação auditada
+        let _ = writeln!(self.writer, "{}", event.to_json());
+        let _ = self.writer.flush();
+    }
+}
+
+/// The audit state owned by each cache.
+///
+/// Sinks only need to be `Send`; the `Mutex` keeps a cache holding one
+/// `Sync`. Events are only recorded through `&mut self`, so it is reached
+/// with `Mutex::get_mut` and never locked. Without `std` there are no
+/// sinks, and recording an event does nothing.
+#[derive(Default)]
+pub(crate) struct AuditLog {
+    #[cfg(feature = "std")]
+    sink: Option<Mutex<Box<dyn AuditSink>>>,
+    context: AuditContext,
+    panics: u64,
+}
+
+impl AuditLog {
+    #[cfg(feature = "std")]
+    pub(crate) fn set_sink(&mut self, sink: Box<dyn AuditSink>) {
+        self.sink = Some(Mutex::new(sink));
+    }
+
+    #[cfg(feature = "std")]
+    pub(crate) fn remove_sink(&mut self) {
+        self.sink = None;
+    }
+
+    pub(crate) fn set_context(&mut self, context: AuditContext) {
+        self.context = context;
+    }
+
+    pub(crate) fn context(&self) -> &AuditContext {
+        &self.context
+    }
+
+    #[cfg(feature = "std")]
+    pub(crate) fn record(&mut self, action: AuditAction) {
+        if let Some(sink) = self.sink.as_mut() {
+            let sink = sink.get_mut().unwrap_or_else(PoisonError::into_inner);
+            let event = AuditEvent {
+                timestamp: SystemTime::now(),
+                actor: self.context.actor.clone(),
+                client_addr: self.context.client_addr,
+                action,
+            };
+            // Um sink que entra em pânico não pode interromper a operação auditada
+            if panic::catch_unwind(AssertUnwindSafe(|| sink.record(&event))).is_err() {
+                self.panics += 1;
+            }
+        }
+    }
+
+    #[cfg(not(feature = "std"))]
+    pub(crate) fn record(&mut self, _action: AuditAction) {}
+
+    /// Returns how many sink calls panicked.
+    pub(crate) fn panics(&self) -> u64 {
+        self.panics
+    }
+}
+
+impl fmt::Debug for AuditLog {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut debug = f.debug_struct("AuditLog");
+        #[cfg(feature = "std")]
+        debug.field("sink", &self.sink.as_ref().map(|_| "AuditSink"));
+        debug
+            .field("context", &self.context)
+            .field("panics", &self.panics)
+            .finish()
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_event_to_json() {
+        let event = AuditEvent {
+            timestamp: UNIX_EPOCH + Duration::from_millis(1500),
+            actor: Some("ops".to_string()),
+            client_addr: Some("127.0.0.1:6379".parse().unwrap()),
+            action: AuditAction::ConfigChange {
+                setting: "bloom_audit".to_string(),
+                value: "on".to_string(),
+            },
+        };
+
+        assert_eq!(
+            event.to_json(),
+            "{\"timestamp_ms\":1500,\"actor\":\"ops\",\"client_addr\":\"127.0.0.1:6379\",\
+             \"action\":\"config_change\",\"setting\":\"bloom_audit\",\"value\":\"on\"}"
+        );
+    }
+
+    #[test]
+    fn test_writer_sink_writes_json_lines() {
+        let mut sink = WriterAuditSink::new(Vec::new());
+        let mut event = AuditEvent {
+            timestamp: UNIX_EPOCH,
+            actor: None,
+            client_addr: None,
+            action: AuditAction::Flush { entries: 2 },
+        };
+        sink.record(&event);
+        event.action = AuditAction::Flush { entries: 0 };
+        sink.record(&event);
+
+        let output = String::from_utf8(sink.into_inner()).unwrap();
+        let lines: Vec<_> = output.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(
+            lines[0],
+            "{\"timestamp_ms\":0,\"actor\":null,\"client_addr\":null,\"action\":\"flush\",\"entries\":2}"
+        );
+    }
+}
@@ -0,0 +1,59 @@
+//! `spectra-server`: serves a `DistributedHashTable` over the Redis protocol.
+//!
+//! ```text
+//! spectra-server [--bind ADDR] [--max-memory LIMIT] [--databases N]
+//! ```
+//!
+//! `--bind` defaults to `127.0.0.1:6379`. `--max-memory` takes the same
+//! spellings as `MemoryLimit`, e.g. `512mb` or `60%`, and applies to each
+//! database. `--databases` sets the number of logical databases clients can
+//! `SELECT`, 16 by default as in Redis.
+
+use std::env;
+use std::net::TcpListener;
+use std::process;
+
+use spectra_cache::{DistributedHashTable, MemoryLimit, RespServer};
+
+const USAGE: &str = "usage: spectra-server [--bind ADDR] [--max-memory LIMIT] [--databases N]";
+
+fn main() {
+    let mut bind = "127.0.0.1:6379".to_string();
+    let mut builder = DistributedHashTable::builder();
+    let mut databases = 16;
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().unwrap_or_else(|| fail(&format!("{} needs a value", arg)));
+        match arg.as_str() {
+            "--bind" => bind = value(),
+            "--max-memory" => {
+                let limit: MemoryLimit = value().parse().unwrap_or_else(|error| fail(&format!("{}", error)));
+                builder = builder.max_memory(limit);
+            }
+            "--databases" => {
+                databases = match value().parse() {
+                    Ok(count) if count > 0 => count,
+                    _ => fail("--databases needs a positive number"),
+                };
+            }
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return;
+            }
+            _ => fail(&format!("unknown argument '{}'", arg)),
+        }
+    }
+
+    let listener = TcpListener::bind(&bind).unwrap_or_else(|error| fail(&format!("cannot bind {}: {}", bind, error)));
+    eprintln!("spectra-server listening on {}", bind);
+    let tables = (0..databases).map(|_| builder.clone().build()).collect();
+    if let Err(error) = RespServer::with_databases(tables).serve(listener) {
+        fail(&format!("server stopped: {}", error));
+    }
+}
+
+fn fail(message: &str) -> ! {
+    eprintln!("spectra-server: {}\n{}", message, USAGE);
+    process::exit(2);
+}
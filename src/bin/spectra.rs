@@ -0,0 +1,303 @@
+//! `spectra`: inspects and edits cache state saved to disk.
+//!
+//! ```text
+//! spectra diff [--values] [--redact PREFIX]... BEFORE AFTER
+//! spectra repl FILE
+//! spectra export FILE
+//! spectra import FILE
+//! ```
+//!
+//! `diff` compares two snapshot files written by `save_snapshot()` and
+//! prints one line per key that differs: `+` for keys only in `AFTER`, `-`
+//! for keys only in `BEFORE` and `~` for keys whose value changed. With
+//! `--values` the lines carry the values as well, except that the values of
+//! keys starting with a `--redact` prefix are shown as `[REDACTED]`. The
+//! exit status is 0 if the snapshots match, 1 if they differ and 2 on
+//! errors, as with diff(1).
+//!
+//! The other commands work on a snapshot or an append-only file, told
+//! apart by their header; a `FILE` that doesn't exist yet is created as an
+//! append-only file if its name ends in `.aof` and as a snapshot otherwise.
+//! Changes to an append-only file are logged as they are made, while a
+//! snapshot is rewritten when the command ends.
+//!
+//! `repl` reads commands from standard input, one per line:
+//!
+//! ```text
+//! get KEY            print the value, or (nil)
+//! set KEY VALUE...   store the rest of the line under KEY
+//! del KEY...         remove keys and print how many existed
+//! keys [PATTERN]     list keys, all of them or those matching a glob
+//! ttl KEY            print the seconds left, -1 if it never expires, -2 if missing
+//! expire KEY SECS    give KEY a TTL
+//! save               rewrite the snapshot now; a no-op for append-only files
+//! quit
+//! ```
+//!
+//! `export` writes every entry to standard output as JSON lines of the form
+//! `{"key":"user:1","value":"Ana","ttl_ms":5000}`, with `ttl_ms` left out
+//! for entries that never expire. `import` reads the same lines from
+//! standard input and stores them in `FILE`.
+
+use std::env;
+use std::fs::File;
+use std::io::{self, BufRead, IsTerminal, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::time::Duration;
+
+use serde_json::{json, Value};
+use spectra_cache::{diff_snapshots, DistributedHashTable, FsyncPolicy, KeyDiff, Redactor};
+
+const USAGE: &str = "usage: spectra diff [--values] [--redact PREFIX]... BEFORE AFTER
+       spectra repl FILE
+       spectra export FILE
+       spectra import FILE";
+
+const SNAPSHOT_MAGIC: &[u8; 8] = b"SPECSNAP";
+const AOF_MAGIC: &[u8; 8] = b"SPECAOF\0";
+
+fn main() {
+    let mut args = env::args().skip(1);
+    match args.next().as_deref() {
+        Some("diff") => diff(args.collect()),
+        Some("repl") => repl(&mut open(single_path(args))),
+        Some("export") => export(&open(single_path(args))),
+        Some("import") => import(&mut open(single_path(args))),
+        Some("-h" | "--help") => println!("{}", USAGE),
+        Some(command) => fail(&format!("unknown command '{}'", command)),
+        None => fail("missing command"),
+    }
+}
+
+fn diff(args: Vec<String>) {
+    let mut include_values = false;
+    let mut redactor = Redactor::new();
+    let mut paths = Vec::new();
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--values" => include_values = true,
+            "--redact" => match args.next() {
+                Some(prefix) => redactor = redactor.mask(prefix),
+                None => fail("--redact needs a key prefix"),
+            },
+            flag if flag.starts_with("--") => fail(&format!("unknown argument '{}'", flag)),
+            _ => paths.push(arg),
+        }
+    }
+    let [before, after] = &paths[..] else {
+        fail("diff needs two snapshot files");
+    };
+
+    let mut diff = diff_snapshots(before, after, include_values).unwrap_or_else(|error| fail(&format!("cannot diff: {}", error)));
+    diff.redact(&redactor);
+    for change in &diff.added {
+        println!("+ {}{}", change.key, shown(&change.after));
+    }
+    for change in &diff.removed {
+        println!("- {}{}", change.key, shown(&change.before));
+    }
+    for KeyDiff { key, before, after } in &diff.changed {
+        match (before, after) {
+            (Some(before), Some(after)) => println!("~ {}: {:?} -> {:?}", key, before, after),
+            _ => println!("~ {}", key),
+        }
+    }
+    if !diff.is_empty() {
+        process::exit(1);
+    }
+}
+
+/// A table loaded from a file, and how to write changes back to it.
+struct Opened {
+    table: DistributedHashTable,
+    path: PathBuf,
+    /// Writes go to the append-only file as they happen; otherwise the
+    /// snapshot is rewritten by `save`.
+    logged: bool,
+}
+
+impl Opened {
+    fn save(&mut self) {
+        if self.logged {
+            // Fecha o log para garantir o fsync antes de sair
+            self.table.disable_aof();
+        } else if let Err(error) = self.table.save_snapshot(&self.path) {
+            fail(&format!("cannot write {}: {}", self.path.display(), error));
+        }
+    }
+}
+
+fn single_path(args: impl Iterator<Item = String>) -> PathBuf {
+    let paths: Vec<String> = args.collect();
+    let [path] = &paths[..] else {
+        fail("expected one snapshot or append-only file");
+    };
+    PathBuf::from(path)
+}
+
+fn open(path: PathBuf) -> Opened {
+    let mut table = DistributedHashTable::new();
+    let logged = match read_magic(&path) {
+        Ok(Some(magic)) if &magic == SNAPSHOT_MAGIC => {
+            load(&path, table.warm_from_snapshot(&path, |_| {}));
+            false
+        }
+        Ok(Some(magic)) if &magic == AOF_MAGIC => {
+            load(&path, table.replay(&path));
+            true
+        }
+        Ok(Some(_)) => fail(&format!("{} is neither a snapshot nor an append-only file", path.display())),
+        Ok(None) => path.extension().is_some_and(|extension| extension == "aof"),
+        Err(error) => fail(&format!("cannot read {}: {}", path.display(), error)),
+    };
+    if logged {
+        if let Err(error) = table.enable_aof(&path, FsyncPolicy::Always) {
+            fail(&format!("cannot open {}: {}", path.display(), error));
+        }
+    }
+    Opened { table, path, logged }
+}
+
+/// Reads the first 8 bytes of `path`, or `None` if it doesn't exist.
+fn read_magic(path: &Path) -> io::Result<Option<[u8; 8]>> {
+    let mut file = match File::open(path) {
+        Ok(file) => file,
+        Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(error) => return Err(error),
+    };
+    let mut magic = [0; 8];
+    file.read_exact(&mut magic)?;
+    Ok(Some(magic))
+}
+
+fn load(path: &Path, loaded: io::Result<usize>) {
+    if let Err(error) = loaded {
+        fail(&format!("cannot load {}: {}", path.display(), error));
+    }
+}
+
+fn repl(opened: &mut Opened) {
+    let interactive = io::stdin().is_terminal();
+    let mut lines = io::stdin().lock().lines();
+    loop {
+        if interactive {
+            print!("spectra> ");
+            let _ = io::stdout().flush();
+        }
+        let Some(line) = lines.next() else { break };
+        let line = line.unwrap_or_else(|error| fail(&format!("cannot read input: {}", error)));
+        let (command, rest) = line.trim().split_once(' ').unwrap_or((line.trim(), ""));
+        let rest = rest.trim_start();
+        match command {
+            "" => {}
+            "quit" | "exit" => break,
+            "help" => println!("commands: get set del keys ttl expire save quit"),
+            // No arquivo de log cada escrita já foi sincronizada
+            "save" if opened.logged => println!("OK"),
+            "save" => match opened.table.save_snapshot(&opened.path) {
+                Ok(count) => println!("saved {} keys", count),
+                Err(error) => println!("(error) {}", error),
+            },
+            "get" => match opened.table.get(rest) {
+                Some(value) => println!("{}", value),
+                None => println!("(nil)"),
+            },
+            "set" => match rest.split_once(' ') {
+                Some((key, value)) => {
+                    opened.table.insert(key, value);
+                    println!("OK");
+                }
+                None => println!("(error) usage: set KEY VALUE"),
+            },
+            "del" => {
+                let removed = rest.split_whitespace().filter(|key| opened.table.remove(key).is_some()).count();
+                println!("{}", removed);
+            }
+            "keys" => {
+                let mut keys: Vec<&String> = match rest {
+                    "" => opened.table.keys().collect(),
+                    pattern => opened.table.keys_matching(pattern).collect(),
+                };
+                keys.sort();
+                for key in keys {
+                    println!("{}", key);
+                }
+            }
+            "ttl" => match opened.table.ttl(rest) {
+                Some(ttl) => println!("{}", ttl.as_secs()),
+                None if opened.table.contains_key(rest) => println!("-1"),
+                None => println!("-2"),
+            },
+            "expire" => match rest.split_once(' ').map(|(key, secs)| (key, secs.trim().parse::<u64>())) {
+                Some((key, Ok(secs))) => {
+                    println!("{}", u8::from(opened.table.expire(key, Duration::from_secs(secs))));
+                }
+                _ => println!("(error) usage: expire KEY SECONDS"),
+            },
+            command => println!("(error) unknown command '{}'", command),
+        }
+    }
+    opened.save();
+}
+
+fn export(opened: &Opened) {
+    let mut out = io::BufWriter::new(io::stdout().lock());
+    let mut entries: Vec<(&String, &str)> = opened.table.iter().map(|(key, value, _)| (key, value)).collect();
+    entries.sort();
+    for (key, value) in entries {
+        let mut line = json!({ "key": key, "value": value });
+        if let Some(ttl) = opened.table.ttl(key) {
+            line["ttl_ms"] = json!(ttl.as_millis() as u64);
+        }
+        if let Err(error) = writeln!(out, "{}", line) {
+            fail(&format!("cannot write output: {}", error));
+        }
+    }
+    if let Err(error) = out.flush() {
+        fail(&format!("cannot write output: {}", error));
+    }
+}
+
+fn import(opened: &mut Opened) {
+    let mut imported = 0;
+    for (number, line) in io::stdin().lock().lines().enumerate() {
+        let line = line.unwrap_or_else(|error| fail(&format!("cannot read input: {}", error)));
+        if line.trim().is_empty() {
+            continue;
+        }
+        let (key, value, ttl) = parse_line(&line).unwrap_or_else(|reason| fail(&format!("line {}: {}", number + 1, reason)));
+        match ttl {
+            Some(ttl) => opened.table.insert_with_ttl(&key, &value, ttl),
+            None => opened.table.insert(&key, &value),
+        }
+        imported += 1;
+    }
+    opened.save();
+    eprintln!("imported {} keys", imported);
+}
+
+/// Parses one line of `export` output.
+fn parse_line(line: &str) -> Result<(String, String, Option<Duration>), String> {
+    let line: Value = serde_json::from_str(line).map_err(|error| error.to_string())?;
+    let field = |name: &str| match &line[name] {
+        Value::String(text) => Ok(text.clone()),
+        _ => Err(format!("missing string field '{}'", name)),
+    };
+    let ttl = match &line["ttl_ms"] {
+        Value::Null => None,
+        ttl => Some(Duration::from_millis(ttl.as_u64().ok_or("'ttl_ms' is not a number of milliseconds")?)),
+    };
+    Ok((field("key")?, field("value")?, ttl))
+}
+
+/// Formats an optional value as ` = "value"`, or nothing without one.
+fn shown(value: &Option<String>) -> String {
+    value.as_ref().map_or_else(String::new, |value| format!(" = {:?}", value))
+}
+
+fn fail(message: &str) -> ! {
+    eprintln!("spectra: {}\n{}", message, USAGE);
+    process::exit(2);
+}
@@ -0,0 +1,699 @@
+use alloc::vec;
+use alloc::vec::Vec;
+use core::hash::{BuildHasher, BuildHasherDefault, Hash};
+#[cfg(feature = "core")]
+use core::time::Duration;
+#[cfg(feature = "std")]
+use std::collections::hash_map::DefaultHasher;
+
+#[cfg(not(any(feature = "std", test)))]
+use crate::float::FloatMath;
+
+/// The hasher Bloom filters use unless given another one: SipHash with
+/// fixed keys, so an element hashes the same in every filter and process.
+///
+/// Without the `std` feature it is `core`'s SipHash-2-4 rather than the
+/// standard library's SipHash-1-3, so filters can't be exchanged between
+/// `std` and `no_std` builds unless both pick the same hasher.
+#[cfg(feature = "std")]
+pub type DefaultBloomHasher = BuildHasherDefault<DefaultHasher>;
+
+/// The hasher Bloom filters use unless given another one.
+#[cfg(not(feature = "std"))]
+#[allow(deprecated)]
+pub type DefaultBloomHasher = BuildHasherDefault<core::hash::SipHasher>;
+
+/// Mixed into an element's second hash so it comes out independent of the
+/// first.
+const SECOND_HASH_SEED: u64 = 0x9e37_79b9_7f4a_7c15;
+
+/// A probabilistic data structure for testing set membership.
+/// 
+/// This structure provides:
+/// - Fast membership testing with a small probability of false positives
+/// - No false negatives
+/// - Space-efficient storage
+/// - Merge operations for combining filters
+/// 
+/// Elements are hashed twice with `S`, [`DefaultBloomHasher`] unless the
+/// filter is built with [`with_hasher`](Self::with_hasher), and the bits
+/// of an element are picked from the two hashes by double hashing
+/// (Kirsch–Mitzenmacher), which keeps the false positive bound of fully
+/// independent hash functions however many the filter uses. Merging and
+/// serializing filters only make sense if `S` hashes the same way in every
+/// instance, which seeded hashers such as `RandomState` don't.
+#[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(
+        into = "PackedBloomFilter",
+        try_from = "PackedBloomFilter",
+        bound(serialize = "S: Clone", deserialize = "S: Default")
+    )
+)]
+pub struct BloomFilter<S = DefaultBloomHasher> {
+    bits: Vec<bool>,
+    num_hash_functions: usize,
+    size: usize,
+    hasher: S,
+}
+
+impl BloomFilter {
+    /// Creates a new Bloom filter with the specified capacity and false positive rate.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `capacity` - Expected number of elements to be stored
+    /// * `false_positive_rate` - Desired probability of false positives (0.0 to 1.0)
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use spectra_cache::BloomFilter;
+    /// 
+    /// let filter = BloomFilter::new(1000, 0.01);
+    /// assert!(filter.is_empty());
+    /// ```
+    pub fn new(capacity: usize, false_positive_rate: f64) -> Self {
+        Self::with_hasher(capacity, false_positive_rate, DefaultBloomHasher::default())
+    }
+}
+
+impl<S: BuildHasher> BloomFilter<S> {
+    /// Creates a Bloom filter that hashes elements with `hasher`.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use spectra_cache::BloomFilter;
+    /// use std::collections::hash_map::DefaultHasher;
+    /// use std::hash::BuildHasherDefault;
+    /// 
+    /// let mut filter = BloomFilter::with_hasher(1000, 0.01, BuildHasherDefault::<DefaultHasher>::default());
+    /// filter.insert("key");
+    /// assert!(filter.contains("key"));
+    /// ```
+    pub fn with_hasher(capacity: usize, false_positive_rate: f64, hasher: S) -> Self {
+        let num_bits = Self::optimal_num_bits(capacity, false_positive_rate);
+        let num_hash_functions = Self::optimal_num_hash_functions(num_bits, capacity);
+        
+        Self {
+            bits: vec![false; num_bits],
+            num_hash_functions,
+            size: 0,
+            hasher,
+        }
+    }
+    
+    /// Returns the number of elements in the filter.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+    
+    /// Returns true if the filter is empty.
+    pub fn is_empty(&self) -> bool {
+        self.size == 0
+    }
+    
+    /// Inserts an element into the filter.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `item` - The element to insert
+    pub fn insert<T: Hash + ?Sized>(&mut self, item: &T) {
+        self.insert_hash(hash_pair(&self.hasher, item));
+    }
+    
+    /// Inserts an element already hashed with this filter's hasher.
+    fn insert_hash(&mut self, hashes: (u64, u64)) {
+        for i in 0..self.num_hash_functions {
+            let index = self.get_index(hashes, i);
+            self.bits[index] = true;
+        }
+        
+        self.size += 1;
+    }
+    
+    /// Checks if an element is in the filter.
+    /// 
+    /// Returns true if the element is probably in the filter.
+    /// There is a small probability of false positives.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `item` - The element to check
+    pub fn contains<T: Hash + ?Sized>(&self, item: &T) -> bool {
+        self.contains_hash(hash_pair(&self.hasher, item))
+    }
+    
+    /// Checks for an element already hashed with this filter's hasher.
+    fn contains_hash(&self, hashes: (u64, u64)) -> bool {
+        for i in 0..self.num_hash_functions {
+            let index = self.get_index(hashes, i);
+            if !self.bits[index] {
+                return false;
+            }
+        }
+        
+        true
+    }
+    
+    /// Returns the fraction of bits that are set, from 0 to 1.
+    /// 
+    /// A filter filled to its capacity sits near 0.5; past that, lookups of
+    /// absent elements find all their bits set more and more often.
+    pub fn fill_ratio(&self) -> f64 {
+        if self.bits.is_empty() {
+            return 0.0;
+        }
+        self.ones() as f64 / self.bits.len() as f64
+    }
+    
+    /// Estimates the current false positive rate from the bits actually set:
+    /// the chance that every bit an absent element maps to is set.
+    /// 
+    /// Unlike the rate the filter was built for, this keeps rising as more
+    /// elements than its capacity are inserted.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use spectra_cache::BloomFilter;
+    /// 
+    /// let mut filter = BloomFilter::new(100, 0.01);
+    /// for i in 0..100 {
+    ///     filter.insert(&i);
+    /// }
+    /// assert!(filter.estimated_false_positive_rate() < 0.05);
+    /// for i in 100..1000 {
+    ///     filter.insert(&i);
+    /// }
+    /// assert!(filter.is_saturated(0.9));
+    /// assert!(filter.estimated_false_positive_rate() > 0.5);
+    /// ```
+    pub fn estimated_false_positive_rate(&self) -> f64 {
+        self.fill_ratio().powi(self.num_hash_functions as i32)
+    }
+    
+    /// Returns true if at least `threshold` of the bits are set, see
+    /// [`fill_ratio`](Self::fill_ratio).
+    pub fn is_saturated(&self, threshold: f64) -> bool {
+        self.fill_ratio() >= threshold
+    }
+    
+    fn ones(&self) -> usize {
+        self.bits.iter().filter(|&&bit| bit).count()
+    }
+    
+    /// Removes all elements from the filter.
+    pub fn clear(&mut self) {
+        self.bits.fill(false);
+        self.size = 0;
+    }
+    
+    /// Merges another Bloom filter into this one.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `other` - The Bloom filter to merge with
+    pub fn merge(&mut self, other: &BloomFilter<S>) {
+        assert_eq!(self.bits.len(), other.bits.len(), "Bloom filters must have the same size to merge");
+        assert_eq!(self.num_hash_functions, other.num_hash_functions, "Bloom filters must have the same number of hash functions to merge");
+        
+        for i in 0..self.bits.len() {
+            self.bits[i] |= other.bits[i];
+        }
+        
+        // Não somamos os tamanhos porque podem haver elementos duplicados
+        // O tamanho real é uma estimativa baseada na densidade dos bits
+        let density = self.fill_ratio();
+        self.size = (self.bits.len() as f64 * density / self.num_hash_functions as f64).round() as usize;
+    }
+    
+    /// Calculates the optimal number of bits based on capacity and false positive rate.
+    fn optimal_num_bits(capacity: usize, false_positive_rate: f64) -> usize {
+        let ln2 = core::f64::consts::LN_2;
+        let ln2_squared = ln2 * ln2;
+        let capacity_f64 = capacity as f64;
+        let bits = (-capacity_f64 * false_positive_rate.ln()) / ln2_squared;
+        bits.ceil() as usize
+    }
+    
+    /// Calculates the optimal number of hash functions based on number of bits and capacity.
+    fn optimal_num_hash_functions(num_bits: usize, capacity: usize) -> usize {
+        let ln2 = core::f64::consts::LN_2;
+        ((num_bits as f64 / capacity as f64) * ln2).round() as usize
+    }
+    
+    /// Gets the index of the `i`-th hash function: `h1 + i * h2` modulo
+    /// the number of bits.
+    fn get_index(&self, (h1, h2): (u64, u64), i: usize) -> usize {
+        let len = self.bits.len() as u128;
+        // Um passo múltiplo do tamanho levaria todas as funções ao mesmo bit
+        let step = (u128::from(h2) % len).max(1);
+        ((u128::from(h1) + i as u128 * step) % len) as usize
+    }
+}
+
+/// Hashes `item` twice with `hasher`, the second time together with a
+/// seed, for double hashing.
+fn hash_pair<S: BuildHasher, T: Hash + ?Sized>(hasher: &S, item: &T) -> (u64, u64) {
+    (hasher.hash_one(item), hasher.hash_one((item, SECOND_HASH_SEED)))
+}
+
+/// The serialized form of a [`BloomFilter`], with its bits packed into
+/// 64-bit words.
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct PackedBloomFilter {
+    bits: usize,
+    words: Vec<u64>,
+    hash_functions: usize,
+    size: usize,
+}
+
+#[cfg(feature = "serde")]
+impl<S> From<BloomFilter<S>> for PackedBloomFilter {
+    fn from(filter: BloomFilter<S>) -> Self {
+        let mut words = vec![0u64; filter.bits.len().div_ceil(64)];
+        for (index, _) in filter.bits.iter().enumerate().filter(|(_, &bit)| bit) {
+            words[index / 64] |= 1 << (index % 64);
+        }
+        Self {
+            bits: filter.bits.len(),
+            words,
+            hash_functions: filter.num_hash_functions,
+            size: filter.size,
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<S: Default> TryFrom<PackedBloomFilter> for BloomFilter<S> {
+    type Error = String;
+
+    fn try_from(packed: PackedBloomFilter) -> Result<Self, Self::Error> {
+        if packed.bits == 0 || packed.words.len() != packed.bits.div_ceil(64) {
+            return Err(format!("{} words can't hold {} filter bits", packed.words.len(), packed.bits));
+        }
+        let bits = (0..packed.bits)
+            .map(|index| packed.words[index / 64] & (1 << (index % 64)) != 0)
+            .collect();
+        Ok(Self {
+            bits,
+            num_hash_functions: packed.hash_functions,
+            size: packed.size,
+            hasher: S::default(),
+        })
+    }
+}
+
+/// How much larger each sub-filter of a [`ScalableBloomFilter`] is than the previous one.
+const GROWTH_FACTOR: usize = 2;
+
+/// How much tighter each sub-filter's false positive rate is than the previous one's.
+const TIGHTENING_RATIO: f64 = 0.5;
+
+/// A Bloom filter that keeps its false positive rate as it grows.
+/// 
+/// A plain [`BloomFilter`] is sized up front: once more elements than its
+/// capacity are inserted, its false positive rate climbs towards 100%. This
+/// filter instead chains sub-filters (Almeida et al., "Scalable Bloom
+/// Filters"). When the newest one is full, a new sub-filter twice as large
+/// and with half the error rate is added, so the compound false positive
+/// rate stays below the requested one at any size.
+/// 
+/// # Examples
+/// 
+/// ```
+/// use spectra_cache::ScalableBloomFilter;
+/// 
+/// let mut filter = ScalableBloomFilter::new(100, 0.01);
+/// for i in 0..10_000 {
+///     filter.insert(&format!("key:{}", i));
+/// }
+/// assert!(filter.contains("key:9999"));
+/// assert!(filter.filter_count() > 1);
+/// ```
+#[derive(Debug, Clone)]
+pub struct ScalableBloomFilter<S = DefaultBloomHasher> {
+    stages: Vec<Stage<S>>,
+    initial_capacity: usize,
+    false_positive_rate: f64,
+    size: usize,
+    hasher: S,
+}
+
+/// One sub-filter of a [`ScalableBloomFilter`] and the number of elements it was sized for.
+#[derive(Debug, Clone)]
+struct Stage<S> {
+    filter: BloomFilter<S>,
+    capacity: usize,
+}
+
+impl ScalableBloomFilter {
+    /// Creates a filter whose first sub-filter holds `initial_capacity`
+    /// elements, keeping the overall false positive rate under
+    /// `false_positive_rate`.
+    /// 
+    /// # Panics
+    /// 
+    /// Panics if `initial_capacity` is zero or `false_positive_rate` is not
+    /// strictly between 0 and 1.
+    pub fn new(initial_capacity: usize, false_positive_rate: f64) -> Self {
+        Self::with_hasher(initial_capacity, false_positive_rate, DefaultBloomHasher::default())
+    }
+}
+
+impl<S: BuildHasher + Clone> ScalableBloomFilter<S> {
+    /// Creates a filter like [`new`](ScalableBloomFilter::new) that hashes
+    /// elements with `hasher`. Every sub-filter shares it, so an element is
+    /// hashed once per call however many sub-filters there are.
+    /// 
+    /// # Panics
+    /// 
+    /// Panics under the same conditions as `new`.
+    pub fn with_hasher(initial_capacity: usize, false_positive_rate: f64, hasher: S) -> Self {
+        assert!(initial_capacity > 0, "initial capacity must be non-zero");
+        assert!(
+            false_positive_rate > 0.0 && false_positive_rate < 1.0,
+            "false positive rate must be between 0 and 1"
+        );
+        let mut filter = Self {
+            stages: Vec::new(),
+            initial_capacity,
+            false_positive_rate,
+            size: 0,
+            hasher,
+        };
+        filter.add_stage();
+        filter
+    }
+
+    /// Returns the number of distinct elements inserted, as far as the filter can tell.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Returns true if the filter is empty.
+    pub fn is_empty(&self) -> bool {
+        self.size == 0
+    }
+
+    /// Returns the number of chained sub-filters.
+    pub fn filter_count(&self) -> usize {
+        self.stages.len()
+    }
+
+    /// Inserts an element, adding a sub-filter first if the newest one is full.
+    /// 
+    /// Elements the filter already (probably) contains are skipped, so
+    /// rewriting the same key does not make the filter grow.
+    pub fn insert<T: Hash + ?Sized>(&mut self, item: &T) {
+        let hashes = hash_pair(&self.hasher, item);
+        if self.contains_hash(hashes) {
+            return;
+        }
+        let full = self.stages.last().is_some_and(|stage| stage.filter.size() >= stage.capacity);
+        if full {
+            self.add_stage();
+        }
+        if let Some(stage) = self.stages.last_mut() {
+            stage.filter.insert_hash(hashes);
+        }
+        self.size += 1;
+    }
+
+    /// Checks if an element is probably in the filter.
+    pub fn contains<T: Hash + ?Sized>(&self, item: &T) -> bool {
+        self.contains_hash(hash_pair(&self.hasher, item))
+    }
+
+    fn contains_hash(&self, hashes: (u64, u64)) -> bool {
+        self.stages.iter().any(|stage| stage.filter.contains_hash(hashes))
+    }
+
+    /// Returns the fraction of bits set across every sub-filter, from 0 to 1.
+    /// 
+    /// Only the newest sub-filter still fills up, so this falls each time
+    /// one is added.
+    pub fn fill_ratio(&self) -> f64 {
+        let (ones, bits) = self
+            .stages
+            .iter()
+            .fold((0, 0), |(ones, bits), stage| (ones + stage.filter.ones(), bits + stage.filter.bits.len()));
+        if bits == 0 {
+            0.0
+        } else {
+            ones as f64 / bits as f64
+        }
+    }
+
+    /// Estimates the current false positive rate from the bits set in each
+    /// sub-filter: the chance that any of them reports an absent element.
+    pub fn estimated_false_positive_rate(&self) -> f64 {
+        let all_negative: f64 = self
+            .stages
+            .iter()
+            .map(|stage| 1.0 - stage.filter.estimated_false_positive_rate())
+            .product();
+        1.0 - all_negative
+    }
+
+    /// Adds sub-filters ahead of time until the newest one has room for
+    /// `additional` more elements, so inserting them doesn't allocate.
+    /// 
+    /// Room left in the sub-filter that was newest before the call goes
+    /// unused, which costs memory but not accuracy.
+    pub fn reserve(&mut self, additional: usize) {
+        while self.stages.last().is_some_and(|stage| stage.capacity - stage.filter.size() < additional) {
+            self.add_stage();
+        }
+    }
+
+    /// Removes all elements and shrinks the filter back to a single sub-filter.
+    pub fn clear(&mut self) {
+        self.stages.clear();
+        self.size = 0;
+        self.add_stage();
+    }
+
+    fn add_stage(&mut self) {
+        // Série geométrica: p0 / (1 - r) = taxa pedida
+        let level = self.stages.len() as u32;
+        let capacity = self.initial_capacity.saturating_mul(GROWTH_FACTOR.saturating_pow(level));
+        let rate = self.false_positive_rate * (1.0 - TIGHTENING_RATIO) * TIGHTENING_RATIO.powi(level as i32);
+        self.stages.push(Stage {
+            filter: BloomFilter::with_hasher(capacity, rate, self.hasher.clone()),
+            capacity,
+        });
+    }
+}
+
+/// Outcomes the adaptive bypass looks at before deciding again.
+#[cfg(feature = "core")]
+const BYPASS_WINDOW: u32 = 256;
+
+/// While the filter is bypassed, one lookup in this many still checks the
+/// map on its own to measure the miss rate and lookup cost; while it is in
+/// use, one in this many is timed.
+#[cfg(feature = "core")]
+const BYPASS_SAMPLE_INTERVAL: u32 = 16;
+
+/// How many times its cost a bypassed filter must be expected to save
+/// before it is used again, so the cache doesn't flap between the two.
+#[cfg(feature = "core")]
+const BYPASS_HYSTERESIS: f64 = 1.5;
+
+/// Weight of a new sample in the running cost averages.
+#[cfg(feature = "core")]
+const BYPASS_COST_SMOOTHING: f64 = 0.2;
+
+/// Decides at runtime whether asking the Bloom filter before the map pays
+/// off.
+///
+/// The filter saves a map lookup for every key it rejects and costs a
+/// probe on every lookup, so it pays when the rejection rate times the
+/// cost of a map lookup exceeds the cost of a probe. Both costs are timed
+/// on a sample of lookups. While the filter is bypassed the map's miss
+/// rate stands in for the rejection rate, and the filter is used again
+/// once misses rise enough to make it worth its cost.
+#[cfg(feature = "core")]
+#[derive(Debug, Clone, Default)]
+pub(crate) struct BloomBypass {
+    bypassing: bool,
+    lookups: u32,
+    outcomes: u32,
+    negatives: u32,
+    /// Running averages, unset until first timed
+    probe_nanos: Option<f64>,
+    lookup_nanos: Option<f64>,
+}
+
+#[cfg(feature = "core")]
+impl BloomBypass {
+    pub(crate) fn is_bypassing(&self) -> bool {
+        self.bypassing
+    }
+
+    /// Counts a lookup and returns true if it should be sampled.
+    pub(crate) fn sample_due(&mut self) -> bool {
+        self.lookups = self.lookups.wrapping_add(1);
+        self.lookups.is_multiple_of(BYPASS_SAMPLE_INTERVAL)
+    }
+
+    pub(crate) fn record_probe_cost(&mut self, cost: Duration) {
+        self.probe_nanos = smooth(self.probe_nanos, cost);
+    }
+
+    pub(crate) fn record_lookup_cost(&mut self, cost: Duration) {
+        self.lookup_nanos = smooth(self.lookup_nanos, cost);
+    }
+
+    /// Records whether a lookup found nothing: rejected by the filter, or
+    /// missing from the map while the filter is bypassed.
+    pub(crate) fn record(&mut self, negative: bool) {
+        self.outcomes += 1;
+        self.negatives += u32::from(negative);
+        if self.outcomes < BYPASS_WINDOW {
+            return;
+        }
+        if let (Some(probe), Some(lookup)) = (self.probe_nanos, self.lookup_nanos) {
+            let savings = f64::from(self.negatives) / f64::from(self.outcomes) * lookup;
+            self.bypassing = match self.bypassing {
+                false => savings < probe,
+                true => savings <= probe * BYPASS_HYSTERESIS,
+            };
+        }
+        self.outcomes = 0;
+        self.negatives = 0;
+    }
+}
+
+#[cfg(feature = "core")]
+fn smooth(average: Option<f64>, sample: Duration) -> Option<f64> {
+    let sample = sample.as_nanos() as f64;
+    Some(average.map_or(sample, |average| average + BYPASS_COST_SMOOTHING * (sample - average)))
+}
+
+/// Counters collected by a cache running in Bloom filter audit mode.
+///
+/// In audit mode every lookup is cross-checked against the underlying map,
+/// which makes it possible to measure how the filter actually behaves:
+/// - False negatives should always be zero; any other value means the filter
+///   has gone out of sync with the stored keys
+/// - The measured false positive rate can be compared against the configured one
+///
+/// Auditing looks up every checked key in the map, whatever the filter
+/// answers: a rejected key no longer saves that lookup, and a stored one is
+/// looked up twice. Reads through `&self` also take a lock to record the
+/// outcome, and an audited cache never bypasses its filter. It is meant for
+/// validating filter configuration, not for production traffic.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BloomAudit {
+    lookups: u64,
+    true_positives: u64,
+    false_positives: u64,
+    true_negatives: u64,
+    false_negatives: u64,
+}
+
+impl BloomAudit {
+    /// Records the outcome of a single filter check.
+    ///
+    /// # Arguments
+    ///
+    /// * `filter_says_present` - What the Bloom filter answered
+    /// * `actually_present` - Whether the key is stored in the map
+    #[cfg(feature = "core")]
+    pub(crate) fn record(&mut self, filter_says_present: bool, actually_present: bool) {
+        self.lookups += 1;
+        match (filter_says_present, actually_present) {
+            (true, true) => self.true_positives += 1,
+            (true, false) => self.false_positives += 1,
+            (false, false) => self.true_negatives += 1,
+            (false, true) => self.false_negatives += 1,
+        }
+    }
+
+    /// Returns the number of audited lookups.
+    pub fn lookups(&self) -> u64 {
+        self.lookups
+    }
+
+    /// Returns how many lookups the filter correctly let through.
+    pub fn true_positives(&self) -> u64 {
+        self.true_positives
+    }
+
+    /// Returns how many absent keys the filter claimed to contain.
+    pub fn false_positives(&self) -> u64 {
+        self.false_positives
+    }
+
+    /// Returns how many absent keys the filter correctly rejected.
+    pub fn true_negatives(&self) -> u64 {
+        self.true_negatives
+    }
+
+    /// Returns how many stored keys the filter wrongly rejected.
+    pub fn false_negatives(&self) -> u64 {
+        self.false_negatives
+    }
+
+    /// Returns true if the filter ever rejected a key that was stored.
+    pub fn has_false_negatives(&self) -> bool {
+        self.false_negatives > 0
+    }
+
+    /// Returns the measured false positive rate.
+    ///
+    /// This is the fraction of lookups for absent keys that the filter let
+    /// through, or `0.0` if no absent key has been looked up yet.
+    pub fn false_positive_rate(&self) -> f64 {
+        let negatives = self.false_positives + self.true_negatives;
+        if negatives == 0 {
+            0.0
+        } else {
+            self.false_positives as f64 / negatives as f64
+        }
+    }
+}
+
+#[cfg(all(test, feature = "core"))]
+mod tests {
+    use super::*;
+
+    fn window(bypass: &mut BloomBypass, probe: u64, lookup: u64, negative_every: u32) {
+        bypass.record_probe_cost(Duration::from_nanos(probe));
+        bypass.record_lookup_cost(Duration::from_nanos(lookup));
+        for i in 0..BYPASS_WINDOW {
+            bypass.record(negative_every > 0 && i % negative_every == 0);
+        }
+    }
+
+    #[test]
+    fn test_bypass_follows_the_miss_rate() {
+        let mut bypass = BloomBypass::default();
+        // Sem custos medidos não há decisão
+        for _ in 0..BYPASS_WINDOW {
+            bypass.record(false);
+        }
+        assert!(!bypass.is_bypassing());
+
+        // Só acertos: o filtro nunca poupa nada
+        window(&mut bypass, 100, 200, 0);
+        assert!(bypass.is_bypassing());
+
+        // Um terço de misses poupa 67ns por 100ns de sonda: ainda não compensa
+        window(&mut bypass, 100, 200, 3);
+        assert!(bypass.is_bypassing());
+
+        // Todos misses poupam 200ns, acima da histerese
+        window(&mut bypass, 100, 200, 1);
+        assert!(!bypass.is_bypassing());
+        window(&mut bypass, 100, 200, 2);
+        assert!(!bypass.is_bypassing());
+    }
+}
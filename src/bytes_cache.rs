@@ -0,0 +1,637 @@
+use alloc::boxed::Box;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::time::Duration;
+#[cfg(feature = "persistence")]
+use std::io;
+#[cfg(feature = "persistence")]
+use std::path::Path;
+#[cfg(feature = "std")]
+use std::sync::mpsc::Receiver;
+
+use crate::collections::HashMap;
+use crate::config::{CacheConfig, CacheType};
+use crate::core::CacheCore;
+use crate::entry::CacheEntry;
+use crate::{
+    CacheBuilder, CacheError, CacheStats, ConflictStrategy, EffectiveConfig, Entry, EntryMetadata, GetOptions, MaybeStale,
+    RemovalCause, Transaction,
+};
+#[cfg(feature = "std")]
+use crate::{CacheEvent, Durability, HistoryEntry};
+#[cfg(feature = "persistence")]
+use crate::FsyncPolicy;
+#[cfg(feature = "cluster")]
+use crate::{BucketPatch, DivergentBuckets, MerkleDigest, Transport};
+
+/// A hash-table cache for binary values.
+///
+/// Works like `DistributedHashTable` but stores `Vec<u8>` values, so
+/// protobuf or msgpack payloads can be cached as-is instead of being
+/// base64-encoded into strings. Reads borrow the stored bytes without
+/// copying. Keys are still strings.
+///
+/// # Examples
+///
+/// ```
+/// use spectra_cache::BytesCache;
+///
+/// let mut cache = BytesCache::new();
+/// cache.insert("avatar:123", &[0x89, b'P', b'N', b'G']);
+/// assert_eq!(cache.get("avatar:123"), Some(&[0x89, b'P', b'N', b'G'][..]));
+/// ```
+#[derive(Debug, Clone)]
+pub struct BytesCache {
+    pub(crate) core: CacheCore<HashMap<String, CacheEntry<Vec<u8>>>>,
+}
+
+impl BytesCache {
+    /// Creates a new empty cache.
+    pub fn new() -> Self {
+        Self {
+            core: CacheCore::new(),
+        }
+    }
+
+    /// Returns a builder for a cache with non-default settings.
+    pub fn builder() -> CacheBuilder<Self> {
+        CacheBuilder::new()
+    }
+
+    /// Returns a deep copy whose entries count as freshly written.
+    ///
+    /// See [`DistributedHashTable::clone_with_fresh_timestamps`](crate::DistributedHashTable::clone_with_fresh_timestamps).
+    pub fn clone_with_fresh_timestamps(&self) -> Self {
+        let mut copy = self.clone();
+        copy.core.refresh_timestamps();
+        copy
+    }
+
+    fn with_config(config: CacheConfig) -> Self {
+        Self {
+            core: CacheCore::with_config(config),
+        }
+    }
+
+    /// Returns the number of entries in the cache.
+    pub fn size(&self) -> usize {
+        self.core.size()
+    }
+
+    /// Returns true if the cache is empty.
+    pub fn is_empty(&self) -> bool {
+        self.core.is_empty()
+    }
+
+    /// Inserts a value, replacing any previous value under `key`.
+    ///
+    /// The entry never expires unless the cache was built with a default TTL.
+    pub fn insert(&mut self, key: &str, value: &[u8]) {
+        self.core.insert(key, value);
+    }
+
+    /// Inserts a value, failing if the cache refuses writes.
+    ///
+    /// See [`DistributedHashTable::try_insert`](crate::DistributedHashTable::try_insert).
+    pub fn try_insert(&mut self, key: &str, value: &[u8]) -> Result<(), CacheError> {
+        self.core.try_insert(key, value)
+    }
+
+    /// Inserts a value that expires after `ttl`.
+    pub fn insert_with_ttl(&mut self, key: &str, value: &[u8], ttl: Duration) {
+        self.core.insert_entry(key, CacheEntry::with_ttl(key, value, Some(ttl), self.core.now()));
+    }
+
+    /// Inserts a value that expires after `idle` without reads.
+    pub fn insert_with_tti(&mut self, key: &str, value: &[u8], idle: Duration) {
+        self.core.insert_entry(key, CacheEntry::with_tti(key, value, idle, self.core.now()));
+    }
+
+    /// Inserts a value carrying `tags`, with the default TTL.
+    ///
+    /// See [`DistributedHashTable::insert_with_tags`](crate::DistributedHashTable::insert_with_tags).
+    pub fn insert_with_tags(&mut self, key: &str, value: &[u8], tags: &[&str]) {
+        self.core.insert_with_tags(key, value, tags);
+    }
+
+    /// Retrieves a value by key, borrowing the stored bytes.
+    ///
+    /// Returns None if the key doesn't exist or if the entry has expired.
+    ///
+    /// See [`DistributedHashTable::get`](crate::DistributedHashTable::get).
+    pub fn get(&self, key: &str) -> Option<&[u8]> {
+        self.core.get_ref(key)
+    }
+
+    /// Retrieves a value by key along with its version.
+    ///
+    /// See [`DistributedHashTable::get_versioned`](crate::DistributedHashTable::get_versioned).
+    pub fn get_versioned(&mut self, key: &str) -> Option<(&[u8], u64)> {
+        self.core.get_versioned(key)
+    }
+
+    /// Retrieves a value without marking it as used.
+    ///
+    /// See [`DistributedHashTable::peek`](crate::DistributedHashTable::peek).
+    pub fn peek(&self, key: &str) -> Option<&[u8]> {
+        self.core.peek(key)
+    }
+
+    /// Retrieves a value by key within the latency budget of `options`.
+    ///
+    /// See [`DistributedHashTable::get_with`](crate::DistributedHashTable::get_with).
+    pub fn get_with(&mut self, key: &str, options: &GetOptions) -> Option<&[u8]> {
+        self.core.get_with(key, options)
+    }
+
+    /// Retrieves a value by key, flagged as stale if it is older than the
+    /// soft TTL.
+    ///
+    /// See [`DistributedHashTable::get_stale`](crate::DistributedHashTable::get_stale).
+    pub fn get_stale(&mut self, key: &str) -> Option<MaybeStale<&[u8]>> {
+        self.core.get_stale(key)
+    }
+
+    /// Returns how long `key` has left to live.
+    ///
+    /// See [`DistributedHashTable::ttl`](crate::DistributedHashTable::ttl).
+    pub fn ttl(&self, key: &str) -> Option<Duration> {
+        self.core.ttl(key)
+    }
+
+    /// Sets or replaces the TTL of an existing entry, counted from now.
+    ///
+    /// Returns true if the key existed.
+    pub fn expire(&mut self, key: &str, ttl: Duration) -> bool {
+        self.core.expire(key, ttl)
+    }
+
+    /// Removes the TTL and idle timeout of an entry so it never expires.
+    ///
+    /// Returns true if the entry existed and had an expiry to remove.
+    pub fn persist(&mut self, key: &str) -> bool {
+        self.core.persist(key)
+    }
+
+    /// Removes an entry, returning its value if the key existed.
+    pub fn remove(&mut self, key: &str) -> Option<Vec<u8>> {
+        self.core.remove(key)
+    }
+
+    /// Removes an entry, failing if the cache refuses writes.
+    ///
+    /// See [`DistributedHashTable::try_remove`](crate::DistributedHashTable::try_remove).
+    pub fn try_remove(&mut self, key: &str) -> Result<Option<Vec<u8>>, CacheError> {
+        self.core.try_remove(key)
+    }
+
+    /// Replaces the value of an existing entry, keeping its TTL.
+    ///
+    /// Returns true if the key existed.
+    pub fn update(&mut self, key: &str, value: &[u8]) -> bool {
+        self.core.update(key, value)
+    }
+
+    /// Replaces the value of an existing entry if it is still at
+    /// `expected_version`, and returns the new version.
+    ///
+    /// See [`DistributedHashTable::update_if_version`](crate::DistributedHashTable::update_if_version).
+    pub fn update_if_version(&mut self, key: &str, expected_version: u64, value: &[u8]) -> Result<u64, CacheError> {
+        self.core.update_if_version(key, expected_version, value)
+    }
+
+    /// Gets the given key's entry for in-place insert-or-update.
+    ///
+    /// The key is looked up only once; expired entries are reported as
+    /// vacant. Panics if the cache refuses writes; see
+    /// [`DistributedHashTable::entry`](crate::DistributedHashTable::entry).
+    pub fn entry(&mut self, key: &str) -> Entry<'_, Vec<u8>> {
+        self.core.entry(key)
+    }
+
+    /// Gets the given key's entry, failing if the cache refuses writes.
+    ///
+    /// See [`DistributedHashTable::try_entry`](crate::DistributedHashTable::try_entry).
+    pub fn try_entry(&mut self, key: &str) -> Result<Entry<'_, Vec<u8>>, CacheError> {
+        self.core.try_entry(key)
+    }
+
+    /// Checks if a key exists and has not expired.
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.core.contains_key(key)
+    }
+
+    /// Removes all entries from the cache.
+    pub fn clear(&mut self) {
+        self.core.clear();
+    }
+
+    /// Keeps only the live entries for which `keep` returns true, and
+    /// returns how many were removed.
+    ///
+    /// See [`DistributedHashTable::retain`](crate::DistributedHashTable::retain).
+    pub fn retain(&mut self, keep: impl FnMut(&str, &[u8]) -> bool) -> usize {
+        self.core.retain(keep)
+    }
+
+    /// Removes every entry carrying `tag` and returns how many were removed.
+    ///
+    /// See [`DistributedHashTable::invalidate_tag`](crate::DistributedHashTable::invalidate_tag).
+    pub fn invalidate_tag(&mut self, tag: &str) -> usize {
+        self.core.invalidate_tag(tag)
+    }
+
+    /// Applies every write of `transaction`, or none of them if a key it
+    /// watches no longer holds the expected value.
+    ///
+    /// See [`DistributedHashTable::apply`](crate::DistributedHashTable::apply).
+    pub fn apply(&mut self, transaction: Transaction<Vec<u8>>) -> Result<(), CacheError> {
+        self.core.commit(transaction)
+    }
+
+    /// Removes every entry, returning the live ones as `(key, value)` pairs.
+    ///
+    /// See [`DistributedHashTable::drain`](crate::DistributedHashTable::drain).
+    pub fn drain(&mut self) -> Vec<(String, Vec<u8>)> {
+        self.core.drain()
+    }
+
+    /// Removes every expired entry now and returns how many were removed.
+    ///
+    /// See [`DistributedHashTable::purge_expired`](crate::DistributedHashTable::purge_expired).
+    pub fn purge_expired(&mut self) -> usize {
+        self.core.purge_expired()
+    }
+
+    /// Removes every expired entry now and returns them as `(key, value)`
+    /// pairs.
+    ///
+    /// See [`DistributedHashTable::drain_expired`](crate::DistributedHashTable::drain_expired).
+    pub fn drain_expired(&mut self) -> Vec<(String, Vec<u8>)> {
+        self.core.drain_expired()
+    }
+
+    /// Folds the live entries of `other` into this cache.
+    ///
+    /// See [`DistributedHashTable::merge_from`](crate::DistributedHashTable::merge_from).
+    pub fn merge_from(&mut self, other: &Self, strategy: ConflictStrategy<Vec<u8>>) -> usize {
+        self.core.merge_from(&other.core, strategy)
+    }
+
+    /// Bulk-loads `(key, value)` pairs and returns how many were loaded.
+    ///
+    /// See [`DistributedHashTable::warm_from`](crate::DistributedHashTable::warm_from).
+    pub fn warm_from<I, K, V>(&mut self, entries: I, mut progress: impl FnMut(usize)) -> usize
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: AsRef<str>,
+        V: AsRef<[u8]>,
+    {
+        self.core.warm_from(entries, &mut progress)
+    }
+
+    /// Returns an iterator over the live keys in the cache.
+    pub fn keys(&self) -> impl Iterator<Item = &String> {
+        self.core.keys()
+    }
+
+    /// Returns an iterator over the live values in the cache.
+    pub fn values(&self) -> impl Iterator<Item = &Vec<u8>> {
+        self.core.values()
+    }
+
+    /// Returns an iterator over the live entries as `(key, value, metadata)`.
+    ///
+    /// See [`DistributedHashTable::iter`](crate::DistributedHashTable::iter).
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &[u8], EntryMetadata)> {
+        self.core.iter()
+    }
+
+    /// Returns an iterator over every stored key, including expired entries
+    /// that haven't been purged yet.
+    pub fn keys_raw(&self) -> impl Iterator<Item = &String> {
+        self.core.keys_raw()
+    }
+
+    /// Returns an iterator over every stored value, including expired
+    /// entries that haven't been purged yet.
+    pub fn values_raw(&self) -> impl Iterator<Item = &Vec<u8>> {
+        self.core.values_raw()
+    }
+
+    /// Returns an iterator over the live keys matching a glob pattern.
+    ///
+    /// See [`DistributedHashTable::keys_matching`](crate::DistributedHashTable::keys_matching).
+    pub fn keys_matching(&self, pattern: &str) -> impl Iterator<Item = &String> {
+        self.core.keys_matching(pattern)
+    }
+
+    /// Returns the live keys whose value equals `value`, in no particular order.
+    ///
+    /// See [`DistributedHashTable::keys_with_value`](crate::DistributedHashTable::keys_with_value).
+    pub fn keys_with_value(&self, value: &[u8]) -> Vec<&String> {
+        self.core.keys_with_value(value)
+    }
+
+    /// Returns true if any live key holds `value`, e.g. to check whether a
+    /// blob is already cached under another key.
+    pub fn contains_value(&self, value: &[u8]) -> bool {
+        !self.core.keys_with_value(value).is_empty()
+    }
+
+    /// Returns the approximate number of bytes used by the cache's entries.
+    pub fn memory_usage(&self) -> usize {
+        self.core.memory_usage()
+    }
+
+    /// Returns the total weight of the entries.
+    ///
+    /// See [`DistributedHashTable::total_weight`](crate::DistributedHashTable::total_weight).
+    pub fn total_weight(&self) -> u64 {
+        self.core.total_weight()
+    }
+
+    /// Pre-allocates room for `additional` more entries ahead of an expected
+    /// burst of writes.
+    ///
+    /// See [`DistributedHashTable::reserve_capacity`](crate::DistributedHashTable::reserve_capacity).
+    pub fn reserve_capacity(&mut self, additional: usize) {
+        self.core.reserve_capacity(additional);
+    }
+
+    /// Returns how many entries the cache holds without allocating more
+    /// buckets.
+    pub fn capacity(&self) -> usize {
+        self.core.capacity()
+    }
+
+    /// Returns the `n` largest live entries with their size in bytes, largest first.
+    pub fn biggest_keys(&self, n: usize) -> Vec<(&String, usize)> {
+        self.core.biggest_keys(n)
+    }
+
+    /// Returns an iterator over the live entries from the least to the most
+    /// recently used.
+    ///
+    /// See [`DistributedHashTable::iter_by_recency`](crate::DistributedHashTable::iter_by_recency).
+    pub fn iter_by_recency(&self) -> impl DoubleEndedIterator<Item = (&String, &[u8])> {
+        self.core.iter_by_recency()
+    }
+
+    /// Returns an iterator over the live entries from the least to the most used.
+    ///
+    /// See [`DistributedHashTable::iter_by_frequency`](crate::DistributedHashTable::iter_by_frequency).
+    pub fn iter_by_frequency(&self) -> impl Iterator<Item = (&String, &[u8])> {
+        self.core.iter_by_frequency()
+    }
+
+    /// Returns up to `n` live keys, least recently used first.
+    pub fn coldest(&self, n: usize) -> Vec<&String> {
+        self.core.coldest(n)
+    }
+
+    /// Returns up to `n` live keys, most recently used first.
+    pub fn hottest(&self, n: usize) -> Vec<&String> {
+        self.core.hottest(n)
+    }
+
+    /// Returns a snapshot of the cache's hit, miss, and eviction counters.
+    pub fn stats(&self) -> CacheStats {
+        self.core.stats()
+    }
+
+    /// Returns true if the cache is currently skipping the Bloom filter in
+    /// adaptive mode.
+    ///
+    /// See [`DistributedHashTable::bloom_bypassed`](crate::DistributedHashTable::bloom_bypassed).
+    pub fn bloom_bypassed(&self) -> bool {
+        self.core.bloom_bypassed()
+    }
+
+    /// Rebuilds the Bloom filter from the keys currently stored.
+    ///
+    /// See [`DistributedHashTable::rebuild_bloom_filter`](crate::DistributedHashTable::rebuild_bloom_filter).
+    pub fn rebuild_bloom_filter(&mut self) {
+        self.core.rebuild_bloom_filter();
+    }
+
+    /// Returns the settings the cache is running with.
+    pub fn config(&self) -> EffectiveConfig {
+        self.core.config()
+    }
+
+    /// Writes pending write-back changes to the backing store now.
+    ///
+    /// See [`DistributedHashTable::flush`](crate::DistributedHashTable::flush).
+    #[cfg(feature = "std")]
+    pub fn flush(&self) {
+        self.core.flush();
+    }
+
+    /// Has the write-back flusher write every change made so far, and waits
+    /// up to `timeout` for it to finish.
+    ///
+    /// See [`DistributedHashTable::flush_and_wait`](crate::DistributedHashTable::flush_and_wait).
+    #[cfg(feature = "std")]
+    pub fn flush_and_wait(&self, timeout: Duration) -> bool {
+        self.core.flush_and_wait(timeout)
+    }
+
+    /// Inserts a key-value pair and waits up to `timeout` for it to become
+    /// as durable as `durability` asks.
+    ///
+    /// See [`DistributedHashTable::insert_with_durability`](crate::DistributedHashTable::insert_with_durability).
+    #[cfg(feature = "std")]
+    pub fn insert_with_durability(&mut self, key: &str, value: &[u8], durability: Durability, timeout: Duration) -> Result<(), CacheError> {
+        self.core.insert_with_durability(key, value, durability, timeout)
+    }
+
+    /// Starts appending every write to the append-only log at `path`.
+    ///
+    /// See [`DistributedHashTable::enable_aof`](crate::DistributedHashTable::enable_aof).
+    #[cfg(feature = "persistence")]
+    pub fn enable_aof(&mut self, path: impl AsRef<Path>, fsync: FsyncPolicy) -> io::Result<()> {
+        self.core.enable_aof(path.as_ref(), fsync)
+    }
+
+    /// Stops logging writes, syncing and closing the append-only log.
+    #[cfg(feature = "persistence")]
+    pub fn disable_aof(&mut self) {
+        self.core.disable_aof();
+    }
+
+    /// Returns true once persistence failed three times in a row.
+    ///
+    /// See [`DistributedHashTable::is_degraded`](crate::DistributedHashTable::is_degraded).
+    #[cfg(feature = "persistence")]
+    pub fn is_degraded(&self) -> bool {
+        self.core.is_degraded()
+    }
+
+    /// Forgets the persistence failures that made the cache degraded.
+    ///
+    /// See [`DistributedHashTable::resume_persistence`](crate::DistributedHashTable::resume_persistence).
+    #[cfg(feature = "persistence")]
+    pub fn resume_persistence(&mut self) {
+        self.core.resume_persistence();
+    }
+
+    /// Fails if the cache is degraded and rejects writes.
+    ///
+    /// See [`DistributedHashTable::check_writable`](crate::DistributedHashTable::check_writable).
+    #[cfg(feature = "persistence")]
+    pub fn check_writable(&self) -> Result<(), CacheError> {
+        self.core.check_writable()
+    }
+
+    /// Applies the operations logged at `path` in order.
+    ///
+    /// See [`DistributedHashTable::replay`](crate::DistributedHashTable::replay).
+    /// Logs written by the text caches replay as their UTF-8 bytes.
+    #[cfg(feature = "persistence")]
+    pub fn replay(&mut self, path: impl AsRef<Path>) -> io::Result<usize> {
+        self.core.replay(path.as_ref())
+    }
+
+    /// Makes this cache the primary of the replica at the other end of
+    /// `transport`.
+    ///
+    /// See [`DistributedHashTable::add_replica`](crate::DistributedHashTable::add_replica).
+    #[cfg(feature = "cluster")]
+    pub fn add_replica(&mut self, transport: impl Transport + 'static) {
+        self.core.add_replica(Box::new(transport));
+    }
+
+    /// Returns how many replicas are still connected.
+    #[cfg(feature = "cluster")]
+    pub fn replica_count(&self) -> usize {
+        self.core.replicas()
+    }
+
+    /// Applies the writes a primary streamed through `transport` and
+    /// acknowledges them.
+    ///
+    /// See [`DistributedHashTable::apply_replicated`](crate::DistributedHashTable::apply_replicated).
+    /// Writes from a text cache apply as their UTF-8 bytes.
+    #[cfg(feature = "cluster")]
+    pub fn apply_replicated(&mut self, transport: &mut impl Transport, timeout: Duration) -> io::Result<usize> {
+        self.core.apply_replicated(transport, timeout)
+    }
+
+    /// Returns a Merkle tree over the live entries, spread over `buckets`
+    /// buckets by key hash.
+    ///
+    /// See [`DistributedHashTable::merkle_digest`](crate::DistributedHashTable::merkle_digest).
+    #[cfg(feature = "cluster")]
+    pub fn merkle_digest(&self, buckets: usize) -> MerkleDigest {
+        self.core.merkle_digest(buckets)
+    }
+
+    /// Returns the live entries in the `divergent` buckets, with their TTLs.
+    #[cfg(feature = "cluster")]
+    pub fn export_buckets(&self, divergent: &DivergentBuckets) -> BucketPatch {
+        self.core.export_buckets(divergent)
+    }
+
+    /// Makes the patch's buckets hold exactly the patch's entries.
+    ///
+    /// See [`DistributedHashTable::apply_bucket_patch`](crate::DistributedHashTable::apply_bucket_patch).
+    /// Patches from the text caches apply as their UTF-8 bytes.
+    #[cfg(feature = "cluster")]
+    pub fn apply_bucket_patch(&mut self, patch: &BucketPatch) -> Result<usize, CacheError> {
+        self.core.apply_bucket_patch(patch)
+    }
+
+    /// Starts counting accesses to find the `k` most accessed keys.
+    ///
+    /// See [`DistributedHashTable::track_hot_keys`](crate::DistributedHashTable::track_hot_keys).
+    #[cfg(feature = "probabilistic")]
+    pub fn track_hot_keys(&mut self, k: usize) {
+        self.core.track_hot_keys(k);
+    }
+
+    /// Stops tracking hot keys and discards the counts.
+    #[cfg(feature = "probabilistic")]
+    pub fn stop_tracking_hot_keys(&mut self) {
+        self.core.stop_tracking_hot_keys();
+    }
+
+    /// Returns up to `k` of the most accessed keys with their estimated
+    /// access counts, most accessed first, or nothing if tracking is off.
+    #[cfg(feature = "probabilistic")]
+    pub fn hot_keys(&self) -> Vec<(String, u64)> {
+        self.core.hot_keys()
+    }
+
+    /// Registers a callback fired whenever an entry leaves the cache.
+    ///
+    /// See [`DistributedHashTable::on_evict`](crate::DistributedHashTable::on_evict).
+    pub fn on_evict(&mut self, listener: impl FnMut(&str, &[u8], RemovalCause) + Send + 'static) {
+        self.core.on_evict(Box::new(listener));
+    }
+
+    /// Unregisters every `on_evict` listener.
+    pub fn remove_evict_listeners(&mut self) {
+        self.core.remove_evict_listeners();
+    }
+
+    /// Subscribes to changes of the keys matching a glob pattern.
+    ///
+    /// See [`DistributedHashTable::subscribe`](crate::DistributedHashTable::subscribe).
+    #[cfg(feature = "std")]
+    pub fn subscribe(&mut self, pattern: &str) -> Receiver<CacheEvent<Vec<u8>>> {
+        self.core.subscribe(pattern)
+    }
+
+    /// Returns up to `n` of the latest changes to `key`, newest first.
+    ///
+    /// See [`DistributedHashTable::history`](crate::DistributedHashTable::history).
+    #[cfg(feature = "std")]
+    pub fn history(&self, key: &str, n: usize) -> Vec<HistoryEntry<Vec<u8>>> {
+        self.core.history(key, n)
+    }
+}
+
+impl CacheBuilder<BytesCache> {
+    /// Creates the binary value cache.
+    pub fn build(self) -> BytesCache {
+        BytesCache::with_config(self.config)
+    }
+}
+
+impl CacheType for BytesCache {
+    type Value = Vec<u8>;
+}
+
+impl Default for BytesCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Two caches are equal when they hold the same live keys and values.
+impl PartialEq for BytesCache {
+    fn eq(&self, other: &Self) -> bool {
+        self.core.same_content(&other.core)
+    }
+}
+
+/// Inserts every pair with [`insert`](BytesCache::insert).
+impl<K: AsRef<str>, V: AsRef<[u8]>> Extend<(K, V)> for BytesCache {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        let iter = iter.into_iter();
+        self.reserve_capacity(iter.size_hint().0);
+        for (key, value) in iter {
+            self.insert(key.as_ref(), value.as_ref());
+        }
+    }
+}
+
+/// Builds a cache with default settings holding the pairs.
+impl<K: AsRef<str>, V: AsRef<[u8]>> FromIterator<(K, V)> for BytesCache {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut cache = Self::new();
+        cache.extend(iter);
+        cache
+    }
+}
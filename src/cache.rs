@@ -0,0 +1,109 @@
+use alloc::borrow::ToOwned;
+use alloc::boxed::Box;
+use core::hash::BuildHasher;
+use core::time::Duration;
+
+use crate::{BTreeCache, BytesCache, CacheStats, DistributedHashTable};
+
+/// The operations every cache type supports, so application code can be
+/// written once and run against any of them.
+///
+/// `K` is the borrowed key type and `V` the borrowed value type: `str` and
+/// `str` for [`DistributedHashTable`] and [`BTreeCache`], `str` and `[u8]`
+/// for [`BytesCache`]. The trait is object safe, so the backend can also be
+/// picked at runtime behind a `Box<dyn Cache>`.
+///
+/// The caches' own methods of the same names behave the same way; import
+/// the trait only where code should be generic.
+///
+/// # Examples
+///
+/// ```
+/// use spectra_cache::{BTreeCache, Cache, DistributedHashTable};
+///
+/// fn remember_login<C: Cache + ?Sized>(cache: &mut C, user: &str) {
+///     cache.insert(&format!("login:{}", user), "yes");
+/// }
+///
+/// let mut backends: Vec<Box<dyn Cache>> = vec![Box::new(DistributedHashTable::new()), Box::new(BTreeCache::new())];
+/// for cache in &mut backends {
+///     remember_login(cache.as_mut(), "ana");
+///     assert_eq!(cache.get("login:ana"), Some("yes"));
+///     assert_eq!(cache.stats().inserts, 1);
+/// }
+/// ```
+pub trait Cache<K: ?Sized = str, V: ?Sized + ToOwned = str> {
+    /// Returns the number of entries, including expired ones not removed yet.
+    fn size(&self) -> usize;
+
+    /// Returns the value stored under `key` if it is live.
+    fn get(&mut self, key: &K) -> Option<&V>;
+
+    /// Stores `value` under `key` with the cache's default TTL, if any.
+    fn insert(&mut self, key: &K, value: &V);
+
+    /// Stores `value` under `key`, expiring after `ttl`.
+    fn insert_with_ttl(&mut self, key: &K, value: &V, ttl: Duration);
+
+    /// Removes `key`, returning its value if it was live.
+    fn remove(&mut self, key: &K) -> Option<V::Owned>;
+
+    /// Returns true if a live entry is stored under `key`.
+    fn contains(&mut self, key: &K) -> bool;
+
+    /// Returns how long the entry under `key` has left, or `None` if it
+    /// doesn't expire or doesn't exist.
+    fn ttl(&self, key: &K) -> Option<Duration>;
+
+    /// Iterates over the live entries, in the cache's own order.
+    fn iter(&self) -> Box<dyn Iterator<Item = (&K, &V)> + '_>;
+
+    /// Returns a snapshot of the cache's statistics.
+    fn stats(&self) -> CacheStats;
+}
+
+macro_rules! impl_cache {
+    ([$($generics:tt)*] $cache:ty, $value:ty) => {
+        impl<$($generics)*> Cache<str, $value> for $cache {
+            fn size(&self) -> usize {
+                <$cache>::size(self)
+            }
+
+            fn get(&mut self, key: &str) -> Option<&$value> {
+                <$cache>::get(self, key)
+            }
+
+            fn insert(&mut self, key: &str, value: &$value) {
+                <$cache>::insert(self, key, value);
+            }
+
+            fn insert_with_ttl(&mut self, key: &str, value: &$value, ttl: Duration) {
+                <$cache>::insert_with_ttl(self, key, value, ttl);
+            }
+
+            fn remove(&mut self, key: &str) -> Option<<$value as ToOwned>::Owned> {
+                <$cache>::remove(self, key)
+            }
+
+            fn contains(&mut self, key: &str) -> bool {
+                <$cache>::contains_key(self, key)
+            }
+
+            fn ttl(&self, key: &str) -> Option<Duration> {
+                <$cache>::ttl(self, key)
+            }
+
+            fn iter(&self) -> Box<dyn Iterator<Item = (&str, &$value)> + '_> {
+                Box::new(<$cache>::iter(self).map(|(key, value, _)| (key.as_str(), value)))
+            }
+
+            fn stats(&self) -> CacheStats {
+                <$cache>::stats(self)
+            }
+        }
+    };
+}
+
+impl_cache!([S: BuildHasher + Clone + Default] DistributedHashTable<S>, str);
+impl_cache!([] BTreeCache, str);
+impl_cache!([] BytesCache, [u8]);
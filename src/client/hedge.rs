@@ -0,0 +1,198 @@
+use std::sync::{mpsc, Arc};
+use std::thread;
+use std::time::Duration;
+
+/// Hedged reads: if the primary target hasn't answered within a latency
+/// budget, the same read is issued to the next replica and the first
+/// successful answer wins.
+///
+/// Hedging trades a little extra load for a much better tail latency when
+/// a single node is transiently slow. Attempts run on their own threads;
+/// once an answer is returned the slower attempts are left to finish in
+/// the background and their results are discarded, so only use it for
+/// idempotent reads.
+///
+/// # Examples
+///
+/// ```
+/// use spectra_cache::HedgePolicy;
+/// use std::time::Duration;
+///
+/// let policy = HedgePolicy::new(Duration::from_millis(10));
+/// let value: Result<String, ()> = policy.execute(["primary", "replica"], |node| {
+///     if node == "primary" {
+///         // Nó primário lento demais
+///         std::thread::sleep(Duration::from_millis(200));
+///     }
+///     Ok(format!("from {}", node))
+/// });
+/// assert_eq!(value, Ok("from replica".to_string()));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HedgePolicy {
+    delay: Duration,
+    max_hedges: usize,
+}
+
+impl HedgePolicy {
+    /// Creates a policy that sends one hedged request after `delay`.
+    pub fn new(delay: Duration) -> Self {
+        Self { delay, max_hedges: 1 }
+    }
+
+    /// Sets how many replicas may be tried in addition to the primary.
+    pub fn with_max_hedges(mut self, max_hedges: usize) -> Self {
+        self.max_hedges = max_hedges;
+        self
+    }
+
+    /// Returns how long to wait for an answer before hedging.
+    pub fn delay(&self) -> Duration {
+        self.delay
+    }
+
+    /// Returns how many replicas may be tried in addition to the primary.
+    pub fn max_hedges(&self) -> usize {
+        self.max_hedges
+    }
+
+    /// Reads from `targets` in order, hedging while no answer arrives.
+    ///
+    /// The first target is the primary. A read against the next target is
+    /// started each time `delay` passes without an answer, or immediately
+    /// when a read fails, up to `max_hedges` extra reads. Returns the first
+    /// successful answer, or the last error once every started read failed.
+    ///
+    /// # Arguments
+    ///
+    /// * `targets` - The nodes able to serve the read, in preference order
+    /// * `read` - Performs the read against a single target
+    ///
+    /// # Panics
+    ///
+    /// Panics if `targets` is empty.
+    pub fn execute<N, T, E, F>(&self, targets: impl IntoIterator<Item = N>, read: F) -> Result<T, E>
+    where
+        N: Send + 'static,
+        T: Send + 'static,
+        E: Send + 'static,
+        F: Fn(N) -> Result<T, E> + Send + Sync + 'static,
+    {
+        let read = Arc::new(read);
+        let (sender, receiver) = mpsc::channel();
+        let mut targets = targets.into_iter().take(self.max_hedges.saturating_add(1));
+
+        let spawn = |target: N| {
+            let sender = sender.clone();
+            let read = Arc::clone(&read);
+            thread::spawn(move || {
+                // O receptor pode já ter desistido; o resultado é descartado
+                let _ = sender.send(read(target));
+            });
+        };
+
+        spawn(targets.next().expect("hedged read needs at least one target"));
+        let mut in_flight = 1;
+        let mut exhausted = false;
+        let mut last_error = None;
+
+        loop {
+            let outcome = if exhausted {
+                receiver.recv().ok()
+            } else {
+                match receiver.recv_timeout(self.delay) {
+                    Ok(outcome) => Some(outcome),
+                    Err(mpsc::RecvTimeoutError::Timeout) => None,
+                    Err(mpsc::RecvTimeoutError::Disconnected) => unreachable!("sender is still held"),
+                }
+            };
+
+            match outcome {
+                Some(Ok(value)) => return Ok(value),
+                Some(Err(error)) => {
+                    in_flight -= 1;
+                    last_error = Some(error);
+                }
+                None => {}
+            }
+
+            if !exhausted {
+                match targets.next() {
+                    Some(target) => {
+                        spawn(target);
+                        in_flight += 1;
+                    }
+                    None => exhausted = true,
+                }
+            }
+
+            if exhausted && in_flight == 0 {
+                return Err(last_error.expect("every read failed"));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// A fake node answering after `delay_ms` with `result`.
+    type Node = (u64, Result<&'static str, &'static str>);
+
+    fn read_counting(calls: &Arc<AtomicUsize>) -> impl Fn(Node) -> Result<&'static str, &'static str> + Send + Sync {
+        let calls = Arc::clone(calls);
+        move |(delay_ms, result)| {
+            calls.fetch_add(1, Ordering::SeqCst);
+            thread::sleep(Duration::from_millis(delay_ms));
+            result
+        }
+    }
+
+    #[test]
+    fn test_fast_primary_is_not_hedged() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let policy = HedgePolicy::new(Duration::from_millis(200));
+
+        let result = policy.execute(vec![(0, Ok("primary")), (0, Ok("replica"))], read_counting(&calls));
+
+        assert_eq!(result, Ok("primary"));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_slow_primary_is_hedged() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let policy = HedgePolicy::new(Duration::from_millis(10));
+
+        let result = policy.execute(vec![(500, Ok("primary")), (0, Ok("replica"))], read_counting(&calls));
+
+        assert_eq!(result, Ok("replica"));
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_failure_hedges_immediately() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let policy = HedgePolicy::new(Duration::from_secs(10));
+
+        let result = policy.execute(vec![(0, Err("down")), (0, Ok("replica"))], read_counting(&calls));
+
+        assert_eq!(result, Ok("replica"));
+    }
+
+    #[test]
+    fn test_all_failures_return_last_error() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let policy = HedgePolicy::new(Duration::from_millis(10)).with_max_hedges(2);
+
+        let result = policy.execute(
+            vec![(0, Err("first")), (30, Err("second")), (60, Err("third")), (0, Ok("never tried"))],
+            read_counting(&calls),
+        );
+
+        assert_eq!(result, Err("third"));
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+}
@@ -0,0 +1,7 @@
+//! Client-side policies for talking to remote cache nodes.
+
+mod hedge;
+mod retry;
+
+pub use hedge::HedgePolicy;
+pub use retry::{Idempotency, Jitter, RetryPolicy, RetryableError};
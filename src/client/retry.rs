@@ -0,0 +1,285 @@
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::io;
+use std::thread;
+use std::time::Duration;
+
+/// Classifies errors for [`RetryPolicy`].
+pub trait RetryableError {
+    /// Returns true if the failure is transient and the request may succeed
+    /// if sent again (timeouts, dropped connections, ...).
+    fn is_retryable(&self) -> bool;
+
+    /// Returns true if the server may have applied the request before the
+    /// failure was observed.
+    ///
+    /// Non-idempotent commands are only retried when this is false. The
+    /// default is the conservative answer.
+    fn may_have_been_applied(&self) -> bool {
+        true
+    }
+}
+
+impl RetryableError for io::Error {
+    fn is_retryable(&self) -> bool {
+        matches!(
+            self.kind(),
+            io::ErrorKind::TimedOut
+                | io::ErrorKind::WouldBlock
+                | io::ErrorKind::Interrupted
+                | io::ErrorKind::ConnectionRefused
+                | io::ErrorKind::ConnectionReset
+                | io::ErrorKind::ConnectionAborted
+                | io::ErrorKind::NotConnected
+                | io::ErrorKind::BrokenPipe
+                | io::ErrorKind::UnexpectedEof
+        )
+    }
+
+    fn may_have_been_applied(&self) -> bool {
+        // Conexão recusada significa que nada chegou ao servidor
+        !matches!(
+            self.kind(),
+            io::ErrorKind::ConnectionRefused | io::ErrorKind::NotConnected
+        )
+    }
+}
+
+/// Whether repeating a command can change its outcome.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Idempotency {
+    /// Safe to repeat: reads, plain overwrites, deletes.
+    Idempotent,
+    /// Repeating may apply it twice: increments, appends, pushes.
+    ///
+    /// Attach a write ID checked by a `ReplayGuard` on the server to make
+    /// such commands safe to retry.
+    NonIdempotent,
+}
+
+/// How the backoff delay is randomised.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Jitter {
+    /// Always wait exactly the computed backoff.
+    None,
+    /// Wait a random duration between zero and the computed backoff.
+    Full,
+    /// Wait half the computed backoff plus a random amount up to the other half.
+    Equal,
+}
+
+/// Retries failed client requests with exponential backoff and jitter.
+///
+/// # Examples
+///
+/// ```
+/// use spectra_cache::{Idempotency, RetryPolicy};
+/// use std::io;
+/// use std::time::Duration;
+///
+/// let policy = RetryPolicy::new(3).with_base_delay(Duration::from_millis(1));
+/// let mut calls = 0;
+/// let result = policy.run(Idempotency::Idempotent, |_attempt| {
+///     calls += 1;
+///     if calls < 3 {
+///         Err(io::Error::from(io::ErrorKind::TimedOut))
+///     } else {
+///         Ok("value")
+///     }
+/// });
+/// assert_eq!(result.unwrap(), "value");
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    base_delay: Duration,
+    max_delay: Duration,
+    multiplier: f64,
+    jitter: Jitter,
+}
+
+impl RetryPolicy {
+    /// Creates a policy making at most `max_attempts` attempts in total.
+    ///
+    /// Defaults to a 50 ms base delay doubling up to 2 s, with full jitter.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max_attempts` is zero.
+    pub fn new(max_attempts: u32) -> Self {
+        assert!(max_attempts > 0, "RetryPolicy needs at least one attempt");
+        Self {
+            max_attempts,
+            base_delay: Duration::from_millis(50),
+            max_delay: Duration::from_secs(2),
+            multiplier: 2.0,
+            jitter: Jitter::Full,
+        }
+    }
+
+    /// Creates a policy that never retries.
+    pub fn no_retry() -> Self {
+        Self::new(1)
+    }
+
+    /// Sets the backoff before the first retry.
+    pub fn with_base_delay(mut self, base_delay: Duration) -> Self {
+        self.base_delay = base_delay;
+        self
+    }
+
+    /// Sets the upper bound for a single backoff.
+    pub fn with_max_delay(mut self, max_delay: Duration) -> Self {
+        self.max_delay = max_delay;
+        self
+    }
+
+    /// Sets the factor the backoff grows by after every retry.
+    pub fn with_multiplier(mut self, multiplier: f64) -> Self {
+        self.multiplier = multiplier;
+        self
+    }
+
+    /// Sets how the backoff is randomised.
+    pub fn with_jitter(mut self, jitter: Jitter) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Returns the maximum number of attempts, including the first one.
+    pub fn max_attempts(&self) -> u32 {
+        self.max_attempts
+    }
+
+    /// Returns the backoff ceiling before retry number `retry` (starting at 1),
+    /// before jitter is applied.
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let factor = self.multiplier.powi(retry.saturating_sub(1) as i32);
+        let delay = self.base_delay.as_secs_f64() * factor;
+        Duration::from_secs_f64(delay.min(self.max_delay.as_secs_f64()))
+    }
+
+    /// Returns true if a request that failed with `error` should be sent again.
+    pub fn should_retry<E: RetryableError>(&self, error: &E, idempotency: Idempotency) -> bool {
+        error.is_retryable()
+            && (idempotency == Idempotency::Idempotent || !error.may_have_been_applied())
+    }
+
+    /// Runs `request` until it succeeds, fails permanently, or runs out of attempts.
+    ///
+    /// `request` receives the attempt number, starting at 1. The error of
+    /// the last attempt is returned on failure.
+    ///
+    /// # Arguments
+    ///
+    /// * `idempotency` - Whether the command may safely be applied twice
+    /// * `request` - Sends the command once
+    pub fn run<T, E, F>(&self, idempotency: Idempotency, mut request: F) -> Result<T, E>
+    where
+        E: RetryableError,
+        F: FnMut(u32) -> Result<T, E>,
+    {
+        let mut attempt = 1;
+        loop {
+            match request(attempt) {
+                Ok(value) => return Ok(value),
+                Err(error) => {
+                    if attempt >= self.max_attempts || !self.should_retry(&error, idempotency) {
+                        return Err(error);
+                    }
+                    thread::sleep(self.jittered(self.backoff(attempt)));
+                    attempt += 1;
+                }
+            }
+        }
+    }
+
+    fn jittered(&self, delay: Duration) -> Duration {
+        match self.jitter {
+            Jitter::None => delay,
+            Jitter::Full => delay.mul_f64(random_fraction()),
+            Jitter::Equal => delay / 2 + (delay / 2).mul_f64(random_fraction()),
+        }
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::new(3)
+    }
+}
+
+/// Returns a random number in `[0, 1)` using the standard library's
+/// randomly keyed hasher, which is plenty for spreading out retries.
+fn random_fraction() -> f64 {
+    let bits = RandomState::new().build_hasher().finish();
+    (bits >> 11) as f64 / (1u64 << 53) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fast_policy(max_attempts: u32) -> RetryPolicy {
+        RetryPolicy::new(max_attempts).with_base_delay(Duration::from_millis(1))
+    }
+
+    #[test]
+    fn test_backoff_grows_and_is_capped() {
+        let policy = RetryPolicy::new(10)
+            .with_base_delay(Duration::from_millis(100))
+            .with_max_delay(Duration::from_millis(500))
+            .with_jitter(Jitter::None);
+
+        assert_eq!(policy.backoff(1), Duration::from_millis(100));
+        assert_eq!(policy.backoff(2), Duration::from_millis(200));
+        assert_eq!(policy.backoff(3), Duration::from_millis(400));
+        assert_eq!(policy.backoff(4), Duration::from_millis(500));
+    }
+
+    #[test]
+    fn test_jitter_stays_within_bounds() {
+        let delay = Duration::from_millis(100);
+        for _ in 0..100 {
+            let full = RetryPolicy::new(1).with_jitter(Jitter::Full).jittered(delay);
+            assert!(full <= delay);
+            let equal = RetryPolicy::new(1).with_jitter(Jitter::Equal).jittered(delay);
+            assert!(equal >= delay / 2 && equal <= delay);
+        }
+    }
+
+    #[test]
+    fn test_gives_up_after_max_attempts() {
+        let mut attempts = Vec::new();
+        let result: Result<(), io::Error> = fast_policy(3).run(Idempotency::Idempotent, |attempt| {
+            attempts.push(attempt);
+            Err(io::ErrorKind::TimedOut.into())
+        });
+
+        assert!(result.is_err());
+        assert_eq!(attempts, vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_permanent_errors_are_not_retried() {
+        let mut calls = 0;
+        let result: Result<(), io::Error> = fast_policy(5).run(Idempotency::Idempotent, |_| {
+            calls += 1;
+            Err(io::ErrorKind::InvalidData.into())
+        });
+
+        assert!(result.is_err());
+        assert_eq!(calls, 1);
+    }
+
+    #[test]
+    fn test_non_idempotent_commands_only_retry_unsent_requests() {
+        let policy = fast_policy(5);
+        let timed_out = io::Error::from(io::ErrorKind::TimedOut);
+        let refused = io::Error::from(io::ErrorKind::ConnectionRefused);
+
+        assert!(policy.should_retry(&timed_out, Idempotency::Idempotent));
+        assert!(!policy.should_retry(&timed_out, Idempotency::NonIdempotent));
+        assert!(policy.should_retry(&refused, Idempotency::NonIdempotent));
+    }
+}
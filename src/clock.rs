@@ -0,0 +1,260 @@
+//! The time source behind TTLs, idle timeouts, rate meters and history
+//! timestamps.
+//!
+//! On native targets these are the `std::time` types. On `wasm32` targets
+//! `std::time::Instant::now()` and `SystemTime::now()` panic, so with the
+//! `wasm` feature they come from `web-time` instead, which reads
+//! `performance.now()` and `Date.now()` through `js-sys`. Public fields
+//! such as [`HistoryEntry::timestamp`](crate::HistoryEntry::timestamp)
+//! use these types, so code that has to build for both should name them
+//! through this module.
+//!
+//! A cache reads the time through a [`Clock`], [`SystemClock`] unless its
+//! builder was given another, so tests can move it with a [`ManualClock`].
+//!
+//! Without the `std` feature there is no system clock: [`Instant`] is a
+//! type of this module counting from an origin the clock picks, such as
+//! the boot of the device, and a cache built without a clock reads a
+//! [`ManualClock`] that never moves, so its entries never expire. Give the
+//! builder a clock reading the device's timer instead.
+
+use alloc::sync::Arc;
+use core::fmt;
+#[cfg(not(feature = "std"))]
+use core::ops::{Add, AddAssign, Sub, SubAssign};
+#[cfg(feature = "std")]
+use core::sync::atomic::AtomicU64;
+use core::sync::atomic::Ordering;
+#[cfg(not(feature = "std"))]
+use crate::sync::AtomicU64;
+use core::time::Duration;
+
+// Fora do wasm32 o web-time só reexporta o std::time, então isso é no-op
+#[cfg(all(feature = "std", not(feature = "wasm")))]
+pub use std::time::{Instant, SystemTime, UNIX_EPOCH};
+#[cfg(feature = "wasm")]
+pub use web_time::{Instant, SystemTime, UNIX_EPOCH};
+
+/// A point in time on a [`Clock`], counted from the clock's origin.
+///
+/// Stands in for `std::time::Instant` without the `std` feature, with the
+/// part of its API the caches need.
+///
+/// # Examples
+///
+/// ```
+/// use spectra_cache::clock::Instant;
+/// use core::time::Duration;
+///
+/// let boot = Instant::from_origin(Duration::ZERO);
+/// let later = boot + Duration::from_millis(1500);
+/// assert_eq!(later.duration_since(boot), Duration::from_millis(1500));
+/// assert_eq!(boot.saturating_duration_since(later), Duration::ZERO);
+/// ```
+#[cfg(not(feature = "std"))]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Instant(Duration);
+
+#[cfg(not(feature = "std"))]
+impl Instant {
+    /// Returns the instant `elapsed` after the clock's origin.
+    pub const fn from_origin(elapsed: Duration) -> Self {
+        Self(elapsed)
+    }
+
+    /// Returns the time elapsed from the clock's origin to this instant.
+    pub const fn since_origin(&self) -> Duration {
+        self.0
+    }
+
+    /// Returns the time elapsed from `earlier` to this instant, or zero if
+    /// `earlier` is later.
+    pub fn duration_since(&self, earlier: Instant) -> Duration {
+        self.saturating_duration_since(earlier)
+    }
+
+    /// Returns the time elapsed from `earlier` to this instant, or `None`
+    /// if `earlier` is later.
+    pub fn checked_duration_since(&self, earlier: Instant) -> Option<Duration> {
+        self.0.checked_sub(earlier.0)
+    }
+
+    /// Returns the time elapsed from `earlier` to this instant, or zero if
+    /// `earlier` is later.
+    pub fn saturating_duration_since(&self, earlier: Instant) -> Duration {
+        self.0.saturating_sub(earlier.0)
+    }
+
+    /// Returns the instant `duration` later, or `None` on overflow.
+    pub fn checked_add(&self, duration: Duration) -> Option<Instant> {
+        self.0.checked_add(duration).map(Self)
+    }
+
+    /// Returns the instant `duration` earlier, or `None` before the origin.
+    pub fn checked_sub(&self, duration: Duration) -> Option<Instant> {
+        self.0.checked_sub(duration).map(Self)
+    }
+}
+
+#[cfg(not(feature = "std"))]
+impl Add<Duration> for Instant {
+    type Output = Instant;
+
+    /// # Panics
+    ///
+    /// Panics on overflow, like `std::time::Instant`.
+    fn add(self, duration: Duration) -> Instant {
+        self.checked_add(duration).expect("overflow when adding duration to instant")
+    }
+}
+
+#[cfg(not(feature = "std"))]
+impl AddAssign<Duration> for Instant {
+    fn add_assign(&mut self, duration: Duration) {
+        *self = *self + duration;
+    }
+}
+
+#[cfg(not(feature = "std"))]
+impl Sub<Duration> for Instant {
+    type Output = Instant;
+
+    /// # Panics
+    ///
+    /// Panics if the result would be before the origin.
+    fn sub(self, duration: Duration) -> Instant {
+        self.checked_sub(duration).expect("overflow when subtracting duration from instant")
+    }
+}
+
+#[cfg(not(feature = "std"))]
+impl SubAssign<Duration> for Instant {
+    fn sub_assign(&mut self, duration: Duration) {
+        *self = *self - duration;
+    }
+}
+
+#[cfg(not(feature = "std"))]
+impl Sub<Instant> for Instant {
+    type Output = Duration;
+
+    fn sub(self, earlier: Instant) -> Duration {
+        self.duration_since(earlier)
+    }
+}
+
+/// The source of the current time a cache measures TTLs and idle timeouts
+/// against, set with [`CacheBuilder::clock`](crate::CacheBuilder::clock).
+///
+/// Caches read [`SystemClock`] unless built with another one. Tests can
+/// hand them a [`ManualClock`] to make entries expire without sleeping.
+/// Without `std`, implement it over the device's monotonic timer.
+pub trait Clock: Send + Sync {
+    /// Returns the current time.
+    fn now(&self) -> Instant;
+}
+
+/// The clock of the operating system, through [`Instant::now`].
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+#[cfg(feature = "std")]
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// A clock that only moves when [`advance`](Self::advance) is called.
+///
+/// Clones share the same time, so a test can keep one and give another to
+/// the cache. Without `std` it starts at the origin.
+///
+/// # Examples
+///
+/// ```
+/// use spectra_cache::clock::ManualClock;
+/// use spectra_cache::DistributedHashTable;
+/// use std::time::Duration;
+///
+/// let clock = ManualClock::new();
+/// let mut cache = DistributedHashTable::builder().clock(clock.clone()).build();
+/// cache.insert_with_ttl("session", "on", Duration::from_secs(60));
+///
+/// clock.advance(Duration::from_secs(59));
+/// assert_eq!(cache.get("session"), Some("on"));
+/// clock.advance(Duration::from_secs(2));
+/// assert_eq!(cache.get("session"), None);
+/// ```
+#[derive(Debug, Clone)]
+pub struct ManualClock {
+    start: Instant,
+    /// Nanoseconds advanced since `start`
+    elapsed: Arc<AtomicU64>,
+}
+
+impl ManualClock {
+    /// Creates a clock stopped at the current time.
+    pub fn new() -> Self {
+        #[cfg(feature = "std")]
+        let start = Instant::now();
+        #[cfg(not(feature = "std"))]
+        let start = Instant::default();
+        Self {
+            start,
+            elapsed: Arc::default(),
+        }
+    }
+
+    /// Moves the clock, and every clone of it, forward by `by`.
+    pub fn advance(&self, by: Duration) {
+        let nanos = u64::try_from(by.as_nanos()).unwrap_or(u64::MAX);
+        self.elapsed.fetch_add(nanos, Ordering::Relaxed);
+    }
+}
+
+impl Default for ManualClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Instant {
+        self.start + Duration::from_nanos(self.elapsed.load(Ordering::Relaxed))
+    }
+}
+
+/// The clock a cache reads, kept in its configuration.
+#[derive(Clone)]
+pub(crate) struct SharedClock(Arc<dyn Clock>);
+
+impl SharedClock {
+    pub(crate) fn new(clock: impl Clock + 'static) -> Self {
+        Self(Arc::new(clock))
+    }
+
+    pub(crate) fn now(&self) -> Instant {
+        self.0.now()
+    }
+}
+
+impl Default for SharedClock {
+    #[cfg(feature = "std")]
+    fn default() -> Self {
+        Self::new(SystemClock)
+    }
+
+    /// Without a system clock to read, time stands still.
+    #[cfg(not(feature = "std"))]
+    fn default() -> Self {
+        Self::new(ManualClock::new())
+    }
+}
+
+impl fmt::Debug for SharedClock {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SharedClock")
+    }
+}
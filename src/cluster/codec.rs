@@ -0,0 +1,147 @@
+//! Binary encoding shared by the cluster wire messages.
+//!
+//! Integers are big-endian; strings are a `u32` byte length followed by
+//! UTF-8 bytes; optional values are prefixed by a `0`/`1` presence byte.
+
+use std::io;
+use std::time::Duration;
+
+#[derive(Debug, Default)]
+pub(crate) struct Encoder {
+    buffer: Vec<u8>,
+}
+
+impl Encoder {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    pub(crate) fn u8(mut self, value: u8) -> Self {
+        self.buffer.push(value);
+        self
+    }
+
+    pub(crate) fn u64(mut self, value: u64) -> Self {
+        self.buffer.extend_from_slice(&value.to_be_bytes());
+        self
+    }
+
+    pub(crate) fn str(mut self, value: &str) -> Self {
+        self.buffer.extend_from_slice(&(value.len() as u32).to_be_bytes());
+        self.buffer.extend_from_slice(value.as_bytes());
+        self
+    }
+
+    pub(crate) fn opt_str(self, value: Option<&str>) -> Self {
+        match value {
+            Some(value) => self.u8(1).str(value),
+            None => self.u8(0),
+        }
+    }
+
+    pub(crate) fn opt_duration(self, value: Option<Duration>) -> Self {
+        match value {
+            Some(value) => self.u8(1).u64(value.as_millis() as u64),
+            None => self.u8(0),
+        }
+    }
+
+    pub(crate) fn finish(self) -> Vec<u8> {
+        self.buffer
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct Decoder<'a> {
+    bytes: &'a [u8],
+}
+
+pub(crate) fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+}
+
+impl<'a> Decoder<'a> {
+    pub(crate) fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes }
+    }
+
+    fn take(&mut self, len: usize) -> io::Result<&'a [u8]> {
+        if self.bytes.len() < len {
+            return Err(invalid("truncated message"));
+        }
+        let (head, tail) = self.bytes.split_at(len);
+        self.bytes = tail;
+        Ok(head)
+    }
+
+    pub(crate) fn u8(&mut self) -> io::Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    pub(crate) fn u64(&mut self) -> io::Result<u64> {
+        let bytes = self.take(8)?;
+        Ok(u64::from_be_bytes(bytes.try_into().expect("took 8 bytes")))
+    }
+
+    pub(crate) fn str(&mut self) -> io::Result<String> {
+        let len_bytes = self.take(4)?;
+        let len = u32::from_be_bytes(len_bytes.try_into().expect("took 4 bytes")) as usize;
+        let bytes = self.take(len)?;
+        String::from_utf8(bytes.to_vec()).map_err(|_| invalid("string is not valid UTF-8"))
+    }
+
+    pub(crate) fn opt_str(&mut self) -> io::Result<Option<String>> {
+        match self.u8()? {
+            0 => Ok(None),
+            1 => self.str().map(Some),
+            _ => Err(invalid("invalid presence flag")),
+        }
+    }
+
+    pub(crate) fn opt_duration(&mut self) -> io::Result<Option<Duration>> {
+        match self.u8()? {
+            0 => Ok(None),
+            1 => self.u64().map(|millis| Some(Duration::from_millis(millis))),
+            _ => Err(invalid("invalid presence flag")),
+        }
+    }
+
+    pub(crate) fn finish(self) -> io::Result<()> {
+        if self.bytes.is_empty() {
+            Ok(())
+        } else {
+            Err(invalid("trailing bytes after message"))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let bytes = Encoder::new()
+            .u8(7)
+            .u64(42)
+            .str("héllo")
+            .opt_str(None)
+            .opt_duration(Some(Duration::from_millis(1500)))
+            .finish();
+
+        let mut decoder = Decoder::new(&bytes);
+        assert_eq!(decoder.u8().unwrap(), 7);
+        assert_eq!(decoder.u64().unwrap(), 42);
+        assert_eq!(decoder.str().unwrap(), "héllo");
+        assert_eq!(decoder.opt_str().unwrap(), None);
+        assert_eq!(decoder.opt_duration().unwrap(), Some(Duration::from_millis(1500)));
+        decoder.finish().unwrap();
+    }
+
+    #[test]
+    fn test_truncated_input() {
+        let bytes = Encoder::new().str("hello").finish();
+        let mut decoder = Decoder::new(&bytes[..6]);
+        assert_eq!(decoder.str().unwrap_err().kind(), io::ErrorKind::InvalidData);
+    }
+}
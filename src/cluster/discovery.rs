@@ -0,0 +1,140 @@
+use std::io;
+use std::net::{SocketAddr, ToSocketAddrs};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::clock::Instant;
+
+/// A source of peer addresses for clients and cluster nodes.
+///
+/// Implemented by [`StaticSeeds`], [`DnsDiscovery`], and any
+/// `Fn() -> io::Result<Vec<SocketAddr>>` closure, which is the hook for
+/// integrations such as querying the Kubernetes API for pod addresses.
+pub trait Discovery: Send + Sync {
+    /// Returns the currently known peers.
+    fn discover(&self) -> io::Result<Vec<SocketAddr>>;
+}
+
+impl<F> Discovery for F
+where
+    F: Fn() -> io::Result<Vec<SocketAddr>> + Send + Sync,
+{
+    fn discover(&self) -> io::Result<Vec<SocketAddr>> {
+        self()
+    }
+}
+
+/// A fixed list of seed nodes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StaticSeeds {
+    seeds: Vec<SocketAddr>,
+}
+
+impl StaticSeeds {
+    /// Creates a discovery source that always returns `seeds`.
+    pub fn new(seeds: impl IntoIterator<Item = SocketAddr>) -> Self {
+        Self {
+            seeds: seeds.into_iter().collect(),
+        }
+    }
+}
+
+impl Discovery for StaticSeeds {
+    fn discover(&self) -> io::Result<Vec<SocketAddr>> {
+        Ok(self.seeds.clone())
+    }
+}
+
+/// Discovers peers by resolving a host name's A/AAAA records.
+///
+/// Results are cached and the name is re-resolved once `refresh_interval`
+/// has passed, so peers added behind a headless service show up without a
+/// restart. If re-resolution fails, the last successful answer is served.
+///
+/// SRV records are not supported: the standard library resolver only
+/// answers address queries, so every peer is assumed to listen on `port`.
+#[derive(Debug)]
+pub struct DnsDiscovery {
+    host: String,
+    port: u16,
+    refresh_interval: Duration,
+    cached: Mutex<Option<(Instant, Vec<SocketAddr>)>>,
+}
+
+impl DnsDiscovery {
+    /// Creates a discovery source for `host`, re-resolving every 30 seconds.
+    pub fn new(host: &str, port: u16) -> Self {
+        Self {
+            host: host.to_string(),
+            port,
+            refresh_interval: Duration::from_secs(30),
+            cached: Mutex::new(None),
+        }
+    }
+
+    /// Sets how long a resolution result is reused.
+    pub fn with_refresh_interval(mut self, refresh_interval: Duration) -> Self {
+        self.refresh_interval = refresh_interval;
+        self
+    }
+
+    fn resolve(&self) -> io::Result<Vec<SocketAddr>> {
+        let mut peers: Vec<SocketAddr> = (self.host.as_str(), self.port).to_socket_addrs()?.collect();
+        peers.sort();
+        peers.dedup();
+        Ok(peers)
+    }
+}
+
+impl Discovery for DnsDiscovery {
+    fn discover(&self) -> io::Result<Vec<SocketAddr>> {
+        let mut cached = self.cached.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        if let Some((resolved_at, peers)) = cached.as_ref() {
+            if resolved_at.elapsed() < self.refresh_interval {
+                return Ok(peers.clone());
+            }
+        }
+
+        match self.resolve() {
+            Ok(peers) => {
+                *cached = Some((Instant::now(), peers.clone()));
+                Ok(peers)
+            }
+            // Mantém os peers antigos se o DNS estiver fora do ar
+            Err(error) => match cached.as_ref() {
+                Some((_, peers)) => Ok(peers.clone()),
+                None => Err(error),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_static_seeds() {
+        let seeds = StaticSeeds::new(vec!["10.0.0.1:7000".parse().unwrap(), "10.0.0.2:7000".parse().unwrap()]);
+        assert_eq!(seeds.discover().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_dns_discovery_resolves_host() {
+        let discovery = DnsDiscovery::new("127.0.0.1", 7000);
+        assert_eq!(discovery.discover().unwrap(), vec!["127.0.0.1:7000".parse().unwrap()]);
+    }
+
+    #[test]
+    fn test_dns_discovery_reports_unresolvable_host() {
+        let discovery = DnsDiscovery::new("invalid host name", 7000);
+        assert!(discovery.discover().is_err());
+    }
+
+    #[test]
+    fn test_closure_discovery() {
+        let discovery = || Ok(vec!["192.168.0.10:7000".parse().unwrap()]);
+        let peers = Discovery::discover(&discovery).unwrap();
+        assert_eq!(peers.len(), 1);
+    }
+}
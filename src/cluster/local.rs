@@ -0,0 +1,454 @@
+use std::collections::HashMap;
+use std::io;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use crate::cluster::codec::{invalid, Decoder, Encoder};
+use crate::cluster::transport::{ChannelTransport, Transport};
+use crate::DistributedHashTable;
+
+/// How often idle node threads check whether they were asked to stop.
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// One side of a link in the simulated network.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Endpoint {
+    Client,
+    Node(usize),
+}
+
+/// Which endpoints can currently talk to each other.
+///
+/// Every endpoint belongs to a group; frames only flow within a group.
+#[derive(Debug, Default)]
+struct Network {
+    groups: Mutex<HashMap<Endpoint, u64>>,
+    next_group: AtomicU64,
+}
+
+impl Network {
+    fn groups(&self) -> MutexGuard<'_, HashMap<Endpoint, u64>> {
+        self.groups.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn reachable(&self, from: Endpoint, to: Endpoint) -> bool {
+        let groups = self.groups();
+        groups.get(&from).copied().unwrap_or(0) == groups.get(&to).copied().unwrap_or(0)
+    }
+
+    fn move_to_new_group(&self, endpoints: impl IntoIterator<Item = Endpoint>) {
+        let group = self.next_group.fetch_add(1, Ordering::Relaxed) + 1;
+        let mut groups = self.groups();
+        for endpoint in endpoints {
+            groups.insert(endpoint, group);
+        }
+    }
+
+    fn heal(&self) {
+        self.groups().clear();
+    }
+}
+
+/// A transport whose frames are silently dropped while its two ends are
+/// partitioned, like packets on a real network.
+#[derive(Debug)]
+struct SimulatedLink {
+    inner: ChannelTransport,
+    network: Arc<Network>,
+    local: Endpoint,
+    remote: Endpoint,
+}
+
+impl SimulatedLink {
+    fn pair(network: &Arc<Network>, a: Endpoint, b: Endpoint) -> (Self, Self) {
+        let (a_end, b_end) = ChannelTransport::pair();
+        (
+            Self {
+                inner: a_end,
+                network: Arc::clone(network),
+                local: a,
+                remote: b,
+            },
+            Self {
+                inner: b_end,
+                network: Arc::clone(network),
+                local: b,
+                remote: a,
+            },
+        )
+    }
+}
+
+impl Transport for SimulatedLink {
+    fn send(&mut self, frame: &[u8]) -> io::Result<()> {
+        if self.network.reachable(self.local, self.remote) {
+            self.inner.send(frame)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn recv(&mut self) -> io::Result<Vec<u8>> {
+        self.inner.recv()
+    }
+
+    fn recv_timeout(&mut self, timeout: Duration) -> io::Result<Option<Vec<u8>>> {
+        self.inner.recv_timeout(timeout)
+    }
+}
+
+/// A command sent by the harness to a node.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Request {
+    Get { key: String },
+    Insert { key: String, value: String, ttl: Option<Duration> },
+    Remove { key: String },
+}
+
+impl Request {
+    fn encode(&self, id: u64) -> Vec<u8> {
+        let encoder = Encoder::new().u64(id);
+        match self {
+            Request::Get { key } => encoder.u8(0).str(key),
+            Request::Insert { key, value, ttl } => encoder.u8(1).str(key).str(value).opt_duration(*ttl),
+            Request::Remove { key } => encoder.u8(2).str(key),
+        }
+        .finish()
+    }
+
+    fn decode(frame: &[u8]) -> io::Result<(u64, Self)> {
+        let mut decoder = Decoder::new(frame);
+        let id = decoder.u64()?;
+        let request = match decoder.u8()? {
+            0 => Request::Get { key: decoder.str()? },
+            1 => Request::Insert {
+                key: decoder.str()?,
+                value: decoder.str()?,
+                ttl: decoder.opt_duration()?,
+            },
+            2 => Request::Remove { key: decoder.str()? },
+            _ => return Err(invalid("unknown request type")),
+        };
+        decoder.finish()?;
+        Ok((id, request))
+    }
+
+    fn apply(self, table: &mut DistributedHashTable) -> Option<String> {
+        match self {
+            Request::Get { key } => table.get(&key).map(str::to_string),
+            Request::Insert { key, value, ttl } => {
+                match ttl {
+                    Some(ttl) => table.insert_with_ttl(&key, &value, ttl),
+                    None => table.insert(&key, &value),
+                }
+                None
+            }
+            Request::Remove { key } => table.remove(&key),
+        }
+    }
+}
+
+fn encode_response(id: u64, value: Option<&str>) -> Vec<u8> {
+    Encoder::new().u64(id).opt_str(value).finish()
+}
+
+fn decode_response(frame: &[u8]) -> io::Result<(u64, Option<String>)> {
+    let mut decoder = Decoder::new(frame);
+    let id = decoder.u64()?;
+    let value = decoder.opt_str()?;
+    decoder.finish()?;
+    Ok((id, value))
+}
+
+/// A running node: its data, its serving thread, and the harness's end of its link.
+#[derive(Debug)]
+struct LocalNode {
+    table: Arc<Mutex<DistributedHashTable>>,
+    link: SimulatedLink,
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl LocalNode {
+    fn start(id: usize, network: &Arc<Network>) -> Self {
+        let (client_end, mut node_end) = SimulatedLink::pair(network, Endpoint::Client, Endpoint::Node(id));
+        let table = Arc::new(Mutex::new(DistributedHashTable::new()));
+        let stop = Arc::new(AtomicBool::new(false));
+
+        let thread = {
+            let table = Arc::clone(&table);
+            let stop = Arc::clone(&stop);
+            thread::spawn(move || {
+                while !stop.load(Ordering::Relaxed) {
+                    let frame = match node_end.recv_timeout(POLL_INTERVAL) {
+                        Ok(Some(frame)) => frame,
+                        Ok(None) => continue,
+                        Err(_) => break,
+                    };
+                    // Frames malformados são descartados, como faria um nó real
+                    let Ok((id, request)) = Request::decode(&frame) else { continue };
+                    let value = {
+                        let mut table = table.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+                        request.apply(&mut table)
+                    };
+                    if node_end.send(&encode_response(id, value.as_deref())).is_err() {
+                        break;
+                    }
+                }
+            })
+        };
+
+        Self {
+            table,
+            link: client_end,
+            stop,
+            thread: Some(thread),
+        }
+    }
+
+    fn is_running(&self) -> bool {
+        self.thread.is_some()
+    }
+
+    fn shutdown(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// N cache nodes running inside one process over an in-memory network.
+///
+/// Each node is a `DistributedHashTable` served by its own thread and
+/// reached through a [`Transport`]. The harness can partition and heal the
+/// simulated network and crash or restart nodes, which makes it possible to
+/// test distributed behaviour deterministically in-process.
+///
+/// Requests to a node that is partitioned away from the harness or has
+/// crashed fail with a `TimedOut` or `NotConnected` error, just like they
+/// would against a remote node.
+///
+/// # Examples
+///
+/// ```
+/// use spectra_cache::LocalCluster;
+///
+/// let mut cluster = LocalCluster::new(3);
+/// cluster.insert(0, "user:1", "Ana").unwrap();
+/// assert_eq!(cluster.get(0, "user:1").unwrap(), Some("Ana".to_string()));
+///
+/// cluster.isolate(0);
+/// assert!(cluster.get(0, "user:1").is_err());
+///
+/// cluster.heal();
+/// assert_eq!(cluster.get(0, "user:1").unwrap(), Some("Ana".to_string()));
+/// ```
+#[derive(Debug)]
+pub struct LocalCluster {
+    network: Arc<Network>,
+    nodes: Vec<LocalNode>,
+    request_timeout: Duration,
+    next_request_id: u64,
+}
+
+impl LocalCluster {
+    /// Starts a cluster of `nodes` empty nodes, numbered from zero.
+    pub fn new(nodes: usize) -> Self {
+        let network = Arc::new(Network::default());
+        let nodes = (0..nodes).map(|id| LocalNode::start(id, &network)).collect();
+        Self {
+            network,
+            nodes,
+            request_timeout: Duration::from_millis(200),
+            next_request_id: 0,
+        }
+    }
+
+    /// Sets how long requests wait for a node to answer.
+    pub fn with_request_timeout(mut self, request_timeout: Duration) -> Self {
+        self.request_timeout = request_timeout;
+        self
+    }
+
+    /// Returns the number of nodes, running or crashed.
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Returns true if the cluster has no nodes.
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Reads `key` from `node`.
+    pub fn get(&mut self, node: usize, key: &str) -> io::Result<Option<String>> {
+        self.request(node, Request::Get { key: key.to_string() })
+    }
+
+    /// Writes `key` on `node`.
+    pub fn insert(&mut self, node: usize, key: &str, value: &str) -> io::Result<()> {
+        let request = Request::Insert {
+            key: key.to_string(),
+            value: value.to_string(),
+            ttl: None,
+        };
+        self.request(node, request).map(|_| ())
+    }
+
+    /// Writes `key` on `node` with a TTL.
+    pub fn insert_with_ttl(&mut self, node: usize, key: &str, value: &str, ttl: Duration) -> io::Result<()> {
+        let request = Request::Insert {
+            key: key.to_string(),
+            value: value.to_string(),
+            ttl: Some(ttl),
+        };
+        self.request(node, request).map(|_| ())
+    }
+
+    /// Removes `key` from `node`, returning the removed value.
+    pub fn remove(&mut self, node: usize, key: &str) -> io::Result<Option<String>> {
+        self.request(node, Request::Remove { key: key.to_string() })
+    }
+
+    /// Splits the network in two: the nodes in `side` on one side, every
+    /// other node and the harness on the other.
+    pub fn partition(&mut self, side: &[usize]) {
+        self.network.move_to_new_group(side.iter().map(|&node| Endpoint::Node(node)));
+    }
+
+    /// Cuts `node` off from every other node and from the harness.
+    pub fn isolate(&mut self, node: usize) {
+        self.partition(&[node]);
+    }
+
+    /// Removes every partition.
+    pub fn heal(&mut self) {
+        self.network.heal();
+    }
+
+    /// Returns true if `node` and the harness can currently exchange messages.
+    pub fn is_reachable(&self, node: usize) -> bool {
+        self.network.reachable(Endpoint::Client, Endpoint::Node(node))
+    }
+
+    /// Stops `node`, losing everything it stored.
+    pub fn crash(&mut self, node: usize) {
+        self.nodes[node].shutdown();
+    }
+
+    /// Starts a fresh, empty process for `node`, crashing it first if it is running.
+    pub fn restart(&mut self, node: usize) {
+        self.nodes[node].shutdown();
+        self.nodes[node] = LocalNode::start(node, &self.network);
+    }
+
+    /// Returns true unless `node` has crashed and not been restarted.
+    pub fn is_running(&self, node: usize) -> bool {
+        self.nodes[node].is_running()
+    }
+
+    /// Runs `f` directly against the table of `node`, bypassing the network.
+    ///
+    /// Meant for test assertions about a node's state; works even while the
+    /// node is partitioned.
+    pub fn inspect<R>(&self, node: usize, f: impl FnOnce(&mut DistributedHashTable) -> R) -> R {
+        let mut table = self.nodes[node].table.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        f(&mut table)
+    }
+
+    fn request(&mut self, node: usize, request: Request) -> io::Result<Option<String>> {
+        if !self.nodes[node].is_running() {
+            return Err(io::Error::new(io::ErrorKind::NotConnected, format!("node {} is down", node)));
+        }
+
+        self.next_request_id += 1;
+        let id = self.next_request_id;
+        let link = &mut self.nodes[node].link;
+        link.send(&request.encode(id))?;
+
+        loop {
+            let frame = link
+                .recv_timeout(self.request_timeout)?
+                .ok_or_else(|| io::Error::new(io::ErrorKind::TimedOut, format!("node {} did not answer", node)))?;
+            let (response_id, value) = decode_response(&frame)?;
+            // Respostas atrasadas de requisições anteriores são ignoradas
+            if response_id == id {
+                return Ok(value);
+            }
+        }
+    }
+}
+
+impl Drop for LocalCluster {
+    fn drop(&mut self) {
+        for node in &mut self.nodes {
+            node.shutdown();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cluster(nodes: usize) -> LocalCluster {
+        LocalCluster::new(nodes).with_request_timeout(Duration::from_millis(50))
+    }
+
+    #[test]
+    fn test_request_round_trip() {
+        let request = Request::Insert {
+            key: "k".to_string(),
+            value: "v".to_string(),
+            ttl: Some(Duration::from_millis(1500)),
+        };
+        assert_eq!(Request::decode(&request.encode(7)).unwrap(), (7, request));
+        assert!(Request::decode(&[0, 0, 0, 0, 0, 0, 0, 1, 9]).is_err());
+    }
+
+    #[test]
+    fn test_nodes_are_independent() {
+        let mut cluster = cluster(2);
+        cluster.insert(0, "key", "zero").unwrap();
+
+        assert_eq!(cluster.get(0, "key").unwrap(), Some("zero".to_string()));
+        assert_eq!(cluster.get(1, "key").unwrap(), None);
+        assert_eq!(cluster.remove(0, "key").unwrap(), Some("zero".to_string()));
+        assert_eq!(cluster.inspect(0, |table| table.size()), 0);
+    }
+
+    #[test]
+    fn test_partition_and_heal() {
+        let mut cluster = cluster(3);
+        cluster.insert(1, "key", "value").unwrap();
+
+        cluster.partition(&[1, 2]);
+        assert!(!cluster.is_reachable(1));
+        assert!(cluster.is_reachable(0));
+        assert_eq!(cluster.get(1, "key").unwrap_err().kind(), io::ErrorKind::TimedOut);
+
+        // A escrita foi descartada pela rede, então o nó não a viu
+        let _ = cluster.insert(2, "lost", "value");
+        assert!(!cluster.inspect(2, |table| table.contains_key("lost")));
+
+        cluster.heal();
+        assert_eq!(cluster.get(1, "key").unwrap(), Some("value".to_string()));
+    }
+
+    #[test]
+    fn test_crash_and_restart_lose_data() {
+        let mut cluster = cluster(2);
+        cluster.insert(0, "key", "value").unwrap();
+
+        cluster.crash(0);
+        assert!(!cluster.is_running(0));
+        assert_eq!(cluster.get(0, "key").unwrap_err().kind(), io::ErrorKind::NotConnected);
+
+        cluster.restart(0);
+        assert!(cluster.is_running(0));
+        assert_eq!(cluster.get(0, "key").unwrap(), None);
+    }
+}
@@ -0,0 +1,31 @@
+//! Building blocks for running several cache nodes together.
+//!
+//! The hash ring and its hash are always built, since `ShardedCache` and
+//! `ConcurrentCache` spread keys with them inside a single process;
+//! everything that talks to other nodes needs the `cluster` feature.
+
+#[cfg(feature = "cluster")]
+mod codec;
+#[cfg(feature = "cluster")]
+mod discovery;
+#[cfg(feature = "cluster")]
+mod local;
+#[cfg(feature = "cluster")]
+mod replication;
+mod ring;
+#[cfg(feature = "cluster")]
+mod transport;
+
+#[cfg(feature = "cluster")]
+pub use discovery::{Discovery, DnsDiscovery, StaticSeeds};
+#[cfg(feature = "cluster")]
+pub use local::LocalCluster;
+#[cfg(feature = "cluster")]
+pub(crate) use replication::{acknowledge, receive, Replicator};
+pub use ring::HashRing;
+#[cfg(feature = "core")]
+pub(crate) use ring::stable_hash;
+#[cfg(feature = "cluster")]
+pub(crate) use ring::stable_hash_bytes;
+#[cfg(feature = "cluster")]
+pub use transport::{ChannelTransport, TcpTransport, Transport, MAX_FRAME_SIZE};
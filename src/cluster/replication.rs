@@ -0,0 +1,246 @@
+//! Streaming a primary cache's writes to its replicas.
+//!
+//! Every write the primary makes becomes one frame: its sequence number as
+//! a big-endian `u64`, followed by the append-only log record for it. A
+//! replica answers each batch it applies with the sequence number of the
+//! last frame in it, as a bare big-endian `u64`.
+
+use std::fmt;
+use std::io;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, PoisonError};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use crate::aof::{self, Operation};
+use crate::cluster::codec::invalid;
+use crate::cluster::transport::Transport;
+use crate::error::CacheError;
+use crate::value::CacheValue;
+
+/// How long a sender thread waits for writes before it checks for
+/// acknowledgements.
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// The replicas of a primary cache, each fed by its own sender thread so a
+/// slow replica doesn't slow down the cache.
+pub(crate) struct Replicator {
+    links: Vec<Link>,
+    /// Sequence number of the last write sent
+    sequence: AtomicU64,
+    progress: Arc<Progress>,
+}
+
+struct Link {
+    queue: Sender<Vec<u8>>,
+    sender: JoinHandle<()>,
+}
+
+/// How far each replica got.
+#[derive(Default)]
+struct Progress {
+    replicas: Mutex<Vec<ReplicaState>>,
+    changed: Condvar,
+}
+
+#[derive(Clone, Copy)]
+struct ReplicaState {
+    /// Sequence number of the last write the replica acknowledged
+    acked: u64,
+    /// False once the link broke. Writes it acknowledged before still count.
+    connected: bool,
+}
+
+impl Replicator {
+    pub(crate) fn new() -> Self {
+        Self {
+            links: Vec::new(),
+            sequence: AtomicU64::new(0),
+            progress: Arc::default(),
+        }
+    }
+
+    /// Starts streaming to the replica at the other end of `transport`,
+    /// sending it the `initial` records first to bring it up to date.
+    pub(crate) fn add(&mut self, transport: Box<dyn Transport>, initial: Vec<Vec<u8>>) {
+        let (queue, frames) = mpsc::channel();
+        // O estado inicial reflete tudo até a última escrita enviada
+        let sequence = self.sequence.load(Ordering::Relaxed);
+        for record in initial {
+            let _ = queue.send(frame(sequence, &record));
+        }
+        let index = {
+            let mut replicas = lock(&self.progress.replicas);
+            replicas.push(ReplicaState { acked: 0, connected: true });
+            replicas.len() - 1
+        };
+        let progress = Arc::clone(&self.progress);
+        let sender = thread::Builder::new()
+            .name("spectra-cache-replicator".to_string())
+            .spawn(move || {
+                let _ = stream(transport, &frames, &progress, index);
+                lock(&progress.replicas)[index].connected = false;
+                progress.changed.notify_all();
+            })
+            .expect("failed to spawn the replication sender thread");
+        self.links.push(Link { queue, sender });
+    }
+
+    /// Sends `record` to every replica.
+    pub(crate) fn send(&self, record: &[u8]) {
+        let sequence = self.sequence.fetch_add(1, Ordering::Relaxed) + 1;
+        let frame = frame(sequence, record);
+        for link in &self.links {
+            // Uma réplica desconectada já foi marcada pelo seu thread
+            let _ = link.queue.send(frame.clone());
+        }
+    }
+
+    /// Returns how many replicas are still connected.
+    pub(crate) fn connected(&self) -> usize {
+        lock(&self.progress.replicas).iter().filter(|replica| replica.connected).count()
+    }
+
+    /// Waits up to `timeout` for `replicas` replicas to apply every write
+    /// sent so far.
+    pub(crate) fn wait_for(&self, replicas: usize, timeout: Duration) -> Result<(), CacheError> {
+        let target = self.sequence.load(Ordering::Relaxed);
+        let caught_up = |states: &[ReplicaState]| states.iter().filter(|state| state.acked >= target).count();
+        let connected = |states: &[ReplicaState]| states.iter().filter(|state| state.connected).count();
+        let states = lock(&self.progress.replicas);
+        let (states, _) = self
+            .progress
+            .changed
+            .wait_timeout_while(states, timeout, |states| caught_up(states) < replicas && connected(states) >= replicas)
+            .unwrap_or_else(PoisonError::into_inner);
+        if caught_up(&states) >= replicas {
+            Ok(())
+        } else if connected(&states) < replicas {
+            Err(CacheError::NotDurable { reason: "the cache has fewer replicas than required" })
+        } else {
+            Err(CacheError::NotDurable { reason: "timed out waiting for replicas" })
+        }
+    }
+}
+
+impl Drop for Replicator {
+    fn drop(&mut self) {
+        // Fechar a fila faz o thread enviar o que falta e encerrar o link
+        for Link { queue, sender } in self.links.drain(..) {
+            drop(queue);
+            let _ = sender.join();
+        }
+    }
+}
+
+impl fmt::Debug for Replicator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Replicator")
+            .field("replicas", &self.connected())
+            .field("sequence", &self.sequence.load(Ordering::Relaxed))
+            .finish()
+    }
+}
+
+/// Feeds one replica until the primary goes away or the link breaks.
+fn stream(mut transport: Box<dyn Transport>, frames: &Receiver<Vec<u8>>, progress: &Progress, index: usize) -> io::Result<()> {
+    loop {
+        match frames.recv_timeout(POLL_INTERVAL) {
+            Ok(frame) => {
+                transport.send(&frame)?;
+                for frame in frames.try_iter() {
+                    transport.send(&frame)?;
+                }
+            }
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => return Ok(()),
+        }
+        while let Some(ack) = transport.recv_timeout(Duration::ZERO)? {
+            let ack = <[u8; 8]>::try_from(ack.as_slice()).map_err(|_| invalid("malformed acknowledgement"))?;
+            lock(&progress.replicas)[index].acked = u64::from_be_bytes(ack);
+            progress.changed.notify_all();
+        }
+    }
+}
+
+fn frame(sequence: u64, record: &[u8]) -> Vec<u8> {
+    let mut frame = Vec::with_capacity(8 + record.len());
+    frame.extend_from_slice(&sequence.to_be_bytes());
+    frame.extend_from_slice(record);
+    frame
+}
+
+/// Receives the operations a primary sent, waiting up to `timeout` for the
+/// first one and then taking whatever else already arrived.
+///
+/// Returns them in order with the sequence number to acknowledge once they
+/// are applied, or `None` if nothing arrived.
+pub(crate) fn receive<V: CacheValue>(transport: &mut dyn Transport, timeout: Duration) -> io::Result<(Option<u64>, Vec<Operation<V>>)> {
+    let Some(first) = transport.recv_timeout(timeout)? else {
+        return Ok((None, Vec::new()));
+    };
+    let mut last = None;
+    let mut operations = Vec::new();
+    let mut next = Some(first);
+    while let Some(frame) = next {
+        let (sequence, record) = frame.split_first_chunk::<8>().ok_or_else(|| invalid("truncated frame"))?;
+        operations.push(aof::decode(record)?);
+        last = Some(u64::from_be_bytes(*sequence));
+        next = transport.recv_timeout(Duration::ZERO)?;
+    }
+    Ok((last, operations))
+}
+
+/// Tells the primary every frame up to `sequence` was applied.
+pub(crate) fn acknowledge(transport: &mut dyn Transport, sequence: u64) -> io::Result<()> {
+    transport.send(&sequence.to_be_bytes())
+}
+
+fn lock<T: ?Sized>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cluster::transport::ChannelTransport;
+    use crate::clock::Instant;
+    use crate::entry::CacheEntry;
+
+    #[test]
+    fn test_frames_arrive_in_order_and_are_acknowledged() {
+        let (primary_end, mut replica_end) = ChannelTransport::pair();
+        let mut replicator = Replicator::new();
+        replicator.add(Box::new(primary_end), vec![aof::clear_record()]);
+        let now = Instant::now();
+        replicator.send(&aof::set_record("k", &CacheEntry::<String>::new("k", "1", now), now));
+        replicator.send(&aof::delete_record("k"));
+        assert_eq!(replicator.connected(), 1);
+        assert_eq!(
+            replicator.wait_for(1, Duration::from_millis(30)),
+            Err(CacheError::NotDurable { reason: "timed out waiting for replicas" })
+        );
+
+        let mut operations = Vec::new();
+        let mut last = None;
+        while operations.len() < 3 {
+            let (sequence, received) = receive::<String>(&mut replica_end, Duration::from_secs(1)).unwrap();
+            operations.extend(received);
+            last = sequence.or(last);
+        }
+        assert_eq!(operations[0], Operation::Clear);
+        assert_eq!(operations[2], Operation::Delete { key: "k".to_string() });
+        assert_eq!(last, Some(2));
+        acknowledge(&mut replica_end, 2).unwrap();
+        assert_eq!(replicator.wait_for(1, Duration::from_secs(1)), Ok(()));
+
+        drop(replica_end);
+        replicator.send(&aof::clear_record());
+        assert_eq!(
+            replicator.wait_for(1, Duration::from_secs(1)),
+            Err(CacheError::NotDurable { reason: "the cache has fewer replicas than required" })
+        );
+        assert_eq!(replicator.connected(), 0);
+    }
+}
@@ -0,0 +1,223 @@
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+use core::hash::{Hash, Hasher};
+
+/// Virtual nodes placed on the ring for each node unless configured otherwise.
+const DEFAULT_VIRTUAL_NODES: usize = 160;
+
+/// A consistent hashing ring that maps keys to nodes.
+///
+/// Every node is placed on the ring at several pseudo-random points
+/// (virtual nodes), and a key belongs to the first point at or after its
+/// own hash. Adding or removing a node therefore only moves the keys that
+/// land next to that node's points, about `1/n` of them, instead of
+/// reshuffling everything as `hash % n` would.
+///
+/// Positions use a fixed hash function, so every process built for the same
+/// target agrees on where a key lives.
+///
+/// # Examples
+///
+/// ```
+/// use spectra_cache::HashRing;
+///
+/// let mut ring = HashRing::new();
+/// ring.add_node("cache-a");
+/// ring.add_node("cache-b");
+/// let owner = *ring.node_for("user:123").unwrap();
+/// assert!(owner == "cache-a" || owner == "cache-b");
+/// ```
+#[derive(Debug, Clone)]
+pub struct HashRing<N> {
+    virtual_nodes: usize,
+    points: BTreeMap<u64, N>,
+    nodes: Vec<N>,
+}
+
+impl<N: Hash + Eq + Clone> HashRing<N> {
+    /// Creates an empty ring with 160 virtual nodes per node.
+    pub fn new() -> Self {
+        Self::with_virtual_nodes(DEFAULT_VIRTUAL_NODES)
+    }
+
+    /// Creates an empty ring placing `virtual_nodes` points per node.
+    ///
+    /// More points spread keys more evenly at the cost of memory and a
+    /// slightly slower `add_node`. Panics if `virtual_nodes` is zero.
+    pub fn with_virtual_nodes(virtual_nodes: usize) -> Self {
+        assert!(virtual_nodes > 0, "a node needs at least one virtual node");
+        Self {
+            virtual_nodes,
+            points: BTreeMap::new(),
+            nodes: Vec::new(),
+        }
+    }
+
+    /// Adds a node to the ring. Returns false if it was already present.
+    pub fn add_node(&mut self, node: N) -> bool {
+        if self.nodes.contains(&node) {
+            return false;
+        }
+        for replica in 0..self.virtual_nodes {
+            // Em caso de colisão o primeiro nó a ocupar o ponto fica com ele
+            self.points.entry(point(&node, replica)).or_insert_with(|| node.clone());
+        }
+        self.nodes.push(node);
+        true
+    }
+
+    /// Removes a node from the ring. Returns false if it was not present.
+    pub fn remove_node(&mut self, node: &N) -> bool {
+        let Some(index) = self.nodes.iter().position(|n| n == node) else {
+            return false;
+        };
+        self.nodes.remove(index);
+        self.points.retain(|_, owner| owner != node);
+        true
+    }
+
+    /// Returns the node responsible for `key`, or `None` if the ring is empty.
+    pub fn node_for<K: Hash + ?Sized>(&self, key: &K) -> Option<&N> {
+        let hash = stable_hash(key);
+        self.points
+            .range(hash..)
+            .next()
+            .or_else(|| self.points.iter().next())
+            .map(|(_, node)| node)
+    }
+
+    /// Returns true if `node` is on the ring.
+    pub fn contains_node(&self, node: &N) -> bool {
+        self.nodes.contains(node)
+    }
+
+    /// Returns an iterator over the nodes, in the order they were added.
+    pub fn nodes(&self) -> impl Iterator<Item = &N> {
+        self.nodes.iter()
+    }
+
+    /// Returns the number of nodes on the ring.
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Returns true if the ring has no nodes.
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+}
+
+impl<N: Hash + Eq + Clone> Default for HashRing<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Position of one virtual node of `node` on the ring.
+fn point<N: Hash>(node: &N, replica: usize) -> u64 {
+    stable_hash(&(node, replica as u64))
+}
+
+/// Hashes `value` with FNV-1a followed by a SplitMix64 finalizer.
+///
+/// Unlike `DefaultHasher`, the result is not allowed to change between Rust
+/// releases, which matters when several processes must agree on the ring.
+pub(crate) fn stable_hash<T: Hash + ?Sized>(value: &T) -> u64 {
+    let mut hasher = Fnv1a(0xcbf2_9ce4_8422_2325);
+    value.hash(&mut hasher);
+    finalize(hasher.finish())
+}
+
+/// Hashes the concatenation of `parts` like [`stable_hash`], writing the
+/// bytes directly: `Hash` prefixes slices with their length as a `usize`,
+/// whose width depends on the platform.
+#[cfg(feature = "cluster")]
+pub(crate) fn stable_hash_bytes(parts: &[&[u8]]) -> u64 {
+    let mut hasher = Fnv1a(0xcbf2_9ce4_8422_2325);
+    for part in parts {
+        hasher.write(part);
+    }
+    finalize(hasher.finish())
+}
+
+/// SplitMix64's finalizer, which spreads FNV's weak low bits.
+fn finalize(mut z: u64) -> u64 {
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+struct Fnv1a(u64);
+
+impl Hasher for Fnv1a {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 ^= byte as u64;
+            self.0 = self.0.wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::format;
+
+    fn owners(ring: &HashRing<u32>, keys: usize) -> Vec<u32> {
+        (0..keys).map(|i| *ring.node_for(&format!("key:{}", i)).unwrap()).collect()
+    }
+
+    #[test]
+    fn test_empty_ring() {
+        let ring: HashRing<u32> = HashRing::new();
+        assert_eq!(ring.node_for("key"), None);
+        assert!(ring.is_empty());
+    }
+
+    #[test]
+    fn test_add_and_remove_nodes() {
+        let mut ring = HashRing::new();
+        assert!(ring.add_node(1));
+        assert!(!ring.add_node(1));
+        assert!(ring.add_node(2));
+        assert_eq!(ring.len(), 2);
+
+        assert!(ring.remove_node(&1));
+        assert!(!ring.remove_node(&1));
+        assert!(owners(&ring, 100).iter().all(|&owner| owner == 2));
+    }
+
+    #[test]
+    fn test_keys_spread_evenly() {
+        let mut ring = HashRing::new();
+        for node in 0..4 {
+            ring.add_node(node);
+        }
+
+        let mut counts = BTreeMap::new();
+        for owner in owners(&ring, 10_000) {
+            *counts.entry(owner).or_insert(0) += 1;
+        }
+        // Com 160 nós virtuais cada nó fica perto de 25% das chaves
+        assert!(counts.values().all(|&count| (1_500..3_500).contains(&count)));
+    }
+
+    #[test]
+    fn test_adding_a_node_moves_few_keys() {
+        let mut ring = HashRing::new();
+        for node in 0..4 {
+            ring.add_node(node);
+        }
+        let before = owners(&ring, 10_000);
+        ring.add_node(4);
+        let after = owners(&ring, 10_000);
+
+        let moved = before.iter().zip(&after).filter(|(b, a)| b != a).count();
+        assert!(moved < 3_000, "{} keys moved", moved);
+        assert!(before.iter().zip(&after).all(|(b, a)| b == a || *a == 4));
+    }
+}
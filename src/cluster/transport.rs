@@ -0,0 +1,223 @@
+use std::io::{self, Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::time::Duration;
+
+/// Largest frame accepted from the network, to bound memory use on corrupt input.
+pub const MAX_FRAME_SIZE: usize = 64 * 1024 * 1024;
+
+/// A bidirectional link carrying framed messages between two cache nodes.
+///
+/// Frames are opaque byte strings delivered whole and in order. The
+/// distributed components only talk through this trait, so they can run
+/// over TCP in production, over in-process channels in tests, or over any
+/// custom medium (shared memory, a message bus) that implements it.
+pub trait Transport: Send {
+    /// Sends one frame to the other end.
+    fn send(&mut self, frame: &[u8]) -> io::Result<()>;
+
+    /// Blocks until the next frame arrives.
+    ///
+    /// Returns an `UnexpectedEof` error once the other end has closed the link.
+    fn recv(&mut self) -> io::Result<Vec<u8>>;
+
+    /// Waits up to `timeout` for the next frame, returning None on timeout.
+    fn recv_timeout(&mut self, timeout: Duration) -> io::Result<Option<Vec<u8>>>;
+}
+
+/// A transport over a TCP stream using 4-byte big-endian length prefixes.
+#[derive(Debug)]
+pub struct TcpTransport {
+    stream: TcpStream,
+    buffer: Vec<u8>,
+}
+
+impl TcpTransport {
+    /// Connects to a remote node.
+    pub fn connect(addr: impl ToSocketAddrs) -> io::Result<Self> {
+        Ok(Self::from_stream(TcpStream::connect(addr)?))
+    }
+
+    /// Wraps an already connected stream, such as one returned by `TcpListener::accept`.
+    pub fn from_stream(stream: TcpStream) -> Self {
+        Self {
+            stream,
+            buffer: Vec::new(),
+        }
+    }
+
+    /// Removes and returns a complete frame from the read buffer, if one is there.
+    fn take_buffered_frame(&mut self) -> io::Result<Option<Vec<u8>>> {
+        if self.buffer.len() < 4 {
+            return Ok(None);
+        }
+        let len = u32::from_be_bytes([self.buffer[0], self.buffer[1], self.buffer[2], self.buffer[3]]) as usize;
+        if len > MAX_FRAME_SIZE {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("frame of {} bytes exceeds the {} byte limit", len, MAX_FRAME_SIZE),
+            ));
+        }
+        if self.buffer.len() < 4 + len {
+            return Ok(None);
+        }
+        let frame = self.buffer[4..4 + len].to_vec();
+        self.buffer.drain(..4 + len);
+        Ok(Some(frame))
+    }
+
+    /// Reads whatever is available into the buffer, honouring the stream's read timeout.
+    ///
+    /// Returns false if the read timed out.
+    fn fill_buffer(&mut self) -> io::Result<bool> {
+        let mut chunk = [0u8; 8192];
+        match self.stream.read(&mut chunk) {
+            Ok(0) => Err(io::Error::new(io::ErrorKind::UnexpectedEof, "connection closed")),
+            Ok(n) => {
+                self.buffer.extend_from_slice(&chunk[..n]);
+                Ok(true)
+            }
+            Err(error) if matches!(error.kind(), io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut) => Ok(false),
+            Err(error) => Err(error),
+        }
+    }
+}
+
+impl Transport for TcpTransport {
+    fn send(&mut self, frame: &[u8]) -> io::Result<()> {
+        let len = u32::try_from(frame.len())
+            .ok()
+            .filter(|&len| len as usize <= MAX_FRAME_SIZE)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "frame too large"))?;
+        self.stream.write_all(&len.to_be_bytes())?;
+        self.stream.write_all(frame)?;
+        self.stream.flush()
+    }
+
+    fn recv(&mut self) -> io::Result<Vec<u8>> {
+        self.stream.set_read_timeout(None)?;
+        loop {
+            if let Some(frame) = self.take_buffered_frame()? {
+                return Ok(frame);
+            }
+            self.fill_buffer()?;
+        }
+    }
+
+    fn recv_timeout(&mut self, timeout: Duration) -> io::Result<Option<Vec<u8>>> {
+        if let Some(frame) = self.take_buffered_frame()? {
+            return Ok(Some(frame));
+        }
+        // Um timeout zero desativaria o timeout do socket
+        self.stream.set_read_timeout(Some(timeout.max(Duration::from_millis(1))))?;
+        // Bytes parciais ficam no buffer para a próxima chamada
+        while self.fill_buffer()? {
+            if let Some(frame) = self.take_buffered_frame()? {
+                return Ok(Some(frame));
+            }
+        }
+        Ok(None)
+    }
+}
+
+/// An in-process transport backed by a pair of channels.
+///
+/// Created in connected pairs by [`ChannelTransport::pair`].
+#[derive(Debug)]
+pub struct ChannelTransport {
+    sender: Sender<Vec<u8>>,
+    receiver: Receiver<Vec<u8>>,
+}
+
+impl ChannelTransport {
+    /// Creates two connected ends: frames sent on one are received on the other.
+    pub fn pair() -> (Self, Self) {
+        let (left_sender, right_receiver) = mpsc::channel();
+        let (right_sender, left_receiver) = mpsc::channel();
+        (
+            Self {
+                sender: left_sender,
+                receiver: left_receiver,
+            },
+            Self {
+                sender: right_sender,
+                receiver: right_receiver,
+            },
+        )
+    }
+}
+
+fn closed() -> io::Error {
+    io::Error::new(io::ErrorKind::UnexpectedEof, "channel closed")
+}
+
+impl Transport for ChannelTransport {
+    fn send(&mut self, frame: &[u8]) -> io::Result<()> {
+        self.sender
+            .send(frame.to_vec())
+            .map_err(|_| io::Error::new(io::ErrorKind::BrokenPipe, "channel closed"))
+    }
+
+    fn recv(&mut self) -> io::Result<Vec<u8>> {
+        self.receiver.recv().map_err(|_| closed())
+    }
+
+    fn recv_timeout(&mut self, timeout: Duration) -> io::Result<Option<Vec<u8>>> {
+        match self.receiver.recv_timeout(timeout) {
+            Ok(frame) => Ok(Some(frame)),
+            Err(RecvTimeoutError::Timeout) => Ok(None),
+            Err(RecvTimeoutError::Disconnected) => Err(closed()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+    use std::thread;
+
+    fn exchange(mut a: impl Transport, mut b: impl Transport) {
+        a.send(b"hello").unwrap();
+        a.send(b"").unwrap();
+        assert_eq!(b.recv().unwrap(), b"hello");
+        assert_eq!(b.recv().unwrap(), b"");
+
+        b.send(b"reply").unwrap();
+        assert_eq!(a.recv_timeout(Duration::from_secs(1)).unwrap(), Some(b"reply".to_vec()));
+        assert_eq!(a.recv_timeout(Duration::from_millis(20)).unwrap(), None);
+
+        drop(b);
+        assert_eq!(a.recv().unwrap_err().kind(), io::ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn test_channel_transport() {
+        let (a, b) = ChannelTransport::pair();
+        exchange(a, b);
+    }
+
+    #[test]
+    fn test_tcp_transport() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let accept = thread::spawn(move || TcpTransport::from_stream(listener.accept().unwrap().0));
+
+        let a = TcpTransport::connect(addr).unwrap();
+        let b = accept.join().unwrap();
+        exchange(a, b);
+    }
+
+    #[test]
+    fn test_tcp_rejects_oversized_frames() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let accept = thread::spawn(move || TcpTransport::from_stream(listener.accept().unwrap().0));
+
+        let mut raw = TcpStream::connect(addr).unwrap();
+        let mut transport = accept.join().unwrap();
+        raw.write_all(&u32::MAX.to_be_bytes()).unwrap();
+
+        assert_eq!(transport.recv().unwrap_err().kind(), io::ErrorKind::InvalidData);
+    }
+}
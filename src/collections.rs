@@ -0,0 +1,29 @@
+//! The hash maps and sets behind the cache types: the standard library's
+//! with `std`, and `hashbrown`'s without it, since `alloc` has none.
+//!
+//! Without `std` there is no `RandomState` to seed them from, so they hash
+//! with [`DefaultBloomHasher`], whose keys are fixed. Maps are made with
+//! `default()` rather than `new()`, which only exists for `RandomState`.
+
+#[cfg(not(feature = "std"))]
+use crate::DefaultBloomHasher;
+
+#[cfg(feature = "std")]
+pub(crate) use std::collections::{hash_map, HashMap, HashSet};
+
+#[cfg(not(feature = "std"))]
+pub(crate) use hashbrown::hash_map;
+
+#[cfg(not(feature = "std"))]
+pub(crate) type HashMap<K, V, S = DefaultBloomHasher> = hashbrown::HashMap<K, V, S>;
+
+#[cfg(not(feature = "std"))]
+pub(crate) type HashSet<T, S = DefaultBloomHasher> = hashbrown::HashSet<T, S>;
+
+/// The hasher `DistributedHashTable` uses unless built with another.
+#[cfg(feature = "std")]
+pub(crate) type DefaultHashBuilder = std::collections::hash_map::RandomState;
+
+/// The hasher `DistributedHashTable` uses unless built with another.
+#[cfg(not(feature = "std"))]
+pub(crate) type DefaultHashBuilder = DefaultBloomHasher;
@@ -0,0 +1,162 @@
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use std::time::Duration;
+
+use crate::cluster::stable_hash;
+use crate::config::CacheBuilder;
+use crate::{CacheError, CacheStats, DistributedHashTable};
+
+/// Number of shards of a cache built without `shard_count()`.
+const DEFAULT_SHARD_COUNT: usize = 16;
+
+/// A thread-safe cache split into a power-of-two number of
+/// `DistributedHashTable` shards, each behind its own lock.
+///
+/// A key's shard is picked by the low bits of its hash, so operations on
+/// keys in different shards never wait for each other, and every shard has
+/// its own Bloom filter, eviction order and statistics. All methods take
+/// `&self`: share the cache between threads with an `Arc` or a scoped
+/// borrow. Values are returned as owned `String`s because a borrow cannot
+/// outlive the shard's lock.
+///
+/// # Examples
+///
+/// ```
+/// use spectra_cache::ConcurrentCache;
+/// use std::thread;
+///
+/// let cache = ConcurrentCache::builder().shard_count(8).build();
+/// thread::scope(|scope| {
+///     for worker in 0..4 {
+///         let cache = &cache;
+///         scope.spawn(move || {
+///             for i in 0..100 {
+///                 cache.insert(&format!("job:{}:{}", worker, i), "done");
+///             }
+///         });
+///     }
+/// });
+///
+/// assert_eq!(cache.size(), 400);
+/// assert_eq!(cache.get("job:3:99"), Some("done".to_string()));
+/// assert_eq!(cache.per_shard_stats().iter().map(|stats| stats.inserts).sum::<u64>(), 400);
+/// ```
+#[derive(Debug)]
+pub struct ConcurrentCache {
+    shards: Box<[Mutex<DistributedHashTable>]>,
+}
+
+impl ConcurrentCache {
+    /// Creates an empty cache with 16 shards.
+    pub fn new() -> Self {
+        Self::builder().build()
+    }
+
+    /// Returns a builder for a cache with non-default settings.
+    pub fn builder() -> CacheBuilder<Self> {
+        CacheBuilder::new()
+    }
+
+    /// Returns the number of shards.
+    pub fn shard_count(&self) -> usize {
+        self.shards.len()
+    }
+
+    /// Returns the index of the shard that owns `key`.
+    pub fn shard_for(&self, key: &str) -> usize {
+        stable_hash(key) as usize & (self.shards.len() - 1)
+    }
+
+    /// Returns the statistics of every shard, in shard order.
+    ///
+    /// Each shard is locked in turn, so the snapshots are not taken at the
+    /// same instant. A shard well above the others in `entries` or hits
+    /// points at a hot key.
+    pub fn per_shard_stats(&self) -> Vec<CacheStats> {
+        self.shards.iter().map(|shard| lock(shard).stats()).collect()
+    }
+
+    /// Returns the total number of entries across all shards.
+    pub fn size(&self) -> usize {
+        self.shards.iter().map(|shard| lock(shard).size()).sum()
+    }
+
+    /// Returns true if every shard is empty.
+    pub fn is_empty(&self) -> bool {
+        self.shards.iter().all(|shard| lock(shard).is_empty())
+    }
+
+    /// Inserts a key-value pair into the owning shard.
+    pub fn insert(&self, key: &str, value: &str) {
+        self.shard(key).insert(key, value);
+    }
+
+    /// Inserts a key-value pair with TTL into the owning shard.
+    pub fn insert_with_ttl(&self, key: &str, value: &str, ttl: Duration) {
+        self.shard(key).insert_with_ttl(key, value, ttl);
+    }
+
+    /// Retrieves a copy of the value stored under `key`.
+    pub fn get(&self, key: &str) -> Option<String> {
+        self.shard(key).get(key).map(str::to_string)
+    }
+
+    /// Removes a key-value pair, returning the removed value.
+    pub fn remove(&self, key: &str) -> Option<String> {
+        self.shard(key).remove(key)
+    }
+
+    /// Checks if a key exists and has not expired.
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.shard(key).contains_key(key)
+    }
+
+    /// Adds `delta` to the integer stored under `key` and returns the new value.
+    pub fn incr(&self, key: &str, delta: i64) -> Result<i64, CacheError> {
+        self.shard(key).incr(key, delta)
+    }
+
+    /// Removes all entries from every shard, one shard at a time.
+    pub fn clear(&self) {
+        for shard in self.shards.iter() {
+            lock(shard).clear();
+        }
+    }
+
+    fn shard(&self, key: &str) -> MutexGuard<'_, DistributedHashTable> {
+        lock(&self.shards[self.shard_for(key)])
+    }
+}
+
+impl Default for ConcurrentCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CacheBuilder<ConcurrentCache> {
+    /// Sets the number of shards. More shards mean less contention between
+    /// threads, at the cost of one table, lock and Bloom filter each.
+    ///
+    /// # Panics
+    ///
+    /// Panics unless `shards` is a power of two.
+    pub fn shard_count(mut self, shards: usize) -> Self {
+        assert!(shards.is_power_of_two(), "the shard count must be a power of two");
+        self.config.shard_count = Some(shards);
+        self
+    }
+
+    /// Creates the concurrent cache. Every shard gets the builder's
+    /// settings, so memory limits apply to each shard on its own.
+    pub fn build(self) -> ConcurrentCache {
+        let shards = self.config.shard_count.unwrap_or(DEFAULT_SHARD_COUNT);
+        let table = self.cast::<DistributedHashTable>();
+        let shards = (0..shards).map(|_| Mutex::new(table.clone().build())).collect();
+        ConcurrentCache { shards }
+    }
+}
+
+/// Locks a shard, carrying on if another thread panicked while holding it.
+fn lock(shard: &Mutex<DistributedHashTable>) -> MutexGuard<'_, DistributedHashTable> {
+    shard.lock().unwrap_or_else(PoisonError::into_inner)
+}
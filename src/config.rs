@@ -0,0 +1,496 @@
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::sync::Arc;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+use core::marker::PhantomData;
+use core::time::Duration;
+
+#[cfg(feature = "persistence")]
+use crate::aof::PersistenceFailure;
+use crate::clock::{Clock, SharedClock};
+use crate::eviction::{ErasedWeigher, Weigher};
+use crate::json;
+use crate::listener::ListenerOverflow;
+use crate::memory_limit::MemoryLimit;
+use crate::redact::Redactor;
+#[cfg(feature = "std")]
+use crate::store::{BackingStore, StoreConfig};
+use crate::value::CacheValue;
+
+/// Settings shared by every cache type, filled in by [`CacheBuilder`].
+#[derive(Debug, Clone, Default)]
+pub(crate) struct CacheConfig {
+    pub(crate) default_ttl: Option<Duration>,
+    pub(crate) soft_ttl: Option<Duration>,
+    pub(crate) bloom_audit: bool,
+    pub(crate) adaptive_bloom: bool,
+    pub(crate) bloom_rebuild: Option<f64>,
+    #[cfg(feature = "probabilistic")]
+    pub(crate) hot_keys: Option<usize>,
+    pub(crate) max_memory: Option<MemoryLimit>,
+    pub(crate) soft_memory_bytes: Option<usize>,
+    pub(crate) max_weight: Option<u64>,
+    pub(crate) weigher: Option<ErasedWeigher>,
+    #[cfg(feature = "std")]
+    pub(crate) listener_queue: Option<(usize, ListenerOverflow)>,
+    #[cfg(feature = "std")]
+    pub(crate) store: Option<StoreConfig>,
+    pub(crate) value_index: bool,
+    pub(crate) deterministic: bool,
+    #[cfg(feature = "std")]
+    pub(crate) shard_count: Option<usize>,
+    #[cfg(feature = "std")]
+    pub(crate) history_depth: Option<usize>,
+    pub(crate) redactor: Redactor,
+    pub(crate) clock: SharedClock,
+    #[cfg(feature = "persistence")]
+    pub(crate) persistence_failure: PersistenceFailure,
+}
+
+/// A cache type `CacheBuilder` can build, and the values it stores.
+pub trait CacheType {
+    type Value: CacheValue;
+}
+
+/// Builds a `DistributedHashTable` or a `BTreeCache` with non-default settings.
+///
+/// Obtained through `DistributedHashTable::builder()` or `BTreeCache::builder()`.
+///
+/// # Examples
+///
+/// ```
+/// use spectra_cache::DistributedHashTable;
+/// use std::time::Duration;
+///
+/// let mut cache = DistributedHashTable::builder()
+///     .default_ttl(Duration::from_secs(300))
+///     .build();
+/// cache.insert("user:123", "John Doe"); // expires after five minutes
+/// ```
+#[derive(Debug, Clone)]
+pub struct CacheBuilder<C> {
+    pub(crate) config: CacheConfig,
+    cache: PhantomData<fn() -> C>,
+}
+
+impl<C> CacheBuilder<C> {
+    pub(crate) fn new() -> Self {
+        Self {
+            config: CacheConfig::default(),
+            cache: PhantomData,
+        }
+    }
+
+    /// Keeps the settings for building a different cache type.
+    pub(crate) fn cast<D>(self) -> CacheBuilder<D> {
+        CacheBuilder {
+            config: self.config,
+            cache: PhantomData,
+        }
+    }
+
+    /// Sets the TTL applied by plain `insert()` calls.
+    ///
+    /// Without a default TTL, entries inserted through `insert()` never
+    /// expire. Entries inserted with an explicit TTL or idle timeout are not
+    /// affected.
+    pub fn default_ttl(mut self, ttl: Duration) -> Self {
+        self.config.default_ttl = Some(ttl);
+        self
+    }
+
+    /// Sets the clock TTLs, idle timeouts and soft TTLs are measured with.
+    ///
+    /// Defaults to [`SystemClock`](crate::clock::SystemClock), or without
+    /// `std` to a clock that never moves. A
+    /// [`ManualClock`](crate::clock::ManualClock) lets tests expire entries
+    /// without sleeping.
+    pub fn clock(mut self, clock: impl Clock + 'static) -> Self {
+        self.config.clock = SharedClock::new(clock);
+        self
+    }
+
+    /// Sets how long after being written an entry counts as stale.
+    ///
+    /// A stale entry is still served by `get()` until its hard TTL runs
+    /// out; `get_stale()` returns it flagged, so the caller can refresh it
+    /// while serving the old value. Set it below the TTL, so entries are
+    /// refreshed before they expire.
+    pub fn soft_ttl(mut self, ttl: Duration) -> Self {
+        self.config.soft_ttl = Some(ttl);
+        self
+    }
+
+    /// Caps the approximate memory used by keys and values.
+    ///
+    /// Whenever a write pushes `memory_usage()` over `bytes`, the least
+    /// recently used entries are evicted until the cache fits again. Without
+    /// a cap the cache grows without bound.
+    pub fn max_memory_bytes(self, bytes: usize) -> Self {
+        self.max_memory(MemoryLimit::Bytes(bytes))
+    }
+
+    /// Caps the approximate memory used by keys and values, possibly
+    /// relative to the memory of the container.
+    ///
+    /// `MemoryLimit::Percent` is resolved against the cgroup memory limit
+    /// (or the machine's memory outside a container) and revalidated every
+    /// 30 seconds. If the available memory can't be detected, a relative
+    /// limit does not evict anything.
+    pub fn max_memory(mut self, limit: MemoryLimit) -> Self {
+        self.config.max_memory = Some(limit);
+        self
+    }
+
+    /// Caps the total weight of the entries.
+    ///
+    /// Whenever a write pushes the total over `weight`, the least recently
+    /// used entries are evicted until it fits again, like with
+    /// `max_memory_bytes`; both limits can be set. Entries weigh 1 each
+    /// unless the cache has a [`weigher`](Self::weigher), so without one
+    /// this caps the number of entries.
+    pub fn max_weight(mut self, weight: u64) -> Self {
+        self.config.max_weight = Some(weight);
+        self
+    }
+
+    /// Sets a soft memory watermark below `max_memory_bytes`.
+    ///
+    /// Once `memory_usage()` exceeds `bytes`, every write also evicts up to
+    /// two least recently used entries, so the cache drifts back under the
+    /// watermark gradually instead of hitting the hard limit and evicting in
+    /// a burst. These early evictions are counted in
+    /// `CacheStats::proactive_evictions` and reported to `on_evict`
+    /// listeners as `RemovalCause::Evicted`.
+    pub fn soft_memory_bytes(mut self, bytes: usize) -> Self {
+        self.config.soft_memory_bytes = Some(bytes);
+        self
+    }
+
+    /// Runs `on_evict` listeners on a dispatcher thread, fed by a queue of
+    /// at most `capacity` notifications, instead of inline.
+    ///
+    /// Inline listeners run while the cache is mutably borrowed. That is
+    /// harmless for a cache owned by one thread, but once the cache sits
+    /// behind a lock (a `Mutex`, an `AsyncCache`) a listener that locks it
+    /// again deadlocks. Queued listeners only start after the triggering
+    /// write has returned and the lock was released, so they may call back
+    /// into the cache freely. Writes never wait for listeners: when the
+    /// queue is full, `overflow` decides which notification is dropped.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is zero.
+    #[cfg(feature = "std")]
+    pub fn listener_queue(mut self, capacity: usize, overflow: ListenerOverflow) -> Self {
+        assert!(capacity > 0, "listener queue capacity must be non-zero");
+        self.config.listener_queue = Some((capacity, overflow));
+        self
+    }
+
+    /// Maintains an inverted index from values to the keys holding them.
+    ///
+    /// Makes `keys_with_value()` and `contains_value()` O(1) instead of a
+    /// scan over every entry, at the cost of hashing each value on write
+    /// and keeping a second copy of every key.
+    pub fn index_values(mut self) -> Self {
+        self.config.value_index = true;
+        self
+    }
+
+    /// Makes bulk operations visit keys in sorted order, so runs that make
+    /// the same calls see the same results on every run and platform.
+    ///
+    /// `clear()`, `migrate()` and namespace clears then notify listeners in
+    /// key order, and a new append-only log, a new replica's initial sync,
+    /// bucket patches and snapshots list entries in key order, so they come
+    /// out byte for byte the same. Eviction is already deterministic: it
+    /// picks the least recently used entry, and expirations due at the same
+    /// instant go in write order. Iterators such as `iter()` still follow
+    /// the map's own order. Meant for tests and simulations; sorting costs
+    /// O(n log n) per bulk operation.
+    pub fn deterministic(mut self) -> Self {
+        self.config.deterministic = true;
+        self
+    }
+
+    /// Turns on the debug history: the cache remembers the last `depth`
+    /// changes to every key, with when they happened and who made them,
+    /// for `history()` to return.
+    ///
+    /// Meant for diagnosing who overwrote an entry and when. Every write
+    /// then copies its value into the history, and the history of a key is
+    /// kept after the key leaves the cache, so it grows with the number of
+    /// distinct keys ever written. Histories aren't cloned with the cache.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `depth` is zero.
+    #[cfg(feature = "std")]
+    pub fn history(mut self, depth: usize) -> Self {
+        assert!(depth > 0, "history depth must be non-zero");
+        self.config.history_depth = Some(depth);
+        self
+    }
+
+    /// Rewrites the values `redactor` has rules for wherever the cache shows
+    /// them to people: its `Debug` output and `history()`.
+    ///
+    /// Reads, `dump()`, snapshots and the append-only log still see the
+    /// real values.
+    pub fn redact(mut self, redactor: Redactor) -> Self {
+        self.config.redactor = redactor;
+        self
+    }
+
+    /// Starts the cache with Bloom filter audit mode turned on.
+    pub fn bloom_audit(mut self, enabled: bool) -> Self {
+        self.config.bloom_audit = enabled;
+        self
+    }
+
+    /// Lets the cache skip the Bloom filter while it isn't paying for
+    /// itself.
+    ///
+    /// The filter spares a map lookup for every absent key it rejects but
+    /// costs a probe on every lookup, which is a loss when nearly every
+    /// lookup hits. In adaptive mode the cache times both on a sample of
+    /// lookups, stops probing the filter when the lookups it saves cost
+    /// less than the probes, and probes again once misses rise. The filter
+    /// is kept up to date either way, and lookup results never change.
+    /// Reads through `&self` take a lock to record their lookup, as with
+    /// auditing. Ignored while Bloom filter audit mode is on. See `bloom_bypassed()`.
+    pub fn adaptive_bloom(mut self) -> Self {
+        self.config.adaptive_bloom = true;
+        self
+    }
+
+    /// Rebuilds the Bloom filter from the stored keys once more than
+    /// `stale_fraction` of the keys it holds have left the cache.
+    ///
+    /// A Bloom filter can't forget a key, so under heavy churn it keeps
+    /// answering "maybe" for removed, expired and evicted keys, and keeps
+    /// growing to hold them. With this set, the write that pushes the share
+    /// of departed keys over `stale_fraction` first replaces the filter
+    /// with one holding only the keys still stored. Small filters are left
+    /// alone, since they cost little either way. Rebuilds are counted in
+    /// `CacheStats::bloom_rebuilds`; `rebuild_bloom_filter()` forces one.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `stale_fraction` is not strictly between 0 and 1.
+    pub fn bloom_rebuild(mut self, stale_fraction: f64) -> Self {
+        assert!(
+            stale_fraction > 0.0 && stale_fraction < 1.0,
+            "stale fraction must be between 0 and 1"
+        );
+        self.config.bloom_rebuild = Some(stale_fraction);
+        self
+    }
+
+    /// Picks what the cache does once its persistence keeps failing.
+    ///
+    /// The cache counts as degraded after three failures in a row of the
+    /// append-only log, or of `save_snapshot()`. It then either goes on in
+    /// memory only, the default, or rejects writes; see
+    /// [`PersistenceFailure`]. Writes made before the third failure are
+    /// applied either way.
+    #[cfg(feature = "persistence")]
+    pub fn on_persistence_failure(mut self, policy: PersistenceFailure) -> Self {
+        self.config.persistence_failure = policy;
+        self
+    }
+
+    /// Starts the cache tracking its `k` most accessed keys, reported by
+    /// `hot_keys()`.
+    #[cfg(feature = "probabilistic")]
+    pub fn track_hot_keys(mut self, k: usize) -> Self {
+        self.config.hot_keys = Some(k);
+        self
+    }
+}
+
+impl<C: CacheType> CacheBuilder<C> {
+    /// Weighs every entry with `weigher` for [`max_weight`](Self::max_weight).
+    ///
+    /// The weight can stand for anything the caller wants to budget, such
+    /// as the cost of recomputing a value or the memory it points to
+    /// outside the cache. It must depend only on the key and the value,
+    /// since the weigher is asked again when an entry leaves. An entry
+    /// weighing more than the whole budget is evicted right away.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use spectra_cache::DistributedHashTable;
+    ///
+    /// let mut cache = DistributedHashTable::builder()
+    ///     .weigher(|_key, value: &str| value.len() as u64)
+    ///     .max_weight(10)
+    ///     .build();
+    /// cache.insert("a", "12345");
+    /// cache.insert("b", "1234");
+    /// assert_eq!(cache.total_weight(), 9);
+    ///
+    /// cache.insert("c", "12");
+    /// assert!(!cache.contains_key("a"));
+    /// assert_eq!(cache.total_weight(), 6);
+    /// ```
+    pub fn weigher(
+        mut self,
+        weigher: impl Fn(&str, &<C::Value as CacheValue>::Ref) -> u64 + Send + Sync + 'static,
+    ) -> Self {
+        let weigher: Weigher<<C::Value as CacheValue>::Ref> = Arc::new(weigher);
+        self.config.weigher = Some(Arc::new(weigher));
+        self
+    }
+
+    /// Puts the cache in front of `store`, writing every change through to it.
+    ///
+    /// `get_with()` loads keys missing from the cache from the store; `get()`
+    /// reads through a shared reference and only sees the cache. Inserts,
+    /// updates and removals reach the store before the cache call returns;
+    /// failed store calls are counted in `CacheStats::store_errors`, and the
+    /// cache itself is still updated.
+    #[cfg(feature = "std")]
+    pub fn write_through(mut self, store: impl BackingStore<<C::Value as CacheValue>::Ref> + 'static) -> Self {
+        self.config.store = Some(StoreConfig::new(store, None));
+        self
+    }
+
+    /// Puts the cache in front of `store`, writing changes back in batches.
+    ///
+    /// Like [`write_through`](Self::write_through), but writes and removals
+    /// are collected and sent to the store by a background thread every
+    /// `flush_interval`, keeping only the latest change per key. Pending
+    /// changes are also written by `flush()` and when the cache is dropped.
+    /// A failed store call is counted in `CacheStats::store_errors` and
+    /// retried on the next flush.
+    #[cfg(feature = "std")]
+    pub fn write_back(
+        mut self,
+        store: impl BackingStore<<C::Value as CacheValue>::Ref> + 'static,
+        flush_interval: Duration,
+    ) -> Self {
+        self.config.store = Some(StoreConfig::new(store, Some(flush_interval)));
+        self
+    }
+}
+
+/// The settings a cache is actually running with, returned by `config()`.
+///
+/// Reflects defaults, runtime toggles such as Bloom filter audit mode, and
+/// relative memory limits resolved to the byte budget last enforced,
+/// so operators can confirm what a live instance does rather than what it
+/// was configured with.
+///
+/// # Examples
+///
+/// ```
+/// use spectra_cache::DistributedHashTable;
+///
+/// let cache = DistributedHashTable::builder().max_memory_bytes(1 << 20).build();
+/// let config = cache.config();
+/// assert_eq!(config.max_memory_bytes, Some(1 << 20));
+/// assert!(config.to_json().contains("\"max_memory_bytes\":1048576"));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct EffectiveConfig {
+    /// TTL applied by plain `insert()` calls
+    pub default_ttl: Option<Duration>,
+    /// Age after which `get_stale()` flags an entry as stale
+    pub soft_ttl: Option<Duration>,
+    /// The memory limit as configured, possibly relative
+    pub max_memory: Option<MemoryLimit>,
+    /// The memory limit in bytes currently enforced
+    pub max_memory_bytes: Option<usize>,
+    /// The soft memory watermark in bytes
+    pub soft_memory_bytes: Option<usize>,
+    /// The cap on the total weight of the entries
+    pub max_weight: Option<u64>,
+    /// Whether Bloom filter audit mode is on
+    pub bloom_audit: bool,
+    /// Whether the Bloom filter may be bypassed when it isn't paying for itself
+    pub adaptive_bloom: bool,
+    /// Capacity and overflow policy of the listener queue, if listeners are queued
+    pub listener_queue: Option<(usize, ListenerOverflow)>,
+    /// Whether values are indexed for `keys_with_value()`
+    pub value_index: bool,
+    /// `"write-through"` or `"write-back"` if the cache has a backing store
+    pub backing_store: Option<&'static str>,
+    /// How often write-back changes are flushed
+    pub write_back_interval: Option<Duration>,
+}
+
+impl EffectiveConfig {
+    /// Returns every setting as a name and a display value, in a stable
+    /// order. Unset options are reported as `"none"`, durations in
+    /// milliseconds.
+    pub fn settings(&self) -> Vec<(&'static str, String)> {
+        fn show<T: ToString>(value: Option<T>) -> String {
+            value.map_or_else(|| "none".to_string(), |value| value.to_string())
+        }
+        let millis = |duration: Option<Duration>| show(duration.map(|duration| duration.as_millis()));
+        let flag = |enabled: bool| if enabled { "yes" } else { "no" }.to_string();
+        vec![
+            ("default-ttl-ms", millis(self.default_ttl)),
+            ("soft-ttl-ms", millis(self.soft_ttl)),
+            ("max-memory", show(self.max_memory)),
+            ("max-memory-bytes", show(self.max_memory_bytes)),
+            ("soft-memory-bytes", show(self.soft_memory_bytes)),
+            ("max-weight", show(self.max_weight)),
+            ("bloom-audit", flag(self.bloom_audit)),
+            ("adaptive-bloom", flag(self.adaptive_bloom)),
+            ("listener-queue-capacity", show(self.listener_queue.map(|(capacity, _)| capacity))),
+            ("listener-overflow", show(self.listener_queue.map(|(_, overflow)| overflow))),
+            ("value-index", flag(self.value_index)),
+            ("backing-store", show(self.backing_store)),
+            ("write-back-interval-ms", millis(self.write_back_interval)),
+        ]
+    }
+
+    /// Serializes the configuration as a single-line JSON object.
+    ///
+    /// Field names are the struct's, durations are in milliseconds, the
+    /// memory limit and listener overflow are strings, and unset options
+    /// are `null`.
+    pub fn to_json(&self) -> String {
+        fn number<T: ToString>(value: Option<T>) -> String {
+            value.map_or_else(|| "null".to_string(), |value| value.to_string())
+        }
+        let millis = |duration: Option<Duration>| number(duration.map(|duration| duration.as_millis()));
+        let max_memory = self.max_memory.map(|limit| limit.to_string());
+        let overflow = self.listener_queue.map(|(_, overflow)| overflow.to_string());
+        format!(
+            concat!(
+                "{{\"default_ttl_ms\":{},\"soft_ttl_ms\":{},\"max_memory\":{},\"max_memory_bytes\":{},",
+                "\"soft_memory_bytes\":{},\"max_weight\":{},\"bloom_audit\":{},\"adaptive_bloom\":{},\"listener_queue_capacity\":{},",
+                "\"listener_overflow\":{},\"value_index\":{},\"backing_store\":{},\"write_back_interval_ms\":{}}}"
+            ),
+            millis(self.default_ttl),
+            millis(self.soft_ttl),
+            json::quote_opt(max_memory.as_deref()),
+            number(self.max_memory_bytes),
+            number(self.soft_memory_bytes),
+            number(self.max_weight),
+            self.bloom_audit,
+            self.adaptive_bloom,
+            number(self.listener_queue.map(|(capacity, _)| capacity)),
+            json::quote_opt(overflow.as_deref()),
+            self.value_index,
+            json::quote_opt(self.backing_store),
+            millis(self.write_back_interval),
+        )
+    }
+}
+
+impl fmt::Display for ListenerOverflow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ListenerOverflow::DropNewest => "drop-newest",
+            ListenerOverflow::DropOldest => "drop-oldest",
+        })
+    }
+}
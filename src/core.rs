@@ -0,0 +1,1815 @@
+use alloc::boxed::Box;
+use alloc::collections::{btree_map, BTreeMap, BinaryHeap};
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use alloc::format;
+use core::cmp::Reverse;
+use core::fmt;
+use core::hash::BuildHasher;
+#[cfg(feature = "cluster")]
+use core::iter;
+#[cfg(feature = "persistence")]
+use core::mem;
+#[cfg(feature = "persistence")]
+use core::sync::atomic::{AtomicU64, Ordering};
+use core::time::Duration;
+#[cfg(feature = "persistence")]
+use std::fs::{self, File};
+#[cfg(feature = "persistence")]
+use std::io::{self, BufReader, BufWriter};
+#[cfg(feature = "persistence")]
+use std::path::Path;
+#[cfg(feature = "std")]
+use std::sync::mpsc::Receiver;
+
+#[cfg(feature = "persistence")]
+use crate::aof::{self, AppendLog, FsyncPolicy, Operation, PersistenceFailure};
+use crate::audit::{AuditAction, AuditContext, AuditLog};
+#[cfg(feature = "std")]
+use crate::audit::AuditSink;
+use crate::bloom::{BloomAudit, BloomBypass, DefaultBloomHasher, ScalableBloomFilter};
+use crate::clock::{Instant, SharedClock};
+#[cfg(feature = "persistence")]
+use crate::clock::SystemTime;
+#[cfg(feature = "cluster")]
+use crate::cluster::{self, Transport};
+use crate::cluster::stable_hash;
+use crate::collections::{hash_map, HashMap};
+use crate::config::{CacheConfig, EffectiveConfig};
+use crate::dump;
+use crate::entry::{CacheEntry, EntryMetadata};
+use crate::entry_api::{self, Entry, Slot};
+use crate::error::CacheError;
+use crate::eviction::{self, EvictionIndex};
+use crate::glob::Glob;
+#[cfg(feature = "std")]
+use crate::history::HistoryEntry;
+use crate::listener::{Listener, RemovalCause, RemovalListeners};
+use crate::memory_limit::MemoryBudget;
+use crate::options::{GetOptions, MaybeStale};
+#[cfg(feature = "cluster")]
+use crate::merkle::{BucketPatch, DivergentBuckets, MerkleDigest};
+use crate::read_buffer::ReadBuffer;
+#[cfg(feature = "probabilistic")]
+use crate::sketch::HotKeys;
+#[cfg(feature = "persistence")]
+use crate::snapshot;
+use crate::stats::{CacheStats, StatsRecorder};
+#[cfg(feature = "std")]
+use crate::store::Durability;
+use crate::store::WriteStore;
+#[cfg(feature = "std")]
+use crate::subscription::CacheEvent;
+use crate::sync::Lock;
+use crate::transaction::{Transaction, Write};
+use crate::value::CacheValue;
+
+/// A page of `scan()` results and the cursor of the next page.
+pub(crate) type ScanPage<'a, V> = (Vec<(&'a String, &'a <V as CacheValue>::Ref)>, Option<String>);
+
+/// Entries taken out of a cache with their metadata, for `migrate()`.
+pub(crate) type TakenEntries<V> = Vec<(String, CacheEntry<V>)>;
+
+/// Storage backend shared by the cache implementations.
+///
+/// `DistributedHashTable` stores its entries in a `HashMap` and `BTreeCache`
+/// in a `BTreeMap`; everything else (expiration, Bloom filter bookkeeping,
+/// auditing) lives in `CacheCore` and is written once against this trait.
+pub(crate) trait EntryMap: Default {
+    type Value: CacheValue;
+    /// The hasher of the cache's Bloom filter
+    type Hasher: BuildHasher + Clone + Default;
+    type Iter<'a>: Iterator<Item = (&'a String, &'a CacheEntry<Self::Value>)>
+    where
+        Self: 'a;
+    type ValuesMut<'a>: Iterator<Item = &'a mut CacheEntry<Self::Value>>
+    where
+        Self: 'a;
+
+    fn get(&self, key: &str) -> Option<&CacheEntry<Self::Value>>;
+    fn get_mut(&mut self, key: &str) -> Option<&mut CacheEntry<Self::Value>>;
+    fn insert(&mut self, key: String, entry: CacheEntry<Self::Value>) -> Option<CacheEntry<Self::Value>>;
+    fn remove(&mut self, key: &str) -> Option<CacheEntry<Self::Value>>;
+    fn len(&self) -> usize;
+    fn clear(&mut self);
+    fn iter(&self) -> Self::Iter<'_>;
+    fn values_mut(&mut self) -> Self::ValuesMut<'_>;
+    fn entry(&mut self, key: String) -> Slot<'_, Self::Value>;
+    /// Makes room for `additional` more entries, where the map supports it.
+    fn reserve(&mut self, additional: usize);
+    /// Returns how many entries fit before the map has to allocate.
+    fn capacity(&self) -> usize;
+}
+
+impl<V: CacheValue, S: BuildHasher + Clone + Default> EntryMap for HashMap<String, CacheEntry<V>, S> {
+    type Value = V;
+    type Hasher = S;
+    type Iter<'a> = hash_map::Iter<'a, String, CacheEntry<V>> where S: 'a;
+    type ValuesMut<'a> = hash_map::ValuesMut<'a, String, CacheEntry<V>> where S: 'a;
+
+    fn get(&self, key: &str) -> Option<&CacheEntry<V>> {
+        HashMap::get(self, key)
+    }
+
+    fn get_mut(&mut self, key: &str) -> Option<&mut CacheEntry<V>> {
+        HashMap::get_mut(self, key)
+    }
+
+    fn insert(&mut self, key: String, entry: CacheEntry<V>) -> Option<CacheEntry<V>> {
+        HashMap::insert(self, key, entry)
+    }
+
+    fn remove(&mut self, key: &str) -> Option<CacheEntry<V>> {
+        HashMap::remove(self, key)
+    }
+
+    fn len(&self) -> usize {
+        HashMap::len(self)
+    }
+
+    fn clear(&mut self) {
+        HashMap::clear(self)
+    }
+
+    fn iter(&self) -> Self::Iter<'_> {
+        HashMap::iter(self)
+    }
+
+    fn values_mut(&mut self) -> Self::ValuesMut<'_> {
+        HashMap::values_mut(self)
+    }
+
+    fn entry(&mut self, key: String) -> Slot<'_, V> {
+        match HashMap::entry(self, key) {
+            hash_map::Entry::Occupied(entry) => Slot::Occupied(Box::new(entry)),
+            hash_map::Entry::Vacant(entry) => Slot::Vacant(Box::new(entry)),
+        }
+    }
+
+    fn reserve(&mut self, additional: usize) {
+        HashMap::reserve(self, additional)
+    }
+
+    fn capacity(&self) -> usize {
+        HashMap::capacity(self)
+    }
+}
+
+impl<V: CacheValue> EntryMap for BTreeMap<String, CacheEntry<V>> {
+    type Value = V;
+    type Hasher = DefaultBloomHasher;
+    type Iter<'a> = btree_map::Iter<'a, String, CacheEntry<V>>;
+    type ValuesMut<'a> = btree_map::ValuesMut<'a, String, CacheEntry<V>>;
+
+    fn get(&self, key: &str) -> Option<&CacheEntry<V>> {
+        BTreeMap::get(self, key)
+    }
+
+    fn get_mut(&mut self, key: &str) -> Option<&mut CacheEntry<V>> {
+        BTreeMap::get_mut(self, key)
+    }
+
+    fn insert(&mut self, key: String, entry: CacheEntry<V>) -> Option<CacheEntry<V>> {
+        BTreeMap::insert(self, key, entry)
+    }
+
+    fn remove(&mut self, key: &str) -> Option<CacheEntry<V>> {
+        BTreeMap::remove(self, key)
+    }
+
+    fn len(&self) -> usize {
+        BTreeMap::len(self)
+    }
+
+    fn clear(&mut self) {
+        BTreeMap::clear(self)
+    }
+
+    fn iter(&self) -> Self::Iter<'_> {
+        BTreeMap::iter(self)
+    }
+
+    fn values_mut(&mut self) -> Self::ValuesMut<'_> {
+        BTreeMap::values_mut(self)
+    }
+
+    fn entry(&mut self, key: String) -> Slot<'_, V> {
+        match BTreeMap::entry(self, key) {
+            btree_map::Entry::Occupied(entry) => Slot::Occupied(Box::new(entry)),
+            btree_map::Entry::Vacant(entry) => Slot::Vacant(Box::new(entry)),
+        }
+    }
+
+    fn reserve(&mut self, _additional: usize) {
+        // BTreeMap aloca nó a nó; não há o que reservar
+    }
+
+    fn capacity(&self) -> usize {
+        BTreeMap::len(self)
+    }
+}
+
+/// How many entries a write may evict once the soft memory limit is exceeded.
+///
+/// Each write adds at most one entry, so evicting two lets the cache drift
+/// back under the soft limit without long pauses.
+const PROACTIVE_EVICTIONS_PER_WRITE: usize = 2;
+
+/// How many keys the Bloom filter is sized for before it has to grow.
+const BLOOM_INITIAL_CAPACITY: usize = 1000;
+
+/// The false positive rate the Bloom filter keeps as it grows.
+const BLOOM_FALSE_POSITIVE_RATE: f64 = 0.01;
+
+/// How many departed keys the Bloom filter must hold before a rebuild
+/// policy rebuilds it, so small caches don't rebuild on every write.
+const BLOOM_REBUILD_MIN_STALE: usize = 256;
+
+/// How many entries a warm-up loads between progress reports.
+const WARM_PROGRESS_INTERVAL: usize = 1024;
+
+/// How many persistence failures in a row make a cache degraded.
+#[cfg(feature = "persistence")]
+const PERSISTENCE_FAILURE_LIMIT: u64 = 3;
+
+/// The state and behaviour common to every cache type.
+pub(crate) struct CacheCore<M: EntryMap> {
+    pub(crate) entries: M,
+    config: CacheConfig,
+    bloom_filter: ScalableBloomFilter<M::Hasher>,
+    bloom_audit: Option<Lock<BloomAudit>>,
+    bloom_bypass: Option<Lock<BloomBypass>>,
+    #[cfg(feature = "probabilistic")]
+    hot_keys: Option<Lock<HotKeys>>,
+    stats: StatsRecorder,
+    audit_log: AuditLog,
+    eviction: EvictionIndex<M::Value>,
+    listeners: RemovalListeners<M::Value>,
+    memory_budget: Option<MemoryBudget>,
+    store: WriteStore<M::Value>,
+    reads: ReadBuffer,
+    /// `save_snapshot()` failures since the last snapshot that was written
+    #[cfg(feature = "persistence")]
+    snapshot_failures: AtomicU64,
+}
+
+impl<M: EntryMap> CacheCore<M> {
+    pub(crate) fn new() -> Self {
+        Self::with_config(CacheConfig::default())
+    }
+
+    pub(crate) fn with_config(config: CacheConfig) -> Self {
+        let now = config.clock.now();
+        let bloom_audit = config.bloom_audit.then(Lock::default);
+        let bloom_bypass = config.adaptive_bloom.then(Lock::default);
+        #[cfg(feature = "probabilistic")]
+        let hot_keys = config.hot_keys.map(|k| Lock::new(HotKeys::new(k)));
+        let memory_budget = config.max_memory.map(|limit| MemoryBudget::new(limit, now));
+        let listeners = Self::listeners_for(&config);
+        let store = Self::store_for(&config);
+        let eviction = EvictionIndex::new(config.value_index, config.weigher.as_ref(), config.clock.clone());
+        Self {
+            entries: M::default(),
+            config,
+            bloom_filter: ScalableBloomFilter::with_hasher(BLOOM_INITIAL_CAPACITY, BLOOM_FALSE_POSITIVE_RATE, M::Hasher::default()),
+            bloom_audit,
+            bloom_bypass,
+            #[cfg(feature = "probabilistic")]
+            hot_keys,
+            stats: StatsRecorder::new(now),
+            audit_log: AuditLog::default(),
+            eviction,
+            listeners,
+            memory_budget,
+            store,
+            reads: ReadBuffer::default(),
+            #[cfg(feature = "persistence")]
+            snapshot_failures: AtomicU64::new(0),
+        }
+    }
+
+    #[cfg(feature = "std")]
+    fn listeners_for(config: &CacheConfig) -> RemovalListeners<M::Value> {
+        let listeners = match config.listener_queue {
+            Some((capacity, overflow)) => RemovalListeners::queued(capacity, overflow),
+            None => RemovalListeners::default(),
+        };
+        match config.history_depth {
+            Some(depth) => listeners.with_history(depth),
+            None => listeners,
+        }
+    }
+
+    #[cfg(not(feature = "std"))]
+    fn listeners_for(_config: &CacheConfig) -> RemovalListeners<M::Value> {
+        RemovalListeners::default()
+    }
+
+    #[cfg(feature = "std")]
+    fn store_for(config: &CacheConfig) -> WriteStore<M::Value> {
+        WriteStore::from_config(config.store.as_ref())
+    }
+
+    #[cfg(not(feature = "std"))]
+    fn store_for(_config: &CacheConfig) -> WriteStore<M::Value> {
+        WriteStore::default()
+    }
+
+    pub(crate) fn size(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.entries.len() == 0
+    }
+
+    /// Inserts `value` with the configured default TTL, if any.
+    pub(crate) fn insert(&mut self, key: &str, value: &<M::Value as CacheValue>::Ref) {
+        // Uma escrita recusada só aparece em `rejected_writes`
+        let _ = self.try_insert(key, value);
+    }
+
+    /// Inserts `value` like `insert`, failing if the write is refused.
+    pub(crate) fn try_insert(&mut self, key: &str, value: &<M::Value as CacheValue>::Ref) -> Result<(), CacheError> {
+        let entry = CacheEntry::with_ttl(key, value, self.config.default_ttl, self.now());
+        self.try_insert_entry(key, entry)
+    }
+
+    /// Inserts an owned value with the default TTL, without copying it.
+    #[cfg(feature = "std")]
+    pub(crate) fn insert_value(&mut self, key: &str, value: M::Value) {
+        let entry = CacheEntry::from_value(value, self.config.default_ttl, self.now());
+        self.insert_entry(key, entry);
+    }
+
+    /// Inserts `value` with the default TTL, carrying `tags`.
+    pub(crate) fn insert_with_tags(&mut self, key: &str, value: &<M::Value as CacheValue>::Ref, tags: &[&str]) {
+        if self.try_insert(key, value).is_err() {
+            return;
+        }
+        // A própria inserção pode ter despejado a entrada
+        if self.entries.get(key).is_some() {
+            self.eviction.tag(key, tags);
+        }
+    }
+
+    pub(crate) fn insert_entry(&mut self, key: &str, entry: CacheEntry<M::Value>) {
+        let _ = self.try_insert_entry(key, entry);
+    }
+
+    pub(crate) fn try_insert_entry(&mut self, key: &str, entry: CacheEntry<M::Value>) -> Result<(), CacheError> {
+        self.begin_write()?;
+        self.write_entry(key, entry);
+        Ok(())
+    }
+
+    /// Stores `entry` once the write was allowed.
+    fn write_entry(&mut self, key: &str, mut entry: CacheEntry<M::Value>) {
+        self.apply_reads();
+        self.maybe_rebuild_bloom_filter();
+        self.record_access(key);
+        self.store.written(key, &entry, self.now());
+        self.eviction.admit(key, &mut entry);
+        let mut updated = false;
+        if let Some(replaced) = self.entries.insert(key.to_string(), entry) {
+            self.eviction.release(key, &replaced);
+            updated = !replaced.is_expired(self.now());
+            let cause = if updated {
+                RemovalCause::Replaced
+            } else {
+                RemovalCause::Expired
+            };
+            self.listeners.notify(key, replaced.value(), cause);
+        }
+        if !self.listeners.is_empty() {
+            if let Some(stored) = self.entries.get(key) {
+                self.listeners.written(key, stored.value(), updated);
+            }
+        }
+        self.bloom_filter.insert(key);
+        self.stats.record_insert();
+        self.enforce_memory_limit();
+    }
+
+    /// Removes the live entries `filter` accepts and hands them over with
+    /// their metadata, for `migrate()`.
+    ///
+    /// Listeners see the entries as removed, but the backing store keeps
+    /// them: they move to another cache rather than go away.
+    pub(crate) fn take_matching(
+        &mut self,
+        filter: &mut dyn FnMut(&str, &<M::Value as CacheValue>::Ref) -> bool,
+    ) -> Result<TakenEntries<M::Value>, CacheError> {
+        self.begin_write()?;
+        let keys: Vec<String> = self
+            .live_in_walk_order()
+            .into_iter()
+            .filter(|(key, entry)| filter(key, entry.value()))
+            .map(|(key, _)| key.clone())
+            .collect();
+        let mut taken = Vec::with_capacity(keys.len());
+        for key in keys {
+            if let Some(entry) = self.entries.remove(&key) {
+                self.eviction.release(&key, &entry);
+                self.stats.record_removal();
+                self.store.dropped(&key);
+                self.listeners.notify(&key, entry.value(), RemovalCause::Removed);
+                taken.push((key, entry));
+            }
+        }
+        Ok(taken)
+    }
+
+    /// Stores entries taken from another cache, keeping their TTLs. They
+    /// are either all stored or, if the write is refused, none are.
+    pub(crate) fn insert_entries(&mut self, entries: TakenEntries<M::Value>) -> Result<(), CacheError> {
+        self.begin_write()?;
+        for (key, entry) in entries {
+            self.write_entry(&key, entry);
+        }
+        Ok(())
+    }
+
+    /// Bulk-loads `(key, value)` pairs with the default TTL. See `warm()`.
+    pub(crate) fn warm_from<K, V>(&mut self, entries: impl IntoIterator<Item = (K, V)>, progress: &mut dyn FnMut(usize)) -> usize
+    where
+        K: AsRef<str>,
+        V: AsRef<<M::Value as CacheValue>::Ref>,
+    {
+        let (ttl, now) = (self.config.default_ttl, self.now());
+        let entries = entries.into_iter().map(|(key, value)| {
+            let entry = CacheEntry::with_ttl(key.as_ref(), value.as_ref(), ttl, now);
+            (key.as_ref().to_string(), entry)
+        });
+        self.warm(entries, progress)
+    }
+
+    /// Makes room for `additional` more entries in the map, the Bloom
+    /// filter and the value index, so that inserting them doesn't allocate
+    /// along the way.
+    pub(crate) fn reserve_capacity(&mut self, additional: usize) {
+        self.entries.reserve(additional);
+        self.bloom_filter.reserve(additional);
+        self.eviction.reserve(additional);
+    }
+
+    pub(crate) fn capacity(&self) -> usize {
+        self.entries.capacity()
+    }
+
+    /// Inserts `entries` in bulk, reporting the running count to `progress`
+    /// every `WARM_PROGRESS_INTERVAL` entries and once at the end.
+    ///
+    /// The map is pre-sized from the iterator's size hint, and an empty
+    /// cache gets a Bloom filter sized for the whole load in place of the
+    /// default one, which would otherwise grow one sub-filter at a time.
+    pub(crate) fn warm(&mut self, entries: impl IntoIterator<Item = (String, CacheEntry<M::Value>)>, progress: &mut dyn FnMut(usize)) -> usize {
+        if self.begin_write().is_err() {
+            progress(0);
+            return 0;
+        }
+        let entries = entries.into_iter();
+        let expected = entries.size_hint().0;
+        self.entries.reserve(expected);
+        if self.bloom_filter.is_empty() && expected > BLOOM_INITIAL_CAPACITY {
+            self.bloom_filter = ScalableBloomFilter::with_hasher(expected, BLOOM_FALSE_POSITIVE_RATE, M::Hasher::default());
+        }
+
+        let mut loaded = 0;
+        for (key, entry) in entries {
+            self.write_entry(&key, entry);
+            loaded += 1;
+            if loaded % WARM_PROGRESS_INTERVAL == 0 {
+                progress(loaded);
+            }
+        }
+        if loaded == 0 || loaded % WARM_PROGRESS_INTERVAL != 0 {
+            progress(loaded);
+        }
+        loaded
+    }
+
+    pub(crate) fn get(&mut self, key: &str) -> Option<&<M::Value as CacheValue>::Ref> {
+        self.get_with(key, &GetOptions::default())
+    }
+
+    pub(crate) fn get_with(&mut self, key: &str, options: &GetOptions) -> Option<&<M::Value as CacheValue>::Ref> {
+        self.get_entry_with(key, options).map(CacheEntry::value)
+    }
+
+    /// Like `get`, but returns the stored value itself, for callers that
+    /// hand out clones of it.
+    #[cfg(feature = "std")]
+    pub(crate) fn get_stored(&mut self, key: &str) -> Option<&M::Value> {
+        self.get_entry_with(key, &GetOptions::default()).map(|entry| &entry.value)
+    }
+
+    fn get_entry_with(&mut self, key: &str, options: &GetOptions) -> Option<&CacheEntry<M::Value>> {
+        self.apply_reads();
+        self.record_access(key);
+        // Primeiro verifica no Bloom Filter
+        if !options.skip_bloom && !self.passes_bloom_filter(key) {
+            return self.miss(key, options);
+        }
+
+        let now = self.now();
+        match self.entries.get(key).map(|entry| entry.is_expired(now)) {
+            None => self.miss(key, options),
+            Some(true) if options.allow_stale => {
+                // Sem touch: renovaria o tempo ocioso de uma entrada já vencida
+                self.stats.record_hit(now);
+                self.entries.get(key)
+            }
+            Some(true) => {
+                self.remove_expired(key);
+                self.miss(key, options)
+            }
+            Some(false) => {
+                let entry = self.entries.get_mut(key)?;
+                self.eviction.touch(entry);
+                self.stats.record_hit(now);
+                Some(entry)
+            }
+        }
+    }
+
+    /// Reads `key` like `get`, along with the version of its value.
+    pub(crate) fn get_versioned(&mut self, key: &str) -> Option<(&<M::Value as CacheValue>::Ref, u64)> {
+        self.get(key)?;
+        let entry = self.entries.get(key)?;
+        Some((entry.value(), entry.version()))
+    }
+
+    pub(crate) fn get_stale(&mut self, key: &str) -> Option<MaybeStale<&<M::Value as CacheValue>::Ref>> {
+        let stale = match (self.config.soft_ttl, self.entries.get(key)) {
+            (Some(soft_ttl), Some(entry)) => self.now().saturating_duration_since(entry.written_at()) > soft_ttl,
+            _ => false,
+        };
+        self.get(key).map(|value| MaybeStale { value, stale })
+    }
+
+    /// Counts a miss and reads `key` through from the backing store, if any
+    /// and the lookup's budget allows it.
+    fn miss(&mut self, key: &str, options: &GetOptions) -> Option<&CacheEntry<M::Value>> {
+        self.stats.record_miss(self.now());
+        if !options.may_read_through() {
+            return None;
+        }
+        let value = self.store.load(key)?;
+
+        // Veio do store: entra no cache sem ser escrito de volta
+        let mut entry = CacheEntry::with_ttl(key, value.view(), self.config.default_ttl, self.now());
+        self.eviction.admit(key, &mut entry);
+        self.entries.insert(key.to_string(), entry);
+        self.bloom_filter.insert(key);
+        self.enforce_memory_limit();
+        self.entries.get(key)
+    }
+
+    /// Reads `key` through a shared reference.
+    ///
+    /// Unlike `get`, a miss doesn't read through to the backing store and
+    /// an expired entry is left for the next write to remove. The read is
+    /// noted in the read buffer and counts as an access (recency, idle
+    /// clock, hit statistics) once a call with `&mut self` applies it;
+    /// until then the views of the cache read the buffer alongside it.
+    pub(crate) fn get_ref(&self, key: &str) -> Option<&<M::Value as CacheValue>::Ref> {
+        self.record_access(key);
+        let found = self.passes_bloom_filter_shared(key).then(|| self.peek(key)).flatten();
+        match found {
+            Some(_) => self.reads.hit(key, self.now()),
+            None => self.reads.miss(),
+        }
+        found
+    }
+
+    /// Applies the reads made through `get_ref` since the last call, as if
+    /// they had gone through `get`.
+    fn apply_reads(&mut self) {
+        let (hits, misses) = self.reads.take();
+        let now = self.now();
+        if misses > 0 {
+            self.stats.record_misses(now, misses);
+        }
+        for (key, pending) in hits {
+            // A entrada pode ter sido removida desde a leitura
+            if let Some(entry) = self.entries.get_mut(&key) {
+                self.eviction.touch_at(entry, pending.at, pending.count);
+            }
+            self.stats.record_hits(now, pending.count);
+        }
+    }
+
+    pub(crate) fn peek(&self, key: &str) -> Option<&<M::Value as CacheValue>::Ref> {
+        self.peek_stored(key).map(CacheValue::view)
+    }
+
+    pub(crate) fn peek_stored(&self, key: &str) -> Option<&M::Value> {
+        self.entries.get(key).filter(|entry| !self.is_expired(key, entry)).map(|entry| &entry.value)
+    }
+
+    /// Checks if `entry`, stored under `key`, has expired, counting a read
+    /// of it through `&self` that hasn't been applied yet.
+    fn is_expired(&self, key: &str, entry: &CacheEntry<M::Value>) -> bool {
+        // Só entradas com tempo ocioso dependem da última leitura
+        let read_at = entry.idle_timeout().and_then(|_| self.reads.last_read(key));
+        entry.is_expired_after_read(read_at, self.now())
+    }
+
+    /// Returns the current time on the cache's clock.
+    pub(crate) fn now(&self) -> Instant {
+        self.eviction.now()
+    }
+
+    pub(crate) fn ttl(&self, key: &str) -> Option<Duration> {
+        let now = self.now();
+        self.entries
+            .get(key)
+            .filter(|entry| !entry.is_expired(now))
+            .and_then(|entry| entry.time_to_live(now))
+    }
+
+    pub(crate) fn expire(&mut self, key: &str, ttl: Duration) -> bool {
+        if self.begin_write().is_err() {
+            return false;
+        }
+        self.with_live_entry(key, |entry, eviction, store| {
+            entry.set_ttl(ttl, eviction.now());
+            eviction.reschedule(key, entry);
+            store.retimed(key, entry, eviction.now());
+            true
+        })
+    }
+
+    pub(crate) fn persist(&mut self, key: &str) -> bool {
+        if self.begin_write().is_err() {
+            return false;
+        }
+        self.with_live_entry(key, |entry, eviction, store| {
+            let had_expiry = entry.persist();
+            eviction.reschedule(key, entry);
+            if had_expiry {
+                store.retimed(key, entry, eviction.now());
+            }
+            had_expiry
+        })
+    }
+
+    pub(crate) fn remove(&mut self, key: &str) -> Option<M::Value> {
+        self.try_remove(key).ok().flatten()
+    }
+
+    /// Removes `key` like `remove`, failing if the write is refused.
+    pub(crate) fn try_remove(&mut self, key: &str) -> Result<Option<M::Value>, CacheError> {
+        self.begin_write()?;
+        Ok(self.delete_entry(key))
+    }
+
+    /// Removes `key` once the write was allowed.
+    fn delete_entry(&mut self, key: &str) -> Option<M::Value> {
+        self.apply_reads();
+        self.store.deleted(key);
+        let removed = self.entries.remove(key)?;
+        self.eviction.release(key, &removed);
+        self.stats.record_removal();
+        self.listeners.notify(key, removed.value(), RemovalCause::Removed);
+        Some(removed.value)
+    }
+
+    pub(crate) fn update(&mut self, key: &str, value: &<M::Value as CacheValue>::Ref) -> bool {
+        self.update_versioned(key, value).is_some()
+    }
+
+    /// Replaces the value of `key` if present and returns its new version.
+    fn update_versioned(&mut self, key: &str, value: &<M::Value as CacheValue>::Ref) -> Option<u64> {
+        self.begin_write().ok()?;
+        let entry = self.entries.get_mut(key)?;
+        let before = self.eviction.footprint(key, entry);
+        let now = self.eviction.now();
+        let old = entry.replace_value(value, now);
+        let version = entry.version();
+        self.store.written(key, entry, now);
+        self.eviction.revalue(key, before, entry);
+        self.eviction.touch(entry);
+        self.listeners.notify(key, old.view(), RemovalCause::Replaced);
+        self.listeners.written(key, value, true);
+        self.enforce_memory_limit();
+        Some(version)
+    }
+
+    /// Updates `key` like `update`, only if its value is still at
+    /// `expected`, and returns the new version.
+    pub(crate) fn update_if_version(
+        &mut self,
+        key: &str,
+        expected: u64,
+        value: &<M::Value as CacheValue>::Ref,
+    ) -> Result<u64, CacheError> {
+        self.begin_write()?;
+        let now = self.now();
+        let current = self.entries.get(key).filter(|entry| !entry.is_expired(now)).map(CacheEntry::version);
+        if current != Some(expected) {
+            return Err(CacheError::VersionMismatch { key: key.to_string(), current });
+        }
+        self.update_versioned(key, value)
+            .ok_or_else(|| CacheError::VersionMismatch { key: key.to_string(), current: None })
+    }
+
+    pub(crate) fn clear(&mut self) {
+        if self.begin_write().is_ok() {
+            self.clear_entries();
+        }
+    }
+
+    /// Empties the cache once the write was allowed.
+    fn clear_entries(&mut self) {
+        let entries = self.entries.len();
+        if !self.listeners.is_empty() {
+            let mut removed: Vec<_> = self.entries.iter().collect();
+            self.sort_for_walk(&mut removed, |(key, _)| key);
+            for (key, entry) in removed {
+                self.listeners.notify(key, entry.value(), RemovalCause::Removed);
+            }
+        }
+        self.entries.clear();
+        self.eviction.clear();
+        self.bloom_filter.clear();
+        self.store.cleared();
+        self.audit_log.record(AuditAction::Flush { entries });
+    }
+
+    /// Removes the live entries `keep` returns false for, in walk order,
+    /// and returns how many were removed.
+    pub(crate) fn retain(&mut self, mut keep: impl FnMut(&str, &<M::Value as CacheValue>::Ref) -> bool) -> usize {
+        if self.begin_write().is_err() {
+            return 0;
+        }
+        let doomed: Vec<String> = self
+            .live_in_walk_order()
+            .into_iter()
+            .filter(|(key, entry)| !keep(key, entry.value()))
+            .map(|(key, _)| key.clone())
+            .collect();
+        for key in &doomed {
+            self.delete_entry(key);
+        }
+        self.audit_log.record(AuditAction::MassDelete {
+            operation: "retain".to_string(),
+            entries: doomed.len(),
+        });
+        doomed.len()
+    }
+
+    /// Removes every entry carrying `tag`, in key order, and returns how
+    /// many live ones were removed. Expired entries carrying it are purged
+    /// as expired.
+    pub(crate) fn invalidate_tag(&mut self, tag: &str) -> usize {
+        if self.begin_write().is_err() {
+            return 0;
+        }
+        let mut doomed = self.eviction.tagged(tag);
+        doomed.sort_unstable();
+        let mut removed = 0;
+        for key in &doomed {
+            if self.entries.get(key).is_some_and(|entry| entry.is_expired(self.now())) {
+                self.remove_expired(key);
+            } else if self.delete_entry(key).is_some() {
+                removed += 1;
+            }
+        }
+        self.audit_log.record(AuditAction::MassDelete {
+            operation: format!("invalidate_tag {}", tag),
+            entries: removed,
+        });
+        removed
+    }
+
+    /// Applies the writes of `transaction` in order, unless a key it watches
+    /// changed. The log and the replicas get them as one batch record.
+    pub(crate) fn commit(&mut self, transaction: Transaction<M::Value>) -> Result<(), CacheError> {
+        self.begin_write()?;
+        if let Some(key) = transaction.conflict(|key| self.peek(key)) {
+            return Err(CacheError::TransactionConflict { key: key.to_string() });
+        }
+        self.store.begin_batch();
+        for write in transaction.writes {
+            match write {
+                Write::Insert { key, value, ttl } => {
+                    let entry = CacheEntry::with_ttl(&key, value.view(), ttl.or(self.config.default_ttl), self.now());
+                    self.write_entry(&key, entry);
+                }
+                Write::Remove { key } => {
+                    self.delete_entry(&key);
+                }
+            }
+        }
+        self.store.end_batch();
+        Ok(())
+    }
+
+    /// Empties the cache like `clear`, returning copies of the live entries,
+    /// or nothing if the write is refused.
+    pub(crate) fn drain(&mut self) -> Vec<(String, M::Value)> {
+        if self.begin_write().is_err() {
+            return Vec::new();
+        }
+        let drained = self
+            .live_in_walk_order()
+            .into_iter()
+            .map(|(key, entry)| (key.clone(), M::Value::from_ref(entry.value())))
+            .collect();
+        self.clear_entries();
+        drained
+    }
+
+    pub(crate) fn contains_key(&self, key: &str) -> bool {
+        // Primeiro verifica no Bloom Filter; uma entrada vencida fica para a
+        // próxima escrita ou purge_expired remover
+        self.passes_bloom_filter_shared(key) && self.peek(key).is_some()
+    }
+
+    /// Looks up `key` once for the entry API.
+    ///
+    /// Writes made through the returned entry are accounted right away, but
+    /// the memory budget is only enforced on the next write, since the entry
+    /// keeps the map borrowed.
+    ///
+    /// Panics if the write is refused; see `try_entry`.
+    pub(crate) fn entry(&mut self, key: &str) -> Entry<'_, M::Value> {
+        match self.try_entry(key) {
+            Ok(entry) => entry,
+            Err(error) => panic!("entry() on a cache that refuses writes: {}", error),
+        }
+    }
+
+    /// Looks up `key` for the entry API like `entry`, failing if the write
+    /// is refused.
+    pub(crate) fn try_entry(&mut self, key: &str) -> Result<Entry<'_, M::Value>, CacheError> {
+        self.begin_write()?;
+        self.apply_reads();
+        self.enforce_memory_limit();
+        self.maybe_rebuild_bloom_filter();
+        let slot = self.entries.entry(key.to_string());
+        Ok(entry_api::entry_for_slot(
+            slot,
+            self.config.default_ttl,
+            &mut self.bloom_filter,
+            &mut self.stats,
+            &mut self.eviction,
+            &mut self.listeners,
+            &self.store,
+        ))
+    }
+
+    /// Returns the entries that haven't expired, whether or not they were
+    /// purged yet.
+    fn live(&self) -> impl Iterator<Item = (&String, &CacheEntry<M::Value>)> {
+        self.entries.iter().filter(|(key, entry)| !self.is_expired(key, entry))
+    }
+
+    pub(crate) fn keys(&self) -> impl Iterator<Item = &String> {
+        self.live().map(|(key, _)| key)
+    }
+
+    pub(crate) fn values(&self) -> impl Iterator<Item = &M::Value> {
+        self.live().map(|(_, entry)| &entry.value)
+    }
+
+    pub(crate) fn iter(&self) -> impl Iterator<Item = (&String, &<M::Value as CacheValue>::Ref, EntryMetadata)> {
+        let now = self.now();
+        self.live().map(move |(key, entry)| (key, entry.value(), entry.metadata(key, now)))
+    }
+
+    /// Returns the live entries from the least to the most recently used,
+    /// the order eviction takes them in.
+    pub(crate) fn iter_by_recency(&self) -> impl DoubleEndedIterator<Item = (&String, &<M::Value as CacheValue>::Ref)> {
+        let mut order: Vec<&String> = self.eviction.by_recency().collect();
+        let pending = self.reads.pending_hits();
+        if !pending.is_empty() {
+            // Leituras por &self ainda não aplicadas vão para o fim, na ordem em que ocorreram
+            order.sort_by_key(|key| pending.get(key.as_str()).map(|hit| hit.at));
+        }
+        order.into_iter().filter_map(|key| {
+            let entry = self.entries.get(key).filter(|entry| !self.is_expired(key, entry))?;
+            Some((key, entry.value()))
+        })
+    }
+
+    /// Returns the live entries from the least to the most used, ties
+    /// broken by recency.
+    pub(crate) fn iter_by_frequency(&self) -> impl Iterator<Item = (&String, &<M::Value as CacheValue>::Ref)> {
+        let mut entries: Vec<_> = self.live().collect();
+        let pending = self.reads.pending_hits();
+        entries.sort_by_key(|(key, entry)| {
+            let hit = pending.get(key.as_str());
+            (entry.accesses() + hit.map_or(0, |hit| hit.count), hit.map(|hit| hit.at), entry.recency)
+        });
+        entries.into_iter().map(|(key, entry)| (key, entry.value()))
+    }
+
+    /// Returns up to `n` live keys, least recently used first.
+    pub(crate) fn coldest(&self, n: usize) -> Vec<&String> {
+        self.iter_by_recency().take(n).map(|(key, _)| key).collect()
+    }
+
+    /// Returns up to `n` live keys, most recently used first.
+    pub(crate) fn hottest(&self, n: usize) -> Vec<&String> {
+        self.iter_by_recency().rev().take(n).map(|(key, _)| key).collect()
+    }
+
+    pub(crate) fn keys_raw(&self) -> impl Iterator<Item = &String> {
+        self.entries.iter().map(|(key, _)| key)
+    }
+
+    /// Returns up to `limit` live entries in stable hash order, starting
+    /// after the key `cursor`, and the cursor of the next page if there is
+    /// one. Every call walks the whole map.
+    pub(crate) fn scan_by_hash(&self, cursor: Option<&str>, limit: usize) -> ScanPage<'_, M::Value> {
+        if limit == 0 {
+            return (Vec::new(), cursor.map(str::to_string));
+        }
+        let after = cursor.map(|cursor| (stable_hash(cursor), cursor));
+        let mut page: Vec<(u64, &String, &CacheEntry<M::Value>)> = self
+            .live()
+            .map(|(key, entry)| (stable_hash(key.as_str()), key, entry))
+            .filter(|(hash, key, _)| after.is_none_or(|after| (*hash, key.as_str()) > after))
+            .collect();
+        let order = |a: &(u64, &String, _), b: &(u64, &String, _)| (a.0, a.1).cmp(&(b.0, b.1));
+        let more = page.len() > limit;
+        if more {
+            // Só os `limit` primeiros precisam de ordem total
+            page.select_nth_unstable_by(limit, order);
+            page.truncate(limit);
+        }
+        page.sort_unstable_by(order);
+        let next = page.last().filter(|_| more).map(|(_, key, _)| key.to_string());
+        (page.into_iter().map(|(_, key, entry)| (key, entry.value())).collect(), next)
+    }
+
+    pub(crate) fn values_raw(&self) -> impl Iterator<Item = &M::Value> {
+        self.entries.iter().map(|(_, entry)| &entry.value)
+    }
+
+    /// Returns the live keys holding `value`, from the inverted index if
+    /// the cache keeps one and by scanning every entry otherwise.
+    pub(crate) fn keys_with_value(&self, value: &<M::Value as CacheValue>::Ref) -> Vec<&String> {
+        let now = self.now();
+        let holds_value = |entry: &CacheEntry<M::Value>| !entry.is_expired(now) && entry.value() == value;
+        match self.eviction.value_candidates(value) {
+            Some(candidates) => candidates
+                .iter()
+                .filter(|key| self.entries.get(key).is_some_and(holds_value))
+                .collect(),
+            None => self
+                .entries
+                .iter()
+                .filter(|(_, entry)| holds_value(entry))
+                .map(|(key, _)| key)
+                .collect(),
+        }
+    }
+
+    pub(crate) fn memory_usage(&self) -> usize {
+        self.eviction.memory_usage()
+    }
+
+    pub(crate) fn total_weight(&self) -> u64 {
+        self.eviction.weight()
+    }
+
+    pub(crate) fn biggest_keys(&self, n: usize) -> Vec<(&String, usize)> {
+        // Heap mínimo limitado a n: o menor dos maiores fica no topo
+        let mut heap = BinaryHeap::with_capacity(n + 1);
+        for (key, entry) in self.live() {
+            heap.push(Reverse((eviction::entry_size(key, entry), key)));
+            if heap.len() > n {
+                heap.pop();
+            }
+        }
+
+        let mut biggest: Vec<_> = heap.into_iter().map(|Reverse((size, key))| (key, size)).collect();
+        biggest.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+        biggest
+    }
+
+    pub(crate) fn stats(&self) -> CacheStats {
+        let mut stats = self.stats.snapshot(self.entries.len(), self.now());
+        let (hits, misses) = self.reads.pending();
+        stats.hits += hits;
+        stats.misses += misses;
+        stats.callback_panics = self.listeners.panics() + self.audit_log.panics();
+        stats.dropped_notifications = self.listeners.dropped();
+        stats.store_errors = self.store.errors();
+        stats.bloom_fill_ratio = self.bloom_filter.fill_ratio();
+        stats.bloom_false_positive_rate = self.bloom_filter.estimated_false_positive_rate();
+        #[cfg(feature = "persistence")]
+        {
+            stats.aof_errors = self.store.log().map_or(0, AppendLog::errors);
+            stats.persistence_degraded = self.is_degraded();
+        }
+        stats
+    }
+
+    /// Returns true if both caches hold the same live keys and values.
+    pub(crate) fn same_content(&self, other: &Self) -> bool {
+        self.live().count() == other.live().count()
+            && self
+                .live()
+                .all(|(key, entry)| other.peek(key).is_some_and(|value| value == entry.value()))
+    }
+
+    /// Restarts the TTL and idle clock of every entry, as if it had just
+    /// been written.
+    pub(crate) fn refresh_timestamps(&mut self) {
+        let now = self.now();
+        self.entries.values_mut().for_each(|entry| entry.refresh(now));
+    }
+
+    pub(crate) fn config(&self) -> EffectiveConfig {
+        #[cfg(feature = "std")]
+        let (listener_queue, backing_store, write_back_interval) = {
+            let store = self.config.store.as_ref();
+            (
+                self.config.listener_queue,
+                store.map(|store| match store.write_back() {
+                    Some(_) => "write-back",
+                    None => "write-through",
+                }),
+                store.and_then(|store| store.write_back()),
+            )
+        };
+        #[cfg(not(feature = "std"))]
+        let (listener_queue, backing_store, write_back_interval) = (None, None, None);
+        EffectiveConfig {
+            default_ttl: self.config.default_ttl,
+            soft_ttl: self.config.soft_ttl,
+            max_memory: self.config.max_memory,
+            max_memory_bytes: self.memory_budget.as_ref().and_then(MemoryBudget::current),
+            soft_memory_bytes: self.config.soft_memory_bytes,
+            max_weight: self.config.max_weight,
+            bloom_audit: self.bloom_audit.is_some(),
+            adaptive_bloom: self.config.adaptive_bloom,
+            listener_queue,
+            value_index: self.config.value_index,
+            backing_store,
+            write_back_interval,
+        }
+    }
+
+    #[cfg(feature = "std")]
+    pub(crate) fn flush(&self) {
+        self.store.flush();
+    }
+
+    #[cfg(feature = "std")]
+    pub(crate) fn flush_and_wait(&self, timeout: Duration) -> bool {
+        self.store.flush_and_wait(timeout)
+    }
+
+    /// Inserts `value` and reports whether the write reached `durability`
+    /// within `timeout`. The write is applied either way.
+    #[cfg(feature = "std")]
+    pub(crate) fn insert_with_durability(
+        &mut self,
+        key: &str,
+        value: &<M::Value as CacheValue>::Ref,
+        durability: Durability,
+        timeout: Duration,
+    ) -> Result<(), CacheError> {
+        let errors = self.store.errors();
+        self.try_insert(key, value)?;
+        match durability {
+            Durability::Memory | Durability::Replicated(0) => Ok(()),
+            Durability::Disk => self.store.acknowledge(errors, timeout),
+            Durability::Replicated(replicas) => self.store.wait_for_replicas(replicas, timeout),
+        }
+    }
+
+    /// Starts appending every write to the log at `path`. A new log first
+    /// gets the live entries, so that replaying it rebuilds the whole cache.
+    #[cfg(feature = "persistence")]
+    pub(crate) fn enable_aof(&mut self, path: &Path, fsync: FsyncPolicy) -> io::Result<()> {
+        let (log, fresh) = AppendLog::open(path, fsync)?;
+        if fresh {
+            let now = self.now();
+            for (key, entry) in self.live_in_walk_order() {
+                log.append(&aof::set_record(key, entry, now));
+            }
+            log.sync()?;
+        }
+        self.store.set_log(Some(log));
+        self.record_config_change("aof", "on");
+        Ok(())
+    }
+
+    #[cfg(feature = "persistence")]
+    pub(crate) fn disable_aof(&mut self) {
+        self.store.set_log(None);
+        self.record_config_change("aof", "off");
+    }
+
+    /// Returns true once the log or `save_snapshot()` failed
+    /// `PERSISTENCE_FAILURE_LIMIT` times in a row.
+    #[cfg(feature = "persistence")]
+    pub(crate) fn is_degraded(&self) -> bool {
+        let log_failures = self.store.log().map_or(0, AppendLog::consecutive_failures);
+        log_failures.max(self.snapshot_failures.load(Ordering::Relaxed)) >= PERSISTENCE_FAILURE_LIMIT
+    }
+
+    /// Forgets the failures that made the cache degraded, so writes are
+    /// accepted again.
+    #[cfg(feature = "persistence")]
+    pub(crate) fn resume_persistence(&mut self) {
+        if let Some(log) = self.store.log() {
+            log.reset_failures();
+        }
+        *self.snapshot_failures.get_mut() = 0;
+    }
+
+    /// Fails if the cache is degraded and built to reject writes.
+    pub(crate) fn check_writable(&self) -> Result<(), CacheError> {
+        #[cfg(feature = "persistence")]
+        if self.config.persistence_failure == PersistenceFailure::RejectWrites && self.is_degraded() {
+            return Err(CacheError::PersistenceFailed);
+        }
+        Ok(())
+    }
+
+    /// Checks that a write asked for by a caller may go ahead, counting it
+    /// if it is refused. Every such write passes here once, before it
+    /// changes anything, so a refused one is never half applied.
+    fn begin_write(&mut self) -> Result<(), CacheError> {
+        // Leituras anteriores por &self contam antes da escrita
+        self.apply_reads();
+        let writable = self.check_writable();
+        if writable.is_err() {
+            self.stats.record_rejected_write();
+        }
+        writable
+    }
+
+    /// Applies the operations logged at `path` in order and returns how
+    /// many there were.
+    #[cfg(feature = "persistence")]
+    pub(crate) fn replay(&mut self, path: &Path) -> io::Result<usize> {
+        let operations = aof::read::<M::Value>(path)?;
+        let count = operations.len();
+        // Aplica sem repassar ao store nem registrar de novo no log
+        let store = mem::take(&mut self.store);
+        self.apply(operations);
+        self.store = store;
+        Ok(count)
+    }
+
+    /// Starts streaming every write to the replica at the other end of
+    /// `transport`, after bringing it up to date with the live entries.
+    #[cfg(feature = "cluster")]
+    pub(crate) fn add_replica(&mut self, transport: Box<dyn Transport>) {
+        let now = self.now();
+        let initial = iter::once(aof::clear_record())
+            .chain(self.live_in_walk_order().into_iter().map(|(key, entry)| aof::set_record(key, entry, now)))
+            .collect();
+        self.store.add_replica(transport, initial);
+        self.record_config_change("replicas", &self.store.replicas().to_string());
+    }
+
+    #[cfg(feature = "cluster")]
+    pub(crate) fn replicas(&self) -> usize {
+        self.store.replicas()
+    }
+
+    /// Applies the writes a primary streamed through `transport`, waiting up
+    /// to `timeout` for the first one, and acknowledges them. Returns how
+    /// many were applied.
+    #[cfg(feature = "cluster")]
+    pub(crate) fn apply_replicated(&mut self, transport: &mut dyn Transport, timeout: Duration) -> io::Result<usize> {
+        // Nada é lido nem confirmado: o primário não pode contar com uma
+        // réplica que recusa escritas
+        self.check_writable().map_err(io::Error::other)?;
+        let (sequence, operations) = cluster::receive::<M::Value>(transport, timeout)?;
+        let count = operations.len();
+        self.apply(operations);
+        if let Some(sequence) = sequence {
+            cluster::acknowledge(transport, sequence)?;
+        }
+        Ok(count)
+    }
+
+    #[cfg(feature = "cluster")]
+    pub(crate) fn merkle_digest(&self, buckets: usize) -> MerkleDigest {
+        MerkleDigest::build(buckets, self.live().map(|(key, entry)| (key.as_str(), entry.value().as_ref())))
+    }
+
+    #[cfg(feature = "cluster")]
+    pub(crate) fn export_buckets(&self, divergent: &DivergentBuckets) -> BucketPatch {
+        let now = self.now();
+        let records = self
+            .live_in_walk_order()
+            .into_iter()
+            .filter(|(key, _)| divergent.contains_key(key))
+            .map(|(key, entry)| aof::set_record(key, entry, now));
+        BucketPatch::new(divergent, records)
+    }
+
+    /// Replaces the content of the patch's buckets with its entries and
+    /// returns how many keys were written or removed.
+    #[cfg(feature = "cluster")]
+    pub(crate) fn apply_bucket_patch(&mut self, patch: &BucketPatch) -> Result<usize, CacheError> {
+        let operations = patch.operations::<M::Value>()?;
+        self.begin_write()?;
+        let patched = BucketPatch::keys(&operations);
+        let stale: Vec<String> = self
+            .live_in_walk_order()
+            .into_iter()
+            .filter(|(key, _)| patch.divergent().contains_key(key) && !patched.contains(key.as_str()))
+            .map(|(key, _)| key.clone())
+            .collect();
+        for key in &stale {
+            self.delete_entry(key);
+        }
+        let written = operations.len();
+        self.apply(operations);
+        Ok(stale.len() + written)
+    }
+
+    /// Applies logged or replicated operations in order, without checking
+    /// that the cache accepts writes. Entries whose deadline already passed
+    /// are left out.
+    #[cfg(feature = "persistence")]
+    fn apply(&mut self, operations: Vec<Operation<M::Value>>) {
+        let now = SystemTime::now();
+        let remaining = |expires_at: Option<SystemTime>| expires_at.map(|at| at.duration_since(now).unwrap_or_default());
+        for operation in operations {
+            match operation {
+                Operation::Set { key, expires_at, .. } | Operation::Retime { key, expires_at, .. }
+                    if remaining(expires_at) == Some(Duration::ZERO) =>
+                {
+                    self.remove_expired(&key);
+                }
+                Operation::Set { key, value, expires_at, idle_timeout } => {
+                    let mut entry = CacheEntry::with_ttl(&key, value.view(), remaining(expires_at), self.now());
+                    entry.set_idle_timeout(idle_timeout);
+                    self.write_entry(&key, entry);
+                }
+                Operation::Retime { key, expires_at, idle_timeout } => {
+                    self.with_live_entry(&key, |entry, eviction, store| {
+                        entry.persist();
+                        if let Some(ttl) = remaining(expires_at) {
+                            entry.set_ttl(ttl, eviction.now());
+                        }
+                        entry.set_idle_timeout(idle_timeout);
+                        eviction.reschedule(&key, entry);
+                        store.retimed(&key, entry, eviction.now());
+                        true
+                    });
+                }
+                Operation::Delete { key } => {
+                    self.delete_entry(&key);
+                }
+                Operation::Clear => self.clear_entries(),
+                Operation::Batch(operations) => {
+                    self.store.begin_batch();
+                    self.apply(operations);
+                    self.store.end_batch();
+                }
+            }
+        }
+    }
+
+    /// Returns the live entries in the order bulk operations visit them:
+    /// the map's own, or by key if the cache is deterministic.
+    pub(crate) fn live_in_walk_order(&self) -> Vec<(&String, &CacheEntry<M::Value>)> {
+        let mut live: Vec<_> = self.live().collect();
+        self.sort_for_walk(&mut live, |(key, _)| key);
+        live
+    }
+
+    /// Sorts `items` by the key `key_of` picks out if the cache is
+    /// deterministic, and leaves them alone otherwise.
+    pub(crate) fn sort_for_walk<T>(&self, items: &mut [T], key_of: impl Fn(&T) -> &str) {
+        if self.config.deterministic {
+            items.sort_unstable_by(|a, b| key_of(a).cmp(key_of(b)));
+        }
+    }
+
+    pub(crate) fn keys_matching(&self, pattern: &str) -> impl Iterator<Item = &String> {
+        let glob = Glob::new(pattern);
+        self.live().filter(move |(key, _)| glob.matches(key)).map(|(key, _)| key)
+    }
+
+    pub(crate) fn enable_bloom_audit(&mut self) {
+        self.bloom_audit.get_or_insert_with(Lock::default);
+        self.record_config_change("bloom_audit", "on");
+    }
+
+    pub(crate) fn disable_bloom_audit(&mut self) {
+        self.bloom_audit = None;
+        self.record_config_change("bloom_audit", "off");
+    }
+
+    pub(crate) fn bloom_audit(&self) -> Option<BloomAudit> {
+        let audit = self.bloom_audit.as_ref()?;
+        Some(*audit.lock())
+    }
+
+    #[cfg(feature = "probabilistic")]
+    pub(crate) fn track_hot_keys(&mut self, k: usize) {
+        self.hot_keys = Some(Lock::new(HotKeys::new(k)));
+        self.record_config_change("hot_keys", &k.to_string());
+    }
+
+    #[cfg(feature = "probabilistic")]
+    pub(crate) fn stop_tracking_hot_keys(&mut self) {
+        self.hot_keys = None;
+        self.record_config_change("hot_keys", "off");
+    }
+
+    #[cfg(feature = "probabilistic")]
+    pub(crate) fn hot_keys(&self) -> Vec<(String, u64)> {
+        let hot_keys = self.hot_keys.as_ref();
+        hot_keys.map(|hot_keys| hot_keys.lock().ranking()).unwrap_or_default()
+    }
+
+    #[cfg_attr(not(feature = "probabilistic"), allow(unused_variables))]
+    fn record_access(&self, key: &str) {
+        #[cfg(feature = "probabilistic")]
+        if let Some(hot_keys) = &self.hot_keys {
+            hot_keys.lock().record(key);
+        }
+    }
+
+    pub(crate) fn on_evict(&mut self, listener: Listener<<M::Value as CacheValue>::Ref>) {
+        self.listeners.add(listener);
+    }
+
+    pub(crate) fn remove_evict_listeners(&mut self) {
+        self.listeners.clear();
+    }
+
+    #[cfg(feature = "std")]
+    pub(crate) fn subscribe(&mut self, pattern: &str) -> Receiver<CacheEvent<M::Value>> {
+        self.listeners.subscribe(pattern)
+    }
+
+    #[cfg(feature = "std")]
+    pub(crate) fn set_audit_sink(&mut self, sink: Box<dyn AuditSink>) {
+        self.audit_log.set_sink(sink);
+    }
+
+    #[cfg(feature = "std")]
+    pub(crate) fn remove_audit_sink(&mut self) {
+        self.audit_log.remove_sink();
+    }
+
+    pub(crate) fn set_audit_context(&mut self, context: AuditContext) {
+        self.listeners.set_context(context.clone());
+        self.audit_log.set_context(context);
+    }
+
+    pub(crate) fn audit_context(&self) -> &AuditContext {
+        self.audit_log.context()
+    }
+
+    #[cfg(feature = "std")]
+    pub(crate) fn history(&self, key: &str, n: usize) -> Vec<HistoryEntry<M::Value>>
+    where
+        M::Value: Clone,
+    {
+        let mut history = self.listeners.history(key, n);
+        if self.config.redactor.rule_for(key).is_some() {
+            for change in &mut history {
+                if let CacheEvent::Insert { value, .. } | CacheEvent::Update { value, .. } = &mut change.event {
+                    let redacted = self.redacted(key, value.view()).unwrap_or_default();
+                    *value = M::Value::from_bytes(redacted.into_bytes()).expect("text is a valid value of every type");
+                }
+            }
+        }
+        history
+    }
+
+    /// Returns `value` as the redactor rewrites it for `key`, or `None` if
+    /// no rule applies.
+    fn redacted(&self, key: &str, value: &<M::Value as CacheValue>::Ref) -> Option<String> {
+        let rewrite = self.config.redactor.rule_for(key)?;
+        Some(rewrite(&String::from_utf8_lossy(value.as_ref())))
+    }
+
+    /// Applies `f` to the entry under `key` if it is live, dropping it if it
+    /// expired. Returns false for missing or expired keys.
+    fn with_live_entry<F>(&mut self, key: &str, f: F) -> bool
+    where
+        F: FnOnce(&mut CacheEntry<M::Value>, &mut EvictionIndex<M::Value>, &WriteStore<M::Value>) -> bool,
+    {
+        let now = self.now();
+        match self.entries.get_mut(key) {
+            Some(entry) if !entry.is_expired(now) => f(entry, &mut self.eviction, &self.store),
+            Some(_) => {
+                self.remove_expired(key);
+                false
+            }
+            None => false,
+        }
+    }
+
+    fn remove_expired(&mut self, key: &str) -> Option<M::Value> {
+        let expired = self.entries.remove(key)?;
+        self.eviction.release(key, &expired);
+        self.stats.record_expiration();
+        self.listeners.notify(key, expired.value(), RemovalCause::Expired);
+        Some(expired.value)
+    }
+
+    /// Removes the entries whose deadline has passed and returns how many
+    /// were removed.
+    pub(crate) fn purge_expired(&mut self) -> usize {
+        let mut purged = 0;
+        self.expire_due(|_, _| purged += 1);
+        purged
+    }
+
+    /// Removes the entries whose deadline has passed and returns them.
+    pub(crate) fn drain_expired(&mut self) -> Vec<(String, M::Value)> {
+        let mut drained = Vec::new();
+        self.expire_due(|key, value| drained.push((key, value)));
+        drained
+    }
+
+    /// Removes the entries whose deadline has passed, visiting only those,
+    /// and hands each one to `removed`.
+    ///
+    /// Idle entries read since they were scheduled are not expired yet and
+    /// get rescheduled instead.
+    fn expire_due(&mut self, mut removed: impl FnMut(String, M::Value)) {
+        self.apply_reads();
+        let now = self.now();
+        for key in self.eviction.pop_due(now) {
+            let Some(entry) = self.entries.get_mut(&key) else {
+                continue;
+            };
+            if !entry.is_expired(now) {
+                self.eviction.reschedule(&key, entry);
+            } else if let Some(value) = self.remove_expired(&key) {
+                removed(key, value);
+            }
+        }
+    }
+
+    /// Evicts least recently used entries to honour the memory limits.
+    ///
+    /// Expired entries are purged first, so live entries are only evicted
+    /// if that doesn't bring the cache under the limit. Above the hard
+    /// limit entries are evicted until the cache fits again.
+    /// The entry written last is the most recently used, so it only goes
+    /// when it alone is larger than the whole budget. Above the soft limit
+    /// only a few entries are evicted per write, spreading the work out so
+    /// the hard limit is rarely reached.
+    fn enforce_memory_limit(&mut self) {
+        let now = self.now();
+        let limit = self.memory_budget.as_mut().and_then(|budget| budget.bytes(now));
+        let soft_limit = self.config.soft_memory_bytes;
+        let max_weight = self.config.max_weight;
+        let usage = self.eviction.memory_usage();
+        if limit.is_some_and(|limit| usage > limit)
+            || soft_limit.is_some_and(|soft_limit| usage > soft_limit)
+            || max_weight.is_some_and(|max_weight| self.eviction.weight() > max_weight)
+        {
+            self.purge_expired();
+        }
+
+        if let Some(limit) = limit {
+            while self.eviction.memory_usage() > limit {
+                if !self.evict_coldest() {
+                    break;
+                }
+            }
+        }
+
+        if let Some(max_weight) = max_weight {
+            while self.eviction.weight() > max_weight {
+                if !self.evict_coldest() {
+                    break;
+                }
+            }
+        }
+
+        if let Some(soft_limit) = soft_limit {
+            for _ in 0..PROACTIVE_EVICTIONS_PER_WRITE {
+                if self.eviction.memory_usage() <= soft_limit || !self.evict_coldest() {
+                    break;
+                }
+                self.stats.record_proactive_eviction();
+            }
+        }
+    }
+
+    /// Evicts the least recently used entry. Returns false if there was none.
+    fn evict_coldest(&mut self) -> bool {
+        let Some(key) = self.eviction.pop_coldest() else {
+            return false;
+        };
+        if let Some(evicted) = self.entries.remove(&key) {
+            self.eviction.release(&key, &evicted);
+            self.stats.record_eviction(self.now());
+            self.store.dropped(&key);
+            self.listeners.notify(&key, evicted.value(), RemovalCause::Evicted);
+        }
+        true
+    }
+
+    /// Records that `clear_namespace` dropped `entries` live entries.
+    pub(crate) fn record_namespace_drop(&mut self, namespace: &str, entries: usize) {
+        self.audit_log.record(AuditAction::NamespaceDrop {
+            namespace: namespace.to_string(),
+            entries,
+        });
+    }
+
+    pub(crate) fn record_config_change(&mut self, setting: &str, value: &str) {
+        self.audit_log.record(AuditAction::ConfigChange {
+            setting: setting.to_string(),
+            value: value.to_string(),
+        });
+    }
+
+    /// Decides whether a lookup for `key` has to reach the map.
+    ///
+    /// Without auditing this is just the Bloom filter answer. With auditing
+    /// enabled the map is always consulted, the outcome is recorded, and a
+    /// negative answer from the filter is overridden whenever the key is
+    /// actually stored, so a broken filter can never hide live entries.
+    fn passes_bloom_filter(&mut self, key: &str) -> bool {
+        if let (Some(bypass), None) = (self.bloom_bypass.as_mut(), &self.bloom_audit) {
+            return Self::adaptive_bloom_check(bypass.get_mut(), &self.bloom_filter, &self.entries, &self.config.clock, key);
+        }
+        let maybe_present = self.bloom_filter.contains(key);
+
+        match self.bloom_audit.as_mut() {
+            None => maybe_present,
+            Some(audit) => {
+                let present = self.entries.get(key).is_some();
+                audit.get_mut().record(maybe_present, present);
+                maybe_present || present
+            }
+        }
+    }
+
+    /// The Bloom filter check for reads through `&self`, like
+    /// `passes_bloom_filter` but locking the audit or the bypass to record
+    /// the lookup.
+    fn passes_bloom_filter_shared(&self, key: &str) -> bool {
+        if let (Some(bypass), None) = (&self.bloom_bypass, &self.bloom_audit) {
+            let mut bypass = bypass.lock();
+            return Self::adaptive_bloom_check(&mut bypass, &self.bloom_filter, &self.entries, &self.config.clock, key);
+        }
+        let maybe_present = self.bloom_filter.contains(key);
+        match &self.bloom_audit {
+            None => maybe_present,
+            Some(audit) => {
+                let present = self.entries.get(key).is_some();
+                audit.lock().record(maybe_present, present);
+                maybe_present || present
+            }
+        }
+    }
+
+    /// The Bloom filter check in adaptive mode: skipped while bypassed,
+    /// with a sample of lookups timed on `clock` to decide when to switch.
+    fn adaptive_bloom_check(
+        bypass: &mut BloomBypass,
+        filter: &ScalableBloomFilter<M::Hasher>,
+        entries: &M,
+        clock: &SharedClock,
+        key: &str,
+    ) -> bool {
+        let sampled = bypass.sample_due();
+        if bypass.is_bypassing() {
+            // Só as amostras consultam o mapa aqui; as demais seguem direto para ele
+            if sampled {
+                let started = clock.now();
+                let present = entries.get(key).is_some();
+                bypass.record_lookup_cost(clock.now().saturating_duration_since(started));
+                bypass.record(!present);
+            }
+            return true;
+        }
+        if !sampled {
+            let maybe_present = filter.contains(key);
+            bypass.record(!maybe_present);
+            return maybe_present;
+        }
+        let started = clock.now();
+        let maybe_present = filter.contains(key);
+        let probed = clock.now();
+        bypass.record_probe_cost(probed.saturating_duration_since(started));
+        entries.get(key);
+        bypass.record_lookup_cost(clock.now().saturating_duration_since(probed));
+        bypass.record(!maybe_present);
+        maybe_present
+    }
+
+    /// Replaces the Bloom filter with one holding only the stored keys,
+    /// expired ones not yet purged included.
+    pub(crate) fn rebuild_bloom_filter(&mut self) {
+        let capacity = self.entries.len().max(BLOOM_INITIAL_CAPACITY);
+        let mut filter = ScalableBloomFilter::with_hasher(capacity, BLOOM_FALSE_POSITIVE_RATE, M::Hasher::default());
+        for (key, _) in self.entries.iter() {
+            filter.insert(key);
+        }
+        self.bloom_filter = filter;
+        self.stats.record_bloom_rebuild();
+    }
+
+    /// Rebuilds the Bloom filter if the rebuild policy says it holds too
+    /// many keys that left the cache.
+    fn maybe_rebuild_bloom_filter(&mut self) {
+        let Some(stale_fraction) = self.config.bloom_rebuild else {
+            return;
+        };
+        let held = self.bloom_filter.size();
+        let stale = held.saturating_sub(self.entries.len());
+        if stale >= BLOOM_REBUILD_MIN_STALE && stale as f64 > held as f64 * stale_fraction {
+            self.rebuild_bloom_filter();
+        }
+    }
+
+    pub(crate) fn bloom_bypassed(&self) -> bool {
+        self.bloom_audit.is_none()
+            && self
+                .bloom_bypass
+                .as_ref()
+                .is_some_and(|bypass| bypass.lock().is_bypassing())
+    }
+}
+
+/// Shows the entries as a map from keys to values, with the values the
+/// redactor has rules for rewritten.
+impl<M: EntryMap> fmt::Debug for CacheCore<M> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        struct Entries<'a, M: EntryMap>(&'a CacheCore<M>);
+
+        impl<M: EntryMap> fmt::Debug for Entries<'_, M> {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                let mut map = f.debug_map();
+                for (key, entry) in self.0.entries.iter() {
+                    match self.0.redacted(key, entry.value()) {
+                        Some(redacted) => map.entry(key, &redacted),
+                        None => map.entry(key, &entry.value()),
+                    };
+                }
+                map.finish()
+            }
+        }
+
+        f.debug_struct("CacheCore")
+            .field("entries", &Entries(self))
+            .field("config", &self.config)
+            .field("bloom_audit", &self.bloom_audit)
+            .field("bloom_bypass", &self.bloom_bypass)
+            .field("stats", &self.stats)
+            .field("listeners", &self.listeners)
+            .field("memory_budget", &self.memory_budget)
+            .field("store", &self.store)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Copies the entries, configuration and eviction order. Statistics start
+/// from zero, and listeners and the audit sink are not carried over since
+/// callbacks can't be cloned.
+impl<M: EntryMap + Clone> Clone for CacheCore<M> {
+    fn clone(&self) -> Self {
+        Self {
+            entries: self.entries.clone(),
+            config: self.config.clone(),
+            bloom_filter: self.bloom_filter.clone(),
+            bloom_audit: self.bloom_audit().map(Lock::new),
+            bloom_bypass: self.bloom_bypass.as_ref().map(|bypass| Lock::new(bypass.lock().clone())),
+            #[cfg(feature = "probabilistic")]
+            hot_keys: self.hot_keys.as_ref().map(|hot_keys| Lock::new(hot_keys.lock().clone())),
+            stats: StatsRecorder::new(self.now()),
+            audit_log: AuditLog::default(),
+            eviction: self.eviction.clone(),
+            listeners: Self::listeners_for(&self.config),
+            memory_budget: self.memory_budget.clone(),
+            store: Self::store_for(&self.config),
+            reads: ReadBuffer::default(),
+            #[cfg(feature = "persistence")]
+            snapshot_failures: AtomicU64::new(self.snapshot_failures.load(Ordering::Relaxed)),
+        }
+    }
+}
+
+/// Operations that only make sense for text values.
+impl<M: EntryMap<Value = String>> CacheCore<M> {
+    pub(crate) fn incr(&mut self, key: &str, delta: i64) -> Result<i64, CacheError> {
+        let result = match self.try_entry(key)? {
+            Entry::Occupied(mut entry) => {
+                let current: i64 = entry.get().parse().map_err(|_| CacheError::NotAnInteger {
+                    key: key.to_string(),
+                })?;
+                let updated = current.checked_add(delta).ok_or_else(|| CacheError::Overflow {
+                    key: key.to_string(),
+                })?;
+                entry.insert(&updated.to_string());
+                Ok(updated)
+            }
+            Entry::Vacant(entry) => {
+                // Chave ausente começa em zero
+                entry.insert(&delta.to_string());
+                Ok(delta)
+            }
+        };
+        self.enforce_memory_limit();
+        result
+    }
+
+    pub(crate) fn decr(&mut self, key: &str, delta: i64) -> Result<i64, CacheError> {
+        let negated = delta.checked_neg().ok_or_else(|| CacheError::Overflow {
+            key: key.to_string(),
+        })?;
+        self.incr(key, negated)
+    }
+
+    pub(crate) fn dump(&mut self, key: &str) -> Option<Vec<u8>> {
+        self.get(key).map(dump::encode)
+    }
+
+    pub(crate) fn restore(&mut self, key: &str, payload: &[u8], ttl: Option<Duration>) -> Result<(), CacheError> {
+        let value = dump::decode(payload)?;
+        self.try_insert_entry(key, CacheEntry::with_ttl(key, value.as_str(), ttl, self.now()))
+    }
+
+    /// Writes the live entries to `path`, replacing it only once the new
+    /// snapshot is complete.
+    #[cfg(feature = "persistence")]
+    pub(crate) fn save_snapshot(&self, path: &Path) -> io::Result<usize> {
+        let now = self.now();
+        let entries: Vec<_> = self
+            .live_in_walk_order()
+            .into_iter()
+            .map(|(key, entry)| (key.as_str(), entry.value(), entry.time_to_live(now)))
+            .collect();
+        let written = Self::write_snapshot(path, &entries);
+        // Só uma sequência de falhas deixa o cache degradado
+        match written {
+            Ok(()) => self.snapshot_failures.store(0, Ordering::Relaxed),
+            Err(_) => {
+                self.snapshot_failures.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+        written.map(|()| entries.len())
+    }
+
+    #[cfg(feature = "persistence")]
+    fn write_snapshot(path: &Path, entries: &[(&str, &str, Option<Duration>)]) -> io::Result<()> {
+        let mut partial = path.as_os_str().to_owned();
+        partial.push(".partial");
+        let mut file = BufWriter::new(File::create(&partial)?);
+        snapshot::write(&mut file, entries)?;
+        file.into_inner().map_err(io::IntoInnerError::into_error)?.sync_all()?;
+        fs::rename(&partial, path)
+    }
+
+    #[cfg(feature = "persistence")]
+    pub(crate) fn warm_from_snapshot(&mut self, path: &Path, progress: &mut dyn FnMut(usize)) -> io::Result<usize> {
+        let entries = snapshot::read(BufReader::new(File::open(path)?))?;
+        let now = self.now();
+        let entries = entries.into_iter().map(|entry| {
+            let stored = CacheEntry::with_ttl(&entry.key, entry.value.as_str(), entry.ttl, now);
+            (entry.key, stored)
+        });
+        Ok(self.warm(entries, progress))
+    }
+}
+
+impl<V: CacheValue> CacheCore<BTreeMap<String, CacheEntry<V>>> {
+    pub(crate) fn pop_first(&mut self) -> Option<(String, V)> {
+        self.pop_live(BTreeMap::pop_first)
+    }
+
+    pub(crate) fn pop_last(&mut self) -> Option<(String, V)> {
+        self.pop_live(BTreeMap::pop_last)
+    }
+
+    /// Pops entries from one end until a live one comes out; expired
+    /// entries met on the way are dropped as expirations.
+    fn pop_live<F>(&mut self, pop: F) -> Option<(String, V)>
+    where
+        F: Fn(&mut BTreeMap<String, CacheEntry<V>>) -> Option<(String, CacheEntry<V>)>,
+    {
+        self.begin_write().ok()?;
+        loop {
+            let (key, entry) = pop(&mut self.entries)?;
+            self.eviction.release(&key, &entry);
+            if entry.is_expired(self.now()) {
+                self.stats.record_expiration();
+                self.listeners.notify(&key, entry.value(), RemovalCause::Expired);
+            } else {
+                self.stats.record_removal();
+                self.store.deleted(&key);
+                self.listeners.notify(&key, entry.value(), RemovalCause::Removed);
+                return Some((key, entry.value));
+            }
+        }
+    }
+}
@@ -0,0 +1,218 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::Duration;
+
+use crate::value_index::ValueIndex;
+use crate::{CacheBuilder, DistributedHashTable};
+
+/// A content-addressed cache: identical values are stored once and shared
+/// by every key holding them.
+///
+/// Values live in a blob store keyed by content hash, with a reference
+/// count per blob; each key only stores the id of its blob. Writing a
+/// value that is already stored under another key bumps the count instead
+/// of copying the payload, and a blob is freed once the last key
+/// referencing it is removed, replaced, expires or is evicted. This suits
+/// workloads that cache the same payload under many keys, such as
+/// per-user copies of a global configuration.
+///
+/// Keys, TTLs and eviction are handled by an inner `DistributedHashTable`.
+/// Its `max_memory_bytes` limit only counts keys and blob ids, not the
+/// shared payloads; see [`value_bytes`](Self::value_bytes) for those.
+/// Its removal listeners always run inline, since they keep the reference
+/// counts: a `listener_queue` set on the builder is ignored.
+///
+/// # Examples
+///
+/// ```
+/// use spectra_cache::DedupCache;
+///
+/// let config = "{\"theme\":\"dark\",\"beta\":false}".repeat(100);
+/// let mut cache = DedupCache::new();
+/// for user in 0..1000 {
+///     cache.insert(&format!("config:{}", user), &config);
+/// }
+/// assert_eq!(cache.size(), 1000);
+/// assert_eq!(cache.unique_values(), 1);
+/// assert_eq!(cache.value_bytes(), config.len());
+/// assert_eq!(cache.get("config:7").as_deref(), Some(config.as_str()));
+/// ```
+#[derive(Debug)]
+pub struct DedupCache {
+    table: DistributedHashTable,
+    blobs: Arc<Mutex<BlobStore>>,
+}
+
+/// The shared payloads, by id, and the ids of the blobs with each content hash.
+#[derive(Debug, Default)]
+struct BlobStore {
+    blobs: HashMap<u64, Blob>,
+    by_hash: HashMap<u64, Vec<u64>>,
+    next_id: u64,
+    bytes: usize,
+}
+
+#[derive(Debug)]
+struct Blob {
+    value: Arc<str>,
+    hash: u64,
+    refs: usize,
+}
+
+impl BlobStore {
+    /// Takes a reference to the blob holding `value`, storing it first if
+    /// no key holds it yet. Returns the blob id.
+    fn acquire(&mut self, value: &str) -> u64 {
+        let hash = ValueIndex::hash(value);
+        let candidates = self.by_hash.entry(hash).or_default();
+        // Hashes iguais não garantem conteúdo igual: compara antes de reaproveitar
+        if let Some(&id) = candidates.iter().find(|id| &*self.blobs[id].value == value) {
+            self.blobs.get_mut(&id).expect("indexed blob exists").refs += 1;
+            return id;
+        }
+
+        let id = self.next_id;
+        self.next_id += 1;
+        candidates.push(id);
+        self.bytes += value.len();
+        self.blobs.insert(
+            id,
+            Blob {
+                value: Arc::from(value),
+                hash,
+                refs: 1,
+            },
+        );
+        id
+    }
+
+    /// Drops a reference, freeing the blob when it was the last one.
+    fn release(&mut self, id: u64) {
+        let Some(blob) = self.blobs.get_mut(&id) else {
+            return;
+        };
+        blob.refs -= 1;
+        if blob.refs > 0 {
+            return;
+        }
+        let blob = self.blobs.remove(&id).expect("blob was just found");
+        self.bytes -= blob.value.len();
+        if let Some(ids) = self.by_hash.get_mut(&blob.hash) {
+            ids.retain(|&other| other != id);
+            if ids.is_empty() {
+                self.by_hash.remove(&blob.hash);
+            }
+        }
+    }
+
+    fn get(&self, id: u64) -> Option<Arc<str>> {
+        self.blobs.get(&id).map(|blob| Arc::clone(&blob.value))
+    }
+}
+
+impl DedupCache {
+    /// Creates an empty cache.
+    pub fn new() -> Self {
+        Self::with_builder(DistributedHashTable::builder())
+    }
+
+    /// Creates an empty cache whose keys are managed by a table built from
+    /// `builder`, e.g. to set a default TTL or a memory limit.
+    pub fn with_builder(mut builder: CacheBuilder<DistributedHashTable>) -> Self {
+        // Notificações enfileiradas podem ser descartadas e vazariam referências
+        builder.config.listener_queue = None;
+        let blobs = Arc::new(Mutex::new(BlobStore::default()));
+        let mut table = builder.build();
+        let store = Arc::clone(&blobs);
+        // Toda saída de uma chave (remoção, troca, expiração, despejo) solta a referência
+        table.on_evict(move |_key, id, _cause| {
+            if let Some(id) = parse_id(id) {
+                lock(&store).release(id);
+            }
+        });
+        Self { table, blobs }
+    }
+
+    /// Returns the number of keys in the cache.
+    pub fn size(&self) -> usize {
+        self.table.size()
+    }
+
+    /// Returns true if the cache holds no keys.
+    pub fn is_empty(&self) -> bool {
+        self.table.is_empty()
+    }
+
+    /// Inserts `value` under `key`, sharing the stored copy if another key
+    /// already holds an identical value.
+    pub fn insert(&mut self, key: &str, value: &str) {
+        let id = self.acquire(value);
+        self.table.insert(key, &id);
+    }
+
+    /// Inserts `value` under `key`, expiring after `ttl`.
+    pub fn insert_with_ttl(&mut self, key: &str, value: &str, ttl: Duration) {
+        let id = self.acquire(value);
+        self.table.insert_with_ttl(key, &id, ttl);
+    }
+
+    /// Returns a shared handle to the value under `key`, without copying it.
+    pub fn get(&mut self, key: &str) -> Option<Arc<str>> {
+        let id = parse_id(self.table.get(key)?)?;
+        lock(&self.blobs).get(id)
+    }
+
+    /// Returns true if `key` holds a live value.
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.table.contains_key(key)
+    }
+
+    /// Removes `key`, returning its value.
+    pub fn remove(&mut self, key: &str) -> Option<Arc<str>> {
+        let id = parse_id(self.table.peek(key)?)?;
+        // Pega o valor antes que a remoção possa liberar o blob
+        let value = lock(&self.blobs).get(id);
+        self.table.remove(key);
+        value
+    }
+
+    /// Removes every key and frees every blob.
+    pub fn clear(&mut self) {
+        self.table.clear();
+    }
+
+    /// Returns the number of distinct values stored.
+    pub fn unique_values(&self) -> usize {
+        lock(&self.blobs).blobs.len()
+    }
+
+    /// Returns the number of payload bytes actually stored, each distinct
+    /// value counted once.
+    pub fn value_bytes(&self) -> usize {
+        lock(&self.blobs).bytes
+    }
+
+    /// Returns the inner table holding keys and blob ids, for statistics
+    /// and configuration.
+    pub fn table(&self) -> &DistributedHashTable {
+        &self.table
+    }
+
+    fn acquire(&self, value: &str) -> String {
+        format!("{:x}", lock(&self.blobs).acquire(value))
+    }
+}
+
+impl Default for DedupCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn parse_id(id: &str) -> Option<u64> {
+    u64::from_str_radix(id, 16).ok()
+}
+
+fn lock(blobs: &Mutex<BlobStore>) -> MutexGuard<'_, BlobStore> {
+    blobs.lock().unwrap_or_else(PoisonError::into_inner)
+}
@@ -0,0 +1,121 @@
+//! Serialized form of a single entry, produced by `dump()` and read back by
+//! `restore()`.
+//!
+//! The layout follows the shape of a Redis `DUMP` payload so it can travel
+//! as an opaque RESP bulk string:
+//!
+//! | Bytes | Meaning |
+//! |-------|---------|
+//! | 1 | Value type, `0` for a string |
+//! | 4 | Value length, big-endian |
+//! | n | Value bytes (UTF-8) |
+//! | 2 | Format version, little-endian |
+//! | 8 | CRC-64 (Jones) of everything before it, little-endian |
+//!
+//! Expiration is not part of the payload; the caller passes a TTL to
+//! `restore()`, as with Redis `RESTORE`.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::error::CacheError;
+
+/// Version written into every payload. Payloads from newer versions are rejected.
+pub(crate) const DUMP_VERSION: u16 = 1;
+
+const TYPE_STRING: u8 = 0;
+const TRAILER_LEN: usize = 2 + 8;
+
+/// Serializes a string value.
+pub(crate) fn encode(value: &str) -> Vec<u8> {
+    let mut payload = Vec::with_capacity(1 + 4 + value.len() + TRAILER_LEN);
+    payload.push(TYPE_STRING);
+    payload.extend_from_slice(&(value.len() as u32).to_be_bytes());
+    payload.extend_from_slice(value.as_bytes());
+    payload.extend_from_slice(&DUMP_VERSION.to_le_bytes());
+    let checksum = crc64(&payload);
+    payload.extend_from_slice(&checksum.to_le_bytes());
+    payload
+}
+
+/// Validates a payload and returns the value it carries.
+pub(crate) fn decode(payload: &[u8]) -> Result<String, CacheError> {
+    if payload.len() < 1 + 4 + TRAILER_LEN {
+        return Err(invalid("payload is truncated"));
+    }
+
+    let (body, checksum) = payload.split_at(payload.len() - 8);
+    if crc64(body) != u64::from_le_bytes(checksum.try_into().unwrap()) {
+        return Err(invalid("checksum mismatch"));
+    }
+
+    let (body, version) = body.split_at(body.len() - 2);
+    if u16::from_le_bytes(version.try_into().unwrap()) > DUMP_VERSION {
+        return Err(invalid("unsupported format version"));
+    }
+
+    if body[0] != TYPE_STRING {
+        return Err(invalid("unknown value type"));
+    }
+    let len = u32::from_be_bytes(body[1..5].try_into().unwrap()) as usize;
+    let value = &body[5..];
+    if value.len() != len {
+        return Err(invalid("value length mismatch"));
+    }
+
+    String::from_utf8(value.to_vec()).map_err(|_| invalid("value is not valid UTF-8"))
+}
+
+fn invalid(reason: &'static str) -> CacheError {
+    CacheError::InvalidDump { reason }
+}
+
+/// CRC-64 with the Jones polynomial, the variant Redis uses for `DUMP`.
+pub(crate) fn crc64(bytes: &[u8]) -> u64 {
+    const POLY: u64 = 0x95ac_9329_ac4b_c9b5; // Forma refletida
+
+    let mut crc = 0u64;
+    for &byte in bytes {
+        crc ^= byte as u64;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 { (crc >> 1) ^ POLY } else { crc >> 1 };
+        }
+    }
+    crc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_crc64_check_value() {
+        assert_eq!(crc64(b"123456789"), 0xe9c6_d914_c4b8_d9ca);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for value in ["", "John Doe", "ação"] {
+            assert_eq!(decode(&encode(value)).unwrap(), value);
+        }
+    }
+
+    #[test]
+    fn test_rejects_corruption() {
+        let mut payload = encode("value");
+        payload[6] ^= 0xff;
+        assert_eq!(decode(&payload), Err(invalid("checksum mismatch")));
+        assert_eq!(decode(&payload[..4]), Err(invalid("payload is truncated")));
+    }
+
+    #[test]
+    fn test_rejects_newer_versions() {
+        let mut payload = encode("value");
+        payload.truncate(payload.len() - TRAILER_LEN);
+        payload.extend_from_slice(&(DUMP_VERSION + 1).to_le_bytes());
+        let checksum = crc64(&payload);
+        payload.extend_from_slice(&checksum.to_le_bytes());
+
+        assert_eq!(decode(&payload), Err(invalid("unsupported format version")));
+    }
+}
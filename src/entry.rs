@@ -0,0 +1,283 @@
+use alloc::string::String;
+use core::mem;
+use core::sync::atomic::Ordering;
+use core::time::Duration;
+
+use crate::clock::Instant;
+use crate::eviction;
+use crate::expiry::ExpirySlot;
+use crate::sync::AtomicU64;
+use crate::value::CacheValue;
+
+/// The version the next write is stamped with, shared by every cache so a
+/// key never gets a version back after being removed and written again.
+static NEXT_VERSION: AtomicU64 = AtomicU64::new(1);
+
+fn next_version() -> u64 {
+    NEXT_VERSION.fetch_add(1, Ordering::Relaxed)
+}
+
+/// A single value stored in one of the caches, together with its
+/// expiration metadata.
+#[derive(Debug, Clone)]
+pub(crate) struct CacheEntry<V = String> {
+    pub(crate) value: V,
+    ttl: Option<Duration>,
+    idle_timeout: Option<Duration>,
+    created_at: Instant,
+    /// When the value was last written, by an insert or an in-place update
+    written_at: Instant,
+    last_accessed_at: Instant,
+    /// How many times the entry was used since it was stored
+    accesses: u64,
+    /// Stamped on every write of the value, higher for later writes
+    version: u64,
+    /// Position in the cache's recency order, assigned by `EvictionIndex`
+    pub(crate) recency: u64,
+    /// Position in the cache's deadline order, assigned by `EvictionIndex`
+    pub(crate) expiry: Option<ExpirySlot>,
+}
+
+/// What a cache knows about an entry besides its value, as yielded by
+/// `iter()`.
+///
+/// Durations are measured when the iterator reaches the entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EntryMetadata {
+    /// How long until the entry expires, `None` if it never does
+    pub time_to_live: Option<Duration>,
+    /// How long the entry may go unread, if it has an idle timeout
+    pub idle_timeout: Option<Duration>,
+    /// How long ago the entry was created
+    pub age: Duration,
+    /// How long ago the entry was last read or written
+    pub idle_time: Duration,
+    /// Approximate bytes the entry occupies, as counted by `memory_usage()`
+    pub size: usize,
+}
+
+impl<V: CacheValue> CacheEntry<V> {
+    /// Creates a new cache entry without TTL.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The unique identifier for this cache entry
+    /// * `value` - The data stored in this cache entry
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use spectra_cache::DistributedHashTable;
+    ///
+    /// let mut cache = DistributedHashTable::new();
+    /// cache.insert("user:123", "John Doe");
+    /// assert_eq!(cache.get("user:123"), Some("John Doe"));
+    /// ```
+    pub(crate) fn new(key: &str, value: &V::Ref, now: Instant) -> Self {
+        Self::with_ttl(key, value, None, now)
+    }
+
+    /// Creates a new cache entry with optional TTL.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The unique identifier for this cache entry
+    /// * `value` - The data stored in this cache entry
+    /// * `ttl` - Optional duration after which the entry expires
+    /// * `now` - The current time on the cache's clock
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use spectra_cache::DistributedHashTable;
+    /// use std::time::Duration;
+    ///
+    /// let mut cache = DistributedHashTable::new();
+    /// cache.insert_with_ttl("session:456", "active", Duration::from_secs(3600));
+    /// assert!(cache.contains_key("session:456"));
+    /// ```
+    pub(crate) fn with_ttl(_key: &str, value: &V::Ref, ttl: Option<Duration>, now: Instant) -> Self {
+        Self::from_value(V::from_ref(value), ttl, now)
+    }
+
+    /// Creates a new cache entry that takes ownership of `value` instead of
+    /// copying it, with optional TTL.
+    pub(crate) fn from_value(value: V, ttl: Option<Duration>, now: Instant) -> Self {
+        Self {
+            value,
+            ttl,
+            idle_timeout: None,
+            created_at: now,
+            written_at: now,
+            last_accessed_at: now,
+            accesses: 0,
+            version: next_version(),
+            recency: 0,
+            expiry: None,
+        }
+    }
+
+    /// Creates a new cache entry that expires after a period without access.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The unique identifier for this cache entry
+    /// * `value` - The data stored in this cache entry
+    /// * `idle_timeout` - How long the entry may go unread before it expires
+    /// * `now` - The current time on the cache's clock
+    pub(crate) fn with_tti(key: &str, value: &V::Ref, idle_timeout: Duration, now: Instant) -> Self {
+        let mut entry = Self::new(key, value, now);
+        entry.set_idle_timeout(Some(idle_timeout));
+        entry
+    }
+
+    /// Returns the value of the cache entry.
+    pub(crate) fn value(&self) -> &V::Ref {
+        self.value.view()
+    }
+
+    /// Replaces the value in place, keeping the TTL, and returns the old one.
+    pub(crate) fn replace_value(&mut self, value: &V::Ref, now: Instant) -> V {
+        self.written_at = now;
+        self.version = next_version();
+        mem::replace(&mut self.value, V::from_ref(value))
+    }
+
+    /// Returns the value for modification in place, counting it as a write.
+    pub(crate) fn value_mut(&mut self, now: Instant) -> &mut V {
+        self.written_at = now;
+        self.version = next_version();
+        &mut self.value
+    }
+
+    /// Returns the version of the value, which changes on every write.
+    pub(crate) fn version(&self) -> u64 {
+        self.version
+    }
+
+    /// Returns when the value was last written.
+    pub(crate) fn written_at(&self) -> Instant {
+        self.written_at
+    }
+
+    /// Checks if the entry has expired at `now` based on its TTL or idle
+    /// timeout.
+    ///
+    /// Returns `true` if the entry has a TTL and the current age exceeds it,
+    /// or if it has an idle timeout and hasn't been accessed for that long.
+    /// Returns `false` otherwise.
+    pub(crate) fn is_expired(&self, now: Instant) -> bool {
+        self.ttl.is_some_and(|ttl| self.age(now) > ttl)
+            || self.idle_timeout.is_some_and(|idle| self.idle_time(now) > idle)
+    }
+
+    /// Like `is_expired`, counting a read at `read_at` that hasn't been
+    /// applied to the entry yet towards its idle timeout.
+    pub(crate) fn is_expired_after_read(&self, read_at: Option<Instant>, now: Instant) -> bool {
+        match read_at {
+            Some(at) if at > self.last_accessed_at => {
+                self.ttl.is_some_and(|ttl| self.age(now) > ttl)
+                    || self.idle_timeout.is_some_and(|idle| now.saturating_duration_since(at) > idle)
+            }
+            _ => self.is_expired(now),
+        }
+    }
+
+    /// Returns how long from `now` until the entry expires, or `None` if it
+    /// never does.
+    ///
+    /// With both a TTL and an idle timeout, the sooner deadline wins.
+    pub(crate) fn time_to_live(&self, now: Instant) -> Option<Duration> {
+        let ttl = self.ttl.map(|ttl| ttl.saturating_sub(self.age(now)));
+        let idle = self.idle_timeout.map(|idle| idle.saturating_sub(self.idle_time(now)));
+        match (ttl, idle) {
+            (Some(ttl), Some(idle)) => Some(ttl.min(idle)),
+            (ttl, idle) => ttl.or(idle),
+        }
+    }
+
+    /// Returns the instant the entry expires at, or `None` if it never does.
+    pub(crate) fn deadline(&self) -> Option<Instant> {
+        let ttl = self.ttl_deadline();
+        let idle = self.idle_timeout().map(|idle| self.last_accessed_at + idle);
+        match (ttl, idle) {
+            (Some(ttl), Some(idle)) => Some(ttl.min(idle)),
+            (ttl, idle) => ttl.or(idle),
+        }
+    }
+
+    /// Returns the instant the entry's TTL runs out, ignoring its idle timeout.
+    pub(crate) fn ttl_deadline(&self) -> Option<Instant> {
+        self.ttl.map(|ttl| self.created_at + ttl)
+    }
+
+    /// Returns the idle timeout, if the entry has one.
+    pub(crate) fn idle_timeout(&self) -> Option<Duration> {
+        self.idle_timeout
+    }
+
+    /// Sets or clears the idle timeout, keeping the TTL.
+    pub(crate) fn set_idle_timeout(&mut self, idle_timeout: Option<Duration>) {
+        self.idle_timeout = idle_timeout;
+    }
+
+    /// Makes the entry expire `ttl` after `now`, replacing any previous TTL.
+    pub(crate) fn set_ttl(&mut self, ttl: Duration, now: Instant) {
+        // O TTL é contado a partir da criação da entrada
+        self.ttl = Some(self.age(now) + ttl);
+    }
+
+    /// Removes the TTL and idle timeout so the entry never expires.
+    ///
+    /// Returns true if the entry had either.
+    pub(crate) fn persist(&mut self) -> bool {
+        let had_expiry = self.ttl.is_some() || self.idle_timeout.is_some();
+        self.ttl = None;
+        self.idle_timeout = None;
+        had_expiry
+    }
+
+    /// Resets the creation, write and access times to `now`, restarting the
+    /// TTL and idle clocks.
+    pub(crate) fn refresh(&mut self, now: Instant) {
+        self.created_at = now;
+        self.written_at = now;
+        self.last_accessed_at = now;
+    }
+
+    /// Updates the last accessed time to `at` and counts `times` accesses.
+    ///
+    /// This method should be called whenever the entry is accessed
+    /// to maintain accurate idle time tracking. `at` is in the past for
+    /// reads made through `&self` and applied later.
+    pub(crate) fn touch_at(&mut self, at: Instant, times: u64) {
+        self.last_accessed_at = self.last_accessed_at.max(at);
+        self.accesses += times;
+    }
+
+    /// Returns how many times the entry was used since it was stored.
+    pub(crate) fn accesses(&self) -> u64 {
+        self.accesses
+    }
+
+    /// Describes the entry stored under `key` as of `now`.
+    pub(crate) fn metadata(&self, key: &str, now: Instant) -> EntryMetadata {
+        EntryMetadata {
+            time_to_live: self.time_to_live(now),
+            idle_timeout: self.idle_timeout,
+            age: self.age(now),
+            idle_time: self.idle_time(now),
+            size: eviction::entry_size(key, self),
+        }
+    }
+
+    /// Returns how long this entry has been in the cache at `now`.
+    pub(crate) fn age(&self, now: Instant) -> Duration {
+        now.saturating_duration_since(self.created_at)
+    }
+
+    /// Returns how long before `now` this entry was last accessed.
+    pub(crate) fn idle_time(&self, now: Instant) -> Duration {
+        now.saturating_duration_since(self.last_accessed_at)
+    }
+}
@@ -0,0 +1,405 @@
+use alloc::boxed::Box;
+use alloc::collections::btree_map;
+use alloc::string::String;
+use core::fmt;
+use core::hash::BuildHasher;
+use core::time::Duration;
+
+use crate::bloom::ScalableBloomFilter;
+use crate::collections::hash_map;
+use crate::entry::CacheEntry;
+use crate::eviction::EvictionIndex;
+use crate::listener::{RemovalCause, RemovalListeners};
+use crate::stats::StatsRecorder;
+use crate::store::WriteStore;
+use crate::value::CacheValue;
+
+/// A view into a single key of a cache, which may be occupied or vacant.
+///
+/// Returned by `entry()` on every cache type. The key is looked up once, so
+/// conditional insert-or-update doesn't pay for a second lookup. Expired
+/// entries are reported as vacant.
+///
+/// # Examples
+///
+/// ```
+/// use spectra_cache::DistributedHashTable;
+///
+/// let mut cache = DistributedHashTable::new();
+/// for _ in 0..3 {
+///     cache
+///         .entry("page:views")
+///         .and_modify(|views| *views = (views.parse::<u64>().unwrap() + 1).to_string())
+///         .or_insert("1");
+/// }
+/// assert_eq!(cache.get("page:views"), Some("3"));
+/// ```
+pub enum Entry<'a, V: CacheValue = String> {
+    Occupied(OccupiedEntry<'a, V>),
+    Vacant(VacantEntry<'a, V>),
+}
+
+impl<'a, V: CacheValue> Entry<'a, V> {
+    /// Returns the key of this entry.
+    pub fn key(&self) -> &str {
+        match self {
+            Entry::Occupied(entry) => entry.key(),
+            Entry::Vacant(entry) => entry.key(),
+        }
+    }
+
+    /// Returns the stored value, inserting `default` if the entry is vacant.
+    pub fn or_insert(self, default: &V::Ref) -> &'a V::Ref {
+        match self {
+            Entry::Occupied(entry) => entry.into_value(),
+            Entry::Vacant(entry) => entry.insert(default),
+        }
+    }
+
+    /// Returns the stored value, inserting the result of `default` if the
+    /// entry is vacant. `default` is only called for vacant entries.
+    pub fn or_insert_with<F: FnOnce() -> V>(self, default: F) -> &'a V::Ref {
+        match self {
+            Entry::Occupied(entry) => entry.into_value(),
+            Entry::Vacant(entry) => entry.insert(default().view()),
+        }
+    }
+
+    /// Modifies the value in place if the entry is occupied.
+    ///
+    /// The entry's TTL is kept; only its last access time is refreshed.
+    pub fn and_modify<F: FnOnce(&mut V)>(mut self, f: F) -> Self {
+        if let Entry::Occupied(entry) = &mut self {
+            let before = entry.eviction.footprint(entry.slot.key(), entry.slot.entry());
+            let now = entry.eviction.now();
+            let stored = entry.slot.entry_mut();
+            f(stored.value_mut(now));
+            entry.eviction.touch(stored);
+            entry.eviction.revalue(entry.slot.key(), before, entry.slot.entry());
+            entry.store.written(entry.slot.key(), entry.slot.entry(), now);
+        }
+        self
+    }
+}
+
+impl<V: CacheValue> fmt::Debug for Entry<'_, V>
+where
+    V::Ref: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Entry::Occupied(entry) => f.debug_tuple("Occupied").field(entry).finish(),
+            Entry::Vacant(entry) => f.debug_tuple("Vacant").field(entry).finish(),
+        }
+    }
+}
+
+/// A view into a live entry of a cache.
+pub struct OccupiedEntry<'a, V: CacheValue = String> {
+    slot: Box<dyn OccupiedSlot<'a, V> + 'a>,
+    stats: &'a mut StatsRecorder,
+    eviction: &'a mut EvictionIndex<V>,
+    listeners: &'a mut RemovalListeners<V>,
+    store: &'a WriteStore<V>,
+}
+
+impl<'a, V: CacheValue> OccupiedEntry<'a, V> {
+    /// Returns the key of this entry.
+    pub fn key(&self) -> &str {
+        self.slot.key()
+    }
+
+    /// Returns the stored value.
+    pub fn get(&self) -> &V::Ref {
+        self.slot.entry().value()
+    }
+
+    /// Replaces the stored value, returning the old one.
+    ///
+    /// The entry's TTL is kept.
+    pub fn insert(&mut self, value: &V::Ref) -> V {
+        let before = self.eviction.footprint(self.slot.key(), self.slot.entry());
+        let now = self.eviction.now();
+        let stored = self.slot.entry_mut();
+        let old = stored.replace_value(value, now);
+        self.eviction.touch(stored);
+        self.eviction.revalue(self.slot.key(), before, self.slot.entry());
+        self.store.written(self.slot.key(), self.slot.entry(), now);
+        self.listeners.notify(self.slot.key(), old.view(), RemovalCause::Replaced);
+        self.listeners.written(self.slot.key(), value, true);
+        old
+    }
+
+    /// Removes the entry from the cache, returning its value.
+    pub fn remove(self) -> V {
+        self.stats.record_removal();
+        let (key, removed) = self.slot.remove();
+        self.store.deleted(&key);
+        self.eviction.release(&key, &removed);
+        self.listeners.notify(&key, removed.value(), RemovalCause::Removed);
+        removed.value
+    }
+
+    /// Converts the entry into a reference to its value with the cache's lifetime.
+    pub fn into_value(self) -> &'a V::Ref {
+        let stored: &'a CacheEntry<V> = self.slot.into_mut();
+        stored.value()
+    }
+}
+
+impl<V: CacheValue> fmt::Debug for OccupiedEntry<'_, V>
+where
+    V::Ref: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OccupiedEntry")
+            .field("key", &self.key())
+            .field("value", &self.get())
+            .finish()
+    }
+}
+
+/// A view into a key with no live entry.
+pub struct VacantEntry<'a, V: CacheValue = String> {
+    slot: VacantSlotKind<'a, V>,
+    default_ttl: Option<Duration>,
+    bloom_filter: &'a mut dyn KeyFilter,
+    stats: &'a mut StatsRecorder,
+    eviction: &'a mut EvictionIndex<V>,
+    listeners: &'a mut RemovalListeners<V>,
+    store: &'a WriteStore<V>,
+}
+
+/// Where a vacant entry's value ends up: a fresh map slot, or the slot of an
+/// expired entry that gets overwritten in place.
+enum VacantSlotKind<'a, V> {
+    Fresh(Box<dyn VacantSlot<'a, V> + 'a>),
+    Expired(Box<dyn OccupiedSlot<'a, V> + 'a>),
+}
+
+impl<'a, V: CacheValue> VacantEntry<'a, V> {
+    /// Returns the key of this entry.
+    pub fn key(&self) -> &str {
+        match &self.slot {
+            VacantSlotKind::Fresh(slot) => slot.key(),
+            VacantSlotKind::Expired(slot) => slot.key(),
+        }
+    }
+
+    /// Inserts `value` under this entry's key, with the cache's default TTL.
+    pub fn insert(self, value: &V::Ref) -> &'a V::Ref {
+        let entry = CacheEntry::with_ttl(self.key(), value, self.default_ttl, self.eviction.now());
+        self.insert_entry(entry)
+    }
+
+    /// Inserts `value` under this entry's key, expiring after `ttl`.
+    pub fn insert_with_ttl(self, value: &V::Ref, ttl: Duration) -> &'a V::Ref {
+        let entry = CacheEntry::with_ttl(self.key(), value, Some(ttl), self.eviction.now());
+        self.insert_entry(entry)
+    }
+
+    fn insert_entry(self, mut entry: CacheEntry<V>) -> &'a V::Ref {
+        self.stats.record_insert();
+        self.store.written(self.key(), &entry, self.eviction.now());
+        let stored: &'a CacheEntry<V> = match self.slot {
+            VacantSlotKind::Fresh(slot) => {
+                self.bloom_filter.insert_key(slot.key());
+                self.eviction.admit(slot.key(), &mut entry);
+                self.listeners.written(slot.key(), entry.value(), false);
+                slot.insert(entry)
+            }
+            VacantSlotKind::Expired(slot) => {
+                self.stats.record_expiration();
+                self.eviction.release(slot.key(), slot.entry());
+                self.eviction.admit(slot.key(), &mut entry);
+                self.listeners.notify(slot.key(), slot.entry().value(), RemovalCause::Expired);
+                self.listeners.written(slot.key(), entry.value(), false);
+                let stored = slot.into_mut();
+                *stored = entry;
+                stored
+            }
+        };
+        stored.value()
+    }
+}
+
+impl<V: CacheValue> fmt::Debug for VacantEntry<'_, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("VacantEntry").field("key", &self.key()).finish()
+    }
+}
+
+/// The cache's Bloom filter as seen by a vacant entry, whatever its hasher.
+pub(crate) trait KeyFilter {
+    fn insert_key(&mut self, key: &str);
+}
+
+impl<S: BuildHasher + Clone> KeyFilter for ScalableBloomFilter<S> {
+    fn insert_key(&mut self, key: &str) {
+        self.insert(key);
+    }
+}
+
+/// Builds the public `Entry` for a map slot, treating expired entries as vacant.
+pub(crate) fn entry_for_slot<'a, V: CacheValue>(
+    slot: Slot<'a, V>,
+    default_ttl: Option<Duration>,
+    bloom_filter: &'a mut dyn KeyFilter,
+    stats: &'a mut StatsRecorder,
+    eviction: &'a mut EvictionIndex<V>,
+    listeners: &'a mut RemovalListeners<V>,
+    store: &'a WriteStore<V>,
+) -> Entry<'a, V> {
+    match slot {
+        Slot::Occupied(slot) if slot.entry().is_expired(eviction.now()) => Entry::Vacant(VacantEntry {
+            slot: VacantSlotKind::Expired(slot),
+            default_ttl,
+            bloom_filter,
+            stats,
+            eviction,
+            listeners,
+            store,
+        }),
+        Slot::Occupied(mut slot) => {
+            eviction.touch(slot.entry_mut());
+            Entry::Occupied(OccupiedEntry {
+                slot,
+                stats,
+                eviction,
+                listeners,
+                store,
+            })
+        }
+        Slot::Vacant(slot) => Entry::Vacant(VacantEntry {
+            slot: VacantSlotKind::Fresh(slot),
+            default_ttl,
+            bloom_filter,
+            stats,
+            eviction,
+            listeners,
+            store,
+        }),
+    }
+}
+
+/// The result of a single map lookup, erased over the map type.
+pub(crate) enum Slot<'a, V> {
+    Occupied(Box<dyn OccupiedSlot<'a, V> + 'a>),
+    Vacant(Box<dyn VacantSlot<'a, V> + 'a>),
+}
+
+/// The operations `Entry` needs from a native occupied map entry.
+pub(crate) trait OccupiedSlot<'a, V> {
+    fn key(&self) -> &String;
+    fn entry(&self) -> &CacheEntry<V>;
+    fn entry_mut(&mut self) -> &mut CacheEntry<V>;
+    fn into_mut(self: Box<Self>) -> &'a mut CacheEntry<V>;
+    fn remove(self: Box<Self>) -> (String, CacheEntry<V>);
+}
+
+/// The operations `Entry` needs from a native vacant map entry.
+pub(crate) trait VacantSlot<'a, V> {
+    fn key(&self) -> &String;
+    fn insert(self: Box<Self>, entry: CacheEntry<V>) -> &'a mut CacheEntry<V>;
+}
+
+#[cfg(feature = "std")]
+impl<'a, V> OccupiedSlot<'a, V> for hash_map::OccupiedEntry<'a, String, CacheEntry<V>> {
+    fn key(&self) -> &String {
+        hash_map::OccupiedEntry::key(self)
+    }
+
+    fn entry(&self) -> &CacheEntry<V> {
+        self.get()
+    }
+
+    fn entry_mut(&mut self) -> &mut CacheEntry<V> {
+        self.get_mut()
+    }
+
+    fn into_mut(self: Box<Self>) -> &'a mut CacheEntry<V> {
+        hash_map::OccupiedEntry::into_mut(*self)
+    }
+
+    fn remove(self: Box<Self>) -> (String, CacheEntry<V>) {
+        hash_map::OccupiedEntry::remove_entry(*self)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<'a, V> VacantSlot<'a, V> for hash_map::VacantEntry<'a, String, CacheEntry<V>> {
+    fn key(&self) -> &String {
+        hash_map::VacantEntry::key(self)
+    }
+
+    fn insert(self: Box<Self>, entry: CacheEntry<V>) -> &'a mut CacheEntry<V> {
+        hash_map::VacantEntry::insert(*self, entry)
+    }
+}
+
+// As entradas do hashbrown carregam o hasher do mapa no tipo
+#[cfg(not(feature = "std"))]
+impl<'a, V, S> OccupiedSlot<'a, V> for hash_map::OccupiedEntry<'a, String, CacheEntry<V>, S> {
+    fn key(&self) -> &String {
+        hash_map::OccupiedEntry::key(self)
+    }
+
+    fn entry(&self) -> &CacheEntry<V> {
+        self.get()
+    }
+
+    fn entry_mut(&mut self) -> &mut CacheEntry<V> {
+        self.get_mut()
+    }
+
+    fn into_mut(self: Box<Self>) -> &'a mut CacheEntry<V> {
+        hash_map::OccupiedEntry::into_mut(*self)
+    }
+
+    fn remove(self: Box<Self>) -> (String, CacheEntry<V>) {
+        hash_map::OccupiedEntry::remove_entry(*self)
+    }
+}
+
+#[cfg(not(feature = "std"))]
+impl<'a, V, S: BuildHasher> VacantSlot<'a, V> for hash_map::VacantEntry<'a, String, CacheEntry<V>, S> {
+    fn key(&self) -> &String {
+        hash_map::VacantEntry::key(self)
+    }
+
+    fn insert(self: Box<Self>, entry: CacheEntry<V>) -> &'a mut CacheEntry<V> {
+        hash_map::VacantEntry::insert(*self, entry)
+    }
+}
+
+impl<'a, V> OccupiedSlot<'a, V> for btree_map::OccupiedEntry<'a, String, CacheEntry<V>> {
+    fn key(&self) -> &String {
+        btree_map::OccupiedEntry::key(self)
+    }
+
+    fn entry(&self) -> &CacheEntry<V> {
+        self.get()
+    }
+
+    fn entry_mut(&mut self) -> &mut CacheEntry<V> {
+        self.get_mut()
+    }
+
+    fn into_mut(self: Box<Self>) -> &'a mut CacheEntry<V> {
+        btree_map::OccupiedEntry::into_mut(*self)
+    }
+
+    fn remove(self: Box<Self>) -> (String, CacheEntry<V>) {
+        btree_map::OccupiedEntry::remove_entry(*self)
+    }
+}
+
+impl<'a, V> VacantSlot<'a, V> for btree_map::VacantEntry<'a, String, CacheEntry<V>> {
+    fn key(&self) -> &String {
+        btree_map::VacantEntry::key(self)
+    }
+
+    fn insert(self: Box<Self>, entry: CacheEntry<V>) -> &'a mut CacheEntry<V> {
+        btree_map::VacantEntry::insert(*self, entry)
+    }
+}
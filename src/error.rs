@@ -0,0 +1,55 @@
+use alloc::string::String;
+use core::error::Error;
+use core::fmt;
+
+/// Errors returned by cache operations that can fail.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum CacheError {
+    /// The stored value can't be parsed as a 64-bit signed integer.
+    NotAnInteger { key: String },
+    /// The numeric result doesn't fit in a 64-bit signed integer.
+    Overflow { key: String },
+    /// A `restore()` payload is corrupt, truncated, or from a newer format version.
+    InvalidDump { reason: &'static str },
+    /// A memory limit string is not a percentage or a byte size.
+    InvalidMemoryLimit { value: String },
+    /// The node was put into read-only mode and rejects mutations.
+    ReadOnly,
+    /// A key was not produced by the `OrderedKey` encoder it is decoded with.
+    InvalidKey { key: String },
+    /// A sorted set score is NaN, or an increment would make it NaN.
+    InvalidScore { key: String },
+    /// A write was applied but couldn't be acknowledged with the requested durability.
+    NotDurable { reason: &'static str },
+    /// A key watched by a transaction changed, so none of its writes were applied.
+    TransactionConflict { key: String },
+    /// A versioned update found another version of the key, `None` if it was absent.
+    VersionMismatch { key: String, current: Option<u64> },
+    /// Persistence keeps failing and the cache was built to reject writes
+    /// until it recovers.
+    PersistenceFailed,
+}
+
+impl fmt::Display for CacheError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CacheError::NotAnInteger { key } => write!(f, "value of key '{}' is not an integer", key),
+            CacheError::Overflow { key } => write!(f, "increment of key '{}' would overflow", key),
+            CacheError::InvalidDump { reason } => write!(f, "invalid dump payload: {}", reason),
+            CacheError::InvalidMemoryLimit { value } => write!(f, "invalid memory limit '{}'", value),
+            CacheError::ReadOnly => write!(f, "cache is read-only"),
+            CacheError::InvalidKey { key } => write!(f, "'{}' is not an encoded ordered key", key),
+            CacheError::InvalidScore { key } => write!(f, "score for key '{}' is not a number", key),
+            CacheError::NotDurable { reason } => write!(f, "write not acknowledged as durable: {}", reason),
+            CacheError::TransactionConflict { key } => write!(f, "transaction aborted: watched key '{}' changed", key),
+            CacheError::VersionMismatch { key, current: Some(current) } => {
+                write!(f, "key '{}' is at version {}", key, current)
+            }
+            CacheError::VersionMismatch { key, current: None } => write!(f, "key '{}' does not exist", key),
+            CacheError::PersistenceFailed => write!(f, "persistence is failing, writes are rejected"),
+        }
+    }
+}
+
+impl Error for CacheError {}
@@ -0,0 +1,318 @@
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::any::Any;
+use core::fmt;
+use core::hash::Hash;
+use core::mem;
+
+use crate::clock::{Instant, SharedClock};
+use crate::entry::CacheEntry;
+use crate::expiry::ExpiryIndex;
+use crate::tag_index::TagIndex;
+use crate::value::CacheValue;
+use crate::value_index::ValueIndex;
+
+/// Fixed cost charged per entry on top of its key and value bytes: the
+/// `CacheEntry` itself plus the key's `String` header.
+const fn entry_overhead<V>() -> usize {
+    mem::size_of::<CacheEntry<V>>() + mem::size_of::<String>()
+}
+
+/// Returns the approximate number of bytes an entry occupies in the cache.
+pub(crate) fn entry_size<V: CacheValue>(key: &str, entry: &CacheEntry<V>) -> usize {
+    key.len() + entry.value.byte_len() + entry_overhead::<V>()
+}
+
+/// The cost of an entry as set with `CacheBuilder::weigher`.
+pub(crate) type Weigher<R> = Arc<dyn Fn(&str, &R) -> u64 + Send + Sync>;
+
+/// A weigher as kept by `CacheConfig`, with its value type erased like a
+/// backing store's.
+pub(crate) type ErasedWeigher = Arc<dyn Any + Send + Sync>;
+
+/// The bookkeeping eviction relies on: how many bytes are stored and how
+/// much they weigh, in which order entries were last used, and when they
+/// expire. Also which keys carry which tag, and optionally which keys hold
+/// which value.
+///
+/// Every entry carries the recency stamp it was last given, so moving it to
+/// the hot end is a removal and an insertion in the index, without
+/// allocating a new key. It likewise carries its slot in the expiry index.
+/// Weights aren't stored: the weigher is asked again when an entry leaves.
+pub(crate) struct EvictionIndex<V: CacheValue> {
+    memory_usage: usize,
+    weight: u64,
+    weigher: Option<Weigher<V::Ref>>,
+    recency: BTreeMap<u64, String>,
+    clock: u64,
+    /// The cache's clock, read for access times and deadlines
+    time: SharedClock,
+    expiry: ExpiryIndex,
+    tags: TagIndex,
+    values: Option<ValueIndex>,
+}
+
+/// The size, weight and value hash of an entry before an in-place change,
+/// taken with `footprint` and handed back to `revalue`.
+pub(crate) struct Footprint {
+    len: usize,
+    weight: u64,
+    hash: Option<u64>,
+}
+
+impl<V: CacheValue> EvictionIndex<V> {
+    /// Creates an index that also maps values to keys if `index_values` is
+    /// set, weighs entries with `weigher` instead of counting them, and
+    /// reads the time from `time`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `weigher` was made for a different value type.
+    pub(crate) fn new(index_values: bool, weigher: Option<&ErasedWeigher>, time: SharedClock) -> Self {
+        let weigher = weigher.map(|weigher| {
+            Arc::clone(weigher)
+                .downcast::<Weigher<V::Ref>>()
+                .map(|weigher| Weigher::clone(&weigher))
+                .unwrap_or_else(|_| panic!("weigher value type doesn't match the cache"))
+        });
+        Self {
+            values: index_values.then(ValueIndex::default),
+            weigher,
+            time,
+            ..Self::default()
+        }
+    }
+
+    /// Returns the current time on the cache's clock.
+    pub(crate) fn now(&self) -> Instant {
+        self.time.now()
+    }
+
+    pub(crate) fn memory_usage(&self) -> usize {
+        self.memory_usage
+    }
+
+    /// Returns the total weight of the stored entries: their count, unless
+    /// the cache has a weigher.
+    pub(crate) fn weight(&self) -> u64 {
+        self.weight
+    }
+
+    fn weigh(&self, key: &str, entry: &CacheEntry<V>) -> u64 {
+        self.weigher.as_ref().map_or(1, |weigher| weigher(key, entry.value()))
+    }
+
+    /// Makes room for `additional` more entries in the value index. The
+    /// recency and expiry orders are B-trees, which allocate node by node.
+    pub(crate) fn reserve(&mut self, additional: usize) {
+        if let Some(values) = self.values.as_mut() {
+            values.reserve(additional);
+        }
+    }
+
+    /// Starts tracking a newly stored entry as the most recently used one.
+    pub(crate) fn admit(&mut self, key: &str, entry: &mut CacheEntry<V>) {
+        self.memory_usage += entry_size(key, entry);
+        self.weight += self.weigh(key, entry);
+        entry.recency = self.tick();
+        self.recency.insert(entry.recency, key.to_string());
+        entry.expiry = self.expiry.schedule(key, entry.deadline());
+        if let Some(values) = &mut self.values {
+            values.add(key, ValueIndex::hash(entry.value()));
+        }
+    }
+
+    /// Stops tracking an entry that left the cache.
+    pub(crate) fn release(&mut self, key: &str, entry: &CacheEntry<V>) {
+        self.memory_usage = self.memory_usage.saturating_sub(entry_size(key, entry));
+        self.weight = self.weight.saturating_sub(self.weigh(key, entry));
+        self.recency.remove(&entry.recency);
+        self.expiry.cancel(entry.expiry);
+        self.tags.untag(key);
+        if let Some(values) = &mut self.values {
+            values.remove(key, ValueIndex::hash(entry.value()));
+        }
+    }
+
+    /// Files an entry again after its TTL or idle timeout changed.
+    pub(crate) fn reschedule(&mut self, key: &str, entry: &mut CacheEntry<V>) {
+        self.expiry.cancel(entry.expiry);
+        entry.expiry = self.expiry.schedule(key, entry.deadline());
+    }
+
+    /// Removes and returns the keys of entries whose deadline has passed.
+    ///
+    /// The entries stay in the cache and keep their bytes and recency; the
+    /// caller either releases them or, if a read pushed their deadline
+    /// back, reschedules them.
+    pub(crate) fn pop_due(&mut self, now: Instant) -> Vec<String> {
+        self.expiry.pop_due(now)
+    }
+
+    /// Marks an entry as used right now and refreshes its access time.
+    pub(crate) fn touch(&mut self, entry: &mut CacheEntry<V>) {
+        self.touch_at(entry, self.now(), 1);
+    }
+
+    /// Marks an entry as used `times`, last at `at`, moving it to the most
+    /// recently used end.
+    pub(crate) fn touch_at(&mut self, entry: &mut CacheEntry<V>, at: Instant, times: u64) {
+        entry.touch_at(at, times);
+        if let Some(key) = self.recency.remove(&entry.recency) {
+            entry.recency = self.tick();
+            self.recency.insert(entry.recency, key);
+        }
+    }
+
+    /// Records what an entry looks like before its value is changed in place.
+    pub(crate) fn footprint(&self, key: &str, entry: &CacheEntry<V>) -> Footprint {
+        Footprint {
+            len: entry.value.byte_len(),
+            weight: self.weigh(key, entry),
+            hash: self.values.as_ref().map(|_| ValueIndex::hash(entry.value())),
+        }
+    }
+
+    /// Accounts for an entry whose value changed in place since `before`
+    /// was taken.
+    pub(crate) fn revalue(&mut self, key: &str, before: Footprint, entry: &CacheEntry<V>) {
+        self.memory_usage = (self.memory_usage + entry.value.byte_len()).saturating_sub(before.len);
+        self.weight = (self.weight + self.weigh(key, entry)).saturating_sub(before.weight);
+        if let (Some(values), Some(old_hash)) = (&mut self.values, before.hash) {
+            values.remove(key, old_hash);
+            values.add(key, ValueIndex::hash(entry.value()));
+        }
+    }
+
+    /// Returns the keys that may hold `value`, or `None` if values aren't
+    /// indexed. Hashes can collide, so candidates must still be compared.
+    pub(crate) fn value_candidates<R: Hash + ?Sized>(&self, value: &R) -> Option<&[String]> {
+        let values = self.values.as_ref()?;
+        Some(values.candidates(ValueIndex::hash(value)))
+    }
+
+    /// Sets the tags of a stored entry. They last until the entry is
+    /// released, replaced included.
+    pub(crate) fn tag(&mut self, key: &str, tags: &[&str]) {
+        self.tags.tag(key, tags);
+    }
+
+    /// Returns the keys of the entries carrying `tag`, expired ones included.
+    pub(crate) fn tagged(&self, tag: &str) -> Vec<String> {
+        self.tags.keys(tag)
+    }
+
+    /// Returns the keys from the least to the most recently used, expired
+    /// entries included.
+    pub(crate) fn by_recency(&self) -> impl DoubleEndedIterator<Item = &String> {
+        self.recency.values()
+    }
+
+    /// Removes the least recently used entry from the recency order and
+    /// returns its key; the caller still has to `release` its bytes.
+    pub(crate) fn pop_coldest(&mut self) -> Option<String> {
+        self.recency.pop_first().map(|(_, key)| key)
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.memory_usage = 0;
+        self.weight = 0;
+        self.recency.clear();
+        self.expiry.clear();
+        self.tags.clear();
+        if let Some(values) = &mut self.values {
+            values.clear();
+        }
+    }
+
+    fn tick(&mut self) -> u64 {
+        self.clock += 1;
+        self.clock
+    }
+}
+
+impl<V: CacheValue> Default for EvictionIndex<V> {
+    fn default() -> Self {
+        Self {
+            memory_usage: 0,
+            weight: 0,
+            weigher: None,
+            recency: BTreeMap::new(),
+            clock: 0,
+            time: SharedClock::default(),
+            expiry: ExpiryIndex::default(),
+            tags: TagIndex::default(),
+            values: None,
+        }
+    }
+}
+
+impl<V: CacheValue> Clone for EvictionIndex<V> {
+    fn clone(&self) -> Self {
+        Self {
+            memory_usage: self.memory_usage,
+            weight: self.weight,
+            weigher: self.weigher.clone(),
+            recency: self.recency.clone(),
+            clock: self.clock,
+            time: self.time.clone(),
+            expiry: self.expiry.clone(),
+            tags: self.tags.clone(),
+            values: self.values.clone(),
+        }
+    }
+}
+
+impl<V: CacheValue> fmt::Debug for EvictionIndex<V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EvictionIndex")
+            .field("memory_usage", &self.memory_usage)
+            .field("weight", &self.weight)
+            .field("weighted", &self.weigher.is_some())
+            .field("recency", &self.recency)
+            .field("clock", &self.clock)
+            .field("expiry", &self.expiry)
+            .field("tags", &self.tags)
+            .field("values", &self.values)
+            .finish()
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_admit_and_release_balance() {
+        let mut index = EvictionIndex::<String>::default();
+        let mut entry = CacheEntry::<String>::new("key", "value", Instant::now());
+        index.admit("key", &mut entry);
+        assert_eq!(index.memory_usage(), 3 + 5 + entry_overhead::<String>());
+        assert_eq!(index.weight(), 1);
+
+        let before = index.footprint("key", &entry);
+        entry.value = "0123456789".to_string();
+        index.revalue("key", before, &entry);
+        assert_eq!(index.memory_usage(), 3 + 10 + entry_overhead::<String>());
+
+        index.release("key", &entry);
+        assert_eq!(index.memory_usage(), 0);
+        assert_eq!(index.weight(), 0);
+        assert_eq!(index.by_recency().next(), None);
+    }
+
+    #[test]
+    fn test_touch_moves_entry_to_hot_end() {
+        let mut index = EvictionIndex::<String>::default();
+        let mut a = CacheEntry::<String>::new("a", "1", index.now());
+        let mut b = CacheEntry::<String>::new("b", "2", index.now());
+        index.admit("a", &mut a);
+        index.admit("b", &mut b);
+        assert_eq!(index.by_recency().next().map(String::as_str), Some("a"));
+
+        index.touch(&mut a);
+        assert_eq!(index.by_recency().next().map(String::as_str), Some("b"));
+    }
+}
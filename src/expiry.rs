@@ -0,0 +1,94 @@
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use crate::clock::Instant;
+
+/// Where an entry sits in the `ExpiryIndex`: its deadline plus a sequence
+/// number that tells apart entries due at the same instant.
+pub(crate) type ExpirySlot = (Instant, u64);
+
+/// Entries with a TTL or idle timeout, ordered by deadline.
+///
+/// Lets expiration sweeps visit only the entries that are due instead of
+/// scanning the whole cache. Deadlines are only moved eagerly when they get
+/// earlier; reads that push an idle deadline back leave the slot where it
+/// is, and the sweep reschedules entries that turn out not to be expired
+/// yet.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ExpiryIndex {
+    deadlines: BTreeMap<ExpirySlot, String>,
+    sequence: u64,
+}
+
+impl ExpiryIndex {
+    /// Files `key` under `deadline`, returning the slot to keep on the entry.
+    pub(crate) fn schedule(&mut self, key: &str, deadline: Option<Instant>) -> Option<ExpirySlot> {
+        let slot = (deadline?, self.sequence);
+        self.sequence += 1;
+        self.deadlines.insert(slot, key.to_string());
+        Some(slot)
+    }
+
+    /// Forgets a slot returned by `schedule`.
+    pub(crate) fn cancel(&mut self, slot: Option<ExpirySlot>) {
+        if let Some(slot) = slot {
+            self.deadlines.remove(&slot);
+        }
+    }
+
+    /// Removes and returns the keys whose deadline is at or before `now`,
+    /// earliest first.
+    pub(crate) fn pop_due(&mut self, now: Instant) -> Vec<String> {
+        let mut due = Vec::new();
+        while let Some(entry) = self.deadlines.first_entry() {
+            if entry.key().0 > now {
+                break;
+            }
+            due.push(entry.remove());
+        }
+        due
+    }
+
+    #[cfg(all(test, feature = "std"))]
+    pub(crate) fn len(&self) -> usize {
+        self.deadlines.len()
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.deadlines.clear();
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_pops_only_due_keys_in_deadline_order() {
+        let now = Instant::now();
+        let mut index = ExpiryIndex::default();
+        index.schedule("late", Some(now + Duration::from_secs(60)));
+        index.schedule("second", Some(now - Duration::from_millis(1)));
+        index.schedule("first", Some(now - Duration::from_millis(2)));
+        assert_eq!(index.schedule("never", None), None);
+
+        assert_eq!(index.pop_due(now), vec!["first", "second"]);
+        assert_eq!(index.pop_due(now), Vec::<String>::new());
+        assert_eq!(index.len(), 1);
+    }
+
+    #[test]
+    fn test_cancel_removes_only_its_slot() {
+        let deadline = Some(Instant::now());
+        let mut index = ExpiryIndex::default();
+        let a = index.schedule("key", deadline);
+        index.schedule("key", deadline);
+
+        index.cancel(a);
+        index.cancel(None);
+        assert_eq!(index.len(), 1);
+        assert_eq!(index.pop_due(Instant::now()), vec!["key"]);
+    }
+}
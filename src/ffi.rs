@@ -0,0 +1,258 @@
+//! A C interface to `DistributedHashTable`, for embedding the cache in C
+//! and C++ programs.
+//!
+//! A cache is an opaque [`SpectraCache`] handle made by [`spectra_new`] and
+//! released with [`spectra_free`]. Keys and values cross the boundary as
+//! NUL-terminated UTF-8 strings; functions that can fail return a
+//! [`SpectraStatus`]. Values read with [`spectra_get`] are copies owned by
+//! the caller, who hands them back with [`spectra_string_free`].
+//!
+//! A handle isn't synchronized: calls on the same handle from several
+//! threads need a lock on the C side. A panic inside any of these functions
+//! aborts the process, since it can't unwind into C.
+//!
+//! The signatures are meant for cbindgen, configured by `cbindgen.toml`:
+//!
+//! ```text
+//! cargo rustc --lib --release --features ffi --crate-type cdylib
+//! cbindgen --config cbindgen.toml --output spectra.h
+//! ```
+
+use std::ffi::{c_char, CStr, CString};
+use std::time::Duration;
+
+use crate::DistributedHashTable;
+
+/// A cache owned by C code.
+pub struct SpectraCache {
+    table: DistributedHashTable,
+}
+
+/// The outcome of a call that can fail.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpectraStatus {
+    /// The call succeeded.
+    Ok = 0,
+    /// The key isn't in the cache, or has expired.
+    NotFound = 1,
+    /// A pointer argument was null.
+    NullArgument = 2,
+    /// A key or value wasn't valid UTF-8.
+    InvalidUtf8 = 3,
+    /// The value contains a NUL byte, so it can't be returned as a C string.
+    InteriorNul = 4,
+}
+
+/// Creates an empty cache with default settings.
+#[no_mangle]
+pub extern "C" fn spectra_new() -> *mut SpectraCache {
+    into_handle(DistributedHashTable::new())
+}
+
+/// Creates an empty cache that evicts entries once they take up more than
+/// `bytes`, as with `CacheBuilder::max_memory_bytes`.
+#[no_mangle]
+pub extern "C" fn spectra_new_with_memory_limit(bytes: usize) -> *mut SpectraCache {
+    into_handle(DistributedHashTable::builder().max_memory_bytes(bytes).build())
+}
+
+/// Destroys a cache. Null is ignored.
+///
+/// # Safety
+///
+/// `cache` must be null or a handle from [`spectra_new`] that hasn't been
+/// freed yet; it can't be used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn spectra_free(cache: *mut SpectraCache) {
+    if !cache.is_null() {
+        drop(Box::from_raw(cache));
+    }
+}
+
+/// Inserts `value` under `key`, replacing any value already there.
+///
+/// # Safety
+///
+/// `cache` must be a live handle, and `key` and `value` NUL-terminated
+/// strings.
+#[no_mangle]
+pub unsafe extern "C" fn spectra_insert(
+    cache: *mut SpectraCache,
+    key: *const c_char,
+    value: *const c_char,
+) -> SpectraStatus {
+    with_entry(cache, key, value, |table, key, value| table.insert(key, value))
+}
+
+/// Inserts `value` under `key`, expiring after `ttl_ms` milliseconds.
+///
+/// # Safety
+///
+/// Same as [`spectra_insert`].
+#[no_mangle]
+pub unsafe extern "C" fn spectra_insert_with_ttl(
+    cache: *mut SpectraCache,
+    key: *const c_char,
+    value: *const c_char,
+    ttl_ms: u64,
+) -> SpectraStatus {
+    with_entry(cache, key, value, |table, key, value| {
+        table.insert_with_ttl(key, value, Duration::from_millis(ttl_ms))
+    })
+}
+
+/// Looks up `key` and, if found, stores a copy of its value in `*out`.
+///
+/// `*out` is only written on [`SpectraStatus::Ok`]; the string must be
+/// released with [`spectra_string_free`].
+///
+/// # Safety
+///
+/// `cache` must be a live handle, `key` a NUL-terminated string and `out`
+/// a writable pointer.
+#[no_mangle]
+pub unsafe extern "C" fn spectra_get(
+    cache: *mut SpectraCache,
+    key: *const c_char,
+    out: *mut *mut c_char,
+) -> SpectraStatus {
+    if out.is_null() {
+        return SpectraStatus::NullArgument;
+    }
+    let (table, key) = match table_and_key(cache, key) {
+        Ok(found) => found,
+        Err(status) => return status,
+    };
+    let Some(value) = table.get(key) else {
+        return SpectraStatus::NotFound;
+    };
+    match CString::new(value) {
+        Ok(value) => {
+            *out = value.into_raw();
+            SpectraStatus::Ok
+        }
+        Err(_) => SpectraStatus::InteriorNul,
+    }
+}
+
+/// Returns [`SpectraStatus::Ok`] if `key` is in the cache and
+/// [`SpectraStatus::NotFound`] if it isn't.
+///
+/// # Safety
+///
+/// `cache` must be a live handle and `key` a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn spectra_contains(cache: *mut SpectraCache, key: *const c_char) -> SpectraStatus {
+    let (table, key) = match table_and_key(cache, key) {
+        Ok(found) => found,
+        Err(status) => return status,
+    };
+    if table.contains_key(key) {
+        SpectraStatus::Ok
+    } else {
+        SpectraStatus::NotFound
+    }
+}
+
+/// Removes `key`, returning [`SpectraStatus::NotFound`] if it wasn't there.
+///
+/// # Safety
+///
+/// `cache` must be a live handle and `key` a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn spectra_remove(cache: *mut SpectraCache, key: *const c_char) -> SpectraStatus {
+    match table_and_key(cache, key) {
+        Ok((table, key)) => match table.remove(key) {
+            Some(_) => SpectraStatus::Ok,
+            None => SpectraStatus::NotFound,
+        },
+        Err(status) => status,
+    }
+}
+
+/// Returns the number of entries in the cache, or 0 for null.
+///
+/// # Safety
+///
+/// `cache` must be null or a live handle.
+#[no_mangle]
+pub unsafe extern "C" fn spectra_len(cache: *const SpectraCache) -> usize {
+    cache.as_ref().map_or(0, |cache| cache.table.size())
+}
+
+/// Removes every entry. Null is ignored.
+///
+/// # Safety
+///
+/// `cache` must be null or a live handle.
+#[no_mangle]
+pub unsafe extern "C" fn spectra_clear(cache: *mut SpectraCache) {
+    if let Some(cache) = cache.as_mut() {
+        cache.table.clear();
+    }
+}
+
+/// Releases a string returned by [`spectra_get`]. Null is ignored.
+///
+/// # Safety
+///
+/// `value` must be null or a string from [`spectra_get`] that hasn't been
+/// freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn spectra_string_free(value: *mut c_char) {
+    if !value.is_null() {
+        drop(CString::from_raw(value));
+    }
+}
+
+fn into_handle(table: DistributedHashTable) -> *mut SpectraCache {
+    Box::into_raw(Box::new(SpectraCache { table }))
+}
+
+/// Borrows the table behind `cache` and reads `key`, or says which
+/// argument was bad.
+///
+/// # Safety
+///
+/// Same as [`spectra_contains`]; the lifetime is picked by the caller and
+/// must not outlive the call.
+unsafe fn table_and_key<'a>(
+    cache: *mut SpectraCache,
+    key: *const c_char,
+) -> Result<(&'a mut DistributedHashTable, &'a str), SpectraStatus> {
+    let cache = cache.as_mut().ok_or(SpectraStatus::NullArgument)?;
+    Ok((&mut cache.table, c_str(key)?))
+}
+
+/// # Safety
+///
+/// `value` must be null or a NUL-terminated string that outlives `'a`.
+unsafe fn c_str<'a>(value: *const c_char) -> Result<&'a str, SpectraStatus> {
+    if value.is_null() {
+        return Err(SpectraStatus::NullArgument);
+    }
+    CStr::from_ptr(value).to_str().map_err(|_| SpectraStatus::InvalidUtf8)
+}
+
+/// # Safety
+///
+/// Same as [`spectra_insert`].
+unsafe fn with_entry(
+    cache: *mut SpectraCache,
+    key: *const c_char,
+    value: *const c_char,
+    write: impl FnOnce(&mut DistributedHashTable, &str, &str),
+) -> SpectraStatus {
+    let (table, key) = match table_and_key(cache, key) {
+        Ok(found) => found,
+        Err(status) => return status,
+    };
+    match c_str(value) {
+        Ok(value) => {
+            write(table, key, value);
+            SpectraStatus::Ok
+        }
+        Err(status) => status,
+    }
+}
@@ -0,0 +1,35 @@
+//! The `f64` methods the Bloom filters and the rate meters use that `core`
+//! lacks, for `no_std` builds. With `std`, and in unit tests, which always link it, the
+//! inherent methods are used instead.
+
+pub(crate) trait FloatMath {
+    fn ln(self) -> f64;
+    #[cfg(feature = "core")]
+    fn exp(self) -> f64;
+    fn ceil(self) -> f64;
+    fn round(self) -> f64;
+    fn powi(self, n: i32) -> f64;
+}
+
+impl FloatMath for f64 {
+    fn ln(self) -> f64 {
+        libm::log(self)
+    }
+
+    #[cfg(feature = "core")]
+    fn exp(self) -> f64 {
+        libm::exp(self)
+    }
+
+    fn ceil(self) -> f64 {
+        libm::ceil(self)
+    }
+
+    fn round(self) -> f64 {
+        libm::round(self)
+    }
+
+    fn powi(self, n: i32) -> f64 {
+        libm::pow(self, f64::from(n))
+    }
+}
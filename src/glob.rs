@@ -0,0 +1,201 @@
+//! Redis-style glob matching for key patterns.
+//!
+//! Supported syntax:
+//! - `*` matches any sequence of characters, including none
+//! - `?` matches exactly one character
+//! - `[abc]`, `[a-z]` match one character from a set or range; `[^a]` or `[!a]` negate
+//! - `\` escapes the next character
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// A glob pattern parsed once and matched against many keys.
+#[derive(Debug, Clone)]
+pub(crate) struct Glob {
+    pattern: Vec<char>,
+}
+
+impl Glob {
+    pub(crate) fn new(pattern: &str) -> Self {
+        Self {
+            pattern: pattern.chars().collect(),
+        }
+    }
+
+    /// Returns true if `text` matches the pattern.
+    pub(crate) fn matches(&self, text: &str) -> bool {
+        let text: Vec<char> = text.chars().collect();
+        matches_chars(&self.pattern, &text)
+    }
+}
+
+/// Returns true if `text` matches the glob `pattern`.
+#[cfg(test)]
+fn matches(pattern: &str, text: &str) -> bool {
+    Glob::new(pattern).matches(text)
+}
+
+fn matches_chars(pattern: &[char], text: &[char]) -> bool {
+
+    let (mut p, mut t) = (0, 0);
+    // Posição do último `*` e do texto quando ele foi visto, para backtracking
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        if p < pattern.len() {
+            match pattern[p] {
+                '*' => {
+                    backtrack = Some((p, t));
+                    p += 1;
+                    continue;
+                }
+                '?' => {
+                    p += 1;
+                    t += 1;
+                    continue;
+                }
+                '[' => {
+                    if let Some((matched, next)) = match_class(pattern, p, text[t]) {
+                        if matched {
+                            p = next;
+                            t += 1;
+                            continue;
+                        }
+                    } else if text[t] == '[' {
+                        // Classe sem `]` de fechamento é tratada como literal
+                        p += 1;
+                        t += 1;
+                        continue;
+                    }
+                }
+                '\\' if p + 1 < pattern.len() => {
+                    if pattern[p + 1] == text[t] {
+                        p += 2;
+                        t += 1;
+                        continue;
+                    }
+                }
+                c => {
+                    if c == text[t] {
+                        p += 1;
+                        t += 1;
+                        continue;
+                    }
+                }
+            }
+        }
+
+        match backtrack {
+            Some((star, star_t)) => {
+                p = star + 1;
+                t = star_t + 1;
+                backtrack = Some((star, star_t + 1));
+            }
+            None => return false,
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// Returns the literal text every match of `pattern` must start with.
+///
+/// Ordered stores use this to narrow a scan to a key range.
+pub(crate) fn literal_prefix(pattern: &str) -> String {
+    let mut prefix = String::new();
+    let mut chars = pattern.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' | '?' | '[' => break,
+            '\\' => match chars.next() {
+                Some(escaped) => prefix.push(escaped),
+                None => prefix.push('\\'),
+            },
+            c => prefix.push(c),
+        }
+    }
+    prefix
+}
+
+/// Matches `c` against the character class starting at `pattern[start]`.
+///
+/// Returns whether it matched and the index just past the closing `]`, or
+/// None if the class is never closed.
+fn match_class(pattern: &[char], start: usize, c: char) -> Option<(bool, usize)> {
+    let mut i = start + 1;
+    let negated = matches!(pattern.get(i), Some('^') | Some('!'));
+    if negated {
+        i += 1;
+    }
+
+    let mut matched = false;
+    let mut first = true;
+    while i < pattern.len() {
+        let mut low = pattern[i];
+        if low == ']' && !first {
+            return Some((matched != negated, i + 1));
+        }
+        first = false;
+        if low == '\\' && i + 1 < pattern.len() {
+            i += 1;
+            low = pattern[i];
+        }
+
+        if i + 2 < pattern.len() && pattern[i + 1] == '-' && pattern[i + 2] != ']' {
+            let high = pattern[i + 2];
+            if low <= c && c <= high {
+                matched = true;
+            }
+            i += 3;
+        } else {
+            if low == c {
+                matched = true;
+            }
+            i += 1;
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wildcards() {
+        assert!(matches("user:*", "user:42"));
+        assert!(matches("user:*", "user:"));
+        assert!(!matches("user:*", "session:1"));
+        assert!(matches("*:42", "user:42"));
+        assert!(matches("a*b*c", "aXXbYYc"));
+        assert!(!matches("a*b*c", "aXXbYY"));
+        assert!(matches("session:??7", "session:127"));
+        assert!(!matches("session:??7", "session:17"));
+        assert!(matches("*", ""));
+    }
+
+    #[test]
+    fn test_character_classes() {
+        assert!(matches("key[12]", "key1"));
+        assert!(!matches("key[12]", "key3"));
+        assert!(matches("key[a-c]", "keyb"));
+        assert!(!matches("key[^a-c]", "keyb"));
+        assert!(matches("key[!a-c]", "keyz"));
+        assert!(matches("key[", "key["));
+    }
+
+    #[test]
+    fn test_escapes() {
+        assert!(matches("what\\?", "what?"));
+        assert!(!matches("what\\?", "whatx"));
+        assert!(matches("star\\*", "star*"));
+    }
+
+    #[test]
+    fn test_literal_prefix() {
+        assert_eq!(literal_prefix("user:*"), "user:");
+        assert_eq!(literal_prefix("session:??7"), "session:");
+        assert_eq!(literal_prefix("a\\*b*"), "a*b");
+        assert_eq!(literal_prefix("*"), "");
+    }
+}
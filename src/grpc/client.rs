@@ -0,0 +1,103 @@
+use std::time::Duration;
+
+use tokio_stream::{Stream, StreamExt};
+use tonic::transport::{Channel, Endpoint};
+use tonic::Status;
+
+use super::proto::spectra_cache_client::SpectraCacheClient;
+use super::proto::{DeleteRequest, GetRequest, ScanRequest, SetRequest, SetWithTtlRequest, WatchRequest};
+use crate::CacheEvent;
+
+/// A client for a [`GrpcServer`](super::GrpcServer).
+///
+/// Cloning is cheap and clones share the connection, so one client can be
+/// handed to every task. Failed calls return the server's `tonic::Status`.
+///
+/// # Examples
+///
+/// ```no_run
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// use spectra_cache::SpectraClient;
+/// use std::time::Duration;
+///
+/// let mut client = SpectraClient::connect("http://127.0.0.1:50051").await?;
+/// client.set_with_ttl("session:1", "active", Duration::from_secs(60)).await?;
+/// assert_eq!(client.get("session:1").await?.as_deref(), Some("active"));
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// # });
+/// ```
+#[derive(Debug, Clone)]
+pub struct SpectraClient {
+    inner: SpectraCacheClient<Channel>,
+}
+
+impl SpectraClient {
+    /// Connects to the server at `endpoint`, a URI such as
+    /// `http://127.0.0.1:50051`.
+    pub async fn connect(endpoint: impl Into<String>) -> Result<Self, tonic::transport::Error> {
+        let channel = Endpoint::from_shared(endpoint.into())?.connect().await?;
+        Ok(Self {
+            inner: SpectraCacheClient::new(channel),
+        })
+    }
+
+    /// Returns the value under `key`, or `None` if it is missing or expired.
+    pub async fn get(&mut self, key: &str) -> Result<Option<String>, Status> {
+        let request = GetRequest { key: key.to_string() };
+        Ok(self.inner.get(request).await?.into_inner().value)
+    }
+
+    /// Stores `value` under `key`, with the table's default TTL if it has one.
+    pub async fn set(&mut self, key: &str, value: &str) -> Result<(), Status> {
+        let request = SetRequest {
+            key: key.to_string(),
+            value: value.to_string(),
+        };
+        self.inner.set(request).await?;
+        Ok(())
+    }
+
+    /// Stores `value` under `key`, expiring after `ttl`, which is sent in
+    /// whole milliseconds and must be at least one.
+    pub async fn set_with_ttl(&mut self, key: &str, value: &str, ttl: Duration) -> Result<(), Status> {
+        let request = SetWithTtlRequest {
+            key: key.to_string(),
+            value: value.to_string(),
+            ttl_ms: ttl.as_millis().try_into().unwrap_or(u64::MAX),
+        };
+        self.inner.set_with_ttl(request).await?;
+        Ok(())
+    }
+
+    /// Removes `key`, returning true if it was live.
+    pub async fn delete(&mut self, key: &str) -> Result<bool, Status> {
+        let request = DeleteRequest { key: key.to_string() };
+        Ok(self.inner.delete(request).await?.into_inner().deleted)
+    }
+
+    /// Returns a page of at most `limit` live entries after `cursor`, and
+    /// the cursor of the next page, as [`DistributedHashTable::scan`](crate::DistributedHashTable::scan) does.
+    pub async fn scan(
+        &mut self,
+        cursor: Option<&str>,
+        limit: u32,
+    ) -> Result<(Vec<(String, String)>, Option<String>), Status> {
+        let request = ScanRequest {
+            cursor: cursor.map(str::to_string),
+            limit,
+        };
+        let page = self.inner.scan(request).await?.into_inner();
+        let entries = page.entries.into_iter().map(|entry| (entry.key, entry.value)).collect();
+        Ok((entries, page.next_cursor))
+    }
+
+    /// Streams every change to the keys matching the glob `pattern`, from
+    /// now until the stream is dropped.
+    pub async fn watch(&mut self, pattern: &str) -> Result<impl Stream<Item = Result<CacheEvent, Status>>, Status> {
+        let request = WatchRequest {
+            pattern: pattern.to_string(),
+        };
+        let events = self.inner.watch(request).await?.into_inner();
+        Ok(events.map(|event| event.and_then(CacheEvent::try_from)))
+    }
+}
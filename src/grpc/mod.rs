@@ -0,0 +1,22 @@
+//! A gRPC front end for `DistributedHashTable`, for running the cache as a
+//! sidecar shared by several services.
+//!
+//! [`GrpcServer`] serves a table with tonic, and [`SpectraClient`] talks to
+//! it from Rust. The service is described in `proto/spectra.proto` for
+//! clients in other languages; [`proto`] holds its messages and the
+//! generated tonic server and client.
+
+// `tonic::Status` é grande, mas é o erro que o código do tonic espera
+#![allow(clippy::result_large_err)]
+
+mod client;
+pub mod proto;
+mod service;
+
+pub use client::SpectraClient;
+pub use service::GrpcServer;
+
+/// The request metadata key under which a client attaches a unique write ID
+/// to `Set`, `SetWithTtl` and `Delete`, making retries safe; see
+/// [`GrpcServer`].
+pub const WRITE_ID_HEADER: &str = "write-id";
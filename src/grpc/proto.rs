@@ -0,0 +1,136 @@
+//! The messages of the gRPC service, mirroring `proto/spectra.proto`, and
+//! the tonic server and client generated for it.
+
+use crate::CacheEvent;
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct GetRequest {
+    #[prost(string, tag = "1")]
+    pub key: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct GetResponse {
+    #[prost(string, optional, tag = "1")]
+    pub value: Option<String>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct SetRequest {
+    #[prost(string, tag = "1")]
+    pub key: String,
+    #[prost(string, tag = "2")]
+    pub value: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct SetWithTtlRequest {
+    #[prost(string, tag = "1")]
+    pub key: String,
+    #[prost(string, tag = "2")]
+    pub value: String,
+    #[prost(uint64, tag = "3")]
+    pub ttl_ms: u64,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct SetResponse {}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct DeleteRequest {
+    #[prost(string, tag = "1")]
+    pub key: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct DeleteResponse {
+    #[prost(bool, tag = "1")]
+    pub deleted: bool,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct ScanRequest {
+    #[prost(string, optional, tag = "1")]
+    pub cursor: Option<String>,
+    #[prost(uint32, tag = "2")]
+    pub limit: u32,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Entry {
+    #[prost(string, tag = "1")]
+    pub key: String,
+    #[prost(string, tag = "2")]
+    pub value: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct ScanResponse {
+    #[prost(message, repeated, tag = "1")]
+    pub entries: Vec<Entry>,
+    #[prost(string, optional, tag = "2")]
+    pub next_cursor: Option<String>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct WatchRequest {
+    #[prost(string, tag = "1")]
+    pub pattern: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct WatchEvent {
+    #[prost(enumeration = "Kind", tag = "1")]
+    pub kind: i32,
+    #[prost(string, tag = "2")]
+    pub key: String,
+    #[prost(string, optional, tag = "3")]
+    pub value: Option<String>,
+}
+
+/// What happened to the key of a `WatchEvent`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, prost::Enumeration)]
+#[repr(i32)]
+pub enum Kind {
+    Insert = 0,
+    Update = 1,
+    Remove = 2,
+    Expire = 3,
+    Evict = 4,
+}
+
+impl From<CacheEvent> for WatchEvent {
+    fn from(event: CacheEvent) -> Self {
+        let (kind, key, value) = match event {
+            CacheEvent::Insert { key, value } => (Kind::Insert, key, Some(value)),
+            CacheEvent::Update { key, value } => (Kind::Update, key, Some(value)),
+            CacheEvent::Remove { key } => (Kind::Remove, key, None),
+            CacheEvent::Expire { key } => (Kind::Expire, key, None),
+            CacheEvent::Evict { key } => (Kind::Evict, key, None),
+        };
+        Self {
+            kind: kind as i32,
+            key,
+            value,
+        }
+    }
+}
+
+impl TryFrom<WatchEvent> for CacheEvent {
+    type Error = tonic::Status;
+
+    fn try_from(event: WatchEvent) -> Result<Self, Self::Error> {
+        let WatchEvent { kind, key, value } = event;
+        let kind = Kind::try_from(kind).map_err(|_| tonic::Status::internal(format!("unknown event kind {}", kind)))?;
+        Ok(match (kind, value) {
+            (Kind::Insert, Some(value)) => CacheEvent::Insert { key, value },
+            (Kind::Update, Some(value)) => CacheEvent::Update { key, value },
+            (Kind::Insert | Kind::Update, None) => return Err(tonic::Status::internal("write event without a value")),
+            (Kind::Remove, _) => CacheEvent::Remove { key },
+            (Kind::Expire, _) => CacheEvent::Expire { key },
+            (Kind::Evict, _) => CacheEvent::Evict { key },
+        })
+    }
+}
+
+include!(concat!(env!("OUT_DIR"), "/spectra.SpectraCache.rs"));
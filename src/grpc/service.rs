@@ -0,0 +1,203 @@
+use std::sync::mpsc::RecvTimeoutError;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::thread;
+use std::time::Duration;
+
+use tokio::net::TcpListener;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::{ReceiverStream, TcpListenerStream};
+use tonic::metadata::MetadataMap;
+use tonic::{Request, Response, Status};
+
+use super::proto::spectra_cache_server::{SpectraCache, SpectraCacheServer};
+use super::proto::{
+    DeleteRequest, DeleteResponse, Entry, GetRequest, GetResponse, ScanRequest, ScanResponse, SetRequest,
+    SetResponse, SetWithTtlRequest, WatchEvent, WatchRequest,
+};
+use super::WRITE_ID_HEADER;
+use crate::{DistributedHashTable, ReplayGuard};
+
+/// Events a `Watch` stream buffers for a client that reads slower than
+/// the table changes; past that, the table's subscription grows instead.
+const WATCH_BUFFER: usize = 256;
+
+/// How often an idle `Watch` stream checks whether its client went away.
+const WATCH_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// How many recent write IDs the server remembers.
+const WRITE_ID_WINDOW: usize = 100_000;
+
+/// Serves a `DistributedHashTable` over gRPC.
+///
+/// Implements the `spectra.SpectraCache` service of `proto/spectra.proto`:
+///
+/// | Method | Notes |
+/// |--------|-------|
+/// | `Get` | `value` is unset for a missing or expired key |
+/// | `Set` | |
+/// | `SetWithTtl` | `ttl_ms` must be positive |
+/// | `Delete` | `deleted` tells whether the key was live |
+/// | `Scan` | Pages of at most `limit` entries; see [`DistributedHashTable::scan`] |
+/// | `Watch` | Streams the changes to keys matching a glob pattern; see [`DistributedHashTable::subscribe`] |
+///
+/// Requests are applied one at a time under a lock on the table, which
+/// in-process code can share through [`table`](Self::table). Invalid
+/// arguments are answered with `INVALID_ARGUMENT`.
+///
+/// `Set`, `SetWithTtl` and `Delete` can carry a unique ID in the
+/// [`WRITE_ID_HEADER`](super::WRITE_ID_HEADER) request metadata, so a client
+/// can retry them after a timeout: a write whose ID was already seen is not
+/// applied again and is answered like the original. The server remembers
+/// the most recent 100 000 IDs through a [`ReplayGuard`].
+///
+/// # Examples
+///
+/// ```no_run
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// use spectra_cache::{DistributedHashTable, GrpcServer};
+/// use tokio::net::TcpListener;
+///
+/// let server = GrpcServer::new(DistributedHashTable::new());
+/// server.serve(TcpListener::bind("127.0.0.1:50051").await?).await?;
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// # });
+/// ```
+#[derive(Debug, Clone)]
+pub struct GrpcServer {
+    table: Arc<Mutex<DistributedHashTable>>,
+    write_ids: Arc<Mutex<ReplayGuard<Written>>>,
+}
+
+/// The answer to a write, remembered under its write ID.
+#[derive(Debug, Clone, Copy)]
+enum Written {
+    Set,
+    Delete { deleted: bool },
+}
+
+impl GrpcServer {
+    /// Creates a server exposing `table`.
+    pub fn new(table: DistributedHashTable) -> Self {
+        Self {
+            table: Arc::new(Mutex::new(table)),
+            write_ids: Arc::new(Mutex::new(ReplayGuard::new(WRITE_ID_WINDOW))),
+        }
+    }
+
+    /// Locks and returns the served table, for use by the process hosting
+    /// the server.
+    pub fn table(&self) -> MutexGuard<'_, DistributedHashTable> {
+        self.table.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Wraps the server as a tonic service, to be mounted next to others
+    /// on a `tonic::transport::Server`.
+    pub fn into_service(self) -> SpectraCacheServer<Self> {
+        SpectraCacheServer::new(self)
+    }
+
+    /// Accepts connections on `listener` until the server fails.
+    pub async fn serve(self, listener: TcpListener) -> Result<(), tonic::transport::Error> {
+        tonic::transport::Server::builder()
+            .add_service(self.into_service())
+            .serve_with_incoming(TcpListenerStream::new(listener))
+            .await
+    }
+
+    /// Applies a write, or answers a retried one with its original outcome
+    /// if `metadata` carries a write ID that was already seen.
+    fn write(
+        &self,
+        metadata: &MetadataMap,
+        apply: impl FnOnce(&mut DistributedHashTable) -> Written,
+    ) -> Result<Written, Status> {
+        let Some(write_id) = metadata.get(WRITE_ID_HEADER) else {
+            return Ok(apply(&mut self.table()));
+        };
+        let write_id = write_id
+            .to_str()
+            .map_err(|_| Status::invalid_argument(format!("{} must be ASCII", WRITE_ID_HEADER)))?;
+        // O guarda fica travado durante a escrita: uma repetição concorrente espera pelo original
+        let mut write_ids = self.write_ids.lock().unwrap_or_else(PoisonError::into_inner);
+        Ok(write_ids.execute(write_id, || apply(&mut self.table())))
+    }
+}
+
+#[tonic::async_trait]
+impl SpectraCache for GrpcServer {
+    async fn get(&self, request: Request<GetRequest>) -> Result<Response<GetResponse>, Status> {
+        let value = self.table().get(&request.into_inner().key).map(str::to_string);
+        Ok(Response::new(GetResponse { value }))
+    }
+
+    async fn set(&self, request: Request<SetRequest>) -> Result<Response<SetResponse>, Status> {
+        let (metadata, _, SetRequest { key, value }) = request.into_parts();
+        self.write(&metadata, |table| {
+            table.insert(&key, &value);
+            Written::Set
+        })?;
+        Ok(Response::new(SetResponse {}))
+    }
+
+    async fn set_with_ttl(&self, request: Request<SetWithTtlRequest>) -> Result<Response<SetResponse>, Status> {
+        let (metadata, _, SetWithTtlRequest { key, value, ttl_ms }) = request.into_parts();
+        if ttl_ms == 0 {
+            return Err(Status::invalid_argument("ttl_ms must be positive"));
+        }
+        self.write(&metadata, |table| {
+            table.insert_with_ttl(&key, &value, Duration::from_millis(ttl_ms));
+            Written::Set
+        })?;
+        Ok(Response::new(SetResponse {}))
+    }
+
+    async fn delete(&self, request: Request<DeleteRequest>) -> Result<Response<DeleteResponse>, Status> {
+        let (metadata, _, DeleteRequest { key }) = request.into_parts();
+        let written = self.write(&metadata, |table| Written::Delete {
+            deleted: table.remove(&key).is_some(),
+        })?;
+        // Um ID já usado por um Set não apagou nada
+        let deleted = matches!(written, Written::Delete { deleted: true });
+        Ok(Response::new(DeleteResponse { deleted }))
+    }
+
+    async fn scan(&self, request: Request<ScanRequest>) -> Result<Response<ScanResponse>, Status> {
+        let ScanRequest { cursor, limit } = request.into_inner();
+        if limit == 0 {
+            return Err(Status::invalid_argument("limit must be positive"));
+        }
+        let table = self.table();
+        let (page, next_cursor) = table.scan(cursor.as_deref(), limit as usize);
+        let entries = page
+            .into_iter()
+            .map(|(key, value)| Entry {
+                key: key.clone(),
+                value: value.to_string(),
+            })
+            .collect();
+        Ok(Response::new(ScanResponse { entries, next_cursor }))
+    }
+
+    type WatchStream = ReceiverStream<Result<WatchEvent, Status>>;
+
+    async fn watch(&self, request: Request<WatchRequest>) -> Result<Response<Self::WatchStream>, Status> {
+        let events = self.table().subscribe(&request.into_inner().pattern);
+        let (sender, receiver) = mpsc::channel(WATCH_BUFFER);
+        // A assinatura da tabela é um canal síncrono: uma thread repassa os eventos
+        thread::Builder::new()
+            .name("spectra-cache-grpc-watch".to_string())
+            .spawn(move || loop {
+                match events.recv_timeout(WATCH_POLL_INTERVAL) {
+                    Ok(event) => {
+                        if sender.blocking_send(Ok(event.into())).is_err() {
+                            break;
+                        }
+                    }
+                    Err(RecvTimeoutError::Timeout) if !sender.is_closed() => {}
+                    Err(_) => break,
+                }
+            })
+            .map_err(|error| Status::internal(format!("cannot start watch: {}", error)))?;
+        Ok(Response::new(ReceiverStream::new(receiver)))
+    }
+}
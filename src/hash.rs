@@ -0,0 +1,165 @@
+use std::collections::{BTreeMap, HashMap};
+use std::time::Duration;
+
+use crate::clock::Instant;
+
+/// A cache of hashes: under each key, a map of fields to string values
+/// that can be read and written one field at a time, as in Redis.
+///
+/// Storing a record as a JSON blob means rewriting the whole blob to change
+/// one field; a hash updates just that field. Fields are kept in name
+/// order, so [`hgetall`](Self::hgetall) returns them in a stable order.
+///
+/// A hash can be given a TTL with [`expire`](Self::expire), which covers
+/// all its fields, and disappears along with its last field.
+///
+/// # Examples
+///
+/// ```
+/// use spectra_cache::HashCache;
+///
+/// let mut users = HashCache::new();
+/// users.hset("user:42", "name", "Ana");
+/// users.hset("user:42", "plan", "free");
+/// users.hset("user:42", "plan", "pro");
+///
+/// assert_eq!(users.hget("user:42", "plan"), Some("pro"));
+/// assert_eq!(users.hgetall("user:42"), [("name", "Ana"), ("plan", "pro")]);
+/// assert!(users.hdel("user:42", "plan"));
+/// assert_eq!(users.hlen("user:42"), 1);
+/// ```
+#[derive(Debug, Default)]
+pub struct HashCache {
+    hashes: HashMap<String, Hash>,
+}
+
+#[derive(Debug, Default)]
+struct Hash {
+    fields: BTreeMap<String, String>,
+    expires_at: Option<Instant>,
+}
+
+impl Hash {
+    fn is_live(&self, now: Instant) -> bool {
+        self.expires_at.is_none_or(|expires_at| expires_at > now)
+    }
+}
+
+impl HashCache {
+    /// Creates an empty cache.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets `field` of the hash at `key` to `value`, creating the hash if
+    /// needed. Returns true if the field is new.
+    pub fn hset(&mut self, key: &str, field: &str, value: &str) -> bool {
+        if self.live_mut(key).is_none() {
+            self.hashes.insert(key.to_string(), Hash::default());
+        }
+        let hash = self.hashes.get_mut(key).expect("hash was just created");
+        hash.fields.insert(field.to_string(), value.to_string()).is_none()
+    }
+
+    /// Returns the value of `field` in the hash at `key`.
+    pub fn hget(&self, key: &str, field: &str) -> Option<&str> {
+        self.live(key)?.fields.get(field).map(String::as_str)
+    }
+
+    /// Returns every field of the hash at `key` with its value, in field
+    /// order, or nothing if there is no hash.
+    pub fn hgetall(&self, key: &str) -> Vec<(&str, &str)> {
+        match self.live(key) {
+            Some(hash) => hash.fields.iter().map(|(field, value)| (field.as_str(), value.as_str())).collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Removes `field` from the hash at `key`. Returns true if it existed.
+    pub fn hdel(&mut self, key: &str, field: &str) -> bool {
+        let Some(hash) = self.live_mut(key) else {
+            return false;
+        };
+        let removed = hash.fields.remove(field).is_some();
+        if hash.fields.is_empty() {
+            self.hashes.remove(key);
+        }
+        removed
+    }
+
+    /// Returns true if the hash at `key` has `field`.
+    pub fn hexists(&self, key: &str, field: &str) -> bool {
+        self.hget(key, field).is_some()
+    }
+
+    /// Returns the number of fields in the hash at `key`, or zero if there is none.
+    pub fn hlen(&self, key: &str) -> usize {
+        self.live(key).map_or(0, |hash| hash.fields.len())
+    }
+
+    /// Sets the hash at `key` to expire after `ttl`. Returns true if the
+    /// hash existed.
+    pub fn expire(&mut self, key: &str, ttl: Duration) -> bool {
+        match self.live_mut(key) {
+            Some(hash) => {
+                hash.expires_at = Some(Instant::now() + ttl);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Returns how long the hash at `key` has left to live, or `None` if it
+    /// doesn't exist or never expires.
+    pub fn ttl(&self, key: &str) -> Option<Duration> {
+        let expires_at = self.live(key)?.expires_at?;
+        Some(expires_at.saturating_duration_since(Instant::now()))
+    }
+
+    /// Returns true if `key` holds a hash.
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.live(key).is_some()
+    }
+
+    /// Removes the hash at `key`, returning its fields if it existed.
+    pub fn remove(&mut self, key: &str) -> Option<BTreeMap<String, String>> {
+        let now = Instant::now();
+        self.hashes.remove(key).filter(|hash| hash.is_live(now)).map(|hash| hash.fields)
+    }
+
+    /// Returns an iterator over the keys holding hashes.
+    pub fn keys(&self) -> impl Iterator<Item = &String> {
+        let now = Instant::now();
+        self.hashes.iter().filter(move |(_, hash)| hash.is_live(now)).map(|(key, _)| key)
+    }
+
+    /// Returns true if no key holds a hash.
+    pub fn is_empty(&self) -> bool {
+        self.keys().next().is_none()
+    }
+
+    /// Removes every expired hash. Returns the number removed.
+    pub fn purge_expired(&mut self) -> usize {
+        let now = Instant::now();
+        let before = self.hashes.len();
+        self.hashes.retain(|_, hash| hash.is_live(now));
+        before - self.hashes.len()
+    }
+
+    /// Removes every hash.
+    pub fn clear(&mut self) {
+        self.hashes.clear();
+    }
+
+    fn live(&self, key: &str) -> Option<&Hash> {
+        self.hashes.get(key).filter(|hash| hash.is_live(Instant::now()))
+    }
+
+    /// Returns the hash at `key`, dropping it first if it has expired.
+    fn live_mut(&mut self, key: &str) -> Option<&mut Hash> {
+        if self.hashes.get(key).is_some_and(|hash| !hash.is_live(Instant::now())) {
+            self.hashes.remove(key);
+        }
+        self.hashes.get_mut(key)
+    }
+}
@@ -0,0 +1,86 @@
+use std::collections::{HashMap, VecDeque};
+
+use crate::audit::AuditContext;
+use crate::clock::SystemTime;
+use crate::listener::RemovalCause;
+use crate::subscription::CacheEvent;
+use crate::value::CacheValue;
+
+/// A past change to a key, as returned by `history()`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HistoryEntry<V = String> {
+    /// When the change happened.
+    pub timestamp: SystemTime,
+    /// Who the change is attributed to, as set with `set_audit_context()`
+    /// at the time.
+    pub context: AuditContext,
+    /// What happened, with the value written, if any.
+    pub event: CacheEvent<V>,
+}
+
+/// The last `depth` changes to every key written since history was turned
+/// on, newest last.
+pub(crate) struct History<V> {
+    depth: usize,
+    context: AuditContext,
+    keys: HashMap<String, VecDeque<HistoryEntry<V>>>,
+}
+
+impl<V: CacheValue> History<V> {
+    pub(crate) fn new(depth: usize) -> Self {
+        Self {
+            depth,
+            context: AuditContext::default(),
+            keys: HashMap::new(),
+        }
+    }
+
+    pub(crate) fn set_context(&mut self, context: AuditContext) {
+        self.context = context;
+    }
+
+    pub(crate) fn written(&mut self, key: &str, value: &V::Ref, updated: bool) {
+        let (key, value) = (key.to_string(), V::from_ref(value));
+        let event = if updated {
+            CacheEvent::Update { key, value }
+        } else {
+            CacheEvent::Insert { key, value }
+        };
+        self.record(event);
+    }
+
+    /// Records an entry leaving the cache. Replacements are recorded by the
+    /// write that caused them instead.
+    pub(crate) fn removed(&mut self, key: &str, cause: RemovalCause) {
+        let key = key.to_string();
+        let event = match cause {
+            RemovalCause::Removed => CacheEvent::Remove { key },
+            RemovalCause::Expired => CacheEvent::Expire { key },
+            RemovalCause::Evicted => CacheEvent::Evict { key },
+            RemovalCause::Replaced => return,
+        };
+        self.record(event);
+    }
+
+    /// Returns up to `n` of the latest changes to `key`, newest first.
+    pub(crate) fn get(&self, key: &str, n: usize) -> Vec<HistoryEntry<V>>
+    where
+        V: Clone,
+    {
+        self.keys
+            .get(key)
+            .map_or_else(Vec::new, |changes| changes.iter().rev().take(n).cloned().collect())
+    }
+
+    fn record(&mut self, event: CacheEvent<V>) {
+        let changes = self.keys.entry(event.key().to_string()).or_default();
+        if changes.len() == self.depth {
+            changes.pop_front();
+        }
+        changes.push_back(HistoryEntry {
+            timestamp: SystemTime::now(),
+            context: self.context.clone(),
+            event,
+        });
+    }
+}
@@ -0,0 +1,327 @@
+//! A small HTTP/1.1 front end for inspecting a `DistributedHashTable`.
+
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::thread;
+use std::time::Duration;
+
+use crate::json;
+use crate::DistributedHashTable;
+
+/// Largest request body accepted, in bytes.
+const MAX_BODY_BYTES: usize = 16 * 1024 * 1024;
+
+/// Keys listed by `GET /keys` when the request sets no `limit`.
+const DEFAULT_LIST_LIMIT: usize = 1000;
+
+/// Serves a `DistributedHashTable` over HTTP, for debugging and ops tooling.
+///
+/// Routes, all answering with JSON:
+///
+/// | Route | Notes |
+/// |-------|-------|
+/// | `GET /keys/{key}` | `{"key", "value", "ttl_ms"}`, or 404; doesn't count as an access |
+/// | `PUT /keys/{key}[?ttl_ms=N]` | Stores the request body as the value; 204 |
+/// | `DELETE /keys/{key}` | 204, or 404 if the key wasn't live |
+/// | `GET /keys[?prefix=P][&limit=N]` | `{"keys", "truncated"}`: the live keys starting with `P`, sorted, at most `N` (1000 by default) |
+/// | `GET /stats` | The table's [`stats()`](DistributedHashTable::stats) as JSON |
+///
+/// Keys in paths and query values are percent-decoded. Errors are
+/// answered with a status code and `{"error": "..."}`. Reads use
+/// [`peek`](DistributedHashTable::peek), so inspecting the cache doesn't
+/// disturb its eviction order or statistics.
+///
+/// Meant for trusted networks only: there is no authentication or TLS.
+/// Each connection is handled on its own thread, keep-alive included;
+/// requests are applied one at a time under a lock on the table.
+///
+/// # Examples
+///
+/// ```no_run
+/// use spectra_cache::{DistributedHashTable, HttpServer};
+/// use std::net::TcpListener;
+///
+/// let server = HttpServer::new(DistributedHashTable::new());
+/// server.serve(TcpListener::bind("127.0.0.1:8080")?)?;
+/// // curl -X PUT --data 'Ana' localhost:8080/keys/user:1
+/// // curl 'localhost:8080/keys?prefix=user:'
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct HttpServer {
+    table: Arc<Mutex<DistributedHashTable>>,
+}
+
+/// A parsed request; the path and query are already percent-decoded.
+struct Request {
+    method: String,
+    path: String,
+    query: Vec<(String, String)>,
+    body: Vec<u8>,
+    close: bool,
+}
+
+struct Response {
+    status: u16,
+    body: Option<String>,
+}
+
+impl Response {
+    fn json(body: String) -> Self {
+        Self { status: 200, body: Some(body) }
+    }
+
+    fn no_content() -> Self {
+        Self { status: 204, body: None }
+    }
+
+    fn error(status: u16, message: &str) -> Self {
+        Self {
+            status,
+            body: Some(format!("{{\"error\":{}}}", json::quote(message))),
+        }
+    }
+
+    fn write_to(&self, out: &mut impl Write, close: bool) -> io::Result<()> {
+        let body = self.body.as_deref().unwrap_or_default();
+        write!(out, "HTTP/1.1 {} {}\r\n", self.status, reason(self.status))?;
+        if self.body.is_some() {
+            out.write_all(b"Content-Type: application/json\r\n")?;
+        }
+        write!(out, "Content-Length: {}\r\n", body.len())?;
+        if close {
+            out.write_all(b"Connection: close\r\n")?;
+        }
+        out.write_all(b"\r\n")?;
+        out.write_all(body.as_bytes())?;
+        out.flush()
+    }
+}
+
+impl HttpServer {
+    /// Creates a server exposing `table`.
+    pub fn new(table: DistributedHashTable) -> Self {
+        Self {
+            table: Arc::new(Mutex::new(table)),
+        }
+    }
+
+    /// Locks and returns the served table, for use by the process hosting
+    /// the server.
+    pub fn table(&self) -> MutexGuard<'_, DistributedHashTable> {
+        self.table.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Accepts connections on `listener` forever, one thread per client.
+    ///
+    /// Only returns if accepting a connection fails.
+    pub fn serve(&self, listener: TcpListener) -> io::Result<()> {
+        loop {
+            let (stream, _) = listener.accept()?;
+            let server = self.clone();
+            thread::spawn(move || {
+                // Um cliente que some no meio de uma requisição só derruba a própria conexão
+                let _ = server.handle_connection(stream);
+            });
+        }
+    }
+
+    /// Serves a single client until it closes the connection or asks to.
+    pub fn handle_connection(&self, stream: TcpStream) -> io::Result<()> {
+        let mut reader = BufReader::new(stream.try_clone()?);
+        let mut writer = BufWriter::new(stream);
+        loop {
+            let (response, close) = match read_request(&mut reader) {
+                Ok(Some(request)) => (self.route(&request), request.close),
+                Ok(None) => return Ok(()),
+                Err(error) if error.kind() == io::ErrorKind::InvalidData => {
+                    (Response::error(400, &error.to_string()), true)
+                }
+                Err(error) => return Err(error),
+            };
+            response.write_to(&mut writer, close)?;
+            if close {
+                return Ok(());
+            }
+        }
+    }
+
+    fn route(&self, request: &Request) -> Response {
+        let method = request.method.as_str();
+        if let Some(key) = request.path.strip_prefix("/keys/") {
+            return match method {
+                "GET" => self.get_key(key),
+                "PUT" => self.put_key(key, request),
+                "DELETE" => match self.table().remove(key) {
+                    Some(_) => Response::no_content(),
+                    None => Response::error(404, "key not found"),
+                },
+                _ => Response::error(405, "method not allowed"),
+            };
+        }
+        match (method, request.path.as_str()) {
+            ("GET", "/keys") => self.list_keys(request),
+            ("GET", "/stats") => Response::json(self.table().stats().to_json()),
+            (_, "/keys" | "/stats") => Response::error(405, "method not allowed"),
+            _ => Response::error(404, "no such route"),
+        }
+    }
+
+    fn get_key(&self, key: &str) -> Response {
+        let table = self.table();
+        let Some(value) = table.peek(key) else {
+            return Response::error(404, "key not found");
+        };
+        let ttl = table.ttl(key).map_or_else(|| "null".to_string(), |ttl| ttl.as_millis().to_string());
+        Response::json(format!(
+            "{{\"key\":{},\"value\":{},\"ttl_ms\":{}}}",
+            json::quote(key),
+            json::quote(value),
+            ttl
+        ))
+    }
+
+    fn put_key(&self, key: &str, request: &Request) -> Response {
+        let Ok(value) = std::str::from_utf8(&request.body) else {
+            return Response::error(400, "values must be valid UTF-8");
+        };
+        match param(request, "ttl_ms").map(str::parse::<u64>) {
+            None => self.table().insert(key, value),
+            Some(Ok(ttl)) if ttl > 0 => self.table().insert_with_ttl(key, value, Duration::from_millis(ttl)),
+            Some(_) => return Response::error(400, "ttl_ms must be a positive integer"),
+        }
+        Response::no_content()
+    }
+
+    fn list_keys(&self, request: &Request) -> Response {
+        let prefix = param(request, "prefix").unwrap_or_default();
+        let limit = match param(request, "limit").map(str::parse::<usize>) {
+            None => DEFAULT_LIST_LIMIT,
+            Some(Ok(limit)) => limit,
+            Some(Err(_)) => return Response::error(400, "limit must be a non-negative integer"),
+        };
+        let table = self.table();
+        let mut keys: Vec<&String> = table.keys().filter(|key| key.starts_with(prefix)).collect();
+        keys.sort_unstable();
+        let truncated = keys.len() > limit;
+        keys.truncate(limit);
+        let keys: Vec<String> = keys.into_iter().map(|key| json::quote(key)).collect();
+        Response::json(format!("{{\"keys\":[{}],\"truncated\":{}}}", keys.join(","), truncated))
+    }
+}
+
+/// Returns the value of the first query parameter called `name`.
+fn param<'a>(request: &'a Request, name: &str) -> Option<&'a str> {
+    request.query.iter().find(|(key, _)| key == name).map(|(_, value)| value.as_str())
+}
+
+/// Reads one request, or returns `None` if the client closed the connection
+/// between requests. A malformed request is an `InvalidData` error.
+fn read_request(reader: &mut impl BufRead) -> io::Result<Option<Request>> {
+    let mut line = String::new();
+    if reader.read_line(&mut line)? == 0 {
+        return Ok(None);
+    }
+    let mut parts = line.split_whitespace();
+    let (Some(method), Some(target), Some(version)) = (parts.next(), parts.next(), parts.next()) else {
+        return Err(invalid("malformed request line"));
+    };
+    let (method, version) = (method.to_string(), version.to_string());
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let path = percent_decode(path).ok_or_else(|| invalid("malformed path"))?;
+    let query = query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            Some((percent_decode(key)?, percent_decode(&value.replace('+', " "))?))
+        })
+        .collect::<Option<Vec<_>>>()
+        .ok_or_else(|| invalid("malformed query"))?;
+
+    let mut content_length = 0;
+    // HTTP/1.0 fecha a conexão por padrão; 1.1 a mantém aberta
+    let mut close = version == "HTTP/1.0";
+    loop {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 {
+            return Err(invalid("headers cut short"));
+        }
+        let header = line.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        let Some((name, value)) = header.split_once(':') else {
+            return Err(invalid("malformed header"));
+        };
+        let value = value.trim();
+        if name.eq_ignore_ascii_case("content-length") {
+            content_length = value.parse().map_err(|_| invalid("malformed Content-Length"))?;
+        } else if name.eq_ignore_ascii_case("connection") {
+            close = value.eq_ignore_ascii_case("close");
+        } else if name.eq_ignore_ascii_case("transfer-encoding") {
+            return Err(invalid("chunked bodies are not supported"));
+        }
+    }
+    if content_length > MAX_BODY_BYTES {
+        return Err(invalid("request body too large"));
+    }
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body)?;
+    Ok(Some(Request {
+        method,
+        path,
+        query,
+        body,
+        close,
+    }))
+}
+
+/// Decodes `%XX` escapes, returning `None` for a malformed escape or a
+/// result that isn't UTF-8.
+fn percent_decode(text: &str) -> Option<String> {
+    let mut bytes = Vec::with_capacity(text.len());
+    let mut rest = text.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        if byte == b'%' {
+            let hex = std::str::from_utf8(tail.get(..2)?).ok()?;
+            bytes.push(u8::from_str_radix(hex, 16).ok()?);
+            rest = &tail[2..];
+        } else {
+            bytes.push(byte);
+            rest = tail;
+        }
+    }
+    String::from_utf8(bytes).ok()
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        204 => "No Content",
+        400 => "Bad Request",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        _ => "",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_percent_decode() {
+        assert_eq!(percent_decode("user%3A1").as_deref(), Some("user:1"));
+        assert_eq!(percent_decode("caf%C3%A9").as_deref(), Some("café"));
+        assert_eq!(percent_decode("plain"), Some("plain".to_string()));
+        assert_eq!(percent_decode("bad%2"), None);
+        assert_eq!(percent_decode("bad%zz"), None);
+        assert_eq!(percent_decode("%ff"), None);
+    }
+}
@@ -0,0 +1,43 @@
+//! Minimal helpers for the hand-written JSON emitted by the crate.
+
+use alloc::string::{String, ToString};
+use core::fmt::Write;
+
+/// Returns `value` as a quoted JSON string literal.
+pub(crate) fn quote(value: &str) -> String {
+    let mut out = String::with_capacity(value.len() + 2);
+    out.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// Returns `value` as a JSON string literal, or `null` when absent.
+pub(crate) fn quote_opt(value: Option<&str>) -> String {
+    value.map_or_else(|| "null".to_string(), quote)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quote_escapes_special_characters() {
+        assert_eq!(quote("plain"), "\"plain\"");
+        assert_eq!(quote("a\"b\\c"), "\"a\\\"b\\\\c\"");
+        assert_eq!(quote("line\nbreak\u{1}"), "\"line\\nbreak\\u0001\"");
+        assert_eq!(quote_opt(None), "null");
+    }
+}
@@ -0,0 +1,169 @@
+use alloc::format;
+use alloc::string::{String, ToString};
+#[cfg(feature = "std")]
+use std::time::Duration;
+
+#[cfg(feature = "std")]
+use crate::clock::{SystemTime, UNIX_EPOCH};
+use crate::error::CacheError;
+
+/// Encodes values as `BTreeCache` keys whose string order matches the
+/// values' natural order.
+///
+/// Numbers are written as fixed-width lowercase hex of their big-endian
+/// bytes, with the sign bit flipped for signed types so negatives sort
+/// first. Range queries over IDs and timestamps then work without manual
+/// zero-padding, and encoded keys can be combined with a textual prefix.
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(feature = "core")] {
+/// use spectra_cache::{BTreeCache, OrderedKey};
+///
+/// let mut cache = BTreeCache::new();
+/// for id in [9u64, 10, 100] {
+///     cache.insert(&format!("order:{}", id.to_ordered_key()), "...");
+/// }
+///
+/// let from = format!("order:{}", 10u64.to_ordered_key());
+/// let to = format!("order:{}", u64::MAX.to_ordered_key());
+/// let ids: Vec<u64> = cache
+///     .range(from.as_str()..=to.as_str())
+///     .map(|(key, _)| u64::from_ordered_key(&key["order:".len()..]).unwrap())
+///     .collect();
+/// assert_eq!(ids, [10, 100]);
+/// # }
+/// ```
+pub trait OrderedKey: Sized {
+    /// Encodes the value as an order-preserving key.
+    fn to_ordered_key(&self) -> String;
+
+    /// Decodes a key produced by [`to_ordered_key`](Self::to_ordered_key).
+    fn from_ordered_key(key: &str) -> Result<Self, CacheError>;
+}
+
+/// `u64` keys are 16 hex digits.
+impl OrderedKey for u64 {
+    fn to_ordered_key(&self) -> String {
+        format!("{:016x}", self)
+    }
+
+    fn from_ordered_key(key: &str) -> Result<Self, CacheError> {
+        parse_hex(key, 16).map(|value| value as u64)
+    }
+}
+
+/// `i64` keys are 16 hex digits with the sign bit flipped.
+impl OrderedKey for i64 {
+    fn to_ordered_key(&self) -> String {
+        flip_sign(*self).to_ordered_key()
+    }
+
+    fn from_ordered_key(key: &str) -> Result<Self, CacheError> {
+        u64::from_ordered_key(key).map(|biased| (biased ^ SIGN_BIT) as i64)
+    }
+}
+
+/// Timestamps are the signed seconds since the Unix epoch followed by the
+/// nanoseconds, 24 hex digits in all, so times before 1970 sort correctly
+/// and decoding is exact.
+#[cfg(feature = "std")]
+impl OrderedKey for SystemTime {
+    fn to_ordered_key(&self) -> String {
+        let (seconds, nanos) = match self.duration_since(UNIX_EPOCH) {
+            Ok(since) => (since.as_secs() as i64, since.subsec_nanos()),
+            Err(error) => {
+                // Antes de 1970: arredonda os segundos para baixo e os nanos ficam positivos
+                let before = error.duration();
+                match before.subsec_nanos() {
+                    0 => (-(before.as_secs() as i64), 0),
+                    nanos => (-(before.as_secs() as i64) - 1, 1_000_000_000 - nanos),
+                }
+            }
+        };
+        format!("{}{:08x}", seconds.to_ordered_key(), nanos)
+    }
+
+    fn from_ordered_key(key: &str) -> Result<Self, CacheError> {
+        let invalid = || CacheError::InvalidKey { key: key.to_string() };
+        if key.len() != 24 || !key.is_char_boundary(16) {
+            return Err(invalid());
+        }
+        let seconds = i64::from_ordered_key(&key[..16]).map_err(|_| invalid())?;
+        let nanos = parse_hex(&key[16..], 8).map_err(|_| invalid())? as u32;
+        if nanos >= 1_000_000_000 {
+            return Err(invalid());
+        }
+        let time = if seconds >= 0 {
+            UNIX_EPOCH.checked_add(Duration::new(seconds as u64, nanos))
+        } else {
+            UNIX_EPOCH
+                .checked_sub(Duration::from_secs(seconds.unsigned_abs()))
+                .and_then(|time| time.checked_add(Duration::from_nanos(nanos.into())))
+        };
+        time.ok_or_else(invalid)
+    }
+}
+
+const SIGN_BIT: u64 = 1 << 63;
+
+fn flip_sign(value: i64) -> u64 {
+    (value as u64) ^ SIGN_BIT
+}
+
+/// Parses exactly `digits` lowercase hex digits, as written by the encoders.
+fn parse_hex(key: &str, digits: usize) -> Result<u128, CacheError> {
+    let lowercase_hex = |c: u8| c.is_ascii_digit() || (b'a'..=b'f').contains(&c);
+    if key.len() != digits || !key.bytes().all(lowercase_hex) {
+        return Err(CacheError::InvalidKey { key: key.to_string() });
+    }
+    u128::from_str_radix(key, 16).map_err(|_| CacheError::InvalidKey { key: key.to_string() })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+
+    fn assert_order_preserved<T: OrderedKey + Ord + Copy + core::fmt::Debug>(values: &[T]) {
+        let mut sorted = values.to_vec();
+        sorted.sort();
+        let mut keys: Vec<String> = values.iter().map(T::to_ordered_key).collect();
+        keys.sort();
+        let decoded: Vec<T> = keys.iter().map(|key| T::from_ordered_key(key).unwrap()).collect();
+        assert_eq!(decoded, sorted);
+    }
+
+    #[test]
+    fn test_integers_sort_numerically() {
+        assert_order_preserved(&[0u64, 9, 10, 255, 256, u64::MAX, 1 << 40]);
+        assert_order_preserved(&[0i64, -1, 1, i64::MIN, i64::MAX, -300, 42]);
+        assert_eq!(5u64.to_ordered_key(), "0000000000000005");
+        assert_eq!((-1i64).to_ordered_key(), "7fffffffffffffff");
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_timestamps_sort_chronologically() {
+        let times = [
+            UNIX_EPOCH,
+            UNIX_EPOCH + Duration::new(1_700_000_000, 5),
+            UNIX_EPOCH + Duration::from_nanos(1),
+            UNIX_EPOCH - Duration::from_nanos(1),
+            UNIX_EPOCH - Duration::new(86_400, 500),
+            UNIX_EPOCH - Duration::from_secs(86_400),
+        ];
+        assert_order_preserved(&times);
+        assert_eq!(SystemTime::from_ordered_key(&times[3].to_ordered_key()), Ok(times[3]));
+        assert!(SystemTime::from_ordered_key("8000000000000000ffffffff").is_err());
+        assert!(SystemTime::from_ordered_key("é000000000000000000000000").is_err());
+    }
+
+    #[test]
+    fn test_rejects_foreign_keys() {
+        for key in ["", "5", "000000000000000G", "000000000000000A", "00000000000000005"] {
+            assert_eq!(u64::from_ordered_key(key), Err(CacheError::InvalidKey { key: key.to_string() }));
+        }
+    }
+}
@@ -0,0 +1,207 @@
+use std::borrow::Cow;
+use std::time::Duration;
+
+use crate::{CacheError, DistributedHashTable};
+
+/// A policy mapping the keys callers use to the keys stored in the cache,
+/// applied by [`TransformedCache`].
+///
+/// Implemented for `Fn(&str) -> String` closures, which is enough for
+/// one-way transforms such as hashing keys for privacy. [`Prefix`],
+/// [`Lowercase`] and tuples of transforms cover the common reversible
+/// cases.
+pub trait KeyTransform {
+    /// Maps a caller's key to the key stored in the cache.
+    fn apply<'a>(&self, key: &'a str) -> Cow<'a, str>;
+
+    /// Maps a stored key back to the caller's key, or returns `None` if
+    /// the transform can't be undone, as with hashing.
+    fn invert<'a>(&self, _stored: &'a str) -> Option<Cow<'a, str>> {
+        None
+    }
+}
+
+impl<F: Fn(&str) -> String> KeyTransform for F {
+    fn apply<'a>(&self, key: &'a str) -> Cow<'a, str> {
+        Cow::Owned(self(key))
+    }
+}
+
+/// Applies `A`, then `B`.
+impl<A: KeyTransform, B: KeyTransform> KeyTransform for (A, B) {
+    fn apply<'a>(&self, key: &'a str) -> Cow<'a, str> {
+        match self.0.apply(key) {
+            Cow::Borrowed(key) => self.1.apply(key),
+            Cow::Owned(key) => Cow::Owned(self.1.apply(&key).into_owned()),
+        }
+    }
+
+    fn invert<'a>(&self, stored: &'a str) -> Option<Cow<'a, str>> {
+        match self.1.invert(stored)? {
+            Cow::Borrowed(key) => self.0.invert(key),
+            Cow::Owned(key) => Some(Cow::Owned(self.0.invert(&key)?.into_owned())),
+        }
+    }
+}
+
+/// Stores every key under a fixed prefix, e.g. the tenant's name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Prefix(pub String);
+
+impl KeyTransform for Prefix {
+    fn apply<'a>(&self, key: &'a str) -> Cow<'a, str> {
+        Cow::Owned(format!("{}{}", self.0, key))
+    }
+
+    fn invert<'a>(&self, stored: &'a str) -> Option<Cow<'a, str>> {
+        stored.strip_prefix(self.0.as_str()).map(Cow::Borrowed)
+    }
+}
+
+/// Folds keys to lowercase, so `User:1` and `user:1` are the same entry.
+///
+/// Inverting yields the lowercase form, since the original casing is gone.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Lowercase;
+
+impl KeyTransform for Lowercase {
+    fn apply<'a>(&self, key: &'a str) -> Cow<'a, str> {
+        if key.chars().any(char::is_uppercase) {
+            Cow::Owned(key.to_lowercase())
+        } else {
+            Cow::Borrowed(key)
+        }
+    }
+
+    fn invert<'a>(&self, stored: &'a str) -> Option<Cow<'a, str>> {
+        Some(Cow::Borrowed(stored))
+    }
+}
+
+/// A `DistributedHashTable` whose keys all go through a [`KeyTransform`].
+///
+/// The table is owned by the wrapper, so every access goes through the
+/// transform and a policy such as per-tenant prefixes or hashed keys is
+/// enforced in one place instead of at each call site. Listeners,
+/// subscriptions and the audit log of the inner table see the stored keys.
+///
+/// # Examples
+///
+/// ```
+/// use spectra_cache::{DistributedHashTable, Lowercase, Prefix, TransformedCache};
+///
+/// let mut cache = TransformedCache::new(DistributedHashTable::new(), (Lowercase, Prefix("tenant-7:".to_string())));
+/// cache.insert("User:1", "Ana");
+/// assert_eq!(cache.get("user:1"), Some("Ana"));
+/// assert_eq!(cache.table().peek("tenant-7:user:1"), Some("Ana"));
+/// assert_eq!(cache.keys().collect::<Vec<_>>(), ["user:1"]);
+///
+/// // Transforms that can't be undone, such as hashing, are plain closures
+/// let mut hashed = TransformedCache::new(DistributedHashTable::new(), |key: &str| format!("{:x}", key.len()));
+/// hashed.insert("secret@example.com", "1");
+/// assert_eq!(hashed.keys().collect::<Vec<_>>(), ["12"]);
+/// ```
+#[derive(Debug)]
+pub struct TransformedCache<T: KeyTransform> {
+    table: DistributedHashTable,
+    transform: T,
+}
+
+impl<T: KeyTransform> TransformedCache<T> {
+    /// Wraps `table`, applying `transform` to every key from now on. Keys
+    /// already in the table are left as they are.
+    pub fn new(table: DistributedHashTable, transform: T) -> Self {
+        Self { table, transform }
+    }
+
+    /// Returns the key `key` is stored under.
+    pub fn stored_key<'a>(&self, key: &'a str) -> Cow<'a, str> {
+        self.transform.apply(key)
+    }
+
+    /// Inserts a key-value pair into the table.
+    pub fn insert(&mut self, key: &str, value: &str) {
+        let key = self.transform.apply(key);
+        self.table.insert(&key, value);
+    }
+
+    /// Inserts a key-value pair that expires after `ttl`.
+    pub fn insert_with_ttl(&mut self, key: &str, value: &str, ttl: Duration) {
+        let key = self.transform.apply(key);
+        self.table.insert_with_ttl(&key, value, ttl);
+    }
+
+    /// Retrieves a value by key, like [`DistributedHashTable::get`].
+    pub fn get(&mut self, key: &str) -> Option<&str> {
+        let key = self.transform.apply(key);
+        self.table.get(&key)
+    }
+
+    /// Retrieves a value without marking it as used.
+    pub fn peek(&self, key: &str) -> Option<&str> {
+        self.table.peek(&self.transform.apply(key))
+    }
+
+    /// Returns how long `key` has left to live.
+    pub fn ttl(&self, key: &str) -> Option<Duration> {
+        self.table.ttl(&self.transform.apply(key))
+    }
+
+    /// Sets or replaces the TTL of an existing entry. Returns true if the key existed.
+    pub fn expire(&mut self, key: &str, ttl: Duration) -> bool {
+        let key = self.transform.apply(key);
+        self.table.expire(&key, ttl)
+    }
+
+    /// Removes a key, returning its value if it existed.
+    pub fn remove(&mut self, key: &str) -> Option<String> {
+        let key = self.transform.apply(key);
+        self.table.remove(&key)
+    }
+
+    /// Adds `delta` to the integer stored under `key`, like
+    /// [`DistributedHashTable::incr`].
+    pub fn incr(&mut self, key: &str, delta: i64) -> Result<i64, CacheError> {
+        let key = self.transform.apply(key);
+        self.table.incr(&key, delta)
+    }
+
+    /// Checks if a live key exists in the table.
+    pub fn contains_key(&self, key: &str) -> bool {
+        let key = self.transform.apply(key);
+        self.table.contains_key(&key)
+    }
+
+    /// Returns an iterator over the live keys, mapped back to the callers'
+    /// keys where the transform can be inverted and as stored otherwise.
+    pub fn keys(&self) -> impl Iterator<Item = Cow<'_, str>> {
+        self.table
+            .keys()
+            .map(|stored| self.transform.invert(stored).unwrap_or(Cow::Borrowed(stored)))
+    }
+
+    /// Returns the number of entries in the table.
+    pub fn size(&self) -> usize {
+        self.table.size()
+    }
+
+    /// Returns true if the table is empty.
+    pub fn is_empty(&self) -> bool {
+        self.table.is_empty()
+    }
+
+    /// Removes all entries from the table.
+    pub fn clear(&mut self) {
+        self.table.clear();
+    }
+
+    /// Returns the underlying table, whose keys are the stored ones.
+    pub fn table(&self) -> &DistributedHashTable {
+        &self.table
+    }
+
+    /// Unwraps the underlying table.
+    pub fn into_inner(self) -> DistributedHashTable {
+        self.table
+    }
+}
@@ -1,11 +1,267 @@
 // Este arquivo está vazio de propósito.
 // Estamos começando com os testes primeiro, seguindo TDD. 
 
-use std::time::{Duration, Instant};
-use std::collections::{HashMap, BTreeMap};
-use std::iter::Iterator;
-use std::hash::{Hash, Hasher};
-use std::collections::hash_map::DefaultHasher;
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+#[cfg(feature = "core")]
+use alloc::boxed::Box;
+#[cfg(feature = "core")]
+use alloc::collections::BTreeMap;
+#[cfg(feature = "core")]
+use alloc::string::{String, ToString};
+#[cfg(feature = "core")]
+use alloc::vec::Vec;
+#[cfg(feature = "core")]
+use ::core::hash::BuildHasher;
+#[cfg(feature = "core")]
+use ::core::ops::{Bound, RangeBounds};
+#[cfg(feature = "core")]
+use ::core::time::Duration;
+#[cfg(feature = "core")]
+use crate::collections::{DefaultHashBuilder, HashMap};
+#[cfg(feature = "persistence")]
+use std::io;
+#[cfg(feature = "persistence")]
+use std::path::Path;
+#[cfg(all(feature = "core", feature = "std"))]
+use std::sync::mpsc::Receiver;
+
+#[cfg(feature = "async")]
+mod actor;
+#[cfg(feature = "persistence")]
+mod aof;
+#[cfg(feature = "async")]
+mod async_cache;
+#[cfg(feature = "core")]
+mod audit;
+mod bloom;
+#[cfg(feature = "core")]
+mod bytes_cache;
+#[cfg(feature = "core")]
+mod cache;
+#[cfg(all(feature = "core", feature = "std"))]
+mod client;
+#[cfg(any(feature = "std", feature = "core"))]
+pub mod clock;
+mod cluster;
+#[cfg(feature = "core")]
+mod collections;
+#[cfg(all(feature = "core", feature = "std"))]
+mod concurrent;
+#[cfg(feature = "core")]
+mod config;
+#[cfg(feature = "core")]
+mod core;
+#[cfg(all(feature = "core", feature = "std"))]
+mod dedup;
+#[cfg(feature = "core")]
+mod dump;
+#[cfg(feature = "core")]
+mod entry;
+#[cfg(feature = "core")]
+mod entry_api;
+mod error;
+#[cfg(feature = "core")]
+mod eviction;
+#[cfg(feature = "core")]
+mod expiry;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(not(any(feature = "std", test)))]
+mod float;
+#[cfg(feature = "core")]
+mod glob;
+#[cfg(feature = "grpc")]
+pub mod grpc;
+#[cfg(all(feature = "core", feature = "std"))]
+mod hash;
+#[cfg(all(feature = "core", feature = "std"))]
+mod history;
+#[cfg(feature = "http")]
+mod http;
+#[cfg(feature = "core")]
+mod json;
+mod key_codec;
+#[cfg(all(feature = "core", feature = "std"))]
+mod key_transform;
+#[cfg(all(feature = "core", feature = "std"))]
+mod list;
+#[cfg(feature = "core")]
+mod listener;
+#[cfg(all(feature = "core", feature = "std"))]
+mod loading;
+#[cfg(all(feature = "core", feature = "std"))]
+mod local_buffer;
+#[cfg(feature = "persistence")]
+mod log_store;
+#[cfg(feature = "core")]
+mod memory_limit;
+#[cfg(feature = "core")]
+mod merge;
+#[cfg(feature = "cluster")]
+mod merkle;
+#[cfg(feature = "core")]
+mod migrate;
+#[cfg(feature = "core")]
+mod namespace;
+#[cfg(feature = "core")]
+mod options;
+#[cfg(feature = "core")]
+mod read_buffer;
+#[cfg(feature = "core")]
+mod redact;
+#[cfg(all(feature = "core", feature = "std"))]
+mod replay;
+#[cfg(feature = "serde")]
+mod serialize;
+#[cfg(feature = "server")]
+mod server;
+#[cfg(all(feature = "core", feature = "std"))]
+mod set;
+#[cfg(all(feature = "core", feature = "std"))]
+mod sharded;
+#[cfg(all(feature = "core", feature = "std"))]
+mod shared_cache;
+#[cfg(feature = "probabilistic")]
+mod sketch;
+#[cfg(feature = "persistence")]
+mod snapshot;
+#[cfg(all(feature = "core", feature = "std"))]
+mod soft;
+#[cfg(all(feature = "core", feature = "std"))]
+mod sorted_set;
+#[cfg(feature = "core")]
+mod stats;
+#[cfg(feature = "core")]
+mod store;
+#[cfg(all(feature = "core", feature = "std"))]
+pub mod stress;
+#[cfg(feature = "core")]
+mod subscription;
+#[cfg(all(feature = "core", feature = "std"))]
+mod supervisor;
+#[cfg(feature = "core")]
+mod sync;
+#[cfg(feature = "core")]
+mod tag_index;
+#[cfg(feature = "persistence")]
+mod tiered;
+#[cfg(feature = "core")]
+mod transaction;
+#[cfg(feature = "core")]
+mod value;
+#[cfg(feature = "core")]
+mod value_index;
+
+#[cfg(feature = "async")]
+pub use actor::CacheActor;
+#[cfg(feature = "persistence")]
+pub use aof::{FsyncPolicy, PersistenceFailure};
+#[cfg(feature = "async")]
+pub use async_cache::AsyncCache;
+#[cfg(feature = "core")]
+pub use audit::{AuditAction, AuditContext};
+#[cfg(all(feature = "core", feature = "std"))]
+pub use audit::{AuditEvent, AuditSink, WriterAuditSink};
+pub use bloom::{BloomAudit, BloomFilter, DefaultBloomHasher, ScalableBloomFilter};
+#[cfg(feature = "core")]
+pub use bytes_cache::BytesCache;
+#[cfg(feature = "core")]
+pub use cache::Cache;
+#[cfg(all(feature = "core", feature = "std"))]
+pub use client::{HedgePolicy, Idempotency, Jitter, RetryPolicy, RetryableError};
+pub use cluster::HashRing;
+#[cfg(feature = "cluster")]
+pub use cluster::{
+    ChannelTransport, Discovery, DnsDiscovery, LocalCluster, StaticSeeds, TcpTransport, Transport, MAX_FRAME_SIZE,
+};
+#[cfg(all(feature = "core", feature = "std"))]
+pub use concurrent::ConcurrentCache;
+#[cfg(feature = "core")]
+pub use config::{CacheBuilder, EffectiveConfig};
+#[cfg(all(feature = "core", feature = "std"))]
+pub use dedup::DedupCache;
+#[cfg(feature = "core")]
+pub use entry::EntryMetadata;
+#[cfg(feature = "core")]
+pub use entry_api::{Entry, OccupiedEntry, VacantEntry};
+pub use error::CacheError;
+#[cfg(feature = "grpc")]
+pub use grpc::{GrpcServer, SpectraClient};
+#[cfg(all(feature = "core", feature = "std"))]
+pub use hash::HashCache;
+#[cfg(all(feature = "core", feature = "std"))]
+pub use history::HistoryEntry;
+#[cfg(feature = "http")]
+pub use http::HttpServer;
+pub use key_codec::OrderedKey;
+#[cfg(all(feature = "core", feature = "std"))]
+pub use key_transform::{KeyTransform, Lowercase, Prefix, TransformedCache};
+#[cfg(all(feature = "core", feature = "std"))]
+pub use list::ListCache;
+#[cfg(feature = "core")]
+pub use listener::{ListenerOverflow, RemovalCause};
+#[cfg(all(feature = "core", feature = "std"))]
+pub use loading::LoadingCache;
+#[cfg(all(feature = "core", feature = "std"))]
+pub use local_buffer::LocalBuffer;
+#[cfg(feature = "persistence")]
+pub use log_store::LogStore;
+#[cfg(feature = "core")]
+pub use memory_limit::MemoryLimit;
+#[cfg(feature = "core")]
+pub use merge::ConflictStrategy;
+#[cfg(feature = "cluster")]
+pub use merkle::{BucketPatch, DivergentBuckets, MerkleDigest};
+#[cfg(feature = "core")]
+pub use migrate::migrate;
+#[cfg(feature = "core")]
+pub use namespace::Namespace;
+#[cfg(feature = "core")]
+pub use options::{GetOptions, MaybeStale};
+#[cfg(feature = "core")]
+pub use redact::{Redactor, REDACTED};
+#[cfg(all(feature = "core", feature = "std"))]
+pub use replay::ReplayGuard;
+#[cfg(feature = "server")]
+pub use server::RespServer;
+#[cfg(all(feature = "core", feature = "std"))]
+pub use set::SetCache;
+#[cfg(all(feature = "core", feature = "std"))]
+pub use sharded::ShardedCache;
+#[cfg(all(feature = "core", feature = "std"))]
+pub use shared_cache::SharedCache;
+#[cfg(feature = "probabilistic")]
+pub use sketch::CountMinSketch;
+#[cfg(feature = "persistence")]
+pub use snapshot::{diff_snapshots, KeyDiff, SnapshotDiff};
+#[cfg(all(feature = "core", feature = "std"))]
+pub use soft::SoftCache;
+#[cfg(all(feature = "core", feature = "std"))]
+pub use sorted_set::SortedSetCache;
+#[cfg(feature = "core")]
+pub use stats::{CacheStats, RateWindows, STATS_SCHEMA_VERSION};
+#[cfg(all(feature = "core", feature = "std"))]
+pub use store::{BackingStore, Durability, StoreError};
+#[cfg(feature = "core")]
+pub use subscription::CacheEvent;
+#[cfg(all(feature = "core", feature = "std"))]
+pub use supervisor::{Supervisor, WorkerState, WorkerStats};
+#[cfg(feature = "persistence")]
+pub use tiered::{TierStats, TieredCache};
+#[cfg(feature = "core")]
+pub use transaction::Transaction;
+
+#[cfg(feature = "core")]
+use crate::config::{CacheConfig, CacheType};
+#[cfg(feature = "core")]
+use crate::core::CacheCore;
+#[cfg(feature = "core")]
+use crate::entry::CacheEntry;
+#[cfg(feature = "core")]
+use crate::glob::Glob;
 
 /// A distributed hash table implementation that provides O(1) access time.
 /// 
@@ -14,531 +270,2542 @@ use std::collections::hash_map::DefaultHasher;
 /// - TTL-based expiration
 /// - Automatic cleanup of expired entries
 /// - Thread-safe operations
-#[derive(Debug)]
-pub struct DistributedHashTable {
-    entries: HashMap<String, Entry>,
-    bloom_filter: BloomFilter,
+/// 
+/// Keys are hashed with `S`, the standard library's SipHash-based
+/// `RandomState` unless another hasher is picked with
+/// [`CacheBuilder::hasher`]. The same hasher drives the table's Bloom filter.
+/// Without the `std` feature there is no `RandomState` and the default is
+/// [`DefaultBloomHasher`], whose keys are fixed: pick a seeded hasher if
+/// untrusted clients choose the keys.
+#[cfg(feature = "core")]
+#[derive(Debug, Clone)]
+pub struct DistributedHashTable<S: BuildHasher + Clone + Default = DefaultHashBuilder> {
+    core: CacheCore<HashMap<String, CacheEntry, S>>,
+}
+
+#[cfg(feature = "core")]
+impl DistributedHashTable {
+    /// Creates a new empty distributed hash table.
+    pub fn new() -> Self {
+        Self {
+            core: CacheCore::new(),
+        }
+    }
+
+    /// Returns a builder for a distributed hash table with non-default settings.
+    pub fn builder() -> CacheBuilder<Self> {
+        CacheBuilder::new()
+    }
 }
 
-#[derive(Debug)]
-struct Entry {
-    value: String,
-    ttl: Option<Duration>,
-    created_at: Instant,
-    last_accessed_at: Instant,
-}
+#[cfg(feature = "core")]
+impl<S: BuildHasher + Clone + Default> DistributedHashTable<S> {
+    /// Returns a deep copy whose entries count as freshly written.
+    /// 
+    /// `clone()` keeps every entry's remaining TTL and idle time; this
+    /// restarts them, so each entry gets its full TTL again in the copy.
+    /// Either way the copy keeps the configuration and eviction order,
+    /// starts with zeroed statistics, and has no `on_evict` listeners or
+    /// audit sink, since callbacks can't be cloned.
+    pub fn clone_with_fresh_timestamps(&self) -> Self {
+        let mut copy = self.clone();
+        copy.core.refresh_timestamps();
+        copy
+    }
+
+    fn with_config(config: CacheConfig) -> Self {
+        Self {
+            core: CacheCore::with_config(config),
+        }
+    }
+
+    /// Returns the number of entries in the table.
+    pub fn size(&self) -> usize {
+        self.core.size()
+    }
+
+    /// Returns true if the table is empty.
+    pub fn is_empty(&self) -> bool {
+        self.core.is_empty()
+    }
+
+    /// Inserts a key-value pair into the table.
+    /// 
+    /// If the key already exists, the value will be updated.
+    /// The entry never expires unless the table was built with a default TTL.
+    pub fn insert(&mut self, key: &str, value: &str) {
+        self.core.insert(key, value);
+    }
+
+    /// Inserts a key-value pair like [`insert`](Self::insert), failing
+    /// with [`CacheError::PersistenceFailed`] if the table refuses writes;
+    /// see [`PersistenceFailure`](crate::PersistenceFailure).
+    pub fn try_insert(&mut self, key: &str, value: &str) -> Result<(), CacheError> {
+        self.core.try_insert(key, value)
+    }
+
+    /// Inserts a key-value pair with TTL into the table.
+    /// 
+    /// The entry will be automatically removed when the TTL expires.
+    pub fn insert_with_ttl(&mut self, key: &str, value: &str, ttl: Duration) {
+        self.core.insert_entry(key, CacheEntry::with_ttl(key, value, Some(ttl), self.core.now()));
+    }
+
+    /// Inserts a key-value pair that expires after a period of inactivity.
+    /// 
+    /// Unlike [`insert_with_ttl`](Self::insert_with_ttl), the expiration clock
+    /// restarts every time the entry is read, so the entry only goes away
+    /// once it has been idle for longer than `idle`.
+    pub fn insert_with_tti(&mut self, key: &str, value: &str, idle: Duration) {
+        self.core.insert_entry(key, CacheEntry::with_tti(key, value, idle, self.core.now()));
+    }
+
+    /// Inserts a key-value pair carrying `tags`, with the default TTL.
+    /// 
+    /// Tags name what an entry was derived from, so that everything built
+    /// from the same data can be dropped together with
+    /// [`invalidate_tag`](Self::invalidate_tag) when it changes. They belong
+    /// to the entry: replacing it with [`insert`](Self::insert) drops them,
+    /// while in-place changes such as [`update`](Self::update) keep them.
+    /// Tags aren't written to snapshots or the append-only file.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use spectra_cache::DistributedHashTable;
+    /// 
+    /// let mut cache = DistributedHashTable::new();
+    /// cache.insert_with_tags("profile:42", "<html>", &["user:42"]);
+    /// cache.insert_with_tags("report:7", "...", &["user:42", "report"]);
+    /// cache.insert("user:43", "Bia");
+    /// 
+    /// assert_eq!(cache.invalidate_tag("user:42"), 2);
+    /// assert_eq!(cache.size(), 1);
+    /// ```
+    pub fn insert_with_tags(&mut self, key: &str, value: &str, tags: &[&str]) {
+        self.core.insert_with_tags(key, value, tags);
+    }
+
+    /// Retrieves a value by key.
+    /// 
+    /// Returns None if the key doesn't exist or if the entry has expired.
+    /// The read counts as an access: it moves the entry away from eviction,
+    /// restarts its idle timeout and counts as a hit or miss in
+    /// [`stats`](Self::stats). Since `&self` can't change the table, that
+    /// bookkeeping is noted aside and applied by the next call that takes
+    /// `&mut self`; until then the read still keeps an idle entry alive.
+    /// An expired entry is left for the next write or
+    /// [`purge_expired`](Self::purge_expired) to remove, and a miss doesn't
+    /// read through to a backing store: use [`get_with`](Self::get_with)
+    /// for that.
+    /// 
+    /// Taking `&self` means several values can be borrowed at once, and
+    /// read-only code can be handed `&DistributedHashTable`. The table is
+    /// `Sync`, so behind an `RwLock` any number of threads can read it
+    /// under the read lock at the same time; only writers need the write
+    /// lock.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// # #[cfg(feature = "std")] {
+    /// use spectra_cache::DistributedHashTable;
+    /// use std::sync::RwLock;
+    /// use std::thread;
+    /// 
+    /// let mut cache = DistributedHashTable::new();
+    /// cache.insert("user:1", "Ana");
+    /// cache.insert("user:2", "Bia");
+    /// let cache = RwLock::new(cache);
+    /// 
+    /// thread::scope(|scope| {
+    ///     for key in ["user:1", "user:2"] {
+    ///         scope.spawn(|| assert!(cache.read().unwrap().get(key).is_some()));
+    ///     }
+    /// });
+    /// assert_eq!(cache.read().unwrap().stats().hits, 2);
+    /// # }
+    /// ```
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.core.get_ref(key)
+    }
+
+    /// Retrieves a value by key along with its version.
+    /// 
+    /// Every write of a key gives it a higher version than it had, so a
+    /// version read here can be handed to
+    /// [`update_if_version`](Self::update_if_version) to update the key
+    /// only if no one else wrote it in between.
+    pub fn get_versioned(&mut self, key: &str) -> Option<(&str, u64)> {
+        self.core.get_versioned(key)
+    }
+
+    /// Retrieves a value without marking it as used.
+    /// 
+    /// Unlike [`get`](Self::get), the entry's access time and eviction order
+    /// are left alone and no hit or miss is counted, so monitoring code can
+    /// read every key without disturbing the LRU signal or the idle timeouts.
+    /// Expired entries are reported as absent but not removed.
+    pub fn peek(&self, key: &str) -> Option<&str> {
+        self.core.peek(key)
+    }
+
+    /// Retrieves a value by key within the latency budget of `options`.
+    /// 
+    /// With [`GetOptions::default`] this reads like [`get`](Self::get),
+    /// except that the bookkeeping is done right away: an expired entry is
+    /// removed, and a miss reads through to the backing store, if any.
+    /// A budget can return an expired entry that is still in memory, skip
+    /// the Bloom filter, or turn what would be a read through to the
+    /// backing store into a plain miss.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use spectra_cache::{DistributedHashTable, GetOptions};
+    /// use std::time::Duration;
+    /// 
+    /// let mut cache = DistributedHashTable::new();
+    /// cache.insert("user:1", "Ana");
+    /// let budget = GetOptions { max_wait: Some(Duration::ZERO), skip_bloom: true, ..GetOptions::default() };
+    /// assert_eq!(cache.get_with("user:1", &budget), Some("Ana"));
+    /// assert_eq!(cache.get_with("user:2", &budget), None);
+    /// ```
+    pub fn get_with(&mut self, key: &str, options: &GetOptions) -> Option<&str> {
+        self.core.get_with(key, options)
+    }
+
+    /// Retrieves a value by key, flagged as stale if it was written longer
+    /// ago than the [`soft_ttl`](CacheBuilder::soft_ttl).
+    /// 
+    /// Counts as an access like [`get`](Self::get). Without a soft TTL no
+    /// value is ever stale. Use it to serve a value while it is refreshed,
+    /// instead of making a caller wait on every expiry; a `LoadingCache`
+    /// does the refresh itself in
+    /// [`LoadingCache::get_stale`](crate::LoadingCache::get_stale).
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use spectra_cache::clock::ManualClock;
+    /// use spectra_cache::DistributedHashTable;
+    /// use std::time::Duration;
+    /// 
+    /// let clock = ManualClock::new();
+    /// let mut cache = DistributedHashTable::builder()
+    ///     .default_ttl(Duration::from_secs(60))
+    ///     .soft_ttl(Duration::from_millis(10))
+    ///     .clock(clock.clone())
+    ///     .build();
+    /// cache.insert("quote:EUR", "1.08");
+    /// assert!(!cache.get_stale("quote:EUR").unwrap().stale);
+    /// 
+    /// clock.advance(Duration::from_millis(20));
+    /// let read = cache.get_stale("quote:EUR").unwrap();
+    /// assert_eq!((read.value, read.stale), ("1.08", true));
+    /// ```
+    pub fn get_stale(&mut self, key: &str) -> Option<MaybeStale<&str>> {
+        self.core.get_stale(key)
+    }
+
+    /// Returns how long `key` has left to live.
+    /// 
+    /// Returns `None` if the key is missing, expired, or never expires; use
+    /// [`contains_key`](Self::contains_key) to tell these apart. For an entry
+    /// with an idle timeout this is the time left if it isn't read again.
+    /// Like `peek`, this doesn't count as an access.
+    pub fn ttl(&self, key: &str) -> Option<Duration> {
+        self.core.ttl(key)
+    }
+
+    /// Sets or replaces the TTL of an existing entry, counted from now.
+    /// 
+    /// An idle timeout, if the entry has one, keeps applying as well.
+    /// Returns true if the key existed.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use spectra_cache::DistributedHashTable;
+    /// use std::time::Duration;
+    /// 
+    /// let mut cache = DistributedHashTable::new();
+    /// cache.insert("session:456", "active");
+    /// assert_eq!(cache.ttl("session:456"), None);
+    /// 
+    /// cache.expire("session:456", Duration::from_secs(60));
+    /// assert!(cache.ttl("session:456").unwrap() <= Duration::from_secs(60));
+    /// 
+    /// cache.persist("session:456");
+    /// assert_eq!(cache.ttl("session:456"), None);
+    /// ```
+    pub fn expire(&mut self, key: &str, ttl: Duration) -> bool {
+        self.core.expire(key, ttl)
+    }
+
+    /// Removes the TTL and idle timeout of an entry so it never expires.
+    /// 
+    /// Returns true if the entry existed and had an expiry to remove.
+    pub fn persist(&mut self, key: &str) -> bool {
+        self.core.persist(key)
+    }
+
+    /// Removes a key-value pair from the table.
+    /// 
+    /// Returns the removed value if the key existed.
+    pub fn remove(&mut self, key: &str) -> Option<String> {
+        self.core.remove(key)
+    }
+
+    /// Removes a key-value pair like [`remove`](Self::remove), failing with
+    /// [`CacheError::PersistenceFailed`] if the table refuses writes, so a
+    /// refused removal can be told apart from a missing key.
+    pub fn try_remove(&mut self, key: &str) -> Result<Option<String>, CacheError> {
+        self.core.try_remove(key)
+    }
+
+    /// Updates an existing entry's value.
+    /// 
+    /// Returns true if the update was successful (key existed).
+    pub fn update(&mut self, key: &str, value: &str) -> bool {
+        self.core.update(key, value)
+    }
+
+    /// Updates an existing entry's value if it is still at
+    /// `expected_version`, and returns the new version.
+    /// 
+    /// # Errors
+    /// 
+    /// Returns [`CacheError::VersionMismatch`] with the key's current
+    /// version, or `None` if it doesn't exist, when another write got there
+    /// first; the entry is then left untouched.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use spectra_cache::{CacheError, DistributedHashTable};
+    /// 
+    /// let mut cache = DistributedHashTable::new();
+    /// cache.insert("counter", "1");
+    /// let (_, version) = cache.get_versioned("counter").unwrap();
+    /// 
+    /// let version = cache.update_if_version("counter", version, "2").unwrap();
+    /// cache.update("counter", "5");
+    /// let stale = cache.update_if_version("counter", version, "3");
+    /// assert!(matches!(stale, Err(CacheError::VersionMismatch { .. })));
+    /// assert_eq!(cache.get("counter"), Some("5"));
+    /// ```
+    pub fn update_if_version(&mut self, key: &str, expected_version: u64, value: &str) -> Result<u64, CacheError> {
+        self.core.update_if_version(key, expected_version, value)
+    }
+
+    /// Gets the given key's entry for in-place insert-or-update.
+    /// 
+    /// The key is looked up only once; expired entries are reported as vacant.
+    /// 
+    /// # Panics
+    /// 
+    /// Panics if the table refuses writes because its persistence keeps
+    /// failing; use [`try_entry`](Self::try_entry) where that can happen.
+    pub fn entry(&mut self, key: &str) -> Entry<'_> {
+        self.core.entry(key)
+    }
+
+    /// Gets the given key's entry like [`entry`](Self::entry), failing with
+    /// [`CacheError::PersistenceFailed`] if the table refuses writes.
+    pub fn try_entry(&mut self, key: &str) -> Result<Entry<'_>, CacheError> {
+        self.core.try_entry(key)
+    }
+
+    /// Adds `delta` to the integer stored under `key` and returns the new value.
+    /// 
+    /// A missing or expired key is created as if it held zero. The stored
+    /// entry keeps its TTL.
+    /// 
+    /// # Errors
+    /// 
+    /// Returns [`CacheError::NotAnInteger`] if the stored value isn't a
+    /// 64-bit integer and [`CacheError::Overflow`] if the result doesn't fit.
+    /// The stored value is left untouched in both cases.
+    pub fn incr(&mut self, key: &str, delta: i64) -> Result<i64, CacheError> {
+        self.core.incr(key, delta)
+    }
+
+    /// Subtracts `delta` from the integer stored under `key` and returns the new value.
+    /// 
+    /// Behaves like [`incr`](Self::incr) with a negated delta.
+    pub fn decr(&mut self, key: &str, delta: i64) -> Result<i64, CacheError> {
+        self.core.decr(key, delta)
+    }
+
+    /// Removes all entries from the table.
+    pub fn clear(&mut self) {
+        self.core.clear();
+    }
+
+    /// Keeps only the live entries for which `keep` returns true, removing
+    /// the others, and returns how many were removed.
+    /// 
+    /// Each removal goes through [`remove`](Self::remove): listeners see
+    /// `RemovalCause::Removed` and a backing store deletes the key.
+    /// Expired entries are not offered to `keep`. The audit sink gets a
+    /// single `AuditAction::MassDelete` for the whole call.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use spectra_cache::DistributedHashTable;
+    /// 
+    /// let mut cache: DistributedHashTable = [("user:1", "ana"), ("session:1", "x"), ("user:2", "bia")].into_iter().collect();
+    /// assert_eq!(cache.retain(|key, _| key.starts_with("user:")), 1);
+    /// 
+    /// cache.extend([("user:3", "caio")]);
+    /// let mut drained = cache.drain();
+    /// drained.sort();
+    /// assert_eq!(drained[2], ("user:3".to_string(), "caio".to_string()));
+    /// assert!(cache.is_empty());
+    /// ```
+    pub fn retain(&mut self, keep: impl FnMut(&str, &str) -> bool) -> usize {
+        self.core.retain(keep)
+    }
+
+    /// Removes every entry carrying `tag` and returns how many were removed.
+    /// 
+    /// Entries go in key order through [`remove`](Self::remove), so
+    /// listeners see `RemovalCause::Removed` and a backing store deletes
+    /// the keys. Expired entries carrying the tag are purged as expired and
+    /// not counted. Finding the entries costs one lookup in a reverse index,
+    /// not a scan of the table. The audit sink gets a single
+    /// `AuditAction::MassDelete` naming the tag.
+    pub fn invalidate_tag(&mut self, tag: &str) -> usize {
+        self.core.invalidate_tag(tag)
+    }
+
+    /// Applies every write of `transaction`, or none of them if a key it
+    /// watches no longer holds the expected value.
+    /// 
+    /// The writes behave as if made one by one with [`insert`](Self::insert)
+    /// and [`remove`](Self::remove), listeners and backing store included,
+    /// but no reader sees the table between them and the append-only log
+    /// and the replicas get them as a single record.
+    /// 
+    /// # Errors
+    /// 
+    /// Returns [`CacheError::TransactionConflict`] naming the first watched
+    /// key that changed; the table is then left untouched.
+    pub fn apply(&mut self, transaction: Transaction) -> Result<(), CacheError> {
+        self.core.commit(transaction)
+    }
+
+    /// Removes every entry, returning the live ones as `(key, value)` pairs.
+    /// 
+    /// The table is emptied as by [`clear`](Self::clear), with the same
+    /// effect on listeners and the backing store. If the table refuses
+    /// writes, nothing is removed and nothing is returned.
+    pub fn drain(&mut self) -> Vec<(String, String)> {
+        self.core.drain()
+    }
+
+    /// Removes every expired entry now and returns how many were removed.
+    /// 
+    /// Expired entries are otherwise removed only when read or when the
+    /// memory limit needs room, and count in `size()` and `memory_usage()`
+    /// until then. Listeners see each removal as `RemovalCause::Expired`,
+    /// and `stats().expirations` counts them. The work is proportional to
+    /// the number of expired entries, not the size of the table.
+    pub fn purge_expired(&mut self) -> usize {
+        self.core.purge_expired()
+    }
+
+    /// Removes every expired entry now and returns them as `(key, value)`
+    /// pairs, e.g. to archive them.
+    /// 
+    /// Otherwise behaves like [`purge_expired`](Self::purge_expired).
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use spectra_cache::clock::ManualClock;
+    /// use spectra_cache::DistributedHashTable;
+    /// use std::time::Duration;
+    /// 
+    /// let clock = ManualClock::new();
+    /// let mut cache = DistributedHashTable::builder().clock(clock.clone()).build();
+    /// cache.insert_with_ttl("session:1", "ana", Duration::from_millis(10));
+    /// cache.insert("session:2", "bia");
+    /// clock.advance(Duration::from_millis(20));
+    /// 
+    /// assert_eq!(cache.size(), 2);
+    /// assert_eq!(cache.drain_expired(), [("session:1".to_string(), "ana".to_string())]);
+    /// assert_eq!(cache.size(), 1);
+    /// ```
+    pub fn drain_expired(&mut self) -> Vec<(String, String)> {
+        self.core.drain_expired()
+    }
+
+    /// Folds the live entries of `other` into this table, e.g. to combine
+    /// per-thread local caches into a shared one at a sync point.
+    /// 
+    /// Keys missing here are copied with their TTL and idle timeout. Keys
+    /// live in both tables are settled by `strategy`, which can compare
+    /// when each value was last written. Returns the number of keys written;
+    /// `other` is left unchanged.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use spectra_cache::{ConflictStrategy, DistributedHashTable};
+    /// 
+    /// let mut shared = DistributedHashTable::new();
+    /// shared.insert("hits:/home", "10");
+    /// 
+    /// let mut local = DistributedHashTable::new();
+    /// local.insert("hits:/home", "3");
+    /// local.insert("hits:/about", "1");
+    /// 
+    /// let sum = ConflictStrategy::custom(|_key, existing: &str, incoming: &str| {
+    ///     (existing.parse::<u64>().unwrap() + incoming.parse::<u64>().unwrap()).to_string()
+    /// });
+    /// assert_eq!(shared.merge_from(&local, sum), 2);
+    /// assert_eq!(shared.get("hits:/home"), Some("13"));
+    /// assert_eq!(shared.get("hits:/about"), Some("1"));
+    /// ```
+    pub fn merge_from(&mut self, other: &Self, strategy: ConflictStrategy) -> usize {
+        self.core.merge_from(&other.core, strategy)
+    }
+
+    /// Bulk-loads `(key, value)` pairs, e.g. to warm the table at startup,
+    /// and returns how many were loaded.
+    /// 
+    /// Entries get the default TTL and are written as by [`insert`](Self::insert),
+    /// but the table is sized for the whole load up front and, when empty,
+    /// gets a Bloom filter built for the load in one pass rather than grown
+    /// as it fills. `progress` receives the running count every 1024
+    /// entries and once at the end.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use spectra_cache::DistributedHashTable;
+    /// 
+    /// let users = (0..5000).map(|i| (format!("user:{}", i), "cached"));
+    /// let mut cache = DistributedHashTable::new();
+    /// let mut reports = Vec::new();
+    /// assert_eq!(cache.warm_from(users, |loaded| reports.push(loaded)), 5000);
+    /// assert_eq!(reports, [1024, 2048, 3072, 4096, 5000]);
+    /// ```
+    pub fn warm_from<I, K, V>(&mut self, entries: I, mut progress: impl FnMut(usize)) -> usize
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: AsRef<str>,
+        V: AsRef<str>,
+    {
+        self.core.warm_from(entries, &mut progress)
+    }
+
+    /// Writes the live entries and their TTLs to a snapshot file at `path`,
+    /// for [`warm_from_snapshot`](Self::warm_from_snapshot) to load later.
+    /// Returns the number of entries written.
+    /// 
+    /// The file is written next to `path` and renamed over it once complete,
+    /// so a crash mid-save leaves the previous snapshot intact. Idle
+    /// timeouts are saved as the fixed TTL they currently amount to.
+    #[cfg(feature = "persistence")]
+    pub fn save_snapshot(&self, path: impl AsRef<Path>) -> io::Result<usize> {
+        self.core.save_snapshot(path.as_ref())
+    }
+
+    /// Loads a snapshot written by [`save_snapshot`](Self::save_snapshot),
+    /// like [`warm_from`](Self::warm_from), and returns how many entries
+    /// were loaded.
+    /// 
+    /// Entries keep their TTLs, with the time the snapshot spent on disk
+    /// counted against them; entries that expired meanwhile are skipped.
+    /// Nothing is loaded from a corrupt or truncated file, which fails with
+    /// `io::ErrorKind::InvalidData` wrapping a [`CacheError::InvalidDump`].
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use spectra_cache::DistributedHashTable;
+    /// use std::time::Duration;
+    /// 
+    /// let path = std::env::temp_dir().join(format!("spectra-cache-doc-{}.snapshot", std::process::id()));
+    /// let mut before_deploy = DistributedHashTable::new();
+    /// before_deploy.insert("config:theme", "dark");
+    /// before_deploy.insert_with_ttl("session:1", "active", Duration::from_secs(60));
+    /// before_deploy.save_snapshot(&path).unwrap();
+    /// 
+    /// let mut after_deploy = DistributedHashTable::new();
+    /// assert_eq!(after_deploy.warm_from_snapshot(&path, |_| {}).unwrap(), 2);
+    /// assert_eq!(after_deploy.get("config:theme"), Some("dark"));
+    /// assert!(after_deploy.ttl("session:1").unwrap() <= Duration::from_secs(60));
+    /// # std::fs::remove_file(&path).unwrap();
+    /// ```
+    #[cfg(feature = "persistence")]
+    pub fn warm_from_snapshot(&mut self, path: impl AsRef<Path>, mut progress: impl FnMut(usize)) -> io::Result<usize> {
+        self.core.warm_from_snapshot(path.as_ref(), &mut progress)
+    }
+
+    /// Checks if a key exists in the table.
+    /// 
+    /// Returns false if the key doesn't exist or if the entry has expired.
+    /// This isn't a read of the value, so it doesn't count as an access;
+    /// an expired entry it finds is left for the next write or
+    /// [`purge_expired`](Self::purge_expired) to remove.
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.core.contains_key(key)
+    }
+
+    /// Returns a handle whose operations are confined to the keyspace
+    /// `name`, by prefixing every key with `"{name}:"`.
+    /// 
+    /// See [`Namespace`] for how namespaces nest and what they share.
+    pub fn namespace(&mut self, name: &str) -> Namespace<'_, S> {
+        Namespace::new(self, name)
+    }
+
+    /// Atomically replaces every entry of the namespace `name` with
+    /// `contents`, and returns the number of live entries it held before.
+    /// 
+    /// Meant for reference tables reloaded as a whole: readers see either
+    /// the complete old set or the complete new one. See
+    /// [`Namespace::swap`].
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use spectra_cache::DistributedHashTable;
+    /// 
+    /// let mut cache = DistributedHashTable::new();
+    /// cache.swap_namespace("currency", [("EUR", "Euro"), ("USD", "US Dollar")]);
+    /// 
+    /// let reloaded = vec![("EUR", "Euro"), ("BRL", "Real")];
+    /// assert_eq!(cache.swap_namespace("currency", reloaded), 2);
+    /// assert_eq!(cache.get("currency:BRL"), Some("Real"));
+    /// assert_eq!(cache.get("currency:USD"), None);
+    /// ```
+    pub fn swap_namespace<I, K, V>(&mut self, name: &str, contents: I) -> usize
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: AsRef<str>,
+        V: AsRef<str>,
+    {
+        self.namespace(name).swap(contents)
+    }
+
+    /// Returns an iterator over the live keys in the table.
+    /// 
+    /// Expired entries are skipped even if they haven't been purged yet, so
+    /// every key yielded is one `get()` would find.
+    pub fn keys(&self) -> impl Iterator<Item = &String> {
+        self.core.keys()
+    }
+
+    /// Returns an iterator over the live values in the table.
+    pub fn values(&self) -> impl Iterator<Item = &String> {
+        self.core.values()
+    }
+
+    /// Returns an iterator over the live entries as `(key, value, metadata)`.
+    /// 
+    /// Like [`peek`](Self::peek), iterating doesn't count as an access.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use spectra_cache::DistributedHashTable;
+    /// use std::time::Duration;
+    /// 
+    /// let mut cache = DistributedHashTable::new();
+    /// cache.insert_with_ttl("session:1", "active", Duration::from_secs(60));
+    /// for (key, value, metadata) in cache.iter() {
+    ///     assert_eq!((key.as_str(), value), ("session:1", "active"));
+    ///     assert!(metadata.time_to_live.unwrap() <= Duration::from_secs(60));
+    /// }
+    /// ```
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &str, EntryMetadata)> {
+        self.core.iter()
+    }
+
+    /// Returns an iterator over every stored key, including expired entries
+    /// that haven't been purged yet.
+    pub fn keys_raw(&self) -> impl Iterator<Item = &String> {
+        self.core.keys_raw()
+    }
+
+    /// Returns a page of up to `limit` live entries and the cursor to pass
+    /// for the next page, or `None` once the scan is done.
+    /// 
+    /// Pass `None` as the cursor to start. Entries come in the order of a
+    /// hash of their keys that doesn't change between runs, and the cursor
+    /// is the last key returned, so the scan holds no borrow between pages
+    /// and can be resumed later, even by another process. Keys present
+    /// during the whole scan are returned exactly once; keys written or
+    /// removed meanwhile may or may not be. Every page walks the whole
+    /// table: O(n) per page.
+    /// 
+    /// A `limit` of zero returns an empty page and the cursor it was given.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use spectra_cache::DistributedHashTable;
+    /// 
+    /// let mut cache = DistributedHashTable::new();
+    /// for i in 0..25 {
+    ///     cache.insert(&format!("user:{}", i), "active");
+    /// }
+    /// 
+    /// let mut seen = 0;
+    /// let mut cursor = None;
+    /// loop {
+    ///     let (page, next) = cache.scan(cursor.as_deref(), 10);
+    ///     seen += page.len();
+    ///     match next {
+    ///         Some(next) => cursor = Some(next),
+    ///         None => break,
+    ///     }
+    /// }
+    /// assert_eq!(seen, 25);
+    /// ```
+    pub fn scan(&self, cursor: Option<&str>, limit: usize) -> (Vec<(&String, &str)>, Option<String>) {
+        self.core.scan_by_hash(cursor, limit)
+    }
+
+    /// Returns an iterator over every stored value, including expired
+    /// entries that haven't been purged yet.
+    pub fn values_raw(&self) -> impl Iterator<Item = &String> {
+        self.core.values_raw()
+    }
+
+    /// Returns an iterator over the live keys matching a glob pattern.
+    /// 
+    /// Supports `*` (any run of characters), `?` (one character), character
+    /// classes like `[a-z]` or `[^0-9]`, and `\` to escape a metacharacter.
+    /// Expired entries are skipped. Time complexity: O(n)
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use spectra_cache::DistributedHashTable;
+    /// 
+    /// let mut cache = DistributedHashTable::new();
+    /// cache.insert("user:1", "Ana");
+    /// cache.insert("session:1", "active");
+    /// let users: Vec<_> = cache.keys_matching("user:*").collect();
+    /// assert_eq!(users, vec!["user:1"]);
+    /// ```
+    pub fn keys_matching(&self, pattern: &str) -> impl Iterator<Item = &String> {
+        self.core.keys_matching(pattern)
+    }
+
+    /// Returns the live keys whose value equals `value`, in no particular order.
+    /// 
+    /// With [`CacheBuilder::index_values`] this is a lookup in an inverted
+    /// index; otherwise every entry is compared.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use spectra_cache::DistributedHashTable;
+    /// 
+    /// let mut cache = DistributedHashTable::builder().index_values().build();
+    /// cache.insert("avatar:1", "blob-a");
+    /// cache.insert("avatar:2", "blob-a");
+    /// cache.insert("avatar:3", "blob-b");
+    /// let mut keys = cache.keys_with_value("blob-a");
+    /// keys.sort();
+    /// assert_eq!(keys, ["avatar:1", "avatar:2"]);
+    /// ```
+    pub fn keys_with_value(&self, value: &str) -> Vec<&String> {
+        self.core.keys_with_value(value)
+    }
+
+    /// Returns true if any live key holds `value`, e.g. to check whether a
+    /// blob is already cached under another key.
+    pub fn contains_value(&self, value: &str) -> bool {
+        !self.core.keys_with_value(value).is_empty()
+    }
+
+    /// Serializes the value of `key` into a versioned, checksummed payload.
+    /// 
+    /// Returns `None` if the key is absent or expired. The payload can be
+    /// handed to [`restore`](Self::restore) on this or any other cache, for
+    /// example to move a key between nodes or to back it up.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use spectra_cache::DistributedHashTable;
+    /// 
+    /// let mut source = DistributedHashTable::new();
+    /// source.insert("user:123", "John Doe");
+    /// let payload = source.dump("user:123").unwrap();
+    /// 
+    /// let mut target = DistributedHashTable::new();
+    /// target.restore("user:123", &payload, None).unwrap();
+    /// assert_eq!(target.get("user:123"), Some("John Doe"));
+    /// ```
+    pub fn dump(&mut self, key: &str) -> Option<Vec<u8>> {
+        self.core.dump(key)
+    }
+
+    /// Stores the value carried by a [`dump`](Self::dump) payload under `key`.
+    /// 
+    /// An existing entry is replaced. With `ttl` set to `None` the restored
+    /// entry never expires. Fails with [`CacheError::InvalidDump`] if the
+    /// payload is corrupt or was written by a newer format version.
+    pub fn restore(&mut self, key: &str, payload: &[u8], ttl: Option<Duration>) -> Result<(), CacheError> {
+        self.core.restore(key, payload, ttl)
+    }
+
+    /// Returns the approximate number of bytes used by the table's entries.
+    /// 
+    /// Counts key and value bytes plus a fixed per-entry overhead; this is
+    /// the figure `max_memory_bytes` is enforced against.
+    pub fn memory_usage(&self) -> usize {
+        self.core.memory_usage()
+    }
+
+    /// Returns the total weight of the entries, the figure `max_weight` is
+    /// enforced against.
+    /// 
+    /// This is the number of entries unless the cache was built with a
+    /// [`weigher`](CacheBuilder::weigher).
+    pub fn total_weight(&self) -> u64 {
+        self.core.total_weight()
+    }
+
+    /// Pre-allocates room for `additional` more entries ahead of an expected
+    /// burst of writes, so the burst doesn't pay for rehashing the table or
+    /// growing its Bloom filter at the worst moment.
+    /// 
+    /// Reserves the hash map's buckets, a Bloom sub-filter large enough for
+    /// the burst and, when values are indexed, the value index. The
+    /// recency and expiry orders are B-trees and still allocate per entry.
+    /// Reserved room is kept until the table is dropped.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use spectra_cache::DistributedHashTable;
+    /// 
+    /// let mut table = DistributedHashTable::new();
+    /// table.reserve_capacity(100_000);
+    /// assert!(table.capacity() >= 100_000);
+    /// for i in 0..100_000 {
+    ///     table.insert(&format!("order:{}", i), "pending");
+    /// }
+    /// ```
+    pub fn reserve_capacity(&mut self, additional: usize) {
+        self.core.reserve_capacity(additional);
+    }
+
+    /// Returns how many entries the table holds without allocating more
+    /// buckets.
+    pub fn capacity(&self) -> usize {
+        self.core.capacity()
+    }
+
+    /// Returns the `n` largest live entries with their size in bytes, largest first.
+    /// 
+    /// Sizes are measured the same way as [`memory_usage`](Self::memory_usage),
+    /// which answers "what is eating my memory". Every entry is visited, so
+    /// this is meant for operators, not hot paths. Time complexity: O(m log n)
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use spectra_cache::DistributedHashTable;
+    /// 
+    /// let mut cache = DistributedHashTable::new();
+    /// cache.insert("small", "x");
+    /// cache.insert("large", &"x".repeat(1024));
+    /// let biggest = cache.biggest_keys(1);
+    /// assert_eq!(biggest[0].0, "large");
+    /// ```
+    pub fn biggest_keys(&self, n: usize) -> Vec<(&String, usize)> {
+        self.core.biggest_keys(n)
+    }
+
+    /// Returns an iterator over the live entries from the least to the most
+    /// recently used, which is the order eviction removes them in.
+    /// 
+    /// Reads with [`get`](Self::get) and in-place writes move an entry to
+    /// the end; [`peek`](Self::peek) and this iterator don't.
+    pub fn iter_by_recency(&self) -> impl DoubleEndedIterator<Item = (&String, &str)> {
+        self.core.iter_by_recency()
+    }
+
+    /// Returns an iterator over the live entries from the least to the most
+    /// used since they were stored, ties broken by recency.
+    /// 
+    /// Uses are counted per entry as for [`iter_by_recency`](Self::iter_by_recency)
+    /// and start over when a key is inserted again. The entries are sorted
+    /// when the iterator is made.
+    pub fn iter_by_frequency(&self) -> impl Iterator<Item = (&String, &str)> {
+        self.core.iter_by_frequency()
+    }
+
+    /// Returns up to `n` live keys that would be evicted next, least
+    /// recently used first.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use spectra_cache::DistributedHashTable;
+    /// 
+    /// let mut cache = DistributedHashTable::new();
+    /// cache.insert("a", "1");
+    /// cache.insert("b", "2");
+    /// cache.insert("c", "3");
+    /// cache.get("a");
+    /// assert_eq!(cache.coldest(2), ["b", "c"]);
+    /// assert_eq!(cache.hottest(1), ["a"]);
+    /// ```
+    pub fn coldest(&self, n: usize) -> Vec<&String> {
+        self.core.coldest(n)
+    }
+
+    /// Returns up to `n` live keys, most recently used first.
+    pub fn hottest(&self, n: usize) -> Vec<&String> {
+        self.core.hottest(n)
+    }
+
+    /// Returns a snapshot of the table's hit, miss, and eviction counters.
+    /// 
+    /// Besides lifetime totals the snapshot carries per-second rates over the
+    /// last 1, 5 and 15 minutes, which surface recent regressions that the
+    /// lifetime hit ratio hides.
+    pub fn stats(&self) -> CacheStats {
+        self.core.stats()
+    }
+
+    /// Returns the settings the table is running with.
+    /// 
+    /// See [`EffectiveConfig`] for what is reported.
+    pub fn config(&self) -> EffectiveConfig {
+        self.core.config()
+    }
+
+    /// Writes pending write-back changes to the backing store now.
+    /// 
+    /// Does nothing unless the table was built with `write_back`. Failed
+    /// writes are counted in [`CacheStats::store_errors`] and kept for the
+    /// next flush.
+    #[cfg(feature = "std")]
+    pub fn flush(&self) {
+        self.core.flush();
+    }
+
+    /// Has the write-back flusher write every change made so far, and waits
+    /// up to `timeout` for it to finish.
+    /// 
+    /// Returns true once all of them are in the backing store, or false if
+    /// some are still pending when the timeout expires, for example because
+    /// the store keeps failing. Unlike [`flush`](Self::flush), the caller
+    /// never blocks past `timeout` on a slow store. Without `write_back`
+    /// nothing is ever pending, so this returns true right away.
+    #[cfg(feature = "std")]
+    pub fn flush_and_wait(&self, timeout: Duration) -> bool {
+        self.core.flush_and_wait(timeout)
+    }
+
+    /// Inserts a key-value pair like [`insert`](Self::insert) and waits up
+    /// to `timeout` for it to become as durable as `durability` asks.
+    /// 
+    /// The write is applied in any case; an error only means it couldn't
+    /// be acknowledged, with [`CacheError::NotDurable`] saying why: the
+    /// table has neither a backing store nor an append-only log, the store
+    /// rejected the write, the log couldn't be synced, or the write-back
+    /// flusher didn't get to it in time. Waiting on write-back also flushes
+    /// every change made before this one, and `Durability::Disk` syncs the
+    /// log whatever its `FsyncPolicy`.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use spectra_cache::{CacheError, DistributedHashTable, Durability};
+    /// use std::time::Duration;
+    /// 
+    /// let mut cache = DistributedHashTable::new();
+    /// let timeout = Duration::from_millis(100);
+    /// assert_eq!(cache.insert_with_durability("session:1", "active", Durability::Memory, timeout), Ok(()));
+    /// assert!(matches!(
+    ///     cache.insert_with_durability("order:1", "paid", Durability::Disk, timeout),
+    ///     Err(CacheError::NotDurable { .. })
+    /// ));
+    /// assert_eq!(cache.get("order:1"), Some("paid"));
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn insert_with_durability(&mut self, key: &str, value: &str, durability: Durability, timeout: Duration) -> Result<(), CacheError> {
+        self.core.insert_with_durability(key, value, durability, timeout)
+    }
+
+    /// Starts appending every write to the append-only log at `path`,
+    /// creating it if needed, for [`replay`](Self::replay) to rebuild the
+    /// table after a crash.
+    /// 
+    /// Inserts, updates, removals, TTL changes, evictions and `clear()` are
+    /// logged as they happen; `fsync` decides how often the log is synced
+    /// to disk. A new log starts with the entries already in the table. An
+    /// existing log is appended to as it is, so replay it first when
+    /// restarting. The log keeps growing until it is deleted; take a
+    /// snapshot with [`save_snapshot`](Self::save_snapshot) and start a new
+    /// log to compact it. Failed writes are counted in
+    /// [`CacheStats::aof_errors`]. Clones of the table don't log.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use spectra_cache::{DistributedHashTable, FsyncPolicy};
+    /// 
+    /// let path = std::env::temp_dir().join(format!("spectra-cache-doc-{}.aof", std::process::id()));
+    /// # let _ = std::fs::remove_file(&path);
+    /// let mut cache = DistributedHashTable::new();
+    /// cache.enable_aof(&path, FsyncPolicy::EverySecond).unwrap();
+    /// cache.insert("user:1", "Ana");
+    /// cache.insert("user:2", "Bia");
+    /// cache.remove("user:1");
+    /// drop(cache); // the process dies
+    /// 
+    /// let mut restarted = DistributedHashTable::new();
+    /// assert_eq!(restarted.replay(&path).unwrap(), 3);
+    /// assert_eq!(restarted.get("user:1"), None);
+    /// assert_eq!(restarted.get("user:2"), Some("Bia"));
+    /// restarted.enable_aof(&path, FsyncPolicy::EverySecond).unwrap();
+    /// # std::fs::remove_file(&path).unwrap();
+    /// ```
+    #[cfg(feature = "persistence")]
+    pub fn enable_aof(&mut self, path: impl AsRef<Path>, fsync: FsyncPolicy) -> io::Result<()> {
+        self.core.enable_aof(path.as_ref(), fsync)
+    }
+
+    /// Stops logging writes, syncing and closing the append-only log.
+    #[cfg(feature = "persistence")]
+    pub fn disable_aof(&mut self) {
+        self.core.disable_aof();
+    }
+
+    /// Returns true once the append-only log or
+    /// [`save_snapshot`](Self::save_snapshot) failed three times in a row.
+    /// 
+    /// What the table does then depends on the [`PersistenceFailure`]
+    /// policy it was built with. With [`PersistenceFailure::ServeFromMemory`]
+    /// writes go on, and the table recovers on its own once the log writes
+    /// a record again or a snapshot completes, for whichever of the two was
+    /// failing. With [`PersistenceFailure::RejectWrites`] nothing reaches
+    /// the log any more, so a table degraded by its log stays degraded until
+    /// [`resume_persistence`](Self::resume_persistence) is called; one
+    /// degraded by snapshots also recovers when a snapshot completes.
+    /// 
+    /// While writes are rejected, every method that changes the table
+    /// refuses: those returning a `Result`, and the `try_*` variants, fail
+    /// with [`CacheError::PersistenceFailed`], [`entry`](Self::entry)
+    /// panics, and the others change nothing and return as if there was
+    /// nothing to change.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use spectra_cache::{CacheError, DistributedHashTable, PersistenceFailure};
+    /// 
+    /// let mut cache = DistributedHashTable::builder()
+    ///     .on_persistence_failure(PersistenceFailure::RejectWrites)
+    ///     .build();
+    /// let unwritable = std::env::temp_dir().join("spectra-cache-missing-dir").join("cache.snap");
+    /// for _ in 0..3 {
+    ///     assert!(cache.save_snapshot(&unwritable).is_err());
+    /// }
+    /// assert!(cache.is_degraded());
+    /// 
+    /// cache.insert("user:1", "Ana");
+    /// assert_eq!(cache.get("user:1"), None);
+    /// assert_eq!(cache.check_writable(), Err(CacheError::PersistenceFailed));
+    /// 
+    /// cache.resume_persistence();
+    /// cache.insert("user:1", "Ana");
+    /// assert_eq!(cache.get("user:1"), Some("Ana"));
+    /// ```
+    #[cfg(feature = "persistence")]
+    pub fn is_degraded(&self) -> bool {
+        self.core.is_degraded()
+    }
+
+    /// Forgets the persistence failures that made the table degraded, once
+    /// the cause is fixed, so that writes are accepted again.
+    #[cfg(feature = "persistence")]
+    pub fn resume_persistence(&mut self) {
+        self.core.resume_persistence();
+    }
+
+    /// Returns [`CacheError::PersistenceFailed`] if the table is degraded
+    /// and rejects writes, so callers can check before writing.
+    #[cfg(feature = "persistence")]
+    pub fn check_writable(&self) -> Result<(), CacheError> {
+        self.core.check_writable()
+    }
+
+    /// Applies the operations logged at `path` by
+    /// [`enable_aof`](Self::enable_aof) in order, and returns how many
+    /// there were. A transaction made with [`apply`](Self::apply) counts as
+    /// one operation, and a torn one is dropped whole.
+    /// 
+    /// Entries keep their TTLs, counted from when they were logged, and
+    /// entries that expired meanwhile are left out. The operations are
+    /// neither logged again nor sent to the backing store. A record torn by
+    /// a crash mid-write is cut off the end of the file, so logging can
+    /// resume after it. Nothing is applied from a log that is corrupt
+    /// anywhere else, which fails with `io::ErrorKind::InvalidData`
+    /// wrapping a [`CacheError::InvalidDump`].
+    #[cfg(feature = "persistence")]
+    pub fn replay(&mut self, path: impl AsRef<Path>) -> io::Result<usize> {
+        self.core.replay(path.as_ref())
+    }
+
+    /// Makes this table the primary of the replica at the other end of
+    /// `transport`: the replica is sent the live entries, then every write
+    /// as it happens, in order.
+    /// 
+    /// The replica applies them with
+    /// [`apply_replicated`](Self::apply_replicated). Each replica is fed
+    /// by its own thread, so a slow or unreachable one doesn't hold up the
+    /// table; one whose link breaks stops counting as a replica. Writes can
+    /// wait for replicas to apply them with
+    /// [`insert_with_durability`](Self::insert_with_durability) and
+    /// [`Durability::Replicated`]. Deadlines are sent as wall-clock times,
+    /// so entries with a TTL expire together on nodes with synchronized
+    /// clocks. Clones of the table don't replicate.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use spectra_cache::{ChannelTransport, DistributedHashTable, Durability};
+    /// use std::time::Duration;
+    /// 
+    /// let (primary_end, mut replica_end) = ChannelTransport::pair();
+    /// let mut primary = DistributedHashTable::new();
+    /// primary.insert("user:1", "Ana");
+    /// primary.add_replica(primary_end);
+    /// 
+    /// let follower = std::thread::spawn(move || {
+    ///     let mut replica = DistributedHashTable::new();
+    ///     while replica.get("user:2").is_none() {
+    ///         replica.apply_replicated(&mut replica_end, Duration::from_millis(100)).unwrap();
+    ///     }
+    ///     replica
+    /// });
+    /// primary
+    ///     .insert_with_durability("user:2", "Bia", Durability::Replicated(1), Duration::from_secs(5))
+    ///     .unwrap();
+    /// 
+    /// let mut replica = follower.join().unwrap();
+    /// assert_eq!(replica.get("user:1"), Some("Ana"));
+    /// assert_eq!(replica.get("user:2"), Some("Bia"));
+    /// ```
+    #[cfg(feature = "cluster")]
+    pub fn add_replica(&mut self, transport: impl Transport + 'static) {
+        self.core.add_replica(Box::new(transport));
+    }
+
+    /// Returns how many replicas added with
+    /// [`add_replica`](Self::add_replica) are still connected.
+    #[cfg(feature = "cluster")]
+    pub fn replica_count(&self) -> usize {
+        self.core.replicas()
+    }
+
+    /// Applies the writes a primary streamed through `transport` and
+    /// acknowledges them, making this table a replica of the table that
+    /// called [`add_replica`](Self::add_replica).
+    /// 
+    /// Waits up to `timeout` for the first write, then applies every other
+    /// one that already arrived, in order, and returns how many there were:
+    /// `0` if none arrived in time. Call it in a loop to follow the
+    /// primary. Applied writes go to this table's own backing store,
+    /// append-only log and replicas, so replicas can be chained. Fails with
+    /// `io::ErrorKind::UnexpectedEof` once the primary is gone, and with
+    /// `io::ErrorKind::InvalidData` for a malformed write. While the table
+    /// rejects writes because its persistence keeps failing, it fails with
+    /// an error wrapping [`CacheError::PersistenceFailed`] and leaves the
+    /// writes unread and unacknowledged.
+    #[cfg(feature = "cluster")]
+    pub fn apply_replicated(&mut self, transport: &mut impl Transport, timeout: Duration) -> io::Result<usize> {
+        self.core.apply_replicated(transport, timeout)
+    }
+
+    /// Returns a Merkle tree over the live entries, spread over `buckets`
+    /// buckets by key hash, for finding where two tables differ without
+    /// sending their content.
+    /// 
+    /// Compare the digests of two tables with [`MerkleDigest::diff`], have
+    /// the table with the right data [`export_buckets`](Self::export_buckets)
+    /// the buckets that differ, and apply the patch to the other one with
+    /// [`apply_bucket_patch`](Self::apply_bucket_patch). Digests and
+    /// patches convert to bytes to cross the network. More buckets make
+    /// digests bigger and patches smaller. Only keys and values are
+    /// compared, not TTLs.
+    /// 
+    /// Panics unless `buckets` is a power of two.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use spectra_cache::DistributedHashTable;
+    /// 
+    /// let mut primary = DistributedHashTable::new();
+    /// let mut replica = DistributedHashTable::new();
+    /// for i in 0..1000 {
+    ///     primary.insert(&format!("user:{}", i), "active");
+    ///     replica.insert(&format!("user:{}", i), "active");
+    /// }
+    /// primary.insert("user:7", "banned");
+    /// replica.insert("user:9999", "leftover");
+    /// 
+    /// let divergent = primary.merkle_digest(256).diff(&replica.merkle_digest(256));
+    /// assert!(divergent.len() <= 2);
+    /// let patch = primary.export_buckets(&divergent);
+    /// replica.apply_bucket_patch(&patch).unwrap();
+    /// 
+    /// assert_eq!(replica.get("user:7"), Some("banned"));
+    /// assert_eq!(replica.get("user:9999"), None);
+    /// assert_eq!(replica.merkle_digest(256).root(), primary.merkle_digest(256).root());
+    /// ```
+    #[cfg(feature = "cluster")]
+    pub fn merkle_digest(&self, buckets: usize) -> MerkleDigest {
+        self.core.merkle_digest(buckets)
+    }
+
+    /// Returns the live entries in the `divergent` buckets, with their TTLs,
+    /// for another table to apply with
+    /// [`apply_bucket_patch`](Self::apply_bucket_patch).
+    #[cfg(feature = "cluster")]
+    pub fn export_buckets(&self, divergent: &DivergentBuckets) -> BucketPatch {
+        self.core.export_buckets(divergent)
+    }
+
+    /// Makes the patch's buckets hold exactly the patch's entries: keys in
+    /// those buckets that are not in the patch are removed, and the others
+    /// written. Returns how many keys were written or removed.
+    /// 
+    /// Fails with [`CacheError::InvalidDump`] for a malformed patch, and
+    /// leaves the table untouched.
+    #[cfg(feature = "cluster")]
+    pub fn apply_bucket_patch(&mut self, patch: &BucketPatch) -> Result<usize, CacheError> {
+        self.core.apply_bucket_patch(patch)
+    }
+
+    /// Turns on Bloom filter audit mode.
+    /// 
+    /// Every lookup is cross-checked against the table and the outcome is
+    /// recorded in the report returned by [`bloom_audit`](Self::bloom_audit).
+    /// Keys the filter wrongly rejects are still found, so enabling the audit
+    /// never changes lookup results.
+    pub fn enable_bloom_audit(&mut self) {
+        self.core.enable_bloom_audit();
+    }
+
+    /// Turns off Bloom filter audit mode and discards the collected counters.
+    pub fn disable_bloom_audit(&mut self) {
+        self.core.disable_bloom_audit();
+    }
+
+    /// Returns the Bloom filter audit counters, or None if auditing is disabled.
+    pub fn bloom_audit(&self) -> Option<BloomAudit> {
+        self.core.bloom_audit()
+    }
+
+    /// Returns true if the table was built with
+    /// [`adaptive_bloom`](CacheBuilder::adaptive_bloom) and is currently
+    /// skipping the Bloom filter because it isn't paying for itself.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// # #[cfg(feature = "std")] {
+    /// use spectra_cache::DistributedHashTable;
+    /// 
+    /// let mut cache = DistributedHashTable::builder().adaptive_bloom().build();
+    /// cache.insert("user:1", "Ana");
+    /// for _ in 0..10_000 {
+    ///     cache.get("user:1");
+    /// }
+    /// assert!(cache.bloom_bypassed()); // every lookup hit, so the filter saved nothing
+    /// # }
+    /// ```
+    pub fn bloom_bypassed(&self) -> bool {
+        self.core.bloom_bypassed()
+    }
+
+    /// Rebuilds the Bloom filter from the keys currently stored.
+    /// 
+    /// The filter can't forget keys, so lookups of keys that were removed,
+    /// expired or evicted keep getting past it to the map. Rebuilding drops
+    /// them; [`CacheBuilder::bloom_rebuild`] does it automatically once
+    /// they make up too much of the filter.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use spectra_cache::DistributedHashTable;
+    /// 
+    /// let mut cache = DistributedHashTable::new();
+    /// for i in 0..1000 {
+    ///     cache.insert(&format!("session:{}", i), "active");
+    /// }
+    /// cache.retain(|key, _| !key.starts_with("session:"));
+    /// cache.rebuild_bloom_filter();
+    /// assert!(cache.stats().bloom_fill_ratio < 0.01);
+    /// ```
+    pub fn rebuild_bloom_filter(&mut self) {
+        self.core.rebuild_bloom_filter();
+    }
+
+    /// Starts counting accesses to find the `k` most accessed keys,
+    /// reported by [`hot_keys`](Self::hot_keys). Restarts the counts if
+    /// tracking was already on.
+    ///
+    /// Reads and writes of a key count as accesses, hits and misses alike.
+    /// Counts are estimated with a [`CountMinSketch`] of fixed size, so
+    /// tracking costs a few kilobytes however many keys the table sees, and
+    /// may overcount keys that share counters with hot ones. Counts are
+    /// halved every 65536 accesses, so keys that cooled down leave the top.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use spectra_cache::DistributedHashTable;
+    ///
+    /// let mut table = DistributedHashTable::new();
+    /// table.track_hot_keys(2);
+    /// for i in 0..100 {
+    ///     table.get("config:flags");
+    ///     if i % 3 == 0 {
+    ///         table.insert(&format!("session:{}", i), "x");
+    ///         table.get("user:1");
+    ///     }
+    /// }
+    ///
+    /// let hot: Vec<String> = table.hot_keys().into_iter().map(|(key, _)| key).collect();
+    /// assert_eq!(hot, ["config:flags", "user:1"]);
+    /// ```
+    #[cfg(feature = "probabilistic")]
+    pub fn track_hot_keys(&mut self, k: usize) {
+        self.core.track_hot_keys(k);
+    }
+
+    /// Stops tracking hot keys and discards the counts.
+    #[cfg(feature = "probabilistic")]
+    pub fn stop_tracking_hot_keys(&mut self) {
+        self.core.stop_tracking_hot_keys();
+    }
+
+    /// Returns up to `k` of the most accessed keys with their estimated
+    /// access counts, most accessed first, or nothing if tracking is off.
+    #[cfg(feature = "probabilistic")]
+    pub fn hot_keys(&self) -> Vec<(String, u64)> {
+        self.core.hot_keys()
+    }
+
+    /// Registers a callback fired whenever an entry leaves the table.
+    /// 
+    /// The callback receives the key, the value that left, and the
+    /// [`RemovalCause`]: expiration, eviction for memory, explicit removal
+    /// (including `clear()`), or replacement by a new value. Several
+    /// listeners may be registered; they run in registration order, inline
+    /// with the operation that triggered them.
+    /// 
+    /// Inline listeners run while the table is mutably borrowed, so they
+    /// can't call back into it. If the table is shared behind a lock, build
+    /// it with [`CacheBuilder::listener_queue`] instead: listeners then run
+    /// on a dispatcher thread once the write has returned, and may lock the
+    /// table again without deadlocking.
+    /// 
+    /// A panic inside a listener is caught and counted in
+    /// [`CacheStats::callback_panics`]; the entry is still removed, the other
+    /// listeners still run, and the cache stays usable.
+    /// 
+    /// Expired entries are reported when the table notices them, on the next
+    /// access to the key, not at the exact moment their TTL runs out.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use spectra_cache::{DistributedHashTable, RemovalCause};
+    /// use std::sync::{Arc, Mutex};
+    /// 
+    /// let evicted = Arc::new(Mutex::new(Vec::new()));
+    /// let sink = Arc::clone(&evicted);
+    /// 
+    /// let mut cache = DistributedHashTable::builder().max_memory_bytes(1024).build();
+    /// cache.on_evict(move |key, _value, cause| {
+    ///     if cause == RemovalCause::Evicted {
+    ///         sink.lock().unwrap().push(key.to_string()); // e.g. write back to the database
+    ///     }
+    /// });
+    /// cache.insert("a", &"x".repeat(600));
+    /// cache.insert("b", &"x".repeat(600));
+    /// assert_eq!(*evicted.lock().unwrap(), vec!["a"]);
+    /// ```
+    pub fn on_evict(&mut self, listener: impl FnMut(&str, &str, RemovalCause) + Send + 'static) {
+        self.core.on_evict(Box::new(listener));
+    }
+
+    /// Unregisters every `on_evict` listener.
+    pub fn remove_evict_listeners(&mut self) {
+        self.core.remove_evict_listeners();
+    }
+
+    /// Subscribes to changes of the keys matching a glob pattern.
+    /// 
+    /// Every insert, update, removal, expiration and eviction of a matching
+    /// key is sent to the returned channel as a [`CacheEvent`], so other
+    /// components can react to invalidations without polling. Use `"*"` to
+    /// watch every key. Events are sent as the change happens; dropping the
+    /// receiver ends the subscription. Like `on_evict`, expirations are
+    /// reported when the cache notices them, and values read through from a
+    /// backing store don't count as inserts. Subscriptions aren't cloned.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use spectra_cache::{CacheEvent, DistributedHashTable};
+    /// 
+    /// let mut cache = DistributedHashTable::new();
+    /// let events = cache.subscribe("config:*");
+    /// cache.insert("config:theme", "dark");
+    /// cache.insert("user:1", "Ana");
+    /// cache.remove("config:theme");
+    /// 
+    /// let keys: Vec<_> = events.try_iter().map(|event| event.key().to_string()).collect();
+    /// assert_eq!(keys, ["config:theme", "config:theme"]);
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn subscribe(&mut self, pattern: &str) -> Receiver<CacheEvent> {
+        self.core.subscribe(pattern)
+    }
+
+    /// Returns up to `n` of the latest changes to `key`, newest first,
+    /// including the ones that removed it.
+    /// 
+    /// Only recorded for tables built with [`CacheBuilder::history`];
+    /// otherwise the history is always empty. Each change carries the
+    /// [`AuditContext`] set when it was made, so with a context set per
+    /// caller this shows who overwrote an entry and when.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use spectra_cache::{AuditContext, CacheEvent, DistributedHashTable};
+    /// 
+    /// let mut cache = DistributedHashTable::builder().history(8).build();
+    /// cache.set_audit_context(AuditContext { actor: Some("billing".to_string()), client_addr: None });
+    /// cache.insert("plan:42", "pro");
+    /// cache.set_audit_context(AuditContext { actor: Some("cron".to_string()), client_addr: None });
+    /// cache.insert("plan:42", "free");
+    /// 
+    /// let history = cache.history("plan:42", 10);
+    /// assert_eq!(history.len(), 2);
+    /// assert_eq!(history[0].context.actor.as_deref(), Some("cron"));
+    /// assert_eq!(history[1].event, CacheEvent::Insert { key: "plan:42".to_string(), value: "pro".to_string() });
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn history(&self, key: &str, n: usize) -> Vec<HistoryEntry> {
+        self.core.history(key, n)
+    }
+
+    /// Sends a record of every destructive operation to `sink`.
+    /// 
+    /// Flushes, mass deletes, and configuration changes are audited; single-key
+    /// writes and removals are not. Replaces any previously installed sink.
+    /// A panicking sink is caught and counted in `CacheStats::callback_panics`.
+    #[cfg(feature = "std")]
+    pub fn set_audit_sink(&mut self, sink: impl AuditSink + 'static) {
+        self.core.set_audit_sink(Box::new(sink));
+    }
+
+    /// Stops auditing destructive operations.
+    #[cfg(feature = "std")]
+    pub fn remove_audit_sink(&mut self) {
+        self.core.remove_audit_sink();
+    }
+
+    /// Sets who subsequent audited operations are attributed to.
+    pub fn set_audit_context(&mut self, context: AuditContext) {
+        self.core.set_audit_context(context);
+    }
+
+    /// Returns who audited operations are currently attributed to.
+    pub fn audit_context(&self) -> &AuditContext {
+        self.core.audit_context()
+    }
+}
+
+#[cfg(feature = "core")]
+impl CacheBuilder<DistributedHashTable> {
+    /// Makes the table hash keys with `S` instead of `RandomState`.
+    /// 
+    /// For short keys hashing dominates the cost of a lookup, so a faster
+    /// non-cryptographic hasher (ahash, fxhash) can pay off. `S` replaces
+    /// SipHash for both the map and the Bloom filter in front of it; only
+    /// pick a hasher without HashDoS resistance when keys don't come from
+    /// untrusted clients.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use spectra_cache::DistributedHashTable;
+    /// use std::collections::hash_map::DefaultHasher;
+    /// use std::hash::BuildHasherDefault;
+    /// 
+    /// let mut table = DistributedHashTable::builder()
+    ///     .hasher::<BuildHasherDefault<DefaultHasher>>()
+    ///     .build();
+    /// table.insert("user:1", "Alice");
+    /// assert_eq!(table.get("user:1"), Some("Alice"));
+    /// ```
+    pub fn hasher<S: BuildHasher + Clone + Default>(self) -> CacheBuilder<DistributedHashTable<S>> {
+        self.cast()
+    }
+}
+
+#[cfg(feature = "core")]
+impl<S: BuildHasher + Clone + Default> CacheBuilder<DistributedHashTable<S>> {
+    /// Creates the distributed hash table.
+    pub fn build(self) -> DistributedHashTable<S> {
+        DistributedHashTable::with_config(self.config)
+    }
+}
+
+#[cfg(feature = "core")]
+impl<S: BuildHasher + Clone + Default> CacheType for DistributedHashTable<S> {
+    type Value = String;
+}
+
+#[cfg(feature = "core")]
+impl<S: BuildHasher + Clone + Default> Default for DistributedHashTable<S> {
+    fn default() -> Self {
+        Self::with_config(CacheConfig::default())
+    }
+}
+
+/// Two tables are equal when they hold the same live keys and values;
+/// expiration settings, statistics and listeners are not compared.
+#[cfg(feature = "core")]
+impl<S: BuildHasher + Clone + Default> PartialEq for DistributedHashTable<S> {
+    fn eq(&self, other: &Self) -> bool {
+        self.core.same_content(&other.core)
+    }
+}
+
+/// Inserts every pair with [`insert`](DistributedHashTable::insert), so the
+/// default TTL applies. Accepts owned or borrowed strings, e.g. a drained
+/// `HashMap<String, String>`.
+#[cfg(feature = "core")]
+impl<S: BuildHasher + Clone + Default, K: AsRef<str>, V: AsRef<str>> Extend<(K, V)> for DistributedHashTable<S> {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        let iter = iter.into_iter();
+        self.reserve_capacity(iter.size_hint().0);
+        for (key, value) in iter {
+            self.insert(key.as_ref(), value.as_ref());
+        }
+    }
+}
+
+/// Builds a table with default settings holding the pairs; a later pair
+/// for the same key replaces an earlier one.
+#[cfg(feature = "core")]
+impl<S: BuildHasher + Clone + Default, K: AsRef<str>, V: AsRef<str>> FromIterator<(K, V)> for DistributedHashTable<S> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut table = Self::default();
+        table.extend(iter);
+        table
+    }
+}
+
+/// A B-tree based cache implementation that provides O(log n) access time with ordered keys.
+/// 
+/// This structure manages cache entries with support for:
+/// - Ordered key-value lookups
+/// - TTL-based expiration
+/// - Automatic cleanup of expired entries
+/// - Thread-safe operations
+#[cfg(feature = "core")]
+#[derive(Debug, Clone)]
+pub struct BTreeCache {
+    core: CacheCore<BTreeMap<String, CacheEntry>>,
+}
+
+#[cfg(feature = "core")]
+impl BTreeCache {
+    /// Creates a new empty B-tree cache.
+    pub fn new() -> Self {
+        Self {
+            core: CacheCore::new(),
+        }
+    }
+
+    /// Returns a builder for a B-tree cache with non-default settings.
+    pub fn builder() -> CacheBuilder<Self> {
+        CacheBuilder::new()
+    }
+
+    /// Returns a deep copy whose entries count as freshly written.
+    /// 
+    /// `clone()` keeps every entry's remaining TTL and idle time; this
+    /// restarts them, so each entry gets its full TTL again in the copy.
+    /// Either way the copy keeps the configuration and eviction order,
+    /// starts with zeroed statistics, and has no `on_evict` listeners or
+    /// audit sink, since callbacks can't be cloned.
+    pub fn clone_with_fresh_timestamps(&self) -> Self {
+        let mut copy = self.clone();
+        copy.core.refresh_timestamps();
+        copy
+    }
+
+    fn with_config(config: CacheConfig) -> Self {
+        Self {
+            core: CacheCore::with_config(config),
+        }
+    }
+
+    /// Returns the number of entries in the cache.
+    pub fn size(&self) -> usize {
+        self.core.size()
+    }
+
+    /// Returns true if the cache is empty.
+    pub fn is_empty(&self) -> bool {
+        self.core.is_empty()
+    }
+
+    /// Inserts a key-value pair into the cache.
+    /// 
+    /// If the key already exists, the value will be updated.
+    /// Keys are maintained in sorted order.
+    /// The entry never expires unless the cache was built with a default TTL.
+    pub fn insert(&mut self, key: &str, value: &str) {
+        self.core.insert(key, value);
+    }
+
+    /// Inserts a key-value pair, failing if the cache refuses writes.
+    /// 
+    /// See [`DistributedHashTable::try_insert`].
+    pub fn try_insert(&mut self, key: &str, value: &str) -> Result<(), CacheError> {
+        self.core.try_insert(key, value)
+    }
+
+    /// Inserts a key-value pair with TTL into the cache.
+    /// 
+    /// The entry will be automatically removed when the TTL expires.
+    /// Keys are maintained in sorted order.
+    pub fn insert_with_ttl(&mut self, key: &str, value: &str, ttl: Duration) {
+        self.core.insert_entry(key, CacheEntry::with_ttl(key, value, Some(ttl), self.core.now()));
+    }
+
+    /// Inserts a key-value pair that expires after a period of inactivity.
+    /// 
+    /// Unlike [`insert_with_ttl`](Self::insert_with_ttl), the expiration clock
+    /// restarts every time the entry is read, so the entry only goes away
+    /// once it has been idle for longer than `idle`.
+    pub fn insert_with_tti(&mut self, key: &str, value: &str, idle: Duration) {
+        self.core.insert_entry(key, CacheEntry::with_tti(key, value, idle, self.core.now()));
+    }
+
+    /// Inserts a key-value pair carrying `tags`, with the default TTL.
+    /// 
+    /// See [`DistributedHashTable::insert_with_tags`].
+    pub fn insert_with_tags(&mut self, key: &str, value: &str, tags: &[&str]) {
+        self.core.insert_with_tags(key, value, tags);
+    }
+
+    /// Retrieves a value by key.
+    /// 
+    /// Returns None if the key doesn't exist or if the entry has expired.
+    /// Time complexity: O(log n)
+    /// 
+    /// See [`DistributedHashTable::get`].
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.core.get_ref(key)
+    }
+
+    /// Retrieves a value by key along with its version.
+    /// 
+    /// See [`DistributedHashTable::get_versioned`].
+    pub fn get_versioned(&mut self, key: &str) -> Option<(&str, u64)> {
+        self.core.get_versioned(key)
+    }
+
+    /// Retrieves a value without marking it as used.
+    /// 
+    /// Unlike [`get`](Self::get), the entry's access time and eviction order
+    /// are left alone and no hit or miss is counted, so monitoring code can
+    /// read every key without disturbing the LRU signal or the idle timeouts.
+    /// Expired entries are reported as absent but not removed.
+    pub fn peek(&self, key: &str) -> Option<&str> {
+        self.core.peek(key)
+    }
+
+    /// Retrieves a value by key within the latency budget of `options`.
+    /// 
+    /// See [`DistributedHashTable::get_with`].
+    pub fn get_with(&mut self, key: &str, options: &GetOptions) -> Option<&str> {
+        self.core.get_with(key, options)
+    }
+
+    /// Retrieves a value by key, flagged as stale if it is older than the
+    /// soft TTL.
+    /// 
+    /// See [`DistributedHashTable::get_stale`].
+    pub fn get_stale(&mut self, key: &str) -> Option<MaybeStale<&str>> {
+        self.core.get_stale(key)
+    }
+
+    /// Returns how long `key` has left to live.
+    /// 
+    /// Returns `None` if the key is missing, expired, or never expires; use
+    /// [`contains_key`](Self::contains_key) to tell these apart. For an entry
+    /// with an idle timeout this is the time left if it isn't read again.
+    /// Like `peek`, this doesn't count as an access.
+    pub fn ttl(&self, key: &str) -> Option<Duration> {
+        self.core.ttl(key)
+    }
+
+    /// Sets or replaces the TTL of an existing entry, counted from now.
+    /// 
+    /// An idle timeout, if the entry has one, keeps applying as well.
+    /// Returns true if the key existed.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use spectra_cache::BTreeCache;
+    /// use std::time::Duration;
+    /// 
+    /// let mut cache = BTreeCache::new();
+    /// cache.insert("session:456", "active");
+    /// assert_eq!(cache.ttl("session:456"), None);
+    /// 
+    /// cache.expire("session:456", Duration::from_secs(60));
+    /// assert!(cache.ttl("session:456").unwrap() <= Duration::from_secs(60));
+    /// 
+    /// cache.persist("session:456");
+    /// assert_eq!(cache.ttl("session:456"), None);
+    /// ```
+    pub fn expire(&mut self, key: &str, ttl: Duration) -> bool {
+        self.core.expire(key, ttl)
+    }
+
+    /// Removes the TTL and idle timeout of an entry so it never expires.
+    /// 
+    /// Returns true if the entry existed and had an expiry to remove.
+    pub fn persist(&mut self, key: &str) -> bool {
+        self.core.persist(key)
+    }
+
+    /// Removes a key-value pair from the cache.
+    /// 
+    /// Returns the removed value if the key existed.
+    /// Time complexity: O(log n)
+    pub fn remove(&mut self, key: &str) -> Option<String> {
+        self.core.remove(key)
+    }
+
+    /// Removes a key-value pair, failing if the cache refuses writes.
+    /// 
+    /// See [`DistributedHashTable::try_remove`].
+    pub fn try_remove(&mut self, key: &str) -> Result<Option<String>, CacheError> {
+        self.core.try_remove(key)
+    }
+
+    /// Updates an existing entry's value.
+    /// 
+    /// Returns true if the update was successful (key existed).
+    /// Time complexity: O(log n)
+    pub fn update(&mut self, key: &str, value: &str) -> bool {
+        self.core.update(key, value)
+    }
+
+    /// Updates an existing entry's value if it is still at
+    /// `expected_version`, and returns the new version.
+    /// 
+    /// See [`DistributedHashTable::update_if_version`].
+    pub fn update_if_version(&mut self, key: &str, expected_version: u64, value: &str) -> Result<u64, CacheError> {
+        self.core.update_if_version(key, expected_version, value)
+    }
+
+    /// Gets the given key's entry for in-place insert-or-update.
+    /// 
+    /// The key is looked up only once; expired entries are reported as
+    /// vacant. Panics if the cache refuses writes; see
+    /// [`DistributedHashTable::entry`].
+    pub fn entry(&mut self, key: &str) -> Entry<'_> {
+        self.core.entry(key)
+    }
+
+    /// Gets the given key's entry, failing if the cache refuses writes.
+    /// 
+    /// See [`DistributedHashTable::try_entry`].
+    pub fn try_entry(&mut self, key: &str) -> Result<Entry<'_>, CacheError> {
+        self.core.try_entry(key)
+    }
+
+    /// Adds `delta` to the integer stored under `key` and returns the new value.
+    /// 
+    /// A missing or expired key is created as if it held zero. The stored
+    /// entry keeps its TTL.
+    /// 
+    /// # Errors
+    /// 
+    /// Returns [`CacheError::NotAnInteger`] if the stored value isn't a
+    /// 64-bit integer and [`CacheError::Overflow`] if the result doesn't fit.
+    /// The stored value is left untouched in both cases.
+    pub fn incr(&mut self, key: &str, delta: i64) -> Result<i64, CacheError> {
+        self.core.incr(key, delta)
+    }
+
+    /// Subtracts `delta` from the integer stored under `key` and returns the new value.
+    /// 
+    /// Behaves like [`incr`](Self::incr) with a negated delta.
+    pub fn decr(&mut self, key: &str, delta: i64) -> Result<i64, CacheError> {
+        self.core.decr(key, delta)
+    }
+
+    /// Removes all entries from the cache.
+    pub fn clear(&mut self) {
+        self.core.clear();
+    }
+
+    /// Keeps only the live entries for which `keep` returns true, and
+    /// returns how many were removed. Entries are visited in key order.
+    /// 
+    /// See [`DistributedHashTable::retain`].
+    pub fn retain(&mut self, keep: impl FnMut(&str, &str) -> bool) -> usize {
+        self.core.retain(keep)
+    }
+
+    /// Removes every entry carrying `tag` and returns how many were removed.
+    /// 
+    /// See [`DistributedHashTable::invalidate_tag`].
+    pub fn invalidate_tag(&mut self, tag: &str) -> usize {
+        self.core.invalidate_tag(tag)
+    }
+
+    /// Applies every write of `transaction`, or none of them if a key it
+    /// watches no longer holds the expected value.
+    /// 
+    /// See [`DistributedHashTable::apply`].
+    pub fn apply(&mut self, transaction: Transaction) -> Result<(), CacheError> {
+        self.core.commit(transaction)
+    }
+
+    /// Removes every entry, returning the live ones in key order.
+    /// 
+    /// See [`DistributedHashTable::drain`].
+    pub fn drain(&mut self) -> Vec<(String, String)> {
+        self.core.drain()
+    }
+
+    /// Removes every expired entry now and returns how many were removed.
+    /// 
+    /// See [`DistributedHashTable::purge_expired`].
+    pub fn purge_expired(&mut self) -> usize {
+        self.core.purge_expired()
+    }
 
-impl Entry {
-    /// Creates a new cache entry without TTL.
+    /// Removes every expired entry now and returns them as `(key, value)`
+    /// pairs, in no particular order.
     /// 
-    /// # Arguments
+    /// See [`DistributedHashTable::drain_expired`].
+    pub fn drain_expired(&mut self) -> Vec<(String, String)> {
+        self.core.drain_expired()
+    }
+
+    /// Folds the live entries of `other` into this cache.
     /// 
-    /// * `key` - The unique identifier for this cache entry
-    /// * `value` - The data stored in this cache entry
+    /// See [`DistributedHashTable::merge_from`].
+    pub fn merge_from(&mut self, other: &Self, strategy: ConflictStrategy) -> usize {
+        self.core.merge_from(&other.core, strategy)
+    }
+
+    /// Bulk-loads `(key, value)` pairs and returns how many were loaded.
     /// 
-    /// # Examples
+    /// See [`DistributedHashTable::warm_from`].
+    pub fn warm_from<I, K, V>(&mut self, entries: I, mut progress: impl FnMut(usize)) -> usize
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: AsRef<str>,
+        V: AsRef<str>,
+    {
+        self.core.warm_from(entries, &mut progress)
+    }
+
+    /// Writes the live entries and their TTLs to a snapshot file at `path`.
     /// 
-    /// ```
-    /// use spectra_cache::DistributedHashTable;
+    /// See [`DistributedHashTable::save_snapshot`]; the two share a format,
+    /// so either cache can load the other's snapshots.
+    #[cfg(feature = "persistence")]
+    pub fn save_snapshot(&self, path: impl AsRef<Path>) -> io::Result<usize> {
+        self.core.save_snapshot(path.as_ref())
+    }
+
+    /// Loads a snapshot written by `save_snapshot`.
     /// 
-    /// let mut cache = DistributedHashTable::new();
-    /// cache.insert("user:123", "John Doe");
-    /// assert_eq!(cache.get("user:123"), Some("John Doe"));
-    /// ```
-    fn new(key: &str, value: &str) -> Self {
-        Self::with_ttl(key, value, None)
+    /// See [`DistributedHashTable::warm_from_snapshot`].
+    #[cfg(feature = "persistence")]
+    pub fn warm_from_snapshot(&mut self, path: impl AsRef<Path>, mut progress: impl FnMut(usize)) -> io::Result<usize> {
+        self.core.warm_from_snapshot(path.as_ref(), &mut progress)
+    }
+
+    /// Checks if a key exists in the cache.
+    /// 
+    /// Returns false if the key doesn't exist or if the entry has expired.
+    /// Time complexity: O(log n)
+    /// 
+    /// See [`DistributedHashTable::contains_key`].
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.core.contains_key(key)
+    }
+
+    /// Returns an iterator over the live keys in sorted order.
+    /// 
+    /// Expired entries are skipped even if they haven't been purged yet.
+    pub fn keys(&self) -> impl Iterator<Item = &String> {
+        self.core.keys()
+    }
+
+    /// Returns an iterator over the live values in key-sorted order.
+    pub fn values(&self) -> impl Iterator<Item = &String> {
+        self.core.values()
+    }
+
+    /// Returns an iterator over the live entries as `(key, value, metadata)`,
+    /// in key order.
+    /// 
+    /// See [`DistributedHashTable::iter`].
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &str, EntryMetadata)> {
+        self.core.iter()
+    }
+
+    /// Returns an iterator over every stored key in sorted order, including
+    /// expired entries that haven't been purged yet.
+    pub fn keys_raw(&self) -> impl Iterator<Item = &String> {
+        self.core.keys_raw()
     }
-    
-    /// Creates a new cache entry with optional TTL.
+
+    /// Returns a page of up to `limit` live entries in key order, starting
+    /// after the key `cursor`, and the cursor of the next page, or `None`
+    /// once the scan is done.
     /// 
-    /// # Arguments
+    /// Pass `None` as the cursor to start. The cursor is the last key
+    /// returned, so pages stay stable while entries are written: a key
+    /// added behind the cursor is simply not seen. Each page costs
+    /// O(log n + limit). See [`DistributedHashTable::scan`] for the
+    /// hash-ordered equivalent.
     /// 
-    /// * `key` - The unique identifier for this cache entry
-    /// * `value` - The data stored in this cache entry
-    /// * `ttl` - Optional duration after which the entry expires
+    /// A `limit` of zero returns an empty page and the cursor it was given.
     /// 
     /// # Examples
     /// 
     /// ```
-    /// use spectra_cache::DistributedHashTable;
-    /// use std::time::Duration;
+    /// use spectra_cache::BTreeCache;
     /// 
-    /// let mut cache = DistributedHashTable::new();
-    /// cache.insert_with_ttl("session:456", "active", Duration::from_secs(3600));
-    /// assert!(cache.contains_key("session:456"));
+    /// let mut cache = BTreeCache::new();
+    /// for key in ["a", "b", "c", "d", "e"] {
+    ///     cache.insert(key, "1");
+    /// }
+    /// 
+    /// let (page, cursor) = cache.scan(None, 2);
+    /// assert_eq!(page.iter().map(|(key, _)| key.as_str()).collect::<Vec<_>>(), ["a", "b"]);
+    /// let (page, cursor) = cache.scan(cursor.as_deref(), 2);
+    /// assert_eq!(page.iter().map(|(key, _)| key.as_str()).collect::<Vec<_>>(), ["c", "d"]);
+    /// let (page, cursor) = cache.scan(cursor.as_deref(), 2);
+    /// assert_eq!(page.len(), 1);
+    /// assert_eq!(cursor, None);
     /// ```
-    fn with_ttl(_key: &str, value: &str, ttl: Option<Duration>) -> Self {
-        let now = Instant::now();
-        Self {
-            value: value.to_string(),
-            ttl,
-            created_at: now,
-            last_accessed_at: now,
+    pub fn scan(&self, cursor: Option<&str>, limit: usize) -> (Vec<(&String, &str)>, Option<String>) {
+        if limit == 0 {
+            return (Vec::new(), cursor.map(str::to_string));
         }
+        let start = cursor.map_or(Bound::Unbounded, Bound::Excluded);
+        let now = self.core.now();
+        let mut live = self.core.entries
+            .range::<str, _>((start, Bound::Unbounded))
+            .filter(|(_, v)| !v.is_expired(now))
+            .map(|(k, v)| (k, v.value()));
+        let page: Vec<_> = live.by_ref().take(limit).collect();
+        let next = match live.next() {
+            Some(_) => page.last().map(|(k, _)| k.to_string()),
+            None => None,
+        };
+        (page, next)
     }
-    
-    /// Returns the value of the cache entry.
-    fn value(&self) -> &str {
-        &self.value
+
+    /// Returns an iterator over every stored value in key-sorted order,
+    /// including expired entries that haven't been purged yet.
+    pub fn values_raw(&self) -> impl Iterator<Item = &String> {
+        self.core.values_raw()
     }
-    
-    /// Checks if the entry has expired based on its TTL.
+
+    /// Returns an iterator over entries within a range of keys, in ascending order.
+    /// 
+    /// Accepts any range syntax over `&str`: `"a"..="c"`, `"a".."c"`,
+    /// `"user:"..` for everything from a key onwards, or `..` for all entries.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use spectra_cache::BTreeCache;
     /// 
-    /// Returns `true` if the entry has a TTL and the current age exceeds it.
-    /// Returns `false` if the entry has no TTL or hasn't expired yet.
-    fn is_expired(&self) -> bool {
-        self.ttl.map_or(false, |ttl| self.age() > ttl)
+    /// let mut cache = BTreeCache::new();
+    /// cache.insert("a", "1");
+    /// cache.insert("b", "2");
+    /// cache.insert("c", "3");
+    /// let after_a: Vec<_> = cache.range("b"..).map(|(k, _)| k.as_str()).collect();
+    /// assert_eq!(after_a, vec!["b", "c"]);
+    /// ```
+    pub fn range<'a, R: RangeBounds<&'a str>>(&self, range: R) -> impl DoubleEndedIterator<Item = (&String, &String)> {
+        self.core.entries.range::<str, _>(str_bounds(&range))
+            .map(|(k, v)| (k, &v.value))
     }
-    
-    /// Updates the last accessed time to now.
+
+    /// Returns an iterator over entries within a range of keys, in descending order.
     /// 
-    /// This method should be called whenever the entry is accessed
-    /// to maintain accurate idle time tracking.
-    fn touch(&mut self) {
-        self.last_accessed_at = Instant::now();
+    /// Takes the same ranges as [`range`](Self::range).
+    pub fn range_rev<'a, R: RangeBounds<&'a str>>(&self, range: R) -> impl Iterator<Item = (&String, &String)> {
+        self.range(range).rev()
     }
-    
-    /// Updates the value of the cache entry.
+
+    /// Returns an iterator over the live entries whose key starts with `prefix`.
     /// 
-    /// This method also calls `touch()` to update the last accessed time.
+    /// Only the matching key range is visited, in sorted order.
+    /// Time complexity: O(log n + k)
+    pub fn range_prefix<'a>(&'a self, prefix: &'a str) -> impl Iterator<Item = (&'a String, &'a str)> + 'a {
+        let now = self.core.now();
+        self.core.entries
+            .range::<str, _>((Bound::Included(prefix), Bound::Unbounded))
+            .take_while(move |(k, _)| k.starts_with(prefix))
+            .filter(move |(_, v)| !v.is_expired(now))
+            .map(|(k, v)| (k, v.value()))
+    }
+
+    /// Returns an iterator over the live keys matching a glob pattern, in sorted order.
     /// 
-    /// # Arguments
+    /// Supports the same syntax as
+    /// [`DistributedHashTable::keys_matching`](crate::DistributedHashTable::keys_matching).
+    /// The literal text before the first wildcard is used to narrow the scan to
+    /// a key range, so `user:*` only visits keys starting with `user:`.
+    pub fn keys_matching(&self, pattern: &str) -> impl Iterator<Item = &String> {
+        let glob = Glob::new(pattern);
+        let prefix = glob::literal_prefix(pattern);
+        let now = self.core.now();
+        self.core.entries
+            .range::<str, _>((Bound::Included(prefix.as_str()), Bound::Unbounded))
+            .take_while(move |(k, _)| k.starts_with(&prefix))
+            .filter(move |(k, v)| !v.is_expired(now) && glob.matches(k))
+            .map(|(k, _)| k)
+    }
+
+    /// Returns the live keys whose value equals `value`, in sorted order.
     /// 
-    /// * `new_value` - The new value to store in this entry
-    fn update_value(&mut self, new_value: &str) {
-        self.value = new_value.to_string();
-        self.touch();
+    /// See [`DistributedHashTable::keys_with_value`](crate::DistributedHashTable::keys_with_value).
+    pub fn keys_with_value(&self, value: &str) -> Vec<&String> {
+        let mut keys = self.core.keys_with_value(value);
+        keys.sort();
+        keys
     }
-    
-    /// Returns how long this entry has been in the cache.
-    fn age(&self) -> Duration {
-        self.created_at.elapsed()
+
+    /// Returns true if any live key holds `value`.
+    pub fn contains_value(&self, value: &str) -> bool {
+        !self.core.keys_with_value(value).is_empty()
     }
-}
 
-impl DistributedHashTable {
-    /// Creates a new empty distributed hash table.
-    pub fn new() -> Self {
-        Self {
-            entries: HashMap::new(),
-            bloom_filter: BloomFilter::new(1000, 0.01), // Inicializa com capacidade de 1000 e 1% de falsos positivos
-        }
+    /// Returns the first key-value pair in the cache.
+    pub fn first(&self) -> Option<(&String, &str)> {
+        self.core.entries.first_key_value().map(|(k, v)| (k, v.value()))
     }
 
-    /// Returns the number of entries in the table.
-    pub fn size(&self) -> usize {
-        self.entries.len()
+    /// Returns the last key-value pair in the cache.
+    pub fn last(&self) -> Option<(&String, &str)> {
+        self.core.entries.last_key_value().map(|(k, v)| (k, v.value()))
     }
 
-    /// Returns true if the table is empty.
-    pub fn is_empty(&self) -> bool {
-        self.entries.is_empty()
+    /// Removes and returns the live entry with the smallest key.
+    /// 
+    /// Expired entries at the front are dropped along the way. Returns
+    /// `None` without removing anything if the cache refuses writes.
+    pub fn pop_first(&mut self) -> Option<(String, String)> {
+        self.core.pop_first()
     }
 
-    /// Inserts a key-value pair into the table.
+    /// Removes and returns the live entry with the largest key.
     /// 
-    /// If the key already exists, the value will be updated.
-    pub fn insert(&mut self, key: &str, value: &str) {
-        let entry = Entry::new(key, value);
-        self.entries.insert(key.to_string(), entry);
-        self.bloom_filter.insert(&key.to_string());
+    /// Expired entries at the back are dropped along the way. Returns
+    /// `None` without removing anything if the cache refuses writes.
+    pub fn pop_last(&mut self) -> Option<(String, String)> {
+        self.core.pop_last()
     }
 
-    /// Inserts a key-value pair with TTL into the table.
+    /// Serializes the value of `key` into a versioned, checksummed payload.
     /// 
-    /// The entry will be automatically removed when the TTL expires.
-    pub fn insert_with_ttl(&mut self, key: &str, value: &str, ttl: Duration) {
-        let entry = Entry::with_ttl(key, value, Some(ttl));
-        self.entries.insert(key.to_string(), entry);
-        self.bloom_filter.insert(&key.to_string());
+    /// Returns `None` if the key is absent or expired. The payload can be
+    /// handed to [`restore`](Self::restore) on this or any other cache, for
+    /// example to move a key between nodes or to back it up.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use spectra_cache::BTreeCache;
+    /// 
+    /// let mut source = BTreeCache::new();
+    /// source.insert("user:123", "John Doe");
+    /// let payload = source.dump("user:123").unwrap();
+    /// 
+    /// let mut target = BTreeCache::new();
+    /// target.restore("user:123", &payload, None).unwrap();
+    /// assert_eq!(target.get("user:123"), Some("John Doe"));
+    /// ```
+    pub fn dump(&mut self, key: &str) -> Option<Vec<u8>> {
+        self.core.dump(key)
     }
 
-    /// Retrieves a value by key.
+    /// Stores the value carried by a [`dump`](Self::dump) payload under `key`.
     /// 
-    /// Returns None if the key doesn't exist or if the entry has expired.
-    pub fn get(&mut self, key: &str) -> Option<&str> {
-        // Primeiro verifica no Bloom Filter
-        if !self.bloom_filter.contains(&key.to_string()) {
-            return None;
-        }
+    /// An existing entry is replaced. With `ttl` set to `None` the restored
+    /// entry never expires. Fails with [`CacheError::InvalidDump`] if the
+    /// payload is corrupt or was written by a newer format version.
+    pub fn restore(&mut self, key: &str, payload: &[u8], ttl: Option<Duration>) -> Result<(), CacheError> {
+        self.core.restore(key, payload, ttl)
+    }
 
-        let is_expired = self.entries.get(key).map_or(false, |entry| entry.is_expired());
-        
-        if is_expired {
-            self.entries.remove(key);
-            None
-        } else if let Some(entry) = self.entries.get_mut(key) {
-            entry.touch();
-            Some(entry.value())
-        } else {
-            None
-        }
+    /// Returns the approximate number of bytes used by the cache's entries.
+    /// 
+    /// Counts key and value bytes plus a fixed per-entry overhead; this is
+    /// the figure `max_memory_bytes` is enforced against.
+    pub fn memory_usage(&self) -> usize {
+        self.core.memory_usage()
     }
 
-    /// Removes a key-value pair from the table.
+    /// Returns the total weight of the entries, the figure `max_weight` is
+    /// enforced against.
     /// 
-    /// Returns the removed value if the key existed.
-    pub fn remove(&mut self, key: &str) -> Option<String> {
-        if let Some(value) = self.entries.remove(key) {
-            Some(value.value().to_string())
-        } else {
-            None
-        }
+    /// This is the number of entries unless the cache was built with a
+    /// [`weigher`](CacheBuilder::weigher).
+    pub fn total_weight(&self) -> u64 {
+        self.core.total_weight()
     }
 
-    /// Updates an existing entry's value.
+    /// Pre-allocates room for `additional` more entries ahead of an expected
+    /// burst of writes.
     /// 
-    /// Returns true if the update was successful (key existed).
-    pub fn update(&mut self, key: &str, value: &str) -> bool {
-        if let Some(entry) = self.entries.get_mut(key) {
-            entry.update_value(value);
-            true
-        } else {
-            false
-        }
+    /// See [`DistributedHashTable::reserve_capacity`]. A B-tree allocates
+    /// node by node, so only the Bloom filter and the value index are
+    /// reserved.
+    pub fn reserve_capacity(&mut self, additional: usize) {
+        self.core.reserve_capacity(additional);
     }
 
-    /// Removes all entries from the table.
-    pub fn clear(&mut self) {
-        self.entries.clear();
-        self.bloom_filter.clear();
+    /// Returns how many entries the cache holds without allocating, which
+    /// for a B-tree is its size.
+    pub fn capacity(&self) -> usize {
+        self.core.capacity()
     }
 
-    /// Checks if a key exists in the table.
+    /// Returns the `n` largest live entries with their size in bytes, largest first.
     /// 
-    /// Returns false if the key doesn't exist or if the entry has expired.
-    pub fn contains_key(&mut self, key: &str) -> bool {
-        // Primeiro verifica no Bloom Filter
-        if !self.bloom_filter.contains(&key.to_string()) {
-            return false;
-        }
+    /// Sizes are measured the same way as [`memory_usage`](Self::memory_usage),
+    /// which answers "what is eating my memory". Every entry is visited, so
+    /// this is meant for operators, not hot paths. Time complexity: O(m log n)
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use spectra_cache::BTreeCache;
+    /// 
+    /// let mut cache = BTreeCache::new();
+    /// cache.insert("small", "x");
+    /// cache.insert("large", &"x".repeat(1024));
+    /// let biggest = cache.biggest_keys(1);
+    /// assert_eq!(biggest[0].0, "large");
+    /// ```
+    pub fn biggest_keys(&self, n: usize) -> Vec<(&String, usize)> {
+        self.core.biggest_keys(n)
+    }
 
-        if let Some(entry) = self.entries.get(key) {
-            if entry.is_expired() {
-                self.entries.remove(key);
-                false
-            } else {
-                true
-            }
-        } else {
-            false
-        }
+    /// Returns an iterator over the live entries from the least to the most
+    /// recently used, which is the order eviction removes them in.
+    /// 
+    /// See [`DistributedHashTable::iter_by_recency`].
+    pub fn iter_by_recency(&self) -> impl DoubleEndedIterator<Item = (&String, &str)> {
+        self.core.iter_by_recency()
     }
 
-    /// Returns an iterator over all keys in the table.
-    pub fn keys(&self) -> impl Iterator<Item = &String> {
-        self.entries.keys()
+    /// Returns an iterator over the live entries from the least to the most
+    /// used since they were stored, ties broken by recency.
+    /// 
+    /// See [`DistributedHashTable::iter_by_frequency`].
+    pub fn iter_by_frequency(&self) -> impl Iterator<Item = (&String, &str)> {
+        self.core.iter_by_frequency()
     }
 
-    /// Returns an iterator over all values in the table.
-    pub fn values(&self) -> impl Iterator<Item = &String> {
-        self.entries.values().map(|entry| &entry.value)
+    /// Returns up to `n` live keys that would be evicted next, least
+    /// recently used first.
+    pub fn coldest(&self, n: usize) -> Vec<&String> {
+        self.core.coldest(n)
     }
-}
 
-/// A B-tree based cache implementation that provides O(log n) access time with ordered keys.
-/// 
-/// This structure manages cache entries with support for:
-/// - Ordered key-value lookups
-/// - TTL-based expiration
-/// - Automatic cleanup of expired entries
-/// - Thread-safe operations
-#[derive(Debug)]
-pub struct BTreeCache {
-    entries: BTreeMap<String, Entry>,
-    bloom_filter: BloomFilter,
-}
+    /// Returns up to `n` live keys, most recently used first.
+    pub fn hottest(&self, n: usize) -> Vec<&String> {
+        self.core.hottest(n)
+    }
 
-impl BTreeCache {
-    /// Creates a new empty B-tree cache.
-    pub fn new() -> Self {
-        Self {
-            entries: BTreeMap::new(),
-            bloom_filter: BloomFilter::new(1000, 0.01), // Inicializa com capacidade de 1000 e 1% de falsos positivos
-        }
+    /// Returns a snapshot of the cache's hit, miss, and eviction counters.
+    /// 
+    /// Besides lifetime totals the snapshot carries per-second rates over the
+    /// last 1, 5 and 15 minutes, which surface recent regressions that the
+    /// lifetime hit ratio hides.
+    pub fn stats(&self) -> CacheStats {
+        self.core.stats()
     }
 
-    /// Returns the number of entries in the cache.
-    pub fn size(&self) -> usize {
-        self.entries.len()
+    /// Returns the settings the cache is running with.
+    /// 
+    /// See [`EffectiveConfig`] for what is reported.
+    pub fn config(&self) -> EffectiveConfig {
+        self.core.config()
     }
 
-    /// Returns true if the cache is empty.
-    pub fn is_empty(&self) -> bool {
-        self.entries.is_empty()
+    /// Writes pending write-back changes to the backing store now.
+    /// 
+    /// Does nothing unless the cache was built with `write_back`. Failed
+    /// writes are counted in [`CacheStats::store_errors`] and kept for the
+    /// next flush.
+    #[cfg(feature = "std")]
+    pub fn flush(&self) {
+        self.core.flush();
     }
 
-    /// Inserts a key-value pair into the cache.
+    /// Has the write-back flusher write every change made so far, and waits
+    /// up to `timeout` for it to finish.
     /// 
-    /// If the key already exists, the value will be updated.
-    /// Keys are maintained in sorted order.
-    pub fn insert(&mut self, key: &str, value: &str) {
-        let entry = Entry::new(key, value);
-        self.entries.insert(key.to_string(), entry);
-        self.bloom_filter.insert(&key.to_string());
+    /// See [`DistributedHashTable::flush_and_wait`].
+    #[cfg(feature = "std")]
+    pub fn flush_and_wait(&self, timeout: Duration) -> bool {
+        self.core.flush_and_wait(timeout)
     }
 
-    /// Inserts a key-value pair with TTL into the cache.
+    /// Inserts a key-value pair and waits up to `timeout` for it to become
+    /// as durable as `durability` asks.
     /// 
-    /// The entry will be automatically removed when the TTL expires.
-    /// Keys are maintained in sorted order.
-    pub fn insert_with_ttl(&mut self, key: &str, value: &str, ttl: Duration) {
-        let entry = Entry::with_ttl(key, value, Some(ttl));
-        self.entries.insert(key.to_string(), entry);
-        self.bloom_filter.insert(&key.to_string());
+    /// See [`DistributedHashTable::insert_with_durability`].
+    #[cfg(feature = "std")]
+    pub fn insert_with_durability(&mut self, key: &str, value: &str, durability: Durability, timeout: Duration) -> Result<(), CacheError> {
+        self.core.insert_with_durability(key, value, durability, timeout)
     }
 
-    /// Retrieves a value by key.
+    /// Starts appending every write to the append-only log at `path`.
     /// 
-    /// Returns None if the key doesn't exist or if the entry has expired.
-    /// Time complexity: O(log n)
-    pub fn get(&mut self, key: &str) -> Option<&str> {
-        // Primeiro verifica no Bloom Filter
-        if !self.bloom_filter.contains(&key.to_string()) {
-            return None;
-        }
+    /// See [`DistributedHashTable::enable_aof`]; the two share a format, so
+    /// either cache can replay the other's logs.
+    #[cfg(feature = "persistence")]
+    pub fn enable_aof(&mut self, path: impl AsRef<Path>, fsync: FsyncPolicy) -> io::Result<()> {
+        self.core.enable_aof(path.as_ref(), fsync)
+    }
 
-        let is_expired = self.entries.get(key).map_or(false, |entry| entry.is_expired());
-        
-        if is_expired {
-            self.entries.remove(key);
-            None
-        } else if let Some(entry) = self.entries.get_mut(key) {
-            entry.touch();
-            Some(entry.value())
-        } else {
-            None
-        }
+    /// Stops logging writes, syncing and closing the append-only log.
+    #[cfg(feature = "persistence")]
+    pub fn disable_aof(&mut self) {
+        self.core.disable_aof();
     }
 
-    /// Removes a key-value pair from the cache.
+    /// Returns true once persistence failed three times in a row.
     /// 
-    /// Returns the removed value if the key existed.
-    /// Time complexity: O(log n)
-    pub fn remove(&mut self, key: &str) -> Option<String> {
-        if let Some(value) = self.entries.remove(key) {
-            Some(value.value().to_string())
-        } else {
-            None
-        }
+    /// See [`DistributedHashTable::is_degraded`].
+    #[cfg(feature = "persistence")]
+    pub fn is_degraded(&self) -> bool {
+        self.core.is_degraded()
     }
 
-    /// Updates an existing entry's value.
+    /// Forgets the persistence failures that made the cache degraded.
     /// 
-    /// Returns true if the update was successful (key existed).
-    /// Time complexity: O(log n)
-    pub fn update(&mut self, key: &str, value: &str) -> bool {
-        if let Some(entry) = self.entries.get_mut(key) {
-            entry.update_value(value);
-            true
-        } else {
-            false
-        }
+    /// See [`DistributedHashTable::resume_persistence`].
+    #[cfg(feature = "persistence")]
+    pub fn resume_persistence(&mut self) {
+        self.core.resume_persistence();
     }
 
-    /// Removes all entries from the cache.
-    pub fn clear(&mut self) {
-        self.entries.clear();
-        self.bloom_filter.clear();
+    /// Fails if the cache is degraded and rejects writes.
+    /// 
+    /// See [`DistributedHashTable::check_writable`].
+    #[cfg(feature = "persistence")]
+    pub fn check_writable(&self) -> Result<(), CacheError> {
+        self.core.check_writable()
     }
 
-    /// Checks if a key exists in the cache.
+    /// Applies the operations logged at `path` in order.
     /// 
-    /// Returns false if the key doesn't exist or if the entry has expired.
-    /// Time complexity: O(log n)
-    pub fn contains_key(&mut self, key: &str) -> bool {
-        // Primeiro verifica no Bloom Filter
-        if !self.bloom_filter.contains(&key.to_string()) {
-            return false;
-        }
+    /// See [`DistributedHashTable::replay`].
+    #[cfg(feature = "persistence")]
+    pub fn replay(&mut self, path: impl AsRef<Path>) -> io::Result<usize> {
+        self.core.replay(path.as_ref())
+    }
 
-        if let Some(entry) = self.entries.get(key) {
-            if entry.is_expired() {
-                self.entries.remove(key);
-                false
-            } else {
-                true
-            }
-        } else {
-            false
-        }
+    /// Makes this cache the primary of the replica at the other end of
+    /// `transport`.
+    /// 
+    /// See [`DistributedHashTable::add_replica`]; the two share a wire
+    /// format, so either cache can replicate the other.
+    #[cfg(feature = "cluster")]
+    pub fn add_replica(&mut self, transport: impl Transport + 'static) {
+        self.core.add_replica(Box::new(transport));
     }
 
-    /// Returns an iterator over all keys in sorted order.
-    pub fn keys(&self) -> impl Iterator<Item = &String> {
-        self.entries.keys()
+    /// Returns how many replicas are still connected.
+    #[cfg(feature = "cluster")]
+    pub fn replica_count(&self) -> usize {
+        self.core.replicas()
     }
 
-    /// Returns an iterator over all values in key-sorted order.
-    pub fn values(&self) -> impl Iterator<Item = &String> {
-        self.entries.values().map(|entry| &entry.value)
+    /// Applies the writes a primary streamed through `transport` and
+    /// acknowledges them.
+    /// 
+    /// See [`DistributedHashTable::apply_replicated`].
+    #[cfg(feature = "cluster")]
+    pub fn apply_replicated(&mut self, transport: &mut impl Transport, timeout: Duration) -> io::Result<usize> {
+        self.core.apply_replicated(transport, timeout)
     }
 
-    /// Returns an iterator over entries within a range of keys.
+    /// Returns a Merkle tree over the live entries, spread over `buckets`
+    /// buckets by key hash.
     /// 
-    /// # Arguments
+    /// See [`DistributedHashTable::merkle_digest`]; the two share a
+    /// format, so either cache can be synced with the other.
+    #[cfg(feature = "cluster")]
+    pub fn merkle_digest(&self, buckets: usize) -> MerkleDigest {
+        self.core.merkle_digest(buckets)
+    }
+
+    /// Returns the live entries in the `divergent` buckets, with their TTLs.
+    #[cfg(feature = "cluster")]
+    pub fn export_buckets(&self, divergent: &DivergentBuckets) -> BucketPatch {
+        self.core.export_buckets(divergent)
+    }
+
+    /// Makes the patch's buckets hold exactly the patch's entries.
     /// 
-    /// * `start` - The inclusive start of the range
-    /// * `end` - The inclusive end of the range
-    pub fn range(&self, start: &str, end: &str) -> impl Iterator<Item = (&String, &String)> {
-        self.entries.range(start.to_string()..=end.to_string())
-            .map(|(k, v)| (k, &v.value))
+    /// See [`DistributedHashTable::apply_bucket_patch`].
+    #[cfg(feature = "cluster")]
+    pub fn apply_bucket_patch(&mut self, patch: &BucketPatch) -> Result<usize, CacheError> {
+        self.core.apply_bucket_patch(patch)
     }
 
-    /// Returns the first key-value pair in the cache.
-    pub fn first(&self) -> Option<(&String, &str)> {
-        self.entries.first_key_value().map(|(k, v)| (k, v.value()))
+    /// Turns on Bloom filter audit mode.
+    /// 
+    /// Every lookup is cross-checked against the cache and the outcome is
+    /// recorded in the report returned by [`bloom_audit`](Self::bloom_audit).
+    /// Keys the filter wrongly rejects are still found, so enabling the audit
+    /// never changes lookup results.
+    pub fn enable_bloom_audit(&mut self) {
+        self.core.enable_bloom_audit();
     }
 
-    /// Returns the last key-value pair in the cache.
-    pub fn last(&self) -> Option<(&String, &str)> {
-        self.entries.last_key_value().map(|(k, v)| (k, v.value()))
+    /// Turns off Bloom filter audit mode and discards the collected counters.
+    pub fn disable_bloom_audit(&mut self) {
+        self.core.disable_bloom_audit();
     }
-}
 
-/// A probabilistic data structure for testing set membership.
-/// 
-/// This structure provides:
-/// - Fast membership testing with a small probability of false positives
-/// - No false negatives
-/// - Space-efficient storage
-/// - Merge operations for combining filters
-#[derive(Debug)]
-pub struct BloomFilter {
-    bits: Vec<bool>,
-    num_hash_functions: usize,
-    size: usize,
-}
+    /// Returns the Bloom filter audit counters, or None if auditing is disabled.
+    pub fn bloom_audit(&self) -> Option<BloomAudit> {
+        self.core.bloom_audit()
+    }
+
+    /// Returns true if the cache is currently skipping the Bloom filter in
+    /// adaptive mode.
+    /// 
+    /// See [`DistributedHashTable::bloom_bypassed`].
+    pub fn bloom_bypassed(&self) -> bool {
+        self.core.bloom_bypassed()
+    }
+
+    /// Rebuilds the Bloom filter from the keys currently stored.
+    /// 
+    /// See [`DistributedHashTable::rebuild_bloom_filter`].
+    pub fn rebuild_bloom_filter(&mut self) {
+        self.core.rebuild_bloom_filter();
+    }
+
+    /// Starts counting accesses to find the `k` most accessed keys.
+    ///
+    /// See [`DistributedHashTable::track_hot_keys`].
+    #[cfg(feature = "probabilistic")]
+    pub fn track_hot_keys(&mut self, k: usize) {
+        self.core.track_hot_keys(k);
+    }
+
+    /// Stops tracking hot keys and discards the counts.
+    #[cfg(feature = "probabilistic")]
+    pub fn stop_tracking_hot_keys(&mut self) {
+        self.core.stop_tracking_hot_keys();
+    }
+
+    /// Returns up to `k` of the most accessed keys with their estimated
+    /// access counts, most accessed first, or nothing if tracking is off.
+    #[cfg(feature = "probabilistic")]
+    pub fn hot_keys(&self) -> Vec<(String, u64)> {
+        self.core.hot_keys()
+    }
 
-impl BloomFilter {
-    /// Creates a new Bloom filter with the specified capacity and false positive rate.
+    /// Registers a callback fired whenever an entry leaves the cache.
+    /// 
+    /// The callback receives the key, the value that left, and the
+    /// [`RemovalCause`]: expiration, eviction for memory, explicit removal
+    /// (including `clear()`), or replacement by a new value. Several
+    /// listeners may be registered; they run in registration order, inline
+    /// with the operation that triggered them.
     /// 
-    /// # Arguments
+    /// Inline listeners run while the cache is mutably borrowed, so they
+    /// can't call back into it. If the cache is shared behind a lock, build
+    /// it with [`CacheBuilder::listener_queue`] instead: listeners then run
+    /// on a dispatcher thread once the write has returned, and may lock the
+    /// cache again without deadlocking.
     /// 
-    /// * `capacity` - Expected number of elements to be stored
-    /// * `false_positive_rate` - Desired probability of false positives (0.0 to 1.0)
+    /// A panic inside a listener is caught and counted in
+    /// [`CacheStats::callback_panics`]; the entry is still removed, the other
+    /// listeners still run, and the cache stays usable.
+    /// 
+    /// Expired entries are reported when the cache notices them, on the next
+    /// access to the key, not at the exact moment their TTL runs out.
     /// 
     /// # Examples
     /// 
     /// ```
-    /// use spectra_cache::BloomFilter;
+    /// use spectra_cache::{BTreeCache, RemovalCause};
+    /// use std::sync::{Arc, Mutex};
+    /// 
+    /// let evicted = Arc::new(Mutex::new(Vec::new()));
+    /// let sink = Arc::clone(&evicted);
     /// 
-    /// let filter = BloomFilter::new(1000, 0.01);
-    /// assert!(filter.is_empty());
+    /// let mut cache = BTreeCache::builder().max_memory_bytes(1024).build();
+    /// cache.on_evict(move |key, _value, cause| {
+    ///     if cause == RemovalCause::Evicted {
+    ///         sink.lock().unwrap().push(key.to_string()); // e.g. write back to the database
+    ///     }
+    /// });
+    /// cache.insert("a", &"x".repeat(600));
+    /// cache.insert("b", &"x".repeat(600));
+    /// assert_eq!(*evicted.lock().unwrap(), vec!["a"]);
     /// ```
-    pub fn new(capacity: usize, false_positive_rate: f64) -> Self {
-        let num_bits = Self::optimal_num_bits(capacity, false_positive_rate);
-        let num_hash_functions = Self::optimal_num_hash_functions(num_bits, capacity);
-        
-        Self {
-            bits: vec![false; num_bits],
-            num_hash_functions,
-            size: 0,
-        }
-    }
-    
-    /// Returns the number of elements in the filter.
-    pub fn size(&self) -> usize {
-        self.size
+    pub fn on_evict(&mut self, listener: impl FnMut(&str, &str, RemovalCause) + Send + 'static) {
+        self.core.on_evict(Box::new(listener));
     }
-    
-    /// Returns true if the filter is empty.
-    pub fn is_empty(&self) -> bool {
-        self.size == 0
+
+    /// Unregisters every `on_evict` listener.
+    pub fn remove_evict_listeners(&mut self) {
+        self.core.remove_evict_listeners();
     }
-    
-    /// Inserts an element into the filter.
-    /// 
-    /// # Arguments
+
+    /// Subscribes to changes of the keys matching a glob pattern.
     /// 
-    /// * `item` - The element to insert
-    pub fn insert<T: Hash>(&mut self, item: &T) {
-        let mut hasher = DefaultHasher::new();
-        item.hash(&mut hasher);
-        let hash = hasher.finish();
-        
-        for i in 0..self.num_hash_functions {
-            let index = self.get_index(hash, i);
-            self.bits[index] = true;
-        }
-        
-        self.size += 1;
+    /// See [`DistributedHashTable::subscribe`].
+    #[cfg(feature = "std")]
+    pub fn subscribe(&mut self, pattern: &str) -> Receiver<CacheEvent> {
+        self.core.subscribe(pattern)
     }
-    
-    /// Checks if an element is in the filter.
-    /// 
-    /// Returns true if the element is probably in the filter.
-    /// There is a small probability of false positives.
+
+    /// Returns up to `n` of the latest changes to `key`, newest first.
     /// 
-    /// # Arguments
+    /// See [`DistributedHashTable::history`].
+    #[cfg(feature = "std")]
+    pub fn history(&self, key: &str, n: usize) -> Vec<HistoryEntry> {
+        self.core.history(key, n)
+    }
+
+    /// Sends a record of every destructive operation to `sink`.
     /// 
-    /// * `item` - The element to check
-    pub fn contains<T: Hash>(&self, item: &T) -> bool {
-        let mut hasher = DefaultHasher::new();
-        item.hash(&mut hasher);
-        let hash = hasher.finish();
-        
-        for i in 0..self.num_hash_functions {
-            let index = self.get_index(hash, i);
-            if !self.bits[index] {
-                return false;
-            }
-        }
-        
-        true
+    /// Flushes, mass deletes, and configuration changes are audited; single-key
+    /// writes and removals are not. Replaces any previously installed sink.
+    /// A panicking sink is caught and counted in `CacheStats::callback_panics`.
+    #[cfg(feature = "std")]
+    pub fn set_audit_sink(&mut self, sink: impl AuditSink + 'static) {
+        self.core.set_audit_sink(Box::new(sink));
     }
-    
-    /// Removes all elements from the filter.
-    pub fn clear(&mut self) {
-        self.bits.fill(false);
-        self.size = 0;
+
+    /// Stops auditing destructive operations.
+    #[cfg(feature = "std")]
+    pub fn remove_audit_sink(&mut self) {
+        self.core.remove_audit_sink();
     }
-    
-    /// Merges another Bloom filter into this one.
-    /// 
-    /// # Arguments
-    /// 
-    /// * `other` - The Bloom filter to merge with
-    pub fn merge(&mut self, other: &BloomFilter) {
-        assert_eq!(self.bits.len(), other.bits.len(), "Bloom filters must have the same size to merge");
-        assert_eq!(self.num_hash_functions, other.num_hash_functions, "Bloom filters must have the same number of hash functions to merge");
-        
-        for i in 0..self.bits.len() {
-            self.bits[i] |= other.bits[i];
-        }
-        
-        // Não somamos os tamanhos porque podem haver elementos duplicados
-        // O tamanho real é uma estimativa baseada na densidade dos bits
-        let density = self.bits.iter().filter(|&&bit| bit).count() as f64 / self.bits.len() as f64;
-        self.size = (self.bits.len() as f64 * density / self.num_hash_functions as f64).round() as usize;
-    }
-    
-    /// Calculates the optimal number of bits based on capacity and false positive rate.
-    fn optimal_num_bits(capacity: usize, false_positive_rate: f64) -> usize {
-        let ln2 = std::f64::consts::LN_2;
-        let ln2_squared = ln2 * ln2;
-        let capacity_f64 = capacity as f64;
-        let bits = (-capacity_f64 * false_positive_rate.ln()) / ln2_squared;
-        bits.ceil() as usize
-    }
-    
-    /// Calculates the optimal number of hash functions based on number of bits and capacity.
-    fn optimal_num_hash_functions(num_bits: usize, capacity: usize) -> usize {
-        let ln2 = std::f64::consts::LN_2;
-        ((num_bits as f64 / capacity as f64) * ln2).round() as usize
-    }
-    
-    /// Gets the index for a hash value and hash function number.
-    fn get_index(&self, hash: u64, i: usize) -> usize {
-        let mut combined_hash = hash;
-        for _ in 0..i {
-            combined_hash = combined_hash.wrapping_mul(0x517cc1b727220a95);
+
+    /// Sets who subsequent audited operations are attributed to.
+    pub fn set_audit_context(&mut self, context: AuditContext) {
+        self.core.set_audit_context(context);
+    }
+
+    /// Returns who audited operations are currently attributed to.
+    pub fn audit_context(&self) -> &AuditContext {
+        self.core.audit_context()
+    }
+}
+
+#[cfg(feature = "core")]
+impl CacheBuilder<BTreeCache> {
+    /// Creates the B-tree cache.
+    pub fn build(self) -> BTreeCache {
+        BTreeCache::with_config(self.config)
+    }
+}
+
+#[cfg(feature = "core")]
+impl CacheType for BTreeCache {
+    type Value = String;
+}
+
+#[cfg(feature = "core")]
+impl Default for BTreeCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Two caches are equal when they hold the same live keys and values;
+/// expiration settings, statistics and listeners are not compared.
+#[cfg(feature = "core")]
+impl PartialEq for BTreeCache {
+    fn eq(&self, other: &Self) -> bool {
+        self.core.same_content(&other.core)
+    }
+}
+
+/// Inserts every pair with [`insert`](BTreeCache::insert).
+#[cfg(feature = "core")]
+impl<K: AsRef<str>, V: AsRef<str>> Extend<(K, V)> for BTreeCache {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        let iter = iter.into_iter();
+        self.reserve_capacity(iter.size_hint().0);
+        for (key, value) in iter {
+            self.insert(key.as_ref(), value.as_ref());
         }
-        combined_hash as usize % self.bits.len()
     }
 }
 
-#[cfg(test)]
+/// Builds a cache with default settings holding the pairs.
+#[cfg(feature = "core")]
+impl<K: AsRef<str>, V: AsRef<str>> FromIterator<(K, V)> for BTreeCache {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut cache = Self::new();
+        cache.extend(iter);
+        cache
+    }
+}
+
+/// Converts a range over `&str` into bounds `BTreeMap::range` accepts for `String` keys.
+#[cfg(feature = "core")]
+fn str_bounds<'a, R: RangeBounds<&'a str>>(range: &R) -> (Bound<&'a str>, Bound<&'a str>) {
+    (range.start_bound().map(|s| *s), range.end_bound().map(|s| *s))
+}
+
+#[cfg(all(test, feature = "core", feature = "std"))]
 mod tests {
     use super::*;
     use std::thread::sleep;
@@ -0,0 +1,232 @@
+use std::collections::{HashMap, VecDeque};
+use std::ops::{Bound, RangeBounds};
+use std::time::Duration;
+
+use crate::clock::Instant;
+
+/// A cache of lists: under each key, a sequence of strings that grows and
+/// shrinks at both ends, as in Redis.
+///
+/// Pushing and popping touch only the end involved, so appending to a long
+/// list costs the same as appending to a short one. With
+/// [`max_len`](Self::max_len) set, every push trims the list back to that
+/// length from the opposite end, dropping the oldest elements: a list fed
+/// with `lpush` and read with `lrange(key, ..n)` keeps the `n` most recent
+/// events first.
+///
+/// A list can be given a TTL with [`expire`](Self::expire), and disappears
+/// along with its last element.
+///
+/// # Examples
+///
+/// ```
+/// use spectra_cache::ListCache;
+///
+/// let mut feeds = ListCache::new().max_len(3);
+/// for event in ["login", "view:home", "view:cart", "checkout"] {
+///     feeds.lpush("activity:42", event);
+/// }
+/// assert_eq!(feeds.lrange("activity:42", ..), ["checkout", "view:cart", "view:home"]);
+/// assert_eq!(feeds.rpop("activity:42").as_deref(), Some("view:home"));
+/// assert_eq!(feeds.llen("activity:42"), 2);
+/// ```
+#[derive(Debug, Default)]
+pub struct ListCache {
+    lists: HashMap<String, List>,
+    max_len: Option<usize>,
+}
+
+#[derive(Debug, Default)]
+struct List {
+    items: VecDeque<String>,
+    expires_at: Option<Instant>,
+}
+
+impl List {
+    fn is_live(&self, now: Instant) -> bool {
+        self.expires_at.is_none_or(|expires_at| expires_at > now)
+    }
+}
+
+/// The end of a list an operation works on.
+#[derive(Clone, Copy)]
+enum End {
+    Front,
+    Back,
+}
+
+impl ListCache {
+    /// Creates an empty cache whose lists grow without limit.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Caps every list at `max_len` elements, trimming the oldest ones on
+    /// each push.
+    ///
+    /// Panics if `max_len` is zero.
+    pub fn max_len(mut self, max_len: usize) -> Self {
+        assert!(max_len > 0, "a capped list must hold at least one element");
+        self.max_len = Some(max_len);
+        self
+    }
+
+    /// Prepends `value` to the list at `key`, creating the list if needed.
+    /// Returns the list's length afterwards.
+    pub fn lpush(&mut self, key: &str, value: &str) -> usize {
+        self.push(key, value, End::Front)
+    }
+
+    /// Appends `value` to the list at `key`, creating the list if needed.
+    /// Returns the list's length afterwards.
+    pub fn rpush(&mut self, key: &str, value: &str) -> usize {
+        self.push(key, value, End::Back)
+    }
+
+    /// Removes and returns the first element of the list at `key`.
+    pub fn lpop(&mut self, key: &str) -> Option<String> {
+        self.pop(key, End::Front)
+    }
+
+    /// Removes and returns the last element of the list at `key`.
+    pub fn rpop(&mut self, key: &str) -> Option<String> {
+        self.pop(key, End::Back)
+    }
+
+    /// Returns the elements at the positions in `range`, front to back.
+    ///
+    /// Unlike Redis, ranges follow Rust syntax and count from the front
+    /// only: `..10` is the first ten elements. Positions past the end are
+    /// ignored.
+    pub fn lrange(&self, key: &str, range: impl RangeBounds<usize>) -> Vec<&str> {
+        let Some(list) = self.live(key) else {
+            return Vec::new();
+        };
+        let len = list.items.len();
+        let start = match range.start_bound() {
+            Bound::Included(&start) => start,
+            Bound::Excluded(&start) => start.saturating_add(1),
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(&end) => end.saturating_add(1),
+            Bound::Excluded(&end) => end,
+            Bound::Unbounded => len,
+        };
+        let (start, end) = (start.min(len), end.min(len));
+        if start >= end {
+            return Vec::new();
+        }
+        list.items.range(start..end).map(String::as_str).collect()
+    }
+
+    /// Returns the element at `index`, counting from the front.
+    pub fn lindex(&self, key: &str, index: usize) -> Option<&str> {
+        self.live(key)?.items.get(index).map(String::as_str)
+    }
+
+    /// Returns the length of the list at `key`, or zero if there is none.
+    pub fn llen(&self, key: &str) -> usize {
+        self.live(key).map_or(0, |list| list.items.len())
+    }
+
+    /// Sets the list at `key` to expire after `ttl`. Returns true if the
+    /// list existed.
+    pub fn expire(&mut self, key: &str, ttl: Duration) -> bool {
+        match self.live_mut(key) {
+            Some(list) => {
+                list.expires_at = Some(Instant::now() + ttl);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Returns how long the list at `key` has left to live, or `None` if it
+    /// doesn't exist or never expires.
+    pub fn ttl(&self, key: &str) -> Option<Duration> {
+        let expires_at = self.live(key)?.expires_at?;
+        Some(expires_at.saturating_duration_since(Instant::now()))
+    }
+
+    /// Returns true if `key` holds a list.
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.live(key).is_some()
+    }
+
+    /// Removes the list at `key`, returning its elements if it existed.
+    pub fn remove(&mut self, key: &str) -> Option<Vec<String>> {
+        let now = Instant::now();
+        let list = self.lists.remove(key).filter(|list| list.is_live(now))?;
+        Some(list.items.into())
+    }
+
+    /// Returns an iterator over the keys holding lists.
+    pub fn keys(&self) -> impl Iterator<Item = &String> {
+        let now = Instant::now();
+        self.lists.iter().filter(move |(_, list)| list.is_live(now)).map(|(key, _)| key)
+    }
+
+    /// Returns true if no key holds a list.
+    pub fn is_empty(&self) -> bool {
+        self.keys().next().is_none()
+    }
+
+    /// Removes every expired list. Returns the number removed.
+    pub fn purge_expired(&mut self) -> usize {
+        let now = Instant::now();
+        let before = self.lists.len();
+        self.lists.retain(|_, list| list.is_live(now));
+        before - self.lists.len()
+    }
+
+    /// Removes every list.
+    pub fn clear(&mut self) {
+        self.lists.clear();
+    }
+
+    fn live(&self, key: &str) -> Option<&List> {
+        self.lists.get(key).filter(|list| list.is_live(Instant::now()))
+    }
+
+    /// Returns the list at `key`, dropping it first if it has expired.
+    fn live_mut(&mut self, key: &str) -> Option<&mut List> {
+        if self.lists.get(key).is_some_and(|list| !list.is_live(Instant::now())) {
+            self.lists.remove(key);
+        }
+        self.lists.get_mut(key)
+    }
+
+    fn push(&mut self, key: &str, value: &str, end: End) -> usize {
+        if self.live_mut(key).is_none() {
+            self.lists.insert(key.to_string(), List::default());
+        }
+        let list = self.lists.get_mut(key).expect("list was just created");
+        match end {
+            End::Front => list.items.push_front(value.to_string()),
+            End::Back => list.items.push_back(value.to_string()),
+        }
+        if let Some(max_len) = self.max_len {
+            // Os mais antigos ficam na ponta oposta à que recebe
+            while list.items.len() > max_len {
+                match end {
+                    End::Front => list.items.pop_back(),
+                    End::Back => list.items.pop_front(),
+                };
+            }
+        }
+        list.items.len()
+    }
+
+    fn pop(&mut self, key: &str, end: End) -> Option<String> {
+        let list = self.live_mut(key)?;
+        let value = match end {
+            End::Front => list.items.pop_front(),
+            End::Back => list.items.pop_back(),
+        };
+        if list.items.is_empty() {
+            self.lists.remove(key);
+        }
+        value
+    }
+}
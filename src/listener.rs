@@ -0,0 +1,446 @@
+use alloc::boxed::Box;
+use alloc::string::String;
+#[cfg(feature = "std")]
+use alloc::string::ToString;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::fmt;
+use core::sync::atomic::Ordering;
+#[cfg(feature = "std")]
+use std::collections::VecDeque;
+#[cfg(feature = "std")]
+use std::mem;
+#[cfg(feature = "std")]
+use std::panic::{self, AssertUnwindSafe};
+#[cfg(feature = "std")]
+use std::sync::mpsc::Receiver;
+#[cfg(feature = "std")]
+use std::sync::{Condvar, Mutex, MutexGuard, PoisonError};
+#[cfg(feature = "std")]
+use std::thread;
+
+use crate::audit::AuditContext;
+#[cfg(feature = "std")]
+use crate::history::{History, HistoryEntry};
+#[cfg(feature = "std")]
+use crate::subscription::{CacheEvent, Subscribers};
+use crate::sync::{AtomicU64, Lock};
+use crate::value::CacheValue;
+
+/// Why an entry left the cache, as reported to `on_evict` listeners.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RemovalCause {
+    /// The entry's TTL or idle timeout ran out.
+    Expired,
+    /// The entry was dropped to keep the cache within its memory budget.
+    Evicted,
+    /// The caller removed the entry, or cleared the whole cache.
+    Removed,
+    /// A write stored a new value under the same key.
+    Replaced,
+}
+
+impl RemovalCause {
+    /// Returns true if the cache dropped the entry on its own rather than
+    /// because of an explicit removal or overwrite.
+    pub fn was_evicted(self) -> bool {
+        matches!(self, RemovalCause::Expired | RemovalCause::Evicted)
+    }
+}
+
+/// What a listener queue does with a new notification when it is full.
+///
+/// The cache never waits for listeners to catch up: blocking a write until
+/// a listener finishes would bring back the deadlock the queue exists to
+/// avoid. Dropped notifications are counted in
+/// `CacheStats::dropped_notifications`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ListenerOverflow {
+    /// Discard the new notification and keep the queued ones.
+    DropNewest,
+    /// Discard the oldest queued notification to make room for the new one.
+    DropOldest,
+}
+
+pub(crate) type Listener<R> = Box<dyn FnMut(&str, &R, RemovalCause) + Send>;
+
+/// The callbacks registered through `on_evict()`, along with the channels
+/// registered through `subscribe()`.
+///
+/// By default listeners run inline, while the cache is mutably borrowed, so
+/// they cannot reach the cache at all. With a listener queue they run on a
+/// dedicated dispatcher thread instead, after the write that triggered them
+/// has returned and released whatever lock guards the cache.
+///
+/// A panicking listener must not leave the cache half-updated, so every
+/// call is isolated with `catch_unwind`: the panic is counted, the entry
+/// stays removed, the remaining listeners still run, and the panicking
+/// listener stays registered for later events. Without `std` there is no
+/// `catch_unwind`, and neither queues, subscriptions nor history.
+///
+/// Subscriptions are told about writes as well as removals, and always
+/// right away, whatever the delivery mode of the listeners.
+///
+/// Inline listeners sit behind a `Lock` only so that a cache holding them
+/// stays `Sync`: they are only ever called through `&mut self`, which
+/// reaches them with `Lock::get_mut` and never takes the lock.
+pub(crate) struct RemovalListeners<V: CacheValue = String> {
+    delivery: Delivery<V>,
+    registered: usize,
+    panics: Arc<AtomicU64>,
+    #[cfg(feature = "std")]
+    subscribers: Subscribers<V>,
+    #[cfg(feature = "std")]
+    history: Option<History<V>>,
+}
+
+enum Delivery<V: CacheValue> {
+    Inline(Lock<Vec<Listener<V::Ref>>>),
+    #[cfg(feature = "std")]
+    Queued(Arc<DispatchQueue<V>>),
+}
+
+impl<V: CacheValue> Default for RemovalListeners<V> {
+    fn default() -> Self {
+        Self {
+            delivery: Delivery::Inline(Lock::default()),
+            registered: 0,
+            panics: Arc::new(AtomicU64::new(0)),
+            #[cfg(feature = "std")]
+            subscribers: Subscribers::default(),
+            #[cfg(feature = "std")]
+            history: None,
+        }
+    }
+}
+
+impl<V: CacheValue> RemovalListeners<V> {
+    /// Creates listeners that are called from a dispatcher thread through a
+    /// queue holding at most `capacity` notifications.
+    #[cfg(feature = "std")]
+    pub(crate) fn queued(capacity: usize, overflow: ListenerOverflow) -> Self {
+        let queue = Arc::new(DispatchQueue {
+            state: Mutex::new(QueueState {
+                tasks: VecDeque::new(),
+                pending: 0,
+                dropped: 0,
+                closed: false,
+            }),
+            ready: Condvar::new(),
+            capacity,
+            overflow,
+        });
+        let panics = Arc::new(AtomicU64::new(0));
+
+        let dispatcher = Arc::clone(&queue);
+        let dispatcher_panics = Arc::clone(&panics);
+        thread::Builder::new()
+            .name("spectra-cache-listeners".to_string())
+            .spawn(move || dispatcher.run(&dispatcher_panics))
+            .expect("failed to spawn the listener dispatcher thread");
+
+        Self {
+            delivery: Delivery::Queued(queue),
+            registered: 0,
+            panics,
+            subscribers: Subscribers::default(),
+            history: None,
+        }
+    }
+
+    pub(crate) fn add(&mut self, listener: Listener<V::Ref>) {
+        self.registered += 1;
+        match &mut self.delivery {
+            Delivery::Inline(listeners) => listeners.get_mut().push(listener),
+            #[cfg(feature = "std")]
+            Delivery::Queued(queue) => queue.push(Task::Add(listener)),
+        }
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.registered = 0;
+        match &mut self.delivery {
+            Delivery::Inline(listeners) => listeners.get_mut().clear(),
+            #[cfg(feature = "std")]
+            Delivery::Queued(queue) => queue.push(Task::Clear),
+        }
+    }
+
+    #[cfg(feature = "std")]
+    pub(crate) fn subscribe(&mut self, pattern: &str) -> Receiver<CacheEvent<V>> {
+        self.subscribers.subscribe(pattern)
+    }
+
+    /// Starts keeping the last `depth` changes to every key.
+    #[cfg(feature = "std")]
+    pub(crate) fn with_history(mut self, depth: usize) -> Self {
+        self.history = Some(History::new(depth));
+        self
+    }
+
+    /// Sets who subsequent changes are attributed to in the history.
+    #[cfg_attr(not(feature = "std"), allow(unused_variables))]
+    pub(crate) fn set_context(&mut self, context: AuditContext) {
+        #[cfg(feature = "std")]
+        if let Some(history) = &mut self.history {
+            history.set_context(context);
+        }
+    }
+
+    #[cfg(feature = "std")]
+    pub(crate) fn history(&self, key: &str, n: usize) -> Vec<HistoryEntry<V>>
+    where
+        V: Clone,
+    {
+        self.history.as_ref().map_or_else(Vec::new, |history| history.get(key, n))
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        #[cfg(feature = "std")]
+        if !self.subscribers.is_empty() || self.history.is_some() {
+            return false;
+        }
+        self.registered == 0
+    }
+
+    /// Tells the subscriptions that `value` was written under `key`;
+    /// `updated` tells whether a live entry was overwritten.
+    #[cfg_attr(not(feature = "std"), allow(unused_variables))]
+    pub(crate) fn written(&mut self, key: &str, value: &V::Ref, updated: bool) {
+        #[cfg(feature = "std")]
+        {
+            self.subscribers.written(key, value, updated);
+            if let Some(history) = &mut self.history {
+                history.written(key, value, updated);
+            }
+        }
+    }
+
+    /// Tells every listener that `key` left the cache holding `value`.
+    pub(crate) fn notify(&mut self, key: &str, value: &V::Ref, cause: RemovalCause) {
+        #[cfg(feature = "std")]
+        {
+            self.subscribers.removed(key, cause);
+            if let Some(history) = &mut self.history {
+                history.removed(key, cause);
+            }
+        }
+        if self.registered == 0 {
+            return;
+        }
+        match &mut self.delivery {
+            Delivery::Inline(listeners) => {
+                for listener in listeners.get_mut() {
+                    call(listener, key, value, cause, &self.panics);
+                }
+            }
+            #[cfg(feature = "std")]
+            Delivery::Queued(queue) => queue.push(Task::Notify(key.to_string(), V::from_ref(value), cause)),
+        }
+    }
+
+    /// Returns how many listener calls panicked.
+    pub(crate) fn panics(&self) -> u64 {
+        self.panics.load(Ordering::Relaxed)
+    }
+
+    /// Returns how many notifications a full queue discarded.
+    pub(crate) fn dropped(&self) -> u64 {
+        match &self.delivery {
+            Delivery::Inline(_) => 0,
+            #[cfg(feature = "std")]
+            Delivery::Queued(queue) => queue.lock().dropped,
+        }
+    }
+}
+
+impl<V: CacheValue> Drop for RemovalListeners<V> {
+    fn drop(&mut self) {
+        // O despachante entrega o que ainda está na fila e depois termina
+        #[cfg(feature = "std")]
+        if let Delivery::Queued(queue) = &self.delivery {
+            queue.lock().closed = true;
+            queue.ready.notify_one();
+        }
+    }
+}
+
+impl<V: CacheValue> fmt::Debug for RemovalListeners<V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut debug = f.debug_struct("RemovalListeners");
+        debug.field("listeners", &self.registered);
+        #[cfg(feature = "std")]
+        debug.field("queued", &matches!(self.delivery, Delivery::Queued(_)));
+        debug.field("panics", &self.panics());
+        #[cfg(feature = "std")]
+        debug
+            .field("subscribers", &self.subscribers.len())
+            .field("history", &self.history.is_some());
+        debug.finish()
+    }
+}
+
+#[cfg(feature = "std")]
+fn call<R: ?Sized>(listener: &mut Listener<R>, key: &str, value: &R, cause: RemovalCause, panics: &AtomicU64) {
+    if panic::catch_unwind(AssertUnwindSafe(|| listener(key, value, cause))).is_err() {
+        panics.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Without unwinding to catch, a panicking listener panics the caller.
+#[cfg(not(feature = "std"))]
+fn call<R: ?Sized>(listener: &mut Listener<R>, key: &str, value: &R, cause: RemovalCause, _panics: &AtomicU64) {
+    listener(key, value, cause);
+}
+
+/// Work handed to the dispatcher thread, in the order the cache produced it.
+#[cfg(feature = "std")]
+enum Task<V: CacheValue> {
+    Add(Listener<V::Ref>),
+    Clear,
+    Notify(String, V, RemovalCause),
+}
+
+#[cfg(feature = "std")]
+struct DispatchQueue<V: CacheValue> {
+    state: Mutex<QueueState<V>>,
+    ready: Condvar,
+    capacity: usize,
+    overflow: ListenerOverflow,
+}
+
+#[cfg(feature = "std")]
+struct QueueState<V: CacheValue> {
+    tasks: VecDeque<Task<V>>,
+    /// Number of `Task::Notify` in `tasks`; registrations don't count
+    /// against the capacity and are never dropped.
+    pending: usize,
+    dropped: u64,
+    closed: bool,
+}
+
+#[cfg(feature = "std")]
+impl<V: CacheValue> DispatchQueue<V> {
+    fn lock(&self) -> MutexGuard<'_, QueueState<V>> {
+        // O lock só protege a fila; listeners nunca rodam com ele
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn push(&self, task: Task<V>) {
+        let mut state = self.lock();
+        if matches!(task, Task::Notify(..)) {
+            if state.pending >= self.capacity {
+                state.dropped += 1;
+                match self.overflow {
+                    ListenerOverflow::DropNewest => return,
+                    ListenerOverflow::DropOldest => {
+                        let oldest = state.tasks.iter().position(|task| matches!(task, Task::Notify(..)));
+                        if let Some(index) = oldest {
+                            state.tasks.remove(index);
+                            state.pending -= 1;
+                        }
+                    }
+                }
+            }
+            state.pending += 1;
+        }
+        state.tasks.push_back(task);
+        drop(state);
+        self.ready.notify_one();
+    }
+
+    /// The dispatcher loop: takes everything queued so far and runs it with
+    /// the queue unlocked, until the cache is dropped and the queue drained.
+    fn run(&self, panics: &AtomicU64) {
+        let mut listeners: Vec<Listener<V::Ref>> = Vec::new();
+        loop {
+            let tasks = {
+                let mut state = self.lock();
+                while state.tasks.is_empty() && !state.closed {
+                    state = self.ready.wait(state).unwrap_or_else(PoisonError::into_inner);
+                }
+                if state.tasks.is_empty() {
+                    return;
+                }
+                state.pending = 0;
+                mem::take(&mut state.tasks)
+            };
+
+            for task in tasks {
+                match task {
+                    Task::Add(listener) => listeners.push(listener),
+                    Task::Clear => listeners.clear(),
+                    Task::Notify(key, value, cause) => {
+                        for listener in &mut listeners {
+                            call(listener, &key, value.view(), cause, panics);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+    use std::time::Duration;
+
+    #[test]
+    fn test_queued_listeners_run_on_another_thread() {
+        let mut listeners = RemovalListeners::<String>::queued(16, ListenerOverflow::DropNewest);
+        let (sender, receiver) = mpsc::channel();
+        let caller = thread::current().id();
+        listeners.add(Box::new(move |key, value, cause| {
+            sender.send((key.to_string(), value.to_string(), cause, thread::current().id())).unwrap();
+        }));
+
+        listeners.notify("a", "1", RemovalCause::Removed);
+        let (key, value, cause, thread) = receiver.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert_eq!((key.as_str(), value.as_str(), cause), ("a", "1", RemovalCause::Removed));
+        assert_ne!(thread, caller);
+    }
+
+    #[test]
+    fn test_overflow_policies() {
+        for (overflow, expected) in [
+            (ListenerOverflow::DropNewest, vec!["k0", "k1"]),
+            (ListenerOverflow::DropOldest, vec!["k2", "k3"]),
+        ] {
+            let mut listeners = RemovalListeners::<String>::queued(2, overflow);
+            let (gate_sender, gate) = mpsc::channel::<()>();
+            let (sender, receiver) = mpsc::channel();
+            listeners.add(Box::new(move |key, _, _| {
+                if key == "block" {
+                    gate.recv().unwrap();
+                } else {
+                    sender.send(key.to_string()).unwrap();
+                }
+            }));
+
+            // Segura o despachante até a fila transbordar
+            listeners.notify("block", "", RemovalCause::Removed);
+            while listeners.lock_pending() > 0 {
+                thread::yield_now();
+            }
+            for i in 0..4 {
+                listeners.notify(&format!("k{}", i), "", RemovalCause::Removed);
+            }
+            assert_eq!(listeners.dropped(), 2);
+            gate_sender.send(()).unwrap();
+
+            let delivered: Vec<String> = (0..2).map(|_| receiver.recv_timeout(Duration::from_secs(5)).unwrap()).collect();
+            assert_eq!(delivered, expected);
+        }
+    }
+
+    impl<V: CacheValue> RemovalListeners<V> {
+        fn lock_pending(&self) -> usize {
+            match &self.delivery {
+                Delivery::Inline(_) => 0,
+                Delivery::Queued(queue) => queue.lock().pending,
+            }
+        }
+    }
+}
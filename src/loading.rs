@@ -0,0 +1,289 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, PoisonError};
+use std::thread;
+use std::time::Duration;
+
+use crate::{DistributedHashTable, GetOptions, MaybeStale};
+
+type Loader = Box<dyn Fn(&str) -> Option<String> + Send + Sync>;
+
+/// A shared `DistributedHashTable` that fills its own misses.
+///
+/// On a miss `get()` calls the loader and caches what it returns.
+/// Concurrent misses on the same key are coalesced (singleflight): one
+/// caller runs the loader while the others wait for its result, so a hot
+/// key expiring causes one load instead of a stampede on the backend.
+/// Misses on different keys load in parallel, and the table isn't locked
+/// while a loader runs.
+///
+/// The loader returns `None` for keys that don't exist; nothing is cached
+/// for them. Loaded values are stored with `insert()`, so the table's
+/// default TTL applies. If the loader panics, the panic propagates to the
+/// caller that ran it and the callers waiting on it get `None`.
+///
+/// # Examples
+///
+/// ```
+/// use spectra_cache::{DistributedHashTable, LoadingCache};
+/// use std::sync::Arc;
+/// use std::thread;
+///
+/// let cache = Arc::new(LoadingCache::new(DistributedHashTable::new(), |key: &str| {
+///     Some(format!("row for {}", key)) // e.g. a database query
+/// }));
+///
+/// let readers: Vec<_> = (0..4)
+///     .map(|_| {
+///         let cache = Arc::clone(&cache);
+///         thread::spawn(move || cache.get("user:1"))
+///     })
+///     .collect();
+/// for reader in readers {
+///     assert_eq!(reader.join().unwrap().as_deref(), Some("row for user:1"));
+/// }
+/// assert_eq!(cache.loads(), 1);
+/// ```
+pub struct LoadingCache {
+    table: Mutex<DistributedHashTable>,
+    loader: Loader,
+    flights: Mutex<HashMap<String, Arc<Flight>>>,
+    loads: AtomicU64,
+    coalesced: AtomicU64,
+}
+
+/// A load in progress; waiters block on `done` until `result` is set.
+#[derive(Default)]
+struct Flight {
+    result: Mutex<Option<Option<String>>>,
+    done: Condvar,
+}
+
+impl Flight {
+    fn finish(&self, value: Option<String>) {
+        *lock(&self.result) = Some(value);
+        self.done.notify_all();
+    }
+
+    fn wait(&self) -> Option<String> {
+        let result = lock(&self.result);
+        let result = self
+            .done
+            .wait_while(result, |result| result.is_none())
+            .unwrap_or_else(PoisonError::into_inner);
+        result.clone().flatten()
+    }
+
+    /// Waits at most `timeout` for the load, returning `None` if it is
+    /// still running then.
+    fn wait_timeout(&self, timeout: Duration) -> Option<String> {
+        let result = lock(&self.result);
+        let (result, _) = self
+            .done
+            .wait_timeout_while(result, timeout, |result| result.is_none())
+            .unwrap_or_else(PoisonError::into_inner);
+        result.clone().flatten()
+    }
+}
+
+/// Ends a flight even if the loader panics, so waiters are never stranded.
+struct Landing<'a> {
+    cache: &'a LoadingCache,
+    key: &'a str,
+    flight: Arc<Flight>,
+    value: Option<String>,
+}
+
+impl Drop for Landing<'_> {
+    fn drop(&mut self) {
+        lock(&self.cache.flights).remove(self.key);
+        self.flight.finish(self.value.take());
+    }
+}
+
+impl LoadingCache {
+    /// Wraps `table`, loading misses with `loader`.
+    pub fn new(table: DistributedHashTable, loader: impl Fn(&str) -> Option<String> + Send + Sync + 'static) -> Self {
+        Self {
+            table: Mutex::new(table),
+            loader: Box::new(loader),
+            flights: Mutex::new(HashMap::new()),
+            loads: AtomicU64::new(0),
+            coalesced: AtomicU64::new(0),
+        }
+    }
+
+    /// Returns the value for `key`, loading it on a miss.
+    pub fn get(&self, key: &str) -> Option<String> {
+        self.get_with(key, &GetOptions::default())
+    }
+
+    /// Returns the value for `key` within the latency budget of `options`.
+    ///
+    /// With a `max_wait`, a miss waits at most that long for a load another
+    /// caller already started, and returns `None` rather than run the
+    /// loader itself; the next caller without a budget loads the key.
+    /// The other options apply to the lookup in the table, as in
+    /// [`DistributedHashTable::get_with`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use spectra_cache::{DistributedHashTable, GetOptions, LoadingCache};
+    /// use std::time::Duration;
+    ///
+    /// let cache = LoadingCache::new(DistributedHashTable::new(), |key: &str| Some(key.to_uppercase()));
+    /// let budget = GetOptions { max_wait: Some(Duration::ZERO), ..GetOptions::default() };
+    /// assert_eq!(cache.get_with("user:1", &budget), None);
+    /// assert_eq!(cache.get("user:1").as_deref(), Some("USER:1"));
+    /// assert_eq!(cache.get_with("user:1", &budget).as_deref(), Some("USER:1"));
+    /// ```
+    pub fn get_with(&self, key: &str, options: &GetOptions) -> Option<String> {
+        if let Some(value) = self.table().get_with(key, options) {
+            return Some(value.to_string());
+        }
+
+        let flight = {
+            let mut flights = lock(&self.flights);
+            // Confere de novo sob a trava: outro carregamento pode ter terminado
+            // entre o miss acima e agora
+            if let Some(value) = self.table().peek(key) {
+                return Some(value.to_string());
+            }
+            if let Some(flight) = flights.get(key) {
+                let flight = Arc::clone(flight);
+                drop(flights);
+                self.coalesced.fetch_add(1, Ordering::Relaxed);
+                return match options.max_wait {
+                    Some(timeout) => flight.wait_timeout(timeout),
+                    None => flight.wait(),
+                };
+            }
+            if options.max_wait.is_some() {
+                return None;
+            }
+            let flight = Arc::new(Flight::default());
+            flights.insert(key.to_string(), Arc::clone(&flight));
+            flight
+        };
+        self.load(key, flight)
+    }
+
+    /// Returns the value for `key`, serving a stale value at once and
+    /// reloading it in the background (stale-while-revalidate).
+    ///
+    /// A value older than the table's
+    /// [`soft_ttl`](crate::CacheBuilder::soft_ttl) is returned flagged as
+    /// stale, and a background thread reloads it unless a load of the key
+    /// is already running; callers keep getting the stale value until the
+    /// reload lands. A miss loads the key on the calling thread, as in
+    /// [`get`](Self::get). If the reload finds nothing, the stale value is
+    /// kept until its hard TTL runs out.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use spectra_cache::{DistributedHashTable, LoadingCache};
+    /// use std::sync::Arc;
+    /// use std::time::Duration;
+    ///
+    /// let table = DistributedHashTable::builder().soft_ttl(Duration::from_millis(10)).build();
+    /// let cache = Arc::new(LoadingCache::new(table, |key: &str| Some(key.to_uppercase())));
+    /// assert!(!cache.get_stale("user:1").unwrap().stale);
+    ///
+    /// std::thread::sleep(Duration::from_millis(20));
+    /// let read = cache.get_stale("user:1").unwrap();
+    /// assert_eq!((read.value.as_str(), read.stale), ("USER:1", true));
+    /// ```
+    pub fn get_stale(self: &Arc<Self>, key: &str) -> Option<MaybeStale<String>> {
+        let read = self.table().get_stale(key).map(|read| read.map(str::to_string));
+        let Some(read) = read else {
+            return self.get(key).map(|value| MaybeStale { value, stale: false });
+        };
+        if read.stale {
+            self.refresh_in_background(key);
+        }
+        Some(read)
+    }
+
+    /// Starts a reload of `key` on another thread, unless one is running.
+    fn refresh_in_background(self: &Arc<Self>, key: &str) {
+        let flight = {
+            let mut flights = lock(&self.flights);
+            if flights.contains_key(key) {
+                return;
+            }
+            let flight = Arc::new(Flight::default());
+            flights.insert(key.to_string(), Arc::clone(&flight));
+            flight
+        };
+        let cache = Arc::clone(self);
+        let key = key.to_string();
+        thread::spawn(move || {
+            cache.load(&key, flight);
+        });
+    }
+
+    /// Runs the loader for `key` on behalf of `flight` and caches the result.
+    fn load(&self, key: &str, flight: Arc<Flight>) -> Option<String> {
+        let mut landing = Landing {
+            cache: self,
+            key,
+            flight,
+            value: None,
+        };
+        self.loads.fetch_add(1, Ordering::Relaxed);
+        landing.value = (self.loader)(key);
+        if let Some(value) = &landing.value {
+            self.table().insert(key, value);
+        }
+        landing.value.clone()
+    }
+
+    /// Removes `key` so the next `get()` loads it again.
+    ///
+    /// Returns the cached value, if there was one. A load already in
+    /// progress for `key` still stores its result.
+    pub fn invalidate(&self, key: &str) -> Option<String> {
+        self.table().remove(key)
+    }
+
+    /// Returns how many times the loader was called.
+    pub fn loads(&self) -> u64 {
+        self.loads.load(Ordering::Relaxed)
+    }
+
+    /// Returns how many misses waited for another caller's load instead
+    /// of calling the loader.
+    pub fn coalesced(&self) -> u64 {
+        self.coalesced.load(Ordering::Relaxed)
+    }
+
+    /// Locks and returns the underlying table, for operations not
+    /// covered here.
+    ///
+    /// Don't hold the guard across a call to `get()` on the same thread.
+    pub fn table(&self) -> MutexGuard<'_, DistributedHashTable> {
+        lock(&self.table)
+    }
+
+    /// Consumes the loading cache, returning the underlying table.
+    pub fn into_inner(self) -> DistributedHashTable {
+        self.table.into_inner().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl fmt::Debug for LoadingCache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LoadingCache")
+            .field("table", &self.table)
+            .field("loads", &self.loads())
+            .field("coalesced", &self.coalesced())
+            .finish_non_exhaustive()
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
@@ -0,0 +1,189 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::Duration;
+
+use crate::DistributedHashTable;
+use crate::clock::Instant;
+
+/// How many buffered keys trigger a flush by default.
+const DEFAULT_MAX_PENDING: usize = 256;
+
+/// How old the oldest buffered write may get before a flush, by default.
+const DEFAULT_MAX_DELAY: Duration = Duration::from_millis(100);
+
+/// A per-thread write buffer in front of a shared `DistributedHashTable`.
+///
+/// Writes land in the buffer and reach the shared table in batches, under
+/// a single lock acquisition, once `max_pending` keys are buffered or the
+/// oldest buffered write is `max_delay` old. Repeated writes to a key
+/// collapse into the last one. Write-heavy threads thus take the lock
+/// once per batch instead of once per write, at the price of other threads
+/// seeing the writes up to `max_delay` late. Reads through the buffer see
+/// its own pending writes first.
+///
+/// The delay is checked whenever the buffer is used; a buffer that goes
+/// quiet should be flushed explicitly, and is flushed when dropped. A
+/// buffered TTL keeps counting while the write waits, so the entry expires
+/// when it would have had it been written directly.
+///
+/// # Examples
+///
+/// ```
+/// use spectra_cache::{DistributedHashTable, LocalBuffer};
+/// use std::sync::{Arc, Mutex};
+/// use std::thread;
+///
+/// let shared = Arc::new(Mutex::new(DistributedHashTable::new()));
+/// let workers: Vec<_> = (0..4)
+///     .map(|worker| {
+///         let shared = Arc::clone(&shared);
+///         thread::spawn(move || {
+///             let mut buffer = LocalBuffer::new(shared).max_pending(100);
+///             for i in 0..1000 {
+///                 buffer.insert(&format!("worker:{}:{}", worker, i), "done");
+///             }
+///             // Dropping the buffer flushes what's left
+///         })
+///     })
+///     .collect();
+/// for worker in workers {
+///     worker.join().unwrap();
+/// }
+/// assert_eq!(shared.lock().unwrap().size(), 4000);
+/// ```
+#[derive(Debug)]
+pub struct LocalBuffer {
+    shared: Arc<Mutex<DistributedHashTable>>,
+    pending: HashMap<String, PendingWrite>,
+    oldest: Option<Instant>,
+    max_pending: usize,
+    max_delay: Duration,
+}
+
+/// The last buffered write to a key.
+#[derive(Debug)]
+enum PendingWrite {
+    Insert { value: String, expires_at: Option<Instant> },
+    Remove,
+}
+
+impl LocalBuffer {
+    /// Creates an empty buffer in front of `shared`.
+    pub fn new(shared: Arc<Mutex<DistributedHashTable>>) -> Self {
+        Self {
+            shared,
+            pending: HashMap::new(),
+            oldest: None,
+            max_pending: DEFAULT_MAX_PENDING,
+            max_delay: DEFAULT_MAX_DELAY,
+        }
+    }
+
+    /// Flushes once this many distinct keys are buffered. Defaults to 256.
+    ///
+    /// Panics if `max_pending` is zero.
+    pub fn max_pending(mut self, max_pending: usize) -> Self {
+        assert!(max_pending > 0, "a local buffer must hold at least one write");
+        self.max_pending = max_pending;
+        self
+    }
+
+    /// Flushes once the oldest buffered write is this old. Defaults to 100ms.
+    pub fn max_delay(mut self, max_delay: Duration) -> Self {
+        self.max_delay = max_delay;
+        self
+    }
+
+    /// Returns the number of keys with writes not yet flushed.
+    pub fn pending(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Buffers an insert of `value` under `key`.
+    pub fn insert(&mut self, key: &str, value: &str) {
+        self.buffer(key, PendingWrite::Insert { value: value.to_string(), expires_at: None });
+    }
+
+    /// Buffers an insert of `value` under `key` that expires after `ttl`,
+    /// counted from now.
+    pub fn insert_with_ttl(&mut self, key: &str, value: &str, ttl: Duration) {
+        let expires_at = Some(Instant::now() + ttl);
+        self.buffer(key, PendingWrite::Insert { value: value.to_string(), expires_at });
+    }
+
+    /// Buffers a removal of `key`.
+    pub fn remove(&mut self, key: &str) {
+        self.buffer(key, PendingWrite::Remove);
+    }
+
+    /// Reads `key`, from this buffer's pending writes if it has one and
+    /// from the shared table otherwise.
+    pub fn get(&mut self, key: &str) -> Option<String> {
+        self.flush_if_due();
+        match self.pending.get(key) {
+            Some(PendingWrite::Insert { value, expires_at }) => {
+                let expired = expires_at.is_some_and(|expires_at| expires_at <= Instant::now());
+                (!expired).then(|| value.clone())
+            }
+            Some(PendingWrite::Remove) => None,
+            None => lock(&self.shared).get(key).map(str::to_string),
+        }
+    }
+
+    /// Flushes if the oldest buffered write is older than `max_delay`.
+    /// Returns the number of keys written to the shared table.
+    pub fn flush_if_due(&mut self) -> usize {
+        match self.oldest {
+            Some(oldest) if oldest.elapsed() >= self.max_delay => self.flush(),
+            _ => 0,
+        }
+    }
+
+    /// Applies every buffered write to the shared table under one lock.
+    /// Returns the number of keys written.
+    pub fn flush(&mut self) -> usize {
+        self.oldest = None;
+        if self.pending.is_empty() {
+            return 0;
+        }
+        let now = Instant::now();
+        let mut shared = lock(&self.shared);
+        let flushed = self.pending.len();
+        for (key, write) in self.pending.drain() {
+            match write {
+                PendingWrite::Insert { value, expires_at: None } => shared.insert(&key, &value),
+                PendingWrite::Insert { value, expires_at: Some(expires_at) } => {
+                    match expires_at.checked_duration_since(now).filter(|ttl| !ttl.is_zero()) {
+                        Some(ttl) => shared.insert_with_ttl(&key, &value, ttl),
+                        // Expirou enquanto esperava: o efeito final é a chave ausente
+                        None => {
+                            shared.remove(&key);
+                        }
+                    }
+                }
+                PendingWrite::Remove => {
+                    shared.remove(&key);
+                }
+            }
+        }
+        flushed
+    }
+
+    fn buffer(&mut self, key: &str, write: PendingWrite) {
+        self.pending.insert(key.to_string(), write);
+        let oldest = *self.oldest.get_or_insert_with(Instant::now);
+        if self.pending.len() >= self.max_pending || oldest.elapsed() >= self.max_delay {
+            self.flush();
+        }
+    }
+}
+
+impl Drop for LocalBuffer {
+    fn drop(&mut self) {
+        self.flush();
+    }
+}
+
+fn lock(shared: &Mutex<DistributedHashTable>) -> MutexGuard<'_, DistributedHashTable> {
+    shared.lock().unwrap_or_else(PoisonError::into_inner)
+}
@@ -0,0 +1,252 @@
+//! A log-structured file store, the disk tier of a `TieredCache`.
+
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use crate::store::{BackingStore, StoreError};
+
+/// Value length that marks a record as a deletion.
+const TOMBSTONE: u32 = u32::MAX;
+
+/// Bytes of dead records below which the log is never compacted.
+const MIN_COMPACTION_GARBAGE: u64 = 64 * 1024;
+
+/// A key-value store kept in a single append-only file, with an in-memory
+/// index of where each key's latest value sits.
+///
+/// Every write appends a record; reads seek straight to the value. Dead
+/// records, from overwrites and deletions, are dropped by rewriting the
+/// file once they take more room than the live ones. Opening an existing
+/// file rebuilds the index from it, and a record cut short by a crash is
+/// discarded.
+///
+/// A record is the key length and the value length as big-endian `u32`s,
+/// followed by the key and the value; a deletion has a value length of
+/// `u32::MAX` and no value.
+///
+/// # Examples
+///
+/// ```
+/// use spectra_cache::{BackingStore, LogStore};
+///
+/// let path = std::env::temp_dir().join(format!("spectra-log-store-doc-{}", std::process::id()));
+/// let mut store = LogStore::open(&path).unwrap();
+/// store.store("user:1", "Ana").unwrap();
+/// store.store("user:1", "Bia").unwrap();
+/// drop(store);
+///
+/// let mut store = LogStore::open(&path).unwrap();
+/// assert_eq!(store.load("user:1").unwrap().as_deref(), Some("Bia"));
+/// # std::fs::remove_file(&path).unwrap();
+/// ```
+#[derive(Debug)]
+pub struct LogStore {
+    path: PathBuf,
+    file: File,
+    /// Offset and length of the latest value of every live key
+    index: HashMap<String, (u64, u32)>,
+    len: u64,
+    /// Bytes of the records `index` points at
+    live: u64,
+}
+
+impl LogStore {
+    /// Opens the store at `path`, creating the file if needed.
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let file = OpenOptions::new().read(true).append(true).create(true).open(&path)?;
+        let mut store = Self {
+            path,
+            file,
+            index: HashMap::new(),
+            len: 0,
+            live: 0,
+        };
+        store.rebuild_index()?;
+        Ok(store)
+    }
+
+    /// Returns the number of live keys.
+    pub fn len(&self) -> usize {
+        self.index.len()
+    }
+
+    /// Returns true if the store holds no keys.
+    pub fn is_empty(&self) -> bool {
+        self.index.is_empty()
+    }
+
+    /// Returns the size of the file in bytes, dead records included.
+    pub fn file_size(&self) -> u64 {
+        self.len
+    }
+
+    /// Rewrites the file with only the live records.
+    pub fn compact(&mut self) -> io::Result<()> {
+        let mut partial = self.path.as_os_str().to_owned();
+        partial.push(".partial");
+        let mut out = BufWriter::new(File::create(&partial)?);
+        let mut index = HashMap::with_capacity(self.index.len());
+        let mut len = 0;
+        for (key, &(offset, value_len)) in &self.index {
+            let value = self.read_value(offset, value_len)?;
+            let record = record(key, Some(&value));
+            out.write_all(&record)?;
+            index.insert(key.clone(), (len + (record.len() - value.len()) as u64, value_len));
+            len += record.len() as u64;
+        }
+        out.into_inner().map_err(io::IntoInnerError::into_error)?.sync_all()?;
+        fs::rename(&partial, &self.path)?;
+        self.file = OpenOptions::new().read(true).append(true).open(&self.path)?;
+        self.index = index;
+        self.len = len;
+        self.live = len;
+        Ok(())
+    }
+
+    fn rebuild_index(&mut self) -> io::Result<()> {
+        let mut input = BufReader::new(self.file.try_clone()?);
+        let mut offset = 0;
+        loop {
+            let mut header = [0; 8];
+            match input.read_exact(&mut header) {
+                Ok(()) => {}
+                Err(error) if error.kind() == io::ErrorKind::UnexpectedEof => break,
+                Err(error) => return Err(error),
+            }
+            let key_len = u32::from_be_bytes(header[..4].try_into().unwrap());
+            let value_len = u32::from_be_bytes(header[4..].try_into().unwrap());
+            let mut key = Vec::new();
+            if (&mut input).take(u64::from(key_len)).read_to_end(&mut key)? < key_len as usize {
+                break;
+            }
+            let Ok(key) = String::from_utf8(key) else {
+                break;
+            };
+            let header_len = 8 + u64::from(key_len);
+            let stored = if value_len == TOMBSTONE { 0 } else { u64::from(value_len) };
+            if io::copy(&mut (&mut input).take(stored), &mut io::sink())? < stored {
+                break;
+            }
+            let value = (value_len != TOMBSTONE).then_some((offset + header_len, value_len));
+            self.index_record(key, value);
+            offset += header_len + stored;
+        }
+        // Um registro cortado no meio por uma queda é descartado
+        self.file.set_len(offset)?;
+        self.len = offset;
+        Ok(())
+    }
+
+    fn read_value(&self, offset: u64, len: u32) -> io::Result<Vec<u8>> {
+        let mut file = &self.file;
+        file.seek(SeekFrom::Start(offset))?;
+        let mut value = vec![0; len as usize];
+        file.read_exact(&mut value)?;
+        Ok(value)
+    }
+
+    fn append(&mut self, key: &str, value: Option<&[u8]>) -> io::Result<()> {
+        let record = record(key, value);
+        self.file.write_all(&record)?;
+        let header_len = 8 + key.len() as u64;
+        self.index_record(key.to_string(), value.map(|value| (self.len + header_len, value.len() as u32)));
+        self.len += record.len() as u64;
+        let garbage = self.len - self.live;
+        if garbage > MIN_COMPACTION_GARBAGE && garbage > self.live {
+            self.compact()?;
+        }
+        Ok(())
+    }
+
+    /// Points the index at a key's new value, or drops the key for a
+    /// deletion, keeping `live` in step.
+    fn index_record(&mut self, key: String, value: Option<(u64, u32)>) {
+        let header_len = 8 + key.len() as u64;
+        let old = match value {
+            Some((offset, len)) => {
+                self.live += header_len + u64::from(len);
+                self.index.insert(key, (offset, len))
+            }
+            None => self.index.remove(&key),
+        };
+        if let Some((_, len)) = old {
+            self.live -= header_len + u64::from(len);
+        }
+    }
+}
+
+impl BackingStore for LogStore {
+    fn load(&mut self, key: &str) -> Result<Option<String>, StoreError> {
+        let Some(&(offset, len)) = self.index.get(key) else {
+            return Ok(None);
+        };
+        Ok(Some(String::from_utf8(self.read_value(offset, len)?)?))
+    }
+
+    fn store(&mut self, key: &str, value: &str) -> Result<(), StoreError> {
+        Ok(self.append(key, Some(value.as_bytes()))?)
+    }
+
+    fn delete(&mut self, key: &str) -> Result<(), StoreError> {
+        if self.index.contains_key(key) {
+            self.append(key, None)?;
+        }
+        Ok(())
+    }
+}
+
+fn record(key: &str, value: Option<&[u8]>) -> Vec<u8> {
+    let mut record = Vec::with_capacity(8 + key.len() + value.map_or(0, <[u8]>::len));
+    record.extend_from_slice(&(key.len() as u32).to_be_bytes());
+    record.extend_from_slice(&value.map_or(TOMBSTONE, |value| value.len() as u32).to_be_bytes());
+    record.extend_from_slice(key.as_bytes());
+    record.extend_from_slice(value.unwrap_or_default());
+    record
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("spectra-log-store-{}-{}", name, std::process::id()))
+    }
+
+    #[test]
+    fn test_reopen_and_truncated_tail() {
+        let path = temp_path("reopen");
+        let mut store = LogStore::open(&path).unwrap();
+        store.store("a", "1").unwrap();
+        store.store("b", "2").unwrap();
+        store.delete("a").unwrap();
+        let size = store.file_size();
+        drop(store);
+
+        // Metade de um registro, como depois de uma queda no meio da escrita
+        OpenOptions::new().append(true).open(&path).unwrap().write_all(&record("c", Some(b"3"))[..6]).unwrap();
+        let mut store = LogStore::open(&path).unwrap();
+        assert_eq!(store.file_size(), size);
+        assert_eq!(store.len(), 1);
+        assert_eq!(store.load("a").unwrap(), None);
+        assert_eq!(store.load("b").unwrap().as_deref(), Some("2"));
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_compaction_drops_dead_records() {
+        let path = temp_path("compact");
+        let mut store = LogStore::open(&path).unwrap();
+        let value = "x".repeat(1000);
+        for round in 0..200 {
+            store.store(&format!("k{}", round % 10), &value).unwrap();
+        }
+        assert!(store.file_size() < 80 * 1024);
+        store.compact().unwrap();
+        assert_eq!(store.file_size(), 10 * (8 + 2 + 1000));
+        assert_eq!(store.load("k3").unwrap(), Some(value));
+        fs::remove_file(&path).unwrap();
+    }
+}
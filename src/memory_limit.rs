@@ -0,0 +1,236 @@
+use alloc::string::ToString;
+use core::fmt;
+use core::str::FromStr;
+use core::time::Duration;
+#[cfg(feature = "std")]
+use std::fs;
+#[cfg(feature = "std")]
+use std::path::Path;
+
+use crate::clock::Instant;
+use crate::error::CacheError;
+
+/// How often a relative limit is recomputed from the detected memory size.
+const REVALIDATE_INTERVAL: Duration = Duration::from_secs(30);
+
+/// cgroup v1 reports "no limit" as a huge page-aligned number instead of `max`.
+#[cfg(feature = "std")]
+const CGROUP_V1_UNLIMITED: u64 = 1 << 60;
+
+/// A memory budget for a cache, either absolute or relative to the memory
+/// available to the process.
+///
+/// Relative limits are resolved against the container's cgroup memory limit
+/// (v2 or v1) when there is one, and against the machine's total memory
+/// otherwise, so the same configuration works across differently sized
+/// pods. They are re-resolved every 30 seconds to follow limit changes.
+/// Without the `std` feature there is no memory size to detect, and only
+/// absolute limits apply.
+///
+/// Parses from the usual configuration spellings: `"60%"`, `"512mb"`,
+/// `"2gb"`, `"64kb"` or a plain byte count.
+///
+/// # Examples
+///
+/// ```
+/// use spectra_cache::{DistributedHashTable, MemoryLimit};
+///
+/// let limit: MemoryLimit = "60%".parse().unwrap();
+/// let mut cache = DistributedHashTable::builder().max_memory(limit).build();
+/// cache.insert("user:123", "John Doe");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MemoryLimit {
+    /// A fixed number of bytes.
+    Bytes(usize),
+    /// A percentage (0 to 100) of the memory available to the process.
+    Percent(f64),
+}
+
+impl MemoryLimit {
+    /// Returns the limit in bytes, or `None` if it is relative and the
+    /// available memory can't be detected on this platform.
+    pub fn resolve(&self) -> Option<usize> {
+        match *self {
+            MemoryLimit::Bytes(bytes) => Some(bytes),
+            #[cfg(feature = "std")]
+            MemoryLimit::Percent(percent) => {
+                let available = available_memory(Path::new("/"))?;
+                Some((available as f64 * percent / 100.0) as usize)
+            }
+            #[cfg(not(feature = "std"))]
+            MemoryLimit::Percent(_) => None,
+        }
+    }
+}
+
+/// Formats the limit so that it parses back: `"60%"` or a byte count.
+impl fmt::Display for MemoryLimit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MemoryLimit::Bytes(bytes) => write!(f, "{}", bytes),
+            MemoryLimit::Percent(percent) => write!(f, "{}%", percent),
+        }
+    }
+}
+
+impl FromStr for MemoryLimit {
+    type Err = CacheError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let invalid = || CacheError::InvalidMemoryLimit {
+            value: value.to_string(),
+        };
+        let normalized = value.trim().to_ascii_lowercase();
+
+        if let Some(percent) = normalized.strip_suffix('%') {
+            let percent: f64 = percent.trim().parse().map_err(|_| invalid())?;
+            if !(percent > 0.0 && percent <= 100.0) {
+                return Err(invalid());
+            }
+            return Ok(MemoryLimit::Percent(percent));
+        }
+
+        let digits_end = normalized
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(normalized.len());
+        let (number, unit) = normalized.split_at(digits_end);
+        let number: usize = number.parse().map_err(|_| invalid())?;
+        let multiplier = match unit.trim() {
+            "" | "b" => 1,
+            "k" | "kb" => 1 << 10,
+            "m" | "mb" => 1 << 20,
+            "g" | "gb" => 1 << 30,
+            _ => return Err(invalid()),
+        };
+        number.checked_mul(multiplier).map(MemoryLimit::Bytes).ok_or_else(invalid)
+    }
+}
+
+/// A `MemoryLimit` together with its last resolved value.
+#[derive(Debug, Clone)]
+pub(crate) struct MemoryBudget {
+    limit: MemoryLimit,
+    bytes: Option<usize>,
+    resolved_at: Instant,
+}
+
+impl MemoryBudget {
+    /// Resolves `limit` at `now` on the cache's clock.
+    pub(crate) fn new(limit: MemoryLimit, now: Instant) -> Self {
+        Self {
+            limit,
+            bytes: limit.resolve(),
+            resolved_at: now,
+        }
+    }
+
+    /// Returns the budget in bytes as last resolved.
+    pub(crate) fn current(&self) -> Option<usize> {
+        self.bytes
+    }
+
+    /// Returns the current budget in bytes, re-resolving relative limits
+    /// once the revalidation interval has passed by `now`.
+    pub(crate) fn bytes(&mut self, now: Instant) -> Option<usize> {
+        let due = now.saturating_duration_since(self.resolved_at) >= REVALIDATE_INTERVAL;
+        if matches!(self.limit, MemoryLimit::Percent(_)) && due {
+            self.bytes = self.limit.resolve();
+            self.resolved_at = now;
+        }
+        self.bytes
+    }
+}
+
+/// Returns the memory available to the process: the cgroup limit if the
+/// process runs in a limited cgroup, capped by the machine's total memory.
+#[cfg(feature = "std")]
+fn available_memory(root: &Path) -> Option<u64> {
+    let total = total_memory(root);
+    match (cgroup_limit(root), total) {
+        (Some(limit), Some(total)) => Some(limit.min(total)),
+        (limit, total) => limit.or(total),
+    }
+}
+
+#[cfg(feature = "std")]
+fn cgroup_limit(root: &Path) -> Option<u64> {
+    // cgroup v2 expõe "max" quando não há limite
+    if let Ok(contents) = fs::read_to_string(root.join("sys/fs/cgroup/memory.max")) {
+        return contents.trim().parse().ok();
+    }
+    let contents = fs::read_to_string(root.join("sys/fs/cgroup/memory/memory.limit_in_bytes")).ok()?;
+    contents.trim().parse().ok().filter(|&limit| limit < CGROUP_V1_UNLIMITED)
+}
+
+#[cfg(feature = "std")]
+fn total_memory(root: &Path) -> Option<u64> {
+    let meminfo = fs::read_to_string(root.join("proc/meminfo")).ok()?;
+    let line = meminfo.lines().find(|line| line.starts_with("MemTotal:"))?;
+    let kib: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+    Some(kib * 1024)
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn fake_root(name: &str, files: &[(&str, &str)]) -> PathBuf {
+        let root = std::env::temp_dir().join(format!("spectra-cache-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        for (path, contents) in files {
+            let path = root.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, contents).unwrap();
+        }
+        root
+    }
+
+    const MEMINFO: &str = "MemTotal:       16384000 kB\nMemFree:         1024 kB\n";
+
+    #[test]
+    fn test_parse() {
+        assert_eq!("60%".parse(), Ok(MemoryLimit::Percent(60.0)));
+        assert_eq!(" 512MB ".parse(), Ok(MemoryLimit::Bytes(512 << 20)));
+        assert_eq!("2gb".parse(), Ok(MemoryLimit::Bytes(2 << 30)));
+        assert_eq!("64k".parse(), Ok(MemoryLimit::Bytes(64 << 10)));
+        assert_eq!("1000".parse(), Ok(MemoryLimit::Bytes(1000)));
+        for limit in [MemoryLimit::Percent(62.5), MemoryLimit::Bytes(4096)] {
+            assert_eq!(limit.to_string().parse(), Ok(limit));
+        }
+
+        for invalid in ["", "0%", "101%", "12tb", "lots", "-5mb"] {
+            assert!(invalid.parse::<MemoryLimit>().is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_cgroup_v2_limit() {
+        let root = fake_root("v2", &[("sys/fs/cgroup/memory.max", "536870912\n"), ("proc/meminfo", MEMINFO)]);
+        assert_eq!(available_memory(&root), Some(536_870_912));
+
+        fs::write(root.join("sys/fs/cgroup/memory.max"), "max\n").unwrap();
+        assert_eq!(available_memory(&root), Some(16_384_000 * 1024));
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn test_cgroup_v1_limit() {
+        let root = fake_root(
+            "v1",
+            &[("sys/fs/cgroup/memory/memory.limit_in_bytes", "1073741824\n"), ("proc/meminfo", MEMINFO)],
+        );
+        assert_eq!(available_memory(&root), Some(1_073_741_824));
+
+        fs::write(root.join("sys/fs/cgroup/memory/memory.limit_in_bytes"), "9223372036854771712\n").unwrap();
+        assert_eq!(available_memory(&root), Some(16_384_000 * 1024));
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn test_no_information() {
+        let root = fake_root("none", &[]);
+        assert_eq!(available_memory(&root), None);
+    }
+}
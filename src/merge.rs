@@ -0,0 +1,68 @@
+use alloc::boxed::Box;
+use alloc::string::String;
+use core::fmt;
+
+use crate::core::{CacheCore, EntryMap};
+use crate::entry::CacheEntry;
+use crate::value::CacheValue;
+
+type Resolver<V> = Box<dyn FnMut(&str, &<V as CacheValue>::Ref, &<V as CacheValue>::Ref) -> V>;
+
+/// How `merge_from()` settles a key that is live in both caches.
+pub enum ConflictStrategy<V: CacheValue = String> {
+    /// Keeps whichever value was written last, along with its TTL. Ties
+    /// keep the existing entry.
+    KeepNewest,
+    /// Keeps the existing entry and ignores the incoming one.
+    KeepExisting,
+    /// Stores the value returned for `(key, existing, incoming)`, keeping
+    /// the existing entry's TTL. Build with [`custom`](Self::custom).
+    Custom(Resolver<V>),
+}
+
+impl<V: CacheValue> ConflictStrategy<V> {
+    /// Resolves conflicts with `resolve(key, existing, incoming)`, e.g. to
+    /// add up counters collected by several threads.
+    pub fn custom(resolve: impl FnMut(&str, &V::Ref, &V::Ref) -> V + 'static) -> Self {
+        ConflictStrategy::Custom(Box::new(resolve))
+    }
+}
+
+impl<V: CacheValue> fmt::Debug for ConflictStrategy<V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConflictStrategy::KeepNewest => f.write_str("KeepNewest"),
+            ConflictStrategy::KeepExisting => f.write_str("KeepExisting"),
+            ConflictStrategy::Custom(_) => f.write_str("Custom(..)"),
+        }
+    }
+}
+
+impl<M: EntryMap> CacheCore<M>
+where
+    M::Value: Clone,
+{
+    /// Copies the live entries of `other` into this cache, settling keys
+    /// live in both with `strategy`. Returns how many keys were written.
+    pub(crate) fn merge_from(&mut self, other: &Self, mut strategy: ConflictStrategy<M::Value>) -> usize {
+        let mut written = 0;
+        let (now, other_now) = (self.now(), other.now());
+        for (key, incoming) in other.entries.iter().filter(|(_, entry)| !entry.is_expired(other_now)) {
+            let existing = self.entries.get(key).filter(|entry| !entry.is_expired(now));
+            match (existing, &mut strategy) {
+                (Some(_), ConflictStrategy::KeepExisting) => continue,
+                (Some(existing), ConflictStrategy::KeepNewest) if existing.written_at() >= incoming.written_at() => {
+                    continue
+                }
+                (Some(existing), ConflictStrategy::Custom(resolve)) => {
+                    let value = resolve(key, existing.value(), incoming.value());
+                    self.update(key, value.view());
+                }
+                // Chave nova, ou a que chega é mais recente: vai com o TTL dela
+                _ => self.insert_entry(key, CacheEntry::clone(incoming)),
+            }
+            written += 1;
+        }
+        written
+    }
+}
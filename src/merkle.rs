@@ -0,0 +1,305 @@
+//! Merkle-tree digests of a cache's content, for anti-entropy between
+//! nodes that should hold the same entries.
+//!
+//! Keys are spread over a power-of-two number of buckets by ranges of a
+//! stable hash, so every node puts a key in the same bucket. A bucket's
+//! hash is the wrapping sum of the hashes of its live key/value pairs,
+//! which doesn't depend on iteration order; parents hash their two
+//! children, up to the root. Comparing two digests from the root down only
+//! visits the subtrees that differ. TTLs are not part of the digest.
+
+use std::collections::HashSet;
+
+use crate::aof::{self, Operation};
+use crate::cluster::stable_hash_bytes;
+use crate::error::CacheError;
+use crate::value::CacheValue;
+
+/// A Merkle tree over the entries of a cache, made by `merkle_digest()`.
+///
+/// Digests are cheap to send to another node with
+/// [`to_bytes`](Self::to_bytes): 8 bytes per bucket.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MerkleDigest {
+    /// `levels[0]` holds the buckets, every next level the parents of the
+    /// one before, and the last one the root alone
+    levels: Vec<Vec<u64>>,
+}
+
+impl MerkleDigest {
+    /// Builds the digest of `entries` over `buckets` buckets.
+    ///
+    /// Panics unless `buckets` is a power of two.
+    pub(crate) fn build<'a>(buckets: usize, entries: impl Iterator<Item = (&'a str, &'a [u8])>) -> Self {
+        assert!(buckets.is_power_of_two(), "the bucket count must be a power of two");
+        let mut leaves = vec![0u64; buckets];
+        for (key, value) in entries {
+            let bucket = bucket_of(key, buckets);
+            // 0xff nunca aparece em UTF-8, então separa chave e valor sem ambiguidade
+            leaves[bucket] = leaves[bucket].wrapping_add(stable_hash_bytes(&[key.as_bytes(), &[0xff], value]));
+        }
+        Self::from_leaves(leaves)
+    }
+
+    fn from_leaves(leaves: Vec<u64>) -> Self {
+        let mut levels = vec![leaves];
+        while let Some(level) = levels.last().filter(|level| level.len() > 1) {
+            let parents = level
+                .chunks(2)
+                .map(|pair| stable_hash_bytes(&[&pair[0].to_be_bytes(), &pair[1].to_be_bytes()]))
+                .collect();
+            levels.push(parents);
+        }
+        Self { levels }
+    }
+
+    /// Returns the number of buckets.
+    pub fn buckets(&self) -> usize {
+        self.levels[0].len()
+    }
+
+    /// Returns the root hash. Two caches with the same entries have the
+    /// same root.
+    pub fn root(&self) -> u64 {
+        self.levels[self.levels.len() - 1][0]
+    }
+
+    /// Returns the buckets whose content differs between the two digests,
+    /// descending only into the subtrees that differ.
+    ///
+    /// Panics if the digests have different bucket counts.
+    pub fn diff(&self, other: &MerkleDigest) -> DivergentBuckets {
+        assert_eq!(self.buckets(), other.buckets(), "the digests have different bucket counts");
+        let mut buckets = Vec::new();
+        let mut pending = vec![(self.levels.len() - 1, 0)];
+        while let Some((level, index)) = pending.pop() {
+            if self.levels[level][index] == other.levels[level][index] {
+                continue;
+            }
+            match level {
+                0 => buckets.push(index),
+                _ => pending.extend([(level - 1, 2 * index + 1), (level - 1, 2 * index)]),
+            }
+        }
+        DivergentBuckets {
+            bucket_count: self.buckets(),
+            buckets,
+        }
+    }
+
+    /// Encodes the digest as its bucket count, a big-endian `u32`, followed
+    /// by the bucket hashes as big-endian `u64`s.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(4 + 8 * self.buckets());
+        bytes.extend_from_slice(&(self.buckets() as u32).to_be_bytes());
+        for leaf in &self.levels[0] {
+            bytes.extend_from_slice(&leaf.to_be_bytes());
+        }
+        bytes
+    }
+
+    /// Decodes a digest made by [`to_bytes`](Self::to_bytes).
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, CacheError> {
+        let (count, leaves) = bytes.split_first_chunk::<4>().ok_or(invalid("truncated digest"))?;
+        let count = u32::from_be_bytes(*count) as usize;
+        if !count.is_power_of_two() || leaves.len() != 8 * count {
+            return Err(invalid("malformed digest"));
+        }
+        let leaves = leaves.chunks_exact(8).map(|leaf| u64::from_be_bytes(leaf.try_into().unwrap())).collect();
+        Ok(Self::from_leaves(leaves))
+    }
+}
+
+/// The buckets in which two digests differ, from [`MerkleDigest::diff`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DivergentBuckets {
+    bucket_count: usize,
+    buckets: Vec<usize>,
+}
+
+impl DivergentBuckets {
+    /// Returns true if the digests matched.
+    pub fn is_empty(&self) -> bool {
+        self.buckets.is_empty()
+    }
+
+    /// Returns how many buckets differ.
+    pub fn len(&self) -> usize {
+        self.buckets.len()
+    }
+
+    /// Returns the indexes of the buckets that differ, in ascending order.
+    pub fn buckets(&self) -> &[usize] {
+        &self.buckets
+    }
+
+    /// Returns true if `key` falls in one of the buckets that differ.
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.buckets.binary_search(&bucket_of(key, self.bucket_count)).is_ok()
+    }
+}
+
+/// The live entries of some buckets of a cache, made by
+/// `export_buckets()`, that bring another cache's copy of those buckets in
+/// line with `apply_bucket_patch()`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BucketPatch {
+    divergent: DivergentBuckets,
+    /// Append-only log set records, end to end
+    records: Vec<u8>,
+    entries: usize,
+}
+
+impl BucketPatch {
+    pub(crate) fn new(divergent: &DivergentBuckets, records: impl Iterator<Item = Vec<u8>>) -> Self {
+        let mut patch = Self {
+            divergent: divergent.clone(),
+            records: Vec::new(),
+            entries: 0,
+        };
+        for record in records {
+            patch.records.extend_from_slice(&record);
+            patch.entries += 1;
+        }
+        patch
+    }
+
+    /// Returns the buckets the patch replaces.
+    pub fn divergent(&self) -> &DivergentBuckets {
+        &self.divergent
+    }
+
+    /// Returns the number of entries in the patch.
+    pub fn len(&self) -> usize {
+        self.entries
+    }
+
+    /// Returns true if the patch has no entries, so applying it empties its
+    /// buckets.
+    pub fn is_empty(&self) -> bool {
+        self.entries == 0
+    }
+
+    /// Decodes the entries as values of type `V`, checking that every one
+    /// belongs in the patch's buckets.
+    pub(crate) fn operations<V: CacheValue>(&self) -> Result<Vec<Operation<V>>, CacheError> {
+        let operations = aof::decode_all::<V>(&self.records)?;
+        let belongs = |operation: &Operation<V>| match operation {
+            Operation::Set { key, .. } => self.divergent.contains_key(key),
+            _ => false,
+        };
+        if operations.iter().all(belongs) {
+            Ok(operations)
+        } else {
+            Err(invalid("patch entry outside its buckets"))
+        }
+    }
+
+    /// Returns the keys the patch sets.
+    pub(crate) fn keys<V>(operations: &[Operation<V>]) -> HashSet<&str> {
+        operations
+            .iter()
+            .filter_map(|operation| match operation {
+                Operation::Set { key, .. } => Some(key.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Encodes the patch: the bucket count and the number of buckets as
+    /// big-endian `u32`s, the bucket indexes as big-endian `u32`s, then the
+    /// entries in the append-only log's record format.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(8 + 4 * self.divergent.len() + self.records.len());
+        bytes.extend_from_slice(&(self.divergent.bucket_count as u32).to_be_bytes());
+        bytes.extend_from_slice(&(self.divergent.len() as u32).to_be_bytes());
+        for &bucket in &self.divergent.buckets {
+            bytes.extend_from_slice(&(bucket as u32).to_be_bytes());
+        }
+        bytes.extend_from_slice(&self.records);
+        bytes
+    }
+
+    /// Decodes a patch made by [`to_bytes`](Self::to_bytes).
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, CacheError> {
+        let truncated = || invalid("truncated patch");
+        let mut words = bytes.chunks_exact(4).map(|word| u32::from_be_bytes(word.try_into().unwrap()) as usize);
+        let bucket_count = words.next().ok_or_else(truncated)?;
+        let len = words.next().ok_or_else(truncated)?;
+        if !bucket_count.is_power_of_two() || len > bucket_count {
+            return Err(invalid("malformed patch"));
+        }
+        let buckets: Vec<usize> = words.by_ref().take(len).collect();
+        if buckets.len() < len {
+            return Err(truncated());
+        }
+        if buckets.iter().any(|&bucket| bucket >= bucket_count) || !buckets.windows(2).all(|pair| pair[0] < pair[1]) {
+            return Err(invalid("malformed patch"));
+        }
+        let records = bytes[8 + 4 * len..].to_vec();
+        // Valida a estrutura agora; o tipo dos valores só é conferido ao aplicar
+        let entries = aof::decode_all::<Vec<u8>>(&records)?.len();
+        Ok(Self {
+            divergent: DivergentBuckets { bucket_count, buckets },
+            records,
+            entries,
+        })
+    }
+}
+
+/// Returns the bucket of `key` among `buckets`: the top bits of its hash.
+fn bucket_of(key: &str, buckets: usize) -> usize {
+    match buckets.trailing_zeros() {
+        0 => 0,
+        bits => (stable_hash_bytes(&[key.as_bytes()]) >> (64 - bits)) as usize,
+    }
+}
+
+fn invalid(reason: &'static str) -> CacheError {
+    CacheError::InvalidDump { reason }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::Instant;
+    use crate::entry::CacheEntry;
+
+    fn digest(buckets: usize, entries: &[(&str, &str)]) -> MerkleDigest {
+        MerkleDigest::build(buckets, entries.iter().map(|(key, value)| (*key, value.as_bytes())))
+    }
+
+    #[test]
+    fn test_digest_ignores_order_and_finds_changed_buckets() {
+        let a = digest(16, &[("a", "1"), ("b", "2"), ("c", "3")]);
+        assert_eq!(a, digest(16, &[("c", "3"), ("a", "1"), ("b", "2")]));
+        assert!(a.diff(&a).is_empty());
+
+        let b = digest(16, &[("a", "1"), ("b", "changed"), ("c", "3")]);
+        assert_ne!(a.root(), b.root());
+        let divergent = a.diff(&b);
+        assert_eq!(divergent.buckets(), [bucket_of("b", 16)]);
+        assert!(divergent.contains_key("b"));
+
+        // Um único bucket é o caso degenerado de uma árvore só com a raiz
+        let single = digest(1, &[("a", "1")]);
+        assert_eq!(single.diff(&digest(1, &[])).buckets(), [0]);
+    }
+
+    #[test]
+    fn test_round_trips() {
+        let a = digest(8, &[("a", "1"), ("b", "2")]);
+        assert_eq!(MerkleDigest::from_bytes(&a.to_bytes()).unwrap(), a);
+        assert!(MerkleDigest::from_bytes(&a.to_bytes()[..20]).is_err());
+
+        let divergent = a.diff(&digest(8, &[]));
+        let now = Instant::now();
+        let entry = CacheEntry::<String>::new("a", "1", now);
+        let records = [aof::set_record("a", &entry, now)].into_iter().filter(|_| divergent.contains_key("a"));
+        let patch = BucketPatch::new(&divergent, records);
+        let decoded = BucketPatch::from_bytes(&patch.to_bytes()).unwrap();
+        assert_eq!(decoded, patch);
+        assert_eq!(decoded.operations::<String>().unwrap().len(), 1);
+        assert!(BucketPatch::from_bytes(&patch.to_bytes()[..patch.to_bytes().len() - 1]).is_err());
+    }
+}
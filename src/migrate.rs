@@ -0,0 +1,120 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::hash::BuildHasher;
+
+use crate::config::CacheType;
+use crate::entry::CacheEntry;
+use crate::error::CacheError;
+use crate::value::CacheValue;
+use crate::{BTreeCache, BytesCache, DistributedHashTable};
+
+/// Entries taken out of a cache with their TTL and idle metadata intact.
+pub struct MovedEntries<V>(Vec<(String, CacheEntry<V>)>);
+
+/// A cache entries can be moved into and out of with [`migrate`].
+pub trait Migrate: CacheType {
+    /// Fails if the cache refuses writes, as when its persistence keeps
+    /// failing.
+    fn accepts_writes(&self) -> Result<(), CacheError>;
+
+    /// Removes the live entries `filter` accepts.
+    fn take_entries(&mut self, filter: &mut dyn FnMut(&str, &<Self::Value as CacheValue>::Ref) -> bool)
+        -> Result<MovedEntries<Self::Value>, CacheError>;
+
+    /// Stores entries taken from another cache, all of them or none.
+    fn put_entries(&mut self, entries: MovedEntries<Self::Value>) -> Result<(), CacheError>;
+}
+
+/// Moves the live entries of `from` that `filter` accepts into `to`, and
+/// returns how many were moved.
+///
+/// Entries keep their remaining TTL, idle timeout and creation time, so a
+/// moved entry expires exactly when it would have in `from`; only their
+/// recency restarts, as for a fresh write. Either cache may be a
+/// `DistributedHashTable` or a `BTreeCache`, or both `BytesCache`s, which
+/// makes this the building block for promoting and demoting entries between
+/// tiers, splitting a namespace into its own cache, or switching to a
+/// differently configured cache inside a running process.
+///
+/// Moved entries count as removed in `from` and as written in `to`: `from`'s
+/// listeners and subscriptions see removals and `to`'s see inserts, and
+/// `to` writes them to its backing store, if any, while `from` leaves its
+/// store alone since the data still exists. Entries already under the same
+/// key in `to` are replaced, and `to`'s memory limit applies as usual.
+///
+/// Fails with [`CacheError::PersistenceFailed`] without moving anything if
+/// either cache refuses writes because its persistence keeps failing.
+///
+/// # Examples
+///
+/// ```
+/// use spectra_cache::{migrate, BTreeCache, DistributedHashTable};
+/// use std::time::Duration;
+///
+/// let mut hot = DistributedHashTable::new();
+/// hot.insert("user:1", "Ana");
+/// hot.insert_with_ttl("user:2", "Bia", Duration::from_secs(60));
+/// hot.insert("config:theme", "dark");
+///
+/// let mut cold = BTreeCache::new();
+/// let moved = migrate(&mut hot, &mut cold, |key, _| key.starts_with("user:")).unwrap();
+/// assert_eq!(moved, 2);
+/// assert_eq!(hot.size(), 1);
+/// assert_eq!(cold.get("user:1"), Some("Ana"));
+/// assert!(cold.ttl("user:2").unwrap() <= Duration::from_secs(60));
+/// ```
+pub fn migrate<F, T, P>(from: &mut F, to: &mut T, mut filter: P) -> Result<usize, CacheError>
+where
+    F: Migrate,
+    T: Migrate<Value = F::Value>,
+    P: FnMut(&str, &<F::Value as CacheValue>::Ref) -> bool,
+{
+    // O destino é conferido antes: nada sai da origem sem ter para onde ir
+    to.accepts_writes()?;
+    let entries = from.take_entries(&mut filter)?;
+    let moved = entries.0.len();
+    to.put_entries(entries)?;
+    Ok(moved)
+}
+
+impl<S: BuildHasher + Clone + Default> Migrate for DistributedHashTable<S> {
+    fn accepts_writes(&self) -> Result<(), CacheError> {
+        self.core.check_writable()
+    }
+
+    fn take_entries(&mut self, filter: &mut dyn FnMut(&str, &str) -> bool) -> Result<MovedEntries<String>, CacheError> {
+        self.core.take_matching(filter).map(MovedEntries)
+    }
+
+    fn put_entries(&mut self, entries: MovedEntries<String>) -> Result<(), CacheError> {
+        self.core.insert_entries(entries.0)
+    }
+}
+
+impl Migrate for BTreeCache {
+    fn accepts_writes(&self) -> Result<(), CacheError> {
+        self.core.check_writable()
+    }
+
+    fn take_entries(&mut self, filter: &mut dyn FnMut(&str, &str) -> bool) -> Result<MovedEntries<String>, CacheError> {
+        self.core.take_matching(filter).map(MovedEntries)
+    }
+
+    fn put_entries(&mut self, entries: MovedEntries<String>) -> Result<(), CacheError> {
+        self.core.insert_entries(entries.0)
+    }
+}
+
+impl Migrate for BytesCache {
+    fn accepts_writes(&self) -> Result<(), CacheError> {
+        self.core.check_writable()
+    }
+
+    fn take_entries(&mut self, filter: &mut dyn FnMut(&str, &[u8]) -> bool) -> Result<MovedEntries<Vec<u8>>, CacheError> {
+        self.core.take_matching(filter).map(MovedEntries)
+    }
+
+    fn put_entries(&mut self, entries: MovedEntries<Vec<u8>>) -> Result<(), CacheError> {
+        self.core.insert_entries(entries.0)
+    }
+}
@@ -0,0 +1,197 @@
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::hash::BuildHasher;
+use core::time::Duration;
+
+use crate::collections::{DefaultHashBuilder, HashSet};
+use crate::{CacheError, DistributedHashTable};
+
+/// Separates a namespace's name from the keys inside it.
+const SEPARATOR: char = ':';
+
+/// A view of one keyspace inside a `DistributedHashTable`, returned by
+/// [`DistributedHashTable::namespace`].
+///
+/// Every key passed to the handle is stored as `"{name}:{key}"`, and keys
+/// come back out without the prefix, so several tenants can share one
+/// table without building prefixes by hand. Namespaces nest by name: the
+/// keys of `"tenant:42"` are also inside `"tenant"`, so clearing `"tenant"`
+/// wipes every tenant, while `"tenant:4"` and `"tenant:42"` stay apart.
+///
+/// The table's memory limit, default TTL and listeners are shared by all
+/// namespaces; listeners see the full, prefixed keys.
+///
+/// # Examples
+///
+/// ```
+/// use spectra_cache::DistributedHashTable;
+///
+/// let mut cache = DistributedHashTable::new();
+/// cache.namespace("tenant:42").insert("user:1", "Ana");
+/// cache.namespace("tenant:7").insert("user:1", "Bruno");
+/// assert_eq!(cache.get("tenant:42:user:1"), Some("Ana"));
+///
+/// assert_eq!(cache.namespace("tenant:42").clear_namespace(), 1);
+/// assert_eq!(cache.namespace("tenant:42").get("user:1"), None);
+/// assert_eq!(cache.namespace("tenant:7").get("user:1"), Some("Bruno"));
+/// ```
+#[derive(Debug)]
+pub struct Namespace<'a, S: BuildHasher + Clone + Default = DefaultHashBuilder> {
+    table: &'a mut DistributedHashTable<S>,
+    prefix: String,
+}
+
+impl<'a, S: BuildHasher + Clone + Default> Namespace<'a, S> {
+    pub(crate) fn new(table: &'a mut DistributedHashTable<S>, name: &str) -> Self {
+        Self {
+            table,
+            prefix: format!("{}{}", name, SEPARATOR),
+        }
+    }
+
+    /// Returns the namespace's name.
+    pub fn name(&self) -> &str {
+        &self.prefix[..self.prefix.len() - SEPARATOR.len_utf8()]
+    }
+
+    /// Returns the key `key` is stored under in the table.
+    pub fn full_key(&self, key: &str) -> String {
+        format!("{}{}", self.prefix, key)
+    }
+
+    /// Inserts a key-value pair into the namespace.
+    pub fn insert(&mut self, key: &str, value: &str) {
+        let key = self.full_key(key);
+        self.table.insert(&key, value);
+    }
+
+    /// Inserts a key-value pair that expires after `ttl`.
+    pub fn insert_with_ttl(&mut self, key: &str, value: &str, ttl: Duration) {
+        let key = self.full_key(key);
+        self.table.insert_with_ttl(&key, value, ttl);
+    }
+
+    /// Retrieves a value by key, like [`DistributedHashTable::get`].
+    pub fn get(&mut self, key: &str) -> Option<&str> {
+        let key = self.full_key(key);
+        self.table.get(&key)
+    }
+
+    /// Retrieves a value without marking it as used, like
+    /// [`DistributedHashTable::peek`].
+    pub fn peek(&self, key: &str) -> Option<&str> {
+        self.table.peek(&self.full_key(key))
+    }
+
+    /// Returns how long `key` has left to live.
+    pub fn ttl(&self, key: &str) -> Option<Duration> {
+        self.table.ttl(&self.full_key(key))
+    }
+
+    /// Sets or replaces the TTL of an existing entry. Returns true if the key existed.
+    pub fn expire(&mut self, key: &str, ttl: Duration) -> bool {
+        let key = self.full_key(key);
+        self.table.expire(&key, ttl)
+    }
+
+    /// Removes a key, returning its value if it existed.
+    pub fn remove(&mut self, key: &str) -> Option<String> {
+        let key = self.full_key(key);
+        self.table.remove(&key)
+    }
+
+    /// Adds `delta` to the integer stored under `key`, like
+    /// [`DistributedHashTable::incr`].
+    pub fn incr(&mut self, key: &str, delta: i64) -> Result<i64, CacheError> {
+        let key = self.full_key(key);
+        self.table.incr(&key, delta)
+    }
+
+    /// Checks if a live key exists in the namespace.
+    pub fn contains_key(&self, key: &str) -> bool {
+        let key = self.full_key(key);
+        self.table.contains_key(&key)
+    }
+
+    /// Returns an iterator over the namespace's live keys, without the prefix.
+    /// Time complexity: O(n) in the size of the whole table.
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        self.table.keys().filter_map(|key| key.strip_prefix(self.prefix.as_str()))
+    }
+
+    /// Returns the number of live entries in the namespace.
+    pub fn size(&self) -> usize {
+        self.keys().count()
+    }
+
+    /// Returns true if the namespace holds no live entries.
+    pub fn is_empty(&self) -> bool {
+        self.keys().next().is_none()
+    }
+
+    /// Removes every entry of the namespace, expired or not, leaving other
+    /// namespaces untouched. Returns the number of live entries removed.
+    ///
+    /// Each entry is removed as if by [`remove`](Self::remove), so listeners
+    /// are told with [`RemovalCause::Removed`](crate::RemovalCause::Removed).
+    /// The audit sink gets a single
+    /// [`AuditAction::NamespaceDrop`](crate::AuditAction::NamespaceDrop).
+    pub fn clear_namespace(&mut self) -> usize {
+        let mut keys: Vec<String> = self
+            .table
+            .keys_raw()
+            .filter(|key| key.starts_with(self.prefix.as_str()))
+            .cloned()
+            .collect();
+        self.table.core.sort_for_walk(&mut keys, String::as_str);
+        let live = keys.iter().filter(|key| self.table.peek(key).is_some()).count();
+        for key in &keys {
+            self.table.remove(key);
+        }
+        let name = self.name().to_string();
+        self.table.core.record_namespace_drop(&name, live);
+        live
+    }
+
+    /// Replaces the whole content of the namespace with `contents` in one
+    /// step, and returns the number of live entries it held before.
+    ///
+    /// `contents` is read to the end before the table is touched, so a slow
+    /// source never leaves the namespace half reloaded. Keys missing from
+    /// `contents` are removed, as by [`remove`](Self::remove), and the rest
+    /// written as by [`insert`](Self::insert), with the default TTL. Since
+    /// the swap needs `&mut` access, no reader can see the namespace
+    /// between the old and the new set. When the table sits behind a lock,
+    /// build the contents before taking it, so readers only wait for the
+    /// swap itself.
+    pub fn swap<I, K, V>(&mut self, contents: I) -> usize
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: AsRef<str>,
+        V: AsRef<str>,
+    {
+        let staged: Vec<(String, V)> = contents
+            .into_iter()
+            .map(|(key, value)| (self.full_key(key.as_ref()), value))
+            .collect();
+        let incoming: HashSet<&str> = staged.iter().map(|(key, _)| key.as_str()).collect();
+        let mut stale: Vec<String> = self
+            .table
+            .keys_raw()
+            .filter(|key| key.starts_with(self.prefix.as_str()) && !incoming.contains(key.as_str()))
+            .cloned()
+            .collect();
+        self.table.core.sort_for_walk(&mut stale, String::as_str);
+        let replaced = self.size();
+
+        for key in &stale {
+            self.table.remove(key);
+        }
+        self.table.reserve_capacity(staged.len());
+        for (key, value) in &staged {
+            self.table.insert(key, value.as_ref());
+        }
+        replaced
+    }
+}
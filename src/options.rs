@@ -0,0 +1,74 @@
+use core::time::Duration;
+
+/// A latency budget for one lookup, passed to `get_with()`.
+///
+/// The default asks for nothing special, so `get_with(key,
+/// &GetOptions::default())` behaves like `get(key)`. Latency-critical paths
+/// set the fields to give up correctness or freshness they can live
+/// without rather than wait for data that isn't in memory.
+///
+/// # Examples
+///
+/// ```
+/// use spectra_cache::clock::ManualClock;
+/// use spectra_cache::{DistributedHashTable, GetOptions};
+/// use std::time::Duration;
+///
+/// let clock = ManualClock::new();
+/// let mut cache = DistributedHashTable::builder().clock(clock.clone()).build();
+/// cache.insert_with_ttl("quote:EUR", "1.08", Duration::from_millis(10));
+/// clock.advance(Duration::from_millis(20));
+///
+/// let budget = GetOptions { allow_stale: true, ..GetOptions::default() };
+/// assert_eq!(cache.get_with("quote:EUR", &budget), Some("1.08"));
+/// assert_eq!(cache.get("quote:EUR"), None);
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct GetOptions {
+    /// How long the lookup may wait for a value that isn't in memory.
+    ///
+    /// With a budget, a miss never reads through to the backing store,
+    /// whose calls can't be cut short, and a `LoadingCache` waits at most
+    /// this long for another caller's load and doesn't start one itself.
+    /// `Some(Duration::ZERO)` means a miss is returned at once. `None`
+    /// waits as long as it takes.
+    pub max_wait: Option<Duration>,
+    /// Returns an expired entry that hasn't been removed yet instead of a
+    /// miss. The entry is left in place, not refreshed, and counted as a
+    /// hit.
+    pub allow_stale: bool,
+    /// Looks the key up in the map directly, without asking the Bloom
+    /// filter first. Saves the filter's hashing when the key is known to
+    /// be present most of the time.
+    pub skip_bloom: bool,
+}
+
+impl GetOptions {
+    /// Returns true if the lookup may read through to the backing store.
+    pub(crate) fn may_read_through(&self) -> bool {
+        self.max_wait.is_none()
+    }
+}
+
+/// A value returned by `get_stale()`, flagged if it is older than the
+/// cache's soft TTL.
+///
+/// A stale value is still valid to serve, since its hard TTL hasn't run out,
+/// but is due for a refresh.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MaybeStale<T> {
+    /// The cached value
+    pub value: T,
+    /// True if the value was written longer ago than the soft TTL
+    pub stale: bool,
+}
+
+impl<T> MaybeStale<T> {
+    #[cfg(feature = "std")]
+    pub(crate) fn map<U>(self, f: impl FnOnce(T) -> U) -> MaybeStale<U> {
+        MaybeStale {
+            value: f(self.value),
+            stale: self.stale,
+        }
+    }
+}
@@ -0,0 +1,83 @@
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::mem;
+use core::sync::atomic::Ordering;
+
+use crate::clock::Instant;
+use crate::collections::HashMap;
+use crate::sync::{AtomicU64, Lock};
+
+/// Reads made through `&self`, waiting for the next call with `&mut self`
+/// to apply them.
+///
+/// A read through a shared reference can't move the entry up the recency
+/// order, restart its idle clock or count as a hit by itself, so it notes
+/// here which key it read and when. Only the latest read of each key is
+/// kept, along with how many there were, so the buffer holds at most one
+/// slot per key however many reads happen between writes.
+///
+/// Readers on different threads can share the cache, so misses are counted
+/// with an atomic and hits go through a `Lock` held just long enough to
+/// update one slot. Applying the buffer takes `&mut self` and never locks.
+#[derive(Debug, Default)]
+pub(crate) struct ReadBuffer {
+    hits: Lock<HashMap<String, PendingHit>>,
+    misses: AtomicU64,
+}
+
+/// The reads of one key since the buffer was last applied.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct PendingHit {
+    /// When the key was last read.
+    pub(crate) at: Instant,
+    pub(crate) count: u64,
+}
+
+impl ReadBuffer {
+    /// Notes a read of `key` at `at` on the cache's clock.
+    pub(crate) fn hit(&self, key: &str, at: Instant) {
+        let mut hits = self.hits.lock();
+        match hits.get_mut(key) {
+            Some(pending) => {
+                pending.at = at;
+                pending.count += 1;
+            }
+            None => {
+                hits.insert(key.to_string(), PendingHit { at, count: 1 });
+            }
+        }
+    }
+
+    /// Returns when `key` was last read, if that read hasn't been applied.
+    pub(crate) fn last_read(&self, key: &str) -> Option<Instant> {
+        self.hits.lock().get(key).map(|pending| pending.at)
+    }
+
+    /// Returns a copy of the reads not applied yet, by key.
+    pub(crate) fn pending_hits(&self) -> HashMap<String, PendingHit> {
+        self.hits.lock().clone()
+    }
+
+    pub(crate) fn miss(&self) {
+        self.misses.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Returns how many hits and misses haven't been applied yet.
+    pub(crate) fn pending(&self) -> (u64, u64) {
+        let hits = self.hits.lock().values().map(|pending| pending.count).sum();
+        (hits, self.misses.load(Ordering::Relaxed))
+    }
+
+    /// Empties the buffer, returning the keys read from the least to the
+    /// most recently read, and the number of misses.
+    pub(crate) fn take(&mut self) -> (Vec<(String, PendingHit)>, u64) {
+        let misses = mem::take(self.misses.get_mut());
+        let hits = self.hits.get_mut();
+        if hits.is_empty() {
+            return (Vec::new(), misses);
+        }
+        let mut hits: Vec<_> = hits.drain().collect();
+        hits.sort_by_key(|(_, pending)| pending.at);
+        (hits, misses)
+    }
+}
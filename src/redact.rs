@@ -0,0 +1,119 @@
+use alloc::borrow::Cow;
+use alloc::string::{String, ToString};
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::fmt;
+
+/// What a masked value is shown as.
+pub const REDACTED: &str = "[REDACTED]";
+
+type Rule = Arc<dyn Fn(&str) -> String + Send + Sync>;
+
+/// Per-prefix rules rewriting cached values before they are shown to
+/// people, so debugging output doesn't leak the secrets a cache holds.
+///
+/// A cache built with [`CacheBuilder::redact`](crate::CacheBuilder::redact)
+/// applies its rules to its `Debug` output and to `history()`, and
+/// `SnapshotDiff::redact()` applies them to
+/// snapshot diffs. Data meant to be read back, such as `dump()` payloads,
+/// snapshots and the append-only log, is never redacted.
+///
+/// When several prefixes match a key, the longest one wins.
+///
+/// # Examples
+///
+/// ```
+/// use spectra_cache::Redactor;
+///
+/// let redactor = Redactor::new()
+///     .mask("token:")
+///     .mask_all_but_last("card:", 4)
+///     .rule("user:", |value| value.split('@').next().unwrap_or_default().to_string() + "@…");
+///
+/// assert_eq!(redactor.redact("token:42", "eyJhbGciOi"), "[REDACTED]");
+/// assert_eq!(redactor.redact("card:7", "4111111111111111"), "************1111");
+/// assert_eq!(redactor.redact("user:1", "ana@example.com"), "ana@…");
+/// assert_eq!(redactor.redact("page:home", "<html>"), "<html>");
+/// ```
+#[derive(Clone, Default)]
+pub struct Redactor {
+    rules: Vec<(String, Rule)>,
+}
+
+impl Redactor {
+    /// Creates a redactor without rules, which shows every value as it is.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Shows the values of keys starting with `prefix` as [`REDACTED`].
+    pub fn mask(self, prefix: impl Into<String>) -> Self {
+        self.rule(prefix, |_| REDACTED.to_string())
+    }
+
+    /// Shows only the last `visible` characters of the values of keys
+    /// starting with `prefix`, replacing the others with `*`.
+    pub fn mask_all_but_last(self, prefix: impl Into<String>, visible: usize) -> Self {
+        self.rule(prefix, move |value| {
+            let hidden = value.chars().count().saturating_sub(visible);
+            value.chars().enumerate().map(|(i, c)| if i < hidden { '*' } else { c }).collect()
+        })
+    }
+
+    /// Shows the values of keys starting with `prefix` as `rewrite` makes them.
+    pub fn rule(mut self, prefix: impl Into<String>, rewrite: impl Fn(&str) -> String + Send + Sync + 'static) -> Self {
+        self.rules.push((prefix.into(), Arc::new(rewrite)));
+        self
+    }
+
+    /// Returns true if there are no rules.
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Returns `value` as it may be shown when stored under `key`.
+    pub fn redact<'a>(&self, key: &str, value: &'a str) -> Cow<'a, str> {
+        match self.rule_for(key) {
+            Some(rewrite) => Cow::Owned(rewrite(value)),
+            None => Cow::Borrowed(value),
+        }
+    }
+
+    /// Returns the rule of the longest prefix of `key`, if any.
+    pub(crate) fn rule_for(&self, key: &str) -> Option<&Rule> {
+        self.rules
+            .iter()
+            .filter(|(prefix, _)| key.starts_with(prefix.as_str()))
+            .max_by_key(|(prefix, _)| prefix.len())
+            .map(|(_, rule)| rule)
+    }
+}
+
+impl fmt::Debug for Redactor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let prefixes: Vec<&str> = self.rules.iter().map(|(prefix, _)| prefix.as_str()).collect();
+        f.debug_struct("Redactor").field("prefixes", &prefixes).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_longest_prefix_wins() {
+        let redactor = Redactor::new().mask("user:").rule("user:public:", str::to_uppercase);
+        assert_eq!(redactor.redact("user:1", "ana"), REDACTED);
+        assert_eq!(redactor.redact("user:public:1", "ana"), "ANA");
+        assert_eq!(redactor.redact("users", "ana"), "ana");
+        assert!(matches!(redactor.redact("other", "ana"), Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn test_mask_all_but_last_counts_characters() {
+        let redactor = Redactor::new().mask_all_but_last("", 2);
+        assert_eq!(redactor.redact("k", "ação"), "**ão");
+        assert_eq!(redactor.redact("k", "a"), "a");
+        assert_eq!(redactor.redact("k", ""), "");
+    }
+}
@@ -0,0 +1,158 @@
+use std::collections::{HashMap, VecDeque};
+
+/// Remembers the outcome of recently applied writes, keyed by a
+/// client-supplied write ID, so retried commands are applied only once.
+///
+/// A client that times out waiting for a reply can't tell whether its write
+/// was applied. By attaching a unique write ID and retrying with the same ID,
+/// the retry is recognised as a duplicate and answered with the original
+/// result instead of being applied twice. Only the most recent `capacity`
+/// IDs are remembered; retries older than that window are treated as new.
+///
+/// The RESP server's `WRITEID` command and the gRPC service's `write-id`
+/// request metadata are built on a guard like this one.
+///
+/// # Examples
+///
+/// ```
+/// use spectra_cache::{DistributedHashTable, ReplayGuard};
+///
+/// let mut cache = DistributedHashTable::new();
+/// let mut guard = ReplayGuard::new(1024);
+///
+/// let first = guard.execute("req-1", || cache.remove("missing").is_some());
+/// let retry = guard.execute("req-1", || unreachable!());
+/// assert_eq!(first, retry);
+/// ```
+#[derive(Debug)]
+pub struct ReplayGuard<R> {
+    capacity: usize,
+    results: HashMap<String, R>,
+    order: VecDeque<String>,
+}
+
+impl<R: Clone> ReplayGuard<R> {
+    /// Creates a guard remembering up to `capacity` write IDs.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is zero.
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "ReplayGuard capacity must be greater than zero");
+        Self {
+            capacity,
+            results: HashMap::new(),
+            order: VecDeque::new(),
+        }
+    }
+
+    /// Runs `operation` unless `write_id` was already seen.
+    ///
+    /// Returns the result of `operation` for a new ID, or a copy of the
+    /// original result for a duplicate.
+    ///
+    /// # Arguments
+    ///
+    /// * `write_id` - The client-supplied unique ID of this write
+    /// * `operation` - The mutation to apply
+    pub fn execute(&mut self, write_id: &str, operation: impl FnOnce() -> R) -> R {
+        if let Some(result) = self.results.get(write_id) {
+            return result.clone();
+        }
+
+        let result = operation();
+        self.remember(write_id, result.clone());
+        result
+    }
+
+    /// Returns the recorded result for `write_id`, if it is still in the window.
+    pub fn result(&self, write_id: &str) -> Option<&R> {
+        self.results.get(write_id)
+    }
+
+    /// Returns true if `write_id` has been applied and is still remembered.
+    pub fn contains(&self, write_id: &str) -> bool {
+        self.results.contains_key(write_id)
+    }
+
+    /// Returns the number of remembered write IDs.
+    pub fn len(&self) -> usize {
+        self.results.len()
+    }
+
+    /// Returns true if no write IDs are remembered.
+    pub fn is_empty(&self) -> bool {
+        self.results.is_empty()
+    }
+
+    /// Returns the maximum number of remembered write IDs.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Forgets every remembered write ID.
+    pub fn clear(&mut self) {
+        self.results.clear();
+        self.order.clear();
+    }
+
+    fn remember(&mut self, write_id: &str, result: R) {
+        if self.order.len() == self.capacity {
+            if let Some(oldest) = self.order.pop_front() {
+                self.results.remove(&oldest);
+            }
+        }
+        self.order.push_back(write_id.to_string());
+        self.results.insert(write_id.to_string(), result);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_duplicate_returns_original_result() {
+        let mut guard = ReplayGuard::new(4);
+        let mut applied = 0;
+
+        let first = guard.execute("w1", || {
+            applied += 1;
+            applied
+        });
+        let retry = guard.execute("w1", || {
+            applied += 1;
+            applied
+        });
+
+        assert_eq!(first, 1);
+        assert_eq!(retry, 1);
+        assert_eq!(applied, 1);
+        assert_eq!(guard.result("w1"), Some(&1));
+    }
+
+    #[test]
+    fn test_window_is_bounded() {
+        let mut guard = ReplayGuard::new(2);
+        guard.execute("w1", || "a");
+        guard.execute("w2", || "b");
+        guard.execute("w3", || "c");
+
+        assert_eq!(guard.len(), 2);
+        assert!(!guard.contains("w1"));
+        assert!(guard.contains("w2"));
+        assert!(guard.contains("w3"));
+
+        // Fora da janela o ID volta a ser tratado como novo
+        assert_eq!(guard.execute("w1", || "again"), "again");
+    }
+
+    #[test]
+    fn test_clear() {
+        let mut guard = ReplayGuard::new(2);
+        guard.execute("w1", || ());
+        guard.clear();
+        assert!(guard.is_empty());
+        assert_eq!(guard.capacity(), 2);
+    }
+}
@@ -0,0 +1,116 @@
+//! `serde` support for the caches, enabled by the `serde` feature.
+//!
+//! A cache serializes as a map from key to
+//! `{ "value": ..., "expires_at": ..., "idle_timeout": ... }`, holding only
+//! its live entries. The TTL is written as the absolute `SystemTime` the
+//! entry expires at, so a cache loaded later or in another process keeps
+//! the original deadlines; entries whose deadline passed in the meantime
+//! are skipped on load. Configuration, statistics, listeners and backing
+//! stores are not part of the contents: a deserialized cache has default
+//! settings.
+
+use std::collections::BTreeMap;
+use std::hash::BuildHasher;
+use std::time::Duration;
+
+use serde::ser::SerializeMap;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::clock::SystemTime;
+use crate::core::{CacheCore, EntryMap};
+use crate::entry::CacheEntry;
+use crate::value::CacheValue;
+use crate::{BTreeCache, BytesCache, DistributedHashTable};
+
+#[derive(Serialize, Deserialize)]
+struct SerializedEntry<V> {
+    value: V,
+    expires_at: Option<SystemTime>,
+    idle_timeout: Option<Duration>,
+}
+
+impl<M: EntryMap> CacheCore<M>
+where
+    M::Value: Serialize,
+{
+    fn serialize_entries<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let (now, wall_now) = (self.now(), SystemTime::now());
+        let live = || self.entries.iter().filter(|(_, entry)| !entry.is_expired(now));
+
+        let mut map = serializer.serialize_map(Some(live().count()))?;
+        for (key, entry) in live() {
+            let expires_at = entry
+                .ttl_deadline()
+                .map(|deadline| wall_now + deadline.saturating_duration_since(now));
+            map.serialize_entry(
+                key,
+                &SerializedEntry {
+                    value: &entry.value,
+                    expires_at,
+                    idle_timeout: entry.idle_timeout(),
+                },
+            )?;
+        }
+        map.end()
+    }
+}
+
+impl<M: EntryMap> CacheCore<M> {
+    fn deserialize_entries<'de, D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+        M::Value: Deserialize<'de>,
+    {
+        let entries = BTreeMap::<String, SerializedEntry<M::Value>>::deserialize(deserializer)?;
+        let now = SystemTime::now();
+        let mut core = Self::new();
+        for (key, serialized) in entries {
+            let ttl = match serialized.expires_at.map(|expires_at| expires_at.duration_since(now)) {
+                // Expirou depois de serializado
+                Some(Err(_)) => continue,
+                Some(Ok(ttl)) => Some(ttl),
+                None => None,
+            };
+            let mut entry = CacheEntry::with_ttl(&key, serialized.value.view(), ttl, core.now());
+            entry.set_idle_timeout(serialized.idle_timeout);
+            core.insert_entry(&key, entry);
+        }
+        Ok(core)
+    }
+}
+
+impl<H: BuildHasher + Clone + Default> Serialize for DistributedHashTable<H> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.core.serialize_entries(serializer)
+    }
+}
+
+impl<'de, H: BuildHasher + Clone + Default> Deserialize<'de> for DistributedHashTable<H> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        CacheCore::deserialize_entries(deserializer).map(|core| Self { core })
+    }
+}
+
+impl Serialize for BTreeCache {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.core.serialize_entries(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for BTreeCache {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        CacheCore::deserialize_entries(deserializer).map(|core| Self { core })
+    }
+}
+
+impl Serialize for BytesCache {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.core.serialize_entries(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for BytesCache {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        CacheCore::deserialize_entries(deserializer).map(|core| Self { core })
+    }
+}
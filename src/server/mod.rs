@@ -0,0 +1,439 @@
+//! A RESP2 (Redis protocol) front end for `DistributedHashTable`.
+
+mod resp;
+
+use std::io::{self, BufReader, BufWriter, Write};
+use std::mem;
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::ops::{Deref, DerefMut};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::thread;
+use std::time::Duration;
+
+use crate::glob::Glob;
+use crate::{AuditContext, CacheError, DistributedHashTable, ReplayGuard};
+use resp::Reply;
+
+/// How many recent write IDs the server remembers; see `WRITEID`.
+const WRITE_ID_WINDOW: usize = 100_000;
+
+/// Serves a `DistributedHashTable` to Redis clients over TCP.
+///
+/// Speaks RESP2, so `redis-cli` and the Redis client libraries of other
+/// languages can use the cache directly. Supported commands:
+///
+/// | Command | Notes |
+/// |---------|-------|
+/// | `GET key` | |
+/// | `SET key value [EX seconds \| PX milliseconds]` | |
+/// | `SETEX key seconds value` | |
+/// | `DEL key [key ...]` | Replies with the number of removed keys |
+/// | `EXISTS key [key ...]` | Replies with the number of live keys |
+/// | `TTL key` | `-2` if missing, `-1` without expiry, else seconds left |
+/// | `EXPIRE key seconds` | `1` if the key exists |
+/// | `PERSIST key` | `1` if an expiry was removed |
+/// | `KEYS pattern` | Glob patterns, see `keys_matching` |
+/// | `SELECT index` | Switches the connection to another logical database |
+/// | `DBSIZE` | Number of entries in the selected database |
+/// | `FLUSHDB`, `FLUSHALL` | Clear the selected database, or all of them |
+/// | `INFO` | The selected table's `stats()` as JSON |
+/// | `CONFIG GET pattern` | Name/value pairs of the selected table's [`config()`](DistributedHashTable::config), `databases` and `read-only` |
+/// | `CONFIG SET read-only yes\|no` | See [`set_read_only`](Self::set_read_only) |
+/// | `PING [message]`, `QUIT` | |
+/// | `WRITEID id command [arg ...]` | Runs a write command at most once per `id`; see below |
+///
+/// A client that timed out waiting for a reply can't tell whether its write
+/// was applied. Prefixing a write command with `WRITEID` and a unique ID,
+/// e.g. `WRITEID 7f3a-1 SET key value`, makes retrying it safe: a command
+/// whose ID was already seen is not applied again and gets the reply of
+/// the original, even on another connection. The server remembers the most
+/// recent 100 000 IDs across all databases through a [`ReplayGuard`]; older
+/// retries are applied as new writes. Writes refused with `READONLY` or
+/// `MISCONF` are not remembered, so they can be retried once the node
+/// takes writes again.
+///
+/// The server can hold several logical databases, numbered from 0, each a
+/// separate table with its own keys, settings and statistics; see
+/// [`with_databases`](Self::with_databases). Every connection starts on
+/// database 0 and switches with `SELECT`, like Redis.
+///
+/// Keys and values must be valid UTF-8. Each connection is handled on its
+/// own thread; commands are applied one at a time under a per-database lock.
+/// Audited operations such as `FLUSHDB` carry the client's address in their
+/// [`AuditContext`], along with the actor set on the table, if any. A
+/// database that rejects writes while its persistence is failing answers
+/// the mutating commands with a `MISCONF` error, like Redis.
+///
+/// # Examples
+///
+/// ```no_run
+/// use spectra_cache::{DistributedHashTable, RespServer};
+/// use std::net::TcpListener;
+///
+/// let server = RespServer::new(DistributedHashTable::new());
+/// server.serve(TcpListener::bind("127.0.0.1:6379")?)?;
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct RespServer {
+    databases: Arc<Vec<Mutex<DistributedHashTable>>>,
+    read_only: Arc<AtomicBool>,
+    write_ids: Arc<Mutex<ReplayGuard<Reply>>>,
+}
+
+impl RespServer {
+    /// Creates a server exposing `table` as its only database.
+    pub fn new(table: DistributedHashTable) -> Self {
+        Self::with_databases(vec![table])
+    }
+
+    /// Creates a server exposing each table as a logical database, numbered
+    /// in order from 0.
+    ///
+    /// The databases are fully isolated: `FLUSHDB` clears only the selected
+    /// one, and `INFO` and `CONFIG GET` report its own statistics and
+    /// settings, so e.g. staging and production data can share a process.
+    ///
+    /// Panics if `tables` is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use spectra_cache::{DistributedHashTable, RespServer};
+    /// use std::net::TcpListener;
+    ///
+    /// let tables = (0..16).map(|_| DistributedHashTable::new()).collect();
+    /// let server = RespServer::with_databases(tables);
+    /// server.serve(TcpListener::bind("127.0.0.1:6379")?)?;
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn with_databases(tables: Vec<DistributedHashTable>) -> Self {
+        assert!(!tables.is_empty(), "a server needs at least one database");
+        Self {
+            databases: Arc::new(tables.into_iter().map(Mutex::new).collect()),
+            read_only: Arc::new(AtomicBool::new(false)),
+            write_ids: Arc::new(Mutex::new(ReplayGuard::new(WRITE_ID_WINDOW))),
+        }
+    }
+
+    /// Returns the number of logical databases.
+    pub fn database_count(&self) -> usize {
+        self.databases.len()
+    }
+
+    /// Puts the node into or out of read-only mode.
+    ///
+    /// While read-only, reads keep working but every mutating command
+    /// (`SET`, `SETEX`, `DEL`, `EXPIRE`, `PERSIST`, `FLUSHDB`, `FLUSHALL`) is rejected with a
+    /// `READONLY` error built from [`CacheError::ReadOnly`]. Meant for
+    /// migrations, incident response, or a demoted ex-primary. The switch
+    /// applies to every connection at once, including ones already open,
+    /// and can also be flipped by clients with `CONFIG SET read-only yes`.
+    /// Every database audits the switch as a `read_only` config change.
+    pub fn set_read_only(&self, read_only: bool) {
+        self.switch_read_only(read_only, None);
+    }
+
+    /// Returns true if the node rejects mutations.
+    pub fn is_read_only(&self) -> bool {
+        self.read_only.load(Ordering::SeqCst)
+    }
+
+    /// Accepts connections on `listener` forever, one thread per client.
+    ///
+    /// Only returns if accepting a connection fails.
+    pub fn serve(&self, listener: TcpListener) -> io::Result<()> {
+        loop {
+            let (stream, _) = listener.accept()?;
+            let server = self.clone();
+            thread::spawn(move || {
+                // Um cliente que some no meio de um comando só derruba a própria conexão
+                let _ = server.handle_connection(stream);
+            });
+        }
+    }
+
+    /// Serves a single client until it disconnects or sends `QUIT`.
+    pub fn handle_connection(&self, stream: TcpStream) -> io::Result<()> {
+        let mut reader = BufReader::new(stream.try_clone()?);
+        let mut session = Session {
+            db: 0,
+            client_addr: stream.peer_addr().ok(),
+        };
+        let mut writer = BufWriter::new(stream);
+        while let Some(args) = resp::read_command(&mut reader)? {
+            let quit = args.first().is_some_and(|name| name.eq_ignore_ascii_case(b"QUIT"));
+            let reply = if quit { Reply::ok() } else { self.execute(&args, &mut session) };
+            reply.write_to(&mut writer)?;
+            // Só descarrega quando não há mais comandos em pipeline
+            if quit || reader.buffer().is_empty() {
+                writer.flush()?;
+            }
+            if quit {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// Locks database `index` on behalf of the client at `client_addr`, or
+    /// of the table's own audit context when there is no client.
+    fn database(&self, index: usize, client_addr: Option<SocketAddr>) -> ClientTable<'_> {
+        let mut table = self.databases[index].lock().unwrap_or_else(PoisonError::into_inner);
+        let previous = table.audit_context().clone();
+        table.set_audit_context(AuditContext {
+            client_addr: client_addr.or(previous.client_addr),
+            ..previous.clone()
+        });
+        ClientTable { table, previous }
+    }
+
+    fn switch_read_only(&self, read_only: bool, client_addr: Option<SocketAddr>) {
+        self.read_only.store(read_only, Ordering::SeqCst);
+        let value = if read_only { "on" } else { "off" };
+        for index in 0..self.databases.len() {
+            self.database(index, client_addr).core.record_config_change("read_only", value);
+        }
+    }
+
+    /// Runs one command for a connection.
+    fn execute(&self, args: &[Vec<u8>], session: &mut Session) -> Reply {
+        let args: Vec<&str> = match args.iter().map(|arg| std::str::from_utf8(arg)).collect() {
+            Ok(args) => args,
+            Err(_) => return Reply::error("ERR keys and values must be valid UTF-8"),
+        };
+        let Some((name, rest)) = args.split_first() else {
+            return Reply::error("ERR empty command");
+        };
+        if !name.eq_ignore_ascii_case("WRITEID") {
+            return match self.check_writable(name, session) {
+                Ok(()) => self.dispatch(name, rest, session),
+                Err(reply) => reply,
+            };
+        }
+
+        let [write_id, name, args @ ..] = rest else {
+            return Reply::error("ERR wrong number of arguments for 'writeid' command");
+        };
+        if !mutates(name) {
+            return Reply::error("ERR WRITEID only applies to write commands");
+        }
+        if let Err(reply) = self.check_writable(name, session) {
+            return reply;
+        }
+        // O guarda fica travado durante a escrita: uma repetição concorrente espera pelo original
+        let mut write_ids = self.write_ids.lock().unwrap_or_else(PoisonError::into_inner);
+        write_ids.execute(write_id, || self.dispatch(name, args, session))
+    }
+
+    /// Refuses the write command `name` with `READONLY` or `MISCONF` if the
+    /// node or the databases it writes to don't take writes.
+    fn check_writable(&self, name: &str, session: &Session) -> Result<(), Reply> {
+        if !mutates(name) {
+            return Ok(());
+        }
+        if self.is_read_only() {
+            return Err(Reply::error(format!("READONLY {}", CacheError::ReadOnly)));
+        }
+        #[cfg(feature = "persistence")]
+        {
+            // FLUSHALL escreve em todos os bancos
+            let mut databases = match name.to_ascii_uppercase().as_str() {
+                "FLUSHALL" => 0..self.databases.len(),
+                _ => session.db..session.db + 1,
+            };
+            if let Some(error) = databases.find_map(|index| self.database(index, None).check_writable().err()) {
+                return Err(Reply::error(format!("MISCONF {}", error)));
+            }
+        }
+        #[cfg(not(feature = "persistence"))]
+        let _ = session;
+        Ok(())
+    }
+
+    /// Applies the command `name` to the connection's database.
+    fn dispatch(&self, name: &str, args: &[&str], session: &mut Session) -> Reply {
+        let name = name.to_ascii_uppercase();
+        let (db, client_addr) = (session.db, session.client_addr);
+        let table = || self.database(db, client_addr);
+        match (name.as_str(), args) {
+            ("PING", []) => Reply::Simple("PONG"),
+            ("PING", [message]) => Reply::bulk(*message),
+            ("GET", [key]) => Reply::Bulk(table().get(key).map(|value| value.into())),
+            ("SET", [key, value, options @ ..]) => match parse_set_ttl(options) {
+                Ok(ttl) => set(table(), key, value, ttl),
+                Err(reply) => reply,
+            },
+            ("SETEX", [key, seconds, value]) => match parse_positive(seconds) {
+                Some(seconds) => set(table(), key, value, Some(Duration::from_secs(seconds))),
+                None => invalid_expire_time(),
+            },
+            ("DEL", keys @ [_, ..]) => {
+                let mut table = table();
+                count(keys.iter().filter(|key| table.remove(key).is_some()))
+            }
+            ("EXISTS", keys @ [_, ..]) => {
+                let table = table();
+                count(keys.iter().filter(|key| table.contains_key(key)))
+            }
+            ("TTL", [key]) => {
+                let table = table();
+                Reply::Integer(match table.ttl(key) {
+                    // Arredonda para cima como o Redis: 0 só quando já expirou
+                    Some(ttl) => ttl.as_millis().div_ceil(1000) as i64,
+                    None if table.contains_key(key) => -1,
+                    None => -2,
+                })
+            }
+            ("EXPIRE", [key, seconds]) => match parse_positive(seconds) {
+                Some(seconds) => flag(table().expire(key, Duration::from_secs(seconds))),
+                None => invalid_expire_time(),
+            },
+            ("PERSIST", [key]) => flag(table().persist(key)),
+            ("KEYS", [pattern]) => {
+                let table = table();
+                let mut keys: Vec<&String> = table.keys_matching(pattern).collect();
+                keys.sort();
+                Reply::Array(keys.into_iter().map(|key| Reply::bulk(key.as_str())).collect())
+            }
+            ("SELECT", [index]) => match index.parse::<usize>() {
+                Ok(index) if index < self.databases.len() => {
+                    session.db = index;
+                    Reply::ok()
+                }
+                Ok(_) => Reply::error("ERR DB index is out of range"),
+                Err(_) => Reply::error("ERR value is not an integer or out of range"),
+            },
+            ("DBSIZE", []) => Reply::Integer(table().size() as i64),
+            ("FLUSHDB", []) => {
+                table().clear();
+                Reply::ok()
+            }
+            ("FLUSHALL", []) => {
+                for index in 0..self.databases.len() {
+                    self.database(index, client_addr).clear();
+                }
+                Reply::ok()
+            }
+            ("INFO", [] | [_]) => Reply::bulk(table().stats().to_json()),
+            ("CONFIG", [action, rest @ ..]) => match (action.to_ascii_uppercase().as_str(), rest) {
+                ("GET", [pattern]) => self.config_get(pattern, &table()),
+                ("SET", [setting, value]) if setting.eq_ignore_ascii_case("read-only") => {
+                    if value.eq_ignore_ascii_case("yes") || value.eq_ignore_ascii_case("no") {
+                        self.switch_read_only(value.eq_ignore_ascii_case("yes"), client_addr);
+                        Reply::ok()
+                    } else {
+                        Reply::error("ERR syntax error")
+                    }
+                }
+                ("SET", [_, _]) => Reply::error("ERR unsupported CONFIG parameter"),
+                _ => Reply::error("ERR syntax error"),
+            },
+            (
+                "PING" | "GET" | "SET" | "SETEX" | "DEL" | "EXISTS" | "TTL" | "EXPIRE" | "PERSIST" | "KEYS"
+                | "SELECT" | "DBSIZE" | "FLUSHDB" | "FLUSHALL" | "INFO" | "CONFIG",
+                _,
+            ) => Reply::error(format!("ERR wrong number of arguments for '{}' command", name.to_ascii_lowercase())),
+            _ => Reply::error(format!("ERR unknown command '{}'", name)),
+        }
+    }
+
+    /// Replies with the name and value of every setting matching `pattern`,
+    /// flattened into one array as Redis does.
+    fn config_get(&self, pattern: &str, table: &DistributedHashTable) -> Reply {
+        let glob = Glob::new(&pattern.to_ascii_lowercase());
+        let read_only = if self.is_read_only() { "yes" } else { "no" };
+        let mut settings = table.config().settings();
+        settings.push(("databases", self.databases.len().to_string()));
+        settings.push(("read-only", read_only.to_string()));
+        Reply::Array(
+            settings
+                .into_iter()
+                .filter(|(name, _)| glob.matches(name))
+                .flat_map(|(name, value)| [Reply::bulk(name), Reply::bulk(value)])
+                .collect(),
+        )
+    }
+}
+
+/// What the server keeps about one client connection.
+struct Session {
+    /// The selected logical database.
+    db: usize,
+    client_addr: Option<SocketAddr>,
+}
+
+/// A locked database whose audited operations are attributed to one client.
+/// The table gets its own audit context back when the guard drops.
+struct ClientTable<'a> {
+    table: MutexGuard<'a, DistributedHashTable>,
+    previous: AuditContext,
+}
+
+impl Deref for ClientTable<'_> {
+    type Target = DistributedHashTable;
+
+    fn deref(&self) -> &DistributedHashTable {
+        &self.table
+    }
+}
+
+impl DerefMut for ClientTable<'_> {
+    fn deref_mut(&mut self) -> &mut DistributedHashTable {
+        &mut self.table
+    }
+}
+
+impl Drop for ClientTable<'_> {
+    fn drop(&mut self) {
+        let previous = mem::take(&mut self.previous);
+        self.table.set_audit_context(previous);
+    }
+}
+
+/// Returns true if the command `name` changes the data.
+fn mutates(name: &str) -> bool {
+    ["SET", "SETEX", "DEL", "EXPIRE", "PERSIST", "FLUSHDB", "FLUSHALL"]
+        .iter()
+        .any(|command| name.eq_ignore_ascii_case(command))
+}
+
+fn set(mut table: ClientTable<'_>, key: &str, value: &str, ttl: Option<Duration>) -> Reply {
+    match ttl {
+        Some(ttl) => table.insert_with_ttl(key, value, ttl),
+        None => table.insert(key, value),
+    }
+    Reply::ok()
+}
+
+fn parse_set_ttl(options: &[&str]) -> Result<Option<Duration>, Reply> {
+    match options {
+        [] => Ok(None),
+        [unit, amount] => {
+            let amount = parse_positive(amount).ok_or_else(invalid_expire_time)?;
+            match unit.to_ascii_uppercase().as_str() {
+                "EX" => Ok(Some(Duration::from_secs(amount))),
+                "PX" => Ok(Some(Duration::from_millis(amount))),
+                _ => Err(Reply::error("ERR syntax error")),
+            }
+        }
+        _ => Err(Reply::error("ERR syntax error")),
+    }
+}
+
+fn parse_positive(value: &str) -> Option<u64> {
+    value.parse().ok().filter(|&value| value > 0)
+}
+
+fn invalid_expire_time() -> Reply {
+    Reply::error("ERR invalid expire time")
+}
+
+fn count<T>(items: impl Iterator<Item = T>) -> Reply {
+    Reply::Integer(items.count() as i64)
+}
+
+fn flag(value: bool) -> Reply {
+    Reply::Integer(value as i64)
+}
@@ -0,0 +1,177 @@
+//! RESP2 framing: reading client commands and writing replies.
+//!
+//! Clients send every command as an array of bulk strings. Inline commands
+//! (space-separated words on one line, as typed into `telnet`) are
+//! accepted too.
+
+use std::io::{self, BufRead, Read, Write};
+
+/// Largest bulk string a client may send, the same limit Redis uses.
+const MAX_BULK_LEN: usize = 512 * 1024 * 1024;
+
+/// Largest number of arguments in a single command.
+const MAX_ARGS: usize = 1024 * 1024;
+
+/// Most arguments room is reserved for before they arrive; longer commands
+/// grow the vector as they are read.
+const ARGS_CAPACITY_HINT: usize = 64;
+
+/// A reply to a client command.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Reply {
+    Simple(&'static str),
+    Error(String),
+    Integer(i64),
+    Bulk(Option<Vec<u8>>),
+    Array(Vec<Reply>),
+}
+
+impl Reply {
+    pub(crate) fn ok() -> Self {
+        Reply::Simple("OK")
+    }
+
+    pub(crate) fn error(message: impl Into<String>) -> Self {
+        Reply::Error(message.into())
+    }
+
+    pub(crate) fn bulk(value: impl Into<Vec<u8>>) -> Self {
+        Reply::Bulk(Some(value.into()))
+    }
+
+    pub(crate) fn write_to(&self, writer: &mut impl Write) -> io::Result<()> {
+        match self {
+            Reply::Simple(message) => write!(writer, "+{}\r\n", message),
+            Reply::Error(message) => write!(writer, "-{}\r\n", message),
+            Reply::Integer(value) => write!(writer, ":{}\r\n", value),
+            Reply::Bulk(None) => writer.write_all(b"$-1\r\n"),
+            Reply::Bulk(Some(value)) => {
+                write!(writer, "${}\r\n", value.len())?;
+                writer.write_all(value)?;
+                writer.write_all(b"\r\n")
+            }
+            Reply::Array(items) => {
+                write!(writer, "*{}\r\n", items.len())?;
+                items.iter().try_for_each(|item| item.write_to(writer))
+            }
+        }
+    }
+}
+
+/// Reads the next command, or `None` once the client closed the connection.
+pub(crate) fn read_command(reader: &mut impl BufRead) -> io::Result<Option<Vec<Vec<u8>>>> {
+    loop {
+        let Some(line) = read_line(reader)? else {
+            return Ok(None);
+        };
+        let Some(count) = line.strip_prefix(b"*") else {
+            let words: Vec<Vec<u8>> = line
+                .split(|byte| byte.is_ascii_whitespace())
+                .filter(|word| !word.is_empty())
+                .map(<[u8]>::to_vec)
+                .collect();
+            // Linhas em branco entre comandos inline são ignoradas
+            if words.is_empty() {
+                continue;
+            }
+            return Ok(Some(words));
+        };
+
+        let count = parse_len(count, MAX_ARGS)?;
+        let mut args = Vec::with_capacity(count.min(ARGS_CAPACITY_HINT));
+        for _ in 0..count {
+            let header = read_line(reader)?.ok_or_else(|| invalid("unexpected end of command"))?;
+            let len = header
+                .strip_prefix(b"$")
+                .ok_or_else(|| invalid("expected a bulk string"))?;
+            let len = parse_len(len, MAX_BULK_LEN)?;
+
+            // O tamanho vem do cliente: a memória só cresce com os bytes que chegam
+            let mut arg = Vec::new();
+            if reader.by_ref().take(len as u64 + 2).read_to_end(&mut arg)? != len + 2 {
+                return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "unexpected end of bulk string"));
+            }
+            if !arg.ends_with(b"\r\n") {
+                return Err(invalid("bulk string is not terminated by CRLF"));
+            }
+            arg.truncate(len);
+            args.push(arg);
+        }
+        return Ok(Some(args));
+    }
+}
+
+fn read_line(reader: &mut impl BufRead) -> io::Result<Option<Vec<u8>>> {
+    let mut line = Vec::new();
+    if reader.read_until(b'\n', &mut line)? == 0 {
+        return Ok(None);
+    }
+    if line.pop() != Some(b'\n') {
+        return Err(invalid("unexpected end of line"));
+    }
+    if line.last() == Some(&b'\r') {
+        line.pop();
+    }
+    Ok(Some(line))
+}
+
+fn parse_len(digits: &[u8], max: usize) -> io::Result<usize> {
+    std::str::from_utf8(digits)
+        .ok()
+        .and_then(|digits| digits.parse().ok())
+        .filter(|&len| len <= max)
+        .ok_or_else(|| invalid("invalid length"))
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(input: &[u8]) -> io::Result<Option<Vec<Vec<u8>>>> {
+        read_command(&mut &input[..])
+    }
+
+    #[test]
+    fn test_read_array_command() {
+        let command = parse(b"*3\r\n$3\r\nSET\r\n$1\r\nk\r\n$5\r\nv\r\nx!\r\n").unwrap().unwrap();
+        assert_eq!(command, vec![b"SET".to_vec(), b"k".to_vec(), b"v\r\nx!".to_vec()]);
+    }
+
+    #[test]
+    fn test_read_inline_command() {
+        assert_eq!(parse(b"\r\nGET  key\r\n").unwrap().unwrap(), vec![b"GET".to_vec(), b"key".to_vec()]);
+        assert_eq!(parse(b"").unwrap(), None);
+    }
+
+    #[test]
+    fn test_rejects_malformed_commands() {
+        for input in [&b"*1\r\n+GET\r\n"[..], b"*1\r\n$3\r\nGETX\r\n", b"*x\r\n", b"*1\r\n$3\r\nGE"] {
+            assert!(parse(input).is_err(), "{:?}", String::from_utf8_lossy(input));
+        }
+    }
+
+    #[test]
+    fn test_announced_lengths_are_not_trusted() {
+        let error = parse(b"*1048576\r\n$536870912\r\nGET\r\n").unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::UnexpectedEof);
+        assert!(parse(b"*2\r\n$4\r\nPING\r\n").is_err());
+    }
+
+    #[test]
+    fn test_write_replies() {
+        let reply = Reply::Array(vec![
+            Reply::ok(),
+            Reply::error("ERR boom"),
+            Reply::Integer(-2),
+            Reply::bulk("hi"),
+            Reply::Bulk(None),
+        ]);
+        let mut output = Vec::new();
+        reply.write_to(&mut output).unwrap();
+        assert_eq!(output, b"*5\r\n+OK\r\n-ERR boom\r\n:-2\r\n$2\r\nhi\r\n$-1\r\n");
+    }
+}
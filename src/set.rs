@@ -0,0 +1,201 @@
+use std::collections::{BTreeSet, HashMap};
+use std::time::Duration;
+
+use crate::clock::Instant;
+
+/// A cache of sets: under each key, a set of unique string members, with
+/// union, intersection and difference across keys, as in Redis.
+///
+/// Keeping one set per tag turns "items with tags A and B" into
+/// [`sinter`](Self::sinter) over the two tag keys. Members are kept in
+/// order, so every query returns them sorted.
+///
+/// A set can be given a TTL with [`expire`](Self::expire), and disappears
+/// along with its last member. Missing and expired keys count as empty
+/// sets in every operation.
+///
+/// # Examples
+///
+/// ```
+/// use spectra_cache::SetCache;
+///
+/// let mut tags = SetCache::new();
+/// for item in ["item:1", "item:2", "item:3"] {
+///     tags.sadd("tag:sale", item);
+/// }
+/// tags.sadd("tag:new", "item:2");
+/// tags.sadd("tag:new", "item:4");
+///
+/// assert_eq!(tags.sinter(&["tag:sale", "tag:new"]), ["item:2"]);
+/// assert_eq!(tags.sdiff(&["tag:sale", "tag:new"]), ["item:1", "item:3"]);
+/// assert_eq!(tags.sunion(&["tag:sale", "tag:new"]).len(), 4);
+/// ```
+#[derive(Debug, Default)]
+pub struct SetCache {
+    sets: HashMap<String, Set>,
+}
+
+#[derive(Debug, Default)]
+struct Set {
+    members: BTreeSet<String>,
+    expires_at: Option<Instant>,
+}
+
+impl Set {
+    fn is_live(&self, now: Instant) -> bool {
+        self.expires_at.is_none_or(|expires_at| expires_at > now)
+    }
+}
+
+impl SetCache {
+    /// Creates an empty cache.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds `member` to the set at `key`, creating the set if needed.
+    /// Returns true if the member is new.
+    pub fn sadd(&mut self, key: &str, member: &str) -> bool {
+        if self.live_mut(key).is_none() {
+            self.sets.insert(key.to_string(), Set::default());
+        }
+        let set = self.sets.get_mut(key).expect("set was just created");
+        set.members.insert(member.to_string())
+    }
+
+    /// Removes `member` from the set at `key`. Returns true if it was a member.
+    pub fn srem(&mut self, key: &str, member: &str) -> bool {
+        let Some(set) = self.live_mut(key) else {
+            return false;
+        };
+        let removed = set.members.remove(member);
+        if set.members.is_empty() {
+            self.sets.remove(key);
+        }
+        removed
+    }
+
+    /// Returns true if `member` is in the set at `key`.
+    pub fn sismember(&self, key: &str, member: &str) -> bool {
+        self.live(key).is_some_and(|set| set.members.contains(member))
+    }
+
+    /// Returns the members of the set at `key`, sorted.
+    pub fn smembers(&self, key: &str) -> Vec<&str> {
+        self.members(key).map(String::as_str).collect()
+    }
+
+    /// Returns the number of members in the set at `key`, or zero if there is none.
+    pub fn scard(&self, key: &str) -> usize {
+        self.live(key).map_or(0, |set| set.members.len())
+    }
+
+    /// Returns the members of any of the sets at `keys`, sorted.
+    pub fn sunion(&self, keys: &[&str]) -> Vec<&str> {
+        let union: BTreeSet<&str> = keys.iter().flat_map(|key| self.members(key)).map(String::as_str).collect();
+        union.into_iter().collect()
+    }
+
+    /// Returns the members of every one of the sets at `keys`, sorted.
+    /// Time complexity: O(n × k) for the smallest set's n members and k keys
+    pub fn sinter(&self, keys: &[&str]) -> Vec<&str> {
+        // Um conjunto ausente torna a interseção vazia
+        let Some(mut sets) = keys.iter().map(|key| self.live(key)).collect::<Option<Vec<_>>>() else {
+            return Vec::new();
+        };
+        // Percorre o menor conjunto e consulta os outros
+        sets.sort_by_key(|set| set.members.len());
+        let Some((smallest, others)) = sets.split_first() else {
+            return Vec::new();
+        };
+        smallest
+            .members
+            .iter()
+            .filter(|member| others.iter().all(|set| set.members.contains(*member)))
+            .map(String::as_str)
+            .collect()
+    }
+
+    /// Returns the members of the first set at `keys` that are in none of
+    /// the others, sorted.
+    pub fn sdiff(&self, keys: &[&str]) -> Vec<&str> {
+        let Some((first, others)) = keys.split_first() else {
+            return Vec::new();
+        };
+        let others: Vec<_> = others.iter().filter_map(|key| self.live(key)).collect();
+        self.members(first)
+            .filter(|member| others.iter().all(|set| !set.members.contains(*member)))
+            .map(String::as_str)
+            .collect()
+    }
+
+    /// Sets the set at `key` to expire after `ttl`. Returns true if the
+    /// set existed.
+    pub fn expire(&mut self, key: &str, ttl: Duration) -> bool {
+        match self.live_mut(key) {
+            Some(set) => {
+                set.expires_at = Some(Instant::now() + ttl);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Returns how long the set at `key` has left to live, or `None` if it
+    /// doesn't exist or never expires.
+    pub fn ttl(&self, key: &str) -> Option<Duration> {
+        let expires_at = self.live(key)?.expires_at?;
+        Some(expires_at.saturating_duration_since(Instant::now()))
+    }
+
+    /// Returns true if `key` holds a set.
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.live(key).is_some()
+    }
+
+    /// Removes the set at `key`, returning its members if it existed.
+    pub fn remove(&mut self, key: &str) -> Option<BTreeSet<String>> {
+        let now = Instant::now();
+        self.sets.remove(key).filter(|set| set.is_live(now)).map(|set| set.members)
+    }
+
+    /// Returns an iterator over the keys holding sets.
+    pub fn keys(&self) -> impl Iterator<Item = &String> {
+        let now = Instant::now();
+        self.sets.iter().filter(move |(_, set)| set.is_live(now)).map(|(key, _)| key)
+    }
+
+    /// Returns true if no key holds a set.
+    pub fn is_empty(&self) -> bool {
+        self.keys().next().is_none()
+    }
+
+    /// Removes every expired set. Returns the number removed.
+    pub fn purge_expired(&mut self) -> usize {
+        let now = Instant::now();
+        let before = self.sets.len();
+        self.sets.retain(|_, set| set.is_live(now));
+        before - self.sets.len()
+    }
+
+    /// Removes every set.
+    pub fn clear(&mut self) {
+        self.sets.clear();
+    }
+
+    fn members(&self, key: &str) -> impl Iterator<Item = &String> {
+        self.live(key).into_iter().flat_map(|set| set.members.iter())
+    }
+
+    fn live(&self, key: &str) -> Option<&Set> {
+        self.sets.get(key).filter(|set| set.is_live(Instant::now()))
+    }
+
+    /// Returns the set at `key`, dropping it first if it has expired.
+    fn live_mut(&mut self, key: &str) -> Option<&mut Set> {
+        if self.sets.get(key).is_some_and(|set| !set.is_live(Instant::now())) {
+            self.sets.remove(key);
+        }
+        self.sets.get_mut(key)
+    }
+}
@@ -0,0 +1,111 @@
+use std::time::Duration;
+
+use crate::{CacheError, DistributedHashTable, HashRing};
+
+/// A cache partitioned across several `DistributedHashTable` shards.
+///
+/// Keys are routed to shards through a [`HashRing`], so every operation
+/// touches exactly one shard and the shards can later be moved behind
+/// separate locks, threads, or nodes without changing which keys they own.
+///
+/// # Examples
+///
+/// ```
+/// use spectra_cache::ShardedCache;
+///
+/// let mut cache = ShardedCache::new(4);
+/// cache.insert("user:123", "John Doe");
+/// assert_eq!(cache.get("user:123"), Some("John Doe"));
+/// assert_eq!(cache.shard(cache.shard_for("user:123")).size(), 1);
+/// ```
+#[derive(Debug)]
+pub struct ShardedCache {
+    shards: Vec<DistributedHashTable>,
+    ring: HashRing<usize>,
+}
+
+impl ShardedCache {
+    /// Creates a cache with `shards` empty shards.
+    ///
+    /// Panics if `shards` is zero.
+    pub fn new(shards: usize) -> Self {
+        assert!(shards > 0, "a sharded cache needs at least one shard");
+        let mut ring = HashRing::new();
+        for shard in 0..shards {
+            ring.add_node(shard);
+        }
+        Self {
+            shards: (0..shards).map(|_| DistributedHashTable::new()).collect(),
+            ring,
+        }
+    }
+
+    /// Returns the number of shards.
+    pub fn shard_count(&self) -> usize {
+        self.shards.len()
+    }
+
+    /// Returns the index of the shard that owns `key`.
+    pub fn shard_for(&self, key: &str) -> usize {
+        *self.ring.node_for(key).expect("the ring always has every shard")
+    }
+
+    /// Returns a shard by index, for inspection.
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn shard(&self, index: usize) -> &DistributedHashTable {
+        &self.shards[index]
+    }
+
+    /// Returns the total number of entries across all shards.
+    pub fn size(&self) -> usize {
+        self.shards.iter().map(DistributedHashTable::size).sum()
+    }
+
+    /// Returns true if every shard is empty.
+    pub fn is_empty(&self) -> bool {
+        self.shards.iter().all(DistributedHashTable::is_empty)
+    }
+
+    /// Inserts a key-value pair into the owning shard.
+    pub fn insert(&mut self, key: &str, value: &str) {
+        self.shard_mut(key).insert(key, value);
+    }
+
+    /// Inserts a key-value pair with TTL into the owning shard.
+    pub fn insert_with_ttl(&mut self, key: &str, value: &str, ttl: Duration) {
+        self.shard_mut(key).insert_with_ttl(key, value, ttl);
+    }
+
+    /// Retrieves a value by key.
+    pub fn get(&mut self, key: &str) -> Option<&str> {
+        self.shard_mut(key).get(key)
+    }
+
+    /// Removes a key-value pair, returning the removed value.
+    pub fn remove(&mut self, key: &str) -> Option<String> {
+        self.shard_mut(key).remove(key)
+    }
+
+    /// Checks if a key exists and has not expired.
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.shard(self.shard_for(key)).contains_key(key)
+    }
+
+    /// Adds `delta` to the integer stored under `key` and returns the new value.
+    pub fn incr(&mut self, key: &str, delta: i64) -> Result<i64, CacheError> {
+        self.shard_mut(key).incr(key, delta)
+    }
+
+    /// Removes all entries from every shard.
+    pub fn clear(&mut self) {
+        for shard in &mut self.shards {
+            shard.clear();
+        }
+    }
+
+    fn shard_mut(&mut self, key: &str) -> &mut DistributedHashTable {
+        let index = self.shard_for(key);
+        &mut self.shards[index]
+    }
+}
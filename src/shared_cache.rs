@@ -0,0 +1,249 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::config::{CacheConfig, CacheType};
+use crate::core::CacheCore;
+use crate::entry::CacheEntry;
+use crate::{CacheBuilder, CacheStats, EntryMetadata, RemovalCause};
+
+/// A hash-table cache whose reads hand out shared ownership of the value.
+///
+/// Works like `DistributedHashTable`, but values are stored as `Arc<str>`
+/// and `get` returns a clone of that `Arc` instead of a `&str` borrowed
+/// from the cache. The value stays alive for as long as the caller holds
+/// it, even after the entry is replaced, evicted or expires, so it can be
+/// kept across an `.await` or sent to another thread while the cache goes
+/// on being written. Cloning the `Arc` only bumps a reference count, so
+/// large payloads are never copied on a read, and
+/// [`insert_shared`](Self::insert_shared) stores an existing `Arc` without
+/// copying it on the way in either.
+///
+/// # Examples
+///
+/// ```
+/// use spectra_cache::SharedCache;
+/// use std::sync::Arc;
+/// use std::thread;
+///
+/// let mut cache = SharedCache::new();
+/// cache.insert("page:home", "<html>...</html>");
+///
+/// let page: Arc<str> = cache.get("page:home").unwrap();
+/// cache.remove("page:home");
+/// let rendered = thread::spawn(move || page.len()).join().unwrap();
+/// assert_eq!(rendered, 16);
+/// ```
+#[derive(Debug, Clone)]
+pub struct SharedCache {
+    pub(crate) core: CacheCore<HashMap<String, CacheEntry<Arc<str>>>>,
+}
+
+impl SharedCache {
+    /// Creates a new empty cache.
+    pub fn new() -> Self {
+        Self {
+            core: CacheCore::new(),
+        }
+    }
+
+    /// Returns a builder for a cache with non-default settings.
+    pub fn builder() -> CacheBuilder<Self> {
+        CacheBuilder::new()
+    }
+
+    /// Returns a deep copy whose entries count as freshly written.
+    ///
+    /// See [`DistributedHashTable::clone_with_fresh_timestamps`](crate::DistributedHashTable::clone_with_fresh_timestamps).
+    /// The copy shares the value allocations with the original.
+    pub fn clone_with_fresh_timestamps(&self) -> Self {
+        let mut copy = self.clone();
+        copy.core.refresh_timestamps();
+        copy
+    }
+
+    fn with_config(config: CacheConfig) -> Self {
+        Self {
+            core: CacheCore::with_config(config),
+        }
+    }
+
+    /// Returns the number of entries in the cache.
+    pub fn size(&self) -> usize {
+        self.core.size()
+    }
+
+    /// Returns true if the cache is empty.
+    pub fn is_empty(&self) -> bool {
+        self.core.is_empty()
+    }
+
+    /// Inserts a copy of `value`, replacing any previous value under `key`.
+    ///
+    /// The entry never expires unless the cache was built with a default TTL.
+    pub fn insert(&mut self, key: &str, value: &str) {
+        self.core.insert(key, value);
+    }
+
+    /// Inserts `value` itself, without copying it, with the default TTL.
+    pub fn insert_shared(&mut self, key: &str, value: Arc<str>) {
+        self.core.insert_value(key, value);
+    }
+
+    /// Inserts a copy of `value` that expires after `ttl`.
+    pub fn insert_with_ttl(&mut self, key: &str, value: &str, ttl: Duration) {
+        self.core.insert_entry(key, CacheEntry::with_ttl(key, value, Some(ttl), self.core.now()));
+    }
+
+    /// Inserts `value` itself, without copying it, expiring after `ttl`.
+    pub fn insert_shared_with_ttl(&mut self, key: &str, value: Arc<str>, ttl: Duration) {
+        self.core.insert_entry(key, CacheEntry::from_value(value, Some(ttl), self.core.now()));
+    }
+
+    /// Inserts a copy of `value` that expires after `idle` without reads.
+    pub fn insert_with_tti(&mut self, key: &str, value: &str, idle: Duration) {
+        self.core.insert_entry(key, CacheEntry::with_tti(key, value, idle, self.core.now()));
+    }
+
+    /// Retrieves a value by key as a new reference to the stored `Arc`.
+    ///
+    /// Returns None if the key doesn't exist or if the entry has expired.
+    pub fn get(&mut self, key: &str) -> Option<Arc<str>> {
+        self.core.get_stored(key).cloned()
+    }
+
+    /// Retrieves a value without marking it as used.
+    ///
+    /// See [`DistributedHashTable::peek`](crate::DistributedHashTable::peek).
+    pub fn peek(&self, key: &str) -> Option<Arc<str>> {
+        self.core.peek_stored(key).cloned()
+    }
+
+    /// Returns how long `key` has left to live.
+    ///
+    /// See [`DistributedHashTable::ttl`](crate::DistributedHashTable::ttl).
+    pub fn ttl(&self, key: &str) -> Option<Duration> {
+        self.core.ttl(key)
+    }
+
+    /// Sets or replaces the TTL of an existing entry, counted from now.
+    pub fn expire(&mut self, key: &str, ttl: Duration) -> bool {
+        self.core.expire(key, ttl)
+    }
+
+    /// Removes the TTL and idle timeout of an entry so it never expires.
+    pub fn persist(&mut self, key: &str) -> bool {
+        self.core.persist(key)
+    }
+
+    /// Removes a value, returning it if the key existed.
+    pub fn remove(&mut self, key: &str) -> Option<Arc<str>> {
+        self.core.remove(key)
+    }
+
+    /// Checks if a key exists and hasn't expired.
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.core.contains_key(key)
+    }
+
+    /// Removes every entry. Values still held by callers stay alive.
+    pub fn clear(&mut self) {
+        self.core.clear();
+    }
+
+    /// Removes every expired entry now and returns how many there were.
+    pub fn purge_expired(&mut self) -> usize {
+        self.core.purge_expired()
+    }
+
+    /// Returns the live keys in no particular order.
+    pub fn keys(&self) -> impl Iterator<Item = &String> {
+        self.core.keys()
+    }
+
+    /// Returns the live values, as references to the stored `Arc`s.
+    pub fn values(&self) -> impl Iterator<Item = &Arc<str>> {
+        self.core.values()
+    }
+
+    /// Returns the live entries with their metadata.
+    ///
+    /// See [`DistributedHashTable::iter`](crate::DistributedHashTable::iter).
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &str, EntryMetadata)> {
+        self.core.iter()
+    }
+
+    /// Returns the approximate memory taken by the entries, in bytes.
+    ///
+    /// A value counts in full even while callers hold other references to
+    /// it, and counts again in another cache it was inserted into.
+    pub fn memory_usage(&self) -> usize {
+        self.core.memory_usage()
+    }
+
+    /// Reserves room for at least `additional` more entries.
+    pub fn reserve_capacity(&mut self, additional: usize) {
+        self.core.reserve_capacity(additional);
+    }
+
+    /// Returns hit, miss and eviction counts.
+    pub fn stats(&self) -> CacheStats {
+        self.core.stats()
+    }
+
+    /// Registers a callback fired whenever an entry leaves the cache.
+    ///
+    /// See [`DistributedHashTable::on_evict`](crate::DistributedHashTable::on_evict).
+    pub fn on_evict(&mut self, listener: impl FnMut(&str, &str, RemovalCause) + Send + 'static) {
+        self.core.on_evict(Box::new(listener));
+    }
+
+    /// Unregisters every `on_evict` listener.
+    pub fn remove_evict_listeners(&mut self) {
+        self.core.remove_evict_listeners();
+    }
+}
+
+impl CacheBuilder<SharedCache> {
+    /// Creates the shared value cache.
+    pub fn build(self) -> SharedCache {
+        SharedCache::with_config(self.config)
+    }
+}
+
+impl CacheType for SharedCache {
+    type Value = Arc<str>;
+}
+
+impl Default for SharedCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Two caches are equal when they hold the same live keys and values.
+impl PartialEq for SharedCache {
+    fn eq(&self, other: &Self) -> bool {
+        self.core.same_content(&other.core)
+    }
+}
+
+/// Inserts every pair with [`insert`](SharedCache::insert).
+impl<K: AsRef<str>, V: AsRef<str>> Extend<(K, V)> for SharedCache {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        let iter = iter.into_iter();
+        self.reserve_capacity(iter.size_hint().0);
+        for (key, value) in iter {
+            self.insert(key.as_ref(), value.as_ref());
+        }
+    }
+}
+
+/// Builds a cache with default settings holding the pairs.
+impl<K: AsRef<str>, V: AsRef<str>> FromIterator<(K, V)> for SharedCache {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut cache = Self::new();
+        cache.extend(iter);
+        cache
+    }
+}
@@ -0,0 +1,249 @@
+//! Frequency estimation for finding the keys that take most of the load.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+
+/// A count-min sketch: approximate counts of how often items were seen, in
+/// a fixed amount of memory however many distinct items there are.
+///
+/// Every item bumps one counter in each of `depth` rows of `width`
+/// counters, and its estimate is the smallest of those counters. Estimates
+/// never undercount; they overcount by at most `e / width` times the total
+/// count with probability `1 - e^-depth`. Items are hashed with SipHash
+/// under fixed keys, so sketches built with the same dimensions agree.
+///
+/// # Examples
+///
+/// ```
+/// use spectra_cache::CountMinSketch;
+///
+/// let mut sketch = CountMinSketch::with_error(0.001, 0.01);
+/// for _ in 0..500 {
+///     sketch.increment("user:42");
+/// }
+/// sketch.increment("user:7");
+/// assert!(sketch.estimate("user:42") >= 500);
+/// assert!(sketch.estimate("user:7") >= 1);
+/// assert_eq!(sketch.total(), 501);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CountMinSketch {
+    width: usize,
+    depth: usize,
+    counters: Vec<u64>,
+    total: u64,
+}
+
+impl CountMinSketch {
+    /// Creates a sketch with `depth` rows of `width` counters.
+    ///
+    /// Panics if either dimension is zero.
+    pub fn new(width: usize, depth: usize) -> Self {
+        assert!(width > 0 && depth > 0, "a count-min sketch needs at least one counter");
+        Self {
+            width,
+            depth,
+            counters: vec![0; width * depth],
+            total: 0,
+        }
+    }
+
+    /// Creates a sketch whose estimates exceed the true count by at most
+    /// `epsilon` times the total count, except with probability `delta`.
+    ///
+    /// Panics unless both are between 0 and 1, exclusive.
+    pub fn with_error(epsilon: f64, delta: f64) -> Self {
+        assert!(epsilon > 0.0 && epsilon < 1.0, "epsilon must be between 0 and 1");
+        assert!(delta > 0.0 && delta < 1.0, "delta must be between 0 and 1");
+        let width = (std::f64::consts::E / epsilon).ceil() as usize;
+        let depth = (1.0 / delta).ln().ceil().max(1.0) as usize;
+        Self::new(width, depth)
+    }
+
+    /// Counts one occurrence of `item` and returns its new estimate.
+    pub fn increment<T: Hash + ?Sized>(&mut self, item: &T) -> u64 {
+        self.add(item, 1)
+    }
+
+    /// Counts `count` occurrences of `item` and returns its new estimate.
+    pub fn add<T: Hash + ?Sized>(&mut self, item: &T, count: u64) -> u64 {
+        self.total = self.total.saturating_add(count);
+        let mut estimate = u64::MAX;
+        for slot in self.slots(item) {
+            let counter = &mut self.counters[slot];
+            *counter = counter.saturating_add(count);
+            estimate = estimate.min(*counter);
+        }
+        estimate
+    }
+
+    /// Returns how many times `item` was counted, possibly more but never
+    /// less.
+    pub fn estimate<T: Hash + ?Sized>(&self, item: &T) -> u64 {
+        self.slots(item).map(|slot| self.counters[slot]).min().unwrap_or(0)
+    }
+
+    /// Returns the sum of every count added.
+    pub fn total(&self) -> u64 {
+        self.total
+    }
+
+    /// Returns the number of counters per row.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Returns the number of rows.
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Halves every counter, so that old occurrences weigh less than new
+    /// ones.
+    pub fn halve(&mut self) {
+        self.counters.iter_mut().for_each(|counter| *counter /= 2);
+        self.total /= 2;
+    }
+
+    /// Resets every counter to zero.
+    pub fn clear(&mut self) {
+        self.counters.fill(0);
+        self.total = 0;
+    }
+
+    /// Returns the counter of `item` in every row, picked by double hashing.
+    fn slots<T: Hash + ?Sized>(&self, item: &T) -> impl Iterator<Item = usize> {
+        let mut hasher = DefaultHasher::new();
+        item.hash(&mut hasher);
+        let hash = hasher.finish();
+        let (h1, h2) = (hash & 0xffff_ffff, (hash >> 32) | 1);
+        let width = self.width;
+        (0..self.depth).map(move |row| row * width + (h1.wrapping_add(row as u64 * h2) % width as u64) as usize)
+    }
+}
+
+/// Width of the sketch behind hot-key tracking: about 0.1% overcount.
+const HOT_KEYS_WIDTH: usize = 2048;
+
+/// Depth of the sketch behind hot-key tracking.
+const HOT_KEYS_DEPTH: usize = 4;
+
+/// Accesses after which hot-key counts are halved, so keys that stopped
+/// being hot drop out of the top.
+const HOT_KEYS_WINDOW: u64 = 1 << 16;
+
+/// The most accessed keys of a cache, estimated with a count-min sketch.
+///
+/// Only the `capacity` current leaders are stored by name; any other key
+/// takes the place of the least accessed of them once its estimate passes
+/// it.
+#[derive(Debug, Clone)]
+pub(crate) struct HotKeys {
+    sketch: CountMinSketch,
+    capacity: usize,
+    top: HashMap<String, u64>,
+    /// A lower bound of the smallest count in `top` once it is full
+    floor: u64,
+    accesses: u64,
+}
+
+impl HotKeys {
+    pub(crate) fn new(capacity: usize) -> Self {
+        Self {
+            sketch: CountMinSketch::new(HOT_KEYS_WIDTH, HOT_KEYS_DEPTH),
+            capacity,
+            top: HashMap::with_capacity(capacity + 1),
+            floor: 0,
+            accesses: 0,
+        }
+    }
+
+    pub(crate) fn record(&mut self, key: &str) {
+        if self.capacity == 0 {
+            return;
+        }
+        self.accesses += 1;
+        if self.accesses.is_multiple_of(HOT_KEYS_WINDOW) {
+            self.decay();
+        }
+        let estimate = self.sketch.increment(key);
+        if let Some(count) = self.top.get_mut(key) {
+            *count = estimate;
+        } else if self.top.len() < self.capacity {
+            self.top.insert(key.to_string(), estimate);
+        } else if estimate > self.floor {
+            // O piso é só um limite inferior; o mínimo de verdade pode ter subido
+            let (coldest, &count) = self.top.iter().min_by_key(|(_, &count)| count).unwrap();
+            if estimate > count {
+                let coldest = coldest.clone();
+                self.top.remove(&coldest);
+                self.top.insert(key.to_string(), estimate);
+            }
+            self.floor = self.top.values().copied().min().unwrap_or(0);
+        }
+    }
+
+    fn decay(&mut self) {
+        self.sketch.halve();
+        self.top.values_mut().for_each(|count| *count /= 2);
+        self.floor /= 2;
+    }
+
+    /// Returns the tracked keys with their estimated access counts, most
+    /// accessed first.
+    pub(crate) fn ranking(&self) -> Vec<(String, u64)> {
+        let mut ranking: Vec<(String, u64)> = self.top.iter().map(|(key, &count)| (key.clone(), count)).collect();
+        ranking.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        ranking
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_estimates_never_undercount() {
+        let mut sketch = CountMinSketch::new(64, 4);
+        for i in 0..1000u32 {
+            sketch.add(&i, u64::from(i % 7));
+        }
+        for i in 0..1000u32 {
+            assert!(sketch.estimate(&i) >= u64::from(i % 7));
+        }
+        sketch.halve();
+        assert_eq!(sketch.total(), (0..1000u64).map(|i| i % 7).sum::<u64>() / 2);
+        sketch.clear();
+        assert_eq!(sketch.estimate(&3u32), 0);
+    }
+
+    #[test]
+    fn test_hot_keys_find_the_skewed_keys() {
+        let mut hot = HotKeys::new(3);
+        for round in 0..200 {
+            hot.record("popular");
+            if round % 2 == 0 {
+                hot.record("busy");
+            }
+            if round % 4 == 0 {
+                hot.record("warm");
+            }
+            hot.record(&format!("cold:{}", round));
+        }
+        let keys: Vec<String> = hot.ranking().into_iter().map(|(key, _)| key).collect();
+        assert_eq!(keys, ["popular", "busy", "warm"]);
+        assert_eq!(hot.ranking()[0].1, 200);
+    }
+
+    #[test]
+    fn test_decay_halves_counts() {
+        let mut hot = HotKeys::new(1);
+        for _ in 0..HOT_KEYS_WINDOW - 1 {
+            hot.record("a");
+        }
+        hot.record("a");
+        // A contagem foi dividida antes do último acesso ser somado
+        assert_eq!(hot.ranking(), [("a".to_string(), HOT_KEYS_WINDOW / 2)]);
+    }
+}
@@ -0,0 +1,304 @@
+//! On-disk image of a whole cache, written by `save_snapshot()` and loaded
+//! back by `warm_from_snapshot()`.
+//!
+//! The file starts with a header:
+//!
+//! | Bytes | Meaning |
+//! |-------|---------|
+//! | 8 | Magic, `SPECSNAP` |
+//! | 2 | Format version, big-endian |
+//! | 8 | Entry count, big-endian |
+//!
+//! followed by one record per entry:
+//!
+//! | Bytes | Meaning |
+//! |-------|---------|
+//! | 4 | Key length, big-endian |
+//! | n | Key bytes (UTF-8) |
+//! | 8 | Expiration in milliseconds since the Unix epoch, `0` if none |
+//! | 4 | Payload length, big-endian |
+//! | n | The value as a checksummed `dump()` payload |
+//!
+//! Expirations are wall-clock deadlines rather than remaining TTLs, so the
+//! time a snapshot spends on disk counts against its entries.
+
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{self, BufReader, Read, Write};
+use std::path::Path;
+use std::time::Duration;
+
+use crate::clock::{SystemTime, UNIX_EPOCH};
+use crate::dump;
+use crate::error::CacheError;
+use crate::redact::Redactor;
+
+/// Version written into every snapshot. Snapshots from newer versions are rejected.
+pub(crate) const SNAPSHOT_VERSION: u16 = 1;
+
+const MAGIC: &[u8; 8] = b"SPECSNAP";
+
+/// Caps what a corrupt entry count can make the reader allocate up front.
+const MAX_PREALLOCATED_ENTRIES: u64 = 1 << 16;
+
+/// An entry read back from a snapshot, with what's left of its TTL.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct SnapshotEntry {
+    pub(crate) key: String,
+    pub(crate) value: String,
+    pub(crate) ttl: Option<Duration>,
+}
+
+/// Writes `entries`, given as `(key, value, remaining TTL)`.
+pub(crate) fn write(mut out: impl Write, entries: &[(&str, &str, Option<Duration>)]) -> io::Result<()> {
+    let now = SystemTime::now();
+    out.write_all(MAGIC)?;
+    out.write_all(&SNAPSHOT_VERSION.to_be_bytes())?;
+    out.write_all(&(entries.len() as u64).to_be_bytes())?;
+    for (key, value, ttl) in entries {
+        out.write_all(&(key.len() as u32).to_be_bytes())?;
+        out.write_all(key.as_bytes())?;
+        // Zero significa "sem expiração", então um prazo real nunca vale zero
+        let expires_at = ttl.map_or(0, |ttl| unix_millis(now + ttl).max(1));
+        out.write_all(&expires_at.to_be_bytes())?;
+        let payload = dump::encode(value);
+        out.write_all(&(payload.len() as u32).to_be_bytes())?;
+        out.write_all(&payload)?;
+    }
+    out.flush()
+}
+
+/// An entry as stored, with its expiration in Unix milliseconds.
+struct Record {
+    key: String,
+    value: String,
+    expires_at: u64,
+}
+
+/// Reads a whole snapshot, skipping entries that expired in the meantime.
+///
+/// Malformed input fails with `io::ErrorKind::InvalidData` wrapping a
+/// [`CacheError::InvalidDump`].
+pub(crate) fn read(input: impl Read) -> io::Result<Vec<SnapshotEntry>> {
+    let now = unix_millis(SystemTime::now());
+    let entries = parse(input)?
+        .into_iter()
+        .filter_map(|record| {
+            let ttl = match record.expires_at {
+                0 => None,
+                deadline if deadline <= now => return None,
+                deadline => Some(Duration::from_millis(deadline - now)),
+            };
+            Some(SnapshotEntry { key: record.key, value: record.value, ttl })
+        })
+        .collect();
+    Ok(entries)
+}
+
+fn parse(mut input: impl Read) -> io::Result<Vec<Record>> {
+    if &read_array::<8>(&mut input)? != MAGIC {
+        return Err(invalid("not a snapshot file"));
+    }
+    if u16::from_be_bytes(read_array(&mut input)?) > SNAPSHOT_VERSION {
+        return Err(invalid("unsupported format version"));
+    }
+    let count = u64::from_be_bytes(read_array(&mut input)?);
+
+    let mut records = Vec::with_capacity(count.min(MAX_PREALLOCATED_ENTRIES) as usize);
+    for _ in 0..count {
+        let key = String::from_utf8(read_field(&mut input)?).map_err(|_| invalid("key is not valid UTF-8"))?;
+        let expires_at = u64::from_be_bytes(read_array(&mut input)?);
+        let value = dump::decode(&read_field(&mut input)?).map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+        records.push(Record { key, value, expires_at });
+    }
+
+    if input.read(&mut [0])? != 0 {
+        return Err(invalid("trailing bytes after the last entry"));
+    }
+    Ok(records)
+}
+
+/// The key-level differences between two snapshot files, as found by
+/// [`diff_snapshots`].
+///
+/// Each list is sorted by key. Keys count as changed when their values
+/// differ; a different TTL alone is not a change.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SnapshotDiff {
+    /// Keys only in the second snapshot.
+    pub added: Vec<KeyDiff>,
+    /// Keys only in the first snapshot.
+    pub removed: Vec<KeyDiff>,
+    /// Keys in both snapshots with different values.
+    pub changed: Vec<KeyDiff>,
+}
+
+impl SnapshotDiff {
+    /// Returns true if the snapshots hold the same keys and values.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+
+    /// Rewrites the values `redactor` has rules for, so the diff can be
+    /// shown or logged without leaking them.
+    pub fn redact(&mut self, redactor: &Redactor) {
+        for change in self.added.iter_mut().chain(&mut self.removed).chain(&mut self.changed) {
+            for value in [&mut change.before, &mut change.after].into_iter().flatten() {
+                if let Some(rewrite) = redactor.rule_for(&change.key) {
+                    *value = rewrite(value);
+                }
+            }
+        }
+    }
+}
+
+/// A key that differs between two snapshots, with its value in each one
+/// if values were asked for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyDiff {
+    pub key: String,
+    /// The value in the first snapshot, `None` if the key is new or values weren't asked for.
+    pub before: Option<String>,
+    /// The value in the second snapshot, `None` if the key was removed or values weren't asked for.
+    pub after: Option<String>,
+}
+
+/// Compares two snapshot files written by `save_snapshot()` key by key.
+///
+/// With `include_values` set, every [`KeyDiff`] carries the values on
+/// either side; otherwise only keys are reported, which keeps the diff
+/// small for large values. Entries are compared as stored, even if they
+/// have expired since, so the diff reflects the cache as it was when each
+/// snapshot was taken. Fails like `warm_from_snapshot()` if either file is
+/// missing or malformed.
+///
+/// # Examples
+///
+/// ```
+/// use spectra_cache::{diff_snapshots, DistributedHashTable};
+///
+/// let dir = std::env::temp_dir();
+/// let (before, after) = (dir.join("spectra-cache-doc-before.snapshot"), dir.join("spectra-cache-doc-after.snapshot"));
+/// let mut cache = DistributedHashTable::new();
+/// cache.insert("user:1", "Ana");
+/// cache.insert("user:2", "Bia");
+/// cache.save_snapshot(&before).unwrap();
+///
+/// cache.insert("user:1", "Ana Maria");
+/// cache.remove("user:2");
+/// cache.save_snapshot(&after).unwrap();
+///
+/// let diff = diff_snapshots(&before, &after, true).unwrap();
+/// assert!(diff.added.is_empty());
+/// assert_eq!(diff.removed[0].key, "user:2");
+/// assert_eq!(diff.changed[0].before.as_deref(), Some("Ana"));
+/// assert_eq!(diff.changed[0].after.as_deref(), Some("Ana Maria"));
+/// # std::fs::remove_file(before).unwrap();
+/// # std::fs::remove_file(after).unwrap();
+/// ```
+pub fn diff_snapshots(a: impl AsRef<Path>, b: impl AsRef<Path>, include_values: bool) -> io::Result<SnapshotDiff> {
+    let load = |path: &Path| -> io::Result<BTreeMap<String, String>> {
+        let records = parse(BufReader::new(File::open(path)?))?;
+        Ok(records.into_iter().map(|record| (record.key, record.value)).collect())
+    };
+    let (mut before, after) = (load(a.as_ref())?, load(b.as_ref())?);
+    let value = |value: &String| include_values.then(|| value.clone());
+
+    let mut diff = SnapshotDiff::default();
+    for (key, new) in &after {
+        match before.remove(key) {
+            None => diff.added.push(KeyDiff { key: key.clone(), before: None, after: value(new) }),
+            Some(old) if old != *new => diff.changed.push(KeyDiff { key: key.clone(), before: value(&old), after: value(new) }),
+            Some(_) => {}
+        }
+    }
+    // O que sobrou do primeiro snapshot não existe no segundo
+    diff.removed = before
+        .iter()
+        .map(|(key, old)| KeyDiff { key: key.clone(), before: value(old), after: None })
+        .collect();
+    Ok(diff)
+}
+
+fn read_array<const N: usize>(input: &mut impl Read) -> io::Result<[u8; N]> {
+    let mut bytes = [0; N];
+    input.read_exact(&mut bytes).map_err(truncated)?;
+    Ok(bytes)
+}
+
+/// Reads a length-prefixed field without trusting the length for allocation.
+fn read_field(input: &mut impl Read) -> io::Result<Vec<u8>> {
+    let len = u32::from_be_bytes(read_array(input)?) as u64;
+    let mut bytes = Vec::new();
+    if input.take(len).read_to_end(&mut bytes)? as u64 != len {
+        return Err(invalid("snapshot is truncated"));
+    }
+    Ok(bytes)
+}
+
+fn unix_millis(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH).map_or(0, |elapsed| elapsed.as_millis() as u64)
+}
+
+fn truncated(error: io::Error) -> io::Error {
+    match error.kind() {
+        io::ErrorKind::UnexpectedEof => invalid("snapshot is truncated"),
+        _ => error,
+    }
+}
+
+fn invalid(reason: &'static str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, CacheError::InvalidDump { reason })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encoded(entries: &[(&str, &str, Option<Duration>)]) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        write(&mut bytes, entries).unwrap();
+        bytes
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let bytes = encoded(&[("user:1", "Ana", None), ("session:1", "ação", Some(Duration::from_secs(60)))]);
+        let entries = read(bytes.as_slice()).unwrap();
+
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0], SnapshotEntry { key: "user:1".to_string(), value: "Ana".to_string(), ttl: None });
+        assert_eq!((entries[1].key.as_str(), entries[1].value.as_str()), ("session:1", "ação"));
+        let ttl = entries[1].ttl.unwrap();
+        assert!(ttl <= Duration::from_secs(60) && ttl > Duration::from_secs(50));
+    }
+
+    #[test]
+    fn test_skips_entries_that_expired_on_disk() {
+        let bytes = encoded(&[("gone", "x", Some(Duration::ZERO)), ("kept", "y", None)]);
+        let entries = read(bytes.as_slice()).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].key, "kept");
+    }
+
+    #[test]
+    fn test_rejects_malformed_input() {
+        let reason = |bytes: &[u8]| {
+            let error = read(bytes).unwrap_err();
+            assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+            error.into_inner().unwrap().to_string()
+        };
+        let bytes = encoded(&[("key", "value", None)]);
+
+        assert_eq!(reason(b"not a snapshot"), "invalid dump payload: not a snapshot file");
+        assert_eq!(reason(&bytes[..bytes.len() - 3]), "invalid dump payload: snapshot is truncated");
+
+        let mut corrupt = bytes.clone();
+        *corrupt.last_mut().unwrap() ^= 0xff;
+        assert_eq!(reason(&corrupt), "invalid dump payload: checksum mismatch");
+
+        let mut trailing = bytes;
+        trailing.push(0);
+        assert_eq!(reason(&trailing), "invalid dump payload: trailing bytes after the last entry");
+    }
+}
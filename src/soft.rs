@@ -0,0 +1,202 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::sync::{Arc, Weak};
+
+type Loader<T> = Box<dyn Fn(&str) -> Option<T> + Send + Sync>;
+
+/// A cache of large shared objects that gives them up under memory
+/// pressure and rebuilds them on demand.
+///
+/// Values are `Arc<T>`s handed out to callers without copying. Entries
+/// stored with [`insert`](Self::insert) are held strongly until removed.
+/// Entries stored with [`insert_soft`](Self::insert_soft), or rebuilt by
+/// the loader, are soft: the cache keeps at most `max_strong` of them
+/// alive by itself, and demotes the least recently used beyond that to a
+/// `Weak` reference, as does [`release_memory`](Self::release_memory) for
+/// all of them at once. A demoted value stays reachable as long as a
+/// caller still holds it; once the last outside `Arc` is dropped its
+/// memory is freed, and the next [`get`](Self::get) regenerates it with
+/// the loader from the key alone.
+///
+/// # Examples
+///
+/// ```
+/// use spectra_cache::SoftCache;
+/// use std::sync::Arc;
+///
+/// let mut cache = SoftCache::new(1, |key: &str| Some(format!("rendered {}", key)));
+/// cache.insert_soft("page:1", Arc::new("rendered page:1".to_string()));
+///
+/// let page = cache.get("page:1").unwrap();
+/// assert_eq!(cache.release_memory(), 1);
+/// // Still held by `page`, so nothing is rebuilt
+/// assert!(Arc::ptr_eq(&cache.get("page:1").unwrap(), &page));
+///
+/// drop(page);
+/// cache.release_memory();
+/// assert_eq!(cache.get("page:1").as_deref().map(String::as_str), Some("rendered page:1"));
+/// assert_eq!(cache.regenerations(), 1);
+/// ```
+pub struct SoftCache<T> {
+    entries: HashMap<String, Slot<T>>,
+    /// Soft entries held strongly, by last use
+    recency: BTreeMap<u64, String>,
+    max_strong: usize,
+    loader: Loader<T>,
+    clock: u64,
+    regenerations: u64,
+}
+
+enum Slot<T> {
+    /// Held until removed
+    Pinned(Arc<T>),
+    /// Held strongly for now; `used` is its key in `recency`
+    Soft { value: Arc<T>, used: u64 },
+    /// Demoted: alive only while someone else holds it
+    Weak(Weak<T>),
+}
+
+impl<T> SoftCache<T> {
+    /// Creates a cache that keeps at most `max_strong` soft entries alive
+    /// by itself, regenerating reclaimed ones with `loader`.
+    ///
+    /// The loader returns `None` for keys it can't rebuild; they are then
+    /// dropped from the cache.
+    pub fn new(max_strong: usize, loader: impl Fn(&str) -> Option<T> + Send + Sync + 'static) -> Self {
+        Self {
+            entries: HashMap::new(),
+            recency: BTreeMap::new(),
+            max_strong,
+            loader: Box::new(loader),
+            clock: 0,
+            regenerations: 0,
+        }
+    }
+
+    /// Returns the number of entries, including demoted ones whose value
+    /// may already be gone.
+    pub fn size(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns true if the cache has no entries.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns the number of values the cache itself keeps alive.
+    pub fn strong_count(&self) -> usize {
+        self.entries.values().filter(|slot| !matches!(slot, Slot::Weak(_))).count()
+    }
+
+    /// Returns how many times the loader rebuilt a reclaimed value.
+    pub fn regenerations(&self) -> u64 {
+        self.regenerations
+    }
+
+    /// Stores `value` under `key`, held strongly until removed.
+    pub fn insert(&mut self, key: &str, value: Arc<T>) {
+        self.remove(key);
+        self.entries.insert(key.to_string(), Slot::Pinned(value));
+    }
+
+    /// Stores `value` under `key` as a soft entry, which the cache may
+    /// demote to a weak reference under memory pressure.
+    pub fn insert_soft(&mut self, key: &str, value: Arc<T>) {
+        self.remove(key);
+        self.hold_soft(key, value);
+    }
+
+    /// Returns the value under `key`, regenerating it with the loader if
+    /// it was reclaimed.
+    ///
+    /// A demoted value that is still alive is held strongly again, as the
+    /// most recently used soft entry.
+    pub fn get(&mut self, key: &str) -> Option<Arc<T>> {
+        let value = match self.entries.get_mut(key)? {
+            Slot::Pinned(value) => return Some(Arc::clone(value)),
+            Slot::Soft { value, used } => {
+                let value = Arc::clone(value);
+                self.recency.remove(used);
+                value
+            }
+            Slot::Weak(weak) => match weak.upgrade() {
+                Some(value) => value,
+                None => match (self.loader)(key) {
+                    Some(value) => {
+                        self.regenerations += 1;
+                        Arc::new(value)
+                    }
+                    None => {
+                        self.entries.remove(key);
+                        return None;
+                    }
+                },
+            },
+        };
+        self.hold_soft(key, Arc::clone(&value));
+        Some(value)
+    }
+
+    /// Removes `key`, returning its value if it was still alive.
+    pub fn remove(&mut self, key: &str) -> Option<Arc<T>> {
+        match self.entries.remove(key)? {
+            Slot::Pinned(value) => Some(value),
+            Slot::Soft { value, used } => {
+                self.recency.remove(&used);
+                Some(value)
+            }
+            Slot::Weak(weak) => weak.upgrade(),
+        }
+    }
+
+    /// Demotes every soft entry to a weak reference, freeing the values no
+    /// one else holds, and returns how many entries were demoted.
+    ///
+    /// Call it when the process runs short of memory; pinned entries are
+    /// kept.
+    pub fn release_memory(&mut self) -> usize {
+        let demoted = self.recency.len();
+        while self.demote_coldest() {}
+        demoted
+    }
+
+    /// Removes all entries.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.recency.clear();
+    }
+
+    fn hold_soft(&mut self, key: &str, value: Arc<T>) {
+        self.clock += 1;
+        let used = self.clock;
+        self.recency.insert(used, key.to_string());
+        self.entries.insert(key.to_string(), Slot::Soft { value, used });
+        while self.recency.len() > self.max_strong && self.demote_coldest() {}
+    }
+
+    /// Demotes the least recently used soft entry held strongly. Returns
+    /// false if there was none.
+    fn demote_coldest(&mut self) -> bool {
+        let Some((_, key)) = self.recency.pop_first() else {
+            return false;
+        };
+        if let Some(slot) = self.entries.get_mut(&key) {
+            if let Slot::Soft { value, .. } = slot {
+                *slot = Slot::Weak(Arc::downgrade(value));
+            }
+        }
+        true
+    }
+}
+
+impl<T> fmt::Debug for SoftCache<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SoftCache")
+            .field("size", &self.size())
+            .field("strong", &self.strong_count())
+            .field("max_strong", &self.max_strong)
+            .field("regenerations", &self.regenerations)
+            .finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,327 @@
+use std::cmp::Ordering;
+use std::collections::{BTreeSet, HashMap};
+use std::ops::{Bound, RangeBounds};
+use std::time::Duration;
+
+use crate::CacheError;
+use crate::clock::Instant;
+
+/// A cache of sorted sets: under each key, a set of unique members ordered
+/// by a floating-point score, as in Redis.
+///
+/// Each set keeps its members in a `BTreeSet` of `(score, member)`, so
+/// range queries by score and "top N" queries walk only the members they
+/// return. Members with equal scores are ordered by name. Scores may be
+/// any number but NaN, and `-0.0` is stored as `0.0`.
+///
+/// Members can be given a TTL of their own. Expired members are hidden from
+/// every query right away and removed for good on the next write to their
+/// set, or by [`purge_expired`](Self::purge_expired). A set disappears
+/// along with its last member.
+///
+/// # Examples
+///
+/// ```
+/// use spectra_cache::SortedSetCache;
+///
+/// let mut scores = SortedSetCache::new();
+/// scores.zadd("leaderboard", 120.0, "ana").unwrap();
+/// scores.zadd("leaderboard", 95.0, "bia").unwrap();
+/// scores.zadd("leaderboard", 130.0, "caio").unwrap();
+/// scores.zincrby("leaderboard", 20.0, "bia").unwrap();
+///
+/// assert_eq!(scores.zrevrange("leaderboard", ..2), [("caio", 130.0), ("ana", 120.0)]);
+/// assert_eq!(scores.zrevrank("leaderboard", "bia"), Some(2));
+/// assert_eq!(scores.zrange_by_score("leaderboard", 100.0..125.0), [("bia", 115.0), ("ana", 120.0)]);
+/// ```
+#[derive(Debug, Default)]
+pub struct SortedSetCache {
+    sets: HashMap<String, SortedSet>,
+}
+
+/// A score ordered with `f64::total_cmp`, so it can key a `BTreeSet`.
+#[derive(Debug, Clone, Copy)]
+struct Score(f64);
+
+impl PartialEq for Score {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Score {}
+
+impl PartialOrd for Score {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Score {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.total_cmp(&other.0)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Member {
+    score: f64,
+    expires_at: Option<Instant>,
+}
+
+impl Member {
+    fn is_live(&self, now: Instant) -> bool {
+        self.expires_at.is_none_or(|expires_at| expires_at > now)
+    }
+}
+
+#[derive(Debug, Default)]
+struct SortedSet {
+    by_score: BTreeSet<(Score, String)>,
+    members: HashMap<String, Member>,
+    expirations: BTreeSet<(Instant, String)>,
+}
+
+impl SortedSet {
+    fn live(&self, member: &str, now: Instant) -> Option<&Member> {
+        self.members.get(member).filter(|entry| entry.is_live(now))
+    }
+
+    /// Iterates over the live members in score order.
+    fn iter(&self, now: Instant) -> impl DoubleEndedIterator<Item = (&str, f64)> {
+        self.by_score
+            .iter()
+            .filter(move |(_, member)| self.members[member].is_live(now))
+            .map(|(score, member)| (member.as_str(), score.0))
+    }
+
+    /// Stores `member`, replacing any previous score and TTL. Returns true
+    /// if it wasn't a live member before.
+    fn set(&mut self, member: &str, score: f64, expires_at: Option<Instant>, now: Instant) -> bool {
+        let added = self.live(member, now).is_none();
+        self.remove(member);
+        self.by_score.insert((Score(score), member.to_string()));
+        if let Some(expires_at) = expires_at {
+            self.expirations.insert((expires_at, member.to_string()));
+        }
+        self.members.insert(member.to_string(), Member { score, expires_at });
+        added
+    }
+
+    fn remove(&mut self, member: &str) -> Option<Member> {
+        let removed = self.members.remove(member)?;
+        let member = member.to_string();
+        if let Some(expires_at) = removed.expires_at {
+            self.expirations.remove(&(expires_at, member.clone()));
+        }
+        self.by_score.remove(&(Score(removed.score), member));
+        Some(removed)
+    }
+
+    /// Drops the members whose TTL has run out. Returns how many there were.
+    fn purge_expired(&mut self, now: Instant) -> usize {
+        let mut purged = 0;
+        while let Some((expires_at, member)) = self.expirations.first().cloned() {
+            if expires_at > now {
+                break;
+            }
+            self.remove(&member);
+            purged += 1;
+        }
+        purged
+    }
+}
+
+impl SortedSetCache {
+    /// Creates an empty cache.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds `member` to the sorted set at `key` with `score`, or updates its
+    /// score, clearing any TTL it had. Returns true if the member is new.
+    ///
+    /// Fails with [`CacheError::InvalidScore`] if `score` is NaN.
+    pub fn zadd(&mut self, key: &str, score: f64, member: &str) -> Result<bool, CacheError> {
+        self.store(key, score, member, None)
+    }
+
+    /// Like [`zadd`](Self::zadd), but the member expires after `ttl`.
+    pub fn zadd_with_ttl(&mut self, key: &str, score: f64, member: &str, ttl: Duration) -> Result<bool, CacheError> {
+        self.store(key, score, member, Some(Instant::now() + ttl))
+    }
+
+    /// Adds `delta` to the score of `member` and returns the new score.
+    ///
+    /// A missing member starts at zero. The member keeps its TTL, if any.
+    /// Fails with [`CacheError::InvalidScore`] if the result is NaN, as
+    /// when adding negative infinity to positive infinity.
+    pub fn zincrby(&mut self, key: &str, delta: f64, member: &str) -> Result<f64, CacheError> {
+        let now = Instant::now();
+        let current = self.sets.get(key).and_then(|set| set.live(member, now)).copied();
+        let score = current.map_or(0.0, |current| current.score) + delta;
+        self.store(key, score, member, current.and_then(|current| current.expires_at))?;
+        Ok(score + 0.0)
+    }
+
+    /// Returns the score of `member`, if it is a live member of the set at `key`.
+    pub fn zscore(&self, key: &str, member: &str) -> Option<f64> {
+        let now = Instant::now();
+        self.sets.get(key)?.live(member, now).map(|entry| entry.score)
+    }
+
+    /// Removes `member` from the set at `key`. Returns true if it was a live member.
+    pub fn zrem(&mut self, key: &str, member: &str) -> bool {
+        let now = Instant::now();
+        let Some(set) = self.sets.get_mut(key) else {
+            return false;
+        };
+        let removed = set.remove(member).is_some_and(|removed| removed.is_live(now));
+        self.purge_set(key, now);
+        removed
+    }
+
+    /// Returns the number of live members in the set at `key`.
+    /// Time complexity: O(n) in the size of the set
+    pub fn zcard(&self, key: &str) -> usize {
+        self.sets.get(key).map_or(0, |set| set.iter(Instant::now()).count())
+    }
+
+    /// Returns the 0-based position of `member` in ascending score order.
+    /// Time complexity: O(rank)
+    pub fn zrank(&self, key: &str, member: &str) -> Option<usize> {
+        let now = Instant::now();
+        let set = self.sets.get(key)?;
+        set.live(member, now)?;
+        Some(set.iter(now).take_while(|&(name, _)| name != member).count())
+    }
+
+    /// Returns the 0-based position of `member` in descending score order,
+    /// e.g. its place on a leaderboard.
+    pub fn zrevrank(&self, key: &str, member: &str) -> Option<usize> {
+        let now = Instant::now();
+        let set = self.sets.get(key)?;
+        set.live(member, now)?;
+        Some(set.iter(now).rev().take_while(|&(name, _)| name != member).count())
+    }
+
+    /// Returns the live members at the positions in `ranks`, in ascending
+    /// score order, with their scores.
+    ///
+    /// Unlike Redis, ranges follow Rust syntax: `..10` is the first ten
+    /// members and `10..=19` the next ten.
+    pub fn zrange(&self, key: &str, ranks: impl RangeBounds<usize>) -> Vec<(&str, f64)> {
+        match self.sets.get(key) {
+            Some(set) => by_rank(set.iter(Instant::now()), ranks),
+            None => Vec::new(),
+        }
+    }
+
+    /// Like [`zrange`](Self::zrange), in descending score order: `..10` is
+    /// the top ten.
+    pub fn zrevrange(&self, key: &str, ranks: impl RangeBounds<usize>) -> Vec<(&str, f64)> {
+        match self.sets.get(key) {
+            Some(set) => by_rank(set.iter(Instant::now()).rev(), ranks),
+            None => Vec::new(),
+        }
+    }
+
+    /// Returns the live members whose score falls in `scores`, in ascending
+    /// score order, with their scores.
+    /// Time complexity: O(log n + m) for m members returned
+    pub fn zrange_by_score(&self, key: &str, scores: impl RangeBounds<f64>) -> Vec<(&str, f64)> {
+        let Some(set) = self.sets.get(key) else {
+            return Vec::new();
+        };
+        let now = Instant::now();
+        // "" é o menor membro possível, então a busca começa no primeiro com essa nota
+        let start = match scores.start_bound() {
+            Bound::Included(&score) | Bound::Excluded(&score) => Bound::Included((Score(score), String::new())),
+            Bound::Unbounded => Bound::Unbounded,
+        };
+        set.by_score
+            .range((start, Bound::Unbounded))
+            .skip_while(|(score, _)| !scores.contains(&score.0))
+            .take_while(|(score, _)| scores.contains(&score.0))
+            .filter(|(_, member)| set.members[member].is_live(now))
+            .map(|(score, member)| (member.as_str(), score.0))
+            .collect()
+    }
+
+    /// Returns true if `key` holds a sorted set with live members.
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.zcard(key) > 0
+    }
+
+    /// Removes the sorted set at `key`. Returns true if it had live members.
+    pub fn remove(&mut self, key: &str) -> bool {
+        let now = Instant::now();
+        self.sets
+            .remove(key)
+            .is_some_and(|set| set.members.values().any(|member| member.is_live(now)))
+    }
+
+    /// Returns an iterator over the keys holding sorted sets with live members.
+    pub fn keys(&self) -> impl Iterator<Item = &String> {
+        let now = Instant::now();
+        self.sets
+            .iter()
+            .filter(move |(_, set)| set.members.values().any(|member| member.is_live(now)))
+            .map(|(key, _)| key)
+    }
+
+    /// Returns true if no key holds a sorted set with live members.
+    pub fn is_empty(&self) -> bool {
+        self.keys().next().is_none()
+    }
+
+    /// Removes every expired member from every set, and the sets left
+    /// empty. Returns the number of members removed.
+    pub fn purge_expired(&mut self) -> usize {
+        let now = Instant::now();
+        let purged = self.sets.values_mut().map(|set| set.purge_expired(now)).sum();
+        self.sets.retain(|_, set| !set.members.is_empty());
+        purged
+    }
+
+    /// Removes every sorted set.
+    pub fn clear(&mut self) {
+        self.sets.clear();
+    }
+
+    fn store(&mut self, key: &str, score: f64, member: &str, expires_at: Option<Instant>) -> Result<bool, CacheError> {
+        if score.is_nan() {
+            return Err(CacheError::InvalidScore { key: key.to_string() });
+        }
+        let now = Instant::now();
+        let set = self.sets.entry(key.to_string()).or_default();
+        // Somar zero transforma -0.0 em 0.0, que senão ordenaria antes dele
+        let added = set.set(member, score + 0.0, expires_at, now);
+        self.purge_set(key, now);
+        Ok(added)
+    }
+
+    /// Drops expired members of the set at `key`, and the set if it ends up empty.
+    fn purge_set(&mut self, key: &str, now: Instant) {
+        if let Some(set) = self.sets.get_mut(key) {
+            set.purge_expired(now);
+            if set.members.is_empty() {
+                self.sets.remove(key);
+            }
+        }
+    }
+}
+
+fn by_rank<'a>(members: impl Iterator<Item = (&'a str, f64)>, ranks: impl RangeBounds<usize>) -> Vec<(&'a str, f64)> {
+    let start = match ranks.start_bound() {
+        Bound::Included(&start) => start,
+        Bound::Excluded(&start) => start.saturating_add(1),
+        Bound::Unbounded => 0,
+    };
+    members
+        .enumerate()
+        .skip(start)
+        .take_while(|(rank, _)| ranks.contains(rank))
+        .map(|(_, member)| member)
+        .collect()
+}
@@ -0,0 +1,484 @@
+use alloc::format;
+use alloc::string::String;
+use core::time::Duration;
+
+use crate::clock::Instant;
+#[cfg(not(any(feature = "std", test)))]
+use crate::float::FloatMath;
+
+/// How often the rolling-window rates are recomputed.
+const TICK_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Version of the JSON document produced by [`CacheStats::to_json`].
+///
+/// Bumped whenever a field is renamed or removed; adding fields does not
+/// change the version, so consumers should ignore fields they don't know.
+pub const STATS_SCHEMA_VERSION: u32 = 1;
+
+/// A point-in-time snapshot of cache activity returned by `stats()`.
+///
+/// Counters cover the whole lifetime of the cache, while the rate windows
+/// are exponentially weighted moving averages over the last 1, 5 and 15
+/// minutes (the same decay model as Unix load averages). Rates lag by up to
+/// five seconds because they are only recomputed on tick boundaries.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CacheStats {
+    /// Number of entries currently stored, including expired ones not yet removed
+    pub entries: usize,
+    /// Lookups that found a live entry
+    pub hits: u64,
+    /// Lookups that found nothing or an expired entry
+    pub misses: u64,
+    /// Values written through any insert method
+    pub inserts: u64,
+    /// Entries explicitly removed by the caller
+    pub removals: u64,
+    /// Entries dropped because their TTL ran out
+    pub expirations: u64,
+    /// Entries dropped to make room for others
+    pub evictions: u64,
+    /// Evictions made early because the soft memory limit was exceeded,
+    /// also counted in `evictions`
+    pub proactive_evictions: u64,
+    /// Calls into eviction listeners or audit sinks that panicked; the
+    /// cache caught the panic and carried on
+    pub callback_panics: u64,
+    /// Eviction notifications discarded because the listener queue was full
+    pub dropped_notifications: u64,
+    /// Backing store calls that failed
+    pub store_errors: u64,
+    /// Append-only log writes and syncs that failed
+    pub aof_errors: u64,
+    /// Whether persistence keeps failing; see `PersistenceFailure`
+    pub persistence_degraded: bool,
+    /// Writes refused because persistence keeps failing
+    pub rejected_writes: u64,
+    /// Fraction of the Bloom filter's bits that are set
+    pub bloom_fill_ratio: f64,
+    /// The Bloom filter's false positive rate as estimated from its bits
+    pub bloom_false_positive_rate: f64,
+    /// Times the Bloom filter was rebuilt from the stored keys
+    pub bloom_rebuilds: u64,
+    /// Hits per second over the rolling windows
+    pub hit_rate: RateWindows,
+    /// Misses per second over the rolling windows
+    pub miss_rate: RateWindows,
+    /// Evictions per second over the rolling windows
+    pub eviction_rate: RateWindows,
+}
+
+impl CacheStats {
+    /// Returns the lifetime fraction of lookups that were hits.
+    ///
+    /// Returns `0.0` if there were no lookups yet.
+    pub fn hit_ratio(&self) -> f64 {
+        let lookups = self.hits + self.misses;
+        if lookups == 0 {
+            0.0
+        } else {
+            self.hits as f64 / lookups as f64
+        }
+    }
+
+    /// Serializes the snapshot as a single-line JSON object.
+    ///
+    /// The schema is stable across releases and versioned through
+    /// [`STATS_SCHEMA_VERSION`]:
+    ///
+    /// | Field | Type | Meaning |
+    /// |-------|------|---------|
+    /// | `schema_version` | integer | Version of this layout |
+    /// | `entries` | integer | Entries currently stored |
+    /// | `hits` | integer | Lifetime lookup hits |
+    /// | `misses` | integer | Lifetime lookup misses |
+    /// | `inserts` | integer | Lifetime inserts |
+    /// | `removals` | integer | Lifetime explicit removals |
+    /// | `expirations` | integer | Lifetime TTL expirations |
+    /// | `evictions` | integer | Lifetime evictions |
+    /// | `proactive_evictions` | integer | Evictions triggered by the soft memory limit |
+    /// | `callback_panics` | integer | Listener and audit sink calls that panicked |
+    /// | `dropped_notifications` | integer | Listener notifications dropped by a full queue |
+    /// | `store_errors` | integer | Failed backing store calls |
+    /// | `aof_errors` | integer | Failed append-only log writes and syncs |
+    /// | `persistence_degraded` | boolean | Whether persistence keeps failing |
+    /// | `rejected_writes` | integer | Writes refused while persistence is failing |
+    /// | `bloom_fill_ratio` | number | Fraction of Bloom filter bits set |
+    /// | `bloom_false_positive_rate` | number | Estimated Bloom filter false positive rate |
+    /// | `bloom_rebuilds` | integer | Bloom filter rebuilds |
+    /// | `hit_ratio` | number | `hits / (hits + misses)`, `0` without lookups |
+    /// | `rates.hits_per_sec` | object | `1m`, `5m` and `15m` moving averages |
+    /// | `rates.misses_per_sec` | object | `1m`, `5m` and `15m` moving averages |
+    /// | `rates.evictions_per_sec` | object | `1m`, `5m` and `15m` moving averages |
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use spectra_cache::DistributedHashTable;
+    ///
+    /// let mut cache = DistributedHashTable::new();
+    /// cache.insert("user:123", "John Doe");
+    /// let json = cache.stats().to_json();
+    /// assert!(json.starts_with("{\"schema_version\":1,\"entries\":1,"));
+    /// ```
+    pub fn to_json(&self) -> String {
+        format!(
+            concat!(
+                "{{\"schema_version\":{},\"entries\":{},\"hits\":{},\"misses\":{},",
+                "\"inserts\":{},\"removals\":{},\"expirations\":{},\"evictions\":{},",
+                "\"proactive_evictions\":{},\"callback_panics\":{},\"dropped_notifications\":{},",
+                "\"store_errors\":{},\"aof_errors\":{},\"persistence_degraded\":{},\"rejected_writes\":{},\"bloom_fill_ratio\":{},\"bloom_false_positive_rate\":{},\"bloom_rebuilds\":{},",
+                "\"hit_ratio\":{},\"rates\":{{\"hits_per_sec\":{},",
+                "\"misses_per_sec\":{},\"evictions_per_sec\":{}}}}}"
+            ),
+            STATS_SCHEMA_VERSION,
+            self.entries,
+            self.hits,
+            self.misses,
+            self.inserts,
+            self.removals,
+            self.expirations,
+            self.evictions,
+            self.proactive_evictions,
+            self.callback_panics,
+            self.dropped_notifications,
+            self.store_errors,
+            self.aof_errors,
+            self.persistence_degraded,
+            self.rejected_writes,
+            self.bloom_fill_ratio,
+            self.bloom_false_positive_rate,
+            self.bloom_rebuilds,
+            self.hit_ratio(),
+            self.hit_rate.to_json(),
+            self.miss_rate.to_json(),
+            self.eviction_rate.to_json(),
+        )
+    }
+}
+
+/// Per-second event rates over the 1, 5 and 15 minute windows.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct RateWindows {
+    pub one_minute: f64,
+    pub five_minutes: f64,
+    pub fifteen_minutes: f64,
+}
+
+impl RateWindows {
+    fn to_json(self) -> String {
+        format!(
+            "{{\"1m\":{},\"5m\":{},\"15m\":{}}}",
+            self.one_minute, self.five_minutes, self.fifteen_minutes
+        )
+    }
+}
+
+/// An exponentially weighted moving average updated once per tick.
+#[derive(Debug, Clone)]
+struct Ewma {
+    alpha: f64,
+    rate: f64,
+    initialized: bool,
+}
+
+impl Ewma {
+    fn new(window: Duration) -> Self {
+        let alpha = 1.0 - (-TICK_INTERVAL.as_secs_f64() / window.as_secs_f64()).exp();
+        Self {
+            alpha,
+            rate: 0.0,
+            initialized: false,
+        }
+    }
+
+    /// Folds `count` events seen during one tick into the average.
+    fn update(&mut self, count: u64) {
+        let instant_rate = count as f64 / TICK_INTERVAL.as_secs_f64();
+        if self.initialized {
+            self.rate += self.alpha * (instant_rate - self.rate);
+        } else {
+            self.rate = instant_rate;
+            self.initialized = true;
+        }
+    }
+
+    /// Applies `ticks` empty ticks at once.
+    fn decay(&mut self, ticks: u32) {
+        if self.initialized {
+            self.rate *= (1.0 - self.alpha).powi(ticks as i32);
+        }
+    }
+}
+
+/// Tracks the rate of a single kind of event over the three windows.
+#[derive(Debug, Clone)]
+pub(crate) struct RateMeter {
+    uncounted: u64,
+    last_tick: Instant,
+    windows: [Ewma; 3],
+}
+
+impl RateMeter {
+    pub(crate) fn new(now: Instant) -> Self {
+        Self {
+            uncounted: 0,
+            last_tick: now,
+            windows: [
+                Ewma::new(Duration::from_secs(60)),
+                Ewma::new(Duration::from_secs(5 * 60)),
+                Ewma::new(Duration::from_secs(15 * 60)),
+            ],
+        }
+    }
+
+    /// Records one event happening at `now`.
+    pub(crate) fn mark(&mut self, now: Instant) {
+        self.mark_many(now, 1);
+    }
+
+    /// Records `count` events happening at `now`.
+    pub(crate) fn mark_many(&mut self, now: Instant, count: u64) {
+        self.tick_to(now);
+        self.uncounted += count;
+    }
+
+    /// Returns the rates as they stand at `now` without mutating the meter.
+    pub(crate) fn rates_at(&self, now: Instant) -> RateWindows {
+        let mut meter = self.clone();
+        meter.tick_to(now);
+        RateWindows {
+            one_minute: meter.windows[0].rate,
+            five_minutes: meter.windows[1].rate,
+            fifteen_minutes: meter.windows[2].rate,
+        }
+    }
+
+    /// Processes every tick boundary crossed since the last tick.
+    fn tick_to(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.last_tick);
+        let ticks = (elapsed.as_nanos() / TICK_INTERVAL.as_nanos()) as u32;
+        if ticks == 0 {
+            return;
+        }
+
+        // O primeiro tick recebe os eventos acumulados; os demais estavam vazios
+        for window in &mut self.windows {
+            window.update(self.uncounted);
+            window.decay(ticks - 1);
+        }
+        self.uncounted = 0;
+        self.last_tick += TICK_INTERVAL * ticks;
+    }
+}
+
+/// The counters behind `CacheStats`, owned by each cache.
+#[derive(Debug, Clone)]
+pub(crate) struct StatsRecorder {
+    hits: u64,
+    misses: u64,
+    inserts: u64,
+    removals: u64,
+    expirations: u64,
+    evictions: u64,
+    proactive_evictions: u64,
+    bloom_rebuilds: u64,
+    rejected_writes: u64,
+    hit_meter: RateMeter,
+    miss_meter: RateMeter,
+    eviction_meter: RateMeter,
+}
+
+impl StatsRecorder {
+    /// Creates a recorder whose rates start at `now` on the cache's clock.
+    pub(crate) fn new(now: Instant) -> Self {
+        Self {
+            hits: 0,
+            misses: 0,
+            inserts: 0,
+            removals: 0,
+            expirations: 0,
+            evictions: 0,
+            proactive_evictions: 0,
+            bloom_rebuilds: 0,
+            rejected_writes: 0,
+            hit_meter: RateMeter::new(now),
+            miss_meter: RateMeter::new(now),
+            eviction_meter: RateMeter::new(now),
+        }
+    }
+
+    pub(crate) fn record_hit(&mut self, now: Instant) {
+        self.record_hits(now, 1);
+    }
+
+    pub(crate) fn record_miss(&mut self, now: Instant) {
+        self.record_misses(now, 1);
+    }
+
+    pub(crate) fn record_hits(&mut self, now: Instant, count: u64) {
+        self.hits += count;
+        self.hit_meter.mark_many(now, count);
+    }
+
+    pub(crate) fn record_misses(&mut self, now: Instant, count: u64) {
+        self.misses += count;
+        self.miss_meter.mark_many(now, count);
+    }
+
+    pub(crate) fn record_insert(&mut self) {
+        self.inserts += 1;
+    }
+
+    pub(crate) fn record_rejected_write(&mut self) {
+        self.rejected_writes += 1;
+    }
+
+    pub(crate) fn record_removal(&mut self) {
+        self.removals += 1;
+    }
+
+    pub(crate) fn record_expiration(&mut self) {
+        self.expirations += 1;
+    }
+
+    pub(crate) fn record_eviction(&mut self, now: Instant) {
+        self.evictions += 1;
+        self.eviction_meter.mark(now);
+    }
+
+    pub(crate) fn record_proactive_eviction(&mut self) {
+        self.proactive_evictions += 1;
+    }
+
+    pub(crate) fn record_bloom_rebuild(&mut self) {
+        self.bloom_rebuilds += 1;
+    }
+
+    /// Returns the statistics with the rates as of `now`.
+    pub(crate) fn snapshot(&self, entries: usize, now: Instant) -> CacheStats {
+        CacheStats {
+            entries,
+            hits: self.hits,
+            misses: self.misses,
+            inserts: self.inserts,
+            removals: self.removals,
+            expirations: self.expirations,
+            evictions: self.evictions,
+            proactive_evictions: self.proactive_evictions,
+            callback_panics: 0,
+            dropped_notifications: 0,
+            store_errors: 0,
+            aof_errors: 0,
+            persistence_degraded: false,
+            rejected_writes: self.rejected_writes,
+            bloom_fill_ratio: 0.0,
+            bloom_false_positive_rate: 0.0,
+            bloom_rebuilds: self.bloom_rebuilds,
+            hit_rate: self.hit_meter.rates_at(now),
+            miss_rate: self.miss_meter.rates_at(now),
+            eviction_rate: self.eviction_meter.rates_at(now),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rates_before_first_tick_are_zero() {
+        let start = Instant::now();
+        let mut meter = RateMeter::new(start);
+        meter.mark(start);
+        meter.mark(start);
+
+        assert_eq!(meter.rates_at(start + Duration::from_secs(1)), RateWindows::default());
+    }
+
+    #[test]
+    fn test_steady_rate() {
+        let start = Instant::now();
+        let mut meter = RateMeter::new(start);
+
+        // 10 eventos por tick de 5 segundos = 2 eventos por segundo
+        for tick in 0..12 {
+            let at = start + TICK_INTERVAL * tick;
+            for _ in 0..10 {
+                meter.mark(at);
+            }
+        }
+
+        let rates = meter.rates_at(start + TICK_INTERVAL * 12);
+        assert!((rates.one_minute - 2.0).abs() < 1e-9);
+        assert!((rates.five_minutes - 2.0).abs() < 1e-9);
+        assert!((rates.fifteen_minutes - 2.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_shorter_windows_decay_faster() {
+        let start = Instant::now();
+        let mut meter = RateMeter::new(start);
+        for _ in 0..50 {
+            meter.mark(start);
+        }
+
+        let rates = meter.rates_at(start + Duration::from_secs(5 * 60));
+        assert!(rates.one_minute < rates.five_minutes);
+        assert!(rates.five_minutes < rates.fifteen_minutes);
+        assert!(rates.one_minute < 0.1);
+    }
+
+    #[test]
+    fn test_hit_ratio() {
+        let now = Instant::now();
+        let mut recorder = StatsRecorder::new(now);
+        assert_eq!(recorder.snapshot(0, now).hit_ratio(), 0.0);
+
+        recorder.record_hit(now);
+        recorder.record_hit(now);
+        recorder.record_hit(now);
+        recorder.record_miss(now);
+        assert_eq!(recorder.snapshot(0, now).hit_ratio(), 0.75);
+    }
+
+    #[test]
+    fn test_to_json() {
+        let stats = CacheStats {
+            entries: 3,
+            hits: 3,
+            misses: 1,
+            inserts: 4,
+            removals: 1,
+            expirations: 0,
+            evictions: 0,
+            proactive_evictions: 0,
+            callback_panics: 0,
+            dropped_notifications: 0,
+            store_errors: 0,
+            aof_errors: 0,
+            persistence_degraded: false,
+            rejected_writes: 0,
+            bloom_fill_ratio: 0.25,
+            bloom_false_positive_rate: 0.0,
+            bloom_rebuilds: 0,
+            hit_rate: RateWindows {
+                one_minute: 0.5,
+                five_minutes: 0.25,
+                fifteen_minutes: 0.125,
+            },
+            miss_rate: RateWindows::default(),
+            eviction_rate: RateWindows::default(),
+        };
+
+        assert_eq!(
+            stats.to_json(),
+            concat!(
+                "{\"schema_version\":1,\"entries\":3,\"hits\":3,\"misses\":1,",
+                "\"inserts\":4,\"removals\":1,\"expirations\":0,\"evictions\":0,",
+                "\"proactive_evictions\":0,\"callback_panics\":0,\"dropped_notifications\":0,\"store_errors\":0,\"aof_errors\":0,\"persistence_degraded\":false,\"rejected_writes\":0,\"bloom_fill_ratio\":0.25,\"bloom_false_positive_rate\":0,\"bloom_rebuilds\":0,\"hit_ratio\":0.75,\"rates\":{\"hits_per_sec\":{\"1m\":0.5,\"5m\":0.25,\"15m\":0.125},",
+                "\"misses_per_sec\":{\"1m\":0,\"5m\":0,\"15m\":0},",
+                "\"evictions_per_sec\":{\"1m\":0,\"5m\":0,\"15m\":0}}}"
+            )
+        );
+    }
+}
@@ -0,0 +1,632 @@
+use core::fmt;
+#[cfg(not(feature = "std"))]
+use core::marker::PhantomData;
+#[cfg(feature = "std")]
+use std::any::Any;
+#[cfg(feature = "std")]
+use std::collections::HashMap;
+#[cfg(feature = "std")]
+use std::error::Error;
+#[cfg(feature = "std")]
+use std::mem;
+#[cfg(feature = "std")]
+use std::sync::atomic::{AtomicU64, Ordering};
+#[cfg(feature = "std")]
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, PoisonError};
+#[cfg(feature = "std")]
+use std::thread::{self, JoinHandle};
+#[cfg(feature = "std")]
+use std::time::Duration;
+
+#[cfg(feature = "persistence")]
+use crate::aof::{self, AppendLog};
+#[cfg(feature = "cluster")]
+use crate::cluster::{Replicator, Transport};
+use crate::clock::Instant;
+use crate::entry::CacheEntry;
+#[cfg(feature = "std")]
+use crate::error::CacheError;
+use crate::value::CacheValue;
+
+#[cfg(feature = "std")]
+/// The error type returned by [`BackingStore`] implementations.
+pub type StoreError = Box<dyn Error + Send + Sync>;
+
+#[cfg(feature = "std")]
+/// The system of record behind a cache, typically a database.
+///
+/// A cache built with `write_through` or `write_back` loads missing keys
+/// from the store on `get_with()` and forwards its writes and removals to it.
+/// Keys leaving the cache on their own (expiration, eviction, `clear()`)
+/// are not deleted from the store: dropping a cached copy doesn't delete
+/// the data.
+///
+/// `V` is the borrowed value type of the cache: `str` for
+/// `DistributedHashTable` and `BTreeCache`, `[u8]` for `BytesCache`.
+///
+/// # Examples
+///
+/// ```
+/// use spectra_cache::{BackingStore, DistributedHashTable, GetOptions, StoreError};
+/// use std::collections::HashMap;
+///
+/// struct Database(HashMap<String, String>);
+///
+/// impl BackingStore for Database {
+///     fn load(&mut self, key: &str) -> Result<Option<String>, StoreError> {
+///         Ok(self.0.get(key).cloned())
+///     }
+///
+///     fn store(&mut self, key: &str, value: &str) -> Result<(), StoreError> {
+///         self.0.insert(key.to_string(), value.to_string());
+///         Ok(())
+///     }
+///
+///     fn delete(&mut self, key: &str) -> Result<(), StoreError> {
+///         self.0.remove(key);
+///         Ok(())
+///     }
+/// }
+///
+/// let rows = HashMap::from([("user:1".to_string(), "Alice".to_string())]);
+/// let mut cache = DistributedHashTable::builder().write_through(Database(rows)).build();
+/// let read = GetOptions::default();
+/// assert_eq!(cache.get_with("user:1", &read), Some("Alice")); // loaded from the database
+/// cache.insert("user:2", "Bob"); // written to the database as well
+/// ```
+pub trait BackingStore<V: ?Sized + ToOwned = str>: Send {
+    /// Reads the value stored under `key`, or `None` if there is none.
+    fn load(&mut self, key: &str) -> Result<Option<V::Owned>, StoreError>;
+
+    /// Writes `value` under `key`, replacing any previous value.
+    fn store(&mut self, key: &str, value: &V) -> Result<(), StoreError>;
+
+    /// Deletes `key`. Deleting a missing key is not an error.
+    fn delete(&mut self, key: &str) -> Result<(), StoreError>;
+}
+
+#[cfg(feature = "std")]
+/// How durable a write must be before it is acknowledged, for
+/// `insert_with_durability()`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Durability {
+    /// Acknowledged once the write is in memory, like a plain `insert()`.
+    Memory,
+    /// Acknowledged once the write is in the backing store, right away for
+    /// `write_through` and after a flush for `write_back`, and synced to
+    /// the append-only log if one is enabled.
+    Disk,
+    /// Acknowledged once `n` of the replicas added with `add_replica()`
+    /// have applied the write and every write before it. `Replicated(0)` is
+    /// acknowledged like `Memory`.
+    Replicated(usize),
+}
+
+#[cfg(feature = "std")]
+type SharedStore<R> = Mutex<Box<dyn BackingStore<R>>>;
+
+#[cfg(feature = "std")]
+/// A backing store as kept by `CacheConfig`, with its value type erased so
+/// the configuration doesn't depend on the cache type.
+#[derive(Clone)]
+pub(crate) struct StoreConfig {
+    store: Arc<dyn Any + Send + Sync>,
+    write_back: Option<Duration>,
+}
+
+#[cfg(feature = "std")]
+impl StoreConfig {
+    pub(crate) fn new<R>(store: impl BackingStore<R> + 'static, write_back: Option<Duration>) -> Self
+    where
+        R: ?Sized + ToOwned + 'static,
+    {
+        let store: Box<dyn BackingStore<R>> = Box::new(store);
+        Self {
+            store: Arc::new(Mutex::new(store)),
+            write_back,
+        }
+    }
+
+    /// Returns the flush interval, or `None` for a write-through store.
+    pub(crate) fn write_back(&self) -> Option<Duration> {
+        self.write_back
+    }
+}
+
+#[cfg(feature = "std")]
+impl fmt::Debug for StoreConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StoreConfig").field("write_back", &self.write_back).finish()
+    }
+}
+
+#[cfg(feature = "std")]
+/// Where a cache's writes go besides memory: the backing store, which gets
+/// them immediately (write-through) or through the flusher thread
+/// (write-back), the append-only log and the replicas.
+///
+/// Without a configured store, log or replica every method is a no-op.
+pub(crate) struct WriteStore<V: CacheValue> {
+    link: Option<Link<V>>,
+    #[cfg(feature = "persistence")]
+    log: Option<AppendLog>,
+    #[cfg(feature = "cluster")]
+    replicator: Option<Replicator>,
+    /// Records of the transaction being applied, held back to go out as
+    /// one batch record
+    #[cfg(feature = "persistence")]
+    batch: Mutex<Option<Vec<u8>>>,
+}
+
+#[cfg(feature = "std")]
+struct Link<V: CacheValue> {
+    store: Arc<SharedStore<V::Ref>>,
+    write_back: Option<Arc<WriteBack<V>>>,
+    flusher: Option<JoinHandle<()>>,
+    errors: Arc<AtomicU64>,
+}
+
+#[cfg(feature = "std")]
+/// The write-back queue and the flusher's controls.
+///
+/// Every change is numbered in order, and `durable` is the highest number
+/// up to which every change has reached the store, so waiting for a write
+/// means waiting for `durable` to pass its number.
+struct WriteBack<V> {
+    pending: Mutex<Pending<V>>,
+    control: Mutex<Control>,
+    wake: Condvar,
+    durable: Mutex<u64>,
+    flushed: Condvar,
+}
+
+#[cfg(feature = "std")]
+/// Changes not yet written to the store, keyed by cache key, with their
+/// sequence number: `Some` for a write, `None` for a delete. Only the
+/// latest change per key is kept.
+struct Pending<V> {
+    changes: HashMap<String, (u64, Option<V>)>,
+    sequence: u64,
+}
+
+#[cfg(feature = "std")]
+impl<V> Pending<V> {
+    fn push(&mut self, key: &str, change: Option<V>) {
+        self.sequence += 1;
+        self.changes.insert(key.to_string(), (self.sequence, change));
+    }
+}
+
+#[cfg(feature = "std")]
+#[derive(Default)]
+struct Control {
+    stopped: bool,
+    flush_requested: bool,
+}
+
+#[cfg(feature = "std")]
+impl<V: CacheValue> Default for WriteStore<V> {
+    fn default() -> Self {
+        Self {
+            link: None,
+            #[cfg(feature = "persistence")]
+            log: None,
+            #[cfg(feature = "cluster")]
+            replicator: None,
+            #[cfg(feature = "persistence")]
+            batch: Mutex::new(None),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<V: CacheValue> WriteStore<V> {
+    pub(crate) fn from_config(config: Option<&StoreConfig>) -> Self {
+        let Some(config) = config else {
+            return Self::default();
+        };
+        let store = Arc::clone(&config.store)
+            .downcast::<SharedStore<V::Ref>>()
+            .unwrap_or_else(|_| panic!("backing store value type doesn't match the cache"));
+        let errors = Arc::new(AtomicU64::new(0));
+
+        let (write_back, flusher) = match config.write_back {
+            Some(interval) => {
+                let write_back = Arc::new(WriteBack {
+                    pending: Mutex::new(Pending {
+                        changes: HashMap::new(),
+                        sequence: 0,
+                    }),
+                    control: Mutex::new(Control::default()),
+                    wake: Condvar::new(),
+                    durable: Mutex::new(0),
+                    flushed: Condvar::new(),
+                });
+                let flusher = {
+                    let (write_back, store, errors) = (Arc::clone(&write_back), Arc::clone(&store), Arc::clone(&errors));
+                    thread::Builder::new()
+                        .name("spectra-cache-write-back".to_string())
+                        .spawn(move || {
+                            while !write_back.sleep(interval) {
+                                write_back.flush(&store, &errors);
+                            }
+                        })
+                        .expect("failed to spawn the write-back flusher thread")
+                };
+                (Some(write_back), Some(flusher))
+            }
+            None => (None, None),
+        };
+
+        Self {
+            link: Some(Link {
+                store,
+                write_back,
+                flusher,
+                errors,
+            }),
+            #[cfg(feature = "persistence")]
+            log: None,
+            #[cfg(feature = "cluster")]
+            replicator: None,
+            #[cfg(feature = "persistence")]
+            batch: Mutex::new(None),
+        }
+    }
+
+    /// Starts appending every change to `log`, closing the previous log if any.
+    #[cfg(feature = "persistence")]
+    pub(crate) fn set_log(&mut self, log: Option<AppendLog>) {
+        self.log = log;
+    }
+
+    #[cfg(feature = "persistence")]
+    pub(crate) fn log(&self) -> Option<&AppendLog> {
+        self.log.as_ref()
+    }
+
+    /// Starts streaming every change to the replica at the other end of
+    /// `transport`, after the `initial` records.
+    #[cfg(feature = "cluster")]
+    pub(crate) fn add_replica(&mut self, transport: Box<dyn Transport>, initial: Vec<Vec<u8>>) {
+        self.replicator.get_or_insert_with(Replicator::new).add(transport, initial);
+    }
+
+    /// Returns how many replicas are connected.
+    #[cfg(feature = "cluster")]
+    pub(crate) fn replicas(&self) -> usize {
+        self.replicator.as_ref().map_or(0, Replicator::connected)
+    }
+
+    /// Waits up to `timeout` for `replicas` replicas to apply every change
+    /// made so far.
+    #[cfg_attr(not(feature = "cluster"), allow(unused_variables))]
+    pub(crate) fn wait_for_replicas(&self, replicas: usize, timeout: Duration) -> Result<(), CacheError> {
+        #[cfg(feature = "cluster")]
+        if let Some(replicator) = &self.replicator {
+            return replicator.wait_for(replicas, timeout);
+        }
+        Err(CacheError::NotDurable { reason: "the cache has no replicas" })
+    }
+
+    /// Appends the record `encode` makes to the log and sends it to the
+    /// replicas, encoding it only if one of them is there.
+    #[cfg(feature = "persistence")]
+    fn record(&self, encode: impl FnOnce() -> Vec<u8>) {
+        #[cfg(feature = "cluster")]
+        let replicated = self.replicator.is_some();
+        #[cfg(not(feature = "cluster"))]
+        let replicated = false;
+        if self.log.is_none() && !replicated {
+            return;
+        }
+        let record = encode();
+        if let Some(batch) = lock(&self.batch).as_mut() {
+            batch.extend_from_slice(&record);
+            return;
+        }
+        if let Some(log) = &self.log {
+            log.append(&record);
+        }
+        #[cfg(feature = "cluster")]
+        if let Some(replicator) = &self.replicator {
+            replicator.send(&record);
+        }
+    }
+
+    /// Holds back the records of the changes that follow until `end_batch`.
+    pub(crate) fn begin_batch(&self) {
+        #[cfg(feature = "persistence")]
+        lock(&self.batch).get_or_insert_with(Vec::new);
+    }
+
+    /// Sends the records held back since `begin_batch` as one batch record.
+    /// The backing store has had every change already, as they happened.
+    pub(crate) fn end_batch(&self) {
+        #[cfg(feature = "persistence")]
+        {
+            let records = lock(&self.batch).take();
+            if let Some(records) = records.filter(|records| !records.is_empty()) {
+                self.record(|| aof::batch_record(&records));
+            }
+        }
+    }
+
+    /// Forwards a write of `entry` under `key`, made at `now` on the
+    /// cache's clock.
+    #[cfg_attr(not(feature = "persistence"), allow(unused_variables))]
+    pub(crate) fn written(&self, key: &str, entry: &CacheEntry<V>, now: Instant) {
+        #[cfg(feature = "persistence")]
+        self.record(|| aof::set_record(key, entry, now));
+        let Some(link) = &self.link else {
+            return;
+        };
+        let value = entry.value();
+        match &link.write_back {
+            Some(write_back) => {
+                lock(&write_back.pending).push(key, Some(V::from_ref(value)));
+            }
+            None => link.record(lock(&link.store).store(key, value)),
+        }
+    }
+
+    /// Records a new TTL or idle timeout for the entry under `key`. Only the
+    /// log and the replicas care: the store keeps no expirations.
+    #[cfg_attr(not(feature = "persistence"), allow(unused_variables))]
+    pub(crate) fn retimed(&self, key: &str, entry: &CacheEntry<V>, now: Instant) {
+        #[cfg(feature = "persistence")]
+        self.record(|| aof::retime_record(key, entry, now));
+    }
+
+    /// Records that `key` left the cache without being deleted from the
+    /// store, as with evictions and migrations.
+    #[cfg_attr(not(feature = "persistence"), allow(unused_variables))]
+    pub(crate) fn dropped(&self, key: &str) {
+        #[cfg(feature = "persistence")]
+        self.record(|| aof::delete_record(key));
+    }
+
+    /// Records that the cache was cleared, which leaves the store alone.
+    pub(crate) fn cleared(&self) {
+        #[cfg(feature = "persistence")]
+        self.record(aof::clear_record);
+    }
+
+    /// Forwards the removal of `key`.
+    pub(crate) fn deleted(&self, key: &str) {
+        self.dropped(key);
+        let Some(link) = &self.link else {
+            return;
+        };
+        match &link.write_back {
+            Some(write_back) => {
+                lock(&write_back.pending).push(key, None);
+            }
+            None => link.record(lock(&link.store).delete(key)),
+        }
+    }
+
+    /// Reads `key` from the store, seeing write-back changes that were not
+    /// flushed yet.
+    pub(crate) fn load(&self, key: &str) -> Option<V> {
+        let link = self.link.as_ref()?;
+        if let Some(write_back) = &link.write_back {
+            if let Some((_, pending)) = lock(&write_back.pending).changes.get(key) {
+                return pending.as_ref().map(|value| V::from_ref(value.view()));
+            }
+        }
+        match lock(&link.store).load(key) {
+            Ok(value) => value.map(V::from_owned),
+            Err(_) => {
+                link.errors.fetch_add(1, Ordering::Relaxed);
+                None
+            }
+        }
+    }
+
+    /// Writes every pending write-back change to the store now.
+    pub(crate) fn flush(&self) {
+        if let Some(link) = &self.link {
+            if let Some(write_back) = &link.write_back {
+                write_back.flush(&link.store, &link.errors);
+            }
+        }
+    }
+
+    /// Has the flusher write every change made so far and waits up to
+    /// `timeout` for it. Returns false if some change is still pending.
+    ///
+    /// Without write-back there is never anything to wait for.
+    pub(crate) fn flush_and_wait(&self, timeout: Duration) -> bool {
+        match self.link.as_ref().and_then(|link| link.write_back.as_ref()) {
+            Some(write_back) => {
+                let target = lock(&write_back.pending).sequence;
+                write_back.wait_until_durable(target, timeout)
+            }
+            None => true,
+        }
+    }
+
+    /// Checks that the writes made since the store had `errors_before`
+    /// failures reached the store, waiting up to `timeout` for write-back,
+    /// and syncs the log.
+    pub(crate) fn acknowledge(&self, errors_before: u64, timeout: Duration) -> Result<(), CacheError> {
+        #[cfg(feature = "persistence")]
+        let logged = match &self.log {
+            Some(log) if log.sync().is_err() => {
+                return Err(CacheError::NotDurable { reason: "the append-only log could not be synced" });
+            }
+            log => log.is_some(),
+        };
+        #[cfg(not(feature = "persistence"))]
+        let logged = false;
+        let Some(link) = &self.link else {
+            return if logged {
+                Ok(())
+            } else {
+                Err(CacheError::NotDurable { reason: "the cache has no backing store or append-only log" })
+            };
+        };
+        match &link.write_back {
+            // Write-through já gravou, ou falhou, antes de voltar
+            None if link.errors.load(Ordering::Relaxed) > errors_before => Err(CacheError::NotDurable {
+                reason: "the backing store rejected the write",
+            }),
+            None => Ok(()),
+            Some(_) if self.flush_and_wait(timeout) => Ok(()),
+            Some(_) => Err(CacheError::NotDurable {
+                reason: "timed out waiting for the backing store",
+            }),
+        }
+    }
+
+    /// Returns how many store calls failed.
+    pub(crate) fn errors(&self) -> u64 {
+        self.link.as_ref().map_or(0, |link| link.errors.load(Ordering::Relaxed))
+    }
+}
+
+#[cfg(feature = "std")]
+impl<V: CacheValue> Link<V> {
+    fn record(&self, result: Result<(), StoreError>) {
+        if result.is_err() {
+            self.errors.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<V: CacheValue> WriteBack<V> {
+    /// Sleeps for `interval` unless the cache is dropped or a flush is
+    /// requested first.
+    ///
+    /// Returns true once the flusher should stop.
+    fn sleep(&self, interval: Duration) -> bool {
+        let control = lock(&self.control);
+        let (mut control, _) = self
+            .wake
+            .wait_timeout_while(control, interval, |control| !control.stopped && !control.flush_requested)
+            .unwrap_or_else(PoisonError::into_inner);
+        control.flush_requested = false;
+        control.stopped
+    }
+
+    /// Wakes the flusher and waits up to `timeout` for every change up to
+    /// `target` to reach the store.
+    fn wait_until_durable(&self, target: u64, timeout: Duration) -> bool {
+        lock(&self.control).flush_requested = true;
+        self.wake.notify_all();
+        let durable = lock(&self.durable);
+        let (durable, _) = self
+            .flushed
+            .wait_timeout_while(durable, timeout, |durable| *durable < target)
+            .unwrap_or_else(PoisonError::into_inner);
+        *durable >= target
+    }
+
+    fn flush(&self, store: &SharedStore<V::Ref>, errors: &AtomicU64) {
+        // Trava o store antes de esvaziar a fila: uma leitura concorrente
+        // espera a escrita terminar em vez de ler o valor antigo do store
+        let mut store = lock(store);
+        let (pending, flushed_up_to) = {
+            let mut pending = lock(&self.pending);
+            (mem::take(&mut pending.changes), pending.sequence)
+        };
+        let mut first_failure = None;
+        for (key, (sequence, change)) in pending {
+            let result = match &change {
+                Some(value) => store.store(&key, value.view()),
+                None => store.delete(&key),
+            };
+            if result.is_err() {
+                errors.fetch_add(1, Ordering::Relaxed);
+                first_failure = Some(first_failure.map_or(sequence, |first: u64| first.min(sequence)));
+                // Mantém a mudança para a próxima tentativa, a menos que já exista uma mais nova
+                lock(&self.pending).changes.entry(key).or_insert((sequence, change));
+            }
+        }
+
+        let mut durable = lock(&self.durable);
+        *durable = (*durable).max(first_failure.map_or(flushed_up_to, |first| first - 1));
+        self.flushed.notify_all();
+    }
+}
+
+#[cfg(feature = "std")]
+impl<V: CacheValue> Drop for WriteStore<V> {
+    fn drop(&mut self) {
+        // Para o flusher e grava o que ainda estiver pendente
+        let Some(link) = self.link.as_mut() else {
+            return;
+        };
+        if let Some(write_back) = &link.write_back {
+            lock(&write_back.control).stopped = true;
+            write_back.wake.notify_all();
+            if let Some(flusher) = link.flusher.take() {
+                let _ = flusher.join();
+            }
+            write_back.flush(&link.store, &link.errors);
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<V: CacheValue> fmt::Debug for WriteStore<V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mode = match &self.link {
+            None => "none",
+            Some(Link { write_back: None, .. }) => "write-through",
+            Some(_) => "write-back",
+        };
+        f.debug_struct("WriteStore").field("mode", &mode).finish()
+    }
+}
+
+#[cfg(feature = "std")]
+fn lock<T: ?Sized>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Without `std` a cache has no backing store, log or replicas, so its
+/// writes go nowhere but memory.
+#[cfg(not(feature = "std"))]
+pub(crate) struct WriteStore<V>(PhantomData<fn() -> V>);
+
+#[cfg(not(feature = "std"))]
+impl<V> Default for WriteStore<V> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+#[cfg(not(feature = "std"))]
+impl<V: CacheValue> WriteStore<V> {
+    pub(crate) fn begin_batch(&self) {}
+
+    pub(crate) fn end_batch(&self) {}
+
+    pub(crate) fn written(&self, _key: &str, _entry: &CacheEntry<V>, _now: Instant) {}
+
+    pub(crate) fn retimed(&self, _key: &str, _entry: &CacheEntry<V>, _now: Instant) {}
+
+    pub(crate) fn dropped(&self, _key: &str) {}
+
+    pub(crate) fn cleared(&self) {}
+
+    pub(crate) fn deleted(&self, _key: &str) {}
+
+    pub(crate) fn load(&self, _key: &str) -> Option<V> {
+        None
+    }
+
+    pub(crate) fn errors(&self) -> u64 {
+        0
+    }
+}
+
+#[cfg(not(feature = "std"))]
+impl<V> fmt::Debug for WriteStore<V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WriteStore").field("mode", &"none").finish()
+    }
+}
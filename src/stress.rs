@@ -0,0 +1,558 @@
+//! Soak testing: concurrent workloads that check, as they run, that a cache
+//! behaves like one register per key and honours its TTLs.
+//!
+//! [`Stress`] describes a workload and runs it against a [`StressTarget`]
+//! from several threads at once. Every operation is timed, and every value
+//! read is checked against the writes that could have produced it:
+//!
+//! - a read returns a value that was written, and not before its write
+//!   began;
+//! - a read doesn't return a value that another write or removal had
+//!   replaced before the read began;
+//! - a read doesn't return a value whose TTL ran out before the read
+//!   began;
+//! - a read doesn't miss a value that was written without a TTL and not
+//!   touched since, unless evictions are allowed.
+//!
+//! The workload runs in rounds on fresh keys, and each round is checked
+//! while the next one runs, so long soak runs keep memory bounded.
+//!
+//! # Examples
+//!
+//! ```
+//! use spectra_cache::stress::Stress;
+//! use spectra_cache::DistributedHashTable;
+//! use std::sync::Mutex;
+//! use std::time::Duration;
+//!
+//! let cache = Mutex::new(DistributedHashTable::new());
+//! let report = Stress::new()
+//!     .threads(4)
+//!     .keys(8)
+//!     .operations(500)
+//!     .expiring(20, Duration::from_millis(1))
+//!     .run(&cache);
+//! assert!(report.is_ok(), "{:?}", report.violations);
+//! assert_eq!(report.operations, 4 * 500);
+//! ```
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use std::thread;
+use std::time::Duration;
+
+use crate::clock::Instant;
+use crate::{Cache, ConcurrentCache, ShardedCache};
+
+/// How many violations a report keeps; the rest are only counted.
+const MAX_REPORTED_VIOLATIONS: usize = 100;
+
+/// A cache that [`Stress`] can drive from several threads at once.
+///
+/// Implemented for a `Mutex` around any text [`Cache`] or a
+/// [`ShardedCache`], and for a [`ConcurrentCache`], which locks its shards
+/// on its own.
+pub trait StressTarget: Sync {
+    /// Returns the live value under `key`.
+    fn get(&self, key: &str) -> Option<String>;
+
+    /// Stores `value` under `key`, expiring after `ttl` if given.
+    fn insert(&self, key: &str, value: &str, ttl: Option<Duration>);
+
+    /// Removes `key`.
+    fn remove(&self, key: &str);
+}
+
+impl<C: Cache + Send> StressTarget for Mutex<C> {
+    fn get(&self, key: &str) -> Option<String> {
+        lock(self).get(key).map(str::to_string)
+    }
+
+    fn insert(&self, key: &str, value: &str, ttl: Option<Duration>) {
+        match ttl {
+            Some(ttl) => lock(self).insert_with_ttl(key, value, ttl),
+            None => lock(self).insert(key, value),
+        }
+    }
+
+    fn remove(&self, key: &str) {
+        lock(self).remove(key);
+    }
+}
+
+impl StressTarget for Mutex<ShardedCache> {
+    fn get(&self, key: &str) -> Option<String> {
+        lock(self).get(key).map(str::to_string)
+    }
+
+    fn insert(&self, key: &str, value: &str, ttl: Option<Duration>) {
+        match ttl {
+            Some(ttl) => lock(self).insert_with_ttl(key, value, ttl),
+            None => lock(self).insert(key, value),
+        }
+    }
+
+    fn remove(&self, key: &str) {
+        lock(self).remove(key);
+    }
+}
+
+impl StressTarget for ConcurrentCache {
+    fn get(&self, key: &str) -> Option<String> {
+        ConcurrentCache::get(self, key)
+    }
+
+    fn insert(&self, key: &str, value: &str, ttl: Option<Duration>) {
+        match ttl {
+            Some(ttl) => self.insert_with_ttl(key, value, ttl),
+            None => ConcurrentCache::insert(self, key, value),
+        }
+    }
+
+    fn remove(&self, key: &str) {
+        ConcurrentCache::remove(self, key);
+    }
+}
+
+/// A concurrent workload that checks per-key consistency and TTLs as it
+/// runs. See the [module documentation](self).
+#[derive(Debug, Clone)]
+pub struct Stress {
+    threads: usize,
+    keys: usize,
+    operations: usize,
+    rounds: usize,
+    read_percent: u32,
+    remove_percent: u32,
+    expiring: Option<(u32, Duration)>,
+    evictions: bool,
+    seed: u64,
+}
+
+impl Default for Stress {
+    fn default() -> Self {
+        Self {
+            threads: 4,
+            keys: 16,
+            operations: 1000,
+            rounds: 1,
+            read_percent: 50,
+            remove_percent: 10,
+            expiring: None,
+            evictions: false,
+            seed: 0,
+        }
+    }
+}
+
+impl Stress {
+    /// Creates a workload of 4 threads doing 1000 operations each on 16
+    /// keys: half reads, a tenth removals and the rest inserts without TTL.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets how many threads run operations at once. Panics if zero.
+    pub fn threads(mut self, threads: usize) -> Self {
+        assert!(threads > 0, "a stress run needs at least one thread");
+        self.threads = threads;
+        self
+    }
+
+    /// Sets how many keys the threads share; fewer keys mean more
+    /// contention. Panics if zero.
+    pub fn keys(mut self, keys: usize) -> Self {
+        assert!(keys > 0, "a stress run needs at least one key");
+        self.keys = keys;
+        self
+    }
+
+    /// Sets how many operations each thread runs per round.
+    pub fn operations(mut self, operations: usize) -> Self {
+        self.operations = operations;
+        self
+    }
+
+    /// Sets how many rounds to run, each on fresh keys.
+    pub fn rounds(mut self, rounds: usize) -> Self {
+        self.rounds = rounds;
+        self
+    }
+
+    /// Sets which percentages of the operations are reads and removals;
+    /// the rest are inserts. Panics if they add up to more than 100.
+    pub fn mix(mut self, read_percent: u32, remove_percent: u32) -> Self {
+        assert!(read_percent + remove_percent <= 100, "the operation mix adds up to more than 100%");
+        self.read_percent = read_percent;
+        self.remove_percent = remove_percent;
+        self
+    }
+
+    /// Gives `percent` of the inserts a TTL of `ttl`.
+    pub fn expiring(mut self, percent: u32, ttl: Duration) -> Self {
+        assert!(percent <= 100, "the expiring percentage is more than 100%");
+        self.expiring = Some((percent, ttl));
+        self
+    }
+
+    /// Stops reporting reads that miss a value as violations, for caches
+    /// with a capacity or memory limit, which may evict entries at any time.
+    pub fn allow_evictions(mut self) -> Self {
+        self.evictions = true;
+        self
+    }
+
+    /// Sets the seed the threads pick keys and operations with, to repeat
+    /// a run.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Runs the workload against `target` and reports what it found.
+    pub fn run(&self, target: &impl StressTarget) -> StressReport {
+        let started = Instant::now();
+        let mut report = StressReport::default();
+        let mut previous: Option<(usize, Vec<Vec<Operation>>)> = None;
+        for round in 0..self.rounds {
+            let histories = thread::scope(|scope| {
+                let workers: Vec<_> = (0..self.threads)
+                    .map(|worker| scope.spawn(move || self.work(target, round, worker)))
+                    .collect();
+                // Confere a rodada anterior enquanto esta roda
+                if let Some((round, histories)) = previous.take() {
+                    self.check(round, histories, &mut report);
+                }
+                workers
+                    .into_iter()
+                    .map(|worker| worker.join().unwrap_or_else(|panic| std::panic::resume_unwind(panic)))
+                    .collect::<Vec<_>>()
+            });
+            report.operations += histories.iter().map(|history| history.len() as u64).sum::<u64>();
+            previous = Some((round, histories));
+        }
+        if let Some((round, histories)) = previous {
+            self.check(round, histories, &mut report);
+        }
+        report.elapsed = started.elapsed();
+        report
+    }
+
+    fn key_name(round: usize, key: usize) -> String {
+        format!("stress:{}:{}", round, key)
+    }
+
+    /// Runs one thread's share of a round, returning what it did.
+    fn work(&self, target: &impl StressTarget, round: usize, worker: usize) -> Vec<Operation> {
+        let mut rng = SplitMix(self.seed ^ ((round as u64) << 32) ^ worker as u64);
+        let keys: Vec<String> = (0..self.keys).map(|key| Self::key_name(round, key)).collect();
+        let mut history = Vec::with_capacity(self.operations);
+        for n in 0..self.operations {
+            let key = rng.below(self.keys as u64) as usize;
+            let roll = rng.below(100) as u32;
+            let start = Instant::now();
+            let kind = if roll < self.read_percent {
+                Kind::Get(target.get(&keys[key]))
+            } else if roll < self.read_percent + self.remove_percent {
+                target.remove(&keys[key]);
+                Kind::Remove
+            } else {
+                let ttl = self.expiring.and_then(|(percent, ttl)| (rng.below(100) < percent as u64).then_some(ttl));
+                // Valores únicos: cada leitura aponta para a escrita que a produziu
+                let value = format!("{}-{}", worker, n);
+                target.insert(&keys[key], &value, ttl);
+                Kind::Insert { value, ttl }
+            };
+            history.push(Operation { key, kind, start, end: Instant::now() });
+        }
+        history
+    }
+
+    fn check(&self, round: usize, histories: Vec<Vec<Operation>>, report: &mut StressReport) {
+        let mut by_key: Vec<Vec<Operation>> = (0..self.keys).map(|_| Vec::new()).collect();
+        for operation in histories.into_iter().flatten() {
+            by_key[operation.key].push(operation);
+        }
+        for (key, operations) in by_key.iter().enumerate() {
+            for (kind, observed) in check_key(operations, self.evictions) {
+                report.record(Violation { key: Self::key_name(round, key), kind, observed });
+            }
+        }
+    }
+}
+
+/// What a [`Stress`] run did and the violations it found.
+#[derive(Debug, Clone, Default)]
+pub struct StressReport {
+    /// How many operations ran.
+    pub operations: u64,
+    /// How many violations were found.
+    pub violation_count: u64,
+    /// The first violations found, up to 100.
+    pub violations: Vec<Violation>,
+    /// How long the run took, checks included.
+    pub elapsed: Duration,
+}
+
+impl StressReport {
+    /// Returns true if no violation was found.
+    pub fn is_ok(&self) -> bool {
+        self.violation_count == 0
+    }
+
+    /// Returns the operations run per second.
+    pub fn throughput(&self) -> f64 {
+        self.operations as f64 / self.elapsed.as_secs_f64().max(f64::MIN_POSITIVE)
+    }
+
+    fn record(&mut self, violation: Violation) {
+        self.violation_count += 1;
+        if self.violations.len() < MAX_REPORTED_VIOLATIONS {
+            self.violations.push(violation);
+        }
+    }
+}
+
+/// A read that no correct cache could have returned.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Violation {
+    /// The key that was read.
+    pub key: String,
+    /// What was wrong with the read.
+    pub kind: ViolationKind,
+    /// The value the read returned.
+    pub observed: Option<String>,
+}
+
+/// The ways a read can be wrong.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ViolationKind {
+    /// The value was never written to the key.
+    UnknownValue,
+    /// The read finished before the write of its value began.
+    FutureValue,
+    /// Another write or removal replaced the value before the read began.
+    StaleValue,
+    /// The value's TTL ran out before the read began.
+    ExpiredValue,
+    /// The read found nothing, although a value without TTL was written
+    /// before it began and nothing could have removed it.
+    LostValue,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let what = match self.kind {
+            ViolationKind::UnknownValue => "a value that was never written",
+            ViolationKind::FutureValue => "a value before it was written",
+            ViolationKind::StaleValue => "a value that had been replaced",
+            ViolationKind::ExpiredValue => "a value that had expired",
+            ViolationKind::LostValue => "nothing, although a value was live",
+        };
+        write!(f, "read of '{}' returned {}", self.key, what)?;
+        if let Some(observed) = &self.observed {
+            write!(f, " ('{}')", observed)?;
+        }
+        Ok(())
+    }
+}
+
+/// One timed operation on the key with index `key`.
+#[derive(Debug)]
+struct Operation {
+    key: usize,
+    kind: Kind,
+    start: Instant,
+    end: Instant,
+}
+
+#[derive(Debug)]
+enum Kind {
+    Get(Option<String>),
+    Insert { value: String, ttl: Option<Duration> },
+    Remove,
+}
+
+/// Checks the reads of one key against its writes.
+fn check_key(operations: &[Operation], evictions: bool) -> Vec<(ViolationKind, Option<String>)> {
+    // Escritas ordenadas pelo início, com o menor fim de cada sufixo:
+    // dá para saber em O(log n) se alguma começou e terminou entre dois instantes
+    let mut writes: Vec<&Operation> = operations.iter().filter(|op| !matches!(op.kind, Kind::Get(_))).collect();
+    writes.sort_by_key(|op| op.start);
+    let mut earliest_end = vec![None; writes.len() + 1];
+    for (i, op) in writes.iter().enumerate().rev() {
+        earliest_end[i] = Some(earliest_end[i + 1].map_or(op.end, |end: Instant| end.min(op.end)));
+    }
+
+    let values: HashMap<&str, (&Operation, Option<Duration>)> = operations
+        .iter()
+        .filter_map(|op| match &op.kind {
+            Kind::Insert { value, ttl } => Some((value.as_str(), (op, *ttl))),
+            _ => None,
+        })
+        .collect();
+
+    // Inserções sem TTL pelo fim, com o maior início de cada prefixo, e o
+    // que pode fazer um valor sumir (remoções e inserções com TTL) pelo
+    // início, com o maior fim de cada prefixo
+    let mut durable: Vec<&Operation> = operations
+        .iter()
+        .filter(|op| matches!(op.kind, Kind::Insert { ttl: None, .. }))
+        .collect();
+    durable.sort_by_key(|op| op.end);
+    let latest_start: Vec<Instant> = prefix_max(durable.iter().map(|op| op.start));
+    let mut disruptors: Vec<&Operation> = operations
+        .iter()
+        .filter(|op| matches!(op.kind, Kind::Remove | Kind::Insert { ttl: Some(_), .. }))
+        .collect();
+    disruptors.sort_by_key(|op| op.start);
+    let latest_end: Vec<Instant> = prefix_max(disruptors.iter().map(|op| op.end));
+
+    let mut violations = Vec::new();
+    for read in operations {
+        let Kind::Get(observed) = &read.kind else {
+            continue;
+        };
+        let violation = match observed {
+            Some(value) => match values.get(value.as_str()) {
+                None => Some(ViolationKind::UnknownValue),
+                Some((write, _)) if write.start > read.end => Some(ViolationKind::FutureValue),
+                Some((write, ttl)) => {
+                    let after = writes.partition_point(|op| op.start <= write.end);
+                    if earliest_end[after].is_some_and(|end| end < read.start) {
+                        Some(ViolationKind::StaleValue)
+                    } else if ttl.is_some_and(|ttl| read.start > write.end + ttl) {
+                        Some(ViolationKind::ExpiredValue)
+                    } else {
+                        None
+                    }
+                }
+            },
+            None if evictions => None,
+            None => {
+                let done = durable.partition_point(|op| op.end < read.start);
+                let started = disruptors.partition_point(|op| op.start < read.end);
+                match (done, started) {
+                    (0, _) => None,
+                    (_, 0) => Some(ViolationKind::LostValue),
+                    (done, started) => (latest_end[started - 1] <= latest_start[done - 1]).then_some(ViolationKind::LostValue),
+                }
+            }
+        };
+        if let Some(kind) = violation {
+            violations.push((kind, observed.clone()));
+        }
+    }
+    violations
+}
+
+fn prefix_max(times: impl Iterator<Item = Instant>) -> Vec<Instant> {
+    let mut max = None;
+    times
+        .map(|time| {
+            let current = max.map_or(time, |max: Instant| max.max(time));
+            max = Some(current);
+            current
+        })
+        .collect()
+}
+
+/// A small, seedable pseudo-random generator (SplitMix64).
+struct SplitMix(u64);
+
+impl SplitMix {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    fn below(&mut self, bound: u64) -> u64 {
+        self.next() % bound
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Builds an operation spanning `start..end` milliseconds after `base`.
+    fn op(base: Instant, start: u64, end: u64, kind: Kind) -> Operation {
+        let at = |ms| base + Duration::from_millis(ms);
+        Operation { key: 0, kind, start: at(start), end: at(end) }
+    }
+
+    fn insert(value: &str, ttl: Option<u64>) -> Kind {
+        Kind::Insert { value: value.to_string(), ttl: ttl.map(Duration::from_millis) }
+    }
+
+    fn get(value: Option<&str>) -> Kind {
+        Kind::Get(value.map(str::to_string))
+    }
+
+    fn kinds(operations: &[Operation], evictions: bool) -> Vec<ViolationKind> {
+        check_key(operations, evictions).into_iter().map(|(kind, _)| kind).collect()
+    }
+
+    #[test]
+    fn test_consistent_histories_pass() {
+        let base = Instant::now();
+        let history = [
+            op(base, 0, 2, get(None)),
+            op(base, 1, 3, insert("a", None)),
+            // Concorrente com a escrita: pode ver qualquer um dos dois
+            op(base, 2, 4, get(None)),
+            op(base, 2, 4, get(Some("a"))),
+            op(base, 5, 6, insert("b", None)),
+            op(base, 5, 7, get(Some("a"))),
+            op(base, 8, 9, get(Some("b"))),
+            op(base, 10, 11, Kind::Remove),
+            op(base, 12, 13, get(None)),
+            op(base, 14, 15, insert("c", Some(5))),
+            op(base, 16, 17, get(Some("c"))),
+            op(base, 30, 31, get(None)),
+        ];
+        assert_eq!(kinds(&history, false), []);
+    }
+
+    #[test]
+    fn test_violations_are_detected() {
+        let base = Instant::now();
+        let history = [
+            op(base, 0, 1, get(Some("nobody"))),
+            op(base, 2, 3, get(Some("a"))),
+            op(base, 4, 5, insert("a", None)),
+            op(base, 6, 7, insert("b", None)),
+            op(base, 8, 9, get(Some("a"))),
+            op(base, 10, 11, get(None)),
+            op(base, 20, 21, insert("c", Some(5))),
+            op(base, 40, 41, get(Some("c"))),
+        ];
+        assert_eq!(
+            kinds(&history, false),
+            [
+                ViolationKind::UnknownValue,
+                ViolationKind::FutureValue,
+                ViolationKind::StaleValue,
+                ViolationKind::LostValue,
+                ViolationKind::ExpiredValue,
+            ]
+        );
+        // Com evicções, uma leitura vazia não prova nada
+        assert_eq!(kinds(&history, true).len(), 4);
+    }
+
+    #[test]
+    fn test_seeded_runs_repeat() {
+        let mut a = SplitMix(7);
+        let mut b = SplitMix(7);
+        assert!((0..100).all(|_| a.below(10) == b.below(10)));
+    }
+}
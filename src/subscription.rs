@@ -0,0 +1,144 @@
+use alloc::string::String;
+#[cfg(feature = "std")]
+use alloc::string::ToString;
+#[cfg(feature = "std")]
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use std::sync::mpsc::{self, Receiver, Sender};
+
+#[cfg(feature = "std")]
+use crate::glob::Glob;
+#[cfg(feature = "std")]
+use crate::listener::RemovalCause;
+#[cfg(feature = "std")]
+use crate::value::CacheValue;
+
+/// A change to a cache's keyspace, as delivered to `subscribe()` channels.
+///
+/// `V` is the cache's value type: `String` for the text caches and
+/// `Vec<u8>` for `BytesCache`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CacheEvent<V = String> {
+    /// A value was written under a key that held no live entry.
+    Insert { key: String, value: V },
+    /// A live entry's value was overwritten.
+    Update { key: String, value: V },
+    /// The entry was removed by the caller, or the cache was cleared.
+    Remove { key: String },
+    /// The entry's TTL or idle timeout ran out.
+    Expire { key: String },
+    /// The entry was dropped to keep the cache within its memory budget.
+    Evict { key: String },
+}
+
+impl<V> CacheEvent<V> {
+    /// Returns the key the event is about.
+    pub fn key(&self) -> &str {
+        match self {
+            CacheEvent::Insert { key, .. }
+            | CacheEvent::Update { key, .. }
+            | CacheEvent::Remove { key }
+            | CacheEvent::Expire { key }
+            | CacheEvent::Evict { key } => key,
+        }
+    }
+}
+
+/// The channels registered through `subscribe()`, each with the glob its
+/// keys must match.
+///
+/// Events are sent as the change happens, without blocking: the channels
+/// are unbounded, so a slow receiver only grows its own backlog. Channels
+/// whose receiver was dropped are forgotten on the next matching event.
+#[cfg(feature = "std")]
+pub(crate) struct Subscribers<V: CacheValue> {
+    channels: Vec<(Glob, Sender<CacheEvent<V>>)>,
+}
+
+#[cfg(feature = "std")]
+impl<V: CacheValue> Default for Subscribers<V> {
+    fn default() -> Self {
+        Self { channels: Vec::new() }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<V: CacheValue> Subscribers<V> {
+    pub(crate) fn subscribe(&mut self, pattern: &str) -> Receiver<CacheEvent<V>> {
+        let (sender, receiver) = mpsc::channel();
+        self.channels.push((Glob::new(pattern), sender));
+        receiver
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.channels.len()
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.channels.is_empty()
+    }
+
+    /// Publishes a write of `value` under `key`; `updated` tells whether a
+    /// live entry was overwritten.
+    pub(crate) fn written(&mut self, key: &str, value: &V::Ref, updated: bool) {
+        self.publish(key, |key| {
+            let value = V::from_ref(value);
+            if updated {
+                CacheEvent::Update { key, value }
+            } else {
+                CacheEvent::Insert { key, value }
+            }
+        });
+    }
+
+    /// Publishes an entry leaving the cache. Replacements are published by
+    /// the write that caused them instead.
+    pub(crate) fn removed(&mut self, key: &str, cause: RemovalCause) {
+        let event: fn(String) -> CacheEvent<V> = match cause {
+            RemovalCause::Removed => |key| CacheEvent::Remove { key },
+            RemovalCause::Expired => |key| CacheEvent::Expire { key },
+            RemovalCause::Evicted => |key| CacheEvent::Evict { key },
+            RemovalCause::Replaced => return,
+        };
+        self.publish(key, event);
+    }
+
+    fn publish(&mut self, key: &str, event: impl Fn(String) -> CacheEvent<V>) {
+        // Só monta o evento para quem assina a chave; canais fechados saem da lista
+        self.channels
+            .retain(|(glob, sender)| !glob.matches(key) || sender.send(event(key.to_string())).is_ok());
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_filters_by_pattern_and_drops_closed_channels() {
+        let mut subscribers = Subscribers::<String>::default();
+        let users = subscribers.subscribe("user:*");
+        let closed = subscribers.subscribe("*");
+        drop(closed);
+
+        subscribers.written("user:1", "Ana", false);
+        subscribers.written("session:1", "on", false);
+        subscribers.removed("user:1", RemovalCause::Replaced);
+        subscribers.removed("user:1", RemovalCause::Evicted);
+
+        let events: Vec<_> = users.try_iter().collect();
+        assert_eq!(
+            events,
+            [
+                CacheEvent::Insert {
+                    key: "user:1".to_string(),
+                    value: "Ana".to_string()
+                },
+                CacheEvent::Evict {
+                    key: "user:1".to_string()
+                },
+            ]
+        );
+        assert_eq!(subscribers.channels.len(), 1);
+    }
+}
@@ -0,0 +1,452 @@
+use std::any::Any;
+use std::fmt;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, PoisonError};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use crate::client::RetryPolicy;
+use crate::clock::{Instant, SystemTime};
+
+/// Where a supervised worker is in its lifecycle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WorkerState {
+    /// The worker is between iterations or running one.
+    Running,
+    /// The worker panicked and is waiting out its backoff before restarting.
+    Restarting,
+    /// The supervisor was shut down and the worker has exited.
+    Stopped,
+}
+
+/// A point-in-time view of one supervised worker, returned by
+/// [`Supervisor::stats`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct WorkerStats {
+    /// The name the worker was spawned with
+    pub name: String,
+    /// Current lifecycle state
+    pub state: WorkerState,
+    /// Iterations that completed without panicking
+    pub iterations: u64,
+    /// Times the worker was rebuilt after a panic
+    pub restarts: u64,
+    /// When the last successful iteration finished
+    pub last_run: Option<SystemTime>,
+    /// How long the last successful iteration took
+    pub last_duration: Option<Duration>,
+    /// Time spent in iterations since the worker was spawned
+    pub busy_time: Duration,
+    /// Share of time the worker spent running over its last iteration and
+    /// the rest that followed, from 0.0 to 1.0
+    pub cpu_usage: f64,
+    /// The share of time the worker is capped at, if it is throttled
+    pub cpu_limit: Option<f64>,
+    /// Rest added on top of the interval to keep the worker under its limit
+    pub throttled_time: Duration,
+    /// The message of the last panic, if the worker ever panicked
+    pub last_panic: Option<String>,
+}
+
+impl WorkerStats {
+    fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            state: WorkerState::Running,
+            iterations: 0,
+            restarts: 0,
+            last_run: None,
+            last_duration: None,
+            busy_time: Duration::ZERO,
+            cpu_usage: 0.0,
+            cpu_limit: None,
+            throttled_time: Duration::ZERO,
+            last_panic: None,
+        }
+    }
+
+    /// Returns true if the worker is running and its last iteration did not panic.
+    pub fn is_healthy(&self) -> bool {
+        self.state == WorkerState::Running
+    }
+}
+
+/// Owns the background workers of a cache deployment and keeps them alive.
+///
+/// Each worker is a closure run every `interval` on its own thread: an
+/// expiration sweeper, a snapshotter, a write-behind flusher, a replication
+/// sender. A worker that panics is rebuilt from its factory after an
+/// exponential backoff (100 ms doubling up to 30 s, reset by the next
+/// successful iteration), so one bad iteration doesn't silently stop
+/// background maintenance. [`stats`](Self::stats) reports the health, last
+/// run and iteration duration of every worker.
+///
+/// Workers spawned with [`spawn_throttled`](Self::spawn_throttled) are
+/// capped at a share of one core: after a long iteration they rest long
+/// enough to bring their duty cycle back under the cap, so a slow sweep or
+/// snapshot can't starve the threads serving requests.
+///
+/// Dropping the supervisor stops all workers and waits for them to finish
+/// their current iteration.
+///
+/// # Examples
+///
+/// ```
+/// use spectra_cache::{DistributedHashTable, Supervisor, WorkerState};
+/// use std::sync::{Arc, Mutex};
+/// use std::time::Duration;
+///
+/// let cache = Arc::new(Mutex::new(DistributedHashTable::new()));
+/// let mut supervisor = Supervisor::new();
+///
+/// let shared = Arc::clone(&cache);
+/// supervisor.spawn("sweeper", Duration::from_secs(1), move || {
+///     let cache = Arc::clone(&shared);
+///     move || {
+///         let mut cache = cache.lock().unwrap();
+///         let keys: Vec<String> = cache.keys().cloned().collect();
+///         for key in keys {
+///             cache.contains_key(&key); // drops the entry if it expired
+///         }
+///     }
+/// });
+///
+/// assert_eq!(supervisor.stats()[0].state, WorkerState::Running);
+/// supervisor.shutdown();
+/// ```
+pub struct Supervisor {
+    workers: Vec<Worker>,
+    shutdown: Arc<Shutdown>,
+    backoff: RetryPolicy,
+}
+
+struct Worker {
+    stats: Arc<Mutex<WorkerStats>>,
+    handle: Option<JoinHandle<()>>,
+}
+
+/// The stop flag shared with the worker threads; the condvar cuts their
+/// sleeps short so shutdown doesn't wait for a full interval.
+#[derive(Default)]
+struct Shutdown {
+    stopped: Mutex<bool>,
+    wake: Condvar,
+}
+
+impl Shutdown {
+    /// Sleeps for `duration` unless the supervisor stops first.
+    ///
+    /// Returns true if the supervisor was stopped.
+    fn sleep(&self, duration: Duration) -> bool {
+        let deadline = Instant::now() + duration;
+        let mut stopped = self.stopped.lock().unwrap_or_else(PoisonError::into_inner);
+        while !*stopped {
+            let now = Instant::now();
+            if now >= deadline {
+                break;
+            }
+            stopped = self
+                .wake
+                .wait_timeout(stopped, deadline - now)
+                .unwrap_or_else(PoisonError::into_inner)
+                .0;
+        }
+        *stopped
+    }
+
+    fn is_stopped(&self) -> bool {
+        *self.stopped.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn stop(&self) {
+        *self.stopped.lock().unwrap_or_else(PoisonError::into_inner) = true;
+        self.wake.notify_all();
+    }
+}
+
+impl Supervisor {
+    /// Creates a supervisor with no workers.
+    pub fn new() -> Self {
+        Self {
+            workers: Vec::new(),
+            shutdown: Arc::new(Shutdown::default()),
+            backoff: RetryPolicy::new(u32::MAX)
+                .with_base_delay(Duration::from_millis(100))
+                .with_max_delay(Duration::from_secs(30)),
+        }
+    }
+
+    /// Starts a worker that runs every `interval`.
+    ///
+    /// `factory` builds the iteration closure. It is called once at start
+    /// and again after every panic, so a restarted worker begins from fresh
+    /// state instead of whatever the panic left behind.
+    pub fn spawn<F, W>(&mut self, name: &str, interval: Duration, factory: F)
+    where
+        F: FnMut() -> W + Send + 'static,
+        W: FnMut(),
+    {
+        self.start(name, interval, None, factory);
+    }
+
+    /// Starts a worker like [`spawn`](Self::spawn) that spends at most
+    /// `max_cpu` of its time running, from 0.0 exclusive to 1.0.
+    ///
+    /// After every iteration the worker rests for `interval`, or longer if
+    /// the iteration was long enough that resting only `interval` would
+    /// take it over the cap: an iteration of 30 ms under a 0.1 cap is
+    /// followed by at least 270 ms of rest. Iterations are timed by the
+    /// wall clock, so time blocked on locks or I/O counts as usage too.
+    /// [`WorkerStats::cpu_usage`] and [`WorkerStats::throttled_time`]
+    /// report how the worker fares against its cap.
+    ///
+    /// # Panics
+    ///
+    /// Panics unless `max_cpu` is greater than 0.0 and at most 1.0.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use spectra_cache::Supervisor;
+    /// use std::time::Duration;
+    ///
+    /// let mut supervisor = Supervisor::new();
+    /// // A defrag pass may take a while; keep it to a quarter of a core
+    /// supervisor.spawn_throttled("defrag", Duration::from_millis(10), 0.25, || {
+    ///     || std::thread::sleep(Duration::from_millis(20))
+    /// });
+    /// std::thread::sleep(Duration::from_millis(200));
+    ///
+    /// let stats = &supervisor.stats()[0];
+    /// assert_eq!(stats.cpu_limit, Some(0.25));
+    /// assert!(stats.cpu_usage <= 0.26);
+    /// supervisor.shutdown();
+    /// ```
+    pub fn spawn_throttled<F, W>(&mut self, name: &str, interval: Duration, max_cpu: f64, factory: F)
+    where
+        F: FnMut() -> W + Send + 'static,
+        W: FnMut(),
+    {
+        assert!(max_cpu > 0.0 && max_cpu <= 1.0, "the CPU cap must be in (0, 1]");
+        self.start(name, interval, Some(max_cpu), factory);
+    }
+
+    fn start<F, W>(&mut self, name: &str, interval: Duration, max_cpu: Option<f64>, mut factory: F)
+    where
+        F: FnMut() -> W + Send + 'static,
+        W: FnMut(),
+    {
+        let mut worker_stats = WorkerStats::new(name);
+        worker_stats.cpu_limit = max_cpu;
+        let stats = Arc::new(Mutex::new(worker_stats));
+        let shutdown = Arc::clone(&self.shutdown);
+        let backoff = self.backoff.clone();
+        let worker_stats = Arc::clone(&stats);
+
+        let handle = thread::Builder::new()
+            .name(format!("spectra-cache-{}", name))
+            .spawn(move || {
+                let stats = worker_stats;
+                let mut failures = 0;
+                while !shutdown.is_stopped() {
+                    // Uma fábrica que entra em pânico conta como falha da iteração
+                    let outcome = panic::catch_unwind(AssertUnwindSafe(|| {
+                        let mut task = factory();
+                        while !shutdown.is_stopped() {
+                            let started = Instant::now();
+                            task();
+                            let busy = started.elapsed();
+                            let rest = rest_after(busy, interval, max_cpu);
+                            let mut stats = lock(&stats);
+                            stats.iterations += 1;
+                            stats.last_run = Some(SystemTime::now());
+                            stats.last_duration = Some(busy);
+                            stats.busy_time += busy;
+                            stats.throttled_time += rest.saturating_sub(interval);
+                            stats.cpu_usage = match busy + rest {
+                                Duration::ZERO => 0.0,
+                                cycle => busy.as_secs_f64() / cycle.as_secs_f64(),
+                            };
+                            stats.state = WorkerState::Running;
+                            drop(stats);
+                            failures = 0;
+                            if shutdown.sleep(rest) {
+                                break;
+                            }
+                        }
+                    }));
+                    let Err(payload) = outcome else {
+                        break;
+                    };
+
+                    failures += 1;
+                    {
+                        let mut stats = lock(&stats);
+                        stats.state = WorkerState::Restarting;
+                        stats.last_panic = Some(panic_message(payload.as_ref()));
+                    }
+                    if shutdown.sleep(backoff.backoff(failures)) {
+                        break;
+                    }
+                    lock(&stats).restarts += 1;
+                }
+                lock(&stats).state = WorkerState::Stopped;
+            })
+            .expect("failed to spawn a supervised worker thread");
+
+        self.workers.push(Worker {
+            stats,
+            handle: Some(handle),
+        });
+    }
+
+    /// Returns a snapshot of every worker, in spawn order.
+    pub fn stats(&self) -> Vec<WorkerStats> {
+        self.workers.iter().map(|worker| lock(&worker.stats).clone()).collect()
+    }
+
+    /// Returns true if every worker is running normally.
+    pub fn is_healthy(&self) -> bool {
+        self.stats().iter().all(WorkerStats::is_healthy)
+    }
+
+    /// Stops every worker and waits for them to exit.
+    pub fn shutdown(mut self) {
+        self.stop();
+    }
+
+    fn stop(&mut self) {
+        self.shutdown.stop();
+        for worker in &mut self.workers {
+            if let Some(handle) = worker.handle.take() {
+                let _ = handle.join();
+            }
+        }
+    }
+}
+
+impl Default for Supervisor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for Supervisor {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+impl fmt::Debug for Supervisor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Supervisor").field("workers", &self.stats()).finish()
+    }
+}
+
+/// Returns how long a worker rests after an iteration that ran for `busy`:
+/// `interval`, stretched if needed so that running stays within `max_cpu`
+/// of the whole cycle.
+fn rest_after(busy: Duration, interval: Duration, max_cpu: Option<f64>) -> Duration {
+    match max_cpu {
+        Some(max_cpu) if max_cpu < 1.0 => interval.max(busy.mul_f64((1.0 - max_cpu) / max_cpu)),
+        _ => interval,
+    }
+}
+
+fn lock(stats: &Mutex<WorkerStats>) -> MutexGuard<'_, WorkerStats> {
+    stats.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "worker panicked".to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn wait_for(supervisor: &Supervisor, condition: impl Fn(&WorkerStats) -> bool) -> WorkerStats {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        loop {
+            let stats = supervisor.stats().remove(0);
+            if condition(&stats) {
+                return stats;
+            }
+            assert!(Instant::now() < deadline, "worker never reached the expected state: {:?}", stats);
+            thread::sleep(Duration::from_millis(5));
+        }
+    }
+
+    #[test]
+    fn test_runs_worker_periodically() {
+        let mut supervisor = Supervisor::new();
+        supervisor.spawn("ticker", Duration::from_millis(1), || || {});
+
+        let stats = wait_for(&supervisor, |stats| stats.iterations >= 3);
+        assert_eq!(stats.name, "ticker");
+        assert!(stats.last_run.is_some() && stats.last_duration.is_some());
+        assert!(supervisor.is_healthy());
+    }
+
+    #[test]
+    fn test_restarts_panicking_worker_from_factory() {
+        let builds = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&builds);
+        let mut supervisor = Supervisor::new();
+        supervisor.spawn("flaky", Duration::from_millis(1), move || {
+            let build = counter.fetch_add(1, Ordering::SeqCst);
+            move || {
+                if build == 0 {
+                    panic!("first build is broken");
+                }
+            }
+        });
+
+        let stats = wait_for(&supervisor, |stats| stats.restarts == 1 && stats.iterations > 0);
+        assert_eq!(stats.last_panic.as_deref(), Some("first build is broken"));
+        assert_eq!(stats.state, WorkerState::Running);
+        assert_eq!(builds.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_rest_keeps_duty_cycle_under_cap() {
+        let interval = Duration::from_millis(10);
+        assert_eq!(rest_after(Duration::from_secs(1), interval, None), interval);
+        assert_eq!(rest_after(Duration::from_secs(1), interval, Some(1.0)), interval);
+        assert_eq!(rest_after(Duration::from_millis(1), interval, Some(0.5)), interval);
+        assert_eq!(rest_after(Duration::from_millis(30), interval, Some(0.1)), Duration::from_millis(270));
+    }
+
+    #[test]
+    fn test_throttled_worker_reports_usage() {
+        let mut supervisor = Supervisor::new();
+        supervisor.spawn_throttled("busy", Duration::from_millis(1), 0.5, || {
+            || thread::sleep(Duration::from_millis(5))
+        });
+
+        let stats = wait_for(&supervisor, |stats| stats.iterations >= 3);
+        assert!(stats.busy_time >= Duration::from_millis(15));
+        assert!(stats.throttled_time > Duration::ZERO);
+        assert!(stats.cpu_usage > 0.0 && stats.cpu_usage <= 0.5);
+    }
+
+    #[test]
+    fn test_shutdown_interrupts_sleep() {
+        let mut supervisor = Supervisor::new();
+        supervisor.spawn("slow", Duration::from_secs(3600), || || {});
+        wait_for(&supervisor, |stats| stats.iterations == 1);
+
+        let started = Instant::now();
+        let stats = Arc::clone(&supervisor.workers[0].stats);
+        supervisor.shutdown();
+        assert!(started.elapsed() < Duration::from_secs(5));
+        assert_eq!(lock(&stats).state, WorkerState::Stopped);
+    }
+}
@@ -0,0 +1,56 @@
+//! The lock guarding the state that reads through `&self` update, and
+//! the 64-bit atomic counters.
+//!
+//! With `std` it is a `Mutex`, so a cache can be shared between threads.
+//! Without `std` there are no threads to share it with, and a `RefCell`
+//! takes its place; the cache is then not `Sync`.
+
+#[cfg(not(feature = "std"))]
+use core::cell::{RefCell, RefMut};
+#[cfg(feature = "std")]
+pub(crate) use core::sync::atomic::AtomicU64;
+// Vários alvos embarcados (thumbv7em, riscv32) só têm atômicos de 32 bits
+#[cfg(not(feature = "std"))]
+pub(crate) use portable_atomic::AtomicU64;
+#[cfg(feature = "std")]
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+/// A lock that ignores poisoning: nothing guarded by one runs user code
+/// while it is held, so a panic can't leave the value half updated.
+#[derive(Debug, Default)]
+pub(crate) struct Lock<T> {
+    #[cfg(feature = "std")]
+    inner: Mutex<T>,
+    #[cfg(not(feature = "std"))]
+    inner: RefCell<T>,
+}
+
+#[cfg(feature = "std")]
+impl<T> Lock<T> {
+    pub(crate) fn new(value: T) -> Self {
+        Self { inner: Mutex::new(value) }
+    }
+
+    pub(crate) fn lock(&self) -> MutexGuard<'_, T> {
+        self.inner.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    pub(crate) fn get_mut(&mut self) -> &mut T {
+        self.inner.get_mut().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+#[cfg(not(feature = "std"))]
+impl<T> Lock<T> {
+    pub(crate) fn new(value: T) -> Self {
+        Self { inner: RefCell::new(value) }
+    }
+
+    pub(crate) fn lock(&self) -> RefMut<'_, T> {
+        self.inner.borrow_mut()
+    }
+
+    pub(crate) fn get_mut(&mut self) -> &mut T {
+        self.inner.get_mut()
+    }
+}
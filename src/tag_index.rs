@@ -0,0 +1,83 @@
+use alloc::boxed::Box;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use crate::collections::{HashMap, HashSet};
+
+/// Maps tags to the keys carrying them and keys back to their tags, for
+/// `invalidate_tag()`.
+///
+/// Tags are kept here rather than on the entries, so that caches which
+/// never tag anything don't pay for them in every entry.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct TagIndex {
+    keys: HashMap<String, HashSet<String>>,
+    tags: HashMap<String, Box<[String]>>,
+}
+
+impl TagIndex {
+    /// Sets the tags of `key`, replacing any it had.
+    pub(crate) fn tag(&mut self, key: &str, tags: &[&str]) {
+        self.untag(key);
+        let tags: HashSet<&str> = tags.iter().copied().collect();
+        if tags.is_empty() {
+            return;
+        }
+        for tag in &tags {
+            self.keys.entry(tag.to_string()).or_default().insert(key.to_string());
+        }
+        self.tags.insert(key.to_string(), tags.into_iter().map(str::to_string).collect());
+    }
+
+    /// Drops the tags of `key`, if it had any.
+    pub(crate) fn untag(&mut self, key: &str) {
+        // Caminho comum: nenhuma entrada tem tags
+        if self.tags.is_empty() {
+            return;
+        }
+        let Some(tags) = self.tags.remove(key) else {
+            return;
+        };
+        for tag in tags.iter() {
+            if let Some(keys) = self.keys.get_mut(tag) {
+                keys.remove(key);
+                if keys.is_empty() {
+                    self.keys.remove(tag);
+                }
+            }
+        }
+    }
+
+    /// Returns the keys carrying `tag`, in no particular order.
+    pub(crate) fn keys(&self, tag: &str) -> Vec<String> {
+        self.keys.get(tag).map_or_else(Vec::new, |keys| keys.iter().cloned().collect())
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.keys.clear();
+        self.tags.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tracks_tags_both_ways() {
+        let mut index = TagIndex::default();
+        index.tag("a", &["user:42", "report", "report"]);
+        index.tag("b", &["report"]);
+        let mut keys = index.keys("report");
+        keys.sort();
+        assert_eq!(keys, ["a", "b"]);
+
+        index.tag("a", &["user:42"]);
+        assert_eq!(index.keys("report"), ["b"]);
+        index.untag("b");
+        index.untag("missing");
+        assert!(index.keys("report").is_empty());
+        index.tag("a", &[]);
+        assert!(index.keys.is_empty() && index.tags.is_empty());
+    }
+}
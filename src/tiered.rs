@@ -0,0 +1,170 @@
+use std::fmt;
+use std::mem;
+use std::sync::{Arc, Mutex, PoisonError};
+
+use crate::{BackingStore, DistributedHashTable, RemovalCause};
+
+/// Counters kept by a [`TieredCache`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TierStats {
+    /// Lookups answered from memory
+    pub l1_hits: u64,
+    /// Lookups answered from the second tier, and promoted back to memory
+    pub l2_hits: u64,
+    /// Lookups found in neither tier
+    pub misses: u64,
+    /// Entries moved to the second tier after memory evicted them
+    pub demotions: u64,
+    /// Second-tier calls that failed; the lookup counted as a miss, or the
+    /// demoted entry was lost
+    pub l2_errors: u64,
+}
+
+/// A two-tier cache: a `DistributedHashTable` in memory in front of a
+/// larger, slower second tier, such as a [`LogStore`](crate::LogStore) on
+/// disk.
+///
+/// Writes go to memory. Entries the table evicts to stay within its
+/// memory limit are demoted to the second tier instead of being lost, and
+/// a lookup that misses memory but hits the second tier moves the entry
+/// back, so a hot set that fits in memory is served from there while the
+/// rest of the working set stays one disk read away. A key lives in one
+/// tier at a time.
+///
+/// Entries that expire in memory are dropped, not demoted, and demoted
+/// entries keep no TTL. The second tier is any [`BackingStore`]; the
+/// table's own eviction listeners still see demoted entries as evicted.
+///
+/// # Examples
+///
+/// ```
+/// use spectra_cache::{DistributedHashTable, LogStore, TieredCache};
+///
+/// let path = std::env::temp_dir().join(format!("spectra-tiered-doc-{}", std::process::id()));
+/// let l1 = DistributedHashTable::builder().max_memory_bytes(4 * 1024).build();
+/// let mut cache = TieredCache::new(l1, LogStore::open(&path).unwrap());
+/// for i in 0..100 {
+///     cache.insert(&format!("user:{}", i), &"x".repeat(100));
+/// }
+///
+/// assert!(cache.l1().size() < 100);
+/// assert_eq!(cache.get("user:0").map(|value| value.len()), Some(100));
+/// assert_eq!(cache.stats().l2_hits, 1);
+/// # std::fs::remove_file(&path).unwrap();
+/// ```
+pub struct TieredCache {
+    l1: DistributedHashTable,
+    l2: Box<dyn BackingStore>,
+    /// Entries the table evicted, waiting to be written to the second tier
+    evicted: Arc<Mutex<Vec<(String, String)>>>,
+    stats: TierStats,
+}
+
+impl TieredCache {
+    /// Puts `l1` in front of `l2`.
+    ///
+    /// The table's memory limit decides how much stays in memory; without
+    /// one nothing is ever demoted.
+    pub fn new(mut l1: DistributedHashTable, l2: impl BackingStore + 'static) -> Self {
+        let evicted = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&evicted);
+        l1.on_evict(move |key, value, cause| {
+            if cause == RemovalCause::Evicted {
+                sink.lock()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .push((key.to_string(), value.to_string()));
+            }
+        });
+        Self {
+            l1,
+            l2: Box::new(l2),
+            evicted,
+            stats: TierStats::default(),
+        }
+    }
+
+    /// Returns the in-memory tier, for inspection.
+    pub fn l1(&self) -> &DistributedHashTable {
+        &self.l1
+    }
+
+    /// Returns the tier counters.
+    pub fn stats(&self) -> TierStats {
+        self.stats
+    }
+
+    /// Inserts a key-value pair into memory, dropping any copy in the
+    /// second tier.
+    pub fn insert(&mut self, key: &str, value: &str) {
+        if self.l2.delete(key).is_err() {
+            self.stats.l2_errors += 1;
+        }
+        self.l1.insert(key, value);
+        self.demote_evicted();
+    }
+
+    /// Returns the value under `key`, from memory or else from the second
+    /// tier, promoting it back to memory.
+    pub fn get(&mut self, key: &str) -> Option<String> {
+        if let Some(value) = self.l1.get(key) {
+            self.stats.l1_hits += 1;
+            return Some(value.to_string());
+        }
+        let value = match self.l2.load(key) {
+            Ok(Some(value)) => value,
+            Ok(None) => {
+                self.stats.misses += 1;
+                return None;
+            }
+            Err(_) => {
+                self.stats.l2_errors += 1;
+                self.stats.misses += 1;
+                return None;
+            }
+        };
+        self.stats.l2_hits += 1;
+        if self.l2.delete(key).is_err() {
+            self.stats.l2_errors += 1;
+        }
+        self.l1.insert(key, &value);
+        self.demote_evicted();
+        Some(value)
+    }
+
+    /// Removes `key` from both tiers, returning its value.
+    pub fn remove(&mut self, key: &str) -> Option<String> {
+        if let Some(value) = self.l1.remove(key) {
+            return Some(value);
+        }
+        let value = self.l2.load(key).ok().flatten()?;
+        if self.l2.delete(key).is_err() {
+            self.stats.l2_errors += 1;
+        }
+        Some(value)
+    }
+
+    /// Checks if a key is in either tier.
+    pub fn contains_key(&mut self, key: &str) -> bool {
+        self.l1.contains_key(key) || self.l2.load(key).is_ok_and(|value| value.is_some())
+    }
+
+    /// Writes the entries the table evicted to the second tier.
+    fn demote_evicted(&mut self) {
+        let evicted = mem::take(&mut *self.evicted.lock().unwrap_or_else(PoisonError::into_inner));
+        for (key, value) in evicted {
+            match self.l2.store(&key, &value) {
+                Ok(()) => self.stats.demotions += 1,
+                Err(_) => self.stats.l2_errors += 1,
+            }
+        }
+    }
+}
+
+impl fmt::Debug for TieredCache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TieredCache")
+            .field("l1", &self.l1)
+            .field("stats", &self.stats)
+            .finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,111 @@
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::time::Duration;
+
+use crate::value::CacheValue;
+
+/// A batch of writes applied to a cache all together or not at all, with
+/// `apply()`.
+///
+/// Writes are queued with [`insert`](Self::insert),
+/// [`insert_with_ttl`](Self::insert_with_ttl) and [`remove`](Self::remove)
+/// and run in that order. Keys passed to [`watch`](Self::watch) are checked
+/// first: if any no longer holds the value it was watched with, none of the
+/// writes happen and `apply()` returns
+/// [`CacheError::TransactionConflict`](crate::CacheError::TransactionConflict).
+/// Readers never see a transaction half done, and the append-only log and
+/// the replicas get it as a single record.
+///
+/// # Examples
+///
+/// ```
+/// use spectra_cache::{CacheError, DistributedHashTable, Transaction};
+///
+/// let mut cache = DistributedHashTable::new();
+/// cache.insert("stock:1", "3");
+///
+/// let mut tx = Transaction::new();
+/// tx.watch("stock:1", Some("3")).insert("stock:1", "2").insert("order:9", "stock:1");
+/// cache.insert("stock:1", "0");
+/// assert_eq!(cache.apply(tx), Err(CacheError::TransactionConflict { key: "stock:1".to_string() }));
+/// assert!(!cache.contains_key("order:9"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct Transaction<V: CacheValue = String> {
+    pub(crate) watched: Vec<(String, Option<V>)>,
+    pub(crate) writes: Vec<Write<V>>,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) enum Write<V> {
+    /// `ttl` is `None` for the cache's default TTL
+    Insert { key: String, value: V, ttl: Option<Duration> },
+    Remove { key: String },
+}
+
+impl<V: CacheValue> Transaction<V> {
+    /// Creates an empty transaction.
+    pub fn new() -> Self {
+        Self {
+            watched: Vec::new(),
+            writes: Vec::new(),
+        }
+    }
+
+    /// Makes the transaction conditional on `key` holding `expected` when it
+    /// is applied, or on it being absent if `expected` is `None`.
+    pub fn watch(&mut self, key: &str, expected: Option<&V::Ref>) -> &mut Self {
+        self.watched.push((key.to_string(), expected.map(V::from_ref)));
+        self
+    }
+
+    /// Queues an insert with the cache's default TTL, if any.
+    pub fn insert(&mut self, key: &str, value: &V::Ref) -> &mut Self {
+        self.push_insert(key, value, None)
+    }
+
+    /// Queues an insert that expires after `ttl`.
+    pub fn insert_with_ttl(&mut self, key: &str, value: &V::Ref, ttl: Duration) -> &mut Self {
+        self.push_insert(key, value, Some(ttl))
+    }
+
+    /// Queues the removal of `key`.
+    pub fn remove(&mut self, key: &str) -> &mut Self {
+        self.writes.push(Write::Remove { key: key.to_string() });
+        self
+    }
+
+    /// Returns the number of queued writes.
+    pub fn len(&self) -> usize {
+        self.writes.len()
+    }
+
+    /// Returns true if no writes are queued.
+    pub fn is_empty(&self) -> bool {
+        self.writes.is_empty()
+    }
+
+    /// Returns the first watched key whose value, as read by `current`, is
+    /// not the one it was watched with.
+    pub(crate) fn conflict<'c>(&self, current: impl Fn(&str) -> Option<&'c V::Ref>) -> Option<&str> {
+        self.watched
+            .iter()
+            .find(|(key, expected)| current(key) != expected.as_ref().map(V::view))
+            .map(|(key, _)| key.as_str())
+    }
+
+    fn push_insert(&mut self, key: &str, value: &V::Ref, ttl: Option<Duration>) -> &mut Self {
+        self.writes.push(Write::Insert {
+            key: key.to_string(),
+            value: V::from_ref(value),
+            ttl,
+        });
+        self
+    }
+}
+
+impl<V: CacheValue> Default for Transaction<V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,107 @@
+use alloc::borrow::ToOwned;
+use alloc::string::{String, ToString};
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::fmt::Debug;
+use core::hash::Hash;
+
+/// A type the caches can store as a value: `String` for text, `Vec<u8>`
+/// for binary blobs, `Arc<str>` for text shared with callers.
+///
+/// Values are handed in and out through their borrowed form (`str` or
+/// `[u8]`), so reads never copy.
+pub trait CacheValue: Send + 'static {
+    /// The borrowed form callers read and write.
+    type Ref: ?Sized + PartialEq + Hash + Debug + AsRef<[u8]> + ToOwned + 'static;
+
+    /// Copies a borrowed value into an owned one.
+    fn from_ref(value: &Self::Ref) -> Self;
+
+    /// Turns the borrowed form's owned type, as returned by a
+    /// `BackingStore`, into a value.
+    fn from_owned(value: <Self::Ref as ToOwned>::Owned) -> Self;
+
+    /// Borrows the stored value.
+    fn view(&self) -> &Self::Ref;
+
+    /// Returns the size of the value's payload in bytes.
+    fn byte_len(&self) -> usize;
+
+    /// Rebuilds a value from its bytes, or returns `None` if they aren't a
+    /// valid value of this type.
+    fn from_bytes(bytes: Vec<u8>) -> Option<Self>
+    where
+        Self: Sized;
+}
+
+impl CacheValue for String {
+    type Ref = str;
+
+    fn from_ref(value: &str) -> Self {
+        value.to_string()
+    }
+
+    fn from_owned(value: String) -> Self {
+        value
+    }
+
+    fn view(&self) -> &str {
+        self
+    }
+
+    fn byte_len(&self) -> usize {
+        self.len()
+    }
+
+    fn from_bytes(bytes: Vec<u8>) -> Option<Self> {
+        String::from_utf8(bytes).ok()
+    }
+}
+
+impl CacheValue for Vec<u8> {
+    type Ref = [u8];
+
+    fn from_ref(value: &[u8]) -> Self {
+        value.to_vec()
+    }
+
+    fn from_owned(value: Vec<u8>) -> Self {
+        value
+    }
+
+    fn view(&self) -> &[u8] {
+        self
+    }
+
+    fn byte_len(&self) -> usize {
+        self.len()
+    }
+
+    fn from_bytes(bytes: Vec<u8>) -> Option<Self> {
+        Some(bytes)
+    }
+}
+
+impl CacheValue for Arc<str> {
+    type Ref = str;
+
+    fn from_ref(value: &str) -> Self {
+        Arc::from(value)
+    }
+
+    fn from_owned(value: String) -> Self {
+        Arc::from(value)
+    }
+
+    fn view(&self) -> &str {
+        self
+    }
+
+    fn byte_len(&self) -> usize {
+        self.len()
+    }
+
+    fn from_bytes(bytes: Vec<u8>) -> Option<Self> {
+        String::from_utf8(bytes).ok().map(Arc::from)
+    }
+}
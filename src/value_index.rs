@@ -0,0 +1,70 @@
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::hash::{BuildHasher, Hash};
+
+use crate::bloom::DefaultBloomHasher;
+use crate::collections::HashMap;
+
+/// Maps value hashes to the keys holding a value with that hash.
+///
+/// Only the hash is kept, not the value, so a lookup yields candidates
+/// that the caller still compares against the stored values.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ValueIndex {
+    keys: HashMap<u64, Vec<String>>,
+}
+
+impl ValueIndex {
+    pub(crate) fn hash<R: Hash + ?Sized>(value: &R) -> u64 {
+        DefaultBloomHasher::default().hash_one(value)
+    }
+
+    pub(crate) fn reserve(&mut self, additional: usize) {
+        self.keys.reserve(additional);
+    }
+
+    pub(crate) fn add(&mut self, key: &str, hash: u64) {
+        self.keys.entry(hash).or_default().push(key.to_string());
+    }
+
+    pub(crate) fn remove(&mut self, key: &str, hash: u64) {
+        if let Some(keys) = self.keys.get_mut(&hash) {
+            if let Some(position) = keys.iter().position(|candidate| candidate == key) {
+                keys.swap_remove(position);
+            }
+            if keys.is_empty() {
+                self.keys.remove(&hash);
+            }
+        }
+    }
+
+    /// Returns the keys whose value has `hash`, in no particular order.
+    pub(crate) fn candidates(&self, hash: u64) -> &[String] {
+        self.keys.get(&hash).map_or(&[], Vec::as_slice)
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.keys.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tracks_keys_per_hash() {
+        let mut index = ValueIndex::default();
+        let hash = ValueIndex::hash("blob");
+        index.add("a", hash);
+        index.add("b", hash);
+        assert_eq!(index.candidates(hash), ["a", "b"]);
+
+        index.remove("a", hash);
+        index.remove("missing", hash);
+        assert_eq!(index.candidates(hash), ["b"]);
+        index.remove("b", hash);
+        assert!(index.keys.is_empty());
+        assert_eq!(index.candidates(ValueIndex::hash("other")), [] as [String; 0]);
+    }
+}
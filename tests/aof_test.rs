@@ -0,0 +1,288 @@
+#![cfg(feature = "persistence")]
+
+use spectra_cache::{BTreeCache, BytesCache, CacheError, DistributedHashTable, Durability, FsyncPolicy, PersistenceFailure};
+use std::fs::{self, OpenOptions};
+use std::io::ErrorKind;
+use std::path::PathBuf;
+use std::time::Duration;
+
+fn temp_log(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("spectra-cache-test-{}-{}.aof", name, std::process::id()));
+    let _ = fs::remove_file(&path);
+    path
+}
+
+#[test]
+fn test_replay_rebuilds_the_cache() {
+    let path = temp_log("rebuild");
+    let mut cache = DistributedHashTable::new();
+    cache.enable_aof(&path, FsyncPolicy::Always).unwrap();
+    cache.insert("user:1", "Ana");
+    cache.insert("user:1", "Ana Maria");
+    cache.insert_with_ttl("session:1", "active", Duration::from_secs(60));
+    cache.insert_with_ttl("session:2", "active", Duration::from_secs(60));
+    cache.persist("session:2");
+    cache.insert("token", "x");
+    cache.expire("token", Duration::from_secs(30));
+    cache.incr("visits", 3).unwrap();
+    cache.entry("visits").and_modify(|visits| visits.push('0'));
+    cache.insert("gone", "soon");
+    cache.remove("gone");
+    drop(cache);
+
+    let mut replayed = DistributedHashTable::new();
+    assert_eq!(replayed.replay(&path).unwrap(), 11);
+    assert_eq!(replayed.size(), 5);
+    assert_eq!(replayed.get("user:1"), Some("Ana Maria"));
+    assert_eq!(replayed.get("visits"), Some("30"));
+    assert_eq!(replayed.get("gone"), None);
+    assert!(replayed.ttl("session:1").unwrap() <= Duration::from_secs(60));
+    assert_eq!(replayed.ttl("session:2"), None);
+    assert!(replayed.ttl("token").unwrap() <= Duration::from_secs(30));
+
+    // O log serve para qualquer tipo de cache
+    let mut btree = BTreeCache::new();
+    btree.replay(&path).unwrap();
+    assert_eq!(btree.keys().collect::<Vec<_>>(), ["session:1", "session:2", "token", "user:1", "visits"]);
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_new_log_starts_with_the_current_entries() {
+    let path = temp_log("current");
+    let mut cache = DistributedHashTable::new();
+    cache.insert("before", "1");
+    cache.enable_aof(&path, FsyncPolicy::Never).unwrap();
+    cache.insert("after", "2");
+    cache.clear();
+    cache.insert("last", "3");
+    cache.disable_aof();
+    cache.insert("unlogged", "4");
+
+    let mut replayed = DistributedHashTable::new();
+    replayed.insert("stale", "0");
+    assert_eq!(replayed.replay(&path).unwrap(), 4);
+    assert_eq!(replayed.keys().collect::<Vec<_>>(), ["last"]);
+
+    // Reabrir o log continua de onde parou, sem repetir o conteúdo
+    replayed.enable_aof(&path, FsyncPolicy::EverySecond).unwrap();
+    replayed.insert("resumed", "5");
+    drop(replayed);
+    let mut again = DistributedHashTable::new();
+    assert_eq!(again.replay(&path).unwrap(), 5);
+    assert_eq!(again.size(), 2);
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_replay_skips_expired_entries() {
+    let path = temp_log("expired");
+    let mut cache = DistributedHashTable::new();
+    cache.enable_aof(&path, FsyncPolicy::Always).unwrap();
+    cache.insert_with_ttl("short", "x", Duration::from_millis(20));
+    cache.insert("retimed", "y");
+    cache.expire("retimed", Duration::from_millis(20));
+    cache.insert("kept", "z");
+    drop(cache);
+    std::thread::sleep(Duration::from_millis(30));
+
+    let mut replayed = DistributedHashTable::new();
+    replayed.replay(&path).unwrap();
+    assert_eq!(replayed.keys().collect::<Vec<_>>(), ["kept"]);
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_torn_record_is_dropped_and_logging_resumes() {
+    let path = temp_log("torn");
+    let mut cache = DistributedHashTable::new();
+    cache.enable_aof(&path, FsyncPolicy::Always).unwrap();
+    cache.insert("a", "1");
+    cache.insert("b", "2");
+    drop(cache);
+    // Simula uma queda no meio da escrita do último registro
+    let len = fs::metadata(&path).unwrap().len();
+    OpenOptions::new().write(true).open(&path).unwrap().set_len(len - 5).unwrap();
+
+    let mut replayed = DistributedHashTable::new();
+    assert_eq!(replayed.replay(&path).unwrap(), 1);
+    replayed.enable_aof(&path, FsyncPolicy::Always).unwrap();
+    replayed.insert("c", "3");
+    drop(replayed);
+
+    let mut again = DistributedHashTable::new();
+    assert_eq!(again.replay(&path).unwrap(), 2);
+    let mut keys: Vec<_> = again.keys().collect();
+    keys.sort();
+    assert_eq!(keys, ["a", "c"]);
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_replay_errors() {
+    let mut cache = DistributedHashTable::new();
+    let missing = temp_log("missing");
+    assert_eq!(cache.replay(&missing).unwrap_err().kind(), ErrorKind::NotFound);
+
+    let path = temp_log("not-a-log");
+    fs::write(&path, "user:1 Ana").unwrap();
+    assert_eq!(cache.replay(&path).unwrap_err().kind(), ErrorKind::InvalidData);
+    assert_eq!(cache.enable_aof(&path, FsyncPolicy::Always).unwrap_err().kind(), ErrorKind::InvalidData);
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_disk_durability_syncs_the_log() {
+    let path = temp_log("durability");
+    let mut cache = DistributedHashTable::new();
+    cache.enable_aof(&path, FsyncPolicy::Never).unwrap();
+    assert_eq!(cache.insert_with_durability("order:1", "paid", Durability::Disk, Duration::from_millis(50)), Ok(()));
+    assert_eq!(cache.stats().aof_errors, 0);
+
+    let mut replayed = DistributedHashTable::new();
+    replayed.replay(&path).unwrap();
+    assert_eq!(replayed.get("order:1"), Some("paid"));
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_bytes_cache_log() {
+    let path = temp_log("bytes");
+    let mut cache = BytesCache::new();
+    cache.enable_aof(&path, FsyncPolicy::Always).unwrap();
+    cache.insert("blob", &[0, 159, 146, 150]);
+    drop(cache);
+
+    let mut replayed = BytesCache::new();
+    replayed.replay(&path).unwrap();
+    assert_eq!(replayed.get("blob"), Some(&[0, 159, 146, 150][..]));
+    // Bytes que não são UTF-8 não cabem num cache de texto
+    let error = DistributedHashTable::new().replay(&path).unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::InvalidData);
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_transaction_is_logged_as_one_record() {
+    use spectra_cache::Transaction;
+
+    let path = temp_log("transaction");
+    let mut cache = DistributedHashTable::new();
+    cache.enable_aof(&path, FsyncPolicy::Always).unwrap();
+    cache.insert("a", "1");
+    let mut tx = Transaction::new();
+    tx.insert("b", "2").insert("c", "3").remove("a");
+    cache.apply(tx).unwrap();
+    drop(cache);
+
+    let mut replayed = DistributedHashTable::new();
+    assert_eq!(replayed.replay(&path).unwrap(), 2);
+    let mut keys: Vec<_> = replayed.keys().collect();
+    keys.sort();
+    assert_eq!(keys, ["b", "c"]);
+
+    // Uma transação cortada no meio some inteira
+    let len = fs::metadata(&path).unwrap().len();
+    OpenOptions::new().write(true).open(&path).unwrap().set_len(len - 5).unwrap();
+    let mut replayed = DistributedHashTable::new();
+    assert_eq!(replayed.replay(&path).unwrap(), 1);
+    assert_eq!(replayed.keys().collect::<Vec<_>>(), ["a"]);
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_persistence_failure_policies() {
+    let unwritable = std::env::temp_dir().join("spectra-cache-test-missing-dir").join("cache.snap");
+
+    // Por padrão o cache segue só em memória
+    let mut cache = DistributedHashTable::new();
+    cache.save_snapshot(&unwritable).unwrap_err();
+    cache.save_snapshot(&unwritable).unwrap_err();
+    assert!(!cache.is_degraded());
+    cache.save_snapshot(&unwritable).unwrap_err();
+    assert!(cache.is_degraded());
+    assert!(cache.stats().persistence_degraded);
+    cache.insert("user:1", "Ana");
+    assert_eq!(cache.get("user:1"), Some("Ana"));
+    assert_eq!(cache.check_writable(), Ok(()));
+
+    let mut cache = DistributedHashTable::builder()
+        .on_persistence_failure(PersistenceFailure::RejectWrites)
+        .build();
+    cache.insert("user:1", "Ana");
+    for _ in 0..3 {
+        cache.save_snapshot(&unwritable).unwrap_err();
+    }
+    cache.insert("user:2", "Bia");
+    assert_eq!(cache.remove("user:1"), None);
+    assert_eq!(cache.incr("visits", 1), Err(CacheError::PersistenceFailed));
+    cache.clear();
+    assert_eq!(cache.size(), 1);
+    assert_eq!(cache.get("user:2"), None);
+    let stats = cache.stats();
+    assert!(stats.persistence_degraded);
+    assert_eq!(stats.rejected_writes, 4);
+
+    // Um snapshot gravado tira o cache do modo degradado
+    let path = temp_log("degraded-snapshot");
+    cache.save_snapshot(&path).unwrap();
+    assert!(!cache.is_degraded());
+    cache.insert("user:2", "Bia");
+    assert_eq!(cache.get("user:2"), Some("Bia"));
+    fs::remove_file(&path).unwrap();
+
+    for _ in 0..3 {
+        cache.save_snapshot(&unwritable).unwrap_err();
+    }
+    cache.resume_persistence();
+    assert_eq!(cache.check_writable(), Ok(()));
+    assert_eq!(cache.remove("user:1").as_deref(), Some("Ana"));
+}
+
+#[test]
+fn test_refused_writes_change_nothing() {
+    use spectra_cache::migrate;
+
+    let unwritable = std::env::temp_dir().join("spectra-cache-test-missing-dir").join("refused.snap");
+    let mut degraded = BTreeCache::builder()
+        .on_persistence_failure(PersistenceFailure::RejectWrites)
+        .build();
+    degraded.insert_with_ttl("a", "1", Duration::from_secs(60));
+    degraded.insert("b", "2");
+    for _ in 0..3 {
+        degraded.save_snapshot(&unwritable).unwrap_err();
+    }
+
+    assert_eq!(degraded.try_insert("c", "3"), Err(CacheError::PersistenceFailed));
+    assert_eq!(degraded.try_remove("a"), Err(CacheError::PersistenceFailed));
+    assert!(degraded.try_entry("a").is_err());
+    assert!(!degraded.persist("a"));
+    assert!(!degraded.expire("b", Duration::from_secs(1)));
+    assert_eq!(degraded.pop_first(), None);
+    assert_eq!(degraded.drain(), []);
+    assert_eq!(degraded.size(), 2);
+    assert!(degraded.ttl("a").is_some());
+    assert_eq!(degraded.ttl("b"), None);
+    assert_eq!(degraded.stats().rejected_writes, 7);
+
+    // Nada sai da origem se o destino recusa escritas
+    let mut source = DistributedHashTable::new();
+    source.insert("user:1", "Ana");
+    assert_eq!(migrate(&mut source, &mut degraded, |_, _| true), Err(CacheError::PersistenceFailed));
+    assert_eq!(source.get("user:1"), Some("Ana"));
+    assert_eq!(migrate(&mut degraded, &mut source, |_, _| true), Err(CacheError::PersistenceFailed));
+    assert_eq!(degraded.size(), 2);
+}
+
+#[test]
+#[should_panic(expected = "refuses writes")]
+fn test_entry_panics_when_writes_are_refused() {
+    let unwritable = std::env::temp_dir().join("spectra-cache-test-missing-dir").join("entry.snap");
+    let mut cache = DistributedHashTable::builder()
+        .on_persistence_failure(PersistenceFailure::RejectWrites)
+        .build();
+    for _ in 0..3 {
+        cache.save_snapshot(&unwritable).unwrap_err();
+    }
+    cache.entry("visits").or_insert("1");
+}
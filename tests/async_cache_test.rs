@@ -0,0 +1,91 @@
+#![cfg(feature = "async")]
+
+use spectra_cache::{AsyncCache, DistributedHashTable};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+#[tokio::test]
+async fn test_insert_get_remove() {
+    let cache = AsyncCache::new();
+    assert!(cache.is_empty().await);
+
+    cache.insert("key1", "value1").await;
+    assert_eq!(cache.get("key1").await, Some("value1".to_string()));
+    assert!(cache.contains_key("key1").await);
+
+    assert_eq!(cache.remove("key1").await, Some("value1".to_string()));
+    assert_eq!(cache.get("key1").await, None);
+}
+
+#[tokio::test]
+async fn test_insert_with_ttl() {
+    let cache = AsyncCache::new();
+
+    cache.insert_with_ttl("key1", "value1", Duration::from_millis(50)).await;
+    assert_eq!(cache.get("key1").await, Some("value1".to_string()));
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert_eq!(cache.get("key1").await, None);
+}
+
+#[tokio::test]
+async fn test_get_or_load() {
+    let cache = AsyncCache::new();
+    let loads = AtomicUsize::new(0);
+
+    for _ in 0..3 {
+        let value = cache
+            .get_or_load("key1", || async {
+                loads.fetch_add(1, Ordering::SeqCst);
+                "loaded".to_string()
+            })
+            .await;
+        assert_eq!(value, "loaded");
+    }
+
+    // O loader só deve rodar no primeiro miss
+    assert_eq!(loads.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn test_shared_between_tasks() {
+    let mut table = DistributedHashTable::new();
+    table.insert("seed", "1");
+    let cache = AsyncCache::from(table);
+
+    let handles: Vec<_> = (0..8)
+        .map(|i| {
+            let cache = cache.clone();
+            tokio::spawn(async move {
+                cache.insert(&format!("key{}", i), "value").await;
+            })
+        })
+        .collect();
+    for handle in handles {
+        handle.await.unwrap();
+    }
+
+    assert_eq!(cache.size().await, 9);
+    assert_eq!(cache.with_table(|table| table.get("seed").map(str::to_string)).await, Some("1".to_string()));
+}
+
+#[tokio::test]
+async fn test_concurrent_incr() {
+    let cache = AsyncCache::new();
+
+    let handles: Vec<_> = (0..10)
+        .map(|_| {
+            let cache = cache.clone();
+            tokio::spawn(async move {
+                for _ in 0..10 {
+                    cache.incr("counter", 1).await.unwrap();
+                }
+            })
+        })
+        .collect();
+    for handle in handles {
+        handle.await.unwrap();
+    }
+
+    assert_eq!(cache.get("counter").await, Some("100".to_string()));
+}
@@ -0,0 +1,254 @@
+#![cfg(all(feature = "core", feature = "std"))]
+
+use spectra_cache::{BackingStore, BytesCache, CacheError, DistributedHashTable, Durability, GetOptions, StoreError};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// A store whose contents stay visible to the test after the cache takes it.
+#[derive(Clone, Default)]
+struct SharedMap<V>(Arc<Mutex<HashMap<String, V>>>);
+
+impl<V> SharedMap<V> {
+    fn with(rows: &[(&str, V)]) -> Self
+    where
+        V: Clone,
+    {
+        let map = rows.iter().map(|(key, value)| (key.to_string(), value.clone())).collect();
+        Self(Arc::new(Mutex::new(map)))
+    }
+
+    fn get(&self, key: &str) -> Option<V>
+    where
+        V: Clone,
+    {
+        self.0.lock().unwrap().get(key).cloned()
+    }
+}
+
+impl BackingStore for SharedMap<String> {
+    fn load(&mut self, key: &str) -> Result<Option<String>, StoreError> {
+        Ok(self.get(key))
+    }
+
+    fn store(&mut self, key: &str, value: &str) -> Result<(), StoreError> {
+        self.0.lock().unwrap().insert(key.to_string(), value.to_string());
+        Ok(())
+    }
+
+    fn delete(&mut self, key: &str) -> Result<(), StoreError> {
+        self.0.lock().unwrap().remove(key);
+        Ok(())
+    }
+}
+
+impl BackingStore<[u8]> for SharedMap<Vec<u8>> {
+    fn load(&mut self, key: &str) -> Result<Option<Vec<u8>>, StoreError> {
+        Ok(self.get(key))
+    }
+
+    fn store(&mut self, key: &str, value: &[u8]) -> Result<(), StoreError> {
+        self.0.lock().unwrap().insert(key.to_string(), value.to_vec());
+        Ok(())
+    }
+
+    fn delete(&mut self, key: &str) -> Result<(), StoreError> {
+        self.0.lock().unwrap().remove(key);
+        Ok(())
+    }
+}
+
+struct FailingStore;
+
+impl BackingStore for FailingStore {
+    fn load(&mut self, _key: &str) -> Result<Option<String>, StoreError> {
+        Err("database unavailable".into())
+    }
+
+    fn store(&mut self, _key: &str, _value: &str) -> Result<(), StoreError> {
+        Err("database unavailable".into())
+    }
+
+    fn delete(&mut self, _key: &str) -> Result<(), StoreError> {
+        Err("database unavailable".into())
+    }
+}
+
+#[test]
+fn test_write_through() {
+    let db = SharedMap::with(&[("user:1", "Alice".to_string())]);
+    let mut cache = DistributedHashTable::builder().write_through(db.clone()).build();
+    let read = GetOptions::default();
+
+    // get() só vê o cache; get_with() busca no banco o que falta
+    assert_eq!(cache.get("user:1"), None);
+    assert_eq!(cache.get_with("user:1", &read), Some("Alice"));
+    assert_eq!(cache.size(), 1);
+    assert_eq!(cache.get("user:1"), Some("Alice"));
+    assert_eq!(cache.get_with("missing", &read), None);
+
+    cache.insert("user:2", "Bob");
+    assert_eq!(db.get("user:2").as_deref(), Some("Bob"));
+    cache.update("user:2", "Robert");
+    cache.incr("visits", 3).unwrap();
+    cache.entry("user:3").or_insert("Carol");
+    assert_eq!(db.get("user:2").as_deref(), Some("Robert"));
+    assert_eq!(db.get("visits").as_deref(), Some("3"));
+    assert_eq!(db.get("user:3").as_deref(), Some("Carol"));
+
+    // Remover apaga do banco, mesmo que a chave não esteja no cache
+    db.0.lock().unwrap().insert("stale".to_string(), "x".to_string());
+    assert_eq!(cache.remove("stale"), None);
+    assert_eq!(db.get("stale"), None);
+    cache.remove("user:1");
+    assert_eq!(db.get("user:1"), None);
+
+    // Limpar o cache não apaga os dados
+    cache.clear();
+    assert_eq!(db.get("user:2").as_deref(), Some("Robert"));
+    assert_eq!(cache.get_with("user:2", &read), Some("Robert"));
+}
+
+#[test]
+fn test_write_back() {
+    let db = SharedMap::default();
+    let mut cache = DistributedHashTable::builder()
+        .write_back(db.clone(), Duration::from_secs(3600))
+        .build();
+    assert_eq!(cache.config().backing_store, Some("write-back"));
+    assert_eq!(cache.config().write_back_interval, Some(Duration::from_secs(3600)));
+
+    cache.insert("a", "1");
+    cache.insert("a", "2");
+    cache.insert("b", "1");
+    cache.remove("b");
+    assert_eq!(db.get("a"), None);
+
+    // Uma leitura depois de limpar o cache vê a escrita ainda pendente
+    cache.clear();
+    assert_eq!(cache.get_with("a", &GetOptions::default()), Some("2"));
+    assert_eq!(cache.get_with("b", &GetOptions::default()), None);
+
+    cache.flush();
+    assert_eq!(db.get("a").as_deref(), Some("2"));
+    assert_eq!(db.get("b"), None);
+
+    cache.insert("c", "3");
+    drop(cache);
+    assert_eq!(db.get("c").as_deref(), Some("3"));
+}
+
+#[test]
+fn test_write_back_flushes_in_background() {
+    let db = SharedMap::default();
+    let mut cache = DistributedHashTable::builder()
+        .write_back(db.clone(), Duration::from_millis(10))
+        .build();
+    cache.insert("k", "v");
+
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while db.get("k").is_none() {
+        assert!(Instant::now() < deadline, "write-back never flushed");
+        std::thread::sleep(Duration::from_millis(5));
+    }
+}
+
+#[test]
+fn test_flush_and_wait_wakes_the_flusher() {
+    let db = SharedMap::default();
+    let mut cache = DistributedHashTable::builder()
+        .write_back(db.clone(), Duration::from_secs(3600))
+        .build();
+    cache.insert("a", "1");
+    cache.remove("missing");
+
+    let started = Instant::now();
+    assert!(cache.flush_and_wait(Duration::from_secs(5)));
+    assert!(started.elapsed() < Duration::from_secs(5));
+    assert_eq!(db.get("a").as_deref(), Some("1"));
+    // Sem nada pendente, volta na hora
+    assert!(cache.flush_and_wait(Duration::ZERO));
+
+    let mut failing = DistributedHashTable::builder()
+        .write_back(FailingStore, Duration::from_secs(3600))
+        .build();
+    failing.insert("k", "v");
+    assert!(!failing.flush_and_wait(Duration::from_millis(50)));
+
+    let mut write_through = DistributedHashTable::builder().write_through(SharedMap::default()).build();
+    write_through.insert("k", "v");
+    assert!(write_through.flush_and_wait(Duration::ZERO));
+}
+
+#[test]
+fn test_insert_with_durability() {
+    let timeout = Duration::from_secs(5);
+    let not_durable = |reason| Err(CacheError::NotDurable { reason });
+
+    let db = SharedMap::default();
+    let mut cache = DistributedHashTable::builder()
+        .write_back(db.clone(), Duration::from_secs(3600))
+        .build();
+    cache.insert("earlier", "1");
+    assert_eq!(cache.insert_with_durability("k", "v", Durability::Memory, timeout), Ok(()));
+    assert_eq!(db.get("k"), None);
+    assert_eq!(cache.insert_with_durability("k", "v2", Durability::Disk, timeout), Ok(()));
+    assert_eq!(db.get("k").as_deref(), Some("v2"));
+    assert_eq!(db.get("earlier").as_deref(), Some("1"));
+    assert_eq!(cache.insert_with_durability("k", "v3", Durability::Replicated(0), timeout), Ok(()));
+    assert_eq!(cache.insert_with_durability("k", "v4", Durability::Replicated(2), timeout), not_durable("the cache has no replicas"));
+    assert_eq!(cache.get("k"), Some("v4"));
+
+    let mut through = DistributedHashTable::builder().write_through(db.clone()).build();
+    assert_eq!(through.insert_with_durability("t", "1", Durability::Disk, Duration::ZERO), Ok(()));
+    assert_eq!(db.get("t").as_deref(), Some("1"));
+
+    let mut rejected = DistributedHashTable::builder().write_through(FailingStore).build();
+    assert_eq!(
+        rejected.insert_with_durability("k", "v", Durability::Disk, timeout),
+        not_durable("the backing store rejected the write")
+    );
+    let mut stuck = DistributedHashTable::builder()
+        .write_back(FailingStore, Duration::from_secs(3600))
+        .build();
+    assert_eq!(
+        stuck.insert_with_durability("k", "v", Durability::Disk, Duration::from_millis(50)),
+        not_durable("timed out waiting for the backing store")
+    );
+    let mut memory_only = DistributedHashTable::new();
+    assert_eq!(
+        memory_only.insert_with_durability("k", "v", Durability::Disk, timeout),
+        not_durable("the cache has no backing store or append-only log")
+    );
+}
+
+#[test]
+fn test_store_errors_are_counted() {
+    let mut cache = DistributedHashTable::builder().write_through(FailingStore).build();
+    cache.insert("k", "v");
+    assert_eq!(cache.get("k"), Some("v"));
+    assert_eq!(cache.get_with("missing", &GetOptions::default()), None);
+    cache.remove("k");
+    assert_eq!(cache.stats().store_errors, 3);
+}
+
+#[test]
+fn test_bytes_cache_store() {
+    let db = SharedMap::with(&[("blob", vec![1, 2, 3])]);
+    let mut cache = BytesCache::builder().write_through(db.clone()).build();
+    assert_eq!(cache.get_with("blob", &GetOptions::default()), Some(&[1, 2, 3][..]));
+    cache.insert("other", &[4]);
+    assert_eq!(db.get("other"), Some(vec![4]));
+}
+
+#[test]
+fn test_budget_skips_read_through() {
+    let db = SharedMap::with(&[("user:1", "Ana".to_string())]);
+    let mut cache = DistributedHashTable::builder().write_through(db).build();
+    let budget = GetOptions { max_wait: Some(Duration::ZERO), ..GetOptions::default() };
+
+    assert_eq!(cache.get_with("user:1", &budget), None);
+    assert_eq!(cache.get_with("user:1", &GetOptions::default()), Some("Ana"));
+    assert_eq!(cache.get_with("user:1", &budget), Some("Ana"));
+    assert_eq!(cache.stats().misses, 2);
+}
@@ -118,4 +118,140 @@ fn test_merge() {
     assert!(filter1.contains(&String::from("key2")));
     assert!(filter1.contains(&String::from("key3")));
     assert_eq!(filter1.size(), 3);
-} 
\ No newline at end of file
+} 
+#[test]
+fn test_scalable_filter_keeps_false_positive_rate() {
+    use spectra_cache::ScalableBloomFilter;
+
+    let mut filter = ScalableBloomFilter::new(1000, 0.01);
+    for i in 0..50_000 {
+        filter.insert(&format!("key:{}", i));
+    }
+    assert!(filter.filter_count() > 1);
+    assert!((0..50_000).all(|i| filter.contains(&format!("key:{}", i))));
+
+    // A fixed 1000-key filter would answer yes to almost everything by now
+    let false_positives = (0..50_000).filter(|i| filter.contains(&format!("absent:{}", i))).count();
+    assert!(false_positives < 750, "{} false positives", false_positives);
+}
+
+#[test]
+fn test_scalable_filter_ignores_duplicates_and_clears() {
+    use spectra_cache::ScalableBloomFilter;
+
+    let mut filter = ScalableBloomFilter::new(10, 0.01);
+    for _ in 0..100 {
+        filter.insert("same");
+    }
+    assert_eq!(filter.size(), 1);
+    assert_eq!(filter.filter_count(), 1);
+
+    for i in 0..100 {
+        filter.insert(&i);
+    }
+    assert!(filter.filter_count() > 1);
+    filter.clear();
+    assert!(filter.is_empty());
+    assert_eq!(filter.filter_count(), 1);
+    assert!(!filter.contains("same"));
+}
+
+#[test]
+fn test_scalable_filter_reserve() {
+    use spectra_cache::ScalableBloomFilter;
+
+    let mut filter = ScalableBloomFilter::new(100, 0.01);
+    filter.insert("early");
+    filter.reserve(5000);
+    let reserved = filter.filter_count();
+    assert!(reserved > 1);
+    for i in 0..5000 {
+        filter.insert(&i);
+    }
+    // Nada mais foi alocado durante a rajada
+    assert_eq!(filter.filter_count(), reserved);
+    assert!(filter.contains("early") && filter.contains(&4999));
+
+    filter.reserve(1);
+    assert_eq!(filter.filter_count(), reserved);
+}
+
+#[test]
+fn test_clone_is_independent() {
+    let mut filter = BloomFilter::new(100, 0.01);
+    filter.insert("a");
+    let mut copy = filter.clone();
+    copy.insert("b");
+
+    assert!(copy.contains("a") && copy.contains("b"));
+    assert!(!filter.contains("b"));
+    assert_eq!(filter.size(), 1);
+}
+
+#[test]
+fn test_custom_hasher() {
+    use spectra_cache::ScalableBloomFilter;
+    use std::collections::hash_map::RandomState;
+
+    let mut filter = BloomFilter::with_hasher(100, 0.01, RandomState::new());
+    filter.insert("apple");
+    assert!(filter.contains("apple"));
+
+    // Todos os sub-filtros compartilham o hasher, então clones continuam consistentes
+    let mut scalable = ScalableBloomFilter::with_hasher(10, 0.01, RandomState::new());
+    for i in 0..100 {
+        scalable.insert(&i);
+    }
+    let copy = scalable.clone();
+    assert!(scalable.filter_count() > 1);
+    assert!((0..100).all(|i| copy.contains(&i)));
+}
+
+#[test]
+fn test_false_positive_rate_with_many_hash_functions() {
+    // 0,01% pede 13 funções de hash, todas tiradas do mesmo par
+    let mut filter = BloomFilter::new(10_000, 0.0001);
+    for i in 0..10_000 {
+        filter.insert(&format!("key{}", i));
+    }
+    let false_positives = (0..200_000).filter(|i| filter.contains(&format!("absent{}", i))).count();
+    let actual_rate = false_positives as f64 / 200_000.0;
+    assert!(actual_rate <= 0.0003, "False positive rate {} exceeds expected 0.0001", actual_rate);
+}
+
+#[test]
+fn test_saturation_reporting() {
+    let mut filter = BloomFilter::new(1000, 0.01);
+    assert_eq!(filter.fill_ratio(), 0.0);
+    assert_eq!(filter.estimated_false_positive_rate(), 0.0);
+
+    for i in 0..1000 {
+        filter.insert(&format!("key{}", i));
+    }
+    // Cheio até a capacidade: metade dos bits e a taxa pedida
+    assert!((filter.fill_ratio() - 0.5).abs() < 0.05, "{}", filter.fill_ratio());
+    assert!(filter.estimated_false_positive_rate() < 0.02);
+    assert!(!filter.is_saturated(0.6));
+
+    for i in 1000..10_000 {
+        filter.insert(&format!("key{}", i));
+    }
+    assert!(filter.is_saturated(0.99));
+    assert!(filter.estimated_false_positive_rate() > 0.9);
+
+    filter.clear();
+    assert!(!filter.is_saturated(0.01));
+}
+
+#[test]
+fn test_scalable_filter_estimates_false_positive_rate() {
+    use spectra_cache::ScalableBloomFilter;
+
+    let mut filter = ScalableBloomFilter::new(1000, 0.01);
+    for i in 0..50_000 {
+        filter.insert(&format!("key:{}", i));
+    }
+    let estimate = filter.estimated_false_positive_rate();
+    assert!(estimate > 0.0 && estimate < 0.015, "{}", estimate);
+    assert!(filter.fill_ratio() > 0.3 && filter.fill_ratio() < 0.6);
+}
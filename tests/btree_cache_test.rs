@@ -1,3 +1,5 @@
+#![cfg(all(feature = "core", feature = "std"))]
+
 use spectra_cache::BTreeCache;
 use std::time::Duration;
 
@@ -38,8 +40,9 @@ fn test_insert_with_ttl() {
     // Espera o TTL expirar
     std::thread::sleep(Duration::from_millis(100));
     
-    // Verifica se o valor foi removido após o TTL expirar
+    // A entrada vencida some da leitura, mas só sai do cache no purge
     assert!(cache.get(key).is_none());
+    assert_eq!(cache.purge_expired(), 1);
     assert!(cache.is_empty());
 }
 
@@ -144,7 +147,7 @@ fn test_ordered_operations() {
     assert_eq!(cache.last(), Some((&"c".to_string(), "3")));
     
     // Test range
-    let range: Vec<_> = cache.range("a", "b").collect();
+    let range: Vec<_> = cache.range("a"..="b").collect();
     assert_eq!(range.len(), 2);
     assert_eq!(range[0].1, "1");
     assert_eq!(range[1].1, "2");
@@ -167,20 +170,278 @@ fn test_range_queries() {
     }
     
     // Testar range no meio
-    let mid_range: Vec<_> = cache.range("key3", "key6")
+    let mid_range: Vec<_> = cache.range("key3"..="key6")
         .map(|(_, v)| v.to_string())
         .collect();
     assert_eq!(mid_range, vec!["3", "4", "5", "6"]);
     
     // Testar range no início
-    let start_range: Vec<_> = cache.range("key0", "key2")
+    let start_range: Vec<_> = cache.range("key0"..="key2")
         .map(|(_, v)| v.to_string())
         .collect();
     assert_eq!(start_range, vec!["0", "1", "2"]);
     
     // Testar range no fim
-    let end_range: Vec<_> = cache.range("key7", "key9")
+    let end_range: Vec<_> = cache.range("key7"..="key9")
         .map(|(_, v)| v.to_string())
         .collect();
     assert_eq!(end_range, vec!["7", "8", "9"]);
-} 
\ No newline at end of file
+} 
+#[test]
+fn test_bloom_audit() {
+    let mut cache = BTreeCache::new();
+    cache.enable_bloom_audit();
+
+    cache.insert("key1", "value1");
+    cache.remove("key1");
+
+    // A chave removida continua no filtro, então conta como falso positivo
+    assert!(!cache.contains_key("key1"));
+
+    let audit = cache.bloom_audit().unwrap();
+    assert_eq!(audit.lookups(), 1);
+    assert_eq!(audit.false_positives(), 1);
+    assert_eq!(audit.false_negatives(), 0);
+    assert_eq!(audit.false_positive_rate(), 1.0);
+}
+
+#[test]
+fn test_stats() {
+    let mut cache = BTreeCache::new();
+
+    cache.insert("a", "1");
+    assert!(cache.get("a").is_some());
+    assert!(cache.get("b").is_none());
+
+    let stats = cache.stats();
+    assert_eq!(stats.entries, 1);
+    assert_eq!(stats.hits, 1);
+    assert_eq!(stats.misses, 1);
+}
+
+#[test]
+fn test_insert_with_tti() {
+    let mut cache = BTreeCache::new();
+    cache.insert_with_tti("session", "active", Duration::from_millis(50));
+    assert!(cache.contains_key("session"));
+
+    std::thread::sleep(Duration::from_millis(100));
+    assert!(!cache.contains_key("session"));
+}
+
+#[test]
+fn test_entry_api() {
+    let mut cache = BTreeCache::new();
+
+    cache.entry("b").or_insert("2");
+    cache.entry("a").or_insert("1");
+    cache.entry("a").and_modify(|v| v.push('!')).or_insert("unused");
+
+    let keys: Vec<_> = cache.keys().collect();
+    assert_eq!(keys, vec!["a", "b"]);
+    assert_eq!(cache.get("a"), Some("1!"));
+}
+
+#[test]
+fn test_range_prefix() {
+    let mut cache = BTreeCache::new();
+    cache.insert("user:2", "b");
+    cache.insert("user:1", "a");
+    cache.insert("user", "root");
+    cache.insert("userx", "other");
+    cache.insert("video:1", "v");
+
+    let users: Vec<_> = cache.range_prefix("user:").collect();
+    assert_eq!(users, vec![(&"user:1".to_string(), "a"), (&"user:2".to_string(), "b")]);
+    assert_eq!(cache.range_prefix("zzz").count(), 0);
+    assert_eq!(cache.range_prefix("").count(), 5);
+}
+
+#[test]
+fn test_keys_matching() {
+    let mut cache = BTreeCache::new();
+    for key in ["user:1", "user:10", "user:2", "session:1"] {
+        cache.insert(key, "value");
+    }
+
+    let keys: Vec<_> = cache.keys_matching("user:?").collect();
+    assert_eq!(keys, vec!["user:1", "user:2"]);
+
+    let keys: Vec<_> = cache.keys_matching("*:1*").collect();
+    assert_eq!(keys, vec!["session:1", "user:1", "user:10"]);
+}
+
+#[test]
+fn test_builder() {
+    let mut cache = BTreeCache::builder()
+        .default_ttl(Duration::from_millis(50))
+        .bloom_audit(true)
+        .build();
+
+    cache.insert("key", "value");
+    assert!(cache.bloom_audit().is_some());
+
+    std::thread::sleep(Duration::from_millis(100));
+    assert!(!cache.contains_key("key"));
+}
+
+#[test]
+fn test_max_memory_bytes() {
+    let mut cache = BTreeCache::builder().max_memory_bytes(2_000).build();
+    for i in 0..10 {
+        cache.insert(&format!("key:{}", i), &"x".repeat(500));
+    }
+
+    assert!(cache.memory_usage() <= 2_000);
+    assert!(cache.size() < 10);
+    assert!(cache.contains_key("key:9"));
+    assert!(!cache.contains_key("key:0"));
+}
+
+#[test]
+fn test_range_bounds() {
+    let mut cache = BTreeCache::new();
+    for i in 0..5 {
+        cache.insert(&format!("key{}", i), &i.to_string());
+    }
+
+    let values = |iter: Vec<(&String, &String)>| iter.into_iter().map(|(_, v)| v.to_string()).collect::<Vec<_>>();
+    assert_eq!(values(cache.range("key1".."key3").collect()), vec!["1", "2"]);
+    assert_eq!(values(cache.range("key3"..).collect()), vec!["3", "4"]);
+    assert_eq!(values(cache.range(.."key1").collect()), vec!["0"]);
+    assert_eq!(cache.range(..).count(), 5);
+    assert_eq!(values(cache.range_rev("key1"..="key3").collect()), vec!["3", "2", "1"]);
+}
+
+#[test]
+fn test_pop_first_and_last() {
+    let mut cache = BTreeCache::new();
+    cache.insert_with_ttl("a", "expired", Duration::from_millis(10));
+    cache.insert("b", "2");
+    cache.insert("c", "3");
+    cache.insert("d", "4");
+    std::thread::sleep(Duration::from_millis(50));
+
+    assert_eq!(cache.pop_first(), Some(("b".to_string(), "2".to_string())));
+    assert_eq!(cache.pop_last(), Some(("d".to_string(), "4".to_string())));
+    assert_eq!(cache.size(), 1);
+    assert_eq!(cache.stats().expirations, 1);
+
+    assert_eq!(cache.pop_first(), Some(("c".to_string(), "3".to_string())));
+    assert_eq!(cache.pop_last(), None);
+    assert_eq!(cache.memory_usage(), 0);
+}
+
+#[test]
+fn test_biggest_keys() {
+    let mut cache = BTreeCache::new();
+    for (i, len) in [10, 5000, 300, 20000, 1].iter().enumerate() {
+        cache.insert(&format!("key:{}", i), &"x".repeat(*len));
+    }
+    cache.insert_with_ttl("expired", &"x".repeat(100_000), Duration::from_millis(10));
+    std::thread::sleep(Duration::from_millis(50));
+
+    let biggest: Vec<_> = cache.biggest_keys(3).into_iter().map(|(k, _)| k.as_str()).collect();
+    assert_eq!(biggest, vec!["key:3", "key:1", "key:2"]);
+
+    let total: usize = cache.biggest_keys(10).iter().map(|(_, size)| size).sum();
+    assert_eq!(cache.biggest_keys(10).len(), 5);
+    assert!(total < cache.memory_usage());
+    assert!(cache.biggest_keys(0).is_empty());
+}
+
+#[test]
+fn test_default_and_equality() {
+    let mut cache = BTreeCache::default();
+    cache.insert("b", "2");
+    cache.insert("a", "1");
+
+    let mut other = BTreeCache::new();
+    other.insert("a", "1");
+    other.insert("b", "2");
+    assert_eq!(cache, other);
+    assert_eq!(cache.clone(), other);
+
+    other.remove("b");
+    assert_ne!(cache, other);
+}
+
+#[test]
+fn test_reserve_capacity() {
+    let mut cache = BTreeCache::builder().index_values().build();
+    cache.insert("a", "1");
+    cache.reserve_capacity(1000);
+    assert_eq!(cache.capacity(), 1);
+    for i in 0..1000 {
+        cache.insert(&format!("k{}", i), "v");
+    }
+    assert_eq!(cache.keys_with_value("v").len(), 1000);
+    assert_eq!(cache.capacity(), cache.size());
+}
+
+#[test]
+fn test_scan_pages() {
+    let mut cache = BTreeCache::new();
+    for i in 0..10 {
+        cache.insert(&format!("key:{:02}", i), &i.to_string());
+    }
+    cache.insert_with_ttl("key:05a", "gone", Duration::from_millis(1));
+    std::thread::sleep(Duration::from_millis(5));
+
+    let (page, cursor) = cache.scan(None, 4);
+    assert_eq!(page.len(), 4);
+    assert_eq!(cursor.as_deref(), Some("key:03"));
+
+    // Escritas entre páginas não desalinham o cursor
+    let cursor = cursor.unwrap();
+    cache.insert("key:00a", "behind");
+    cache.insert("key:04a", "ahead");
+    let (page, cursor) = cache.scan(Some(&cursor), 4);
+    let keys: Vec<&str> = page.iter().map(|(key, _)| key.as_str()).collect();
+    assert_eq!(keys, ["key:04", "key:04a", "key:05", "key:06"]);
+
+    let (page, cursor) = cache.scan(cursor.as_deref(), 4);
+    assert_eq!(page.len(), 3);
+    assert_eq!(cursor, None);
+
+    // Uma página vazia devolve o mesmo cursor
+    assert_eq!(cache.scan(Some("key:03"), 0), (Vec::new(), Some("key:03".to_string())));
+    assert_eq!(cache.scan(None, 0), (Vec::new(), None));
+}
+
+#[test]
+fn test_retain_and_drain_in_key_order() {
+    let mut cache: BTreeCache = [("c", "3"), ("a", "1"), ("b", "2")].into_iter().collect();
+    let mut visited = Vec::new();
+    assert_eq!(
+        cache.retain(|key, _| {
+            visited.push(key.to_string());
+            key != "b"
+        }),
+        1
+    );
+    assert_eq!(visited, ["a", "b", "c"]);
+
+    cache.extend([("d".to_string(), "4".to_string())]);
+    let drained = cache.drain();
+    assert_eq!(drained.iter().map(|(key, _)| key.as_str()).collect::<Vec<_>>(), ["a", "c", "d"]);
+    assert!(cache.is_empty());
+}
+
+#[test]
+fn test_invalidate_tag_in_key_order() {
+    use std::sync::{Arc, Mutex};
+
+    let mut cache = BTreeCache::new();
+    let removed = Arc::new(Mutex::new(Vec::new()));
+    let sink = Arc::clone(&removed);
+    cache.on_evict(move |key, _, _| sink.lock().unwrap().push(key.to_string()));
+    for key in ["c", "a", "d", "b"] {
+        cache.insert_with_tags(key, "v", &["batch"]);
+    }
+    cache.insert("e", "v");
+
+    assert_eq!(cache.invalidate_tag("batch"), 4);
+    assert_eq!(*removed.lock().unwrap(), ["a", "b", "c", "d"]);
+    assert_eq!(cache.keys().collect::<Vec<_>>(), ["e"]);
+}
@@ -0,0 +1,116 @@
+#![cfg(all(feature = "core", feature = "std"))]
+
+use spectra_cache::{BytesCache, CacheEvent, RemovalCause};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+#[test]
+fn test_binary_values_round_trip() {
+    let mut cache = BytesCache::new();
+    let blob: Vec<u8> = (0..=255).collect();
+    cache.insert("blob", &blob);
+
+    assert_eq!(cache.get("blob"), Some(blob.as_slice()));
+    assert_eq!(cache.peek("blob"), Some(blob.as_slice()));
+    assert_eq!(cache.size(), 1);
+    assert!(cache.memory_usage() > 256);
+
+    assert!(cache.update("blob", b"\xff\x00"));
+    assert_eq!(cache.remove("blob"), Some(vec![0xff, 0x00]));
+    assert!(cache.is_empty());
+}
+
+#[test]
+fn test_ttl_and_entry_api() {
+    let mut cache = BytesCache::builder().default_ttl(Duration::from_millis(50)).build();
+    cache.entry("counter").or_insert(&[0]);
+    cache.entry("counter").and_modify(|bytes| bytes[0] += 1);
+    assert_eq!(cache.get("counter"), Some(&[1][..]));
+
+    std::thread::sleep(Duration::from_millis(100));
+    assert!(!cache.contains_key("counter"));
+}
+
+#[test]
+fn test_eviction_listener_sees_bytes() {
+    let evicted = Arc::new(Mutex::new(Vec::new()));
+    let sink = Arc::clone(&evicted);
+    let mut cache = BytesCache::builder().max_memory_bytes(1_000).build();
+    cache.on_evict(move |key, value, cause| {
+        if cause == RemovalCause::Evicted {
+            sink.lock().unwrap().push((key.to_string(), value.len()));
+        }
+    });
+
+    cache.insert("a", &[0; 600]);
+    cache.insert("b", &[0; 600]);
+    assert_eq!(*evicted.lock().unwrap(), vec![("a".to_string(), 600)]);
+}
+
+#[test]
+fn test_weigher_sees_bytes() {
+    let mut cache = BytesCache::builder()
+        .weigher(|_, value: &[u8]| value.len() as u64)
+        .max_weight(1_000)
+        .build();
+    cache.insert("a", &[0; 600]);
+    cache.entry("a").and_modify(|bytes| bytes.truncate(100));
+    assert_eq!(cache.total_weight(), 100);
+
+    cache.insert("b", &[0; 600]);
+    cache.insert("c", &[0; 600]);
+    assert!(cache.contains_key("c"));
+    assert!(!cache.contains_key("a") && !cache.contains_key("b"));
+    assert_eq!(cache.total_weight(), 600);
+}
+
+#[test]
+fn test_deduplication_check() {
+    let mut cache = BytesCache::builder().index_values().build();
+    cache.insert("upload:1", &[0xde, 0xad]);
+    assert!(cache.contains_value(&[0xde, 0xad]));
+    assert_eq!(cache.keys_with_value(&[0xde, 0xad]), ["upload:1"]);
+
+    cache.clear();
+    assert!(!cache.contains_value(&[0xde, 0xad]));
+}
+
+#[test]
+fn test_subscribe_carries_binary_values() {
+    let mut cache = BytesCache::new();
+    let events = cache.subscribe("*");
+    cache.insert("blob", &[0, 159, 146, 150]);
+    cache.remove("blob");
+
+    let received: Vec<_> = events.try_iter().collect();
+    assert_eq!(
+        received,
+        [
+            CacheEvent::Insert { key: "blob".to_string(), value: vec![0, 159, 146, 150] },
+            CacheEvent::Remove { key: "blob".to_string() },
+        ]
+    );
+}
+
+#[test]
+fn test_migrate_between_bytes_caches() {
+    let mut from = BytesCache::new();
+    from.insert_with_ttl("blob:1", &[1, 2, 3], Duration::from_secs(60));
+    from.insert("other", &[4]);
+    let mut to = BytesCache::new();
+
+    assert_eq!(spectra_cache::migrate(&mut from, &mut to, |key, _| key.starts_with("blob:")), Ok(1));
+    assert_eq!(to.get("blob:1"), Some(&[1, 2, 3][..]));
+    assert!(to.ttl("blob:1").is_some());
+    assert_eq!(from.size(), 1);
+}
+
+#[test]
+fn test_collect_and_retain_bytes() {
+    let mut cache: BytesCache = [("a", vec![1, 2]), ("b", vec![])].into_iter().collect();
+    cache.extend([("c", &[9u8][..])]);
+    assert_eq!(cache.retain(|_, value| !value.is_empty()), 1);
+    let mut drained = cache.drain();
+    drained.sort();
+    assert_eq!(drained, [("a".to_string(), vec![1, 2]), ("c".to_string(), vec![9])]);
+}
@@ -0,0 +1,58 @@
+#![cfg(feature = "async")]
+
+use spectra_cache::{CacheActor, CacheError, DistributedHashTable};
+use std::time::Duration;
+
+#[tokio::test]
+async fn test_commands_round_trip() {
+    let cache = CacheActor::spawn(DistributedHashTable::new());
+    cache.insert("user:1", "Ana").await;
+    cache.insert_with_ttl("session", "on", Duration::from_millis(20)).await;
+    assert_eq!(cache.get("user:1").await.as_deref(), Some("Ana"));
+    assert!(cache.contains_key("session").await);
+    assert_eq!(cache.incr("hits", 2).await, Ok(2));
+    assert_eq!(
+        cache.incr("user:1", 1).await,
+        Err(CacheError::NotAnInteger { key: "user:1".to_string() })
+    );
+
+    tokio::time::sleep(Duration::from_millis(40)).await;
+    assert_eq!(cache.get("session").await, None);
+    assert_eq!(cache.remove("user:1").await.as_deref(), Some("Ana"));
+    assert_eq!(cache.size().await, 1);
+    assert_eq!(cache.stats().await.hits, 1);
+    cache.clear().await;
+    assert_eq!(cache.size().await, 0);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_many_tasks_share_one_owner() {
+    let cache = CacheActor::with_capacity(DistributedHashTable::new(), 4);
+    let tasks: Vec<_> = (0..32)
+        .map(|task| {
+            let cache = cache.clone();
+            tokio::spawn(async move {
+                for _ in 0..50 {
+                    cache.incr("counter", 1).await.unwrap();
+                }
+                cache.insert(&format!("task:{}", task), "done").await;
+            })
+        })
+        .collect();
+    for task in tasks {
+        task.await.unwrap();
+    }
+    assert_eq!(cache.get("counter").await.as_deref(), Some("1600"));
+    assert_eq!(cache.call(|table| table.keys_matching("task:*").count()).await, 32);
+}
+
+#[tokio::test]
+async fn test_panics_reach_the_caller_only() {
+    let cache = CacheActor::spawn(DistributedHashTable::new());
+    cache.insert("k", "v").await;
+
+    let panicking = cache.clone();
+    let result = tokio::spawn(async move { panicking.call(|_| panic!("boom")).await }).await;
+    assert!(result.unwrap_err().is_panic());
+    assert_eq!(cache.get("k").await.as_deref(), Some("v"));
+}
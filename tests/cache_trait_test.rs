@@ -0,0 +1,63 @@
+#![cfg(all(feature = "core", feature = "std"))]
+
+use spectra_cache::{BTreeCache, BytesCache, Cache, DistributedHashTable, SharedCache};
+use std::time::Duration;
+
+/// Runs the same checks against any text cache.
+fn exercise(cache: &mut dyn Cache) {
+    cache.insert("b", "2");
+    cache.insert("a", "1");
+    cache.insert_with_ttl("session", "on", Duration::from_secs(60));
+    cache.insert_with_ttl("gone", "x", Duration::from_millis(1));
+    std::thread::sleep(Duration::from_millis(5));
+
+    assert_eq!(cache.get("a"), Some("1"));
+    assert_eq!(cache.get("gone"), None);
+    assert!(cache.contains("b"));
+    assert!(!cache.contains("missing"));
+    assert!(cache.ttl("session").unwrap() <= Duration::from_secs(60));
+    assert_eq!(cache.ttl("a"), None);
+
+    let mut entries: Vec<_> = cache.iter().collect();
+    entries.sort();
+    assert_eq!(entries, [("a", "1"), ("b", "2"), ("session", "on")]);
+
+    assert_eq!(cache.remove("b"), Some("2".to_string()));
+    assert_eq!(cache.remove("b"), None);
+    // "gone" venceu, mas get() não o remove: conta até ser purgado
+    assert_eq!(cache.size(), 3);
+    let stats = cache.stats();
+    assert_eq!(stats.inserts, 4);
+    assert_eq!(stats.removals, 1);
+}
+
+#[test]
+fn test_text_caches_behave_alike() {
+    exercise(&mut DistributedHashTable::new());
+    exercise(&mut BTreeCache::new());
+}
+
+#[test]
+fn test_generic_code_over_bytes_cache() {
+    fn store_all<C: Cache<str, [u8]>>(cache: &mut C, entries: &[(&str, &[u8])]) -> usize {
+        for (key, value) in entries {
+            cache.insert(key, value);
+        }
+        cache.iter().count()
+    }
+
+    let mut cache = BytesCache::new();
+    assert_eq!(store_all(&mut cache, &[("a", &[1]), ("b", &[2, 3])]), 2);
+    assert_eq!(Cache::get(&mut cache, "b"), Some(&[2, 3][..]));
+    assert_eq!(Cache::remove(&mut cache, "a"), Some(vec![1]));
+}
+
+fn assert_sync<T: Sync>() {}
+
+#[test]
+fn test_caches_are_sync() {
+    assert_sync::<DistributedHashTable>();
+    assert_sync::<BTreeCache>();
+    assert_sync::<BytesCache>();
+    assert_sync::<SharedCache>();
+}
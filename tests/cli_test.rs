@@ -0,0 +1,108 @@
+#![cfg(feature = "cli")]
+
+use spectra_cache::{DistributedHashTable, FsyncPolicy};
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Output, Stdio};
+use std::time::Duration;
+
+fn temp_path(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("spectra-cli-{}-{}", std::process::id(), name));
+    let _ = fs::remove_file(&path);
+    path
+}
+
+fn spectra(args: &[&str], input: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_spectra"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child.stdin.take().unwrap().write_all(input.as_bytes()).unwrap();
+    child.wait_with_output().unwrap()
+}
+
+fn stdout(output: &Output) -> String {
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    String::from_utf8(output.stdout.clone()).unwrap()
+}
+
+#[test]
+fn test_repl_edits_a_snapshot() {
+    let path = temp_path("repl.snapshot");
+    let mut table = DistributedHashTable::new();
+    table.insert("user:1", "Ana");
+    table.insert_with_ttl("session:1", "active", Duration::from_secs(600));
+    table.save_snapshot(&path).unwrap();
+
+    let script = "get user:1\nset user:2 Bia Souza\nkeys user:*\nttl user:1\nttl session:1\nttl nope\ndel user:1 nope\nget user:1\nbogus\n";
+    let output = stdout(&spectra(&["repl", path.to_str().unwrap()], script));
+    let lines: Vec<&str> = output.lines().collect();
+    assert_eq!(lines[..4], ["Ana", "OK", "user:1", "user:2"]);
+    assert_eq!(lines[4], "-1");
+    assert!(lines[5].parse::<u64>().unwrap() > 500);
+    assert_eq!(lines[6..], ["-2", "1", "(nil)", "(error) unknown command 'bogus'"]);
+
+    let mut reloaded = DistributedHashTable::new();
+    reloaded.warm_from_snapshot(&path, |_| {}).unwrap();
+    assert_eq!(reloaded.get("user:1"), None);
+    assert_eq!(reloaded.get("user:2"), Some("Bia Souza"));
+    assert!(reloaded.ttl("session:1").is_some());
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_repl_logs_to_an_aof() {
+    let path = temp_path("repl.aof");
+    let mut table = DistributedHashTable::new();
+    table.enable_aof(&path, FsyncPolicy::Always).unwrap();
+    table.insert("user:1", "Ana");
+    drop(table);
+
+    stdout(&spectra(&["repl", path.to_str().unwrap()], "set user:2 Bia\ndel user:1\nsave\n"));
+
+    let mut replayed = DistributedHashTable::new();
+    replayed.replay(&path).unwrap();
+    assert_eq!(replayed.get("user:1"), None);
+    assert_eq!(replayed.get("user:2"), Some("Bia"));
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_export_then_import() {
+    let source = temp_path("export.snapshot");
+    let mut table = DistributedHashTable::new();
+    table.insert("b", "line\n\"two\"");
+    table.insert_with_ttl("a", "1", Duration::from_secs(60));
+    table.save_snapshot(&source).unwrap();
+
+    let exported = stdout(&spectra(&["export", source.to_str().unwrap()], ""));
+    let lines: Vec<&str> = exported.lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].starts_with(r#"{"key":"a","#) && lines[0].contains(r#""ttl_ms":"#));
+    assert_eq!(lines[1], r#"{"key":"b","value":"line\n\"two\""}"#);
+
+    // Um destino novo terminado em .aof vira arquivo de log
+    let target = temp_path("import.aof");
+    let output = spectra(&["import", target.to_str().unwrap()], &exported);
+    assert_eq!(String::from_utf8_lossy(&output.stderr), "imported 2 keys\n");
+
+    let mut replayed = DistributedHashTable::new();
+    assert_eq!(replayed.replay(&target).unwrap(), 2);
+    assert_eq!(replayed.get("b"), Some("line\n\"two\""));
+    assert!(replayed.ttl("a").unwrap() <= Duration::from_secs(60));
+    fs::remove_file(&source).unwrap();
+    fs::remove_file(&target).unwrap();
+}
+
+#[test]
+fn test_import_rejects_bad_lines() {
+    let path = temp_path("bad.snapshot");
+    let output = spectra(&["import", path.to_str().unwrap()], "{\"key\":\"a\",\"value\":\"1\"}\n{\"key\":\"b\"}\n");
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).starts_with("spectra: line 2: missing string field 'value'"));
+    assert!(!path.exists());
+}
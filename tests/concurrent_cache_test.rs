@@ -0,0 +1,69 @@
+#![cfg(all(feature = "core", feature = "std"))]
+
+use spectra_cache::ConcurrentCache;
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+#[test]
+fn test_threads_write_to_their_shards() {
+    let cache = Arc::new(ConcurrentCache::builder().shard_count(4).build());
+    let workers: Vec<_> = (0..8)
+        .map(|worker| {
+            let cache = Arc::clone(&cache);
+            thread::spawn(move || {
+                for i in 0..250 {
+                    cache.insert(&format!("key:{}:{}", worker, i), &i.to_string());
+                    cache.incr("counter", 1).unwrap();
+                }
+            })
+        })
+        .collect();
+    for worker in workers {
+        worker.join().unwrap();
+    }
+
+    assert_eq!(cache.size(), 2001);
+    assert_eq!(cache.get("counter"), Some("2000".to_string()));
+    let stats = cache.per_shard_stats();
+    assert_eq!(stats.len(), 4);
+    assert_eq!(stats.iter().map(|stats| stats.entries).sum::<usize>(), 2001);
+    for (shard, stats) in stats.iter().enumerate() {
+        assert!(stats.entries > 250, "shard {} has {} entries", shard, stats.entries);
+    }
+    assert_eq!(stats.iter().map(|stats| stats.inserts).sum::<u64>(), 2001);
+}
+
+#[test]
+fn test_operations_route_to_the_owning_shard() {
+    let cache = ConcurrentCache::new();
+    assert_eq!(cache.shard_count(), 16);
+    cache.insert("user:1", "Ana");
+    cache.insert_with_ttl("session:1", "active", Duration::from_millis(50));
+
+    assert_eq!(cache.get("user:1"), Some("Ana".to_string()));
+    assert!(cache.contains_key("session:1"));
+    assert_eq!(cache.remove("user:1"), Some("Ana".to_string()));
+    assert_eq!(cache.per_shard_stats()[cache.shard_for("user:1")].removals, 1);
+
+    thread::sleep(Duration::from_millis(100));
+    assert!(!cache.contains_key("session:1"));
+
+    cache.clear();
+    assert!(cache.is_empty());
+}
+
+#[test]
+fn test_builder_settings_apply_to_every_shard() {
+    let cache = ConcurrentCache::builder().default_ttl(Duration::from_millis(20)).shard_count(2).build();
+    cache.insert("a", "1");
+    cache.insert("b", "2");
+    thread::sleep(Duration::from_millis(50));
+    assert!(!cache.contains_key("a") && !cache.contains_key("b"));
+}
+
+#[test]
+#[should_panic(expected = "power of two")]
+fn test_shard_count_must_be_a_power_of_two() {
+    ConcurrentCache::builder().shard_count(6);
+}
@@ -0,0 +1,78 @@
+#![cfg(all(feature = "core", feature = "std"))]
+
+use spectra_cache::{DedupCache, DistributedHashTable};
+use std::thread;
+use std::time::Duration;
+
+#[test]
+fn test_identical_values_are_stored_once() {
+    let mut cache = DedupCache::new();
+    cache.insert("user:1", "global config");
+    cache.insert("user:2", "global config");
+    cache.insert("user:3", "override");
+
+    assert_eq!(cache.size(), 3);
+    assert_eq!(cache.unique_values(), 2);
+    assert_eq!(cache.value_bytes(), "global config".len() + "override".len());
+
+    let first = cache.get("user:1").unwrap();
+    let second = cache.get("user:2").unwrap();
+    assert_eq!(&*first, "global config");
+    assert!(std::sync::Arc::ptr_eq(&first, &second));
+    assert_eq!(cache.get("missing"), None);
+}
+
+#[test]
+fn test_blob_freed_with_its_last_reference() {
+    let mut cache = DedupCache::new();
+    cache.insert("a", "shared");
+    cache.insert("b", "shared");
+
+    assert_eq!(cache.remove("a").as_deref(), Some("shared"));
+    assert_eq!(cache.unique_values(), 1);
+    assert_eq!(cache.get("b").as_deref(), Some("shared"));
+
+    // Substituir o valor também solta a referência antiga
+    cache.insert("b", "other");
+    assert_eq!(cache.unique_values(), 1);
+    assert_eq!(cache.value_bytes(), "other".len());
+    assert_eq!(cache.remove("b").as_deref(), Some("other"));
+    assert_eq!(cache.remove("b"), None);
+    assert_eq!(cache.unique_values(), 0);
+    assert_eq!(cache.value_bytes(), 0);
+}
+
+#[test]
+fn test_reinserting_same_value_keeps_one_reference() {
+    let mut cache = DedupCache::new();
+    cache.insert("a", "v");
+    cache.insert("a", "v");
+    cache.insert("b", "v");
+    cache.remove("b");
+    assert_eq!(cache.get("a").as_deref(), Some("v"));
+
+    cache.remove("a");
+    assert_eq!(cache.unique_values(), 0);
+}
+
+#[test]
+fn test_expiration_eviction_and_clear_release_blobs() {
+    let mut cache = DedupCache::with_builder(DistributedHashTable::builder().max_memory_bytes(300));
+    cache.insert_with_ttl("short", "payload", Duration::from_millis(20));
+    cache.insert("long", "payload");
+    thread::sleep(Duration::from_millis(40));
+    assert!(!cache.contains_key("short"));
+    assert_eq!(cache.unique_values(), 1);
+
+    // Cada chave guarda só o id, então o despejo é por número de chaves
+    for i in 0..20 {
+        cache.insert(&format!("key:{}", i), &format!("value {}", i));
+    }
+    assert!(cache.size() < 21);
+    assert_eq!(cache.unique_values(), cache.size());
+
+    cache.clear();
+    assert!(cache.is_empty());
+    assert_eq!(cache.unique_values(), 0);
+    assert_eq!(cache.value_bytes(), 0);
+}
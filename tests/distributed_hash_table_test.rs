@@ -1,3 +1,5 @@
+#![cfg(all(feature = "core", feature = "std"))]
+
 use spectra_cache::DistributedHashTable;
 use std::time::Duration;
 
@@ -38,11 +40,71 @@ fn test_insert_with_ttl() {
     // Espera o TTL expirar
     std::thread::sleep(Duration::from_millis(100));
     
-    // Verifica se o valor foi removido após o TTL expirar
+    // A entrada vencida some da leitura, mas só sai da tabela no purge
     assert!(table.get(key).is_none());
+    assert_eq!(table.purge_expired(), 1);
+    assert!(table.is_empty());
+}
+
+#[test]
+fn test_get_stale_after_soft_ttl() {
+    let mut table = DistributedHashTable::builder()
+        .default_ttl(Duration::from_millis(150))
+        .soft_ttl(Duration::from_millis(50))
+        .build();
+    table.insert("key", "old");
+    assert!(!table.get_stale("key").unwrap().stale);
+
+    std::thread::sleep(Duration::from_millis(80));
+    let read = table.get_stale("key").unwrap();
+    assert_eq!((read.value, read.stale), ("old", true));
+    // get() continua servindo o valor vencido pelo TTL suave
+    assert_eq!(table.get("key"), Some("old"));
+
+    // Reescrever zera a idade
+    table.insert("key", "new");
+    assert!(!table.get_stale("key").unwrap().stale);
+
+    // O TTL rígido ainda remove a entrada
+    std::thread::sleep(Duration::from_millis(200));
+    assert!(table.get_stale("key").is_none());
     assert!(table.is_empty());
 }
 
+#[test]
+fn test_manual_clock_drives_expiry() {
+    use spectra_cache::clock::ManualClock;
+
+    let clock = ManualClock::new();
+    let mut table = DistributedHashTable::builder()
+        .clock(clock.clone())
+        .soft_ttl(Duration::from_secs(30))
+        .build();
+    table.insert_with_ttl("session", "on", Duration::from_secs(60));
+    table.insert_with_tti("idle", "x", Duration::from_secs(10));
+    table.insert("forever", "y");
+
+    // Sem avançar o relógio nada vence, por mais que o tempo real passe
+    std::thread::sleep(Duration::from_millis(20));
+    assert_eq!(table.ttl("session"), Some(Duration::from_secs(60)));
+
+    clock.advance(Duration::from_secs(8));
+    assert_eq!(table.get("idle"), Some("x"));
+    clock.advance(Duration::from_secs(8));
+    assert_eq!(table.get("idle"), Some("x"));
+    assert!(!table.get_stale("session").unwrap().stale);
+
+    clock.advance(Duration::from_secs(30));
+    assert!(table.get_stale("session").unwrap().stale);
+    assert_eq!(table.get("idle"), None);
+    assert_eq!(table.ttl("session"), Some(Duration::from_secs(14)));
+
+    clock.advance(Duration::from_secs(15));
+    assert_eq!(table.get("session"), None);
+    assert_eq!(table.purge_expired(), 2);
+    assert_eq!(table.keys().collect::<Vec<_>>(), ["forever"]);
+}
+
 #[test]
 fn test_remove() {
     let mut table = DistributedHashTable::new();
@@ -128,4 +190,1533 @@ fn test_values() {
     assert_eq!(values.len(), 2);
     assert!(values.contains(&&"value1".to_string()));
     assert!(values.contains(&&"value2".to_string()));
-} 
\ No newline at end of file
+} 
+
+#[test]
+fn test_iteration_skips_expired_entries() {
+    let mut table = DistributedHashTable::new();
+    table.insert("kept", "1");
+    table.insert_with_ttl("gone", "2", Duration::from_millis(10));
+    table.insert_with_ttl("session", "3", Duration::from_secs(60));
+    std::thread::sleep(Duration::from_millis(20));
+
+    let mut keys: Vec<_> = table.keys().collect();
+    keys.sort();
+    assert_eq!(keys, ["kept", "session"]);
+    assert_eq!(table.values().count(), 2);
+    assert_eq!(table.keys_raw().count(), 3);
+    assert_eq!(table.values_raw().count(), 3);
+
+    let mut entries: Vec<_> = table.iter().collect();
+    entries.sort_by_key(|(key, _, _)| key.as_str());
+    let (key, value, metadata) = &entries[1];
+    assert_eq!((key.as_str(), *value), ("session", "3"));
+    assert!(metadata.time_to_live.unwrap() <= Duration::from_secs(60));
+    assert_eq!(metadata.idle_timeout, None);
+    assert!(metadata.age >= Duration::from_millis(20));
+    assert_eq!(entries[0].2.time_to_live, None);
+    let biggest = table.biggest_keys(3);
+    assert_eq!(biggest.iter().find(|(key, _)| *key == "kept").map(|(_, size)| *size), Some(entries[0].2.size));
+}
+#[test]
+fn test_bloom_audit() {
+    let mut table = DistributedHashTable::new();
+    assert!(table.bloom_audit().is_none());
+
+    table.enable_bloom_audit();
+    for i in 0..100 {
+        table.insert(&format!("key{}", i), "value");
+    }
+
+    for i in 0..100 {
+        assert!(table.contains_key(&format!("key{}", i)));
+    }
+    for i in 0..1000 {
+        assert!(table.get(&format!("missing{}", i)).is_none());
+    }
+
+    let audit = table.bloom_audit().unwrap();
+    assert_eq!(audit.lookups(), 1100);
+    assert_eq!(audit.true_positives(), 100);
+    assert!(!audit.has_false_negatives());
+    assert_eq!(audit.false_positives() + audit.true_negatives(), 1000);
+    assert!(audit.false_positive_rate() <= 0.01);
+
+    table.disable_bloom_audit();
+    assert!(table.bloom_audit().is_none());
+}
+
+#[test]
+fn test_adaptive_bloom_bypass() {
+    let mut table = DistributedHashTable::builder().adaptive_bloom().build();
+    for i in 0..100 {
+        table.insert(&format!("key{}", i), "value");
+    }
+    assert!(!table.bloom_bypassed());
+
+    // Só acertos: o filtro não poupa nenhuma consulta
+    for round in 0..100 {
+        assert_eq!(table.get(&format!("key{}", round % 100)), Some("value"));
+    }
+    for _ in 0..50 {
+        for i in 0..100 {
+            table.get(&format!("key{}", i));
+        }
+    }
+    assert!(table.bloom_bypassed());
+    assert!(table.config().adaptive_bloom);
+
+    // Desviar do filtro não muda resultados
+    assert_eq!(table.get("absent"), None);
+    table.insert("late", "value");
+    assert_eq!(table.get("late"), Some("value"));
+
+    // Em modo auditoria o filtro é sempre consultado
+    table.enable_bloom_audit();
+    assert!(!table.bloom_bypassed());
+}
+
+#[test]
+fn test_stats() {
+    let mut table = DistributedHashTable::new();
+
+    table.insert("key1", "value1");
+    table.insert_with_ttl("key2", "value2", Duration::from_millis(50));
+    table.get("key1");
+    table.get("key1");
+    table.get("missing");
+    table.remove("key1");
+
+    std::thread::sleep(Duration::from_millis(100));
+    assert!(table.get("key2").is_none());
+    table.purge_expired();
+
+    let stats = table.stats();
+    assert_eq!(stats.entries, 0);
+    assert_eq!(stats.inserts, 2);
+    assert_eq!(stats.hits, 2);
+    assert_eq!(stats.misses, 2);
+    assert_eq!(stats.removals, 1);
+    assert_eq!(stats.expirations, 1);
+    assert_eq!(stats.evictions, 0);
+    assert_eq!(stats.hit_ratio(), 0.5);
+    // O filtro não esquece chaves removidas
+    assert!(stats.bloom_fill_ratio > 0.0);
+    assert!(stats.bloom_false_positive_rate < 0.01);
+}
+
+#[test]
+fn test_insert_with_tti() {
+    let mut table = DistributedHashTable::new();
+    table.insert_with_tti("session", "active", Duration::from_millis(100));
+
+    // Cada leitura reinicia o relógio de inatividade
+    for _ in 0..4 {
+        std::thread::sleep(Duration::from_millis(50));
+        assert_eq!(table.get("session"), Some("active"));
+    }
+
+    std::thread::sleep(Duration::from_millis(150));
+    assert!(table.get("session").is_none());
+    assert_eq!(table.purge_expired(), 1);
+    assert!(table.is_empty());
+}
+
+#[test]
+fn test_audit_log() {
+    use spectra_cache::{AuditAction, AuditContext, AuditEvent};
+    use std::sync::{Arc, Mutex};
+
+    let events: Arc<Mutex<Vec<AuditEvent>>> = Arc::new(Mutex::new(Vec::new()));
+    let sink_events = Arc::clone(&events);
+
+    let mut table = DistributedHashTable::new();
+    table.set_audit_sink(move |event: &AuditEvent| sink_events.lock().unwrap().push(event.clone()));
+    table.set_audit_context(AuditContext {
+        actor: Some("billing-service".to_string()),
+        client_addr: None,
+    });
+
+    table.insert("key1", "value1");
+    table.insert("key2", "value2");
+    table.remove("key1");
+    table.clear();
+    table.enable_bloom_audit();
+    table.insert_with_tags("profile:42", "<html>", &["user:42"]);
+    table.insert_with_tags("report:7", "...", &["user:42"]);
+    table.insert("session:1", "x");
+    table.invalidate_tag("user:42");
+    table.retain(|key, _| !key.starts_with("session:"));
+
+    let events = events.lock().unwrap();
+    assert_eq!(events.len(), 4);
+    assert_eq!(events[0].action, AuditAction::Flush { entries: 1 });
+    assert_eq!(events[0].actor.as_deref(), Some("billing-service"));
+    assert_eq!(
+        events[1].action,
+        AuditAction::ConfigChange {
+            setting: "bloom_audit".to_string(),
+            value: "on".to_string(),
+        }
+    );
+    assert_eq!(
+        events[2].action,
+        AuditAction::MassDelete {
+            operation: "invalidate_tag user:42".to_string(),
+            entries: 2,
+        }
+    );
+    assert_eq!(
+        events[3].action,
+        AuditAction::MassDelete {
+            operation: "retain".to_string(),
+            entries: 1,
+        }
+    );
+}
+
+#[test]
+fn test_entry_api() {
+    use spectra_cache::Entry;
+
+    let mut table = DistributedHashTable::new();
+
+    assert_eq!(table.entry("counter").or_insert("0"), "0");
+    for _ in 0..5 {
+        table
+            .entry("counter")
+            .and_modify(|v| *v = (v.parse::<u32>().unwrap() + 1).to_string())
+            .or_insert("0");
+    }
+    assert_eq!(table.get("counter"), Some("5"));
+
+    assert_eq!(table.entry("lazy").or_insert_with(|| "computed".to_string()), "computed");
+    assert_eq!(table.entry("lazy").or_insert_with(|| unreachable!()), "computed");
+
+    match table.entry("counter") {
+        Entry::Occupied(mut entry) => {
+            assert_eq!(entry.key(), "counter");
+            assert_eq!(entry.insert("10"), "5");
+            assert_eq!(entry.remove(), "10");
+        }
+        Entry::Vacant(_) => panic!("expected an occupied entry"),
+    }
+    assert!(!table.contains_key("counter"));
+    assert_eq!(table.stats().removals, 1);
+}
+
+#[test]
+fn test_entry_treats_expired_as_vacant() {
+    use spectra_cache::Entry;
+
+    let mut table = DistributedHashTable::new();
+    table.insert_with_ttl("key", "old", Duration::from_millis(50));
+    std::thread::sleep(Duration::from_millis(100));
+
+    match table.entry("key") {
+        Entry::Vacant(entry) => assert_eq!(entry.insert("new"), "new"),
+        Entry::Occupied(_) => panic!("expired entries must be vacant"),
+    }
+    assert_eq!(table.get("key"), Some("new"));
+    assert_eq!(table.size(), 1);
+    assert_eq!(table.stats().expirations, 1);
+}
+
+#[test]
+fn test_keys_matching() {
+    let mut table = DistributedHashTable::new();
+    table.insert("user:1", "a");
+    table.insert("user:2", "b");
+    table.insert("session:127", "c");
+    table.insert("session:17", "d");
+    table.insert_with_ttl("user:3", "e", Duration::from_millis(50));
+    std::thread::sleep(Duration::from_millis(100));
+
+    let mut users: Vec<_> = table.keys_matching("user:*").collect();
+    users.sort();
+    assert_eq!(users, vec!["user:1", "user:2"]);
+
+    let sessions: Vec<_> = table.keys_matching("session:??7").collect();
+    assert_eq!(sessions, vec!["session:127"]);
+
+    assert_eq!(table.keys_matching("*").count(), 4);
+    assert_eq!(table.keys_matching("nothing*").count(), 0);
+}
+
+#[test]
+fn test_builder_default_ttl() {
+    let mut table = DistributedHashTable::builder()
+        .default_ttl(Duration::from_millis(50))
+        .build();
+
+    table.insert("short", "value");
+    table.entry("via_entry").or_insert("value");
+    table.insert_with_ttl("long", "value", Duration::from_secs(60));
+    assert_eq!(table.size(), 3);
+
+    std::thread::sleep(Duration::from_millis(100));
+    assert!(table.get("short").is_none());
+    assert!(table.get("via_entry").is_none());
+    assert_eq!(table.get("long"), Some("value"));
+}
+
+#[test]
+fn test_incr_and_decr() {
+    use spectra_cache::CacheError;
+
+    let mut table = DistributedHashTable::new();
+
+    assert_eq!(table.incr("hits", 1), Ok(1));
+    assert_eq!(table.incr("hits", 5), Ok(6));
+    assert_eq!(table.decr("hits", 10), Ok(-4));
+    assert_eq!(table.get("hits"), Some("-4"));
+    assert_eq!(table.decr("fresh", 3), Ok(-3));
+
+    table.insert("name", "John");
+    assert_eq!(
+        table.incr("name", 1),
+        Err(CacheError::NotAnInteger { key: "name".to_string() })
+    );
+    assert_eq!(table.get("name"), Some("John"));
+
+    table.insert("max", &i64::MAX.to_string());
+    assert_eq!(table.incr("max", 1), Err(CacheError::Overflow { key: "max".to_string() }));
+    assert_eq!(table.decr("max", i64::MIN), Err(CacheError::Overflow { key: "max".to_string() }));
+}
+
+#[test]
+fn test_incr_keeps_ttl() {
+    let mut table = DistributedHashTable::new();
+    table.insert_with_ttl("window", "0", Duration::from_millis(50));
+    assert_eq!(table.incr("window", 1), Ok(1));
+
+    std::thread::sleep(Duration::from_millis(100));
+    // A janela expirou, então o contador recomeça do zero
+    assert_eq!(table.incr("window", 1), Ok(1));
+}
+
+#[test]
+fn test_memory_usage() {
+    let mut table = DistributedHashTable::new();
+    assert_eq!(table.memory_usage(), 0);
+
+    table.insert("small", "x");
+    let small = table.memory_usage();
+    table.insert("large", &"x".repeat(10_000));
+    assert!(table.memory_usage() >= small + 10_000);
+
+    table.update("large", "x");
+    assert_eq!(table.memory_usage(), 2 * small);
+    table.entry("small").and_modify(|value| value.push_str("yz"));
+    assert_eq!(table.memory_usage(), 2 * small + 2);
+
+    table.remove("large");
+    table.entry("small").or_insert("ignored");
+    assert_eq!(table.memory_usage(), small + 2);
+
+    table.clear();
+    assert_eq!(table.memory_usage(), 0);
+}
+
+#[test]
+fn test_max_memory_bytes_evicts_least_recently_used() {
+    let mut table = DistributedHashTable::builder().max_memory_bytes(1_000).build();
+    let value = "x".repeat(300);
+    table.insert("a", &value);
+    table.insert("b", &value);
+    assert_eq!(table.get("a"), Some(value.as_str()));
+
+    table.insert("c", &value);
+    assert!(table.memory_usage() <= 1_000);
+    assert!(!table.contains_key("b"));
+    assert!(table.contains_key("a"));
+    assert!(table.contains_key("c"));
+    assert_eq!(table.stats().evictions, 1);
+
+    // Uma entrada maior que o orçamento inteiro não sobrevive
+    table.insert("huge", &"x".repeat(2_000));
+    assert!(table.is_empty());
+}
+
+#[test]
+fn test_max_weight_with_weigher() {
+    // Peso pelo custo declarado no próprio valor, como "custo:payload"
+    let mut table = DistributedHashTable::builder()
+        .weigher(|_, value: &str| value.split(':').next().unwrap().parse().unwrap())
+        .max_weight(100)
+        .build();
+    table.insert("a", "40:a");
+    table.insert("b", "40:b");
+    assert_eq!(table.get("a"), Some("40:a"));
+    assert_eq!(table.total_weight(), 80);
+
+    table.insert("c", "30:c");
+    assert!(!table.contains_key("b"));
+    assert_eq!(table.total_weight(), 70);
+
+    // Atualizações no lugar repesam a entrada
+    assert!(table.update("c", "70:c"));
+    assert!(!table.contains_key("a"));
+    assert_eq!(table.total_weight(), 70);
+    table.remove("c");
+    assert_eq!(table.total_weight(), 0);
+
+    table.insert("huge", "101:x");
+    assert!(table.is_empty());
+    assert_eq!(table.config().max_weight, Some(100));
+}
+
+#[test]
+fn test_max_weight_without_weigher_counts_entries() {
+    let mut table = DistributedHashTable::builder().max_weight(3).build();
+    for key in ["a", "b", "c", "d"] {
+        table.insert(key, "value");
+    }
+    assert_eq!(table.size(), 3);
+    assert_eq!(table.total_weight(), 3);
+    assert!(!table.contains_key("a"));
+}
+
+#[test]
+fn test_memory_limit_purges_expired_entries_before_evicting() {
+    let mut table = DistributedHashTable::builder().max_memory_bytes(1_000).build();
+    let value = "x".repeat(150);
+    table.insert("live", &value);
+    table.insert_with_ttl("short", &value, Duration::from_millis(10));
+    table.insert_with_tti("idle", &value, Duration::from_millis(10));
+    std::thread::sleep(Duration::from_millis(20));
+
+    // "live" é a mais fria, mas as expiradas saem antes dela
+    table.insert("new", &value);
+    assert!(table.contains_key("live"));
+    assert!(table.contains_key("new"));
+    assert_eq!(table.size(), 2);
+    let stats = table.stats();
+    assert_eq!((stats.expirations, stats.evictions), (2, 0));
+}
+
+#[test]
+fn test_purge_and_drain_expired() {
+    let mut table = DistributedHashTable::new();
+    table.insert("live", "1");
+    table.insert_with_ttl("short", "2", Duration::from_millis(10));
+    table.insert_with_tti("idle", "3", Duration::from_millis(30));
+    table.insert_with_ttl("other", "4", Duration::from_millis(10));
+    std::thread::sleep(Duration::from_millis(20));
+    table.get("idle");
+    std::thread::sleep(Duration::from_millis(20));
+
+    // "idle" foi lida a tempo e continua viva
+    assert_eq!(table.size(), 4);
+    let mut drained = table.drain_expired();
+    drained.sort();
+    assert_eq!(drained, [("other".to_string(), "4".to_string()), ("short".to_string(), "2".to_string())]);
+    assert_eq!(table.size(), 2);
+    assert_eq!(table.stats().expirations, 2);
+    assert_eq!(table.purge_expired(), 0);
+
+    std::thread::sleep(Duration::from_millis(40));
+    assert_eq!(table.purge_expired(), 1);
+    assert_eq!(table.keys().collect::<Vec<_>>(), ["live"]);
+}
+
+#[test]
+fn test_dump_and_restore() {
+    use spectra_cache::{BTreeCache, CacheError};
+
+    let mut source = DistributedHashTable::new();
+    source.insert("user:123", "John Doe");
+    assert_eq!(source.dump("missing"), None);
+    let payload = source.dump("user:123").unwrap();
+
+    let mut target = BTreeCache::new();
+    target.restore("user:123", &payload, Some(Duration::from_millis(50))).unwrap();
+    assert_eq!(target.get("user:123"), Some("John Doe"));
+    std::thread::sleep(Duration::from_millis(100));
+    assert_eq!(target.get("user:123"), None);
+
+    let mut corrupted = payload.clone();
+    corrupted[5] ^= 1;
+    assert!(matches!(
+        source.restore("user:123", &corrupted, None),
+        Err(CacheError::InvalidDump { .. })
+    ));
+}
+
+#[test]
+fn test_on_evict_reports_causes() {
+    use spectra_cache::RemovalCause;
+    use std::sync::{Arc, Mutex};
+
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let sink = Arc::clone(&events);
+    let mut table = DistributedHashTable::builder().max_memory_bytes(1_000).build();
+    table.on_evict(move |key, value, cause| sink.lock().unwrap().push((key.to_string(), value.to_string(), cause)));
+
+    table.insert("key", "v1");
+    table.insert("key", "v2");
+    table.update("key", "v3");
+    assert_eq!(table.incr("counter", 1), Ok(1));
+    assert_eq!(table.incr("counter", 1), Ok(2));
+    table.remove("key");
+    table.insert_with_ttl("short", "lived", Duration::from_millis(10));
+    std::thread::sleep(Duration::from_millis(50));
+    assert_eq!(table.get("short"), None);
+    table.insert("big", &"x".repeat(800));
+    table.clear();
+
+    let events = events.lock().unwrap();
+    let summary: Vec<_> = events.iter().map(|(k, v, c)| (k.as_str(), v.len().min(2), *c)).collect();
+    assert_eq!(
+        summary,
+        vec![
+            ("key", 2, RemovalCause::Replaced),
+            ("key", 2, RemovalCause::Replaced),
+            ("counter", 1, RemovalCause::Replaced),
+            ("key", 2, RemovalCause::Removed),
+            ("short", 2, RemovalCause::Expired),
+            ("counter", 1, RemovalCause::Evicted),
+            ("big", 2, RemovalCause::Removed),
+        ]
+    );
+    assert_eq!(events[0].1, "v1");
+    assert_eq!(events[1].1, "v2");
+}
+
+#[test]
+fn test_soft_memory_limit_evicts_gradually() {
+    let mut table = DistributedHashTable::builder()
+        .soft_memory_bytes(2_500)
+        .max_memory_bytes(100_000)
+        .build();
+    let value = "x".repeat(400);
+    for i in 0..4 {
+        table.insert(&format!("key:{}", i), &value);
+    }
+    assert_eq!(table.stats().evictions, 0);
+
+    // Cada escrita acima da marca d'água despeja no máximo duas entradas
+    table.insert("bulk", &"x".repeat(4_000));
+    assert_eq!(table.stats().proactive_evictions, 2);
+    assert!(table.memory_usage() > 2_500);
+    assert!(table.contains_key("bulk"));
+
+    table.insert("small", "x");
+    table.insert("small", "y");
+    let stats = table.stats();
+    assert_eq!(stats.proactive_evictions, stats.evictions);
+    assert!(!table.contains_key("key:3"));
+}
+
+#[test]
+fn test_peek_does_not_touch() {
+    let mut table = DistributedHashTable::builder().max_memory_bytes(1_150).build();
+    let value = "x".repeat(300);
+    table.insert("a", &value);
+    table.insert("b", &value);
+    table.insert_with_tti("idle", "value", Duration::from_millis(50));
+
+    assert_eq!(table.peek("a"), Some(value.as_str()));
+    assert_eq!(table.peek("missing"), None);
+    assert_eq!(table.stats().hits + table.stats().misses, 0);
+
+    // "a" continua sendo a entrada menos usada recentemente
+    table.insert("c", &value);
+    assert_eq!(table.peek("a"), None);
+    assert!(table.peek("b").is_some());
+
+    std::thread::sleep(Duration::from_millis(30));
+    assert!(table.peek("idle").is_some());
+    std::thread::sleep(Duration::from_millis(30));
+    assert_eq!(table.peek("idle"), None);
+}
+
+#[test]
+fn test_get_with_budget() {
+    use spectra_cache::GetOptions;
+
+    let mut table = DistributedHashTable::new();
+    table.enable_bloom_audit();
+    table.insert("key", "value");
+    table.insert_with_tti("idle", "value", Duration::from_millis(20));
+
+    let skip_bloom = GetOptions { skip_bloom: true, ..GetOptions::default() };
+    assert_eq!(table.get_with("key", &skip_bloom), Some("value"));
+    assert_eq!(table.get_with("missing", &skip_bloom), None);
+    assert_eq!(table.bloom_audit().unwrap().lookups(), 0);
+
+    std::thread::sleep(Duration::from_millis(40));
+    let stale = GetOptions { allow_stale: true, ..GetOptions::default() };
+    assert_eq!(table.get_with("idle", &stale), Some("value"));
+    // Ler a entrada vencida não a renova
+    assert_eq!(table.get_with("idle", &stale), Some("value"));
+    // Uma leitura com &mut self remove a entrada vencida
+    assert_eq!(table.get_with("idle", &GetOptions::default()), None);
+    assert_eq!(table.get_with("idle", &stale), None);
+    assert_eq!(table.stats().hits, 3);
+}
+
+#[test]
+fn test_panicking_callbacks_do_not_break_the_table() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    let calls = Arc::new(AtomicUsize::new(0));
+    let counter = Arc::clone(&calls);
+    let mut table = DistributedHashTable::new();
+    table.on_evict(|key, _, _| {
+        if key == "boom" {
+            panic!("listener failure");
+        }
+    });
+    table.on_evict(move |_, _, _| {
+        counter.fetch_add(1, Ordering::Relaxed);
+    });
+    table.set_audit_sink(|_: &spectra_cache::AuditEvent| panic!("sink failure"));
+
+    table.insert("boom", "1");
+    assert_eq!(table.remove("boom"), Some("1".to_string()));
+    table.insert("other", "2");
+    table.clear();
+
+    // O pânico foi contido: a entrada saiu e o segundo listener rodou
+    assert!(table.is_empty());
+    assert_eq!(calls.load(Ordering::Relaxed), 2);
+    assert_eq!(table.stats().callback_panics, 2);
+
+    table.insert("after", "3");
+    assert_eq!(table.get("after"), Some("3"));
+}
+
+#[test]
+fn test_queued_listener_can_reenter_shared_table() {
+    use spectra_cache::{ListenerOverflow, RemovalCause};
+    use std::sync::{mpsc, Arc, Mutex};
+
+    let table = Arc::new(Mutex::new(
+        DistributedHashTable::builder()
+            .listener_queue(64, ListenerOverflow::DropNewest)
+            .build(),
+    ));
+    let (sender, receiver) = mpsc::channel();
+    let shared = Arc::clone(&table);
+    table.lock().unwrap().on_evict(move |key, value, cause| {
+        if cause == RemovalCause::Removed {
+            // Reentrar na tabela travaria se o listener rodasse inline
+            shared.lock().unwrap().insert(&format!("tombstone:{}", key), value);
+            sender.send(key.to_string()).unwrap();
+        }
+    });
+
+    table.lock().unwrap().insert("user:1", "Alice");
+    assert_eq!(table.lock().unwrap().remove("user:1"), Some("Alice".to_string()));
+
+    assert_eq!(receiver.recv_timeout(Duration::from_secs(5)).unwrap(), "user:1");
+    assert_eq!(table.lock().unwrap().get("tombstone:user:1"), Some("Alice"));
+}
+
+#[test]
+fn test_ttl_expire_and_persist() {
+    let mut table = DistributedHashTable::new();
+    table.insert("plain", "1");
+    table.insert_with_ttl("temp", "2", Duration::from_secs(60));
+    table.insert_with_tti("idle", "3", Duration::from_secs(30));
+
+    assert_eq!(table.ttl("plain"), None);
+    assert_eq!(table.ttl("missing"), None);
+    let ttl = table.ttl("temp").unwrap();
+    assert!(ttl > Duration::from_secs(59) && ttl <= Duration::from_secs(60));
+    assert!(table.ttl("idle").unwrap() <= Duration::from_secs(30));
+
+    assert!(table.expire("plain", Duration::from_millis(50)));
+    assert!(!table.expire("missing", Duration::from_secs(1)));
+    std::thread::sleep(Duration::from_millis(100));
+    assert_eq!(table.get("plain"), None);
+    assert!(!table.expire("plain", Duration::from_secs(1)));
+
+    assert!(table.persist("temp"));
+    assert!(table.persist("idle"));
+    assert!(!table.persist("temp"));
+    assert!(!table.persist("missing"));
+    assert_eq!(table.ttl("temp"), None);
+    assert_eq!(table.get("temp"), Some("2"));
+}
+
+#[test]
+fn test_clone_and_equality() {
+    let mut table = DistributedHashTable::builder().max_memory_bytes(1 << 20).build();
+    table.insert("a", "1");
+    table.insert_with_ttl("b", "2", Duration::from_millis(300));
+    table.on_evict(|_, _, _| {});
+
+    let mut copy = table.clone();
+    assert_eq!(copy, table);
+    assert_eq!(copy.stats().hits, 0);
+    assert!(copy.ttl("b").unwrap() <= Duration::from_millis(300));
+
+    copy.insert("a", "changed");
+    assert_ne!(copy, table);
+    copy.insert("a", "1");
+    assert_eq!(copy, table);
+    assert_eq!(table.get("a"), Some("1"));
+
+    std::thread::sleep(Duration::from_millis(150));
+    let fresh = table.clone_with_fresh_timestamps();
+    assert!(fresh.ttl("b").unwrap() > Duration::from_millis(200));
+
+    // Entradas expiradas não contam para a igualdade
+    std::thread::sleep(Duration::from_millis(200));
+    let mut only_a = DistributedHashTable::new();
+    only_a.insert("a", "1");
+    assert_eq!(table, only_a);
+    assert_ne!(fresh, only_a);
+}
+
+#[test]
+fn test_effective_config() {
+    use spectra_cache::{ListenerOverflow, MemoryLimit};
+
+    let mut table = DistributedHashTable::builder()
+        .default_ttl(Duration::from_secs(30))
+        .max_memory(MemoryLimit::Bytes(4096))
+        .listener_queue(64, ListenerOverflow::DropOldest)
+        .build();
+    let config = table.config();
+    assert_eq!(config.default_ttl, Some(Duration::from_secs(30)));
+    assert_eq!(config.max_memory_bytes, Some(4096));
+    assert_eq!(config.listener_queue, Some((64, ListenerOverflow::DropOldest)));
+    assert_eq!(config.backing_store, None);
+    assert!(!config.bloom_audit);
+
+    // Estado alterado em tempo de execução também aparece
+    table.enable_bloom_audit();
+    let config = table.config();
+    assert!(config.bloom_audit);
+    assert!(config.settings().contains(&("listener-overflow", "drop-oldest".to_string())));
+    assert_eq!(
+        config.to_json(),
+        concat!(
+            "{\"default_ttl_ms\":30000,\"soft_ttl_ms\":null,\"max_memory\":\"4096\",\"max_memory_bytes\":4096,",
+            "\"soft_memory_bytes\":null,\"max_weight\":null,\"bloom_audit\":true,\"adaptive_bloom\":false,",
+            "\"listener_queue_capacity\":64,",
+            "\"listener_overflow\":\"drop-oldest\",\"value_index\":false,\"backing_store\":null,",
+            "\"write_back_interval_ms\":null}"
+        )
+    );
+}
+
+#[test]
+fn test_keys_with_value_follows_every_write_path() {
+    for indexed in [false, true] {
+        let builder = DistributedHashTable::builder();
+        let mut table = if indexed { builder.index_values().build() } else { builder.build() };
+        table.insert("a", "blob");
+        table.insert("b", "blob");
+        table.insert("c", "other");
+        table.insert_with_ttl("gone", "blob", Duration::from_millis(5));
+        std::thread::sleep(Duration::from_millis(10));
+
+        let mut keys = table.keys_with_value("blob");
+        keys.sort();
+        assert_eq!(keys, ["a", "b"]);
+
+        table.update("a", "other");
+        table.entry("c").and_modify(|value| *value = "blob".to_string());
+        table.remove("b");
+        let mut keys = table.keys_with_value("other");
+        keys.sort();
+        assert_eq!(keys, ["a"]);
+        assert_eq!(table.keys_with_value("blob"), ["c"]);
+
+        assert!(table.contains_value("other"));
+        assert!(!table.contains_value("missing"));
+        assert_eq!(table.config().value_index, indexed);
+    }
+}
+
+#[test]
+fn test_custom_hasher() {
+    use std::collections::hash_map::DefaultHasher;
+    use std::hash::BuildHasher;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static BUILT: AtomicUsize = AtomicUsize::new(0);
+
+    #[derive(Debug, Clone, Default)]
+    struct CountingHasher;
+
+    impl BuildHasher for CountingHasher {
+        type Hasher = DefaultHasher;
+
+        fn build_hasher(&self) -> DefaultHasher {
+            BUILT.fetch_add(1, Ordering::Relaxed);
+            DefaultHasher::new()
+        }
+    }
+
+    let mut table = DistributedHashTable::builder()
+        .hasher::<CountingHasher>()
+        .default_ttl(Duration::from_secs(60))
+        .build();
+    table.insert("user:1", "Alice");
+    assert_eq!(table.get("user:1"), Some("Alice"));
+    assert_eq!(table.get("user:2"), None);
+    // Mapa e Bloom filter usam o hasher escolhido
+    assert!(BUILT.load(Ordering::Relaxed) >= 4);
+
+    let copy = table.clone();
+    assert_eq!(copy, table);
+    assert!(copy.ttl("user:1").is_some());
+}
+
+#[test]
+fn test_namespaces_isolate_tenants() {
+    use spectra_cache::{AuditAction, AuditEvent};
+    use std::sync::{Arc, Mutex};
+
+    let dropped = Arc::new(Mutex::new(Vec::new()));
+    let sink = Arc::clone(&dropped);
+    let mut table = DistributedHashTable::new();
+    table.set_audit_sink(move |event: &AuditEvent| sink.lock().unwrap().push(event.action.clone()));
+    {
+        let mut tenant = table.namespace("tenant:42");
+        assert_eq!(tenant.name(), "tenant:42");
+        tenant.insert("user:1", "Ana");
+        tenant.insert("user:2", "Bia");
+        tenant.insert_with_ttl("session", "active", Duration::from_millis(20));
+        assert_eq!(tenant.incr("visits", 3), Ok(3));
+        assert_eq!(tenant.get("user:1"), Some("Ana"));
+    }
+    table.namespace("tenant:4").insert("user:1", "Caio");
+    table.namespace("tenant:7").insert("user:1", "Duda");
+    assert_eq!(table.get("tenant:42:user:1"), Some("Ana"));
+    assert_eq!(table.namespace("tenant:7").get("user:1"), Some("Duda"));
+
+    std::thread::sleep(Duration::from_millis(40));
+    let mut tenant = table.namespace("tenant:42");
+    let mut keys: Vec<&str> = tenant.keys().collect();
+    keys.sort();
+    assert_eq!(keys, ["user:1", "user:2", "visits"]);
+    assert_eq!(tenant.size(), 3);
+    assert_eq!(tenant.remove("user:2"), Some("Bia".to_string()));
+
+    // O vencido também sai, mas só os vivos são contados
+    assert_eq!(tenant.clear_namespace(), 2);
+    assert!(tenant.is_empty());
+    assert_eq!(table.size(), 2);
+    assert_eq!(table.namespace("tenant:4").get("user:1"), Some("Caio"));
+
+    // Namespaces aninhados ficam dentro do pai
+    assert_eq!(table.namespace("tenant").clear_namespace(), 2);
+    assert!(table.is_empty());
+
+    let drop = |namespace: &str, entries| AuditAction::NamespaceDrop { namespace: namespace.to_string(), entries };
+    assert_eq!(*dropped.lock().unwrap(), [drop("tenant:42", 2), drop("tenant", 2)]);
+}
+
+#[test]
+fn test_swap_namespace_is_all_or_nothing() {
+    use std::sync::{Arc, Mutex};
+
+    let table = Arc::new(Mutex::new(DistributedHashTable::new()));
+    table.lock().unwrap().insert("other:1", "kept");
+    let version = |tag: &'static str, range: std::ops::Range<usize>| -> Vec<(String, &'static str)> {
+        range.map(|i| (format!("row:{}", i), tag)).collect()
+    };
+
+    let writer = {
+        let table = Arc::clone(&table);
+        std::thread::spawn(move || {
+            for round in 0..50 {
+                // O conjunto novo é montado antes de pegar a trava
+                let contents = if round % 2 == 0 { version("b", 25..75) } else { version("a", 0..50) };
+                table.lock().unwrap().swap_namespace("ref", contents);
+            }
+        })
+    };
+    for _ in 0..200 {
+        let mut table = table.lock().unwrap();
+        let namespace = table.namespace("ref");
+        let mut rows: Vec<(&str, &str)> = namespace.keys().map(|key| (key, namespace.peek(key).unwrap())).collect();
+        rows.sort();
+        if let Some(&(_, tag)) = rows.first() {
+            assert_eq!(rows.len(), 50);
+            assert!(rows.iter().all(|&(_, value)| value == tag));
+        }
+    }
+    writer.join().unwrap();
+
+    let mut table = table.lock().unwrap();
+    assert_eq!(table.swap_namespace("ref", Vec::<(&str, &str)>::new()), 50);
+    assert!(table.namespace("ref").is_empty());
+    assert_eq!(table.get("other:1"), Some("kept"));
+}
+
+#[test]
+fn test_subscribe_reports_keyspace_changes() {
+    use spectra_cache::CacheEvent;
+
+    let mut table = DistributedHashTable::builder().max_memory_bytes(1024).build();
+    let events = table.subscribe("user:*");
+    let everything = table.subscribe("*");
+
+    table.insert("user:1", "Ana");
+    table.insert("user:1", "Bia");
+    table.update("user:1", "Caio");
+    table.insert("session:1", "on");
+    assert_eq!(table.incr("user:visits", 1), Ok(1));
+    assert_eq!(table.incr("user:visits", 1), Ok(2));
+    table.insert_with_ttl("user:2", "Duda", Duration::from_millis(10));
+    std::thread::sleep(Duration::from_millis(20));
+    assert_eq!(table.get("user:2"), None);
+    table.purge_expired();
+    table.remove("user:1");
+    table.insert("user:big", &"x".repeat(750));
+
+    let owned = |key: &str| key.to_string();
+    let value = |value: &str| value.to_string();
+    let received: Vec<CacheEvent> = events.try_iter().collect();
+    assert_eq!(
+        received,
+        [
+            CacheEvent::Insert { key: owned("user:1"), value: value("Ana") },
+            CacheEvent::Update { key: owned("user:1"), value: value("Bia") },
+            CacheEvent::Update { key: owned("user:1"), value: value("Caio") },
+            CacheEvent::Insert { key: owned("user:visits"), value: value("1") },
+            CacheEvent::Update { key: owned("user:visits"), value: value("2") },
+            CacheEvent::Insert { key: owned("user:2"), value: value("Duda") },
+            CacheEvent::Expire { key: owned("user:2") },
+            CacheEvent::Remove { key: owned("user:1") },
+            CacheEvent::Insert { key: owned("user:big"), value: "x".repeat(750) },
+            CacheEvent::Evict { key: owned("user:visits") },
+        ]
+    );
+    let all: Vec<CacheEvent> = everything.try_iter().collect();
+    assert!(all.iter().any(|event| *event == CacheEvent::Evict { key: owned("session:1") }));
+
+    // Sem receptor a assinatura é descartada e o cache segue funcionando
+    drop(events);
+    table.clear();
+    let cleared: Vec<CacheEvent> = everything.try_iter().collect();
+    assert_eq!(cleared, [CacheEvent::Remove { key: owned("user:big") }]);
+}
+
+#[test]
+fn test_migrate_moves_entries_with_their_ttls() {
+    use spectra_cache::{migrate, BTreeCache};
+
+    let mut from = DistributedHashTable::new();
+    from.insert("tenant:1:a", "1");
+    from.insert_with_ttl("tenant:1:b", "2", Duration::from_secs(60));
+    from.insert_with_tti("tenant:1:c", "3", Duration::from_secs(30));
+    from.insert_with_ttl("tenant:1:gone", "4", Duration::from_millis(10));
+    from.insert("tenant:2:a", "5");
+    std::thread::sleep(Duration::from_millis(20));
+    let removed = from.subscribe("*");
+
+    let mut to = DistributedHashTable::builder().default_ttl(Duration::from_secs(5)).build();
+    to.insert("tenant:1:a", "stale");
+    let moved = migrate(&mut from, &mut to, |key, _| key.starts_with("tenant:1:")).unwrap();
+    assert_eq!(moved, 3);
+    assert_eq!(removed.try_iter().count(), 3);
+
+    assert_eq!(from.size(), 2);
+    assert_eq!(from.peek("tenant:1:a"), None);
+    assert_eq!(to.get("tenant:1:a"), Some("1"));
+    assert_eq!(to.ttl("tenant:1:a"), None);
+    let ttl = to.ttl("tenant:1:b").unwrap();
+    assert!(ttl <= Duration::from_secs(60) && ttl > Duration::from_secs(59));
+    assert!(to.ttl("tenant:1:c").unwrap() <= Duration::from_secs(30));
+    assert!(!to.contains_key("tenant:1:gone"));
+
+    // Entre tipos diferentes, filtrando pelo valor
+    let mut sorted = BTreeCache::new();
+    assert_eq!(migrate(&mut to, &mut sorted, |_, value| value != "1"), Ok(2));
+    assert_eq!(sorted.keys().collect::<Vec<_>>(), ["tenant:1:b", "tenant:1:c"]);
+    assert_eq!(to.size(), 1);
+}
+
+#[test]
+fn test_merge_from_conflict_strategies() {
+    use spectra_cache::ConflictStrategy;
+
+    let mut shared = DistributedHashTable::new();
+    shared.insert("old", "shared");
+    shared.insert("only-shared", "s");
+    let mut local = DistributedHashTable::new();
+    local.insert("old", "local");
+    local.insert_with_ttl("new", "local", Duration::from_secs(60));
+    local.insert_with_ttl("expired", "local", Duration::from_millis(1));
+    std::thread::sleep(Duration::from_millis(5));
+    local.insert("fresh", "stale");
+    shared.insert("fresh", "shared");
+    // Atualizar no lugar também conta como escrita
+    shared.update("old", "shared-updated");
+    local.update("old", "local-updated");
+
+    let mut kept = shared.clone();
+    assert_eq!(kept.merge_from(&local, ConflictStrategy::KeepExisting), 1);
+    assert_eq!(kept.get("old"), Some("shared-updated"));
+    assert_eq!(kept.get("fresh"), Some("shared"));
+    assert!(kept.ttl("new").unwrap() > Duration::from_secs(59));
+    assert!(!kept.contains_key("expired"));
+
+    let mut newest = shared.clone();
+    assert_eq!(newest.merge_from(&local, ConflictStrategy::KeepNewest), 2);
+    assert_eq!(newest.get("old"), Some("local-updated"));
+    assert_eq!(newest.get("fresh"), Some("shared"));
+    assert_eq!(newest.get("only-shared"), Some("s"));
+
+    let concat = ConflictStrategy::custom(|key, existing: &str, incoming: &str| format!("{}:{}+{}", key, existing, incoming));
+    assert_eq!(shared.merge_from(&local, concat), 3);
+    assert_eq!(shared.get("fresh"), Some("fresh:shared+stale"));
+    assert_eq!(local.size(), 4);
+}
+
+#[test]
+fn test_warm_from_loads_with_default_ttl_and_reports_progress() {
+    let mut table = DistributedHashTable::builder().default_ttl(Duration::from_secs(60)).build();
+    table.insert("existing", "kept");
+
+    let mut reports = Vec::new();
+    let loaded = table.warm_from((0..3000).map(|i| (format!("key:{}", i), i.to_string())), |n| reports.push(n));
+
+    assert_eq!(loaded, 3000);
+    assert_eq!(reports, [1024, 2048, 3000]);
+    assert_eq!(table.size(), 3001);
+    assert_eq!(table.get("key:2999"), Some("2999"));
+    assert_eq!(table.get("existing"), Some("kept"));
+    assert!(table.ttl("key:0").unwrap() <= Duration::from_secs(60));
+
+    let mut empty = DistributedHashTable::new();
+    let mut reports = Vec::new();
+    assert_eq!(empty.warm_from(Vec::<(&str, &str)>::new(), |n| reports.push(n)), 0);
+    assert_eq!(reports, [0]);
+}
+
+#[test]
+#[cfg(feature = "persistence")]
+fn test_snapshot_round_trip_between_cache_types() {
+    use spectra_cache::BTreeCache;
+
+    let path = std::env::temp_dir().join(format!("spectra-cache-snapshot-{}.bin", std::process::id()));
+    let mut source = DistributedHashTable::new();
+    source.insert("user:1", "Ana");
+    source.insert_with_ttl("session:1", "active", Duration::from_secs(60));
+    source.insert_with_ttl("expired", "gone", Duration::from_millis(1));
+    std::thread::sleep(Duration::from_millis(5));
+    assert_eq!(source.save_snapshot(&path).unwrap(), 2);
+
+    let mut target = BTreeCache::new();
+    assert_eq!(target.warm_from_snapshot(&path, |_| {}).unwrap(), 2);
+    assert_eq!(target.get("user:1"), Some("Ana"));
+    assert_eq!(target.ttl("user:1"), None);
+    assert!(target.ttl("session:1").unwrap() > Duration::from_secs(50));
+    assert!(!target.contains_key("expired"));
+
+    // Um arquivo corrompido não carrega nada
+    let mut bytes = std::fs::read(&path).unwrap();
+    bytes.truncate(bytes.len() - 1);
+    std::fs::write(&path, bytes).unwrap();
+    let mut table = DistributedHashTable::new();
+    let error = table.warm_from_snapshot(&path, |_| {}).unwrap_err();
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+    assert!(table.is_empty());
+
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(table.warm_from_snapshot(&path, |_| {}).unwrap_err().kind(), std::io::ErrorKind::NotFound);
+}
+
+#[test]
+#[cfg(feature = "persistence")]
+fn test_diff_snapshots() {
+    use spectra_cache::{diff_snapshots, KeyDiff};
+
+    let dir = std::env::temp_dir();
+    let before = dir.join(format!("spectra-cache-diff-before-{}.bin", std::process::id()));
+    let after = dir.join(format!("spectra-cache-diff-after-{}.bin", std::process::id()));
+    let mut table = DistributedHashTable::new();
+    table.insert("same", "1");
+    table.insert("changed", "old");
+    table.insert("removed", "x");
+    table.insert_with_ttl("expiring", "e1", Duration::from_millis(50));
+    table.save_snapshot(&before).unwrap();
+
+    table.insert("changed", "new");
+    table.remove("removed");
+    table.insert("added", "y");
+    // Só o TTL mudou: não conta como mudança
+    table.expire("same", Duration::from_secs(60));
+    table.insert_with_ttl("expiring", "e2", Duration::from_millis(50));
+    table.save_snapshot(&after).unwrap();
+    std::thread::sleep(Duration::from_millis(60));
+
+    let diff = diff_snapshots(&before, &after, false).unwrap();
+    let keys = |changes: &[KeyDiff]| changes.iter().map(|change| change.key.clone()).collect::<Vec<_>>();
+    assert_eq!(keys(&diff.added), ["added"]);
+    assert_eq!(keys(&diff.removed), ["removed"]);
+    // Entradas que expiraram desde o snapshot ainda entram na comparação
+    assert_eq!(keys(&diff.changed), ["changed", "expiring"]);
+    assert_eq!(diff.changed[0].before, None);
+
+    let diff = diff_snapshots(&before, &after, true).unwrap();
+    let changed = KeyDiff { key: "changed".to_string(), before: Some("old".to_string()), after: Some("new".to_string()) };
+    assert_eq!(diff.changed[0], changed);
+    assert_eq!(diff.added[0].after.as_deref(), Some("y"));
+    assert!(diff_snapshots(&before, &before, true).unwrap().is_empty());
+
+    std::fs::remove_file(&before).unwrap();
+    std::fs::remove_file(&after).unwrap();
+    assert!(diff_snapshots(&before, &after, false).is_err());
+}
+
+#[test]
+fn test_history_keeps_the_latest_changes_per_key() {
+    use spectra_cache::{AuditContext, CacheEvent};
+
+    let mut table = DistributedHashTable::builder().history(3).build();
+    table.insert("k", "1");
+    table.set_audit_context(AuditContext { actor: Some("worker-2".to_string()), client_addr: None });
+    table.insert("k", "2");
+    table.update("k", "3");
+    table.remove("k");
+    table.insert_with_ttl("session", "on", Duration::from_millis(5));
+    std::thread::sleep(Duration::from_millis(10));
+    assert_eq!(table.get("session"), None);
+    table.purge_expired();
+
+    let events: Vec<_> = table.history("k", 10).into_iter().map(|change| change.event).collect();
+    assert_eq!(
+        events,
+        [
+            CacheEvent::Remove { key: "k".to_string() },
+            CacheEvent::Update { key: "k".to_string(), value: "3".to_string() },
+            CacheEvent::Update { key: "k".to_string(), value: "2".to_string() },
+        ]
+    );
+    let latest = &table.history("k", 1)[0];
+    assert_eq!(latest.context.actor.as_deref(), Some("worker-2"));
+    assert!(latest.timestamp <= std::time::SystemTime::now());
+    assert_eq!(table.history("session", 10)[0].event, CacheEvent::Expire { key: "session".to_string() });
+    assert!(table.history("missing", 10).is_empty());
+
+    let mut plain = DistributedHashTable::new();
+    plain.insert("k", "1");
+    assert!(plain.history("k", 10).is_empty());
+}
+
+#[test]
+fn test_redaction_hides_values_from_debug_and_history() {
+    use spectra_cache::{CacheEvent, Redactor};
+
+    let redactor = Redactor::new().mask("token:").mask_all_but_last("card:", 4);
+    let mut table = DistributedHashTable::builder().history(2).redact(redactor.clone()).build();
+    table.insert("token:1", "s3cr3t");
+    table.insert("card:1", "4111111111111111");
+    table.insert("page", "home");
+
+    let debug = format!("{:?}", table);
+    assert!(!debug.contains("s3cr3t") && !debug.contains("4111111111111111"));
+    assert!(debug.contains("[REDACTED]") && debug.contains("************1111") && debug.contains("home"));
+    assert_eq!(table.history("token:1", 1)[0].event, CacheEvent::Insert { key: "token:1".to_string(), value: "[REDACTED]".to_string() });
+    // As leituras continuam vendo o valor real
+    assert_eq!(table.get("token:1"), Some("s3cr3t"));
+
+    // O diff de snapshots também esconde os valores
+    #[cfg(feature = "persistence")]
+    {
+        let dir = std::env::temp_dir();
+        let before = dir.join(format!("spectra-cache-redact-before-{}.bin", std::process::id()));
+        let after = dir.join(format!("spectra-cache-redact-after-{}.bin", std::process::id()));
+        table.save_snapshot(&before).unwrap();
+        table.insert("token:1", "r0tated");
+        table.insert("page", "about");
+        table.save_snapshot(&after).unwrap();
+        let mut diff = spectra_cache::diff_snapshots(&before, &after, true).unwrap();
+        diff.redact(&redactor);
+        assert_eq!(diff.changed[0].key, "page");
+        assert_eq!(diff.changed[0].after.as_deref(), Some("about"));
+        assert_eq!(diff.changed[1].before.as_deref(), Some("[REDACTED]"));
+        assert_eq!(diff.changed[1].after.as_deref(), Some("[REDACTED]"));
+        std::fs::remove_file(&before).unwrap();
+        std::fs::remove_file(&after).unwrap();
+    }
+}
+
+#[test]
+#[cfg(feature = "probabilistic")]
+fn test_hot_keys() {
+    let mut table = DistributedHashTable::builder().track_hot_keys(2).build();
+    table.insert("hot", "1");
+    for i in 0..300 {
+        table.get("hot");
+        table.get(&format!("miss:{}", i));
+        if i % 2 == 0 {
+            table.get("warm");
+        }
+    }
+    let hot = table.hot_keys();
+    assert_eq!(hot.len(), 2);
+    assert_eq!(hot[0], ("hot".to_string(), 301));
+    assert_eq!(hot[1].0, "warm");
+    assert!(hot[1].1 >= 150);
+
+    table.track_hot_keys(1);
+    assert!(table.hot_keys().is_empty());
+    table.get("warm");
+    assert_eq!(table.hot_keys(), [("warm".to_string(), 1)]);
+    table.stop_tracking_hot_keys();
+    assert!(table.hot_keys().is_empty());
+}
+
+#[test]
+fn test_deterministic_bulk_operations() {
+    use std::sync::{Arc, Mutex};
+
+    let run = || {
+        let mut table = DistributedHashTable::builder().deterministic().build();
+        let removed = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&removed);
+        table.on_evict(move |key, _, _| sink.lock().unwrap().push(key.to_string()));
+        for i in (0..50).rev() {
+            table.insert(&format!("k{:02}", i), "v");
+        }
+        table.clear();
+        let removed = removed.lock().unwrap().clone();
+        removed
+    };
+    let removed = run();
+    let mut sorted = removed.clone();
+    sorted.sort();
+    assert_eq!(removed, sorted);
+    assert_eq!(removed.len(), 50);
+    assert_eq!(run(), removed);
+}
+
+#[test]
+fn test_scan_returns_every_key_once() {
+    use std::collections::HashSet;
+
+    let mut table = DistributedHashTable::new();
+    for i in 0..100 {
+        table.insert(&format!("key:{}", i), "value");
+    }
+
+    let mut seen = HashSet::new();
+    let mut cursor: Option<String> = None;
+    let mut pages = 0;
+    loop {
+        let (page, next) = table.scan(cursor.as_deref(), 7);
+        assert!(page.len() <= 7);
+        for (key, _) in page {
+            assert!(seen.insert(key.clone()), "{} returned twice", key);
+        }
+        pages += 1;
+        // Escritas durante o scan não atrapalham as chaves que ficam
+        table.insert(&format!("new:{}", pages), "value");
+        table.remove(&format!("key:{}", 99 - pages));
+        match next {
+            Some(next) => cursor = Some(next),
+            None => break,
+        }
+    }
+    for i in 0..(99 - pages) {
+        assert!(seen.contains(&format!("key:{}", i)));
+    }
+}
+
+#[test]
+fn test_scan_with_zero_limit() {
+    let mut table = DistributedHashTable::new();
+    table.insert("key:1", "value");
+
+    let (page, cursor) = table.scan(Some("key:1"), 0);
+    assert!(page.is_empty());
+    assert_eq!(cursor.as_deref(), Some("key:1"));
+    assert_eq!(table.scan(None, 0), (Vec::new(), None));
+}
+
+#[test]
+fn test_retain_drain_and_collect() {
+    use spectra_cache::RemovalCause;
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+
+    let source: HashMap<String, String> = (0..10).map(|i| (format!("k{}", i), i.to_string())).collect();
+    let mut table: DistributedHashTable = source.clone().into_iter().collect();
+    assert_eq!(table.size(), 10);
+    let removed = Arc::new(Mutex::new(Vec::new()));
+    let sink = Arc::clone(&removed);
+    table.on_evict(move |key, _, cause| sink.lock().unwrap().push((key.to_string(), cause)));
+
+    // Mantém só os pares
+    table.insert_with_ttl("gone", "1", Duration::from_millis(1));
+    std::thread::sleep(Duration::from_millis(5));
+    assert_eq!(table.retain(|_, value| value.parse::<u32>().unwrap() % 2 == 0), 5);
+    assert_eq!(table.size(), 6);
+    assert!(removed.lock().unwrap().iter().all(|(_, cause)| *cause == RemovalCause::Removed));
+
+    table.extend(vec![("k1", "one")]);
+    table.extend(HashMap::from([("k3".to_string(), "three".to_string())]));
+    let mut drained = table.drain();
+    drained.sort();
+    assert_eq!(drained.len(), 7);
+    assert_eq!(drained[1], ("k1".to_string(), "one".to_string()));
+    assert!(table.is_empty());
+}
+
+#[test]
+fn test_tags_and_invalidate_tag() {
+    use spectra_cache::RemovalCause;
+    use std::sync::{Arc, Mutex};
+
+    let mut table = DistributedHashTable::builder().max_weight(3).build();
+    let removed = Arc::new(Mutex::new(Vec::new()));
+    let sink = Arc::clone(&removed);
+    table.on_evict(move |key, _, cause| sink.lock().unwrap().push((key.to_string(), cause)));
+
+    table.insert_with_tags("profile:42", "a", &["user:42", "user:42"]);
+    table.insert_with_tags("report:7", "b", &["user:42", "report"]);
+    table.insert_with_tags("report:8", "c", &["report"]);
+    // Atualizar no lugar mantém as tags; sobrescrever com insert as descarta
+    assert!(table.update("profile:42", "a2"));
+    table.insert("report:8", "c2");
+
+    assert_eq!(table.invalidate_tag("report"), 1);
+    assert_eq!(*removed.lock().unwrap(), [
+        ("profile:42".to_string(), RemovalCause::Replaced),
+        ("report:8".to_string(), RemovalCause::Replaced),
+        ("report:7".to_string(), RemovalCause::Removed),
+    ]);
+    assert_eq!(table.invalidate_tag("report"), 0);
+    assert!(table.contains_key("report:8"));
+
+    // Entradas despejadas saem do índice reverso
+    table.insert_with_tags("tmp:1", "x", &["user:42"]);
+    table.insert("fill:1", "y");
+    table.insert("fill:2", "z");
+    assert!(!table.contains_key("profile:42"));
+    assert_eq!(table.invalidate_tag("user:42"), 1);
+    assert_eq!(table.keys().count(), 2);
+    assert_eq!(table.invalidate_tag("unknown"), 0);
+
+    table.insert_with_tags("gone", "1", &["t"]);
+    table.insert_with_ttl("also-gone", "2", Duration::from_millis(1));
+    std::thread::sleep(Duration::from_millis(5));
+    table.clear();
+    assert_eq!(table.invalidate_tag("t"), 0);
+}
+
+#[test]
+fn test_transaction_all_or_nothing() {
+    use spectra_cache::{CacheError, Transaction};
+
+    let mut table = DistributedHashTable::builder().default_ttl(Duration::from_secs(60)).build();
+    table.insert("balance:a", "10");
+    table.insert("balance:b", "5");
+    table.insert("pending", "1");
+
+    let mut tx = Transaction::new();
+    tx.watch("balance:a", Some("10"))
+        .watch("lock", None)
+        .insert("balance:a", "7")
+        .insert_with_ttl("balance:b", "8", Duration::from_secs(5))
+        .remove("pending");
+    assert_eq!(tx.len(), 3);
+    let retry = tx.clone();
+    table.apply(tx).unwrap();
+    assert_eq!(table.get("balance:a"), Some("7"));
+    assert_eq!(table.get("balance:b"), Some("8"));
+    assert!(table.ttl("balance:a").unwrap() > Duration::from_secs(5));
+    assert!(table.ttl("balance:b").unwrap() <= Duration::from_secs(5));
+    assert!(!table.contains_key("pending"));
+
+    // Reaplicar falha: "balance:a" já não vale 10
+    let error = table.apply(retry).unwrap_err();
+    assert_eq!(error, CacheError::TransactionConflict { key: "balance:a".to_string() });
+    assert_eq!(error.to_string(), "transaction aborted: watched key 'balance:a' changed");
+
+    table.insert("lock", "held");
+    let mut tx = Transaction::new();
+    tx.watch("lock", None).insert("balance:a", "0");
+    assert!(table.apply(tx).is_err());
+    assert_eq!(table.get("balance:a"), Some("7"));
+    assert!(table.apply(Transaction::new()).is_ok());
+}
+
+#[test]
+fn test_versioned_updates() {
+    use spectra_cache::CacheError;
+
+    let mut table = DistributedHashTable::new();
+    assert_eq!(table.get_versioned("k"), None);
+    table.insert("k", "a");
+    let (value, first) = table.get_versioned("k").unwrap();
+    assert_eq!(value, "a");
+
+    // Dois escritores leem a mesma versão; só o primeiro vence
+    let second = table.update_if_version("k", first, "b").unwrap();
+    assert!(second > first);
+    let error = table.update_if_version("k", first, "c").unwrap_err();
+    assert_eq!(error, CacheError::VersionMismatch { key: "k".to_string(), current: Some(second) });
+    assert_eq!(error.to_string(), format!("key 'k' is at version {}", second));
+    assert_eq!(table.get_versioned("k"), Some(("b", second)));
+
+    // Toda escrita muda a versão, inclusive pela entry API e após remoção
+    table.entry("k").and_modify(|value| *value = "d".to_string());
+    let (_, third) = table.get_versioned("k").unwrap();
+    assert!(third > second);
+    table.remove("k");
+    assert_eq!(
+        table.update_if_version("k", third, "e"),
+        Err(CacheError::VersionMismatch { key: "k".to_string(), current: None })
+    );
+    table.insert("k", "f");
+    assert!(table.get_versioned("k").unwrap().1 > third);
+}
+
+#[test]
+fn test_recency_and_frequency_order() {
+    let mut table = DistributedHashTable::new();
+    for key in ["a", "b", "c", "d"] {
+        table.insert(key, key);
+    }
+    table.insert_with_ttl("gone", "x", Duration::from_millis(1));
+    table.get("a");
+    table.get("a");
+    table.get("c");
+    table.update("b", "B");
+    assert_eq!(table.peek("d"), Some("d"));
+    std::thread::sleep(Duration::from_millis(5));
+
+    let recency: Vec<_> = table.iter_by_recency().map(|(key, _)| key.as_str()).collect();
+    assert_eq!(recency, ["d", "a", "c", "b"]);
+    assert_eq!(table.coldest(2), ["d", "a"]);
+    assert_eq!(table.hottest(2), ["b", "c"]);
+    assert_eq!(table.coldest(10).len(), 4);
+
+    let frequency: Vec<_> = table.iter_by_frequency().collect();
+    assert_eq!(frequency, [(&"d".to_string(), "d"), (&"c".to_string(), "c"), (&"b".to_string(), "B"), (&"a".to_string(), "a")]);
+
+    // Reinserir zera a contagem de usos
+    table.insert("a", "again");
+    assert_eq!(table.iter_by_frequency().next().map(|(key, _)| key.as_str()), Some("d"));
+    assert_eq!(table.hottest(1), ["a"]);
+}
+
+#[test]
+fn test_bloom_rebuild_on_churn() {
+    let mut table = DistributedHashTable::builder().bloom_rebuild(0.5).build();
+    for i in 0..1000 {
+        table.insert(&format!("old:{}", i), "x");
+    }
+    // Metade das chaves saiu, mas o limite ainda não foi passado
+    table.retain(|key, _| key["old:".len()..].parse::<u32>().unwrap() < 550);
+    table.insert("new:0", "x");
+    assert_eq!(table.stats().bloom_rebuilds, 0);
+
+    table.retain(|key, _| !key.starts_with("old:") || key["old:".len()..].parse::<u32>().unwrap() < 200);
+    table.insert("new:1", "x");
+    assert_eq!(table.stats().bloom_rebuilds, 1);
+    assert!(table.contains_key("old:199"));
+    assert!(table.contains_key("new:0"));
+    assert!(table.contains_key("new:1"));
+    assert!(!table.contains_key("old:999"));
+
+    // Sem política, só a reconstrução manual
+    let mut table = DistributedHashTable::new();
+    for i in 0..1000 {
+        table.insert(&format!("old:{}", i), "x");
+    }
+    table.retain(|_, _| false);
+    table.insert("new:0", "x");
+    let before = table.stats();
+    assert_eq!(before.bloom_rebuilds, 0);
+    table.rebuild_bloom_filter();
+    let after = table.stats();
+    assert_eq!(after.bloom_rebuilds, 1);
+    assert!(after.bloom_fill_ratio < before.bloom_fill_ratio);
+    assert!(table.contains_key("new:0"));
+}
+
+#[test]
+fn test_reads_through_shared_reference() {
+    let mut table = DistributedHashTable::new();
+    for key in ["a", "b", "c"] {
+        table.insert(key, key);
+    }
+    table.insert_with_tti("session", "active", Duration::from_millis(150));
+    table.insert_with_ttl("gone", "x", Duration::from_millis(1));
+    std::thread::sleep(Duration::from_millis(100));
+
+    let shared = &table;
+    assert_eq!((shared.get("a"), shared.get("b")), (Some("a"), Some("b")));
+    assert_eq!(shared.get("session"), Some("active"));
+    assert_eq!(shared.get("missing"), None);
+    assert_eq!(shared.get("gone"), None);
+    assert!(!shared.contains_key("gone"));
+    let stats = shared.stats();
+    assert_eq!((stats.hits, stats.misses), (3, 2));
+
+    // As leituras passam a contar na próxima chamada com &mut self
+    std::thread::sleep(Duration::from_millis(100));
+    table.insert("d", "d");
+    let recency: Vec<_> = table.iter_by_recency().map(|(key, _)| key.as_str()).collect();
+    assert_eq!(recency, ["c", "a", "b", "session", "d"]);
+    assert_eq!(table.get("session"), Some("active"));
+
+    // contains_key não removeu a entrada vencida; ela fica até o purge
+    assert_eq!(table.size(), 6);
+    assert_eq!(table.purge_expired(), 1);
+    assert_eq!(table.stats().hits, 4);
+}
+
+#[test]
+fn test_shared_reads_keep_idle_entries_alive() {
+    let mut table = DistributedHashTable::new();
+    table.insert_with_tti("session", "active", Duration::from_millis(100));
+    table.insert("other", "x");
+
+    // Nenhuma chamada com &mut self: só o buffer de leituras renova a entrada
+    let shared = &table;
+    for _ in 0..4 {
+        std::thread::sleep(Duration::from_millis(50));
+        assert_eq!(shared.get("session"), Some("active"));
+        assert!(shared.contains_key("session"));
+    }
+    assert_eq!(shared.hottest(1), ["session"]);
+    assert_eq!(shared.iter_by_frequency().last().map(|(key, _)| key.as_str()), Some("session"));
+
+    std::thread::sleep(Duration::from_millis(150));
+    assert_eq!(shared.get("session"), None);
+    assert_eq!(table.purge_expired(), 1);
+}
+
+#[test]
+fn test_concurrent_readers_behind_rwlock() {
+    let mut table = DistributedHashTable::new();
+    table.insert("a", "1");
+    table.insert("b", "2");
+    table.on_evict(|_, _, _| {});
+    let table = std::sync::RwLock::new(table);
+
+    std::thread::scope(|scope| {
+        for _ in 0..4 {
+            scope.spawn(|| {
+                for _ in 0..100 {
+                    let shared = table.read().unwrap();
+                    assert_eq!(shared.get("a"), Some("1"));
+                    assert_eq!(shared.get("missing"), None);
+                    assert!(shared.contains_key("b"));
+                }
+            });
+        }
+    });
+
+    let mut table = table.into_inner().unwrap();
+    table.insert("c", "3");
+    let stats = table.stats();
+    assert_eq!((stats.hits, stats.misses), (400, 400));
+    let recency: Vec<_> = table.iter_by_recency().map(|(key, _)| key.as_str()).collect();
+    assert_eq!(recency, ["b", "a", "c"]);
+}
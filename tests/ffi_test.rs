@@ -0,0 +1,79 @@
+#![cfg(feature = "ffi")]
+
+use spectra_cache::ffi::*;
+use std::ffi::{c_char, CStr, CString};
+use std::ptr;
+use std::thread;
+use std::time::Duration;
+
+fn c(text: &str) -> CString {
+    CString::new(text).unwrap()
+}
+
+unsafe fn get(cache: *mut SpectraCache, key: &str) -> Result<String, SpectraStatus> {
+    let mut out: *mut c_char = ptr::null_mut();
+    match spectra_get(cache, c(key).as_ptr(), &mut out) {
+        SpectraStatus::Ok => {
+            let value = CStr::from_ptr(out).to_str().unwrap().to_owned();
+            spectra_string_free(out);
+            Ok(value)
+        }
+        status => Err(status),
+    }
+}
+
+#[test]
+fn test_insert_get_remove() {
+    unsafe {
+        let cache = spectra_new();
+        assert_eq!(spectra_insert(cache, c("user:1").as_ptr(), c("Ana").as_ptr()), SpectraStatus::Ok);
+        assert_eq!(spectra_insert(cache, c("user:2").as_ptr(), c("Bia").as_ptr()), SpectraStatus::Ok);
+        assert_eq!(spectra_len(cache), 2);
+        assert_eq!(get(cache, "user:1"), Ok("Ana".to_string()));
+        assert_eq!(spectra_contains(cache, c("user:2").as_ptr()), SpectraStatus::Ok);
+
+        assert_eq!(spectra_remove(cache, c("user:1").as_ptr()), SpectraStatus::Ok);
+        assert_eq!(spectra_remove(cache, c("user:1").as_ptr()), SpectraStatus::NotFound);
+        assert_eq!(get(cache, "user:1"), Err(SpectraStatus::NotFound));
+        assert_eq!(spectra_contains(cache, c("user:1").as_ptr()), SpectraStatus::NotFound);
+
+        spectra_clear(cache);
+        assert_eq!(spectra_len(cache), 0);
+        spectra_free(cache);
+    }
+}
+
+#[test]
+fn test_insert_with_ttl() {
+    unsafe {
+        let cache = spectra_new();
+        spectra_insert_with_ttl(cache, c("session").as_ptr(), c("abc").as_ptr(), 50);
+        assert_eq!(get(cache, "session"), Ok("abc".to_string()));
+        thread::sleep(Duration::from_millis(100));
+        assert_eq!(get(cache, "session"), Err(SpectraStatus::NotFound));
+        spectra_free(cache);
+    }
+}
+
+#[test]
+fn test_bad_arguments() {
+    unsafe {
+        let cache = spectra_new_with_memory_limit(1 << 20);
+        let mut out: *mut c_char = ptr::null_mut();
+        assert_eq!(spectra_insert(ptr::null_mut(), c("k").as_ptr(), c("v").as_ptr()), SpectraStatus::NullArgument);
+        assert_eq!(spectra_insert(cache, c("k").as_ptr(), ptr::null()), SpectraStatus::NullArgument);
+        assert_eq!(spectra_get(cache, c("k").as_ptr(), ptr::null_mut()), SpectraStatus::NullArgument);
+        assert_eq!(spectra_get(cache, ptr::null(), &mut out), SpectraStatus::NullArgument);
+        assert!(out.is_null());
+
+        let invalid = [0xffu8 as c_char, 0];
+        assert_eq!(spectra_insert(cache, invalid.as_ptr(), c("v").as_ptr()), SpectraStatus::InvalidUtf8);
+        assert_eq!(spectra_len(cache), 0);
+
+        assert_eq!(spectra_len(ptr::null()), 0);
+        spectra_clear(ptr::null_mut());
+        spectra_string_free(ptr::null_mut());
+        spectra_free(ptr::null_mut());
+        spectra_free(cache);
+    }
+}
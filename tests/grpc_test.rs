@@ -0,0 +1,121 @@
+#![cfg(feature = "grpc")]
+
+use spectra_cache::grpc::proto::spectra_cache_client::SpectraCacheClient;
+use spectra_cache::grpc::proto::{DeleteRequest, SetRequest};
+use spectra_cache::grpc::WRITE_ID_HEADER;
+use spectra_cache::{CacheEvent, DistributedHashTable, GrpcServer, SpectraClient};
+use std::time::Duration;
+use tokio::net::TcpListener;
+use tokio_stream::StreamExt;
+
+/// Starts a server on a free port and returns a handle on it and a client.
+async fn start_server() -> (GrpcServer, SpectraClient) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = GrpcServer::new(DistributedHashTable::new());
+    tokio::spawn(server.clone().serve(listener));
+    let client = SpectraClient::connect(format!("http://{}", addr)).await.unwrap();
+    (server, client)
+}
+
+#[tokio::test]
+async fn test_get_set_delete() {
+    let (server, mut client) = start_server().await;
+
+    assert_eq!(client.get("user:1").await.unwrap(), None);
+    client.set("user:1", "ana").await.unwrap();
+    assert_eq!(client.get("user:1").await.unwrap().as_deref(), Some("ana"));
+    // O processo que hospeda o servidor vê a mesma tabela
+    assert_eq!(server.table().get("user:1"), Some("ana"));
+
+    client.set_with_ttl("session:1", "x", Duration::from_millis(20)).await.unwrap();
+    assert!(client.get("session:1").await.unwrap().is_some());
+    tokio::time::sleep(Duration::from_millis(40)).await;
+    assert_eq!(client.get("session:1").await.unwrap(), None);
+
+    let error = client.set_with_ttl("k", "v", Duration::ZERO).await.unwrap_err();
+    assert_eq!(error.code(), tonic::Code::InvalidArgument);
+
+    assert!(client.delete("user:1").await.unwrap());
+    assert!(!client.delete("user:1").await.unwrap());
+}
+
+#[tokio::test]
+async fn test_scan_pages_through_every_entry() {
+    let (server, mut client) = start_server().await;
+    for i in 0..25 {
+        server.table().insert(&format!("key:{}", i), "v");
+    }
+
+    let mut seen = Vec::new();
+    let mut cursor = None;
+    loop {
+        let (page, next) = client.scan(cursor.as_deref(), 10).await.unwrap();
+        assert!(page.len() <= 10);
+        seen.extend(page.into_iter().map(|(key, _)| key));
+        cursor = next;
+        if cursor.is_none() {
+            break;
+        }
+    }
+    seen.sort();
+    seen.dedup();
+    assert_eq!(seen.len(), 25);
+    assert_eq!(client.scan(None, 0).await.unwrap_err().code(), tonic::Code::InvalidArgument);
+}
+
+#[tokio::test]
+async fn test_watch_streams_matching_changes() {
+    let (server, mut client) = start_server().await;
+    let mut events = Box::pin(client.watch("config:*").await.unwrap());
+
+    client.set("config:theme", "dark").await.unwrap();
+    client.set("other", "ignored").await.unwrap();
+    server.table().insert("config:theme", "light");
+    client.delete("config:theme").await.unwrap();
+
+    let mut received = Vec::new();
+    while received.len() < 3 {
+        let event = tokio::time::timeout(Duration::from_secs(5), events.next()).await.unwrap();
+        received.push(event.unwrap().unwrap());
+    }
+    assert_eq!(
+        received,
+        [
+            CacheEvent::Insert { key: "config:theme".to_string(), value: "dark".to_string() },
+            CacheEvent::Update { key: "config:theme".to_string(), value: "light".to_string() },
+            CacheEvent::Remove { key: "config:theme".to_string() },
+        ]
+    );
+}
+
+/// Wraps `message` in a request carrying `write_id`.
+fn with_write_id<T>(message: T, write_id: &str) -> tonic::Request<T> {
+    let mut request = tonic::Request::new(message);
+    request.metadata_mut().insert(WRITE_ID_HEADER, write_id.parse().unwrap());
+    request
+}
+
+#[tokio::test]
+async fn test_write_ids_replay_the_original_response() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = GrpcServer::new(DistributedHashTable::new());
+    tokio::spawn(server.clone().serve(listener));
+    let mut client = SpectraCacheClient::connect(format!("http://{}", addr)).await.unwrap();
+    server.table().insert("user:1", "ana");
+    let delete = || DeleteRequest { key: "user:1".to_string() };
+    assert!(client.delete(with_write_id(delete(), "w1")).await.unwrap().into_inner().deleted);
+    server.table().insert("user:1", "bia");
+    // A repetição devolve a resposta original sem apagar de novo
+    assert!(client.delete(with_write_id(delete(), "w1")).await.unwrap().into_inner().deleted);
+    assert_eq!(server.table().get("user:1"), Some("bia"));
+    // Sem ID, cada chamada é uma escrita nova
+    assert!(client.delete(delete()).await.unwrap().into_inner().deleted);
+    assert!(!client.delete(delete()).await.unwrap().into_inner().deleted);
+
+    let set = |value: &str| SetRequest { key: "k".to_string(), value: value.to_string() };
+    client.set(with_write_id(set("a"), "w2")).await.unwrap();
+    client.set(with_write_id(set("b"), "w2")).await.unwrap();
+    assert_eq!(server.table().get("k"), Some("a"));
+}
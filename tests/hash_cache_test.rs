@@ -0,0 +1,74 @@
+#![cfg(all(feature = "core", feature = "std"))]
+
+use spectra_cache::HashCache;
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+#[test]
+fn test_fields_are_updated_independently() {
+    let mut hashes = HashCache::new();
+    assert!(hashes.hset("user:1", "name", "Ana"));
+    assert!(hashes.hset("user:1", "city", "Recife"));
+    assert!(!hashes.hset("user:1", "city", "Olinda"));
+    hashes.hset("user:2", "name", "Bia");
+
+    assert_eq!(hashes.hget("user:1", "city"), Some("Olinda"));
+    assert_eq!(hashes.hget("user:1", "email"), None);
+    assert_eq!(hashes.hget("missing", "name"), None);
+    assert_eq!(hashes.hgetall("user:1"), [("city", "Olinda"), ("name", "Ana")]);
+    assert!(hashes.hgetall("missing").is_empty());
+    assert!(hashes.hexists("user:2", "name"));
+    assert_eq!(hashes.hlen("user:1"), 2);
+
+    let mut keys: Vec<_> = hashes.keys().collect();
+    keys.sort();
+    assert_eq!(keys, ["user:1", "user:2"]);
+}
+
+#[test]
+fn test_hdel_and_remove() {
+    let mut hashes = HashCache::new();
+    hashes.hset("user:1", "name", "Ana");
+    hashes.hset("user:1", "city", "Recife");
+
+    assert!(hashes.hdel("user:1", "city"));
+    assert!(!hashes.hdel("user:1", "city"));
+    assert!(!hashes.hdel("missing", "city"));
+    // O hash some junto com o último campo
+    assert!(hashes.hdel("user:1", "name"));
+    assert!(!hashes.contains_key("user:1"));
+    assert!(hashes.is_empty());
+
+    hashes.hset("user:2", "name", "Bia");
+    let fields = BTreeMap::from([("name".to_string(), "Bia".to_string())]);
+    assert_eq!(hashes.remove("user:2"), Some(fields));
+    assert_eq!(hashes.remove("user:2"), None);
+
+    hashes.hset("user:3", "name", "Caio");
+    hashes.clear();
+    assert!(hashes.is_empty());
+}
+
+#[test]
+fn test_hash_ttl() {
+    let mut hashes = HashCache::new();
+    assert!(!hashes.expire("session:1", Duration::from_secs(1)));
+    hashes.hset("session:1", "user", "42");
+    hashes.hset("session:2", "user", "7");
+    assert!(hashes.expire("session:1", Duration::from_millis(20)));
+    assert!(hashes.ttl("session:1").unwrap() <= Duration::from_millis(20));
+    assert_eq!(hashes.ttl("session:2"), None);
+
+    std::thread::sleep(Duration::from_millis(30));
+    assert_eq!(hashes.hget("session:1", "user"), None);
+    assert_eq!(hashes.hlen("session:1"), 0);
+    assert_eq!(hashes.keys().collect::<Vec<_>>(), ["session:2"]);
+
+    // Escrever num hash expirado começa um novo, sem os campos antigos
+    assert!(hashes.hset("session:1", "theme", "dark"));
+    assert_eq!(hashes.hgetall("session:1"), [("theme", "dark")]);
+    assert_eq!(hashes.ttl("session:1"), None);
+
+    hashes.expire("session:2", Duration::ZERO);
+    assert_eq!(hashes.purge_expired(), 1);
+}
@@ -0,0 +1,117 @@
+#![cfg(feature = "http")]
+
+use spectra_cache::{DistributedHashTable, HttpServer};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::thread;
+use std::time::Duration;
+
+fn start_server(server: HttpServer) -> (TcpStream, BufReader<TcpStream>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    thread::spawn(move || server.serve(listener));
+    let stream = TcpStream::connect(addr).unwrap();
+    let reader = BufReader::new(stream.try_clone().unwrap());
+    (stream, reader)
+}
+
+/// Sends one request on a keep-alive connection and returns the status and
+/// the body of the response.
+fn request(stream: &mut TcpStream, reader: &mut impl BufRead, method: &str, target: &str, body: &str) -> (u16, String) {
+    write!(
+        stream,
+        "{} {} HTTP/1.1\r\nHost: localhost\r\nContent-Length: {}\r\n\r\n{}",
+        method,
+        target,
+        body.len(),
+        body
+    )
+    .unwrap();
+    let mut line = String::new();
+    reader.read_line(&mut line).unwrap();
+    let status = line.split_whitespace().nth(1).unwrap().parse().unwrap();
+    let mut content_length = 0;
+    loop {
+        line.clear();
+        reader.read_line(&mut line).unwrap();
+        if line == "\r\n" {
+            break;
+        }
+        if let Some(len) = line.strip_prefix("Content-Length: ") {
+            content_length = len.trim_end().parse().unwrap();
+        }
+    }
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body).unwrap();
+    (status, String::from_utf8(body).unwrap())
+}
+
+#[test]
+fn test_put_get_delete() {
+    let (mut stream, mut reader) = start_server(HttpServer::new(DistributedHashTable::new()));
+
+    assert_eq!(request(&mut stream, &mut reader, "PUT", "/keys/user%3A1", "Ana \"A\""), (204, String::new()));
+    assert_eq!(
+        request(&mut stream, &mut reader, "GET", "/keys/user:1", ""),
+        (200, r#"{"key":"user:1","value":"Ana \"A\"","ttl_ms":null}"#.to_string())
+    );
+
+    assert_eq!(request(&mut stream, &mut reader, "PUT", "/keys/session?ttl_ms=60000", "abc").0, 204);
+    let (status, body) = request(&mut stream, &mut reader, "GET", "/keys/session", "");
+    assert_eq!(status, 200);
+    assert!(body.starts_with(r#"{"key":"session","value":"abc","ttl_ms":"#));
+    assert!(!body.ends_with("null}"));
+
+    assert_eq!(request(&mut stream, &mut reader, "DELETE", "/keys/user:1", "").0, 204);
+    assert_eq!(
+        request(&mut stream, &mut reader, "GET", "/keys/user:1", ""),
+        (404, r#"{"error":"key not found"}"#.to_string())
+    );
+    assert_eq!(request(&mut stream, &mut reader, "DELETE", "/keys/user:1", "").0, 404);
+}
+
+#[test]
+fn test_list_keys_and_stats() {
+    let mut table = DistributedHashTable::new();
+    for key in ["user:2", "user:1", "order:1", "user:3"] {
+        table.insert(key, "x");
+    }
+    table.get("user:1");
+    let (mut stream, mut reader) = start_server(HttpServer::new(table));
+
+    assert_eq!(
+        request(&mut stream, &mut reader, "GET", "/keys?prefix=user%3A", ""),
+        (200, r#"{"keys":["user:1","user:2","user:3"],"truncated":false}"#.to_string())
+    );
+    assert_eq!(
+        request(&mut stream, &mut reader, "GET", "/keys?limit=2", ""),
+        (200, r#"{"keys":["order:1","user:1"],"truncated":true}"#.to_string())
+    );
+
+    let (status, body) = request(&mut stream, &mut reader, "GET", "/stats", "");
+    assert_eq!(status, 200);
+    assert!(body.contains(r#""hits":1"#), "{}", body);
+    // Inspecionar não conta como acesso
+    request(&mut stream, &mut reader, "GET", "/keys/user:2", "");
+    assert!(request(&mut stream, &mut reader, "GET", "/stats", "").1.contains(r#""hits":1"#));
+}
+
+#[test]
+fn test_errors() {
+    let server = HttpServer::new(DistributedHashTable::new());
+    let (mut stream, mut reader) = start_server(server.clone());
+
+    assert_eq!(request(&mut stream, &mut reader, "GET", "/nowhere", "").0, 404);
+    assert_eq!(request(&mut stream, &mut reader, "POST", "/keys/a", "").0, 405);
+    assert_eq!(request(&mut stream, &mut reader, "DELETE", "/stats", "").0, 405);
+    assert_eq!(request(&mut stream, &mut reader, "PUT", "/keys/a?ttl_ms=soon", "x").0, 400);
+    assert_eq!(request(&mut stream, &mut reader, "GET", "/keys?limit=-1", "").0, 400);
+    assert!(!server.table().contains_key("a"));
+
+    // Uma requisição malformada é respondida com 400 e a conexão é fechada
+    stream.write_all(b"NONSENSE\r\n\r\n").unwrap();
+    stream.set_read_timeout(Some(Duration::from_secs(5))).unwrap();
+    let mut response = String::new();
+    reader.read_to_string(&mut response).unwrap();
+    assert!(response.starts_with("HTTP/1.1 400 Bad Request\r\n"), "{}", response);
+}
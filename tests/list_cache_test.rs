@@ -0,0 +1,79 @@
+#![cfg(all(feature = "core", feature = "std"))]
+
+use spectra_cache::ListCache;
+use std::time::Duration;
+
+#[test]
+fn test_push_and_pop_at_both_ends() {
+    let mut lists = ListCache::new();
+    assert_eq!(lists.rpush("queue", "b"), 1);
+    assert_eq!(lists.rpush("queue", "c"), 2);
+    assert_eq!(lists.lpush("queue", "a"), 3);
+
+    assert_eq!(lists.lrange("queue", ..), ["a", "b", "c"]);
+    assert_eq!(lists.lindex("queue", 1), Some("b"));
+    assert_eq!(lists.lindex("queue", 3), None);
+    assert_eq!(lists.lpop("queue").as_deref(), Some("a"));
+    assert_eq!(lists.rpop("queue").as_deref(), Some("c"));
+    assert_eq!(lists.rpop("queue").as_deref(), Some("b"));
+
+    // A lista some junto com o último elemento
+    assert!(!lists.contains_key("queue"));
+    assert_eq!(lists.lpop("queue"), None);
+    assert!(lists.is_empty());
+}
+
+#[test]
+fn test_lrange_bounds() {
+    let mut lists = ListCache::new();
+    for value in ["a", "b", "c", "d"] {
+        lists.rpush("list", value);
+    }
+
+    assert_eq!(lists.lrange("list", 1..3), ["b", "c"]);
+    assert_eq!(lists.lrange("list", 2..=10), ["c", "d"]);
+    assert_eq!(lists.lrange("list", ..1), ["a"]);
+    assert!(lists.lrange("list", 4..).is_empty());
+    assert!(lists.lrange("list", 3..3).is_empty());
+    assert!(lists.lrange("missing", ..).is_empty());
+}
+
+#[test]
+fn test_max_len_trims_the_oldest_elements() {
+    let mut lists = ListCache::new().max_len(2);
+    lists.lpush("recent", "1");
+    lists.lpush("recent", "2");
+    assert_eq!(lists.lpush("recent", "3"), 2);
+    assert_eq!(lists.lrange("recent", ..), ["3", "2"]);
+
+    lists.rpush("log", "1");
+    lists.rpush("log", "2");
+    assert_eq!(lists.rpush("log", "3"), 2);
+    assert_eq!(lists.lrange("log", ..), ["2", "3"]);
+}
+
+#[test]
+fn test_list_ttl() {
+    let mut lists = ListCache::new();
+    assert!(!lists.expire("feed", Duration::from_secs(1)));
+    lists.rpush("feed", "a");
+    lists.rpush("kept", "b");
+    assert_eq!(lists.ttl("feed"), None);
+    assert!(lists.expire("feed", Duration::from_millis(20)));
+    assert!(lists.ttl("feed").unwrap() <= Duration::from_millis(20));
+
+    std::thread::sleep(Duration::from_millis(30));
+    assert!(!lists.contains_key("feed"));
+    assert_eq!(lists.llen("feed"), 0);
+    assert_eq!(lists.keys().collect::<Vec<_>>(), ["kept"]);
+    assert_eq!(lists.purge_expired(), 1);
+
+    // Escrever numa lista expirada começa uma nova, sem TTL
+    lists.rpush("kept", "c");
+    lists.expire("kept", Duration::from_millis(10));
+    std::thread::sleep(Duration::from_millis(20));
+    assert_eq!(lists.rpush("kept", "d"), 1);
+    assert_eq!(lists.ttl("kept"), None);
+    assert_eq!(lists.remove("kept"), Some(vec!["d".to_string()]));
+    assert_eq!(lists.remove("kept"), None);
+}
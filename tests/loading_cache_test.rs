@@ -0,0 +1,143 @@
+#![cfg(all(feature = "core", feature = "std"))]
+
+use spectra_cache::{DistributedHashTable, GetOptions, LoadingCache};
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Barrier};
+use std::thread;
+use std::time::{Duration, Instant};
+
+#[test]
+fn test_loads_misses_and_caches_them() {
+    let cache = LoadingCache::new(DistributedHashTable::new(), |key: &str| {
+        key.strip_prefix("user:").map(|id| format!("name-{}", id))
+    });
+
+    assert_eq!(cache.get("user:1").as_deref(), Some("name-1"));
+    assert_eq!(cache.get("user:1").as_deref(), Some("name-1"));
+    assert_eq!(cache.loads(), 1);
+
+    // Chaves inexistentes não são guardadas
+    assert_eq!(cache.get("other"), None);
+    assert_eq!(cache.get("other"), None);
+    assert_eq!(cache.loads(), 3);
+    assert_eq!(cache.table().size(), 1);
+
+    assert_eq!(cache.invalidate("user:1").as_deref(), Some("name-1"));
+    assert_eq!(cache.get("user:1").as_deref(), Some("name-1"));
+    assert_eq!(cache.loads(), 4);
+}
+
+#[test]
+fn test_concurrent_misses_coalesce_into_one_load() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let counter = Arc::clone(&calls);
+    let cache = Arc::new(LoadingCache::new(DistributedHashTable::new(), move |key: &str| {
+        counter.fetch_add(1, Ordering::SeqCst);
+        thread::sleep(Duration::from_millis(100));
+        Some(key.to_uppercase())
+    }));
+
+    let barrier = Arc::new(Barrier::new(8));
+    let readers: Vec<_> = (0..8)
+        .map(|_| {
+            let (cache, barrier) = (Arc::clone(&cache), Arc::clone(&barrier));
+            thread::spawn(move || {
+                barrier.wait();
+                cache.get("hot")
+            })
+        })
+        .collect();
+    for reader in readers {
+        assert_eq!(reader.join().unwrap().as_deref(), Some("HOT"));
+    }
+
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+    assert_eq!(cache.loads(), 1);
+    assert_eq!(cache.coalesced(), 7);
+}
+
+#[test]
+fn test_waiters_survive_a_panicking_loader() {
+    let barrier = Arc::new(Barrier::new(2));
+    let loader_barrier = Arc::clone(&barrier);
+    let cache = Arc::new(LoadingCache::new(DistributedHashTable::new(), move |key: &str| {
+        if key == "broken" {
+            loader_barrier.wait();
+            thread::sleep(Duration::from_millis(50));
+            panic!("backend down");
+        }
+        Some(key.to_string())
+    }));
+
+    let leader = {
+        let cache = Arc::clone(&cache);
+        thread::spawn(move || panic::catch_unwind(AssertUnwindSafe(|| cache.get("broken"))).is_err())
+    };
+    // Espera o líder entrar no loader antes de pedir a mesma chave
+    barrier.wait();
+    assert_eq!(cache.get("broken"), None);
+    assert!(leader.join().unwrap());
+
+    assert_eq!(cache.get("fine").as_deref(), Some("fine"));
+}
+
+#[test]
+fn test_budget_gives_up_on_slow_loads() {
+    let barrier = Arc::new(Barrier::new(2));
+    let loader_barrier = Arc::clone(&barrier);
+    let cache = Arc::new(LoadingCache::new(DistributedHashTable::new(), move |key: &str| {
+        loader_barrier.wait();
+        thread::sleep(Duration::from_millis(200));
+        Some(key.to_string())
+    }));
+
+    let leader = {
+        let cache = Arc::clone(&cache);
+        thread::spawn(move || cache.get("slow"))
+    };
+    barrier.wait();
+    let budget = GetOptions { max_wait: Some(Duration::from_millis(10)), ..GetOptions::default() };
+    let started = Instant::now();
+    assert_eq!(cache.get_with("slow", &budget), None);
+    assert!(started.elapsed() < Duration::from_millis(150));
+    assert_eq!(leader.join().unwrap().as_deref(), Some("slow"));
+    assert_eq!(cache.get_with("slow", &budget).as_deref(), Some("slow"));
+
+    // Com orçamento, um miss sem carregamento em andamento não chama o loader
+    assert_eq!(cache.get_with("other", &GetOptions { max_wait: Some(Duration::ZERO), ..GetOptions::default() }), None);
+    assert_eq!(cache.loads(), 1);
+}
+
+#[test]
+fn test_stale_values_refresh_in_the_background() {
+    let version = Arc::new(AtomicUsize::new(1));
+    let current = Arc::clone(&version);
+    let table = DistributedHashTable::builder().soft_ttl(Duration::from_millis(30)).build();
+    let cache = Arc::new(LoadingCache::new(table, move |_: &str| {
+        thread::sleep(Duration::from_millis(50));
+        Some(format!("v{}", current.load(Ordering::SeqCst)))
+    }));
+
+    let read = cache.get_stale("config").unwrap();
+    assert_eq!((read.value.as_str(), read.stale), ("v1", false));
+
+    version.store(2, Ordering::SeqCst);
+    thread::sleep(Duration::from_millis(40));
+    // O valor antigo sai na hora enquanto um único recarregamento roda
+    let started = Instant::now();
+    for _ in 0..5 {
+        let read = cache.get_stale("config").unwrap();
+        assert_eq!((read.value.as_str(), read.stale), ("v1", true));
+    }
+    assert!(started.elapsed() < Duration::from_millis(40));
+
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while cache.table().peek("config") != Some("v2") {
+        assert!(Instant::now() < deadline, "the background refresh never landed");
+        thread::sleep(Duration::from_millis(5));
+    }
+    let read = cache.get_stale("config").unwrap();
+    assert_eq!((read.value.as_str(), read.stale), ("v2", false));
+    assert_eq!(cache.loads(), 2);
+}
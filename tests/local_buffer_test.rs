@@ -0,0 +1,65 @@
+#![cfg(all(feature = "core", feature = "std"))]
+
+use spectra_cache::{DistributedHashTable, LocalBuffer};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+fn shared_table() -> Arc<Mutex<DistributedHashTable>> {
+    Arc::new(Mutex::new(DistributedHashTable::new()))
+}
+
+#[test]
+fn test_writes_wait_for_the_size_threshold() {
+    let shared = shared_table();
+    let mut buffer = LocalBuffer::new(Arc::clone(&shared)).max_pending(3).max_delay(Duration::from_secs(60));
+
+    buffer.insert("a", "1");
+    buffer.insert("a", "2");
+    buffer.insert("b", "1");
+    assert_eq!(buffer.pending(), 2);
+    assert!(shared.lock().unwrap().is_empty());
+    // Leituras veem as escritas pendentes do próprio buffer
+    assert_eq!(buffer.get("a").as_deref(), Some("2"));
+
+    buffer.remove("c");
+    assert_eq!(buffer.pending(), 0);
+    let table = shared.lock().unwrap();
+    assert_eq!(table.get("a"), Some("2"));
+    assert_eq!(table.size(), 2);
+}
+
+#[test]
+fn test_flushes_after_max_delay_and_on_drop() {
+    let shared = shared_table();
+    shared.lock().unwrap().insert("old", "x");
+    let mut buffer = LocalBuffer::new(Arc::clone(&shared)).max_delay(Duration::from_millis(20));
+
+    buffer.remove("old");
+    assert_eq!(buffer.get("old"), None);
+    assert_eq!(buffer.flush_if_due(), 0);
+    thread::sleep(Duration::from_millis(30));
+    assert_eq!(buffer.flush_if_due(), 1);
+    assert!(shared.lock().unwrap().is_empty());
+
+    buffer.insert("new", "y");
+    drop(buffer);
+    assert_eq!(shared.lock().unwrap().get("new"), Some("y"));
+}
+
+#[test]
+fn test_buffered_ttls_keep_counting() {
+    let shared = shared_table();
+    shared.lock().unwrap().insert("short", "stale");
+    let mut buffer = LocalBuffer::new(Arc::clone(&shared)).max_delay(Duration::from_secs(60));
+
+    buffer.insert_with_ttl("short", "v", Duration::from_millis(10));
+    buffer.insert_with_ttl("long", "v", Duration::from_secs(60));
+    thread::sleep(Duration::from_millis(20));
+    assert_eq!(buffer.get("short"), None);
+    assert_eq!(buffer.flush(), 2);
+
+    let table = shared.lock().unwrap();
+    assert_eq!(table.peek("short"), None);
+    assert!(table.ttl("long").unwrap() < Duration::from_secs(60));
+}
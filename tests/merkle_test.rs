@@ -0,0 +1,69 @@
+#![cfg(feature = "cluster")]
+
+use spectra_cache::{BTreeCache, BucketPatch, BytesCache, CacheError, DistributedHashTable, MerkleDigest};
+use std::time::Duration;
+
+#[test]
+fn test_sync_over_the_wire() {
+    let mut source = DistributedHashTable::new();
+    let mut target = BTreeCache::new();
+    for i in 0..500 {
+        source.insert(&format!("k{}", i), "v");
+        target.insert(&format!("k{}", i), "v");
+    }
+    assert_eq!(source.merkle_digest(64), MerkleDigest::from_bytes(&target.merkle_digest(64).to_bytes()).unwrap());
+
+    source.insert_with_ttl("k1", "new", Duration::from_secs(60));
+    source.insert("only-source", "s");
+    target.insert("only-target", "t");
+    target.remove("k2");
+
+    // O alvo manda o digest; a origem responde só com os buckets diferentes
+    let remote = MerkleDigest::from_bytes(&target.merkle_digest(64).to_bytes()).unwrap();
+    let divergent = source.merkle_digest(64).diff(&remote);
+    assert!(divergent.len() <= 4 && !divergent.is_empty());
+    assert!(divergent.contains_key("k1") && divergent.contains_key("only-target"));
+    let patch = source.export_buckets(&divergent);
+    assert!(patch.len() < 50);
+    let patch = BucketPatch::from_bytes(&patch.to_bytes()).unwrap();
+    assert!(target.apply_bucket_patch(&patch).unwrap() >= 4);
+
+    assert_eq!(target.get("k1"), Some("new"));
+    assert!(target.ttl("k1").unwrap() <= Duration::from_secs(60));
+    assert_eq!(target.get("k2"), Some("v"));
+    assert_eq!(target.get("only-source"), Some("s"));
+    assert_eq!(target.get("only-target"), None);
+    assert!(source.merkle_digest(64).diff(&target.merkle_digest(64)).is_empty());
+}
+
+#[test]
+fn test_expired_entries_are_not_digested() {
+    let mut a = DistributedHashTable::new();
+    let b = DistributedHashTable::new();
+    a.insert_with_ttl("gone", "x", Duration::from_millis(1));
+    std::thread::sleep(Duration::from_millis(5));
+    assert_eq!(a.merkle_digest(8).root(), b.merkle_digest(8).root());
+}
+
+#[test]
+fn test_patch_errors() {
+    let mut bytes = BytesCache::new();
+    bytes.insert("blob", &[0xff, 0x00]);
+    let divergent = bytes.merkle_digest(4).diff(&DistributedHashTable::new().merkle_digest(4));
+    let patch = bytes.export_buckets(&divergent);
+
+    // Bytes que não são UTF-8 não cabem num cache de texto, e nada muda
+    let mut text = DistributedHashTable::new();
+    text.insert("blob", "old");
+    assert_eq!(text.apply_bucket_patch(&patch), Err(CacheError::InvalidDump { reason: "value is not valid UTF-8" }));
+    assert_eq!(text.get("blob"), Some("old"));
+
+    assert!(BucketPatch::from_bytes(b"\0\0\0\x03\0\0\0\0").is_err());
+    assert!(MerkleDigest::from_bytes(b"\0\0").is_err());
+}
+
+#[test]
+#[should_panic(expected = "power of two")]
+fn test_bucket_count_must_be_a_power_of_two() {
+    DistributedHashTable::new().merkle_digest(100);
+}
@@ -0,0 +1,53 @@
+#![cfg(all(feature = "core", not(feature = "std")))]
+
+use spectra_cache::clock::ManualClock;
+use spectra_cache::{BTreeCache, DistributedHashTable};
+use std::time::Duration;
+
+#[test]
+fn test_default_clock_stands_still() {
+    let mut table = DistributedHashTable::new();
+    table.insert_with_ttl("session", "on", Duration::from_millis(1));
+
+    // Sem relógio do sistema o padrão nunca anda, então nada vence
+    std::thread::sleep(Duration::from_millis(5));
+    assert_eq!(table.get("session"), Some("on"));
+    assert_eq!(table.ttl("session"), Some(Duration::from_millis(1)));
+}
+
+#[test]
+fn test_hash_table_expires_with_manual_clock() {
+    let clock = ManualClock::new();
+    let mut table = DistributedHashTable::builder().clock(clock.clone()).build();
+    table.insert_with_ttl("session", "on", Duration::from_secs(60));
+    table.insert("forever", "y");
+
+    clock.advance(Duration::from_secs(59));
+    assert_eq!(table.get("session"), Some("on"));
+    clock.advance(Duration::from_secs(2));
+    assert_eq!(table.get("session"), None);
+    assert_eq!(table.get("forever"), Some("y"));
+
+    assert_eq!(table.remove("forever"), Some("y".to_string()));
+    table.purge_expired();
+    assert!(table.is_empty());
+}
+
+#[test]
+fn test_btree_cache_range_with_manual_clock() {
+    let clock = ManualClock::new();
+    let mut cache = BTreeCache::builder().clock(clock.clone()).build();
+    cache.insert("a", "1");
+    cache.insert_with_ttl("b", "2", Duration::from_secs(10));
+    cache.insert("c", "3");
+
+    let keys: Vec<_> = cache.range("a".."c").map(|(k, _)| k.as_str()).collect();
+    assert_eq!(keys, ["a", "b"]);
+
+    clock.advance(Duration::from_secs(11));
+    assert_eq!(cache.get("b"), None);
+    assert_eq!(cache.get("c"), Some("3"));
+    assert_eq!(cache.remove("a"), Some("1".to_string()));
+    cache.purge_expired();
+    assert_eq!(cache.size(), 1);
+}
@@ -0,0 +1,177 @@
+#![cfg(feature = "cluster")]
+
+use spectra_cache::{
+    BTreeCache, BytesCache, CacheError, ChannelTransport, DistributedHashTable, Durability, PersistenceFailure, TcpTransport,
+    Transport,
+};
+use std::io::ErrorKind;
+use std::net::TcpListener;
+use std::thread;
+use std::time::Duration;
+
+/// Applies what the primary sent until it goes quiet.
+fn catch_up(replica: &mut DistributedHashTable, transport: &mut impl Transport) -> usize {
+    let mut applied = 0;
+    loop {
+        match replica.apply_replicated(transport, Duration::from_millis(100)).unwrap() {
+            0 => return applied,
+            n => applied += n,
+        }
+    }
+}
+
+#[test]
+fn test_replica_follows_the_primary() {
+    let (primary_end, mut replica_end) = ChannelTransport::pair();
+    let mut primary = DistributedHashTable::new();
+    primary.insert("before", "1");
+    let mut replica = DistributedHashTable::new();
+    replica.insert("stale", "0");
+
+    primary.add_replica(primary_end);
+    assert_eq!(primary.replica_count(), 1);
+    // O estado inicial apaga o que a réplica tinha antes
+    assert_eq!(catch_up(&mut replica, &mut replica_end), 2);
+    assert_eq!(replica.keys().collect::<Vec<_>>(), ["before"]);
+
+    primary.insert("user:1", "Ana");
+    primary.insert_with_ttl("session:1", "active", Duration::from_secs(60));
+    primary.insert("token", "x");
+    primary.expire("token", Duration::from_secs(30));
+    primary.incr("visits", 2).unwrap();
+    primary.remove("before");
+    assert_eq!(catch_up(&mut replica, &mut replica_end), 6);
+    assert_eq!(replica.size(), 4);
+    assert_eq!(replica.get("user:1"), Some("Ana"));
+    assert_eq!(replica.get("visits"), Some("2"));
+    assert_eq!(replica.get("before"), None);
+    assert!(replica.ttl("session:1").unwrap() <= Duration::from_secs(60));
+    assert!(replica.ttl("token").unwrap() <= Duration::from_secs(30));
+
+    primary.clear();
+    primary.insert("after", "2");
+    catch_up(&mut replica, &mut replica_end);
+    assert_eq!(replica.keys().collect::<Vec<_>>(), ["after"]);
+
+    drop(primary);
+    let error = replica.apply_replicated(&mut replica_end, Duration::from_millis(100)).unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::UnexpectedEof);
+}
+
+#[test]
+fn test_degraded_replica_leaves_writes_unacknowledged() {
+    let (primary_end, mut replica_end) = ChannelTransport::pair();
+    let mut primary = DistributedHashTable::new();
+    primary.add_replica(primary_end);
+    let mut replica = DistributedHashTable::builder()
+        .on_persistence_failure(PersistenceFailure::RejectWrites)
+        .build();
+    let unwritable = std::env::temp_dir().join("spectra-cache-test-missing-dir").join("replica.snap");
+    for _ in 0..3 {
+        replica.save_snapshot(&unwritable).unwrap_err();
+    }
+
+    let result = primary.insert_with_durability("k", "1", Durability::Replicated(1), Duration::from_millis(100));
+    assert_eq!(result, Err(CacheError::NotDurable { reason: "timed out waiting for replicas" }));
+    let error = replica.apply_replicated(&mut replica_end, Duration::from_millis(100)).unwrap_err();
+    assert_eq!(error.to_string(), CacheError::PersistenceFailed.to_string());
+    assert_eq!(replica.get("k"), None);
+
+    // As escritas esperam no transporte até a réplica voltar
+    replica.resume_persistence();
+    catch_up(&mut replica, &mut replica_end);
+    assert_eq!(replica.get("k"), Some("1"));
+}
+
+#[test]
+fn test_replicated_durability_waits_for_replicas() {
+    let timeout = Duration::from_millis(100);
+    let (primary_end, mut replica_end) = ChannelTransport::pair();
+    let mut primary = DistributedHashTable::new();
+    primary.add_replica(primary_end);
+
+    // Sem ninguém aplicando, a escrita não é confirmada
+    let result = primary.insert_with_durability("k", "1", Durability::Replicated(1), timeout);
+    assert_eq!(result, Err(CacheError::NotDurable { reason: "timed out waiting for replicas" }));
+    assert_eq!(primary.get("k"), Some("1"));
+
+    let follower = thread::spawn(move || {
+        let mut replica = DistributedHashTable::new();
+        while replica.get("k") != Some("2") {
+            replica.apply_replicated(&mut replica_end, Duration::from_millis(100)).unwrap();
+        }
+        replica_end
+    });
+    let result = primary.insert_with_durability("k", "2", Durability::Replicated(1), Duration::from_secs(5));
+    assert_eq!(result, Ok(()));
+    let result = primary.insert_with_durability("k", "3", Durability::Replicated(2), timeout);
+    assert_eq!(result, Err(CacheError::NotDurable { reason: "the cache has fewer replicas than required" }));
+
+    drop(follower.join().unwrap());
+    primary.insert("k", "4");
+    let deadline = std::time::Instant::now() + Duration::from_secs(5);
+    while primary.replica_count() > 0 && std::time::Instant::now() < deadline {
+        thread::sleep(Duration::from_millis(10));
+    }
+    assert_eq!(primary.replica_count(), 0);
+}
+
+#[test]
+fn test_replication_between_cache_types() {
+    let (primary_end, mut replica_end) = ChannelTransport::pair();
+    let mut primary = BTreeCache::new();
+    primary.insert("b", "2");
+    primary.add_replica(primary_end);
+    primary.insert("a", "1");
+
+    let mut replica = BytesCache::new();
+    let mut applied = 0;
+    while applied < 3 {
+        applied += replica.apply_replicated(&mut replica_end, Duration::from_secs(1)).unwrap();
+    }
+    assert_eq!(replica.get("a"), Some(&b"1"[..]));
+    assert_eq!(replica.get("b"), Some(&b"2"[..]));
+}
+
+#[test]
+fn test_replication_over_tcp() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let follower = thread::spawn(move || {
+        let mut transport = TcpTransport::from_stream(listener.accept().unwrap().0);
+        let mut replica = DistributedHashTable::new();
+        while replica.get("done").is_none() {
+            replica.apply_replicated(&mut transport, Duration::from_millis(100)).unwrap();
+        }
+        replica
+    });
+
+    let mut primary = DistributedHashTable::new();
+    primary.add_replica(TcpTransport::connect(addr).unwrap());
+    for i in 0..100 {
+        primary.insert(&format!("key:{}", i), &i.to_string());
+    }
+    let result = primary.insert_with_durability("done", "yes", Durability::Replicated(1), Duration::from_secs(5));
+    assert_eq!(result, Ok(()));
+
+    let replica = follower.join().unwrap();
+    assert_eq!(replica.size(), 101);
+    assert_eq!(replica.get("key:99"), Some("99"));
+}
+
+#[test]
+fn test_transaction_replicates_as_one_frame() {
+    use spectra_cache::Transaction;
+
+    let (primary_end, mut replica_end) = ChannelTransport::pair();
+    let mut primary = DistributedHashTable::new();
+    let mut replica = DistributedHashTable::new();
+    primary.add_replica(primary_end);
+    catch_up(&mut replica, &mut replica_end);
+
+    let mut tx = Transaction::new();
+    tx.insert("a", "1").insert("b", "2");
+    primary.apply(tx).unwrap();
+    assert_eq!(replica.apply_replicated(&mut replica_end, Duration::from_millis(100)).unwrap(), 1);
+    assert_eq!(replica.size(), 2);
+}
@@ -0,0 +1,57 @@
+#![cfg(feature = "serde")]
+
+use spectra_cache::{BTreeCache, BloomFilter, BytesCache, DistributedHashTable};
+use std::thread;
+use std::time::Duration;
+
+#[test]
+fn test_table_round_trip_keeps_deadlines() {
+    let mut table = DistributedHashTable::new();
+    table.insert("user:1", "Alice");
+    table.insert_with_ttl("session", "on", Duration::from_secs(60));
+    table.insert_with_tti("idle", "x", Duration::from_secs(30));
+    table.insert_with_ttl("stale", "gone", Duration::from_millis(1));
+    thread::sleep(Duration::from_millis(5));
+
+    let json = serde_json::to_string(&table).unwrap();
+    assert!(!json.contains("stale"));
+    let copy: DistributedHashTable = serde_json::from_str(&json).unwrap();
+    assert_eq!(copy, table);
+    assert_eq!(copy.ttl("user:1"), None);
+    let ttl = copy.ttl("session").unwrap();
+    assert!(ttl > Duration::from_secs(58) && ttl <= Duration::from_secs(60), "{:?}", ttl);
+    assert!(copy.ttl("idle").unwrap() <= Duration::from_secs(30));
+    assert_eq!(copy.get("user:1"), Some("Alice"));
+}
+
+#[test]
+fn test_entries_expiring_in_transit_are_skipped() {
+    let mut cache = BTreeCache::new();
+    cache.insert_with_ttl("short", "v", Duration::from_millis(20));
+    cache.insert("long", "v");
+    let json = serde_json::to_string(&cache).unwrap();
+
+    thread::sleep(Duration::from_millis(30));
+    let copy: BTreeCache = serde_json::from_str(&json).unwrap();
+    assert_eq!(copy.keys().collect::<Vec<_>>(), ["long"]);
+}
+
+#[test]
+fn test_bytes_cache_and_bloom_filter_round_trip() {
+    let mut cache = BytesCache::new();
+    cache.insert("blob", &[0, 159, 146, 150]);
+    let copy: BytesCache = serde_json::from_str(&serde_json::to_string(&cache).unwrap()).unwrap();
+    assert_eq!(copy, cache);
+
+    let mut filter = BloomFilter::new(100, 0.01);
+    for i in 0..50 {
+        filter.insert(&i);
+    }
+    let json = serde_json::to_string(&filter).unwrap();
+    let copy: BloomFilter = serde_json::from_str(&json).unwrap();
+    assert_eq!(copy.size(), 50);
+    assert!((0..50).all(|i| copy.contains(&i)));
+
+    let broken = json.replace("\"bits\":", "\"bits\":1");
+    assert!(serde_json::from_str::<BloomFilter>(&broken).is_err());
+}
@@ -0,0 +1,303 @@
+#![cfg(feature = "server")]
+
+use spectra_cache::{AuditAction, AuditContext, AuditEvent, DistributedHashTable, RespServer};
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+fn start_server() -> TcpStream {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = RespServer::new(DistributedHashTable::new());
+    thread::spawn(move || server.serve(listener));
+    TcpStream::connect(addr).unwrap()
+}
+
+fn command(args: &[&str]) -> Vec<u8> {
+    let mut encoded = format!("*{}\r\n", args.len()).into_bytes();
+    for arg in args {
+        encoded.extend_from_slice(format!("${}\r\n{}\r\n", arg.len(), arg).as_bytes());
+    }
+    encoded
+}
+
+/// Reads one reply and returns it re-encoded as text, for easy comparison.
+fn read_reply(reader: &mut impl BufRead) -> String {
+    let mut line = String::new();
+    reader.read_line(&mut line).unwrap();
+    match line.as_bytes()[0] {
+        b'$' if line != "$-1\r\n" => {
+            let len: usize = line[1..].trim_end().parse().unwrap();
+            let mut body = vec![0; len + 2];
+            reader.read_exact(&mut body).unwrap();
+            line + &String::from_utf8(body).unwrap()
+        }
+        b'*' => {
+            let count: usize = line[1..].trim_end().parse().unwrap();
+            (0..count).fold(line, |reply, _| reply + &read_reply(reader))
+        }
+        _ => line,
+    }
+}
+
+fn call(stream: &mut TcpStream, reader: &mut impl BufRead, args: &[&str]) -> String {
+    stream.write_all(&command(args)).unwrap();
+    read_reply(reader)
+}
+
+#[test]
+fn test_basic_commands() {
+    let mut stream = start_server();
+    let mut reader = BufReader::new(stream.try_clone().unwrap());
+
+    assert_eq!(call(&mut stream, &mut reader, &["PING"]), "+PONG\r\n");
+    assert_eq!(call(&mut stream, &mut reader, &["SET", "user:1", "Alice"]), "+OK\r\n");
+    assert_eq!(call(&mut stream, &mut reader, &["get", "user:1"]), "$5\r\nAlice\r\n");
+    assert_eq!(call(&mut stream, &mut reader, &["GET", "missing"]), "$-1\r\n");
+    assert_eq!(call(&mut stream, &mut reader, &["SETEX", "session", "100", "on"]), "+OK\r\n");
+    assert_eq!(call(&mut stream, &mut reader, &["SET", "user:2", "Bob", "EX", "50"]), "+OK\r\n");
+
+    assert_eq!(call(&mut stream, &mut reader, &["TTL", "session"]), ":100\r\n");
+    assert_eq!(call(&mut stream, &mut reader, &["TTL", "user:1"]), ":-1\r\n");
+    assert_eq!(call(&mut stream, &mut reader, &["TTL", "missing"]), ":-2\r\n");
+    assert_eq!(call(&mut stream, &mut reader, &["EXISTS", "user:1", "user:2", "missing"]), ":2\r\n");
+    assert_eq!(call(&mut stream, &mut reader, &["EXPIRE", "user:1", "30"]), ":1\r\n");
+    assert_eq!(call(&mut stream, &mut reader, &["TTL", "user:1"]), ":30\r\n");
+    assert_eq!(call(&mut stream, &mut reader, &["PERSIST", "user:1"]), ":1\r\n");
+    assert_eq!(call(&mut stream, &mut reader, &["PERSIST", "user:1"]), ":0\r\n");
+    assert_eq!(call(&mut stream, &mut reader, &["EXPIRE", "missing", "30"]), ":0\r\n");
+    assert_eq!(
+        call(&mut stream, &mut reader, &["KEYS", "user:*"]),
+        "*2\r\n$6\r\nuser:1\r\n$6\r\nuser:2\r\n"
+    );
+    assert_eq!(call(&mut stream, &mut reader, &["DEL", "user:1", "missing"]), ":1\r\n");
+    assert!(call(&mut stream, &mut reader, &["INFO"]).contains("\"inserts\":3"));
+    assert_eq!(call(&mut stream, &mut reader, &["QUIT"]), "+OK\r\n");
+}
+
+#[test]
+fn test_errors_and_inline_commands() {
+    let mut stream = start_server();
+    let mut reader = BufReader::new(stream.try_clone().unwrap());
+
+    assert_eq!(call(&mut stream, &mut reader, &["FLY"]), "-ERR unknown command 'FLY'\r\n");
+    assert_eq!(
+        call(&mut stream, &mut reader, &["GET"]),
+        "-ERR wrong number of arguments for 'get' command\r\n"
+    );
+    assert_eq!(call(&mut stream, &mut reader, &["SETEX", "k", "0", "v"]), "-ERR invalid expire time\r\n");
+    assert_eq!(call(&mut stream, &mut reader, &["SET", "k", "v", "XX"]), "-ERR syntax error\r\n");
+
+    stream.write_all(b"SET greeting hello\r\nGET greeting\r\n").unwrap();
+    assert_eq!(read_reply(&mut reader), "+OK\r\n");
+    assert_eq!(read_reply(&mut reader), "$5\r\nhello\r\n");
+}
+
+#[test]
+#[cfg(feature = "persistence")]
+fn test_failing_persistence_rejects_writes() {
+    use spectra_cache::PersistenceFailure;
+
+    let mut table = DistributedHashTable::builder()
+        .on_persistence_failure(PersistenceFailure::RejectWrites)
+        .build();
+    table.insert("k", "v");
+    let unwritable = std::env::temp_dir().join("spectra-cache-test-missing-dir").join("server.snap");
+    for _ in 0..3 {
+        table.save_snapshot(&unwritable).unwrap_err();
+    }
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = RespServer::new(table);
+    thread::spawn(move || server.serve(listener));
+    let mut stream = TcpStream::connect(addr).unwrap();
+    let mut reader = BufReader::new(stream.try_clone().unwrap());
+
+    let misconf = "-MISCONF persistence is failing, writes are rejected\r\n";
+    assert_eq!(call(&mut stream, &mut reader, &["SET", "k", "w"]), misconf);
+    assert_eq!(call(&mut stream, &mut reader, &["FLUSHALL"]), misconf);
+    assert_eq!(call(&mut stream, &mut reader, &["GET", "k"]), "$1\r\nv\r\n");
+}
+
+#[test]
+fn test_read_only_mode() {
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let sink = Arc::clone(&events);
+    let mut table = DistributedHashTable::new();
+    table.set_audit_sink(move |event: &AuditEvent| sink.lock().unwrap().push(event.clone()));
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = RespServer::new(table);
+    let admin = server.clone();
+    thread::spawn(move || server.serve(listener));
+    let mut stream = TcpStream::connect(addr).unwrap();
+    let mut reader = BufReader::new(stream.try_clone().unwrap());
+
+    assert_eq!(call(&mut stream, &mut reader, &["SET", "k", "v"]), "+OK\r\n");
+    admin.set_read_only(true);
+    assert_eq!(call(&mut stream, &mut reader, &["SET", "k", "w"]), "-READONLY cache is read-only\r\n");
+    assert_eq!(call(&mut stream, &mut reader, &["DEL", "k"]), "-READONLY cache is read-only\r\n");
+    assert_eq!(call(&mut stream, &mut reader, &["GET", "k"]), "$1\r\nv\r\n");
+    assert_eq!(
+        call(&mut stream, &mut reader, &["CONFIG", "GET", "read-only"]),
+        "*2\r\n$9\r\nread-only\r\n$3\r\nyes\r\n"
+    );
+
+    assert_eq!(call(&mut stream, &mut reader, &["CONFIG", "SET", "read-only", "no"]), "+OK\r\n");
+    assert!(!admin.is_read_only());
+    assert_eq!(call(&mut stream, &mut reader, &["DEL", "k"]), ":1\r\n");
+
+    let change = |value: &str| AuditAction::ConfigChange { setting: "read_only".to_string(), value: value.to_string() };
+    let events = events.lock().unwrap();
+    assert_eq!(events.iter().map(|event| event.action.clone()).collect::<Vec<_>>(), [change("on"), change("off")]);
+    assert_eq!(events[0].client_addr, None);
+    assert_eq!(events[1].client_addr, Some(stream.local_addr().unwrap()));
+}
+
+#[test]
+fn test_config_get_patterns() {
+    let mut stream = start_server();
+    let mut reader = BufReader::new(stream.try_clone().unwrap());
+
+    let all = call(&mut stream, &mut reader, &["CONFIG", "GET", "*"]);
+    assert!(all.starts_with("*30\r\n"), "{}", all);
+    assert!(all.contains("$9\r\ndatabases\r\n$1\r\n1\r\n"), "{}", all);
+    assert!(all.contains("$11\r\nbloom-audit\r\n$2\r\nno\r\n"), "{}", all);
+    assert!(all.ends_with("$9\r\nread-only\r\n$2\r\nno\r\n"), "{}", all);
+
+    assert_eq!(
+        call(&mut stream, &mut reader, &["CONFIG", "GET", "MAX-MEMORY*"]),
+        "*4\r\n$10\r\nmax-memory\r\n$4\r\nnone\r\n$16\r\nmax-memory-bytes\r\n$4\r\nnone\r\n"
+    );
+    assert_eq!(call(&mut stream, &mut reader, &["CONFIG", "GET", "nothing"]), "*0\r\n");
+    assert_eq!(
+        call(&mut stream, &mut reader, &["CONFIG", "SET", "max-memory", "1mb"]),
+        "-ERR unsupported CONFIG parameter\r\n"
+    );
+}
+
+#[test]
+fn test_logical_databases() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let tables = vec![
+        DistributedHashTable::new(),
+        DistributedHashTable::builder().max_memory_bytes(1 << 20).build(),
+        DistributedHashTable::new(),
+    ];
+    let server = RespServer::with_databases(tables);
+    assert_eq!(server.database_count(), 3);
+    thread::spawn(move || server.serve(listener));
+    let mut stream = TcpStream::connect(addr).unwrap();
+    let mut reader = BufReader::new(stream.try_clone().unwrap());
+    let mut other = TcpStream::connect(addr).unwrap();
+    let mut other_reader = BufReader::new(other.try_clone().unwrap());
+
+    assert_eq!(call(&mut stream, &mut reader, &["SET", "k", "prod"]), "+OK\r\n");
+    assert_eq!(call(&mut stream, &mut reader, &["SELECT", "1"]), "+OK\r\n");
+    assert_eq!(call(&mut stream, &mut reader, &["GET", "k"]), "$-1\r\n");
+    assert_eq!(call(&mut stream, &mut reader, &["SET", "k", "staging"]), "+OK\r\n");
+    assert_eq!(call(&mut stream, &mut reader, &["SET", "j", "staging"]), "+OK\r\n");
+    assert_eq!(call(&mut stream, &mut reader, &["DBSIZE"]), ":2\r\n");
+    assert_eq!(
+        call(&mut stream, &mut reader, &["CONFIG", "GET", "max-memory-bytes"]),
+        "*2\r\n$16\r\nmax-memory-bytes\r\n$7\r\n1048576\r\n"
+    );
+
+    // Cada conexão tem a sua seleção
+    assert_eq!(call(&mut other, &mut other_reader, &["GET", "k"]), "$4\r\nprod\r\n");
+    assert_eq!(call(&mut other, &mut other_reader, &["DBSIZE"]), ":1\r\n");
+
+    assert_eq!(call(&mut stream, &mut reader, &["FLUSHDB"]), "+OK\r\n");
+    assert_eq!(call(&mut stream, &mut reader, &["DBSIZE"]), ":0\r\n");
+    assert_eq!(call(&mut other, &mut other_reader, &["DBSIZE"]), ":1\r\n");
+
+    assert_eq!(call(&mut stream, &mut reader, &["SELECT", "3"]), "-ERR DB index is out of range\r\n");
+    assert_eq!(
+        call(&mut stream, &mut reader, &["SELECT", "one"]),
+        "-ERR value is not an integer or out of range\r\n"
+    );
+    assert_eq!(call(&mut stream, &mut reader, &["SELECT", "2"]), "+OK\r\n");
+    assert_eq!(call(&mut stream, &mut reader, &["SET", "x", "1"]), "+OK\r\n");
+    assert_eq!(call(&mut stream, &mut reader, &["FLUSHALL"]), "+OK\r\n");
+    assert_eq!(call(&mut stream, &mut reader, &["DBSIZE"]), ":0\r\n");
+    assert_eq!(call(&mut other, &mut other_reader, &["GET", "k"]), "$-1\r\n");
+}
+
+#[test]
+fn test_audited_commands_carry_the_client_address() {
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let sink = Arc::clone(&events);
+    let mut table = DistributedHashTable::new();
+    table.set_audit_sink(move |event: &AuditEvent| sink.lock().unwrap().push(event.clone()));
+    table.set_audit_context(AuditContext {
+        actor: Some("spectra-server".to_string()),
+        client_addr: None,
+    });
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = RespServer::with_databases(vec![table, DistributedHashTable::new()]);
+    thread::spawn(move || server.serve(listener));
+    let mut stream = TcpStream::connect(addr).unwrap();
+    let mut reader = BufReader::new(stream.try_clone().unwrap());
+
+    assert_eq!(call(&mut stream, &mut reader, &["SET", "k", "v"]), "+OK\r\n");
+    assert_eq!(call(&mut stream, &mut reader, &["FLUSHDB"]), "+OK\r\n");
+    assert_eq!(call(&mut stream, &mut reader, &["FLUSHALL"]), "+OK\r\n");
+
+    let events = events.lock().unwrap();
+    assert_eq!(events.len(), 2);
+    assert_eq!(events[0].action, AuditAction::Flush { entries: 1 });
+    for event in events.iter() {
+        assert_eq!(event.client_addr, Some(stream.local_addr().unwrap()));
+        assert_eq!(event.actor.as_deref(), Some("spectra-server"));
+    }
+}
+
+#[test]
+fn test_write_ids_replay_the_original_reply() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = RespServer::new(DistributedHashTable::new());
+    let admin = server.clone();
+    thread::spawn(move || server.serve(listener));
+    let mut stream = TcpStream::connect(addr).unwrap();
+    let mut reader = BufReader::new(stream.try_clone().unwrap());
+
+    assert_eq!(call(&mut stream, &mut reader, &["SET", "counter", "1"]), "+OK\r\n");
+    assert_eq!(call(&mut stream, &mut reader, &["WRITEID", "w1", "DEL", "counter"]), ":1\r\n");
+    assert_eq!(call(&mut stream, &mut reader, &["SET", "counter", "2"]), "+OK\r\n");
+
+    // A repetição, mesmo vinda de outra conexão, devolve a resposta original sem apagar de novo
+    let mut retry = TcpStream::connect(addr).unwrap();
+    let mut retry_reader = BufReader::new(retry.try_clone().unwrap());
+    assert_eq!(call(&mut retry, &mut retry_reader, &["writeid", "w1", "DEL", "counter"]), ":1\r\n");
+    assert_eq!(call(&mut retry, &mut retry_reader, &["GET", "counter"]), "$1\r\n2\r\n");
+
+    assert_eq!(call(&mut stream, &mut reader, &["WRITEID", "w2", "SET", "k", "a"]), "+OK\r\n");
+    assert_eq!(call(&mut stream, &mut reader, &["WRITEID", "w2", "SET", "k", "b"]), "+OK\r\n");
+    assert_eq!(call(&mut stream, &mut reader, &["GET", "k"]), "$1\r\na\r\n");
+
+    // Uma escrita recusada não é lembrada e pode ser repetida depois
+    admin.set_read_only(true);
+    assert_eq!(
+        call(&mut stream, &mut reader, &["WRITEID", "w3", "SET", "k", "c"]),
+        "-READONLY cache is read-only\r\n"
+    );
+    admin.set_read_only(false);
+    assert_eq!(call(&mut stream, &mut reader, &["WRITEID", "w3", "SET", "k", "c"]), "+OK\r\n");
+    assert_eq!(call(&mut stream, &mut reader, &["GET", "k"]), "$1\r\nc\r\n");
+
+    assert_eq!(
+        call(&mut stream, &mut reader, &["WRITEID", "w4", "GET", "k"]),
+        "-ERR WRITEID only applies to write commands\r\n"
+    );
+    assert_eq!(
+        call(&mut stream, &mut reader, &["WRITEID", "w4"]),
+        "-ERR wrong number of arguments for 'writeid' command\r\n"
+    );
+}
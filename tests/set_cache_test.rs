@@ -0,0 +1,95 @@
+#![cfg(all(feature = "core", feature = "std"))]
+
+use spectra_cache::SetCache;
+use std::collections::BTreeSet;
+use std::time::Duration;
+
+fn tagged() -> SetCache {
+    let mut tags = SetCache::new();
+    for item in ["item:1", "item:2", "item:3"] {
+        tags.sadd("tag:a", item);
+    }
+    for item in ["item:2", "item:3", "item:4"] {
+        tags.sadd("tag:b", item);
+    }
+    tags.sadd("tag:c", "item:3");
+    tags
+}
+
+#[test]
+fn test_members_are_unique() {
+    let mut sets = SetCache::new();
+    assert!(sets.sadd("tag:a", "item:2"));
+    assert!(sets.sadd("tag:a", "item:1"));
+    assert!(!sets.sadd("tag:a", "item:2"));
+
+    assert!(sets.sismember("tag:a", "item:1"));
+    assert!(!sets.sismember("tag:a", "item:3"));
+    assert!(!sets.sismember("missing", "item:1"));
+    assert_eq!(sets.smembers("tag:a"), ["item:1", "item:2"]);
+    assert!(sets.smembers("missing").is_empty());
+    assert_eq!(sets.scard("tag:a"), 2);
+    assert_eq!(sets.scard("missing"), 0);
+}
+
+#[test]
+fn test_set_algebra() {
+    let tags = tagged();
+
+    assert_eq!(tags.sunion(&["tag:a", "tag:b"]), ["item:1", "item:2", "item:3", "item:4"]);
+    assert_eq!(tags.sinter(&["tag:a", "tag:b"]), ["item:2", "item:3"]);
+    assert_eq!(tags.sinter(&["tag:a", "tag:b", "tag:c"]), ["item:3"]);
+    assert_eq!(tags.sdiff(&["tag:a", "tag:b"]), ["item:1"]);
+    assert_eq!(tags.sdiff(&["tag:b", "tag:a", "tag:c"]), ["item:4"]);
+
+    // Chaves ausentes contam como conjuntos vazios
+    assert_eq!(tags.sunion(&["tag:c", "missing"]), ["item:3"]);
+    assert!(tags.sinter(&["tag:a", "missing"]).is_empty());
+    assert_eq!(tags.sdiff(&["tag:c", "missing"]), ["item:3"]);
+    assert!(tags.sdiff(&["missing", "tag:a"]).is_empty());
+    assert!(tags.sunion(&[]).is_empty());
+    assert!(tags.sinter(&[]).is_empty());
+    assert!(tags.sdiff(&[]).is_empty());
+}
+
+#[test]
+fn test_srem_and_remove() {
+    let mut tags = tagged();
+
+    assert!(tags.srem("tag:c", "item:3"));
+    assert!(!tags.srem("tag:c", "item:3"));
+    assert!(!tags.srem("missing", "item:3"));
+    // O conjunto some junto com o último membro
+    assert!(!tags.contains_key("tag:c"));
+
+    let members = BTreeSet::from(["item:1", "item:2", "item:3"].map(String::from));
+    assert_eq!(tags.remove("tag:a"), Some(members));
+    assert_eq!(tags.remove("tag:a"), None);
+
+    let keys: Vec<_> = tags.keys().collect();
+    assert_eq!(keys, ["tag:b"]);
+    tags.clear();
+    assert!(tags.is_empty());
+}
+
+#[test]
+fn test_set_ttl() {
+    let mut tags = tagged();
+    assert!(!tags.expire("missing", Duration::from_secs(1)));
+    assert!(tags.expire("tag:b", Duration::from_millis(20)));
+    assert!(tags.ttl("tag:b").unwrap() <= Duration::from_millis(20));
+    assert_eq!(tags.ttl("tag:a"), None);
+
+    std::thread::sleep(Duration::from_millis(30));
+    assert!(!tags.contains_key("tag:b"));
+    assert_eq!(tags.sinter(&["tag:a", "tag:b"]), Vec::<&str>::new());
+    assert_eq!(tags.sdiff(&["tag:a", "tag:b"]), ["item:1", "item:2", "item:3"]);
+
+    // Um conjunto expirado recomeça vazio e sem TTL
+    assert!(tags.sadd("tag:b", "item:9"));
+    assert_eq!(tags.smembers("tag:b"), ["item:9"]);
+    assert_eq!(tags.ttl("tag:b"), None);
+
+    tags.expire("tag:c", Duration::ZERO);
+    assert_eq!(tags.purge_expired(), 1);
+}
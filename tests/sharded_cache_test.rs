@@ -0,0 +1,39 @@
+#![cfg(all(feature = "core", feature = "std"))]
+
+use spectra_cache::ShardedCache;
+use std::time::Duration;
+
+#[test]
+fn test_keys_are_spread_across_shards() {
+    let mut cache = ShardedCache::new(4);
+    for i in 0..1000 {
+        cache.insert(&format!("key:{}", i), &i.to_string());
+    }
+
+    assert_eq!(cache.size(), 1000);
+    for shard in 0..cache.shard_count() {
+        assert!(cache.shard(shard).size() > 100);
+    }
+    for i in 0..1000 {
+        let key = format!("key:{}", i);
+        assert!(cache.shard(cache.shard_for(&key)).keys().any(|k| *k == key));
+    }
+}
+
+#[test]
+fn test_operations_route_to_the_owning_shard() {
+    let mut cache = ShardedCache::new(3);
+    cache.insert("user:1", "Ana");
+    cache.insert_with_ttl("session:1", "active", Duration::from_millis(50));
+    assert_eq!(cache.incr("counter", 2), Ok(2));
+
+    assert_eq!(cache.get("user:1"), Some("Ana"));
+    assert!(cache.contains_key("session:1"));
+    assert_eq!(cache.remove("user:1"), Some("Ana".to_string()));
+
+    std::thread::sleep(Duration::from_millis(100));
+    assert!(!cache.contains_key("session:1"));
+
+    cache.clear();
+    assert!(cache.is_empty());
+}
@@ -0,0 +1,58 @@
+#![cfg(all(feature = "core", feature = "std"))]
+
+use spectra_cache::{RemovalCause, SharedCache};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+#[test]
+fn test_get_shares_the_stored_value() {
+    let mut cache = SharedCache::new();
+    let payload: Arc<str> = Arc::from("x".repeat(1 << 16));
+    cache.insert_shared("blob", Arc::clone(&payload));
+
+    let read = cache.get("blob").unwrap();
+    assert!(Arc::ptr_eq(&read, &payload));
+    assert!(Arc::ptr_eq(&cache.peek("blob").unwrap(), &payload));
+    assert_eq!(Arc::strong_count(&payload), 3);
+
+    cache.insert("small", "copied");
+    assert_eq!(cache.get("small").as_deref(), Some("copied"));
+    assert_eq!(cache.size(), 2);
+}
+
+#[test]
+fn test_values_outlive_their_entries() {
+    let mut cache = SharedCache::builder().default_ttl(Duration::from_millis(50)).build();
+    cache.insert("page", "v1");
+    let held = cache.get("page").unwrap();
+
+    cache.insert("page", "v2");
+    let reader = thread::spawn(move || held.to_string());
+    assert_eq!(cache.get("page").as_deref(), Some("v2"));
+    assert_eq!(reader.join().unwrap(), "v1");
+
+    let held = cache.get("page").unwrap();
+    thread::sleep(Duration::from_millis(100));
+    assert_eq!(cache.get("page"), None);
+    assert_eq!(&*held, "v2");
+}
+
+#[test]
+fn test_remove_and_listeners() {
+    let removed = Arc::new(Mutex::new(Vec::new()));
+    let sink = Arc::clone(&removed);
+    let mut cache: SharedCache = [("a", "1"), ("b", "2")].into_iter().collect();
+    cache.on_evict(move |key, value, cause| sink.lock().unwrap().push((key.to_string(), value.to_string(), cause)));
+
+    assert_eq!(cache.remove("a").as_deref(), Some("1"));
+    assert_eq!(cache.remove("a"), None);
+    cache.insert_shared_with_ttl("c", Arc::from("3"), Duration::from_secs(60));
+    assert!(cache.ttl("c").is_some());
+    cache.clear();
+
+    assert!(cache.is_empty());
+    let removed = removed.lock().unwrap();
+    assert_eq!(removed[0], ("a".to_string(), "1".to_string(), RemovalCause::Removed));
+    assert_eq!(removed.len(), 3);
+}
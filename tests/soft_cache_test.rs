@@ -0,0 +1,47 @@
+#![cfg(all(feature = "core", feature = "std"))]
+
+use spectra_cache::SoftCache;
+use std::sync::Arc;
+
+fn render(key: &str) -> Option<Vec<u8>> {
+    key.strip_prefix("img:").map(|id| id.repeat(1000).into_bytes())
+}
+
+#[test]
+fn test_soft_entries_beyond_the_budget_are_demoted() {
+    let mut cache = SoftCache::new(2, render);
+    for id in ["1", "2", "3"] {
+        cache.insert_soft(&format!("img:{}", id), Arc::new(render(&format!("img:{}", id)).unwrap()));
+    }
+    cache.insert("img:pinned", Arc::new(vec![0]));
+    assert_eq!(cache.size(), 4);
+    assert_eq!(cache.strong_count(), 3);
+
+    // "img:1" foi rebaixada e ninguém mais a segurava
+    assert_eq!(cache.get("img:1").unwrap().len(), 1000);
+    assert_eq!(cache.regenerations(), 1);
+    // Ler "img:1" de novo a deixou quente; "img:2" foi a rebaixada agora
+    let held = cache.get("img:3").unwrap();
+    assert_eq!(cache.get("img:2").unwrap().len(), 1000);
+    assert_eq!(cache.regenerations(), 2);
+
+    assert_eq!(cache.release_memory(), 2);
+    assert_eq!(cache.strong_count(), 1);
+    assert!(Arc::ptr_eq(&cache.get("img:3").unwrap(), &held));
+    assert_eq!(cache.get("img:pinned").as_deref(), Some(&vec![0]));
+    assert_eq!(cache.regenerations(), 2);
+}
+
+#[test]
+fn test_unloadable_keys_are_dropped() {
+    let mut cache = SoftCache::new(0, render);
+    cache.insert_soft("raw", Arc::new(vec![1, 2, 3]));
+    assert_eq!(cache.strong_count(), 0);
+    assert_eq!(cache.get("raw"), None);
+    assert!(cache.is_empty());
+
+    let value = Arc::new(vec![4]);
+    cache.insert_soft("kept", Arc::clone(&value));
+    assert_eq!(cache.remove("kept"), Some(value));
+    assert_eq!(cache.remove("kept"), None);
+}
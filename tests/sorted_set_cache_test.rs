@@ -0,0 +1,111 @@
+#![cfg(all(feature = "core", feature = "std"))]
+
+use spectra_cache::{CacheError, SortedSetCache};
+use std::time::Duration;
+
+fn leaderboard() -> SortedSetCache {
+    let mut scores = SortedSetCache::new();
+    for (score, member) in [(10.0, "ana"), (30.0, "bia"), (20.0, "caio"), (20.0, "bruno"), (40.0, "duda")] {
+        assert!(scores.zadd("board", score, member).unwrap());
+    }
+    scores
+}
+
+#[test]
+fn test_zadd_updates_scores_and_orders_ties_by_member() {
+    let mut scores = leaderboard();
+    assert!(!scores.zadd("board", 5.0, "duda").unwrap());
+
+    assert_eq!(scores.zcard("board"), 5);
+    assert_eq!(scores.zscore("board", "duda"), Some(5.0));
+    assert_eq!(scores.zscore("board", "nobody"), None);
+    assert_eq!(
+        scores.zrange("board", ..),
+        [("duda", 5.0), ("ana", 10.0), ("bruno", 20.0), ("caio", 20.0), ("bia", 30.0)]
+    );
+    assert_eq!(scores.zrank("board", "caio"), Some(3));
+    assert_eq!(scores.zrevrank("board", "caio"), Some(1));
+    assert_eq!(scores.zrank("board", "nobody"), None);
+    assert_eq!(scores.zrank("missing", "ana"), None);
+}
+
+#[test]
+fn test_rank_and_score_ranges() {
+    let scores = leaderboard();
+
+    assert_eq!(scores.zrevrange("board", ..3), [("duda", 40.0), ("bia", 30.0), ("caio", 20.0)]);
+    assert_eq!(scores.zrange("board", 1..=2), [("bruno", 20.0), ("caio", 20.0)]);
+    assert!(scores.zrange("board", 10..).is_empty());
+    assert!(scores.zrange("missing", ..).is_empty());
+
+    assert_eq!(scores.zrange_by_score("board", 20.0..=30.0), [("bruno", 20.0), ("caio", 20.0), ("bia", 30.0)]);
+    assert_eq!(scores.zrange_by_score("board", 20.0..30.0), [("bruno", 20.0), ("caio", 20.0)]);
+    assert_eq!(scores.zrange_by_score("board", ..20.0), [("ana", 10.0)]);
+    assert_eq!(scores.zrange_by_score("board", 35.0..), [("duda", 40.0)]);
+
+    use std::ops::Bound;
+    let above_twenty = (Bound::Excluded(20.0), Bound::Unbounded);
+    assert_eq!(scores.zrange_by_score("board", above_twenty), [("bia", 30.0), ("duda", 40.0)]);
+}
+
+#[test]
+fn test_zincrby() {
+    let mut scores = SortedSetCache::new();
+    assert_eq!(scores.zincrby("hits", 1.5, "home").unwrap(), 1.5);
+    assert_eq!(scores.zincrby("hits", -3.0, "home").unwrap(), -1.5);
+    assert_eq!(scores.zincrby("hits", 1.5, "home").unwrap(), 0.0);
+    assert_eq!(scores.zrange_by_score("hits", 0.0..=0.0), [("home", 0.0)]);
+
+    scores.zadd("hits", f64::INFINITY, "home").unwrap();
+    assert_eq!(
+        scores.zincrby("hits", f64::NEG_INFINITY, "home"),
+        Err(CacheError::InvalidScore { key: "hits".to_string() })
+    );
+    assert_eq!(scores.zscore("hits", "home"), Some(f64::INFINITY));
+    assert_eq!(scores.zadd("hits", f64::NAN, "about"), Err(CacheError::InvalidScore { key: "hits".to_string() }));
+}
+
+#[test]
+fn test_member_ttl() {
+    let mut scores = leaderboard();
+    scores.zadd_with_ttl("board", 50.0, "eva", Duration::from_millis(20)).unwrap();
+    scores.zadd_with_ttl("board", 45.0, "ana", Duration::from_millis(20)).unwrap();
+    // O incremento mantém o TTL do membro
+    assert_eq!(scores.zincrby("board", 1.0, "ana").unwrap(), 46.0);
+    assert_eq!(scores.zrevrange("board", ..2), [("eva", 50.0), ("ana", 46.0)]);
+
+    std::thread::sleep(Duration::from_millis(30));
+    assert_eq!(scores.zscore("board", "ana"), None);
+    assert_eq!(scores.zrevrange("board", ..1), [("duda", 40.0)]);
+    assert_eq!(scores.zcard("board"), 4);
+    assert!(scores.zadd("board", 1.0, "eva").unwrap());
+    assert_eq!(scores.zscore("board", "eva"), Some(1.0));
+
+    scores.zadd_with_ttl("temp", 1.0, "x", Duration::from_millis(10)).unwrap();
+    assert!(scores.contains_key("temp"));
+    std::thread::sleep(Duration::from_millis(20));
+    assert!(!scores.contains_key("temp"));
+    assert_eq!(scores.keys().collect::<Vec<_>>(), ["board"]);
+    assert_eq!(scores.purge_expired(), 1);
+    assert_eq!(scores.purge_expired(), 0);
+}
+
+#[test]
+fn test_removing_members_and_sets() {
+    let mut scores = leaderboard();
+    scores.zadd("other", 1.0, "x").unwrap();
+
+    assert!(scores.zrem("board", "ana"));
+    assert!(!scores.zrem("board", "ana"));
+    assert!(!scores.zrem("missing", "ana"));
+
+    assert!(scores.zrem("other", "x"));
+    assert!(!scores.contains_key("other"));
+    assert!(scores.remove("board"));
+    assert!(!scores.remove("board"));
+    assert!(scores.is_empty());
+
+    scores.zadd("board", 1.0, "ana").unwrap();
+    scores.clear();
+    assert!(scores.is_empty());
+}
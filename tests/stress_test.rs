@@ -0,0 +1,67 @@
+#![cfg(all(feature = "core", feature = "std"))]
+
+use spectra_cache::stress::{Stress, StressTarget, ViolationKind};
+use spectra_cache::{BTreeCache, ConcurrentCache, DistributedHashTable, MemoryLimit, ShardedCache};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+fn workload() -> Stress {
+    Stress::new()
+        .threads(4)
+        .keys(8)
+        .operations(400)
+        .rounds(3)
+        .expiring(25, Duration::from_millis(1))
+        .seed(42)
+}
+
+#[test]
+fn test_caches_pass() {
+    let report = workload().run(&Mutex::new(DistributedHashTable::new()));
+    assert!(report.is_ok(), "{:?}", report.violations);
+    assert_eq!(report.operations, 4 * 400 * 3);
+    assert!(report.throughput() > 0.0);
+
+    let report = workload().mix(80, 5).run(&Mutex::new(BTreeCache::new()));
+    assert!(report.is_ok(), "{:?}", report.violations);
+    let report = workload().run(&Mutex::new(ShardedCache::new(4)));
+    assert!(report.is_ok(), "{:?}", report.violations);
+    let report = workload().run(&ConcurrentCache::builder().shard_count(4).build());
+    assert!(report.is_ok(), "{:?}", report.violations);
+}
+
+#[test]
+fn test_evicting_cache_needs_allow_evictions() {
+    let bounded = || Mutex::new(DistributedHashTable::builder().max_memory(MemoryLimit::Bytes(512)).build());
+    let workload = Stress::new().threads(2).keys(64).operations(2000).mix(50, 0);
+    let report = workload.clone().run(&bounded());
+    assert!(report.violations.iter().all(|violation| violation.kind == ViolationKind::LostValue));
+    assert!(!report.is_ok());
+    assert!(workload.allow_evictions().run(&bounded()).is_ok());
+}
+
+/// A cache that forgets to delete.
+struct IgnoresRemovals(Mutex<HashMap<String, String>>);
+
+impl StressTarget for IgnoresRemovals {
+    fn get(&self, key: &str) -> Option<String> {
+        self.0.lock().unwrap().get(key).cloned()
+    }
+
+    fn insert(&self, key: &str, value: &str, _ttl: Option<Duration>) {
+        self.0.lock().unwrap().insert(key.to_string(), value.to_string());
+    }
+
+    fn remove(&self, _key: &str) {}
+}
+
+#[test]
+fn test_broken_cache_is_caught() {
+    let report = Stress::new().threads(2).keys(2).operations(500).mix(60, 20).run(&IgnoresRemovals(Mutex::default()));
+    assert!(!report.is_ok());
+    assert!(report.violations.iter().any(|violation| violation.kind == ViolationKind::StaleValue));
+    assert!(report.violations.len() as u64 <= report.violation_count);
+    let message = report.violations[0].to_string();
+    assert!(message.starts_with("read of 'stress:0:"), "{}", message);
+}
@@ -0,0 +1,67 @@
+#![cfg(feature = "persistence")]
+
+use spectra_cache::{BackingStore, DistributedHashTable, LogStore, StoreError, TieredCache};
+use std::collections::HashMap;
+
+fn temp_path(name: &str) -> std::path::PathBuf {
+    std::env::temp_dir().join(format!("spectra-tiered-{}-{}", name, std::process::id()))
+}
+
+#[test]
+fn test_hot_set_stays_in_memory() {
+    let path = temp_path("hot");
+    let l1 = DistributedHashTable::builder().max_memory_bytes(8 * 1024).build();
+    let mut cache = TieredCache::new(l1, LogStore::open(&path).unwrap());
+    let value = "v".repeat(200);
+    for i in 0..500 {
+        cache.insert(&format!("key:{}", i), &value);
+    }
+    let demoted = cache.stats().demotions;
+    assert!(demoted > 400);
+    assert_eq!(cache.l1().size() as u64 + demoted, 500);
+
+    // O conjunto quente volta para a memória e lá fica
+    for _ in 0..10 {
+        for i in 0..5 {
+            assert_eq!(cache.get(&format!("key:{}", i)).as_deref(), Some(value.as_str()));
+        }
+    }
+    let stats = cache.stats();
+    assert_eq!(stats.l2_hits, 5);
+    assert_eq!(stats.l1_hits, 45);
+
+    assert!(cache.contains_key("key:250"));
+    assert_eq!(cache.remove("key:250").as_deref(), Some(value.as_str()));
+    assert!(!cache.contains_key("key:250"));
+    assert_eq!(cache.get("missing"), None);
+    assert_eq!(cache.stats().misses, 1);
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_overwrite_drops_the_demoted_copy() {
+    let mut disk = HashMap::new();
+    disk.insert("k".to_string(), "old".to_string());
+    let mut cache = TieredCache::new(DistributedHashTable::new(), Disk(disk));
+    cache.insert("k", "new");
+    assert_eq!(cache.remove("k").as_deref(), Some("new"));
+    assert_eq!(cache.get("k"), None);
+}
+
+struct Disk(HashMap<String, String>);
+
+impl BackingStore for Disk {
+    fn load(&mut self, key: &str) -> Result<Option<String>, StoreError> {
+        Ok(self.0.get(key).cloned())
+    }
+
+    fn store(&mut self, key: &str, value: &str) -> Result<(), StoreError> {
+        self.0.insert(key.to_string(), value.to_string());
+        Ok(())
+    }
+
+    fn delete(&mut self, key: &str) -> Result<(), StoreError> {
+        self.0.remove(key);
+        Ok(())
+    }
+}
@@ -0,0 +1,52 @@
+#![cfg(all(feature = "core", feature = "std"))]
+
+use spectra_cache::{DistributedHashTable, KeyTransform, Lowercase, Prefix, TransformedCache};
+use std::borrow::Cow;
+use std::time::Duration;
+
+#[test]
+fn test_every_operation_goes_through_the_transform() {
+    let mut cache = TransformedCache::new(DistributedHashTable::new(), Prefix("tenant-1:".to_string()));
+    cache.insert("user:1", "Ana");
+    cache.insert_with_ttl("session:1", "on", Duration::from_secs(60));
+
+    assert_eq!(cache.stored_key("user:1"), "tenant-1:user:1");
+    assert_eq!(cache.get("user:1"), Some("Ana"));
+    assert_eq!(cache.peek("user:1"), Some("Ana"));
+    assert!(cache.contains_key("session:1"));
+    assert!(cache.ttl("session:1").unwrap() <= Duration::from_secs(60));
+    assert!(cache.expire("user:1", Duration::from_secs(5)));
+    assert_eq!(cache.incr("hits", 2), Ok(2));
+    assert_eq!(cache.table().peek("tenant-1:hits"), Some("2"));
+
+    let mut keys: Vec<_> = cache.keys().collect();
+    keys.sort();
+    assert_eq!(keys, ["hits", "session:1", "user:1"]);
+
+    assert_eq!(cache.remove("user:1").as_deref(), Some("Ana"));
+    assert_eq!(cache.size(), 2);
+    cache.clear();
+    assert!(cache.is_empty());
+}
+
+#[test]
+fn test_composed_and_custom_transforms() {
+    let both = (Lowercase, Prefix("t:".to_string()));
+    assert_eq!(both.apply("User:1"), "t:user:1");
+    assert_eq!(both.invert("t:user:1").as_deref(), Some("user:1"));
+    assert_eq!(both.invert("other:user:1"), None);
+    assert!(matches!(Lowercase.apply("user:1"), Cow::Borrowed(_)));
+
+    // Chaves que não dá para inverter aparecem como estão guardadas
+    let mut hashed = TransformedCache::new(DistributedHashTable::new(), |key: &str| format!("h:{}", key.len()));
+    hashed.insert("ana@example.com", "1");
+    assert_eq!(hashed.get("ana@example.com"), Some("1"));
+    assert_eq!(hashed.keys().collect::<Vec<_>>(), ["h:15"]);
+
+    // Chaves gravadas antes do wrapper não são mexidas
+    let mut table = DistributedHashTable::new();
+    table.insert("legacy", "x");
+    let cache = TransformedCache::new(table, Prefix("t:".to_string()));
+    assert_eq!(cache.keys().collect::<Vec<_>>(), ["legacy"]);
+    assert_eq!(cache.into_inner().peek("legacy"), Some("x"));
+}